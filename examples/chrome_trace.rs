@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+use zk_engine::{
+  nova::{
+    provider::{ipa_pc, Bn256EngineIPA},
+    spartan,
+    traits::Dual,
+  },
+  {
+    error::ZKWASMError,
+    utils::chrome::init_chrome_trace,
+    wasm_ctx::{WASMArgsBuilder, WASMCtx},
+    wasm_snark::{StepSize, WasmSNARK},
+  },
+};
+
+// Curve Cycle to prove/verify on
+pub type E = Bn256EngineIPA;
+pub type EE1 = ipa_pc::EvaluationEngine<E>;
+pub type EE2 = ipa_pc::EvaluationEngine<Dual<E>>;
+pub type S1 = spartan::batched::BatchedRelaxedR1CSSNARK<E, EE1>;
+pub type S2 = spartan::batched::BatchedRelaxedR1CSSNARK<Dual<E>, EE2>;
+
+// Run with `cargo run --release --example chrome_trace --features chrome-trace`, then load
+// `fib_trace.json` in `chrome://tracing` or https://ui.perfetto.dev
+fn main() -> Result<(), ZKWASMError> {
+  // The trace is written when the guard is dropped, at the end of `main`
+  let _guard = init_chrome_trace("fib_trace.json");
+
+  let step_size = StepSize::new(10)?;
+
+  // Produce setup material
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  // Specify arguments to the WASM and use it to build a `WASMCtx`
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))
+    .unwrap()
+    .invoke("fib")
+    .func_args(vec![String::from("16")])
+    .build();
+  let wasm_ctx = WASMCtx::new(wasm_args);
+
+  // Prove wasm execution of fib.wat::fib(16), each folding step & opcode synthesis is a span
+  let (snark, instance) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+
+  // Verify the proof
+  snark.verify(&pp, &instance)?;
+
+  Ok(())
+}
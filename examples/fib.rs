@@ -27,7 +27,7 @@ fn main() -> Result<(), ZKWASMError> {
   //
   // Here we choose `10` as the step size because the wasm execution of fib(16) is 253 opcodes.
   // meaning zkWASM will run for 26 steps (rounds up).
-  let step_size = StepSize::new(10);
+  let step_size = StepSize::new(10)?;
 
   // Produce setup material
   let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
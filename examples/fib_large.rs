@@ -25,7 +25,7 @@ fn main() -> Result<(), ZKWASMError> {
   //
   // Here we choose `1_000` as the step size because the wasm execution of fib(1000) is 16,981
   // opcodes. meaning zkWASM will run for 17 steps (rounds up).
-  let step_size = StepSize::new(1_000);
+  let step_size = StepSize::new(1_000)?;
 
   // Produce setup material
   let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
@@ -25,7 +25,7 @@ fn main() -> Result<(), ZKWASMError> {
   //
   // However the memory size is 147456 address spaces, so we set memory step size to 50_000.
   // Resulting in 3 steps for MCC
-  let step_size = StepSize::new(1000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1000)?.set_memory_step_size(50_000)?;
 
   // Produce setup material
   let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+use zk_engine::{
+  nova::{
+    provider::{ipa_pc, Bn256EngineIPA},
+    spartan,
+    traits::Dual,
+  },
+  {
+    error::ZKWASMError,
+    utils::logging::init_logger,
+    wasm_ctx::{WASMArgsBuilder, WASMCtx},
+    wasm_snark::{ProvingConfig, ProvingPhase, StepSize, WasmSNARK},
+  },
+};
+
+// Curve Cycle to prove/verify on
+pub type E = Bn256EngineIPA;
+pub type EE1 = ipa_pc::EvaluationEngine<E>;
+pub type EE2 = ipa_pc::EvaluationEngine<Dual<E>>;
+pub type S1 = spartan::batched::BatchedRelaxedR1CSSNARK<E, EE1>;
+pub type S2 = spartan::batched::BatchedRelaxedR1CSSNARK<Dual<E>, EE2>;
+
+fn main() -> Result<(), ZKWASMError> {
+  init_logger();
+
+  let step_size = StepSize::new(10)?;
+
+  // Produce setup material
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  // Specify arguments to the WASM and use it to build a `WASMCtx`
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))
+    .unwrap()
+    .invoke("fib")
+    .func_args(vec![String::from("16")])
+    .build();
+  let wasm_ctx = WASMCtx::new(wasm_args);
+
+  // Print the progress of each proving phase, e.g. `Execution 3/26`
+  let progress = |phase: ProvingPhase, current: usize, total: usize| {
+    println!("{phase} {current}/{total}");
+  };
+
+  // Prove wasm execution of fib.wat::fib(16)
+  let config = ProvingConfig::new(step_size).progress(&progress);
+  let (snark, instance) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, config)?;
+
+  // Verify the proof
+  snark.verify(&pp, &instance)?;
+
+  Ok(())
+}
@@ -0,0 +1,69 @@
+use std::{path::PathBuf, time::Instant};
+use zk_engine::{
+  nova::{
+    provider::{ipa_pc, Bn256EngineIPA},
+    spartan,
+    traits::Dual,
+  },
+  {
+    error::ZKWASMError,
+    utils::logging::init_logger,
+    wasm_ctx::{WASMArgsBuilder, WASMCtx},
+    wasm_snark::{StepSize, VerifierContext, WASMPublicParams, WasmSNARK},
+  },
+};
+
+// Curve Cycle to prove/verify on
+pub type E = Bn256EngineIPA;
+pub type EE1 = ipa_pc::EvaluationEngine<E>;
+pub type EE2 = ipa_pc::EvaluationEngine<Dual<E>>;
+pub type S1 = spartan::batched::BatchedRelaxedR1CSSNARK<E, EE1>;
+pub type S2 = spartan::batched::BatchedRelaxedR1CSSNARK<Dual<E>, EE2>;
+
+fn main() -> Result<(), ZKWASMError> {
+  init_logger();
+
+  let step_size = StepSize::new(10)?;
+
+  // Produce setup material
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  // Prove & compress fib(n) for a few n
+  let proofs = ["8", "12", "16"]
+    .into_iter()
+    .map(|n| {
+      let wasm_args = WASMArgsBuilder::default()
+        .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+        .invoke("fib")
+        .func_args(vec![String::from(n)])
+        .build();
+      let (snark, instance) =
+        WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+      Ok((snark.compress(&pp, &instance)?, instance))
+    })
+    .collect::<Result<Vec<_>, ZKWASMError>>()?;
+
+  // A verifier receiving the parameters with each proof derives the verifier key every time
+  let pp_str = serde_json::to_string(&pp).unwrap();
+  let start = Instant::now();
+  for (snark, instance) in &proofs {
+    let pp: WASMPublicParams<E, S1, S2> = serde_json::from_str(&pp_str).unwrap();
+    snark.verify(&pp, instance)?;
+  }
+  println!("verifying with fresh parameters took {:?}", start.elapsed());
+
+  // A verifier context derives it once
+  let start = Instant::now();
+  let ctx = VerifierContext::new(&pp);
+  println!("building the verifier context took {:?}", start.elapsed());
+  let start = Instant::now();
+  for (snark, instance) in &proofs {
+    ctx.verify(snark, instance)?;
+  }
+  println!(
+    "verifying with the verifier context took {:?}",
+    start.elapsed()
+  );
+
+  Ok(())
+}
@@ -112,6 +112,26 @@ where
     }
   }
 
+  /// Aggregate the [`WasmSNARK`]s of independent WASM runs into a single [`AggregationSNARK`],
+  /// which proves each of them verifies. Returns [`ZKWASMError::NothingToAggregate`] if there are
+  /// none.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of [`WasmSNARK`]'s and U's ([`ZKWASMInstance`]'s) are not equal
+  pub fn prove(
+    pp: &AggregationPublicParams<E, S1, S2>,
+    wasm_snarks: &[WasmSNARK<E, S1, S2>],
+    U: &[ZKWASMInstance<E>],
+  ) -> Result<Self, ZKWASMError> {
+    let (Some(first_snark), Some(first_U)) = (wasm_snarks.first(), U.first()) else {
+      return Err(ZKWASMError::NothingToAggregate);
+    };
+    let mut aggregation_snark = Self::new(pp, first_snark, first_U)?;
+    aggregation_snark.aggregate(pp, wasm_snarks, U)?;
+    Ok(aggregation_snark)
+  }
+
   /// Aggregate the [`WasmSNARK`]s
   ///
   /// # Panics
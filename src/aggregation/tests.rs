@@ -25,7 +25,7 @@ pub type S2 = spartan::snark::RelaxedR1CSSNARK<Dual<E>, EE2>;
 
 #[test]
 fn test_aggregation_bit_check() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(16);
+  let step_size = StepSize::new(16)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/nebula/bit_check.wat"))
@@ -44,7 +44,7 @@ fn test_aggregation_bit_check() -> Result<(), ZKWASMError> {
 
 #[test]
 fn test_aggregation_int_opcodes() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(100);
+  let step_size = StepSize::new(100)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/int_opcodes.wat"))
@@ -61,7 +61,7 @@ fn test_aggregation_int_opcodes() -> Result<(), ZKWASMError> {
 
 #[test]
 fn test_aggregation_eq_func() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(500);
+  let step_size = StepSize::new(500)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/nebula/eq_func.wat"))
@@ -81,7 +81,10 @@ fn test_aggregation_eq_func() -> Result<(), ZKWASMError> {
 #[test]
 fn test_aggregation_zk_ads() {
   init_logger();
-  let step_size = StepSize::new(500).set_memory_step_size(50_000);
+  let step_size = StepSize::new(500)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   let input_x = "200.05";
   let input_y = "-30.0";
   let wasm_args = WASMArgsBuilder::default()
@@ -101,6 +104,37 @@ fn test_aggregation_zk_ads() {
   sim_nodes_and_orchestrator_node(&wasm_program, step_size, num_nodes);
 }
 
+#[test]
+fn test_aggregation_fib_runs() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(100)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  // Three independent runs, each proven on its own
+  let mut snarks = Vec::new();
+  let mut instances = Vec::new();
+  for n in ["10", "20", "30"] {
+    let wasm_args = WASMArgsBuilder::default()
+      .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+      .invoke("fib")
+      .func_args(vec![n.to_string()])
+      .build();
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+    snarks.push(snark);
+    instances.push(U);
+  }
+
+  let aggregation_pp = AggregationSNARK::setup(pp);
+  let aggregation_snark = AggregationSNARK::prove(&aggregation_pp, &snarks, &instances)?;
+  aggregation_snark.verify(&aggregation_pp)?;
+
+  assert!(matches!(
+    AggregationSNARK::prove(&aggregation_pp, &[], &[]),
+    Err(ZKWASMError::NothingToAggregate)
+  ));
+  Ok(())
+}
+
 fn sim_nodes_and_orchestrator_node(
   wasm_program: &impl ZKWASMCtx,
   step_size: StepSize,
@@ -2,6 +2,7 @@
 
 use nova::errors::NovaError;
 use thiserror::Error;
+use wasmi::{core::TrapCode, Instruction, UnsupportedInstruction};
 
 /// Errors returned by the zkWASM library
 #[derive(Debug, Error)]
@@ -25,15 +26,104 @@ pub enum ZKWASMError {
   #[error("MultisetVerificationError")]
   MultisetVerificationError,
   #[error("Input SNARK needs to be Recursive")]
-  /// Returned when trying to compress or aggregate an already compressed proof
+  /// Returned when trying to compress or aggregate an already compressed proof, or when the
+  /// operation needs the instance bound by the recursive SNARK, e.g. a context or a program
   NotRecursive,
   /// Returned when invalid [`TraceSliceValues`] are passed
   #[error("InvalidTraceSliceValues: {0}")]
   InvalidTraceSliceValues(String),
+  /// Returned when the outputs claimed by the instance are not the ones proven
+  #[error("OutputMismatch")]
+  OutputMismatch,
+  /// Returned when an invalid [`StepSize`](crate::wasm_snark::StepSize) is configured
+  #[error("InvalidStepSize: {0}")]
+  InvalidStepSize(String),
+  /// Returned when an op of a [`MemoryTable`](crate::wasm_snark::mcc_snark::MemoryTable) is out of
+  /// its memory bounds
+  #[error("MemoryOutOfBounds: {0}")]
+  MemoryOutOfBounds(usize),
+  /// Returned when the WASM program exceeds the stack limits of the execution context
+  #[error("StackOverflow")]
+  StackOverflow,
+  /// Returned when a proof is verified under a different context than it was made with
+  #[error("ContextMismatch")]
+  ContextMismatch,
+  /// Returned when a proof is verified against a different program than it was made for
+  #[error("ProgramMismatch")]
+  ProgramMismatch,
+  /// Returned when the thread pool to prove on can not be built
+  #[error("ThreadPoolError: {0}")]
+  ThreadPoolError(String),
+  /// Returned when a [`ProvingConfig`](crate::wasm_snark::ProvingConfig) sets a knob the proving
+  /// method can not apply
+  #[error("InvalidConfig: {0}")]
+  InvalidConfig(String),
+  /// Returned when the initial memory of a proof is not the final memory of the proof it continues
+  #[error("ContinuationMismatch")]
+  ContinuationMismatch,
+  /// Returned when the WASM module uses proposals the zkVM does not support, e.g. SIMD
+  #[error("UnsupportedFeature: {0}")]
+  UnsupportedFeature(String),
+  /// Returned when a [`StackOpening`](crate::wasm_snark::StackOpening) can not be made or is not
+  /// bound to the proof it opens
+  #[error("InvalidOpening: {0}")]
+  InvalidOpening(String),
+  /// Returned when the WASM module does not export the function to invoke
+  #[error("ExportNotFound: {0}")]
+  ExportNotFound(String),
+  /// Returned when the tables of an execution trace can not be built
+  #[error("TracerError: {0}")]
+  TracerError(#[from] wasmi::TracerError),
+  /// Returned when a public input of an instance is not the one recomputed from the program
+  #[error("InputMismatch: {0}")]
+  InputMismatch(String),
+  /// Returned when a proof can not be serialized or deserialized
+  #[error("SerializationError: {0}")]
+  SerializationError(#[from] bincode::Error),
+  /// Returned when serialized proof bytes are not of the current format version, or were made for
+  /// other public parameters
+  #[error("ProofFormatMismatch: {0}")]
+  ProofFormatMismatch(String),
+  /// Returned when an execution uses more pages of linear memory than the maximum set with
+  /// [`StepSize::set_max_memory_pages`](crate::wasm_snark::StepSize::set_max_memory_pages)
+  #[error("MemoryLimitExceeded: {requested} pages requested, {allowed} allowed")]
+  MemoryLimitExceeded {
+    /// Pages of linear memory the execution uses
+    requested: usize,
+    /// Maximum pages of linear memory
+    allowed: usize,
+  },
+  /// Returned when aggregating an empty list of proofs
+  #[error("NothingToAggregate")]
+  NothingToAggregate,
+  /// Returned when the memory multisets do not match in the clear, with the memory op of the lowest
+  /// timestamp in only one side, see
+  /// [`validate_multiset`](crate::wasm_snark::validate_multiset)
+  #[error("MultisetMismatch: address {addr}, value {val}, timestamp {ts}")]
+  MultisetMismatch {
+    /// Address of the memory op
+    addr: usize,
+    /// Value of the memory op
+    val: u64,
+    /// Timestamp of the memory op
+    ts: u64,
+  },
+  /// Returned when the WASM program executes an instruction the zkVM has no circuit for
+  #[error("UnsupportedInstruction: {0:?}")]
+  UnsupportedInstruction(Instruction),
 }
 
 impl From<wasmi::Error> for ZKWASMError {
   fn from(error: wasmi::Error) -> Self {
-    Self::WasmiError(error)
+    match error {
+      wasmi::Error::Trap(trap) if trap.trap_code() == Some(TrapCode::StackOverflow) => {
+        Self::StackOverflow
+      }
+      wasmi::Error::Trap(trap) => match trap.downcast_ref::<UnsupportedInstruction>() {
+        Some(UnsupportedInstruction(instruction)) => Self::UnsupportedInstruction(*instruction),
+        None => Self::WasmiError(wasmi::Error::Trap(trap)),
+      },
+      error => Self::WasmiError(error),
+    }
   }
 }
@@ -17,3 +17,4 @@ pub use wasmi_wasi::WasiCtx;
 
 // re-export
 pub use nova;
+pub use wasmi::{TraceHook, WitnessVM};
@@ -2,11 +2,12 @@
 //!
 //! i.e. continuations
 
-use std::{cell::OnceCell, marker::PhantomData};
+use std::{cell::OnceCell, marker::PhantomData, num::NonZeroUsize};
 
 use super::{
   error::ZKWASMError,
-  wasm_snark::{WASMPublicParams, WasmSNARK, ZKWASMInstance},
+  wasm_ctx::{WASMArgsBuilder, WasiWASMCtx, ZKWASMCtx},
+  wasm_snark::{ProvingConfig, StepSize, WASMPublicParams, WasmSNARK, ZKWASMInstance},
 };
 use itertools::Itertools;
 use nova::{
@@ -96,6 +97,67 @@ where
     }
   }
 
+  /// Prove each shard of a WASM program, in order, with the same [`WASMPublicParams`].
+  ///
+  /// # Note
+  ///
+  /// Proving only reads the public parameters, so a single [`WasmSNARK::setup`] serves every
+  /// shard of every program proven with the same [`StepSize`]. The shards have to be proven with
+  /// the public parameters later passed to [`ShardingSNARK::setup`], or they will not fold.
+  pub fn prove_shards(
+    pp: &WASMPublicParams<E, S1, S2>,
+    shards: &[impl ZKWASMCtx],
+    step_size: StepSize,
+  ) -> Result<(Vec<WasmSNARK<E, S1, S2>>, Vec<ZKWASMInstance<E>>), ZKWASMError> {
+    let mut snarks = Vec::with_capacity(shards.len());
+    let mut instances = Vec::with_capacity(shards.len());
+    for (i, shard) in shards.iter().enumerate() {
+      tracing::debug!("Proving shard {}/{}", i + 1, shards.len());
+      let (snark, U) = WasmSNARK::prove(pp, shard, step_size)?;
+      snarks.push(snark);
+      instances.push(U);
+    }
+    Ok((snarks, instances))
+  }
+
+  /// Prove the shard `shard_index` of a WASM program, whose execution is split in shards of
+  /// `shard_size` opcodes.
+  ///
+  /// # Note
+  ///
+  /// Shards are proven independently, e.g. by different nodes, so the shard is sliced from a fresh
+  /// execution of `program`. Check the proven shards chain with [`ShardingSNARK::verify_shards`].
+  ///
+  /// # Errors
+  ///
+  /// Returns [`ZKWASMError::InvalidTraceSliceValues`] if the opcodes of the shard overflow `usize`,
+  /// before executing `program`.
+  pub fn prove_shard(
+    pp: &WASMPublicParams<E, S1, S2>,
+    program: &WASMArgsBuilder,
+    shard_index: usize,
+    shard_size: NonZeroUsize,
+    step_size: StepSize,
+  ) -> Result<(WasmSNARK<E, S1, S2>, ZKWASMInstance<E>), ZKWASMError> {
+    let config = ProvingConfig::new(step_size).shard(shard_index, shard_size);
+    WasmSNARK::prove(pp, &WasiWASMCtx::new(program.clone().build()), config)
+  }
+
+  /// Check the instances of successive shards chain, i.e. the commitment to the initial memory
+  /// state (IS) of each shard is the commitment to the final memory state (FS) of the previous one
+  /// (see [`ZKWASMInstance::verify_continuation`]).
+  ///
+  /// # Note
+  ///
+  /// The commitments are only checked against the proofs by [`WasmSNARK::verify`], so only rely on
+  /// this after every shard verified.
+  pub fn verify_shards(instances: &[ZKWASMInstance<E>]) -> Result<(), ZKWASMError> {
+    instances
+      .iter()
+      .tuple_windows()
+      .try_for_each(|(prev, next)| ZKWASMInstance::verify_continuation(prev, next))
+  }
+
   /// Create a new instance of [`ShardingSNARK`]
   ///
   /// # Note
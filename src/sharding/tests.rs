@@ -1,5 +1,6 @@
 use super::ShardingSNARK;
 use crate::{
+  error::ZKWASMError,
   utils::{
     logging::init_logger,
     macros::{start_timer, stop_timer},
@@ -25,7 +26,7 @@ pub type S2 = spartan::snark::RelaxedR1CSSNARK<Dual<E>, EE2>;
 #[test]
 fn test_sharding_eq_func_mismatch() {
   init_logger();
-  let step_size = StepSize::new(100);
+  let step_size = StepSize::new(100).unwrap();
   let wasm_args_builder = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/nebula/eq_func.wat"))
     .unwrap()
@@ -40,7 +41,10 @@ fn test_sharding_eq_func_mismatch() {
 #[test]
 fn test_sharding_zk_ads() {
   init_logger();
-  let step_size = StepSize::new(100).set_memory_step_size(50_000);
+  let step_size = StepSize::new(100)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   let input_x = "200.05";
   let input_y = "-30.0";
   let wasm_args_builder = WASMArgsBuilder::default()
@@ -60,7 +64,10 @@ fn test_sharding_zk_ads() {
 #[test]
 fn test_sharding_bulk_ops() {
   init_logger();
-  let step_size = StepSize::new(200).set_memory_step_size(10_000);
+  let step_size = StepSize::new(200)
+    .unwrap()
+    .set_memory_step_size(10_000)
+    .unwrap();
   let wasm_args_builder = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/misc/bulk-ops.wat"))
     .unwrap()
@@ -73,7 +80,10 @@ fn test_sharding_bulk_ops() {
 #[test]
 fn test_sharding_kth_factor() {
   init_logger();
-  let step_size = StepSize::new(200).set_memory_step_size(50_000);
+  let step_size = StepSize::new(200)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   let wasm_args_builder = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/nebula/kth_factor.wat"))
     .unwrap()
@@ -87,7 +97,10 @@ fn test_sharding_kth_factor() {
 #[test]
 fn test_sharding_integer_hash() {
   init_logger();
-  let step_size = StepSize::new(1_000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1_000)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   let wasm_args_builder = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/nebula/integer_hash.wasm"))
     .unwrap()
@@ -101,7 +114,10 @@ fn test_sharding_integer_hash() {
 #[test]
 fn test_sharding_gradient_boosting() {
   init_logger();
-  let step_size = StepSize::new(1_000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1_000)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   let wasm_args_builder = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/gradient_boosting.wasm"))
     .unwrap()
@@ -111,6 +127,100 @@ fn test_sharding_gradient_boosting() {
   sim_nodes_and_orchestrator_node(&wasm_args_builder, step_size, shard_opcode_size);
 }
 
+#[test]
+fn test_sharding_reuses_public_params() {
+  init_logger();
+  let step_size = StepSize::new(100).unwrap();
+  let wasm_args_builder = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/nebula/kth_factor.wat"))
+    .unwrap()
+    .func_args(vec!["250".to_string(), "15".to_string()])
+    .invoke("kth_factor");
+
+  // Split the execution into 4 shards
+  let num_shards = 4;
+  let (trace, _, _) = estimate_wasm(&WasiWASMCtx::new(wasm_args_builder.clone().build())).unwrap();
+  let shard_opcode_size = trace.len().div_ceil(num_shards);
+  let shards = (0..num_shards)
+    .map(|i| {
+      let start = i * shard_opcode_size;
+      let end = NonZeroUsize::new(start + shard_opcode_size);
+      WasiWASMCtx::new(
+        wasm_args_builder
+          .clone()
+          .trace_slice(TraceSliceValues::new(start, end).unwrap())
+          .build(),
+      )
+    })
+    .collect::<Vec<_>>();
+
+  // Every shard is proven with the public parameters of a single setup
+  let node_pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (node_snarks, node_instances) =
+    ShardingSNARK::prove_shards(&node_pp, &shards, step_size).unwrap();
+  assert_eq!(node_snarks.len(), num_shards);
+  for (snark, U) in node_snarks.iter().zip(&node_instances) {
+    snark.verify(&node_pp, U).unwrap();
+  }
+
+  let sharding_pp = ShardingSNARK::setup(node_pp);
+  let mut sharding_snark =
+    ShardingSNARK::new(&sharding_pp, &node_snarks[0], &node_instances[0]).unwrap();
+  sharding_snark
+    .prove_sharding(&sharding_pp, &node_snarks, &node_instances)
+    .unwrap();
+  sharding_snark.verify(&sharding_pp).unwrap();
+}
+
+#[test]
+fn test_sharding_fib_shards_chain() {
+  init_logger();
+  let step_size = StepSize::new(500).unwrap();
+  // ~10k opcodes
+  let wasm_args_builder = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))
+    .unwrap()
+    .invoke("fib")
+    .func_args(vec!["600".to_string()]);
+
+  // Each of the 4 shards is proven on its own
+  let num_shards = 4;
+  let (trace, _, _) = estimate_wasm(&WasiWASMCtx::new(wasm_args_builder.clone().build())).unwrap();
+  let shard_size = NonZeroUsize::new(trace.len().div_ceil(num_shards)).unwrap();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snarks, instances): (Vec<_>, Vec<_>) = (0..num_shards)
+    .map(|i| ShardingSNARK::prove_shard(&pp, &wasm_args_builder, i, shard_size, step_size).unwrap())
+    .unzip();
+  for (snark, U) in snarks.iter().zip(&instances) {
+    snark.verify(&pp, U).unwrap();
+  }
+
+  // The FS of each shard is the IS of the next, but not the other way around
+  ShardingSNARK::<E, S1, S2>::verify_shards(&instances).unwrap();
+  let swapped = [instances[1].clone(), instances[0].clone()];
+  assert!(ShardingSNARK::<E, S1, S2>::verify_shards(&swapped).is_err());
+}
+
+#[test]
+fn test_sharding_shard_overflow() {
+  init_logger();
+  let step_size = StepSize::new(10).unwrap();
+  let wasm_args_builder = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))
+    .unwrap()
+    .invoke("fib")
+    .func_args(vec!["16".to_string()]);
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  // The start of the shard, then its end, overflows usize
+  let shard_size = NonZeroUsize::new(2).unwrap();
+  for shard_index in [usize::MAX, usize::MAX / 2] {
+    let res =
+      ShardingSNARK::prove_shard(&pp, &wasm_args_builder, shard_index, shard_size, step_size);
+    assert!(matches!(res, Err(ZKWASMError::InvalidTraceSliceValues(_))));
+  }
+}
+
 fn sim_nodes_and_orchestrator_node(
   wasm_args_builder: &WASMArgsBuilder,
   step_size: StepSize,
@@ -203,7 +313,7 @@ fn node_nw(
     let wasm_ctx = WasiWASMCtx::new(
       wasm_args_builder
         .clone()
-        .trace_slice(TraceSliceValues::new(start, NonZeroUsize::new(end)))
+        .trace_slice(TraceSliceValues::new(start, NonZeroUsize::new(end)).unwrap())
         .build(),
     );
     let (snark, U) = WasmSNARK::<E, S1, S2>::prove(node_pp, &wasm_ctx, step_size).unwrap();
@@ -224,7 +334,10 @@ mod test_mismatches {
   #[test]
   fn test_sharding_zk_ads_mismatch1() {
     init_logger();
-    let step_size = StepSize::new(100).set_memory_step_size(50_000);
+    let step_size = StepSize::new(100)
+      .unwrap()
+      .set_memory_step_size(50_000)
+      .unwrap();
     let input_x = "200.05";
     let input_y = "-30.0";
     let wasm_args_builder = WASMArgsBuilder::default()
@@ -244,7 +357,10 @@ mod test_mismatches {
   #[test]
   fn test_sharding_zk_ads_mismatch2() {
     init_logger();
-    let step_size = StepSize::new(99).set_memory_step_size(50_000);
+    let step_size = StepSize::new(99)
+      .unwrap()
+      .set_memory_step_size(50_000)
+      .unwrap();
     let input_x = "200.05";
     let input_y = "-30.0";
     let wasm_args_builder = WASMArgsBuilder::default()
@@ -264,7 +380,10 @@ mod test_mismatches {
   #[test]
   fn test_sharding_zk_ads_mismatch3() {
     init_logger();
-    let step_size = StepSize::new(177).set_memory_step_size(50_000);
+    let step_size = StepSize::new(177)
+      .unwrap()
+      .set_memory_step_size(50_000)
+      .unwrap();
     let input_x = "200.05";
     let input_y = "-30.0";
     let wasm_args_builder = WASMArgsBuilder::default()
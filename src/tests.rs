@@ -1,18 +1,42 @@
 use super::{
   error::ZKWASMError,
-  wasm_ctx::{TraceSliceValues, WASMArgsBuilder, WASMCtx, WasiWASMCtx, ZKWASMCtx},
-  wasm_snark::{StepSize, WasmSNARK},
+  wasm_ctx::{
+    HostRegistry, ISMemSizes, TraceSliceValues, WASMArgs, WASMArgsBuilder, WASMCtx, WasiWASMCtx,
+    ZKWASMCtx,
+  },
+  wasm_snark::{
+    construct_IS, mcc::multiset_ops::step_RS_WS, HostCall, ProvingConfig, StepSize, WasmSNARK,
+    ZKWASMInstance, MEMORY_OPS_PER_STEP,
+  },
 };
 use crate::utils::{
   logging::init_logger,
   macros::{start_timer, stop_timer},
+  memory_diff,
 };
 use nova::{
   provider::{ipa_pc, Bn256EngineIPA},
   spartan,
   traits::Dual,
 };
-use std::{num::NonZeroUsize, path::PathBuf, time::Instant};
+use std::{
+  cell::RefCell,
+  num::NonZeroUsize,
+  path::PathBuf,
+  rc::Rc,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+  time::Instant,
+};
+use wasmi::{
+  core::ValueType,
+  etable::{step_info::StepInfo, ETEntry},
+  mtable::memory_event_of_step,
+  BCTableIdx, Instruction as Instr, TraceHook, Tracer, TracerError, WitnessVM, HOST_CALL_GLOBAL,
+  HOST_CALL_READ,
+};
 
 /// Curve Cycle to prove/verify on
 pub type E = Bn256EngineIPA;
@@ -47,7 +71,7 @@ fn test_wasm_snark_with(wasm_ctx: impl ZKWASMCtx, step_size: StepSize) -> Result
 
 #[test]
 fn test_bit_check() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(16);
+  let step_size = StepSize::new(16)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/nebula/bit_check.wat"))
@@ -65,7 +89,7 @@ fn test_bit_check() -> Result<(), ZKWASMError> {
 
 #[test]
 fn test_int_opcodes() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(100);
+  let step_size = StepSize::new(100)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/int_opcodes.wat"))?
@@ -80,7 +104,7 @@ fn test_int_opcodes() -> Result<(), ZKWASMError> {
 
 #[test]
 fn test_eq_func() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(500);
+  let step_size = StepSize::new(500)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/nebula/eq_func.wat"))?
@@ -97,7 +121,7 @@ fn test_eq_func() -> Result<(), ZKWASMError> {
 
 #[test]
 fn test_complete_int_opcodes() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(1000);
+  let step_size = StepSize::new(1000)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/complete_int_opcodes.wat"))
@@ -114,7 +138,10 @@ fn test_complete_int_opcodes() -> Result<(), ZKWASMError> {
 #[test]
 fn test_zk_ads() {
   init_logger();
-  let step_size = StepSize::new(500).set_memory_step_size(50_000);
+  let step_size = StepSize::new(500)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   let input_x = "200.05";
   let input_y = "-30.0";
   let wasm_args = WASMArgsBuilder::default()
@@ -134,7 +161,7 @@ fn test_zk_ads() {
 
 #[test]
 fn test_bulk_ops() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(1000).set_memory_step_size(10_000);
+  let step_size = StepSize::new(1000)?.set_memory_step_size(10_000)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/misc/bulk-ops.wat"))?
@@ -148,9 +175,162 @@ fn test_bulk_ops() -> Result<(), ZKWASMError> {
   Ok(())
 }
 
+#[test]
+fn test_memory_copy_overlap() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/memory_copy_overlap.wat"))?
+    .build();
+
+  let wasm_ctx = WASMCtx::new(wasm_args);
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+
+  // The copies behave as if through a temporary buffer
+  let mut memory = (1..=32u8).collect::<Vec<_>>();
+  memory.copy_within(2..15, 7);
+  memory.copy_within(9..22, 3);
+  let expected = u64::from_le_bytes(memory[8..16].try_into().unwrap());
+  assert_eq!(U.return_values()?, vec![expected]);
+  Ok(())
+}
+
+#[test]
+fn test_memory_init() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/memory_init.wat"))?
+    .build();
+
+  let wasm_ctx = WASMCtx::new(wasm_args);
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+
+  let mut memory = (1..=16u8).collect::<Vec<_>>();
+  let segment = (0xa1..=0xb3u8).collect::<Vec<_>>();
+  memory[5..16].copy_from_slice(&segment[3..14]);
+  memory[14..16].copy_from_slice(&segment[17..19]);
+  let expected = u64::from_le_bytes(memory[8..16].try_into().unwrap());
+  assert_eq!(U.return_values()?, vec![expected]);
+
+  // A dropped segment is empty
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/memory_init.wat"))?
+    .invoke("init_dropped")
+    .build();
+  assert!(WASMCtx::new(wasm_args).execution_trace().is_err());
+  Ok(())
+}
+
+#[test]
+fn test_unaligned_load() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/memory/unaligned_load.wat"))?
+    .build();
+
+  let wasm_ctx = WASMCtx::new(wasm_args);
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+
+  // Both loads read bytes of the words 0 & 1
+  let memory = (1..=16u8).collect::<Vec<_>>();
+  let i64_load = u64::from_le_bytes(memory[5..13].try_into().unwrap());
+  let i32_load = u32::from_le_bytes(memory[6..10].try_into().unwrap());
+  assert_eq!(U.return_values()?, vec![i64_load + u64::from(i32_load)]);
+  Ok(())
+}
+
+#[test]
+fn test_sub_word_load() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  // i32 values are pushed as their u32 bits
+  let load8_s = i32::from(0xffu8 as i8) as u32 as u64;
+  let load16_u = u64::from(0x8000u16);
+  for (invoke, expected) in [("load8_s", load8_s), ("load16_u", load16_u)] {
+    let wasm_args = WASMArgsBuilder::default()
+      .file_path(PathBuf::from("wasm/memory/sub_word_load.wat"))?
+      .invoke(invoke)
+      .build();
+    let wasm_ctx = WASMCtx::new(wasm_args);
+
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+    snark.verify(&pp, &U)?;
+    assert_eq!(U.return_values()?, vec![expected]);
+  }
+  Ok(())
+}
+
+#[test]
+fn test_sub_word_store() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  // Only the stored byte changes, the other 7 bytes of the word are kept
+  let mut word = [0, 1, 2, 3, 4, 5, 6, 7];
+  word[3] = 0xab;
+  let stored = u64::from_le_bytes(word);
+  for (invoke, expected) in [("store8", stored), ("store_last", 42)] {
+    let wasm_args = WASMArgsBuilder::default()
+      .file_path(PathBuf::from("wasm/memory/sub_word_store.wat"))?
+      .invoke(invoke)
+      .build();
+    let wasm_ctx = WASMCtx::new(wasm_args);
+
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+    snark.verify(&pp, &U)?;
+    assert_eq!(U.return_values()?, vec![expected]);
+  }
+  Ok(())
+}
+
+#[test]
+fn test_memory_size_after_grow() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/memory/grow_size.wat"))?
+    .invoke("main")
+    .build();
+  let wasm_ctx = WASMCtx::new(wasm_args);
+
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![2]);
+
+  // The page count the proof carries is the grown one memory.size read
+  assert_eq!(U.memory_pages()?, 2);
+  assert_eq!(snark.verify_and_extract(&pp, &U)?.memory_pages(), 2);
+  ZKWASMInstance::expected_inputs(&pp, &wasm_ctx, step_size)?.check(&U)?;
+
+  // A trace reporting another memory size does not prove
+  let (mut execution_trace, IS, IS_sizes) = wasm_ctx.execution_trace()?;
+  let vm = execution_trace
+    .iter_mut()
+    .find(|vm| vm.instr == Instr::MemorySize)
+    .unwrap();
+  vm.Y = 3;
+  let forged =
+    WasmSNARK::<E, S1, S2>::prove_from_trace(&pp, execution_trace, IS, IS_sizes, &[], step_size)
+      .and_then(|(snark, U)| snark.verify(&pp, &U));
+  assert!(forged.is_err());
+  Ok(())
+}
+
 #[test]
 fn test_kth_factor() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(1000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1000)?.set_memory_step_size(50_000)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/nebula/kth_factor.wat"))?
@@ -166,7 +346,10 @@ fn test_kth_factor() -> Result<(), ZKWASMError> {
 
 #[test]
 fn test_integer_hash() {
-  let step_size = StepSize::new(2_500).set_memory_step_size(50_000);
+  let step_size = StepSize::new(2_500)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/nebula/integer_hash.wasm"))
@@ -182,7 +365,10 @@ fn test_integer_hash() {
 
 #[test]
 fn test_gradient_boosting() {
-  let step_size = StepSize::new(5_000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(5_000)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/gradient_boosting.wasm"))
@@ -199,7 +385,10 @@ fn test_uni_poly_eval() {
   let x = "1";
   let size = "10";
 
-  let step_size = StepSize::new(2_500).set_memory_step_size(50_000);
+  let step_size = StepSize::new(2_500)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/misc/uni-poly-eval.wasm"))
@@ -214,12 +403,12 @@ fn test_uni_poly_eval() {
 
 #[test]
 fn test_bls() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(1_000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1_000)?.set_memory_step_size(50_000)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/bls.wasm"))
     .unwrap()
-    .trace_slice(TraceSliceValues::new(10_000, NonZeroUsize::new(20_000)))
+    .trace_slice(TraceSliceValues::new(10_000, NonZeroUsize::new(20_000))?)
     .build();
 
   let wasm_ctx = WASMCtx::new(wasm_args);
@@ -228,9 +417,91 @@ fn test_bls() -> Result<(), ZKWASMError> {
   Ok(())
 }
 
+#[test]
+fn test_invalid_trace_slice() -> Result<(), ZKWASMError> {
+  // A zero shard size, or a start past the end, is rejected on construction
+  for (start, end) in [(10, 10), (20, 10)] {
+    assert!(matches!(
+      TraceSliceValues::new(start, NonZeroUsize::new(end)),
+      Err(ZKWASMError::InvalidTraceSliceValues(_))
+    ));
+  }
+  let mut slice = TraceSliceValues::new(0, NonZeroUsize::new(10))?;
+  assert!(slice.set_start(10).is_err());
+  assert!(slice.set_end(NonZeroUsize::new(20)).is_ok());
+
+  // A start past the end of the execution trace is only known once the program runs
+  let step_size = StepSize::new(10)?;
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .func_args(vec![String::from("16")])
+    .trace_slice(TraceSliceValues::new(1_000_000, None)?)
+    .build();
+  let wasm_ctx = WASMCtx::new(wasm_args);
+  assert!(matches!(
+    wasm_ctx.execution_trace(),
+    Err(ZKWASMError::InvalidTraceSliceValues(_))
+  ));
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size),
+    Err(ZKWASMError::InvalidTraceSliceValues(_))
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_exported_functions() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args_builder =
+    WASMArgsBuilder::default().file_path(PathBuf::from("wasm/memory/sub_word_store.wat"))?;
+  let exports = WASMCtx::new(wasm_args_builder.clone().build()).exported_functions()?;
+  assert_eq!(exports, vec!["store8", "store_last"]);
+
+  // Prove the export chosen by name
+  let wasm_ctx = WASMCtx::new(wasm_args_builder.clone().export("store_last")?.build());
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![42]);
+
+  // The name must be exported
+  assert!(matches!(
+    wasm_args_builder.clone().export("missing"),
+    Err(ZKWASMError::ExportNotFound(name)) if name == "missing"
+  ));
+  let wasm_ctx = WASMCtx::new(wasm_args_builder.invoke("missing").build());
+  assert!(matches!(
+    wasm_ctx.execution_trace(),
+    Err(ZKWASMError::ExportNotFound(_))
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_event_id_overflow() {
+  // A step whose memory ops are counted past u32::MAX errors instead of panicking
+  let step = ETEntry {
+    eid: 1,
+    allocated_memory_pages: 0,
+    step_info: StepInfo::GetGlobal { idx: 0, value: 0 },
+    pre_sp: 0,
+  };
+  let err: ZKWASMError = memory_event_of_step(&step, &mut u32::MAX)
+    .unwrap_err()
+    .into();
+  assert!(matches!(
+    err,
+    ZKWASMError::TracerError(TracerError::EventIdOverflow)
+  ));
+  assert!(memory_event_of_step(&step, &mut 1).is_ok());
+}
+
 #[test]
 fn test_fib_large() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(1_000);
+  let step_size = StepSize::new(1_000)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/misc/fib.wat"))?
@@ -246,7 +517,7 @@ fn test_fib_large() -> Result<(), ZKWASMError> {
 
 #[test]
 fn test_fib_small() -> Result<(), ZKWASMError> {
-  let step_size = StepSize::new(10);
+  let step_size = StepSize::new(10)?;
   init_logger();
   let wasm_args = WASMArgsBuilder::default()
     .file_path(PathBuf::from("wasm/misc/fib.wat"))?
@@ -260,10 +531,938 @@ fn test_fib_small() -> Result<(), ZKWASMError> {
   Ok(())
 }
 
+#[test]
+fn test_verify_and_extract() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .func_args(vec![String::from("10")])
+    .build();
+
+  let wasm_ctx = WASMCtx::new(wasm_args);
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+
+  let output = snark.verify_and_extract(&pp, &U)?;
+  assert_eq!(output.stack_top(), 55);
+
+  // Outputs are not extracted from a shard, which does not start from the initial state
+  let shard_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .func_args(vec![String::from("10")])
+    .trace_slice(TraceSliceValues::new(20, None)?)
+    .build();
+  let (shard_snark, shard_U) =
+    WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(shard_args), step_size)?;
+  shard_snark.verify(&pp, &shard_U)?;
+  assert!(matches!(
+    shard_snark.verify_and_extract(&pp, &shard_U),
+    Err(ZKWASMError::InputMismatch(input)) if input == "execution_z0"
+  ));
+
+  // outputs can only be extracted from the recursive SNARK
+  let snark = snark.compress(&pp, &U)?;
+  assert!(matches!(
+    snark.verify_and_extract(&pp, &U),
+    Err(ZKWASMError::NotRecursive)
+  ));
+
+  Ok(())
+}
+
+#[test]
+fn test_extract_return_values() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/answer.wat"))?
+    .build();
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+  let output = snark.verify_and_extract(&pp, &U)?;
+  assert_eq!(output.return_values(), [42]);
+  assert_eq!(output.stack_top(), 42);
+
+  let snark = snark.compress(&pp, &U)?;
+  assert!(matches!(
+    snark.verify_and_extract(&pp, &U),
+    Err(ZKWASMError::NotRecursive)
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_memory_trace() -> Result<(), ZKWASMError> {
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/memory/store_3.wat"))?
+    .invoke("call")
+    .build();
+  let wasm_ctx = WASMCtx::new(wasm_args);
+
+  let memory_trace = wasm_ctx.memory_trace()?;
+  assert_eq!(memory_trace.reads(), 0);
+  // Each store writes the word at its effective address and the following word
+  assert_eq!(memory_trace.writes(), 6);
+  assert_eq!(memory_trace.touched_addresses(), 6);
+  Ok(())
+}
+
+#[test]
+fn test_zero_step_size() {
+  assert!(matches!(
+    StepSize::new(0),
+    Err(ZKWASMError::InvalidStepSize(_))
+  ));
+  assert!(matches!(
+    StepSize::new(10).unwrap().set_memory_step_size(0),
+    Err(ZKWASMError::InvalidStepSize(_))
+  ));
+}
+
+#[test]
+fn test_memory_ops_per_step() -> Result<(), ZKWASMError> {
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/tribonacci.wat"))?
+    .invoke("tribonacci")
+    .func_args(vec![String::from("10")])
+    .build();
+  let wasm_ctx = WASMCtx::new(wasm_args);
+
+  // Each call returns three values, which a drop keep step only keeps with 12 memory ops
+  let step_size = StepSize::new(10)?.set_memory_ops_per_step(12)?;
+  test_wasm_snark_with(wasm_ctx.clone(), step_size)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![81, 149, 274]);
+
+  // Odd or below the minimum
+  assert!(matches!(
+    StepSize::new(10)?.set_memory_ops_per_step(7),
+    Err(ZKWASMError::InvalidStepSize(_))
+  ));
+  assert!(matches!(
+    StepSize::new(10)?.set_memory_ops_per_step(6),
+    Err(ZKWASMError::InvalidStepSize(_))
+  ));
+
+  // Public parameters set up with a different number of memory ops per step
+  let default_step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(default_step_size);
+  assert_eq!(pp.memory_ops_per_step(), MEMORY_OPS_PER_STEP);
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size),
+    Err(ZKWASMError::InvalidStepSize(_))
+  ));
+
+  // The default of 8 memory ops does not fit the returns of the program
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, default_step_size),
+    Err(ZKWASMError::InvalidStepSize(_))
+  ));
+  Ok(())
+}
+
+/// Execution context whose `env/oracle` host function always responds with 42
+struct OracleCtx {
+  args: WASMArgs,
+}
+
+impl ZKWASMCtx for OracleCtx {
+  type T = ();
+
+  fn create_store(engine: &wasmi::Engine) -> wasmi::Store<Self::T> {
+    wasmi::Store::new(engine, ())
+  }
+
+  fn create_linker(engine: &wasmi::Engine) -> Result<wasmi::Linker<Self::T>, ZKWASMError> {
+    let mut linker = <wasmi::Linker<()>>::new(engine);
+    linker
+      .func_wrap("env", "oracle", |_: i64| -> i64 { 42 })
+      .map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+    Ok(linker)
+  }
+
+  fn args(&self) -> &WASMArgs {
+    &self.args
+  }
+}
+
+#[test]
+fn test_host_call_commitment() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(1_000)?;
+  init_logger();
+  let args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/oracle.wat"))?
+    .invoke("main")
+    .func_args(vec![String::from("7")])
+    .build();
+
+  let wasm_ctx = OracleCtx { args };
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![43]);
+
+  assert!(U.commits_to_host_calls(&[HostCall::new(vec![7], vec![42])]));
+  assert!(!U.commits_to_host_calls(&[HostCall::new(vec![7], vec![41])]));
+  assert!(!U.commits_to_host_calls(&[HostCall::new(vec![8], vec![42])]));
+  assert!(!U.commits_to_host_calls(&[]));
+  Ok(())
+}
+
+/// Context whose `env/reenter` host function calls back the guest's exported `add`
+struct ReentrantCtx {
+  args: WASMArgs,
+}
+
+impl ZKWASMCtx for ReentrantCtx {
+  type T = ();
+
+  fn create_store(engine: &wasmi::Engine) -> wasmi::Store<Self::T> {
+    wasmi::Store::new(engine, ())
+  }
+
+  fn create_linker(engine: &wasmi::Engine) -> Result<wasmi::Linker<Self::T>, ZKWASMError> {
+    let mut linker = <wasmi::Linker<()>>::new(engine);
+    linker
+      .func_wrap(
+        "env",
+        "reenter",
+        |mut caller: wasmi::Caller<'_, ()>, x: i64| -> Result<i64, wasmi::core::Trap> {
+          let add = caller
+            .get_export("add")
+            .and_then(wasmi::Extern::into_func)
+            .ok_or_else(|| wasmi::core::Trap::new("missing export `add`"))?
+            .typed::<(i64, i64), i64>(&caller)
+            .map_err(|err| wasmi::core::Trap::new(err.to_string()))?;
+          add.call(&mut caller, (x, 1))
+        },
+      )
+      .map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+    Ok(linker)
+  }
+
+  fn args(&self) -> &WASMArgs {
+    &self.args
+  }
+}
+
+#[test]
+fn test_reentrant_host_call() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(1_000)?;
+  init_logger();
+  let args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/reentrant.wat"))?
+    .invoke("main")
+    .func_args(vec![String::from("7")])
+    .build();
+  let wasm_ctx = ReentrantCtx { args };
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+
+  // `add(7, 1)` stores 8 & bumps the global, which `main` adds to the response
+  assert_eq!(U.return_values()?, vec![8 + 8 + 1]);
+  assert!(U.commits_to_host_calls(&[HostCall::new(vec![7], vec![8])]));
+  Ok(())
+}
+
+/// Context whose `env/nop` host function takes & returns nothing
+struct NopHostCtx {
+  args: WASMArgs,
+}
+
+impl ZKWASMCtx for NopHostCtx {
+  type T = ();
+
+  fn create_store(engine: &wasmi::Engine) -> wasmi::Store<Self::T> {
+    wasmi::Store::new(engine, ())
+  }
+
+  fn create_linker(engine: &wasmi::Engine) -> Result<wasmi::Linker<Self::T>, ZKWASMError> {
+    let mut linker = <wasmi::Linker<()>>::new(engine);
+    linker
+      .func_wrap("env", "nop", || {})
+      .map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+    Ok(linker)
+  }
+
+  fn args(&self) -> &WASMArgs {
+    &self.args
+  }
+}
+
+#[test]
+fn test_host_call_after_memory_grow() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(1_000)?;
+  init_logger();
+  let args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/host_grow.wat"))?
+    .invoke("main")
+    .build();
+  let wasm_ctx = NopHostCtx { args };
+
+  // The host call on an empty stack writes no word of the grown memory, which is all in IS
+  let (execution_trace, _, IS_sizes) = wasm_ctx.execution_trace()?;
+  assert!(!execution_trace
+    .iter()
+    .any(|vm| vm.instr == Instr::HostCallStep && vm.I != HOST_CALL_GLOBAL));
+  assert_eq!(IS_sizes.mem_len(), 2 * 8192);
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![2]);
+  Ok(())
+}
+
+/// Context whose `env/fill` host function writes 32 bytes to linear memory at the given pointer
+struct FillHostCtx {
+  args: WASMArgs,
+}
+
+impl ZKWASMCtx for FillHostCtx {
+  type T = ();
+
+  fn create_store(engine: &wasmi::Engine) -> wasmi::Store<Self::T> {
+    wasmi::Store::new(engine, ())
+  }
+
+  fn create_linker(engine: &wasmi::Engine) -> Result<wasmi::Linker<Self::T>, ZKWASMError> {
+    let mut linker = <wasmi::Linker<()>>::new(engine);
+    linker
+      .func_wrap(
+        "env",
+        "fill",
+        |mut caller: wasmi::Caller<'_, ()>, ptr: i32| -> Result<(), wasmi::core::Trap> {
+          let memory = caller
+            .get_export("memory")
+            .and_then(wasmi::Extern::into_memory)
+            .ok_or_else(|| wasmi::core::Trap::new("missing export `memory`"))?;
+          let buf = (1..=32).collect::<Vec<u8>>();
+          memory
+            .write(&mut caller, ptr as usize, &buf)
+            .map_err(|err| wasmi::core::Trap::new(err.to_string()))
+        },
+      )
+      .map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+    Ok(linker)
+  }
+
+  fn args(&self) -> &WASMArgs {
+    &self.args
+  }
+}
+
+#[test]
+fn test_host_call_memory_writes() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/host_fill.wat"))?
+    .build();
+  let wasm_ctx = FillHostCtx { args };
+
+  // Only the 4 words of the buffer the host call wrote are traced
+  let (execution_trace, _, _) = wasm_ctx.execution_trace()?;
+  let words = (1..=32u8)
+    .collect::<Vec<_>>()
+    .chunks(8)
+    .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+    .collect::<Vec<_>>();
+  let host_writes = execution_trace
+    .iter()
+    .filter(|vm| vm.instr == Instr::HostCallStep && vm.I != HOST_CALL_GLOBAL)
+    .map(|vm| (vm.Y, vm.P))
+    .collect::<Vec<_>>();
+  assert_eq!(
+    host_writes,
+    (2..6).zip(words.iter().copied()).collect::<Vec<_>>()
+  );
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  let sum = words.iter().fold(0u64, |sum, word| sum.wrapping_add(*word));
+  assert_eq!(U.return_values()?, vec![sum]);
+  Ok(())
+}
+
+#[test]
+fn test_private_input() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(1_000)?;
+  init_logger();
+  let words = (0..16u64)
+    .map(|i| 0x0123_4567_89ab_cdef ^ (i << 40))
+    .collect::<Vec<_>>();
+  let args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/private_sum.wat"))?
+    .private_input(words.iter().flat_map(|word| word.to_le_bytes()).collect())
+    .build();
+  let wasm_ctx = WASMCtx::new(args);
+
+  // The private input is written by the host call, it is not in IS
+  let (_, IS, _) = wasm_ctx.execution_trace()?;
+  assert!(IS.iter().all(|(_, val, _)| !words.contains(val)));
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  let sum = words.iter().fold(0u64, |sum, word| sum.wrapping_add(*word));
+  assert_eq!(U.return_values()?, vec![sum]);
+  Ok(())
+}
+
+#[test]
+fn test_proving_config() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(1_000)?;
+  init_logger();
+  let words = [7u64, 35];
+  let wasm_ctx = WASMCtx::new(
+    WASMArgsBuilder::default()
+      .file_path(PathBuf::from("wasm/misc/private_sum.wat"))?
+      .build(),
+  );
+  let config = ProvingConfig::new(step_size)
+    .private_input(words.iter().flat_map(|word| word.to_le_bytes()).collect())
+    .max_memory_pages(1)
+    .parallel(false);
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, config.clone())?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![42]);
+
+  // The threads & the context are knobs of the config too
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(
+    &pp,
+    &wasm_ctx,
+    config
+      .clone()
+      .num_threads(NonZeroUsize::MIN)
+      .context(b"session-3"),
+  )?;
+  snark.verify_with_context(&pp, &U, b"session-3")?;
+  assert!(snark.verify(&pp, &U).is_err());
+
+  // The memory limit holds whichever step size the config proves with
+  let bounded = config.clone().max_memory_pages(0).step_size(step_size);
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, bounded),
+    Err(ZKWASMError::MemoryLimitExceeded { .. })
+  ));
+
+  // A shard out of the range of usize is rejected instead of wrapping around
+  let shard_size = NonZeroUsize::new(2).unwrap();
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, config.shard(usize::MAX, shard_size)),
+    Err(ZKWASMError::InvalidTraceSliceValues(_))
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_host_registry() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/host_add.wat"))?
+    .func_args(vec![String::from("40"), String::from("2")])
+    .build();
+  let add = |args: &[wasmi::Value]| match args {
+    [wasmi::Value::I64(x), wasmi::Value::I64(y)] => vec![wasmi::Value::I64(x.wrapping_add(*y))],
+    _ => unreachable!("`host.add` is declared with two i64 params"),
+  };
+  let registry = HostRegistry::new().register(
+    "host",
+    "add",
+    vec![ValueType::I64, ValueType::I64],
+    vec![ValueType::I64],
+    add,
+  );
+  let wasm_ctx = WASMCtx::new(args.clone()).with_host_registry(registry);
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![84]);
+
+  // Results not matching the declared signature trap
+  let registry = HostRegistry::new().register(
+    "host",
+    "add",
+    vec![ValueType::I64, ValueType::I64],
+    vec![ValueType::I64],
+    |_: &[wasmi::Value]| vec![wasmi::Value::I32(0)],
+  );
+  let wasm_ctx = WASMCtx::new(args).with_host_registry(registry);
+  assert!(wasm_ctx.execution_trace().is_err());
+  Ok(())
+}
+
+#[test]
+fn test_hash_precompiles() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/hash_precompiles.wat"))?
+    .build();
+  let wasm_ctx = WASMCtx::new(args).with_host_registry(HostRegistry::new().with_hash_precompiles());
+
+  // Known vectors of "abc"
+  let words = |digest: &str| {
+    (0..4)
+      .map(|i| u64::from_str_radix(&digest[16 * i..16 * (i + 1)], 16).map(u64::swap_bytes))
+      .collect::<Result<Vec<_>, _>>()
+      .unwrap()
+  };
+  let keccak256 = words("4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+  let sha256 = words("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+
+  // Each precompile reads the word of the input & writes the 4 words of its digest
+  let (execution_trace, _, _) = wasm_ctx.execution_trace()?;
+  let host_reads = execution_trace
+    .iter()
+    .filter(|vm| vm.instr == Instr::HostCallStep && vm.I == HOST_CALL_READ)
+    .map(|vm| (vm.Y, vm.P))
+    .collect::<Vec<_>>();
+  assert_eq!(host_reads, vec![(0, 0x636261); 2]);
+  let host_writes = execution_trace
+    .iter()
+    .filter(|vm| vm.instr == Instr::HostCallStep && vm.I == 0)
+    .map(|vm| (vm.Y, vm.P))
+    .collect::<Vec<_>>();
+  let digests = keccak256.iter().chain(&sha256).copied();
+  assert_eq!(host_writes, (8..16).zip(digests).collect::<Vec<_>>());
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![keccak256[0] ^ sha256[0]]);
+  Ok(())
+}
+
+#[test]
+fn test_opcode_histogram() -> Result<(), ZKWASMError> {
+  let args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .build();
+  let engine = wasmi::Engine::default();
+  let module = wasmi::Module::new(&engine, &args.program[..])?;
+  let mut store = wasmi::Store::new(&engine, ());
+  let tracer = Rc::new(RefCell::new(Tracer::new()));
+  let instance = <wasmi::Linker<()>>::new(&engine)
+    .instantiate_with_trace(&mut store, &module, tracer.clone())?
+    .start_with_trace(&mut store, tracer.clone())?;
+  let func = instance
+    .get_func(&store, "fib")
+    .ok_or_else(|| ZKWASMError::ExportNotFound(String::from("fib")))?;
+  let mut results = [wasmi::Value::I64(0)];
+  func.call_with_trace(
+    &mut store,
+    &[wasmi::Value::I64(16)],
+    &mut results,
+    tracer.clone(),
+  )?;
+  let tracer = tracer.borrow();
+
+  // Each of the 14 iterations of the loop body adds the two previous terms & bumps the counter
+  let histogram = tracer.opcode_histogram();
+  assert_eq!(histogram[&Instr::I64Add.index_j()], 2 * 14);
+  assert_eq!(histogram.values().sum::<usize>(), tracer.step_count());
+  Ok(())
+}
+
+#[test]
+fn test_imported_call() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(1_000)?;
+  init_logger();
+  let args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/oracle.wat"))?
+    .invoke("main")
+    .func_args(vec![String::from("7")])
+    .build();
+  let wasm_ctx = OracleCtx { args };
+
+  // The call to the imported `env/oracle` is traced with its own J index
+  let (execution_trace, _, _) = wasm_ctx.execution_trace()?;
+  let calls = execution_trace
+    .iter()
+    .filter(|vm| matches!(vm.instr, wasmi::Instruction::Call(..)))
+    .collect::<Vec<_>>();
+  assert_eq!(calls.len(), 1);
+  assert_ne!(calls[0].J, wasmi::Instruction::Unreachable.index_j());
+
+  test_wasm_snark_with(wasm_ctx, step_size)
+}
+
+#[test]
+fn test_proof_context() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .func_args(vec![String::from("10")])
+    .build();
+
+  let wasm_ctx = WASMCtx::new(wasm_args);
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let config = ProvingConfig::new(step_size).context(b"session-1");
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, config)?;
+  snark.verify_with_context(&pp, &U, b"session-1")?;
+
+  // The proof can not be replayed in another session, nor verified without its context
+  assert!(matches!(
+    snark.verify_with_context(&pp, &U, b"session-2"),
+    Err(ZKWASMError::ContextMismatch)
+  ));
+  assert!(snark.verify(&pp, &U).is_err());
+
+  // The outputs of the proof are extracted under its context only
+  let output = snark.verify_and_extract_with_context(&pp, &U, b"session-1")?;
+  assert_eq!(output.stack_top(), 55);
+  assert!(matches!(
+    snark.verify_and_extract(&pp, &U),
+    Err(ZKWASMError::ContextMismatch)
+  ));
+
+  // A compressed SNARK can't bind the context, so the proof is not compressed
+  assert!(matches!(
+    snark.compress(&pp, &U),
+    Err(ZKWASMError::NotRecursive)
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_prove_num_threads() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .func_args(vec![String::from("10")])
+    .build();
+
+  let wasm_ctx = WASMCtx::new(wasm_args);
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (_, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+
+  // Proving serially gives the same instance as proving on the global thread pool
+  let config = ProvingConfig::new(step_size).num_threads(NonZeroUsize::new(1).unwrap());
+  let (snark, serial_U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, config)?;
+  snark.verify(&pp, &serial_U)?;
+  assert_eq!(
+    serde_json::to_string(&serial_U).unwrap(),
+    serde_json::to_string(&U).unwrap()
+  );
+  Ok(())
+}
+
+#[test]
+fn test_continuation() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args_builder = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/memory_counter.wat"))?
+    .invoke("count")
+    .func_args(vec![String::from("8")]);
+  let (trace, _, _) = WASMCtx::new(wasm_args_builder.clone().build()).execution_trace()?;
+  let run_len = trace.len().div_ceil(2);
+
+  // Prove two successive runs of the counter, the second one reading the counter written by the
+  // first one
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let mut instances = Vec::new();
+  for (start, end) in [(0, run_len), (run_len, 2 * run_len)] {
+    let wasm_ctx = WASMCtx::new(
+      wasm_args_builder
+        .clone()
+        .trace_slice(TraceSliceValues::new(start, NonZeroUsize::new(end))?)
+        .build(),
+    );
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+    snark.verify(&pp, &U)?;
+    instances.push(U);
+  }
+  ZKWASMInstance::verify_continuation(&instances[0], &instances[1])?;
+  assert_eq!(instances[1].return_values()?, vec![8]);
+
+  // The memory of the runs does not chain the other way around
+  assert!(matches!(
+    ZKWASMInstance::verify_continuation(&instances[1], &instances[0]),
+    Err(ZKWASMError::ContinuationMismatch)
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_resume_mid_function() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args_builder = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .func_args(vec![String::from("16")]);
+  let (trace, mut IS, IS_sizes) =
+    WASMCtx::new(wasm_args_builder.clone().build()).execution_trace()?;
+
+  // The prior shard stops at pc = 10: rebuild its value stack by replaying its memory ops on IS
+  let k = trace
+    .iter()
+    .position(|vm| vm.pc == 10)
+    .expect("fib executes pc 10");
+  let mut global_ts = 0;
+  for vm in &trace[..k] {
+    step_RS_WS(vm, &mut IS, &mut global_ts, &IS_sizes, MEMORY_OPS_PER_STEP);
+  }
+  let stack = IS[..trace[k].pre_sp]
+    .iter()
+    .map(|(_, val, _)| *val)
+    .collect();
+
+  // Resuming there traces the rest of the prior execution
+  let wasm_ctx = WASMCtx::new(wasm_args_builder.resume_at(10, stack, vec![]).build());
+  let (resumed_trace, _, _) = wasm_ctx.execution_trace()?;
+  assert_eq!(
+    bincode::serialize(&resumed_trace)?,
+    bincode::serialize(&trace[k..])?
+  );
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![987]);
+  Ok(())
+}
+
+#[test]
+fn test_return_values() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .func_args(vec![String::from("10")])
+    .build();
+
+  let wasm_ctx = WASMCtx::new(wasm_args);
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![55]);
+
+  // Every value of a function returning several is proven, in order. Three values are kept by a
+  // single drop keep step, which needs 12 memory ops.
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/multi_return.wat"))?
+    .invoke("split")
+    .func_args(vec![String::from("100")])
+    .build();
+  let step_size = step_size.set_memory_ops_per_step(12)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![14, 2, 100]);
+  Ok(())
+}
+
+#[test]
+fn test_i32_wrapping_arithmetic() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/i32_wrapping.wat"))?
+    .invoke("main")
+    .build();
+
+  let wasm_ctx = WASMCtx::new(wasm_args);
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+
+  let expected = 65536_i32
+    .wrapping_mul(65537)
+    .wrapping_add(0x7fff_ffff)
+    .wrapping_sub(-2);
+  let output = snark.verify_and_extract(&pp, &U)?;
+  assert_eq!(output.stack_top(), expected as u32 as u64);
+
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/i32_wrapping.wat"))?
+    .invoke("div_rem")
+    .build();
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+
+  let expected = i32::MIN
+    .wrapping_rem(-1)
+    .wrapping_add((u32::MAX / 3) as i32)
+    .wrapping_add((-7 / 2) * (-7 % 2))
+    .wrapping_add((u32::MAX % 10) as i32);
+  let output = snark.verify_and_extract(&pp, &U)?;
+  assert_eq!(output.stack_top(), expected as u32 as u64);
+  Ok(())
+}
+
+#[test]
+fn test_call_indirect() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let wasm_args_builder =
+    WASMArgsBuilder::default().file_path(PathBuf::from("wasm/misc/call_indirect.wat"))?;
+
+  // Each table index selects a different callee of the same signature
+  let cases: [(u32, u64); 3] = [(0, 14), (1, 49), (2, -7_i32 as u32 as u64)];
+  for (index, expected) in cases {
+    let wasm_args = wasm_args_builder
+      .clone()
+      .func_args(vec![index.to_string()])
+      .build();
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+    snark.verify(&pp, &U)?;
+    assert_eq!(U.return_values()?, vec![expected], "main({index})");
+  }
+  Ok(())
+}
+
+#[test]
+fn test_table_get_set() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let wasm_args_builder =
+    WASMArgsBuilder::default().file_path(PathBuf::from("wasm/misc/table_ops.wat"))?;
+
+  // The entry copied over the one at 0 is the callee of the indirect call
+  for (src, expected) in [(0, 11), (1, 9)] {
+    let wasm_args = wasm_args_builder
+      .clone()
+      .func_args(vec![src.to_string()])
+      .build();
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+    snark.verify(&pp, &U)?;
+    assert_eq!(U.return_values()?, vec![expected], "main({src})");
+  }
+  Ok(())
+}
+
+#[test]
+fn test_funcref_global() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/funcref_global.wat"))?
+    .func_args(vec![String::from("21")])
+    .build();
+  let wasm_ctx = WASMCtx::new(args);
+
+  // The reference `ref.func` pushes is stored in the global & read back from it
+  let (execution_trace, _, _) = wasm_ctx.execution_trace()?;
+  let reference = execution_trace
+    .iter()
+    .find(|vm| matches!(vm.instr, Instr::RefFunc(..)))
+    .map(|vm| vm.P)
+    .ok_or_else(|| ZKWASMError::WASMError(String::from("missing ref.func")))?;
+  assert_ne!(reference, 0);
+  let globals_read = execution_trace
+    .iter()
+    .filter(|vm| matches!(vm.instr, Instr::GlobalGet(..)))
+    .map(|vm| vm.Y)
+    .collect::<Vec<_>>();
+  assert_eq!(globals_read, vec![0, reference]);
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![42]);
+  Ok(())
+}
+
+#[test]
+fn test_sign_extension() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let wasm_args_builder =
+    WASMArgsBuilder::default().file_path(PathBuf::from("wasm/misc/extend.wat"))?;
+
+  let cases: [(&str, i64, u64); 6] = [
+    ("extend_i32_s", -1, u64::MAX),
+    ("extend_i32_s", -5, -5_i64 as u64),
+    ("extend_i32_s", i32::MIN as i64, i32::MIN as i64 as u64),
+    ("extend_i32_s", 7, 7),
+    ("extend8_s", 0x1234_5680, -128_i64 as u64),
+    (
+      "wrap_extend16_s",
+      0x7fff_0000_0000_8001,
+      -0x7fff_i32 as u32 as u64,
+    ),
+  ];
+  for (export, arg, expected) in cases {
+    let wasm_args = wasm_args_builder
+      .clone()
+      .invoke(export)
+      .func_args(vec![arg.to_string()])
+      .build();
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+    snark.verify(&pp, &U)?;
+    assert_eq!(U.return_values()?, vec![expected], "{export}({arg})");
+  }
+  Ok(())
+}
+
+#[test]
+fn test_trunc_float_to_int() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/trunc_f2i.wat"))?
+    .invoke("trunc_f2i")
+    .func_args(vec![
+      "2".to_string(),
+      "3.5".to_string(),
+      "2147483647.9".to_string(),
+    ])
+    .build();
+
+  test_wasm_snark_with(WASMCtx::new(wasm_args), step_size)
+}
+
+#[test]
+fn test_const_pool() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/const_pool.wat"))?
+    .invoke("main")
+    .build();
+
+  let wasm_ctx = WASMCtx::new(wasm_args);
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+
+  let output = snark.verify_and_extract(&pp, &U)?;
+  assert_eq!(output.stack_top(), 0x1123_4567_89ab_cdf0);
+  Ok(())
+}
+
 #[test]
 fn test_defi_transaction() {
   init_logger();
-  let step_size = StepSize::new(500).set_memory_step_size(50_000);
+  let step_size = StepSize::new(500)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   // Simulated user and pool balances
   let user_input_balance = "1000"; // User's balance of token A
   let pool_input_reserve = "10000"; // Pool's reserve of token A
@@ -292,7 +1491,10 @@ fn test_defi_transaction() {
 #[test]
 fn test_energy_consumption() {
   init_logger();
-  let step_size = StepSize::new(200).set_memory_step_size(50_000);
+  let step_size = StepSize::new(200)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
 
   let total_produced = "5000"; // Total energy produced by the microgrid in some time frame (e.g., in watt-hours).
   let total_consumed = "4900"; // Total energy consumed by all devices in the microgrid for the same period.
@@ -320,7 +1522,10 @@ fn test_energy_consumption() {
 #[test]
 fn test_toy_rsa() {
   init_logger();
-  let step_size = StepSize::new(1000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1000)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
 
   let p_candidate = "1009"; // A candidate prime number p.
   let q_candidate = "1013"; // A candidate prime number q.
@@ -349,7 +1554,10 @@ fn test_toy_rsa() {
 #[test]
 fn test_financial_protocol() {
   init_logger();
-  let step_size = StepSize::new(1000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1000)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
   //  Moderately healthy loan, moderate staking, moderate interest
   let collateral_amount = "2000";
   let borrowed_amount = "1000";
@@ -376,7 +1584,10 @@ fn test_financial_protocol() {
 #[test]
 fn test_game_logic() {
   init_logger();
-  let step_size = StepSize::new(1000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1000)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
 
   //  Adequate energy and focus, moderate forging
   let player_energy = "5000";
@@ -404,7 +1615,10 @@ fn test_game_logic() {
 #[test]
 fn test_data_provenance() {
   init_logger();
-  let step_size = StepSize::new(1000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1000)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
 
   // Valid environment, bits 0 and 2 set (binary 0b101 = 5 in decimal)
   let product_id = "12345";
@@ -432,7 +1646,10 @@ fn test_data_provenance() {
 #[test]
 fn test_regulatory_compliance() {
   init_logger();
-  let step_size = StepSize::new(1000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1000)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
 
   //  Likely valid compliance flags (0b1011), moderate emissions
   let measured_emissions = "500000";
@@ -460,7 +1677,10 @@ fn test_regulatory_compliance() {
 #[test]
 fn test_smart_contract_audit() {
   init_logger();
-  let step_size = StepSize::new(1000).set_memory_step_size(50_000);
+  let step_size = StepSize::new(1000)
+    .unwrap()
+    .set_memory_step_size(50_000)
+    .unwrap();
 
   let coverage_flags = "2147483647"; // Example with all bits set (full coverage)
   let total_gas_used = "150000"; // Example total gas usage
@@ -483,3 +1703,412 @@ fn test_smart_contract_audit() {
 
   test_wasm_snark_with(wasm_ctx, step_size).unwrap();
 }
+
+#[test]
+fn test_memory_diff() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/memory/store_2.wat"))?
+    .invoke("call")
+    .build();
+  let wasm_ctx = WASMCtx::new(wasm_args);
+
+  // Derive FS from IS the same way the zkVM does
+  let (execution_trace, IS, IS_sizes) = wasm_ctx.execution_trace()?;
+  let mut FS = IS.clone();
+  construct_IS(
+    execution_trace.len(),
+    step_size,
+    false,
+    execution_trace,
+    &mut FS,
+    &mut 0,
+    &IS_sizes,
+  );
+
+  // Only look at linear memory, the stack is also written to
+  let mem_start = IS_sizes.stack_len();
+  let mem_end = mem_start + IS_sizes.mem_len();
+  let diff = memory_diff(&IS, &FS)
+    .into_iter()
+    .filter(|(addr, _, _)| (mem_start..mem_end).contains(addr))
+    .collect::<Vec<_>>();
+  assert_eq!(diff, vec![(mem_start, 0, 1), (mem_start + 8, 0, 2)]);
+  Ok(())
+}
+
+#[test]
+fn test_imported_global() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/imported_global.wat"))?
+    .global("env", "counter", "41")
+    .build();
+  let wasm_ctx = WASMCtx::new(wasm_args);
+
+  // The imported global comes first in the globals region of IS, with its host provided value
+  let (_, IS, IS_sizes) = wasm_ctx.execution_trace()?;
+  let globals_start = IS_sizes.stack_len() + IS_sizes.mem_len();
+  assert_eq!(IS_sizes.globals_len(), 2);
+  assert_eq!(IS[globals_start].1, 41);
+  assert_eq!(IS[globals_start + 1].1, 1);
+
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![42]);
+  Ok(())
+}
+
+#[test]
+fn test_stack_overflow() -> Result<(), ZKWASMError> {
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/deep_recursion.wat"))?
+    .invoke("depth")
+    .func_args(vec![String::from("100")])
+    .build();
+
+  // Fits in the default stack limits
+  let (execution_trace, _, _) = WASMCtx::new(wasm_args.clone()).execution_trace()?;
+  assert!(!execution_trace.is_empty());
+
+  // Overflows a small call stack
+  let stack_limits = wasmi::StackLimits::new(256, 1024, 16).unwrap();
+  let wasm_ctx = WASMCtx::new(wasm_args).with_stack_limits(stack_limits);
+  assert!(matches!(
+    wasm_ctx.execution_trace(),
+    Err(ZKWASMError::StackOverflow)
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_atomics() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/atomics.wat"))?
+    .invoke("main")
+    .func_args(vec![String::from("7"), String::from("300")])
+    .build();
+
+  // 7 + (300 as u8)
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![51]);
+
+  // Atomics which can not be lowered are rejected
+  let result = WASMArgsBuilder::default().file_path(PathBuf::from("wasm/misc/atomic_rmw.wat"));
+  assert!(
+    matches!(result, Err(ZKWASMError::UnsupportedFeature(features)) if features == "threads")
+  );
+  Ok(())
+}
+
+#[test]
+fn test_unsupported_feature() {
+  let path = PathBuf::from("wasm/misc/simd_add.wat");
+  let result = WASMArgsBuilder::default().file_path(path.clone());
+  assert!(matches!(result, Err(ZKWASMError::UnsupportedFeature(features)) if features == "simd"));
+
+  // Programs set from bytecode are rejected before tracing
+  let bytecode = crate::utils::wasm::read_wasm_or_wat(&path).unwrap();
+  let wasm_args = WASMArgsBuilder::default()
+    .bytecode(bytecode)
+    .invoke("main")
+    .func_args(vec![String::from("1"), String::from("2")])
+    .build();
+  let result = WASMCtx::new(wasm_args).execution_trace();
+  assert!(matches!(result, Err(ZKWASMError::UnsupportedFeature(_))));
+
+  // A v128 value is rejected even if no SIMD opcode touches it
+  let result =
+    WASMArgsBuilder::default().file_path(PathBuf::from("wasm/misc/simd_passthrough.wat"));
+  assert!(matches!(result, Err(ZKWASMError::UnsupportedFeature(features)) if features == "simd"));
+}
+
+#[test]
+fn test_unsupported_instruction() {
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/table_copy.wat"))
+    .unwrap()
+    .build();
+  let result = WASMCtx::new(wasm_args).execution_trace();
+  assert!(matches!(
+    result,
+    Err(ZKWASMError::UnsupportedInstruction(Instr::TableCopy(..)))
+  ));
+  assert!(Instr::TableCopy(BCTableIdx::from(0)).try_index_j().is_err());
+
+  // Only the default table is laid out in the zkWASM memory
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/table_get_set.wat"))
+    .unwrap()
+    .build();
+  let result = WASMCtx::new(wasm_args).execution_trace();
+  assert!(matches!(
+    result,
+    Err(ZKWASMError::UnsupportedInstruction(Instr::TableSet(table))) if table.to_u32() == 1
+  ));
+}
+
+#[test]
+fn test_unsupported_opcodes() -> Result<(), ZKWASMError> {
+  // Each opcode is reported once, before executing the program
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/table_copy.wat"))?
+    .build();
+  let unsupported = WASMCtx::new(wasm_args).unsupported_opcodes()?;
+  assert!(matches!(
+    unsupported[..],
+    [Instr::TableCopy(..), Instr::ElemDrop(..)]
+  ));
+
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .build();
+  assert!(WASMCtx::new(wasm_args).unsupported_opcodes()?.is_empty());
+
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/table_get_set.wat"))?
+    .build();
+  let unsupported = WASMCtx::new(wasm_args).unsupported_opcodes()?;
+  assert!(matches!(
+    unsupported[..],
+    [Instr::TableSet(..), Instr::TableGet(..)]
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_instruction_count() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  let mut instruction_counts = Vec::new();
+  for n in ["4", "8"] {
+    let wasm_args = WASMArgsBuilder::default()
+      .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+      .invoke("fib")
+      .func_args(vec![String::from(n)])
+      .build();
+    let wasm_ctx = WASMCtx::new(wasm_args);
+    let (execution_trace, _, _) = wasm_ctx.execution_trace()?;
+
+    // The padding steps are not counted
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+    let output = snark.verify_and_extract(&pp, &U)?;
+    assert_eq!(output.instruction_count(), execution_trace.len() as u64);
+    assert_eq!(U.instruction_count()?, execution_trace.len() as u64);
+    instruction_counts.push(output.instruction_count());
+  }
+  assert_ne!(instruction_counts[0], instruction_counts[1]);
+  Ok(())
+}
+
+#[test]
+fn test_trace_hook() -> Result<(), ZKWASMError> {
+  init_logger();
+  let i64_adds = Arc::new(AtomicUsize::new(0));
+  let hook = {
+    let i64_adds = i64_adds.clone();
+    TraceHook::new(move |_, vm| {
+      if matches!(vm.instr, Instr::I64Add) {
+        i64_adds.fetch_add(1, Ordering::Relaxed);
+      }
+    })
+  };
+
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .func_args(vec![String::from("16")])
+    .build();
+  let wasm_ctx = WASMCtx::new(wasm_args).with_trace_hook(hook);
+  let (execution_trace, _, _) = wasm_ctx.execution_trace()?;
+
+  // fib(16) loops 14 times, adding twice per iteration
+  assert_eq!(i64_adds.load(Ordering::Relaxed), 28);
+  assert_eq!(
+    execution_trace
+      .iter()
+      .filter(|vm| matches!(vm.instr, Instr::I64Add))
+      .count(),
+    28
+  );
+  Ok(())
+}
+
+#[test]
+fn test_start_function() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/start_fn.wat"))?
+    .invoke("main")
+    .func_args(vec![String::from("1")])
+    .build();
+  let wasm_ctx = WASMCtx::new(wasm_args);
+
+  // The store of the start function is traced before the load of the invoked function
+  let (execution_trace, _, _) = wasm_ctx.execution_trace()?;
+  let store = execution_trace
+    .iter()
+    .position(|vm| matches!(vm.instr, Instr::I64Store(..)));
+  let load = execution_trace
+    .iter()
+    .position(|vm| matches!(vm.instr, Instr::I64Load(..)));
+  assert!(store.unwrap() < load.unwrap());
+
+  // The load sees the value the start function initialized the memory with
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+  let output = snark.verify_and_extract(&pp, &U)?;
+  assert_eq!(output.stack_top(), 43);
+  Ok(())
+}
+
+#[test]
+fn test_IS_sizes_from_module() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(1000)?.set_memory_step_size(10_000)?;
+  init_logger();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/bulk-ops.wat"))?
+    .func_args(vec!["200".to_string()])
+    .build();
+  let module = wasmi::Module::new(&wasmi::Engine::default(), &wasm_args.program[..])?;
+  let IS_sizes = ISMemSizes::from_module(&module);
+  let wasm_ctx = WASMCtx::new(wasm_args);
+
+  // The sizes derived from the module match the layout the execution trace commits to
+  let (_, _, traced_IS_sizes) = wasm_ctx.execution_trace()?;
+  assert_eq!(IS_sizes.mem_len(), 8 * 8192);
+  assert_eq!(IS_sizes.mem_len(), traced_IS_sizes.mem_len());
+  assert_eq!(IS_sizes.globals_len(), traced_IS_sizes.globals_len());
+  assert_eq!(IS_sizes.table_len(), traced_IS_sizes.table_len());
+  assert_eq!(IS_sizes.data_len(), traced_IS_sizes.data_len());
+  assert!(IS_sizes.stack_len() >= traced_IS_sizes.stack_len());
+
+  test_wasm_snark_with(wasm_ctx, step_size)
+}
+
+#[test]
+fn test_return_if_nez() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  // The early return is taken for 150 & not taken for 7
+  for (arg, expected) in [("150", 100), ("7", 7)] {
+    let wasm_args = WASMArgsBuilder::default()
+      .file_path(PathBuf::from("wasm/misc/early_return.wat"))?
+      .invoke("main")
+      .func_args(vec![String::from(arg)])
+      .build();
+    let wasm_ctx = WASMCtx::new(wasm_args);
+
+    let (execution_trace, _, _) = wasm_ctx.execution_trace()?;
+    assert!(execution_trace
+      .iter()
+      .any(|vm| matches!(vm.instr, Instr::ReturnIfNez(..))));
+
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+    let output = snark.verify_and_extract(&pp, &U)?;
+    assert_eq!(output.stack_top(), expected);
+  }
+  Ok(())
+}
+
+#[test]
+fn test_br_table_targets() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let br_table_ctx = |arg: &str| -> Result<WASMCtx, ZKWASMError> {
+    let wasm_args = WASMArgsBuilder::default()
+      .file_path(PathBuf::from("wasm/misc/br_table_default.wat"))?
+      .invoke("main")
+      .func_args(vec![String::from(arg)])
+      .build();
+    Ok(WASMCtx::new(wasm_args))
+  };
+
+  // Indices past the last target (& negative ones, as the index is unsigned) take the default
+  for (arg, expected) in [("0", 10), ("1", 20), ("2", 30), ("7", 40), ("-1", 40)] {
+    let wasm_ctx = br_table_ctx(arg)?;
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+    snark.verify(&pp, &U)?;
+    assert_eq!(U.return_values()?, vec![expected]);
+  }
+
+  // A trace branching to another target than the index selects does not prove
+  let (mut execution_trace, IS, IS_sizes) = br_table_ctx("1")?.execution_trace()?;
+  let vm = execution_trace
+    .iter_mut()
+    .find(|vm| matches!(vm.instr, Instr::BrTable(..)))
+    .unwrap();
+  vm.Z += 2;
+  let forged =
+    WasmSNARK::<E, S1, S2>::prove_from_trace(&pp, execution_trace, IS, IS_sizes, &[], step_size)
+      .and_then(|(snark, U)| snark.verify(&pp, &U));
+  assert!(forged.is_err());
+  Ok(())
+}
+
+#[test]
+fn test_call_internal_args() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let call_args_ctx = |arg: &str| -> Result<WASMCtx, ZKWASMError> {
+    let wasm_args = WASMArgsBuilder::default()
+      .file_path(PathBuf::from("wasm/misc/call_args.wat"))?
+      .invoke("main")
+      .func_args(vec![String::from(arg)])
+      .build();
+    Ok(WASMCtx::new(wasm_args))
+  };
+
+  // The callee gets (x + 3, x * 2) & returns their product
+  for (arg, expected) in [("5", 80), ("-4", 8), ("0", 0)] {
+    let wasm_ctx = call_args_ctx(arg)?;
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &wasm_ctx, step_size)?;
+    snark.verify(&pp, &U)?;
+    assert_eq!(U.return_values()?, vec![expected]);
+  }
+
+  let prove_forged = |forge: &dyn Fn(&mut [WitnessVM])| -> Result<(), ZKWASMError> {
+    let (mut execution_trace, IS, IS_sizes) = call_args_ctx("5")?.execution_trace()?;
+    forge(&mut execution_trace);
+    WasmSNARK::<E, S1, S2>::prove_from_trace(&pp, execution_trace, IS, IS_sizes, &[], step_size)
+      .and_then(|(snark, U)| snark.verify(&pp, &U))
+  };
+
+  // A callee reading another argument than the one the caller pushed does not prove
+  assert!(prove_forged(&|trace| {
+    let vm = trace
+      .iter_mut()
+      .skip_while(|vm| !matches!(vm.instr, Instr::CallZeroWrite))
+      .find(|vm| matches!(vm.instr, Instr::LocalGet(..)))
+      .unwrap();
+    vm.P += 1;
+  })
+  .is_err());
+
+  // Nor does a callee whose local does not start at zero
+  assert!(prove_forged(&|trace| {
+    let vm = trace
+      .iter_mut()
+      .find(|vm| matches!(vm.instr, Instr::CallZeroWrite))
+      .unwrap();
+    vm.P = 7;
+  })
+  .is_err());
+  Ok(())
+}
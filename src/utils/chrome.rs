@@ -0,0 +1,243 @@
+//! Chrome tracing output, to profile proving.
+//!
+//! [`ChromeTraceLayer`] records the time spent in every [`tracing`] span as a complete ("X") event
+//! of the [Chrome trace format], which `chrome://tracing` & [Perfetto](https://ui.perfetto.dev)
+//! load. Proving opens a `prove_step` span per folding step of each phase and a `synthesize` span
+//! per opcode synthesized, so the trace shows where proving time goes.
+//!
+//! [Chrome trace format]: https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU
+use std::{
+  cell::Cell,
+  fmt::Debug,
+  fs::File,
+  io::{self, BufWriter},
+  path::PathBuf,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+  },
+  time::Instant,
+};
+
+use serde_json::{json, Map, Value};
+use tracing::{
+  field::{Field, Visit},
+  span::{Attributes, Id, Record},
+  Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, EnvFilter, Layer};
+
+/// Events recorded by a [`ChromeTraceLayer`]
+type Events = Arc<Mutex<Vec<Value>>>;
+
+/// A [`Layer`] recording span durations as Chrome trace events, written out by its
+/// [`ChromeTraceGuard`].
+pub struct ChromeTraceLayer {
+  start: Instant,
+  events: Events,
+}
+
+/// Writes the events of its [`ChromeTraceLayer`] to a file when dropped.
+pub struct ChromeTraceGuard {
+  path: PathBuf,
+  events: Events,
+}
+
+/// Build a [`ChromeTraceLayer`], to compose with other layers, along with the guard writing its
+/// events to `path`.
+pub fn chrome_layer(path: impl Into<PathBuf>) -> (ChromeTraceLayer, ChromeTraceGuard) {
+  let events = Events::default();
+  let layer = ChromeTraceLayer {
+    start: Instant::now(),
+    events: events.clone(),
+  };
+  let guard = ChromeTraceGuard {
+    path: path.into(),
+    events,
+  };
+  (layer, guard)
+}
+
+/// Initializes a global subscriber recording spans to a Chrome trace at `path`. Spans are
+/// filtered by `RUST_LOG`, and all of them are recorded when it is not set.
+///
+/// The trace is written when the returned guard is dropped, so keep it alive until proving is
+/// done.
+pub fn init_chrome_trace(path: impl Into<PathBuf>) -> ChromeTraceGuard {
+  let (layer, guard) = chrome_layer(path);
+  let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("trace"));
+  let _ = tracing_subscriber::registry()
+    .with(filter)
+    .with(layer)
+    .try_init();
+  guard
+}
+
+impl ChromeTraceGuard {
+  /// Write the events recorded so far, as a Chrome trace JSON object
+  pub fn flush(&self) -> io::Result<()> {
+    let events = self.events.lock().unwrap();
+    let trace = json!({ "traceEvents": *events, "displayTimeUnit": "ms" });
+    let writer = BufWriter::new(File::create(&self.path)?);
+    serde_json::to_writer(writer, &trace)?;
+    Ok(())
+  }
+}
+
+impl Drop for ChromeTraceGuard {
+  fn drop(&mut self) {
+    if let Err(err) = self.flush() {
+      tracing::error!("failed to write chrome trace to {:?}: {err}", self.path);
+    }
+  }
+}
+
+/// Name, fields & entry time of a span, stored in its extensions
+struct SpanTiming {
+  name: String,
+  args: Map<String, Value>,
+  entered: Option<Instant>,
+}
+
+/// Records the fields of a span as event args
+struct ArgsVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for ArgsVisitor<'_> {
+  fn record_str(&mut self, field: &Field, value: &str) {
+    self.0.insert(field.name().to_string(), value.into());
+  }
+
+  fn record_u64(&mut self, field: &Field, value: u64) {
+    self.0.insert(field.name().to_string(), value.into());
+  }
+
+  fn record_i64(&mut self, field: &Field, value: i64) {
+    self.0.insert(field.name().to_string(), value.into());
+  }
+
+  fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+    self
+      .0
+      .insert(field.name().to_string(), format!("{value:?}").into());
+  }
+}
+
+/// Name an event after its span & the values of the span fields, e.g. `synthesize I64Add`, so
+/// spans with the same fields add up in the trace viewer.
+fn event_name(span_name: &str, args: &Map<String, Value>) -> String {
+  args
+    .values()
+    .fold(span_name.to_string(), |name, value| match value.as_str() {
+      Some(value) => format!("{name} {value}"),
+      None => format!("{name} {value}"),
+    })
+}
+
+/// Id of the current thread in the trace, numbered from 1 in the order threads record events
+fn thread_id() -> u64 {
+  static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+  thread_local! {
+    static ID: Cell<u64> = const { Cell::new(0) };
+  }
+  ID.with(|id| {
+    if id.get() == 0 {
+      id.set(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    }
+    id.get()
+  })
+}
+
+impl<S> Layer<S> for ChromeTraceLayer
+where
+  S: Subscriber + for<'a> LookupSpan<'a>,
+{
+  fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+    let Some(span) = ctx.span(id) else { return };
+    let mut args = Map::new();
+    attrs.record(&mut ArgsVisitor(&mut args));
+    span.extensions_mut().insert(SpanTiming {
+      name: span.name().to_string(),
+      args,
+      entered: None,
+    });
+  }
+
+  fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+    let Some(span) = ctx.span(id) else { return };
+    if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+      values.record(&mut ArgsVisitor(&mut timing.args));
+    }
+  }
+
+  fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+    let Some(span) = ctx.span(id) else { return };
+    if let Some(timing) = span.extensions_mut().get_mut::<SpanTiming>() {
+      timing.entered = Some(Instant::now());
+    }
+  }
+
+  fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+    let exited = Instant::now();
+    let Some(span) = ctx.span(id) else { return };
+    let mut extensions = span.extensions_mut();
+    let Some(timing) = extensions.get_mut::<SpanTiming>() else {
+      return;
+    };
+    let Some(entered) = timing.entered.take() else {
+      return;
+    };
+
+    // Timestamps & durations are in microseconds
+    let event = json!({
+      "name": event_name(&timing.name, &timing.args),
+      "cat": span.metadata().target(),
+      "ph": "X",
+      "ts": entered.duration_since(self.start).as_secs_f64() * 1e6,
+      "dur": exited.duration_since(entered).as_secs_f64() * 1e6,
+      "pid": 1,
+      "tid": thread_id(),
+      "args": timing.args,
+    });
+    self.events.lock().unwrap().push(event);
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_chrome_trace() {
+    let path = std::env::temp_dir().join("zk_engine_test_chrome_trace.json");
+    let (layer, guard) = chrome_layer(&path);
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+      let _step = tracing::info_span!("prove_step", phase = "Execution").entered();
+      for opcode in ["I64Add", "I64Mul"] {
+        let _synthesize = tracing::trace_span!("synthesize", opcode).entered();
+      }
+    });
+    guard.flush().unwrap();
+
+    let trace: Value = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+    let names = trace["traceEvents"]
+      .as_array()
+      .unwrap()
+      .iter()
+      .map(|event| event["name"].as_str().unwrap())
+      .collect::<Vec<_>>();
+
+    // Spans are recorded as they exit, the outer step last
+    assert_eq!(
+      names,
+      [
+        "synthesize I64Add",
+        "synthesize I64Mul",
+        "prove_step Execution"
+      ]
+    );
+    let step = &trace["traceEvents"][2];
+    assert_eq!(step["ph"], "X");
+    assert_eq!(step["args"]["phase"], "Execution");
+    assert!(step["dur"].as_f64().unwrap() >= trace["traceEvents"][0]["dur"].as_f64().unwrap());
+  }
+}
@@ -0,0 +1,191 @@
+//! Opcode coverage of the test suite.
+//!
+//! Every execution trace built while testing is recorded here, so we can see which opcodes (J
+//! indices) of the switchboard are exercised by the tests, and which have a handler but no test.
+use std::{collections::BTreeSet, fmt::Write, path::PathBuf, sync::Mutex};
+
+use wasmi::{
+  AddressOffset, BCDataSegmentIdx, BCFuncIdx, BCGlobalIdx, BCTableIdx, BranchOffset,
+  BranchTableTargets, ConstRef, DropKeep, Instruction as Instr, SignatureIdx, WitnessVM,
+};
+
+use crate::wasm_ctx::{WASMArgsBuilder, WASMCtx, ZKWASMCtx};
+
+/// J indices seen in the execution traces built so far
+static EXERCISED: Mutex<BTreeSet<u64>> = Mutex::new(BTreeSet::new());
+
+/// Record the J indices of an execution trace
+pub(crate) fn record(execution_trace: &[WitnessVM]) {
+  let mut exercised = EXERCISED.lock().unwrap();
+  exercised.extend(execution_trace.iter().map(|vm| vm.instr.index_j()));
+}
+
+/// Get the J indices seen in the execution traces built so far
+pub(crate) fn exercised() -> BTreeSet<u64> {
+  EXERCISED.lock().unwrap().clone()
+}
+
+/// An opcode for each J index the switchboard has a handler for, named after its handler
+fn supported() -> Vec<(&'static str, Instr)> {
+  let offset = BranchOffset::uninit();
+  let address = AddressOffset::from(0);
+  vec![
+    ("unreachable", Instr::Unreachable),
+    ("const", Instr::I64Const32(0)),
+    ("const_ref", Instr::ConstRef(ConstRef::from(0))),
+    ("local.get", Instr::local_get(0).unwrap()),
+    ("local.set", Instr::local_set(0).unwrap()),
+    ("local.tee", Instr::local_tee(0).unwrap()),
+    ("br", Instr::Br(offset)),
+    ("br_if_eqz", Instr::BrIfEqz(offset)),
+    ("br_if_nez", Instr::BrIfNez(offset)),
+    ("br_adjust", Instr::BrAdjust(offset)),
+    (
+      "br_table",
+      Instr::BrTable(BranchTableTargets::try_from(0).unwrap()),
+    ),
+    ("drop_keep", Instr::DropKeep),
+    ("ret", Instr::Return(DropKeep::new(0, 0).unwrap())),
+    (
+      "return_if_nez",
+      Instr::ReturnIfNez(DropKeep::new(0, 0).unwrap()),
+    ),
+    ("call", Instr::Call(BCFuncIdx::from(0))),
+    ("call_indirect", Instr::CallIndirect(SignatureIdx::from(0))),
+    ("call_internal_step", Instr::CallZeroWrite),
+    ("host_call_step", Instr::HostCallStep),
+    ("host_call_stack_step", Instr::HostCallStackStep),
+    ("select", Instr::Select),
+    ("global.get", Instr::GlobalGet(BCGlobalIdx::from(0))),
+    ("global.set", Instr::GlobalSet(BCGlobalIdx::from(0))),
+    ("store", Instr::I64Store(address)),
+    ("load", Instr::I64Load(address)),
+    ("memory.size", Instr::MemorySize),
+    ("memory.grow", Instr::MemoryGrow),
+    ("memory.fill", Instr::MemoryFill),
+    ("memory_fill_step", Instr::MemoryFillStep),
+    ("memory.copy", Instr::MemoryCopy),
+    ("memory_copy_step", Instr::MemoryCopyStep),
+    ("memory.init", Instr::MemoryInit(BCDataSegmentIdx::from(0))),
+    ("memory_init_step", Instr::MemoryInitStep),
+    ("data.drop", Instr::DataDrop(BCDataSegmentIdx::from(0))),
+    ("table.get", Instr::TableGet(BCTableIdx::from(0))),
+    ("table.set", Instr::TableSet(BCTableIdx::from(0))),
+    ("ref.func", Instr::RefFunc(BCFuncIdx::from(0))),
+    ("i32.add", Instr::I32Add),
+    ("i32.sub", Instr::I32Sub),
+    ("i32.mul", Instr::I32Mul),
+    ("i32.div_rem_u", Instr::I32DivU),
+    ("i32.div_rem_s", Instr::I32DivS),
+    ("i32.bitops", Instr::I32And),
+    ("i32.unary_ops", Instr::I32Popcnt),
+    ("i32.shift_rotate", Instr::I32Shl),
+    ("i32.lt_ge_s", Instr::I32LtS),
+    ("i32.le_gt_s", Instr::I32GtS),
+    ("i64.add", Instr::I64Add),
+    ("i64.sub", Instr::I64Sub),
+    ("i64.mul", Instr::I64Mul),
+    ("i64.div_rem_u", Instr::I64DivU),
+    ("i64.div_rem_s", Instr::I64DivS),
+    ("i64.bitops", Instr::I64And),
+    ("i64.unary_ops", Instr::I64Popcnt),
+    ("i64.shift_rotate", Instr::I64Shl),
+    ("i64.lt_ge_s", Instr::I64LtS),
+    ("i64.le_gt_s", Instr::I64GtS),
+    ("eqz", Instr::I64Eqz),
+    ("eq", Instr::I64Eq),
+    ("ne", Instr::I64Ne),
+    ("trunc_sat", Instr::I32TruncSatF32S),
+    ("trunc_sat", Instr::I32TruncSatF32U),
+    ("trunc_sat", Instr::I32TruncSatF64S),
+    ("trunc_sat", Instr::I32TruncSatF64U),
+    ("trunc_sat", Instr::I64TruncSatF32S),
+    ("trunc_sat", Instr::I64TruncSatF32U),
+    ("trunc_sat", Instr::I64TruncSatF64S),
+    ("trunc_sat", Instr::I64TruncSatF64U),
+    ("trunc_float_to_int", Instr::I32TruncF32S),
+    ("trunc_float_to_int", Instr::I32TruncF32U),
+    ("trunc_float_to_int", Instr::I32TruncF64S),
+    ("trunc_float_to_int", Instr::I32TruncF64U),
+    ("trunc_float_to_int", Instr::I64TruncF32S),
+    ("trunc_float_to_int", Instr::I64TruncF32U),
+    ("trunc_float_to_int", Instr::I64TruncF64S),
+    ("trunc_float_to_int", Instr::I64TruncF64U),
+    ("i64.extend_i32", Instr::I64ExtendI32S),
+    ("i64.extend_i32", Instr::I64ExtendI32U),
+    ("conversions", Instr::I32WrapI64),
+    ("conversions", Instr::I32Extend8S),
+    ("conversions", Instr::I32Extend16S),
+    ("conversions", Instr::I64Extend8S),
+    ("conversions", Instr::I64Extend16S),
+    ("conversions", Instr::I64Extend32S),
+    ("unary", Instr::F64Neg),
+    ("binary", Instr::F64Min),
+    ("f32.add", Instr::F32Add),
+    ("f32.sub", Instr::F32Sub),
+    ("f32.mul", Instr::F32Mul),
+    ("f32.div", Instr::F32Div),
+    ("f64.add", Instr::F64Add),
+    ("f64.sub", Instr::F64Sub),
+    ("f64.mul", Instr::F64Mul),
+    ("f64.div", Instr::F64Div),
+  ]
+}
+
+/// Format a table of the supported J indices and whether they are exercised
+fn report(exercised: &BTreeSet<u64>) -> String {
+  let mut table = format!("{:<4} {:<22} exercised\n", "J", "handler");
+  for (name, instr) in supported() {
+    let J = instr.index_j();
+    let mark = if exercised.contains(&J) { "x" } else { "" };
+    writeln!(table, "{J:<4} {name:<22} {mark}").unwrap();
+  }
+  table
+}
+
+#[test]
+fn test_opcode_coverage() {
+  // The programs proven by the test suite, with the arguments they are proven with
+  let programs = [
+    ("wasm/int_opcodes.wat", "main", vec![]),
+    ("wasm/complete_int_opcodes.wat", "main", vec![]),
+    ("wasm/nebula/bit_check.wat", "bit_check", vec!["255", "255"]),
+    ("wasm/nebula/eq_func.wat", "eq_func", vec!["255", "255"]),
+    (
+      "wasm/nebula/kth_factor.wat",
+      "kth_factor",
+      vec!["250", "15"],
+    ),
+    ("wasm/misc/fib.wat", "fib", vec!["16"]),
+    ("wasm/misc/bulk-ops.wat", "main", vec!["200"]),
+    ("wasm/misc/const_pool.wat", "main", vec![]),
+    ("wasm/misc/i32_wrapping.wat", "main", vec![]),
+    ("wasm/misc/early_return.wat", "main", vec!["150"]),
+    ("wasm/misc/memory_init.wat", "main", vec![]),
+    ("wasm/misc/funcref_global.wat", "main", vec!["21"]),
+    ("wasm/memory/store_3.wat", "call", vec![]),
+  ];
+  for (path, invoke, args) in programs {
+    let wasm_args = WASMArgsBuilder::default()
+      .file_path(PathBuf::from(path))
+      .unwrap()
+      .invoke(invoke)
+      .func_args(args.into_iter().map(String::from).collect())
+      .build();
+    WASMCtx::new(wasm_args).execution_trace().unwrap();
+  }
+
+  let exercised = exercised();
+  println!("{}", report(&exercised));
+
+  // Every integer arithmetic handler must be exercised
+  let uncovered = supported()
+    .into_iter()
+    .filter(|(name, _)| {
+      name.starts_with("i32.") || name.starts_with("i64.") || ["eqz", "eq", "ne"].contains(name)
+    })
+    .filter(|(_, instr)| !exercised.contains(&instr.index_j()))
+    .map(|(name, _)| name)
+    .collect::<Vec<_>>();
+  assert!(uncovered.is_empty(), "uncovered opcodes: {uncovered:?}");
+}
@@ -1,7 +1,7 @@
 //! Display utilities for pretty-printing types and values.
 #![allow(dead_code)]
 use std::fmt::{self, Display};
-use wasmi::{core::ValueType, FuncType, Value};
+use wasmi::{core::ValueType, FuncType, Value, WitnessVM};
 
 /// [`Display`]-wrapper type for [`ValueType`].
 pub struct DisplayValueType<'a>(&'a ValueType);
@@ -143,3 +143,105 @@ where
     Ok(())
   }
 }
+
+/// [`Display`]-wrapper type around [`WitnessVM`], rendering it on one line as
+/// `pc | instr | sp:pre->post | I X Y Z P Q`.
+pub struct DisplayWitnessVM<'a> {
+  vm: &'a WitnessVM,
+  /// Stack pointer after execution, i.e. the stack pointer before the next step
+  post_sp: Option<usize>,
+}
+
+impl<'a> DisplayWitnessVM<'a> {
+  /// Creates a [`DisplayWitnessVM`] for the given [`WitnessVM`] that also shows its post-execution
+  /// stack pointer.
+  pub fn new(vm: &'a WitnessVM, post_sp: usize) -> Self {
+    Self {
+      vm,
+      post_sp: Some(post_sp),
+    }
+  }
+}
+
+impl<'a> From<&'a WitnessVM> for DisplayWitnessVM<'a> {
+  fn from(vm: &'a WitnessVM) -> Self {
+    Self { vm, post_sp: None }
+  }
+}
+
+impl Display for DisplayWitnessVM<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let vm = self.vm;
+    let instr = format!("{:?}", vm.instr);
+    let post_sp = self
+      .post_sp
+      .map_or_else(|| String::from("?"), |sp| sp.to_string());
+    write!(
+      f,
+      "{:>6} | {instr:<32} | sp:{:>5}->{post_sp:<5} | I={} X={} Y={} Z={} P={} Q={}",
+      vm.pc, vm.pre_sp, vm.I, vm.X, vm.Y, vm.Z, vm.P, vm.Q
+    )
+  }
+}
+
+/// [`Display`]-wrapper type for an execution trace, rendering it as a numbered table with one
+/// [`DisplayWitnessVM`] per row.
+pub struct DisplayTrace<'a>(&'a [WitnessVM]);
+
+impl<'a> From<&'a [WitnessVM]> for DisplayTrace<'a> {
+  fn from(execution_trace: &'a [WitnessVM]) -> Self {
+    Self(execution_trace)
+  }
+}
+
+impl Display for DisplayTrace<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    writeln!(
+      f,
+      "{:>6} | {:>6} | {:<32} | {:<15} | values",
+      "step", "pc", "instr", "sp"
+    )?;
+    for (step, vm) in self.0.iter().enumerate() {
+      let vm = match self.0.get(step + 1) {
+        Some(next) => DisplayWitnessVM::new(vm, next.pre_sp),
+        None => DisplayWitnessVM::from(vm),
+      };
+      writeln!(f, "{step:>6} | {vm}")?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{DisplayTrace, DisplayWitnessVM};
+  use wasmi::{Instruction, WitnessVM};
+
+  #[test]
+  fn test_display_witness_vm() {
+    let vm = WitnessVM {
+      pre_sp: 3,
+      pc: 12,
+      instr: Instruction::I64Add,
+      J: Instruction::I64Add.index_j(),
+      X: 1,
+      Y: 2,
+      Z: 3,
+      ..Default::default()
+    };
+    assert_eq!(
+      DisplayWitnessVM::new(&vm, 2).to_string(),
+      "    12 | I64Add                           | sp:    3->2     | I=0 X=1 Y=2 Z=3 P=0 Q=0"
+    );
+
+    let trace = [vm.clone(), vm];
+    let table = DisplayTrace::from(&trace[..]).to_string();
+    let rows = table.lines().collect::<Vec<_>>();
+    assert_eq!(rows.len(), 3);
+    assert!(rows[1].starts_with("     0 |     12 | I64Add"));
+    assert!(rows[1].contains("sp:    3->3 "));
+    assert!(rows[2].contains("sp:    3->?"));
+    // Columns line up with the header
+    assert_eq!(rows[0].find("| values"), rows[1].find("| I="));
+  }
+}
@@ -0,0 +1,219 @@
+//! Differential fuzzing of the zkVM.
+//!
+//! Random integer-only WASM modules are run in stock wasmi and proven with the traced executor. The
+//! proof must verify and commit to the result stock wasmi computes, so any opcode the tracer or the
+//! switchboard handles differently from wasmi shows up as a failing module.
+use nova::{
+  provider::{ipa_pc, Bn256EngineIPA},
+  spartan,
+  traits::Dual,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+  error::ZKWASMError,
+  utils::logging::init_logger,
+  wasm_ctx::{WASMArgsBuilder, WASMCtx},
+  wasm_snark::{StepSize, WasmSNARK},
+};
+
+/// Curve Cycle to prove/verify on
+type E = Bn256EngineIPA;
+type EE1 = ipa_pc::EvaluationEngine<E>;
+type EE2 = ipa_pc::EvaluationEngine<Dual<E>>;
+type S1 = spartan::batched::BatchedRelaxedR1CSSNARK<E, EE1>;
+type S2 = spartan::snark::RelaxedR1CSSNARK<Dual<E>, EE2>;
+
+/// Number of random modules proven by [`test_differential_fuzz`]
+const CASES: usize = 16;
+
+/// Maximum nesting depth of the expression computing the result of a module
+const MAX_DEPTH: usize = 5;
+
+/// Binary ops of both integer widths
+const BINARY: [&str; 11] = [
+  "add", "sub", "mul", "and", "or", "xor", "shl", "shr_s", "shr_u", "rotl", "rotr",
+];
+
+/// Division ops of both integer widths, whose divisor is made non-zero & positive so they can not
+/// trap
+const DIVISION: [&str; 4] = ["div_s", "div_u", "rem_s", "rem_u"];
+
+/// Unary ops of both integer widths
+const UNARY: [&str; 3] = ["clz", "ctz", "popcnt"];
+
+/// Comparisons of both integer widths, which produce an i32
+const COMPARISON: [&str; 10] = [
+  "eq", "ne", "lt_s", "lt_u", "gt_s", "gt_u", "le_s", "le_u", "ge_s", "ge_u",
+];
+
+/// Integer value types of the generated modules
+#[derive(Clone, Copy)]
+enum Ty {
+  I32,
+  I64,
+}
+
+impl Ty {
+  fn random(rng: &mut StdRng) -> Self {
+    if rng.gen() {
+      Self::I32
+    } else {
+      Self::I64
+    }
+  }
+
+  fn name(self) -> &'static str {
+    match self {
+      Self::I32 => "i32",
+      Self::I64 => "i64",
+    }
+  }
+
+  /// The parameter & the local of the function of this type
+  fn locals(self) -> [&'static str; 2] {
+    match self {
+      Self::I32 => ["$b", "$d"],
+      Self::I64 => ["$a", "$c"],
+    }
+  }
+
+  fn random_const(self, rng: &mut StdRng) -> String {
+    // Mostly small values, as they exercise the edge cases of shifts & comparisons more often
+    let value: i64 = match rng.gen_range(0..3) {
+      0 => rng.gen_range(-8..8),
+      _ => rng.gen(),
+    };
+    match self {
+      Self::I32 => format!("(i32.const {})", value as i32),
+      Self::I64 => format!("(i64.const {value})"),
+    }
+  }
+}
+
+/// Generate a random folded WAT expression of type `ty`
+fn random_expr(rng: &mut StdRng, ty: Ty, depth: usize) -> String {
+  let t = ty.name();
+  if depth == 0 || rng.gen_ratio(1, 4) {
+    return match rng.gen_range(0..3) {
+      0 => ty.random_const(rng),
+      _ => format!("(local.get {})", ty.locals().choose(rng).unwrap()),
+    };
+  }
+
+  let depth = depth - 1;
+  match rng.gen_range(0..8) {
+    0 | 1 => format!(
+      "({t}.{} {} {})",
+      BINARY.choose(rng).unwrap(),
+      random_expr(rng, ty, depth),
+      random_expr(rng, ty, depth)
+    ),
+    2 => format!(
+      "({t}.{} {} ({t}.or ({t}.shr_u {} ({t}.const 1)) ({t}.const 1)))",
+      DIVISION.choose(rng).unwrap(),
+      random_expr(rng, ty, depth),
+      random_expr(rng, ty, depth)
+    ),
+    3 => format!(
+      "({t}.{} {})",
+      UNARY.choose(rng).unwrap(),
+      random_expr(rng, ty, depth)
+    ),
+    4 => {
+      let operand = Ty::random(rng);
+      match (ty, rng.gen_ratio(1, 4)) {
+        (Ty::I32, true) => format!(
+          "({}.eqz {})",
+          operand.name(),
+          random_expr(rng, operand, depth)
+        ),
+        (Ty::I32, false) => format!(
+          "({}.{} {} {})",
+          operand.name(),
+          COMPARISON.choose(rng).unwrap(),
+          random_expr(rng, operand, depth),
+          random_expr(rng, operand, depth)
+        ),
+        // Extend the i32 a comparison produces
+        (Ty::I64, _) => format!(
+          "(i64.extend_i32_u ({}.eqz {}))",
+          operand.name(),
+          random_expr(rng, operand, depth)
+        ),
+      }
+    }
+    5 => match ty {
+      Ty::I32 => format!("(i32.wrap_i64 {})", random_expr(rng, Ty::I64, depth)),
+      Ty::I64 => format!(
+        "(i64.extend_i32_{} {})",
+        ["s", "u"].choose(rng).unwrap(),
+        random_expr(rng, Ty::I32, depth)
+      ),
+    },
+    6 => format!(
+      "(select {} {} {})",
+      random_expr(rng, ty, depth),
+      random_expr(rng, ty, depth),
+      random_expr(rng, Ty::I32, depth)
+    ),
+    _ => format!(
+      "(local.tee {} {})",
+      ty.locals()[1],
+      random_expr(rng, ty, depth)
+    ),
+  }
+}
+
+/// Generate a random module exporting `main`, which computes an i64 from its two parameters
+fn random_module(rng: &mut StdRng) -> String {
+  format!(
+    r#"(module
+  (func (export "main") (param $a i64) (param $b i32) (result i64)
+    (local $c i64) (local $d i32)
+    {}))"#,
+    random_expr(rng, Ty::I64, MAX_DEPTH)
+  )
+}
+
+/// Run `main` of `program` in stock wasmi, without tracing
+fn reference_result(program: &[u8], a: i64, b: i32) -> Result<i64, ZKWASMError> {
+  let engine = wasmi::Engine::default();
+  let module = wasmi::Module::new(&engine, program)?;
+  let mut store = wasmi::Store::new(&engine, ());
+  let instance = wasmi::Linker::<()>::new(&engine)
+    .instantiate(&mut store, &module)?
+    .start(&mut store)?;
+  let main = instance.get_typed_func::<(i64, i32), i64>(&store, "main")?;
+  Ok(main.call(&mut store, (a, b))?)
+}
+
+#[test]
+fn test_differential_fuzz() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  // A fixed seed keeps the corpus reproducible
+  let mut rng = StdRng::seed_from_u64(0);
+  for case in 0..CASES {
+    let wat = random_module(&mut rng);
+    let (a, b) = (rng.gen_range(-1000..1000_i64), rng.gen::<i32>());
+    let program = wat::parse_str(&wat).map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+    let expected = reference_result(&program, a, b)?;
+
+    let wasm_args = WASMArgsBuilder::default()
+      .bytecode(program)
+      .invoke("main")
+      .func_args(vec![a.to_string(), b.to_string()])
+      .build();
+    let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+    snark.verify(&pp, &U)?;
+    assert_eq!(
+      U.return_values()?,
+      vec![expected as u64],
+      "case {case} with args ({a}, {b}) diverges from wasmi:\n{wat}"
+    );
+  }
+  Ok(())
+}
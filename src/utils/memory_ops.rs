@@ -0,0 +1,120 @@
+//! Self-check of the memory ops the zkVM derives from an execution trace.
+//!
+//! The RS & WS of each step are derived again with [`step_RS_WS`], then replayed against the
+//! memory starting from IS: every read must see the last value written to its address, and the
+//! memory after the last op must be FS. The grand-product identity the MCC proves is checked
+//! natively too, so a regression in the memory-op lowering shows up without proving.
+use ff::Field;
+use nova::{provider::Bn256EngineIPA, traits::Engine};
+use wasmi::WitnessVM;
+
+use crate::{
+  wasm_ctx::ISMemSizes,
+  wasm_snark::{mcc::multiset_ops::step_RS_WS, MEMORY_OPS_PER_STEP},
+};
+
+type F = <Bn256EngineIPA as Engine>::Scalar;
+
+/// Derive the RS & WS of `execution_trace` from the initial memory `IS` as the prover does, and
+/// check them with [`check_memory_ops`]
+pub(crate) fn validate_memory_ops(
+  execution_trace: &[WitnessVM],
+  IS: &[(usize, u64, u64)],
+  IS_sizes: &ISMemSizes,
+) -> Result<(), String> {
+  let mut FS = IS.to_vec();
+  let mut global_ts = IS.iter().map(|(_, _, ts)| *ts).max().unwrap_or_default();
+  let (RS, WS): (Vec<_>, Vec<_>) = execution_trace
+    .iter()
+    .map(|vm| step_RS_WS(vm, &mut FS, &mut global_ts, IS_sizes, MEMORY_OPS_PER_STEP))
+    .unzip();
+  check_memory_ops(IS, &RS.concat(), &WS.concat(), &FS)
+}
+
+/// Check `RS` & `WS` are the memory ops of an execution taking the memory from `IS` to `FS`
+pub(crate) fn check_memory_ops(
+  IS: &[(usize, u64, u64)],
+  RS: &[(usize, u64, u64)],
+  WS: &[(usize, u64, u64)],
+  FS: &[(usize, u64, u64)],
+) -> Result<(), String> {
+  if RS.len() != WS.len() {
+    return Err(format!("{} reads but {} writes", RS.len(), WS.len()));
+  }
+
+  // h_IS * h_WS == h_RS * h_FS, for random challenges
+  let mut rng = rand::thread_rng();
+  let (gamma, alpha) = (F::random(&mut rng), F::random(&mut rng));
+  let h = |set: &[(usize, u64, u64)]| {
+    set.iter().fold(F::ONE, |acc, (addr, val, ts)| {
+      acc * (F::from(*addr as u64) + F::from(*val) * gamma + F::from(*ts) * gamma.square() - alpha)
+    })
+  };
+  if h(IS) * h(WS) != h(RS) * h(FS) {
+    return Err("h_IS * h_WS != h_RS * h_FS".to_string());
+  }
+
+  // Replay the ops, to point at the first one that breaks the memory
+  let mut memory = IS.to_vec();
+  for (i, (read, write)) in RS.iter().zip(WS).enumerate() {
+    let addr = read.0;
+    let current = memory
+      .get(addr)
+      .ok_or_else(|| format!("op {i} reads address {addr} out of memory"))?;
+    if current != read {
+      return Err(format!(
+        "op {i} reads {read:?} but memory holds {current:?}"
+      ));
+    }
+    if write.0 != addr || write.2 <= read.2 {
+      return Err(format!("op {i} writes {write:?} after reading {read:?}"));
+    }
+    memory[addr] = *write;
+  }
+  if memory != FS {
+    return Err("FS is not the memory after the last op".to_string());
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::path::PathBuf;
+
+  use super::check_memory_ops;
+  use crate::{
+    error::ZKWASMError,
+    utils::validate_memory_ops,
+    wasm_ctx::{WASMArgsBuilder, WASMCtx, ZKWASMCtx},
+    wasm_snark::{mcc::multiset_ops::step_RS_WS, MEMORY_OPS_PER_STEP},
+  };
+
+  #[test]
+  fn test_validate_memory_ops() -> Result<(), ZKWASMError> {
+    let wasm_args = WASMArgsBuilder::default()
+      .file_path(PathBuf::from("wasm/memory/load_store.wat"))?
+      .invoke("main")
+      .build();
+    let (execution_trace, IS, IS_sizes) = WASMCtx::new(wasm_args).execution_trace()?;
+    assert_eq!(
+      validate_memory_ops(&execution_trace, &IS, &IS_sizes),
+      Ok(())
+    );
+
+    // Recording the 42 the program writes as another value breaks the memory
+    let mut FS = IS.clone();
+    let mut global_ts = 0;
+    let (RS, mut WS): (Vec<_>, Vec<_>) = execution_trace
+      .iter()
+      .flat_map(|vm| {
+        let (rs, ws) = step_RS_WS(vm, &mut FS, &mut global_ts, &IS_sizes, MEMORY_OPS_PER_STEP);
+        rs.into_iter().zip(ws)
+      })
+      .unzip();
+    check_memory_ops(&IS, &RS, &WS, &FS).unwrap();
+    let stored = WS.iter_mut().find(|(_, val, _)| *val == 42).unwrap();
+    stored.1 = 43;
+    assert!(check_memory_ops(&IS, &RS, &WS, &FS).is_err());
+    Ok(())
+  }
+}
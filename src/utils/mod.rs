@@ -1,9 +1,43 @@
 //! Utility code
 
+use wasmi::WitnessVM;
+
+#[cfg(feature = "chrome-trace")]
+pub mod chrome;
+#[cfg(test)]
+pub(crate) mod coverage;
 mod display;
+#[cfg(test)]
+mod fuzz;
+#[cfg(test)]
+mod memory_ops;
 
 pub mod logging;
 #[cfg(test)]
 pub mod macros;
 pub mod tracing;
 pub(crate) mod wasm;
+
+#[cfg(test)]
+pub(crate) use memory_ops::validate_memory_ops;
+
+/// Print an execution trace as a numbered table, one step per row, to eyeball where it goes wrong
+pub fn print_trace(execution_trace: &[WitnessVM]) {
+  println!("{}", display::DisplayTrace::from(execution_trace));
+}
+
+/// Get the net memory changes between the initial (IS) and final (FS) memory of the zkVM, as
+/// `(addr, old_val, new_val)` for each address whose value differs.
+///
+/// # Note
+///
+/// Addresses are those of the memory consistency check, i.e. IS & FS span the stack, the linear
+/// memory (one address per 8-byte word) and the globals. Both are expected to list the same
+/// addresses in the same order, as the zkVM derives FS from IS.
+pub fn memory_diff(is: &[(usize, u64, u64)], fs: &[(usize, u64, u64)]) -> Vec<(usize, u64, u64)> {
+  is.iter()
+    .zip(fs)
+    .filter(|((_, old_val, _), (_, new_val, _))| old_val != new_val)
+    .map(|((addr, old_val, _), (_, new_val, _))| (*addr, *old_val, *new_val))
+    .collect()
+}
@@ -1,11 +1,15 @@
 //! Utility functions for WASM interpretation
 use super::display::DisplayValueType;
 use anyhow::{anyhow, bail, Error};
-use std::{ffi::OsStr, fs, path::Path};
+use std::{collections::BTreeSet, ffi::OsStr, fs, path::Path};
 use wasmi::{
   core::{ValueType, F32, F64},
   FuncType, Value,
 };
+use wasmparser::{
+  for_each_operator, BinaryReader, FunctionBody, MemArg, MemoryType, Operator, Parser, Payload,
+  Type, TypeRef, ValType, VisitOperator,
+};
 
 /// Returns the contents of the given `.wasm` or `.wat` file.
 ///
@@ -44,41 +48,73 @@ pub fn decode_func_args(ty: &FuncType, args: &[String]) -> Result<Box<[Value]>,
     .zip(args)
     .enumerate()
     .map(|(n, (param_type, arg))| {
-      macro_rules! make_err {
-        () => {
-          |_| {
-            anyhow!(
-              "failed to parse function argument \
-                          {arg} at index {n} as {}",
-              DisplayValueType::from(param_type)
-            )
-          }
-        };
-      }
-      match param_type {
-        ValueType::I32 => arg.parse::<i32>().map(Value::from).map_err(make_err!()),
-        ValueType::I64 => arg.parse::<i64>().map(Value::from).map_err(make_err!()),
-        ValueType::F32 => arg
-          .parse::<f32>()
-          .map(F32::from)
-          .map(Value::from)
-          .map_err(make_err!()),
-        ValueType::F64 => arg
-          .parse::<f64>()
-          .map(F64::from)
-          .map(Value::from)
-          .map_err(make_err!()),
-        ValueType::FuncRef => {
-          bail!("the zk_engine CLI cannot take arguments of type funcref")
-        }
-        ValueType::ExternRef => {
-          bail!("the zk_engine CLI cannot take arguments of type externref")
-        }
-      }
+      decode_value(param_type, arg, || {
+        format!("function argument {arg} at index {n}")
+      })
     })
     .collect::<Result<Box<[_]>, _>>()
 }
 
+/// Decode the host provided initial value `arg` of the global `name` imported from `module`, for
+/// the [`ValueType`] the module declares the import with.
+///
+/// # Errors
+///
+/// - If `arg` is not a valid value of type `ty`.
+/// - If unsupported [`ExternRef`] or [`FuncRef`] types are encountered.
+///
+/// [`FuncRef`]: wasmi::FuncRef
+/// [`ExternRef`]: wasmi::ExternRef
+pub fn decode_global_init(
+  ty: ValueType,
+  module: &str,
+  name: &str,
+  arg: &str,
+) -> Result<Value, Error> {
+  decode_value(&ty, arg, || {
+    format!("initial value {arg} of global {module}.{name}")
+  })
+}
+
+/// Decode `arg` as a [`Value`] of type `param_type`, `describe` describes `arg` in errors
+fn decode_value(
+  param_type: &ValueType,
+  arg: &str,
+  describe: impl Fn() -> String,
+) -> Result<Value, Error> {
+  macro_rules! make_err {
+    () => {
+      |_| {
+        anyhow!(
+          "failed to parse {} as {}",
+          describe(),
+          DisplayValueType::from(param_type)
+        )
+      }
+    };
+  }
+  match param_type {
+    ValueType::I32 => arg.parse::<i32>().map(Value::from).map_err(make_err!()),
+    ValueType::I64 => arg.parse::<i64>().map(Value::from).map_err(make_err!()),
+    ValueType::F32 => arg
+      .parse::<f32>()
+      .map(F32::from)
+      .map(Value::from)
+      .map_err(make_err!()),
+    ValueType::F64 => arg
+      .parse::<f64>()
+      .map(F64::from)
+      .map(Value::from)
+      .map_err(make_err!()),
+    ValueType::FuncRef => {
+      bail!("the zk_engine CLI cannot take arguments of type funcref")
+    }
+    ValueType::ExternRef => {
+      bail!("the zk_engine CLI cannot take arguments of type externref")
+    }
+  }
+}
+
 /// Prepares the function results for the given [`FuncType`].
 pub fn prepare_func_results(ty: &FuncType) -> Box<[Value]> {
   ty.results().iter().copied().map(Value::default).collect()
@@ -88,3 +124,234 @@ pub fn prepare_func_results(ty: &FuncType) -> Box<[Value]> {
 pub fn wat2wasm(wat: &str) -> Result<Vec<u8>, wat::Error> {
   wat::parse_str(wat)
 }
+
+/// WASM proposals whose opcodes the zkVM supports, besides the MVP
+const SUPPORTED_PROPOSALS: [&str; 4] = [
+  "sign_extension",
+  "saturating_float_to_int",
+  "bulk_memory",
+  "reference_types",
+];
+
+/// Get the WASM proposals the module `wasm` uses which the zkVM does not support, e.g. `simd`,
+/// `threads` or `memory64`.
+///
+/// # Note
+///
+/// A proposal is reported as soon as the module contains one of its opcodes or types, whether or
+/// not the invoked function ever executes it. E.g. a module is reported to use `simd` as soon as a
+/// signature, local or global has type `v128`, even without SIMD opcodes.
+pub fn unsupported_features(wasm: &[u8]) -> Result<BTreeSet<&'static str>, Error> {
+  let mut features = BTreeSet::new();
+  let mut num_memories = 0;
+  for payload in Parser::new(0).parse_all(wasm) {
+    match payload? {
+      Payload::TypeSection(reader) => {
+        for ty in reader {
+          let Type::Func(func_type) = ty?;
+          let value_types = func_type.params().iter().chain(func_type.results());
+          features.extend(value_types.filter_map(|ty| value_type_feature(*ty)));
+        }
+      }
+      Payload::ImportSection(reader) => {
+        for import in reader {
+          match import?.ty {
+            TypeRef::Memory(memory_type) => {
+              num_memories += 1;
+              features.extend(memory_features(&memory_type));
+            }
+            TypeRef::Global(global_type) => {
+              features.extend(value_type_feature(global_type.content_type));
+            }
+            _ => {}
+          }
+        }
+      }
+      Payload::GlobalSection(reader) => {
+        for global in reader {
+          features.extend(value_type_feature(global?.ty.content_type));
+        }
+      }
+      Payload::MemorySection(reader) => {
+        for memory_type in reader {
+          num_memories += 1;
+          features.extend(memory_features(&memory_type?));
+        }
+      }
+      Payload::TagSection(_) => {
+        features.insert("exceptions");
+      }
+      Payload::CodeSectionEntry(body) => {
+        for local in body.get_locals_reader()? {
+          let (_, ty) = local?;
+          features.extend(value_type_feature(ty));
+        }
+        let mut reader = body.get_operators_reader()?;
+        while !reader.eof() {
+          features.extend(reader.visit_operator(&mut ProposalVisitor)?);
+        }
+      }
+      _ => {}
+    }
+  }
+  if num_memories > 1 {
+    features.insert("multi_memory");
+  }
+  Ok(features)
+}
+
+/// Id of the code section of a WASM module
+const CODE_SECTION_ID: u8 = 10;
+
+/// Lower the atomic loads, stores & fences of the `threads` proposal in the module `wasm` to their
+/// non-atomic equivalents, which is semantically valid as the zkVM runs a single thread. Toolchains
+/// emit these even for single threaded programs.
+///
+/// The other atomic opcodes, e.g. the read-modify-write ones, and shared memories are left as is, so
+/// [`unsupported_features`] still reports the module uses `threads`.
+///
+/// # Note
+///
+/// An atomic access to an address that is not aligned to its width traps, while the lowered access
+/// does not.
+pub fn lower_atomics(wasm: &[u8]) -> Result<Vec<u8>, Error> {
+  // Lower the function bodies in order, then replace the code section they are in
+  let mut bodies = Vec::new();
+  for payload in Parser::new(0).parse_all(wasm) {
+    if let Payload::CodeSectionEntry(body) = payload? {
+      bodies.push(lower_body(wasm, &body)?);
+    }
+  }
+
+  let mut reader = BinaryReader::new(wasm);
+  let mut lowered = reader.read_bytes(8)?.to_vec();
+  while !reader.eof() {
+    let id = reader.read_u8()?;
+    let size = reader.read_var_u32()?;
+    let contents = reader.read_bytes(size as usize)?;
+    lowered.push(id);
+    if id == CODE_SECTION_ID {
+      let mut section = Vec::new();
+      write_leb128(&mut section, bodies.len() as u64);
+      for body in &bodies {
+        write_leb128(&mut section, body.len() as u64);
+        section.extend_from_slice(body);
+      }
+      write_leb128(&mut lowered, section.len() as u64);
+      lowered.extend(section);
+    } else {
+      write_leb128(&mut lowered, u64::from(size));
+      lowered.extend_from_slice(contents);
+    }
+  }
+  Ok(lowered)
+}
+
+/// Lower the atomic loads, stores & fences of the function `body` of the module `wasm`, see
+/// [`lower_atomics`]
+fn lower_body(wasm: &[u8], body: &FunctionBody) -> Result<Vec<u8>, Error> {
+  let mut reader = body.get_operators_reader()?;
+  // The locals are kept as is
+  let mut lowered = wasm[body.range().start..reader.original_position()].to_vec();
+  while !reader.eof() {
+    let start = reader.original_position();
+    match reader.read()? {
+      // Fences order memory accesses between threads, so there is nothing to lower them to
+      Operator::AtomicFence { .. } => {}
+      op => match non_atomic_access(&op) {
+        Some((opcode, memarg)) if memarg.memory == 0 => {
+          lowered.push(opcode);
+          write_leb128(&mut lowered, u64::from(memarg.align));
+          write_leb128(&mut lowered, memarg.offset);
+        }
+        _ => lowered.extend_from_slice(&wasm[start..reader.original_position()]),
+      },
+    }
+  }
+  Ok(lowered)
+}
+
+/// Get the opcode of the non-atomic equivalent of the atomic load or store `op` & its memory
+/// argument
+fn non_atomic_access(op: &Operator) -> Option<(u8, MemArg)> {
+  match *op {
+    Operator::I32AtomicLoad { memarg } => Some((0x28, memarg)),
+    Operator::I64AtomicLoad { memarg } => Some((0x29, memarg)),
+    Operator::I32AtomicLoad8U { memarg } => Some((0x2d, memarg)),
+    Operator::I32AtomicLoad16U { memarg } => Some((0x2f, memarg)),
+    Operator::I64AtomicLoad8U { memarg } => Some((0x31, memarg)),
+    Operator::I64AtomicLoad16U { memarg } => Some((0x33, memarg)),
+    Operator::I64AtomicLoad32U { memarg } => Some((0x35, memarg)),
+    Operator::I32AtomicStore { memarg } => Some((0x36, memarg)),
+    Operator::I64AtomicStore { memarg } => Some((0x37, memarg)),
+    Operator::I32AtomicStore8 { memarg } => Some((0x3a, memarg)),
+    Operator::I32AtomicStore16 { memarg } => Some((0x3b, memarg)),
+    Operator::I64AtomicStore8 { memarg } => Some((0x3c, memarg)),
+    Operator::I64AtomicStore16 { memarg } => Some((0x3d, memarg)),
+    Operator::I64AtomicStore32 { memarg } => Some((0x3e, memarg)),
+    _ => None,
+  }
+}
+
+/// Append the unsigned LEB128 encoding of `value` to `bytes`
+fn write_leb128(bytes: &mut Vec<u8>, mut value: u64) {
+  loop {
+    let byte = (value & 0x7f) as u8;
+    value >>= 7;
+    if value == 0 {
+      bytes.push(byte);
+      return;
+    }
+    bytes.push(byte | 0x80);
+  }
+}
+
+/// Get the unsupported proposal the value type `ty` belongs to, if any.
+///
+/// # v128
+///
+/// The zkVM models the stack as 64-bit words: the tracer records `u64` operands, and each stack
+/// address of the MCC holds one word. A `v128` could be split across two consecutive stack words,
+/// low lane bits first, so `v128.const`, `local.get` & co. push two words and the MCC needs no
+/// change. Lane ops on 64-bit lanes would then lower to a pair of the i64 ops, e.g. `i64x2.add` to
+/// an `i64.add` on each word and `i64x2.extract_lane 1` to dropping the low word, while narrower
+/// lanes need shifts & masks within each word. What breaks is the 1:1 mapping between WASM values
+/// and stack slots that `local.get` depths, `drop_keep` & the call frames rely on, which is why
+/// modules using `v128` are rejected for now.
+fn value_type_feature(ty: ValType) -> Option<&'static str> {
+  match ty {
+    ValType::V128 => Some("simd"),
+    _ => None,
+  }
+}
+
+/// Get the unsupported proposals a linear memory of type `memory_type` belongs to
+fn memory_features(memory_type: &MemoryType) -> impl Iterator<Item = &'static str> {
+  [
+    (memory_type.shared, "threads"),
+    (memory_type.memory64, "memory64"),
+  ]
+  .into_iter()
+  .filter_map(|(used, feature)| used.then_some(feature))
+}
+
+/// Visits an operator to get the proposal it belongs to, if the zkVM does not support it
+struct ProposalVisitor;
+
+macro_rules! define_visit_operator {
+  ($(@$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident)*) => {
+    $(
+      #[allow(unused_variables)]
+      fn $visit(&mut self $($(, $arg: $argty)*)?) -> Self::Output {
+        let proposal = stringify!($proposal);
+        (proposal != "mvp" && !SUPPORTED_PROPOSALS.contains(&proposal)).then_some(proposal)
+      }
+    )*
+  };
+}
+
+impl<'a> VisitOperator<'a> for ProposalVisitor {
+  type Output = Option<&'static str>;
+
+  for_each_operator!(define_visit_operator);
+}
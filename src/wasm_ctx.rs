@@ -1,13 +1,36 @@
 //! Implementation of WASM execution context for zkVM
 use super::error::ZKWASMError;
-use crate::utils::{
-  tracing::unwrap_rc_refcell,
-  wasm::{decode_func_args, prepare_func_results, read_wasm_or_wat},
+use crate::{
+  utils::{
+    tracing::unwrap_rc_refcell,
+    wasm::{
+      decode_func_args, decode_global_init, lower_atomics, prepare_func_results, read_wasm_or_wat,
+      unsupported_features,
+    },
+  },
+  wasm_snark::{mcc::multiset_ops::step_RS_WS, MEMORY_OPS_PER_STEP},
 };
+use itertools::Itertools;
 use rand::{rngs::StdRng, RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
-use std::{cell::RefCell, cmp, num::NonZeroUsize, path::PathBuf, rc::Rc};
-use wasmi::{Tracer, WitnessVM};
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use std::{
+  cell::RefCell,
+  cmp,
+  collections::HashSet,
+  num::NonZeroUsize,
+  path::PathBuf,
+  rc::Rc,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+};
+use wasmi::{
+  core::{Trap, TrapCode, ValueType},
+  FuncType, Instruction as Instr, StackLimits, TraceHook, Tracer, Value, WitnessVM,
+};
 use wasmi_wasi::{clocks_ctx, sched_ctx, Table, WasiCtx};
 
 /// Builder for [`WASMArgs`]. Constructs the arguments needed to construct a WASM execution context
@@ -17,21 +40,36 @@ pub struct WASMArgsBuilder {
   program: Vec<u8>,
   invoke: String,
   func_args: Vec<String>,
+  globals: Vec<(String, String, String)>,
+  memory_images: Vec<(usize, Vec<u8>)>,
+  private_input: Vec<u8>,
+  commitment_hash: CommitmentHash,
+  resume: Option<(usize, Vec<u64>, Vec<usize>)>,
   trace_slice_vals: Option<TraceSliceValues>,
 }
 
 impl WASMArgsBuilder {
-  /// Set the program bytecode from file
+  /// Set the program bytecode from file. Atomic loads, stores & fences are lowered to their
+  /// non-atomic equivalents, as the zkVM runs a single thread.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`ZKWASMError::UnsupportedFeature`] if the program uses WASM proposals the zkVM does
+  /// not support, e.g. `threads` for the atomic opcodes which are not lowered.
   pub fn file_path(mut self, path: PathBuf) -> Result<Self, ZKWASMError> {
-    let bytecode =
-      read_wasm_or_wat(&path).map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+    let bytecode = read_wasm_or_wat(&path)
+      .and_then(|bytecode| lower_atomics(&bytecode))
+      .map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+    validate_features(&bytecode)?;
     self.program = bytecode;
     Ok(self)
   }
 
-  /// Set the program bytecode
+  /// Set the program bytecode. Atomic loads, stores & fences are lowered to their non-atomic
+  /// equivalents, as the zkVM runs a single thread.
   pub fn bytecode(mut self, program: Vec<u8>) -> Self {
-    self.program = program;
+    // A malformed program is kept as is, so tracing it reports why
+    self.program = lower_atomics(&program).unwrap_or(program);
     self
   }
 
@@ -41,12 +79,88 @@ impl WASMArgsBuilder {
     self
   }
 
+  /// Set the WASM function to invoke by its export name, checking the program exports it. The
+  /// program must be set first.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`ZKWASMError::ExportNotFound`] if the program exports no function `name`.
+  pub fn export(self, name: &str) -> Result<Self, ZKWASMError> {
+    if !exported_functions(&self.program)?
+      .iter()
+      .any(|export| export == name)
+    {
+      return Err(ZKWASMError::ExportNotFound(name.to_string()));
+    }
+    Ok(self.invoke(name))
+  }
+
   /// Set the function arguments
   pub fn func_args(mut self, func_args: Vec<String>) -> Self {
     self.func_args = func_args;
     self
   }
 
+  /// Set the initial value of the global `name` imported from `module`. The value is parsed as the
+  /// type the module declares the import with.
+  pub fn global(mut self, module: &str, name: &str, value: &str) -> Self {
+    self
+      .globals
+      .push((module.to_string(), name.to_string(), value.to_string()));
+    self
+  }
+
+  /// Load `bytes` into linear memory at byte `offset` before execution, e.g. a data blob the
+  /// program processes. The bytes are part of the initial memory state (IS) and their hash is
+  /// committed to by the proof, see [`ZKWASMInstance::memory_image_hash`].
+  ///
+  /// [`ZKWASMInstance::memory_image_hash`]: crate::wasm_snark::ZKWASMInstance::memory_image_hash
+  pub fn with_memory_image(mut self, offset: usize, bytes: Vec<u8>) -> Self {
+    self.memory_images.push((offset, bytes));
+    self
+  }
+
+  /// Set the private input the program reads through the `zk.read_private` host import. Unlike
+  /// [`WASMArgsBuilder::func_args`] or [`WASMArgsBuilder::with_memory_image`], the input is not
+  /// part of the public i/o nor of IS: the host call writes it to linear memory, so it is only in
+  /// the witness of the proof, while still being memory-checked.
+  ///
+  /// # ABI
+  ///
+  /// ```wat
+  /// (import "zk" "read_private" (func $read_private (param i32 i32) (result i32)))
+  /// ```
+  ///
+  /// `read_private(ptr, len)` copies the next at most `len` bytes of the private input to linear
+  /// memory at `ptr` and returns how many bytes it copied, 0 once the input is exhausted. The
+  /// program must export its linear memory as `memory`; a copy out of its bounds traps.
+  pub fn private_input(mut self, private_input: Vec<u8>) -> Self {
+    self.private_input = private_input;
+    self
+  }
+
+  /// Set the hash the proof commits to the memory images with, e.g.
+  /// [`CommitmentHash::Keccak256`] for a commitment an EVM contract can recompute. Defaults to
+  /// [`CommitmentHash::Poseidon`].
+  pub fn commitment_hash(mut self, commitment_hash: CommitmentHash) -> Self {
+    self.commitment_hash = commitment_hash;
+    self
+  }
+
+  /// Resume the invoked function in the middle of its body instead of running it from its entry,
+  /// e.g. to continue where a prior shard halted. Execution resumes at the instruction `pc` on the
+  /// value stack `stack`, with the callers returning to the instructions `frames` (outermost
+  /// first) reconstructed below it. The trace is then the one of the prior execution from the step
+  /// at `pc` on.
+  ///
+  /// `stack` replaces the params & locals of the call, and the caller frames are in the instance
+  /// of the invoked function. Linear memory & globals are not restored, e.g. use
+  /// [`WASMArgsBuilder::with_memory_image`] to load the memory of the prior shard.
+  pub fn resume_at(mut self, pc: usize, stack: Vec<u64>, frames: Vec<usize>) -> Self {
+    self.resume = Some((pc, stack, frames));
+    self
+  }
+
   /// Set the `start` and `end` values to slice the execution trace
   pub fn trace_slice(mut self, trace_slice_vals: TraceSliceValues) -> Self {
     self.trace_slice_vals = Some(trace_slice_vals);
@@ -59,6 +173,11 @@ impl WASMArgsBuilder {
       program: self.program,
       func_args: self.func_args,
       invoke: self.invoke,
+      globals: self.globals,
+      memory_images: self.memory_images,
+      private_input: self.private_input,
+      commitment_hash: self.commitment_hash,
+      resume: self.resume,
       trace_slice_vals: self.trace_slice_vals,
     }
   }
@@ -70,6 +189,11 @@ pub struct WASMArgs {
   pub(crate) program: Vec<u8>,
   pub(crate) invoke: String,
   pub(crate) func_args: Vec<String>,
+  pub(crate) globals: Vec<(String, String, String)>,
+  pub(crate) memory_images: Vec<(usize, Vec<u8>)>,
+  pub(crate) private_input: Vec<u8>,
+  pub(crate) commitment_hash: CommitmentHash,
+  pub(crate) resume: Option<(usize, Vec<u64>, Vec<usize>)>,
   pub(crate) trace_slice_vals: Option<TraceSliceValues>,
 }
 
@@ -97,15 +221,41 @@ impl WASMArgs {
     &self.program
   }
 
+  /// Get the hash the proof commits to the memory images with
+  pub fn commitment_hash(&self) -> CommitmentHash {
+    self.commitment_hash
+  }
+
+  /// Get the host provided initial value of the global `name` imported from `module`
+  pub(crate) fn global_init(&self, module: &str, name: &str) -> Option<&str> {
+    self
+      .globals
+      .iter()
+      .find(|(global_module, global_name, _)| global_module == module && global_name == name)
+      .map(|(_, _, value)| value.as_str())
+  }
+
+  /// Get the names of the functions the WASM module exports, in the order it exports them
+  pub fn exported_functions(&self) -> Result<Vec<String>, ZKWASMError> {
+    exported_functions(&self.program)
+  }
+
   /// Get the end slice value after WASM execution
+  ///
+  /// Returns [`ZKWASMError::InvalidTraceSliceValues`] if the slice is empty or starts past the end
+  /// of the execution trace. The slice values are checked again, as deserialized [`WASMArgs`] are
+  /// not validated on construction.
   pub fn end(&self, execution_trace_len: usize) -> Result<usize, ZKWASMError> {
-    let end_slice_val = self.calculate_end_slice_value(execution_trace_len);
-    if self.start() >= end_slice_val {
-      return Err(ZKWASMError::InvalidTraceSliceValues(
-        "start value cannot be greater than or equal to end value".to_string(),
-      ));
+    if let Some(slice) = self.trace_slice_vals {
+      slice.validate()?;
+    }
+    if self.start() >= execution_trace_len {
+      return Err(ZKWASMError::InvalidTraceSliceValues(format!(
+        "start {} is past the end of the execution trace of {execution_trace_len} opcodes",
+        self.start()
+      )));
     }
-    Ok(end_slice_val)
+    Ok(self.calculate_end_slice_value(execution_trace_len))
   }
 
   /// Calculate the end slice value considering the execution trace length
@@ -125,11 +275,29 @@ impl Default for WASMArgsBuilder {
       program: vec![],
       invoke: "main".to_string(),
       func_args: vec![],
+      globals: vec![],
+      memory_images: vec![],
+      private_input: vec![],
+      commitment_hash: CommitmentHash::default(),
+      resume: None,
       trace_slice_vals: None,
     }
   }
 }
 
+/// Hash the proof commits to the data loaded in the initial memory state with, see
+/// [`WASMArgsBuilder::commitment_hash`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommitmentHash {
+  /// The Poseidon hash the circuits use, cheapest to open in a circuit
+  #[default]
+  Poseidon,
+  /// SHA-256, e.g. to recompute the commitment with the EVM precompile
+  Sha256,
+  /// Keccak-256, e.g. to recompute the commitment with the EVM opcode
+  Keccak256,
+}
+
 /// Used to set start and end values to slice execution trace. Used in sharding/continuations
 #[derive(Debug, Clone, Default, Copy, Serialize, Deserialize)]
 pub struct TraceSliceValues {
@@ -142,11 +310,24 @@ pub struct TraceSliceValues {
 impl TraceSliceValues {
   /// Build new [`TraceSliceValues`]
   ///
-  /// # Note:
-  ///
-  /// if end does not equal 0 start value cannot be greater than or equal to end value
-  pub fn new(start: usize, end: Option<NonZeroUsize>) -> Self {
-    TraceSliceValues { start, end }
+  /// Returns [`ZKWASMError::InvalidTraceSliceValues`] if `start` is not before `end`, i.e. if the
+  /// slice is empty. Whether `start` is within the execution trace is only known once the program
+  /// runs, so proving a slice starting past its end errors then.
+  pub fn new(start: usize, end: Option<NonZeroUsize>) -> Result<Self, ZKWASMError> {
+    let slice = TraceSliceValues { start, end };
+    slice.validate()?;
+    Ok(slice)
+  }
+
+  /// Check the slice is not empty, i.e. its shard size is not 0
+  fn validate(&self) -> Result<(), ZKWASMError> {
+    match self.end {
+      Some(end) if self.start >= end.get() => Err(ZKWASMError::InvalidTraceSliceValues(format!(
+        "start {} must be before end {end}",
+        self.start
+      ))),
+      _ => Ok(()),
+    }
   }
 
   /// Get start value
@@ -159,14 +340,16 @@ impl TraceSliceValues {
     self.end
   }
 
-  /// Setter for start value
-  pub fn set_start(&mut self, start: usize) {
-    self.start = start;
+  /// Setter for start value, errors if it leaves the slice empty (see [`TraceSliceValues::new`])
+  pub fn set_start(&mut self, start: usize) -> Result<(), ZKWASMError> {
+    *self = Self::new(start, self.end)?;
+    Ok(())
   }
 
-  /// Setter for end value
-  pub fn set_end(&mut self, end: Option<NonZeroUsize>) {
-    self.end = end;
+  /// Setter for end value, errors if it leaves the slice empty (see [`TraceSliceValues::new`])
+  pub fn set_end(&mut self, end: Option<NonZeroUsize>) -> Result<(), ZKWASMError> {
+    *self = Self::new(self.start, end)?;
+    Ok(())
   }
 
   /// Calculate the shard_size
@@ -192,32 +375,91 @@ pub trait ZKWASMCtx {
   /// Getter for WASM args
   fn args(&self) -> &WASMArgs;
 
+  /// Stack limits to execute the WASM program with. Exceeding them while building the execution
+  /// trace returns [`ZKWASMError::StackOverflow`].
+  fn stack_limits(&self) -> StackLimits {
+    StackLimits::default()
+  }
+
+  /// Callback invoked for each instruction as it is traced, none by default
+  fn trace_hook(&self) -> Option<TraceHook> {
+    None
+  }
+
+  /// Host functions the WASM program can import, none by default
+  fn host_registry(&self) -> Option<&HostRegistry> {
+    None
+  }
+
   /// Get the execution trace from WASM execution context
   fn execution_trace(&self) -> Result<ExecutionTrace, ZKWASMError> {
     // Execute WASM module and build execution trace documenting vm state at
     // each step. Also get meta-date from execution like the max height of the [`ValueStack`]
     let tracer = Rc::new(RefCell::new(Tracer::new()));
+    if let Some(hook) = self.trace_hook() {
+      tracer.borrow_mut().set_hook(hook);
+    }
+    tracer
+      .borrow_mut()
+      .set_memory_images(self.args().memory_images.clone());
+    if let Some((pc, stack, frames)) = &self.args().resume {
+      tracer
+        .borrow_mut()
+        .set_resume(*pc, stack.clone(), frames.clone());
+    }
+    // Reject programs using opcodes the zkVM has no circuit for, before tracing them
+    validate_features(&self.args().program)?;
     // Setup and parse the wasm bytecode.
-    let engine = wasmi::Engine::default();
+    let mut config = wasmi::Config::default();
+    config.set_stack_limits(self.stack_limits());
+    let engine = wasmi::Engine::new(&config);
     let module = wasmi::Module::new(&engine, &self.args().program[..])?;
+    if !self.args().memory_images.is_empty() && module.default_memory().is_none() {
+      return Err(ZKWASMError::WASMError(
+        "memory images are set, but the module has no linear memory".to_string(),
+      ));
+    }
 
     // Create a new store and linker
     let mut store = Self::create_store(&engine);
-    let linker = Self::create_linker(&engine)?;
+    let mut linker = Self::create_linker(&engine)?;
+    if module
+      .imports()
+      .any(|import| import.module() == PRIVATE_INPUT_MODULE && import.name() == PRIVATE_INPUT_NAME)
+    {
+      define_private_input(&mut linker, &self.args().private_input)?;
+    }
+    if let Some(registry) = self.host_registry() {
+      registry.define(&mut linker)?;
+    }
+
+    // Define the imported globals the host provides an initial value for. The tracer puts imported
+    // globals in IS along with the ones the module declares, so their initial values get committed
+    // to in IS.
+    for import in module.imports() {
+      if let wasmi::ExternType::Global(global_ty) = import.ty() {
+        if let Some(value) = self.args().global_init(import.module(), import.name()) {
+          let value =
+            decode_global_init(global_ty.content(), import.module(), import.name(), value)?;
+          let global = wasmi::Global::new(&mut store, value, global_ty.mutability());
+          linker
+            .define(import.module(), import.name(), global)
+            .map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+        }
+      }
+    }
 
-    // Instantiate the module and trace WASM linear memory and global memory initializations
+    // Instantiate the module and trace WASM linear memory and global memory initializations. The
+    // `start` function of the module is traced too, so its effects on memory are proven along with
+    // the invoked function.
     let instance = linker
       .instantiate_with_trace(&mut store, &module, tracer.clone())?
-      .start(&mut store)?;
+      .start_with_trace(&mut store, tracer.clone())?;
 
     // Get the WASM module function we are going to invoke.
     let func = instance
       .get_func(&store, &self.args().invoke)
-      .ok_or_else(|| {
-        ZKWASMError::WasmiError(wasmi::Error::Func(
-          wasmi::errors::FuncError::ExportedFuncNotFound,
-        ))
-      })?;
+      .ok_or_else(|| ZKWASMError::ExportNotFound(self.args().invoke.clone()))?;
 
     // Prepare i/o for the function call.
     let ty = func.ty(&store);
@@ -237,11 +479,16 @@ pub trait ZKWASMCtx {
     // Get the MCC values used to construct the initial memory state of the zkWASM.
     let IS_stack_len = tracer.IS_stack_len();
     let IS_mem_len = tracer.IS_mem_len();
+    let IS_globals_len = tracer.IS_globals_len();
+    let IS_table_len = tracer.IS_table_len();
+    let IS_data_len = tracer.IS_data_len();
     let IS = tracer.IS();
 
     // Take ownership of the execution trace of type [`Vec<WitnessVM>`] because the zkWASM needs
     // this type to execute.
     let execution_trace = tracer.into_execution_trace();
+    #[cfg(test)]
+    crate::utils::coverage::record(&execution_trace);
 
     // If only proving a portion of the execution trace (a shard) calculate the end value to slice
     // execution trace and use it to slice the execution trace.
@@ -256,7 +503,288 @@ pub trait ZKWASMCtx {
     Ok((
       execution_trace,
       IS,
-      ISMemSizes::new(IS_stack_len, IS_mem_len),
+      ISMemSizes::new(
+        IS_stack_len,
+        IS_mem_len,
+        IS_globals_len,
+        IS_table_len,
+        IS_data_len,
+      ),
+    ))
+  }
+
+  /// Get the names of the functions the WASM program exports, any of which can be invoked (see
+  /// [`WASMArgsBuilder::export`])
+  fn exported_functions(&self) -> Result<Vec<String>, ZKWASMError> {
+    self.args().exported_functions()
+  }
+
+  /// Get the instructions of the WASM program the zkVM has no circuit for, one per opcode, without
+  /// executing it. Proving a program which executes any of them returns
+  /// [`ZKWASMError::UnsupportedInstruction`], so an empty list means the program can be proven.
+  fn unsupported_opcodes(&self) -> Result<Vec<Instr>, ZKWASMError> {
+    let engine = wasmi::Engine::default();
+    let module = wasmi::Module::new(&engine, &self.args().program[..])?;
+    Ok(module.unsupported_instructions())
+  }
+
+  /// Get the linear memory trace of the WASM program: the initial contents of linear memory and
+  /// every read & write the zkVM performs on it. Useful to inspect a programs memory behaviour and
+  /// estimate the memory-checking cost before proving.
+  fn memory_trace(&self) -> Result<MemoryTrace, ZKWASMError> {
+    let (execution_trace, IS, IS_sizes) = self.execution_trace()?;
+    Ok(MemoryTrace::new(&execution_trace, IS, &IS_sizes))
+  }
+}
+
+/// Module of the host import the program reads its private input with, see
+/// [`WASMArgsBuilder::private_input`]
+pub const PRIVATE_INPUT_MODULE: &str = "zk";
+
+/// Name of the host import the program reads its private input with, see
+/// [`WASMArgsBuilder::private_input`]
+pub const PRIVATE_INPUT_NAME: &str = "read_private";
+
+/// Define the `zk.read_private` host import reading `private_input` in `linker`. The host call is
+/// traced like any other, i.e. as writes of linear memory, so the input is only in the witness.
+fn define_private_input<T>(
+  linker: &mut wasmi::Linker<T>,
+  private_input: &[u8],
+) -> Result<(), ZKWASMError> {
+  let private_input: Arc<[u8]> = private_input.into();
+  // Offset of the next byte to read
+  let cursor = Arc::new(AtomicUsize::new(0));
+  linker
+    .func_wrap(
+      PRIVATE_INPUT_MODULE,
+      PRIVATE_INPUT_NAME,
+      move |mut caller: wasmi::Caller<'_, T>,
+            ptr: i32,
+            len: i32|
+            -> Result<i32, wasmi::core::Trap> {
+        let memory = caller
+          .get_export("memory")
+          .and_then(wasmi::Extern::into_memory)
+          .ok_or_else(|| wasmi::core::Trap::new("missing export `memory`"))?;
+        let offset = cursor.load(Ordering::Relaxed);
+        let end = cmp::min(offset + len as u32 as usize, private_input.len());
+        let bytes = &private_input[offset..end];
+        memory
+          .write(&mut caller, ptr as u32 as usize, bytes)
+          .map_err(|err| wasmi::core::Trap::new(err.to_string()))?;
+        cursor.store(end, Ordering::Relaxed);
+        Ok(bytes.len() as i32)
+      },
+    )
+    .map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+  Ok(())
+}
+
+/// Module of the host functions [`HostRegistry::with_hash_precompiles`] registers
+pub const PRECOMPILES_MODULE: &str = "zk";
+
+/// Host function of a [`HostRegistry`], mapping the linear memory & the arguments of a call to its
+/// results
+type HostFn = Arc<dyn Fn(&mut HostMemory<'_>, &[Value]) -> Result<Vec<Value>, Trap> + Send + Sync>;
+
+/// Linear memory of the WASM program as seen by a host function of a [`HostRegistry`], i.e. its
+/// memory exported as `memory`, if any. The bytes the host function reads are read in the execution
+/// trace, & the words it writes are written after the call, so both are checked by the MCC.
+pub struct HostMemory<'a> {
+  bytes: &'a mut [u8],
+  reads: Vec<(usize, usize)>,
+}
+
+impl HostMemory<'_> {
+  /// Read `len` bytes at `offset`, trapping if they are out of bounds
+  pub fn read(&mut self, offset: usize, len: usize) -> Result<&[u8], Trap> {
+    let end = offset
+      .checked_add(len)
+      .filter(|end| *end <= self.bytes.len())
+      .ok_or_else(|| Trap::from(TrapCode::MemoryOutOfBounds))?;
+    self.reads.push((offset, len));
+    Ok(&self.bytes[offset..end])
+  }
+
+  /// Write `bytes` at `offset`, trapping if they are out of bounds
+  pub fn write(&mut self, offset: usize, bytes: &[u8]) -> Result<(), Trap> {
+    offset
+      .checked_add(bytes.len())
+      .and_then(|end| self.bytes.get_mut(offset..end))
+      .ok_or_else(|| Trap::from(TrapCode::MemoryOutOfBounds))?
+      .copy_from_slice(bytes);
+    Ok(())
+  }
+}
+
+/// Registry of host functions a WASM program can import, keyed by module & name with their
+/// declared signature. Calls to them are traced like any other host call, i.e. the values they
+/// return & the memory & globals they leave are written in the execution trace.
+#[derive(Clone, Default)]
+pub struct HostRegistry {
+  funcs: Vec<(String, String, FuncType, HostFn)>,
+}
+
+impl HostRegistry {
+  /// Create an empty [`HostRegistry`]
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Register `func` as the host function `module.name` taking `params` & returning `results`.
+  /// Executing the program traps if `func` returns values not matching `results`.
+  pub fn register(
+    mut self,
+    module: &str,
+    name: &str,
+    params: Vec<ValueType>,
+    results: Vec<ValueType>,
+    func: impl Fn(&[Value]) -> Vec<Value> + Send + Sync + 'static,
+  ) -> Self {
+    self.register_with_memory(module, name, params, results, move |_, args| Ok(func(args)))
+  }
+
+  /// Register `func` as the host function `module.name` taking `params` & returning `results`,
+  /// which can read & write the linear memory of the program, e.g. to take a buffer by pointer.
+  /// Executing the program traps if `func` returns values not matching `results`.
+  pub fn register_with_memory<F>(
+    mut self,
+    module: &str,
+    name: &str,
+    params: Vec<ValueType>,
+    results: Vec<ValueType>,
+    func: F,
+  ) -> Self
+  where
+    F: Fn(&mut HostMemory<'_>, &[Value]) -> Result<Vec<Value>, Trap> + Send + Sync + 'static,
+  {
+    self.funcs.push((
+      module.to_string(),
+      name.to_string(),
+      FuncType::new(params, results),
+      Arc::new(func),
+    ));
+    self
+  }
+
+  /// Register the hash precompiles `zk.keccak256(ptr, len, out_ptr)` & `zk.sha256(ptr, len,
+  /// out_ptr)`, which hash the `len` bytes at `ptr` & write the 32-byte digest at `out_ptr`, so a
+  /// program does not have to prove hashing opcode by opcode.
+  ///
+  /// # Trust boundary
+  ///
+  /// The digest is computed natively by the host, outside of the zkVM. The proof only attests to
+  /// the memory movement of the call: the input is read from & the digest written to linear memory
+  /// consistently with the rest of the execution. It does **not** attest that the digest is the
+  /// hash of the input, so the verifier has to trust the prover for it, or recompute it.
+  pub fn with_hash_precompiles(self) -> Self {
+    let params = vec![ValueType::I32; 3];
+    self
+      .register_with_memory(
+        PRECOMPILES_MODULE,
+        "keccak256",
+        params.clone(),
+        vec![],
+        hash_precompile::<Keccak256>,
+      )
+      .register_with_memory(
+        PRECOMPILES_MODULE,
+        "sha256",
+        params,
+        vec![],
+        hash_precompile::<Sha256>,
+      )
+  }
+
+  /// Define the registered host functions in `linker`
+  fn define<T>(&self, linker: &mut wasmi::Linker<T>) -> Result<(), ZKWASMError> {
+    for (module, name, ty, func) in &self.funcs {
+      let (func, expected) = (func.clone(), ty.results().to_vec());
+      let import = format!("{module}.{name}");
+      linker
+        .func_new(
+          module,
+          name,
+          ty.clone(),
+          move |mut caller, args, results| {
+            let memory = caller
+              .get_export("memory")
+              .and_then(wasmi::Extern::into_memory);
+            let mut memory = HostMemory {
+              bytes: match memory {
+                Some(memory) => memory.data_mut(&mut caller),
+                None => &mut [],
+              },
+              reads: Vec::new(),
+            };
+            let values = func(&mut memory, args)?;
+            for (offset, len) in memory.reads {
+              caller.trace_memory_read(offset, len);
+            }
+            if values.iter().map(Value::ty).ne(expected.iter().copied()) {
+              return Err(Trap::new(format!(
+                "host function `{import}` returned {values:?}, expected {expected:?}"
+              )));
+            }
+            results.clone_from_slice(&values);
+            Ok(())
+          },
+        )
+        .map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+    }
+    Ok(())
+  }
+}
+
+impl std::fmt::Debug for HostRegistry {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_list()
+      .entries(
+        self
+          .funcs
+          .iter()
+          .map(|(module, name, ty, _)| (module, name, ty)),
+      )
+      .finish()
+  }
+}
+
+/// Host function hashing the `len` bytes at `ptr` with `D` & writing the digest at `out_ptr`, see
+/// [`HostRegistry::with_hash_precompiles`]
+fn hash_precompile<D: Digest>(
+  memory: &mut HostMemory<'_>,
+  args: &[Value],
+) -> Result<Vec<Value>, Trap> {
+  let [Value::I32(ptr), Value::I32(len), Value::I32(out_ptr)] = args else {
+    return Err(Trap::new("hash precompiles take 3 i32 params"));
+  };
+  let digest = D::digest(memory.read(*ptr as u32 as usize, *len as u32 as usize)?);
+  memory.write(*out_ptr as u32 as usize, &digest)?;
+  Ok(vec![])
+}
+
+/// Get the names of the functions the WASM module `program` exports
+fn exported_functions(program: &[u8]) -> Result<Vec<String>, ZKWASMError> {
+  let engine = wasmi::Engine::default();
+  let module = wasmi::Module::new(&engine, program)?;
+  Ok(
+    module
+      .exports()
+      .filter(|export| matches!(export.ty(), wasmi::ExternType::Func(_)))
+      .map(|export| export.name().to_string())
+      .collect(),
+  )
+}
+
+/// Check the WASM module `program` only uses WASM proposals the zkVM supports
+fn validate_features(program: &[u8]) -> Result<(), ZKWASMError> {
+  let features =
+    unsupported_features(program).map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+  if features.is_empty() {
+    Ok(())
+  } else {
+    Err(ZKWASMError::UnsupportedFeature(
+      features.into_iter().join(", "),
     ))
   }
 }
@@ -265,12 +793,43 @@ pub trait ZKWASMCtx {
 /// A type used to construct a WASM execution context used for proving.
 pub struct WASMCtx {
   args: WASMArgs,
+  stack_limits: StackLimits,
+  trace_hook: Option<TraceHook>,
+  host_registry: Option<HostRegistry>,
 }
 
 impl WASMCtx {
   /// Create a new instance of [`WASMCtx`]
   pub fn new(args: WASMArgs) -> Self {
-    Self { args }
+    Self {
+      args,
+      stack_limits: StackLimits::default(),
+      trace_hook: None,
+      host_registry: None,
+    }
+  }
+
+  /// Set the stack limits to execute the WASM program with
+  pub fn with_stack_limits(mut self, stack_limits: StackLimits) -> Self {
+    self.stack_limits = stack_limits;
+    self
+  }
+
+  /// Set a callback invoked with the index in the execution trace & the [`WitnessVM`] of each
+  /// instruction as it is traced, e.g. to gather custom statistics over the execution.
+  ///
+  /// # Note
+  ///
+  /// The hook is invoked every time the program is traced, and proving traces it too.
+  pub fn with_trace_hook(mut self, hook: TraceHook) -> Self {
+    self.trace_hook = Some(hook);
+    self
+  }
+
+  /// Set the host functions the WASM program can import
+  pub fn with_host_registry(mut self, registry: HostRegistry) -> Self {
+    self.host_registry = Some(registry);
+    self
   }
 }
 
@@ -288,6 +847,18 @@ impl ZKWASMCtx for WASMCtx {
   fn args(&self) -> &WASMArgs {
     &self.args
   }
+
+  fn stack_limits(&self) -> StackLimits {
+    self.stack_limits
+  }
+
+  fn trace_hook(&self) -> Option<TraceHook> {
+    self.trace_hook.clone()
+  }
+
+  fn host_registry(&self) -> Option<&HostRegistry> {
+    self.host_registry.as_ref()
+  }
 }
 
 /// Wasm execution context
@@ -327,6 +898,9 @@ pub fn zkvm_random_ctx() -> Box<dyn RngCore + Send + Sync> {
   Box::new(StdRng::from_seed([0; 32]))
 }
 
+/// Number of 8-byte words in a page of WASM linear memory
+const WORDS_PER_PAGE: usize = 8192;
+
 /// # Initial Set (IS) Memory Sizes.
 ///
 /// i.e. Memory sizes for the initial state.
@@ -336,17 +910,56 @@ pub fn zkvm_random_ctx() -> Box<dyn RngCore + Send + Sync> {
 pub struct ISMemSizes {
   IS_stack_len: usize,
   IS_mem_len: usize,
+  IS_globals_len: usize,
+  IS_table_len: usize,
+  IS_data_len: usize,
 }
 
 impl ISMemSizes {
   /// Create a new instance of [`ISMemSizes`]
-  pub fn new(IS_stack_len: usize, IS_mem_len: usize) -> Self {
+  pub fn new(
+    IS_stack_len: usize,
+    IS_mem_len: usize,
+    IS_globals_len: usize,
+    IS_table_len: usize,
+    IS_data_len: usize,
+  ) -> Self {
     Self {
       IS_stack_len,
       IS_mem_len,
+      IS_globals_len,
+      IS_table_len,
+      IS_data_len,
     }
   }
 
+  /// Derive the [`ISMemSizes`] of a compiled WASM module from its metadata, instead of from an
+  /// execution of it: the initial linear memory (in 8-byte words), the globals, imported ones
+  /// included, the default function table and the data segments match the layout the execution
+  /// trace of the module commits to.
+  ///
+  /// # Note
+  ///
+  /// The stack length is an upper bound on the stack an execution uses, which only holds for
+  /// programs without recursion. The memory length is the initial one, before any `memory.grow`.
+  pub fn from_module(module: &wasmi::Module) -> Self {
+    let IS_mem_len = module.default_memory().map_or(0, |memory| {
+      u32::from(memory.initial_pages()) as usize * WORDS_PER_PAGE
+    });
+    let IS_table_len = module
+      .default_table()
+      .filter(|table| table.element() == wasmi::core::ValueType::FuncRef)
+      .map_or(0, |table| table.minimum() as usize);
+    // IS_stack holds the values at stack pointers 0..=max_sp
+    Self::new(
+      module.max_stack_height() + 1,
+      IS_mem_len,
+      module.len_all_globals(),
+      IS_table_len,
+      module.len_data_words(),
+    )
+  }
+
   /// Get the stack length
   pub fn stack_len(&self) -> usize {
     self.IS_stack_len
@@ -356,4 +969,178 @@ impl ISMemSizes {
   pub fn mem_len(&self) -> usize {
     self.IS_mem_len
   }
+
+  /// Get the number of pages of linear memory, grown ones included
+  pub fn mem_pages(&self) -> usize {
+    self.IS_mem_len / WORDS_PER_PAGE
+  }
+
+  /// Get the number of globals
+  pub fn globals_len(&self) -> usize {
+    self.IS_globals_len
+  }
+
+  /// Get the address of the page count of linear memory, laid out right after the globals
+  pub fn pages_addr(&self) -> usize {
+    self.IS_stack_len + self.IS_mem_len + self.IS_globals_len
+  }
+
+  /// Get the number of entries of the default function table
+  pub fn table_len(&self) -> usize {
+    self.IS_table_len
+  }
+
+  /// Get the address of the default function table, laid out right after the page count of linear
+  /// memory. Entry `i` takes the two words from `table_addr() + 2 * i` on: the reference, as pushed
+  /// on the stack, then the signature word `call_indirect` checks.
+  pub fn table_addr(&self) -> usize {
+    self.pages_addr() + 1
+  }
+
+  /// Get the number of words the data segments are laid out in
+  pub fn data_len(&self) -> usize {
+    self.IS_data_len
+  }
+
+  /// Get the address of the data segments, laid out right after the default function table. Each
+  /// takes its length in bytes, which `data.drop` zeroes, then its bytes in 8-byte words and a
+  /// zero word. Active segments are dropped on instantiation, so they are empty.
+  pub fn data_addr(&self) -> usize {
+    self.table_addr() + 2 * self.IS_table_len
+  }
+
+  /// Get the address of the const pool, laid out right after the data segments
+  pub fn const_pool_addr(&self) -> usize {
+    self.data_addr() + self.IS_data_len
+  }
+}
+
+/// Kind of a [`MemoryTraceEntry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MemoryAccessKind {
+  /// Initial value of the address, i.e. from a data segment or zero
+  Init,
+  /// Value read from the address
+  Read,
+  /// Value written to the address
+  Write,
+}
+
+/// A single linear memory access in a [`MemoryTrace`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryTraceEntry {
+  /// Index of the opcode in the execution trace performing the access. `None` for
+  /// [`MemoryAccessKind::Init`]
+  pub step: Option<usize>,
+  /// Address of the accessed 8-byte word, i.e. the byte address divided by 8
+  pub addr: usize,
+  /// Value at the address after the access
+  pub value: u64,
+  /// Kind of access
+  pub kind: MemoryAccessKind,
+}
+
+/// The linear memory trace of a WASM program execution.
+///
+/// Linear memory is modelled in 8-byte words, the same way the zkVM's memory-checking sees it. Note
+/// a load or store of any width accesses both the word containing its effective address and the
+/// following word.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MemoryTrace {
+  entries: Vec<MemoryTraceEntry>,
+}
+
+impl MemoryTrace {
+  fn new(execution_trace: &[WitnessVM], IS: Vec<(usize, u64, u64)>, IS_sizes: &ISMemSizes) -> Self {
+    let heap = IS_sizes.stack_len()..IS_sizes.stack_len() + IS_sizes.mem_len();
+
+    let mut entries: Vec<MemoryTraceEntry> = IS[heap.clone()]
+      .iter()
+      .map(|(addr, value, _)| MemoryTraceEntry {
+        step: None,
+        addr: addr - heap.start,
+        value: *value,
+        kind: MemoryAccessKind::Init,
+      })
+      .collect();
+
+    // Replay the memory-checking ops of each step and keep the ones on linear memory
+    let mut FS = IS;
+    let mut global_ts = 0;
+    for (step, vm) in execution_trace.iter().enumerate() {
+      let kind = if is_load(&vm.instr) {
+        MemoryAccessKind::Read
+      } else {
+        MemoryAccessKind::Write
+      };
+      let (_, WS) = step_RS_WS(vm, &mut FS, &mut global_ts, IS_sizes, MEMORY_OPS_PER_STEP);
+      entries.extend(
+        WS.into_iter()
+          .filter(|(addr, _, _)| heap.contains(addr))
+          .map(|(addr, value, _)| MemoryTraceEntry {
+            step: Some(step),
+            addr: addr - heap.start,
+            value,
+            kind,
+          }),
+      );
+    }
+
+    Self { entries }
+  }
+
+  /// Get all the entries of the memory trace in execution order. Init entries come first.
+  pub fn entries(&self) -> &[MemoryTraceEntry] {
+    &self.entries
+  }
+
+  /// Number of reads performed on linear memory
+  pub fn reads(&self) -> usize {
+    self.count(MemoryAccessKind::Read)
+  }
+
+  /// Number of writes performed on linear memory
+  pub fn writes(&self) -> usize {
+    self.count(MemoryAccessKind::Write)
+  }
+
+  /// Number of distinct linear memory words read or written during execution
+  pub fn touched_addresses(&self) -> usize {
+    self
+      .entries
+      .iter()
+      .filter(|entry| entry.kind != MemoryAccessKind::Init)
+      .map(|entry| entry.addr)
+      .collect::<HashSet<_>>()
+      .len()
+  }
+
+  fn count(&self, kind: MemoryAccessKind) -> usize {
+    self
+      .entries
+      .iter()
+      .filter(|entry| entry.kind == kind)
+      .count()
+  }
+}
+
+/// Check if the instruction reads from linear memory without writing to it
+fn is_load(instr: &Instr) -> bool {
+  matches!(
+    instr,
+    Instr::I32Load(..)
+      | Instr::I32Load8U(..)
+      | Instr::I32Load8S(..)
+      | Instr::I32Load16U(..)
+      | Instr::I32Load16S(..)
+      | Instr::F32Load(..)
+      | Instr::F64Load(..)
+      | Instr::I64Load(..)
+      | Instr::I64Load8S(..)
+      | Instr::I64Load8U(..)
+      | Instr::I64Load16S(..)
+      | Instr::I64Load16U(..)
+      | Instr::I64Load32S(..)
+      | Instr::I64Load32U(..)
+  )
 }
@@ -0,0 +1,75 @@
+//! Poseidon hash over the scalar field of the execution circuit
+use bellpepper_core::{
+  boolean::AllocatedBit, num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError,
+};
+use ff::{PrimeField, PrimeFieldBits};
+use nova::{
+  provider::poseidon::{PoseidonConstantsCircuit, PoseidonRO, PoseidonROCircuit},
+  traits::{ROCircuitTrait, ROTrait},
+};
+use serde::{Deserialize, Serialize};
+
+/// Number of bits squeezed from the sponge, so the digest fits in the field
+const HASH_BITS: usize = 250;
+
+/// Hash `inputs` in the circuit, the digest matches [`hash_native`]
+pub fn hash<F, CS>(
+  mut cs: CS,
+  inputs: &[AllocatedNum<F>],
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+  CS: ConstraintSystem<F>,
+{
+  let mut ro = PoseidonROCircuit::<F>::new(PoseidonConstantsCircuit::default(), inputs.len());
+  for input in inputs {
+    ro.absorb(input);
+  }
+  let bits = ro.squeeze(cs.namespace(|| "squeeze"), HASH_BITS)?;
+  le_bits_to_num(cs.namespace(|| "digest"), &bits)
+}
+
+/// Hash `inputs` outside of the circuit
+pub fn hash_native<F>(inputs: &[F]) -> F
+where
+  F: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+{
+  let mut ro = PoseidonRO::<F, F>::new(PoseidonConstantsCircuit::default(), inputs.len());
+  for input in inputs {
+    ro.absorb(*input);
+  }
+  ro.squeeze(HASH_BITS)
+}
+
+/// Pack little-endian bits into a num
+fn le_bits_to_num<F, CS>(
+  mut cs: CS,
+  bits: &[AllocatedBit],
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let mut lc = LinearCombination::zero();
+  let mut coeff = F::ONE;
+  let mut value = Some(F::ZERO);
+  for bit in bits {
+    lc = lc + (coeff, bit.get_variable());
+    value = value.and_then(|value| {
+      bit
+        .get_value()
+        .map(|b| if b { value + coeff } else { value })
+    });
+    coeff = coeff.double();
+  }
+  let num = AllocatedNum::alloc(cs.namespace(|| "num"), || {
+    value.ok_or(SynthesisError::AssignmentMissing)
+  })?;
+  cs.enforce(
+    || "num = Σ 2^i · bit_i",
+    |_| lc,
+    |lc| lc + CS::one(),
+    |lc| lc + num.get_variable(),
+  );
+  Ok(num)
+}
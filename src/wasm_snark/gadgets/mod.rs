@@ -1,3 +1,4 @@
+pub mod hash;
 pub mod int;
 pub mod mcc;
 pub mod utils;
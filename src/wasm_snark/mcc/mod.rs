@@ -5,7 +5,6 @@ use super::{
     mcc::{alloc_avt_tuple, randomized_hash_func},
     utils::alloc_one,
   },
-  MEMORY_OPS_PER_STEP,
 };
 
 use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
@@ -131,13 +130,19 @@ impl OpsCircuit {
   pub fn new(RS: Vec<(usize, u64, u64)>, WS: Vec<(usize, u64, u64)>) -> Self {
     OpsCircuit { RS, WS }
   }
+
+  /// Get the RS & WS of the step
+  pub fn into_multisets(self) -> (Vec<(usize, u64, u64)>, Vec<(usize, u64, u64)>) {
+    (self.RS, self.WS)
+  }
 }
 
-impl Default for OpsCircuit {
-  fn default() -> Self {
+impl OpsCircuit {
+  /// Create an empty instance of [`OpsCircuit`] with `memory_ops_per_step` memory ops
+  pub fn empty(memory_ops_per_step: usize) -> Self {
     OpsCircuit {
-      RS: vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2],
-      WS: vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2],
+      RS: vec![(0, 0, 0); memory_ops_per_step / 2],
+      WS: vec![(0, 0, 0); memory_ops_per_step / 2],
     }
   }
 }
@@ -302,9 +307,9 @@ where
 
 impl BatchedOpsCircuit {
   /// Create an empty instance of [`BatchedOpsCircuit`]
-  pub fn empty(step_size: usize) -> Self {
+  pub fn empty(step_size: usize, memory_ops_per_step: usize) -> Self {
     Self {
-      circuits: vec![OpsCircuit::default(); step_size],
+      circuits: vec![OpsCircuit::empty(memory_ops_per_step); step_size],
     }
   }
 
@@ -1,6 +1,7 @@
-use crate::{wasm_ctx::ISMemSizes, wasm_snark::MEMORY_OPS_PER_STEP};
+use crate::{error::ZKWASMError, wasm_ctx::ISMemSizes};
 use ff::PrimeField;
-use wasmi::{Instruction as Instr, WitnessVM};
+use std::collections::BTreeMap;
+use wasmi::{Instruction as Instr, WitnessVM, HOST_CALL_ARG, HOST_CALL_GLOBAL, HOST_CALL_READ};
 
 /// Get the RS & WS for a single execution step. A RS (read-set) & a WS (write-set) are of the form
 /// of a vector of (address, value, timestamp) tuples
@@ -9,18 +10,21 @@ use wasmi::{Instruction as Instr, WitnessVM};
 ///
 /// It is ok to have `FS` and `global_ts` as mutable references since they are used to represent an
 /// untrusted memory which inherently is mutable.
+///
+/// The RS & WS are padded to `memory_ops_per_step / 2` ops each.
 pub fn step_RS_WS(
   vm: &WitnessVM,
   FS: &mut [(usize, u64, u64)],
   global_ts: &mut u64,
   IS_sizes: &ISMemSizes,
+  memory_ops_per_step: usize,
 ) -> (
   Vec<(usize, u64, u64)>, // RS
   Vec<(usize, u64, u64)>, // WS
 ) {
   let instr: Instr = vm.instr;
-  let mut RS: Vec<(usize, u64, u64)> = Vec::with_capacity(MEMORY_OPS_PER_STEP / 2);
-  let mut WS: Vec<(usize, u64, u64)> = Vec::with_capacity(MEMORY_OPS_PER_STEP / 2);
+  let mut RS: Vec<(usize, u64, u64)> = Vec::with_capacity(memory_ops_per_step / 2);
+  let mut WS: Vec<(usize, u64, u64)> = Vec::with_capacity(memory_ops_per_step / 2);
 
   // Construct RS & WS based on the instruction. The RS & WS are constructed as follows:
   match instr {
@@ -57,27 +61,32 @@ pub fn step_RS_WS(
 
     // branch opcodes
     Instr::Br(_) => {}
-    Instr::BrIfEqz(_) | Instr::BrIfNez(_) => {
+    Instr::BrIfEqz(_) | Instr::BrIfNez(_) | Instr::ReturnIfNez(_) => {
       read_op(vm.pre_sp - 1, global_ts, FS, &mut RS, &mut WS); // condition
     }
     Instr::BrAdjust(_) => {}
-    Instr::BrTable(_) => {}
+    Instr::BrTable(_) => {
+      read_op(vm.pre_sp - 1, global_ts, FS, &mut RS, &mut WS); // index
+    }
 
     // memory operations related to return instructions
     Instr::Drop => {}
     Instr::DropKeep => {
       let drop = vm.I as usize;
-      let keep = vm.P as usize;
-
-      // address of the keep value
-      let read_addr = vm.pre_sp - keep;
-
-      // new address to write the keep value
-      let write_addr = vm.pre_sp - drop - keep;
-
-      // read the keep value at `pre_sp - keep` and write it to `pre_sp - drop - keep`
-      read_op(read_addr, global_ts, FS, &mut RS, &mut WS);
-      write_op(write_addr, vm.Y, global_ts, FS, &mut RS, &mut WS);
+      for (offset, value) in kept_values(vm).enumerate() {
+        let keep = vm.P as usize + offset;
+
+        // read the keep value at `pre_sp - keep` and write it to `pre_sp - drop - keep`
+        read_op(vm.pre_sp - keep, global_ts, FS, &mut RS, &mut WS);
+        write_op(
+          vm.pre_sp - drop - keep,
+          value,
+          global_ts,
+          FS,
+          &mut RS,
+          &mut WS,
+        );
+      }
     }
     Instr::Return(..) => {}
 
@@ -85,16 +94,56 @@ pub fn step_RS_WS(
     Instr::CallZeroWrite => {
       write_op(vm.pre_sp, vm.P, global_ts, FS, &mut RS, &mut WS);
     }
+    Instr::HostCallStep if vm.I == HOST_CALL_GLOBAL => {
+      let write_addr = IS_sizes.stack_len() + IS_sizes.mem_len() + vm.Y as usize;
+      write_op(write_addr, vm.P, global_ts, FS, &mut RS, &mut WS);
+    }
+    // the input a host function read, e.g. of a precompile, is bound to linear memory
+    Instr::HostCallStep if vm.I == HOST_CALL_READ => {
+      read_op(
+        vm.Y as usize + IS_sizes.stack_len(),
+        global_ts,
+        FS,
+        &mut RS,
+        &mut WS,
+      );
+    }
     Instr::HostCallStep => {
       let write_addr = vm.Y as usize + IS_sizes.stack_len();
       write_op(write_addr, vm.P, global_ts, FS, &mut RS, &mut WS);
     }
+    // the arguments of a host call are read, so they are bound to the stack values
+    Instr::HostCallStackStep if vm.I == HOST_CALL_ARG => {
+      read_op(vm.pre_sp, global_ts, FS, &mut RS, &mut WS);
+    }
     Instr::HostCallStackStep => {
       write_op(vm.pre_sp, vm.P, global_ts, FS, &mut RS, &mut WS);
     }
+    // an indirect call checks the table entry at the popped index, the jump itself is a no-op
+    Instr::CallIndirect(..) => {
+      read_op(vm.pre_sp - 1, global_ts, FS, &mut RS, &mut WS); // index
+      let entry_addr = IS_sizes.table_addr() + 2 * vm.Y as usize;
+      read_op(entry_addr + 1, global_ts, FS, &mut RS, &mut WS); // signature word
+      read_op(entry_addr, global_ts, FS, &mut RS, &mut WS); // reference of the callee
+    }
+    Instr::TableGet(..) => {
+      read_op(vm.pre_sp - 1, global_ts, FS, &mut RS, &mut WS); // index
+      let read_addr = IS_sizes.table_addr() + 2 * vm.Y as usize;
+      read_op(read_addr, global_ts, FS, &mut RS, &mut WS); // reference
+      write_op(vm.pre_sp - 1, vm.Z, global_ts, FS, &mut RS, &mut WS);
+    }
+    Instr::TableSet(..) => {
+      read_op(vm.pre_sp - 2, global_ts, FS, &mut RS, &mut WS); // index
+      read_op(vm.pre_sp - 1, global_ts, FS, &mut RS, &mut WS); // reference
+      let write_addr = IS_sizes.table_addr() + 2 * vm.Y as usize;
+      write_op(write_addr, vm.X, global_ts, FS, &mut RS, &mut WS);
+      write_op(write_addr + 1, vm.P, global_ts, FS, &mut RS, &mut WS); // signature word
+    }
+    Instr::RefFunc(..) => {
+      write_op(vm.pre_sp, vm.P, global_ts, FS, &mut RS, &mut WS);
+    }
     // no-op call instructions
     Instr::Call(..) => {}
-    Instr::CallIndirect(..) => {}
     Instr::CallInternal(..) => {}
 
     // select
@@ -136,8 +185,16 @@ pub fn step_RS_WS(
 
       let write_addr_1 = effective_addr / 8 + IS_sizes.stack_len();
       let write_addr_2 = effective_addr / 8 + 1 + IS_sizes.stack_len();
+
+      // A store to the last word of memory leaves the address after it, which is out of memory,
+      // untouched. The tracer sets `vm.Q` to 0 for it.
+      let write_val_2 = if write_addr_2 == IS_sizes.stack_len() + IS_sizes.mem_len() {
+        FS[write_addr_2].1
+      } else {
+        vm.Q
+      };
       write_op(write_addr_1, vm.P, global_ts, FS, &mut RS, &mut WS);
-      write_op(write_addr_2, vm.Q, global_ts, FS, &mut RS, &mut WS);
+      write_op(write_addr_2, write_val_2, global_ts, FS, &mut RS, &mut WS);
     }
     Instr::I32Load(..)
     | Instr::I32Load8U(..)
@@ -170,27 +227,82 @@ pub fn step_RS_WS(
 
     // memory size, grow, fill, copy
     Instr::MemorySize => {
+      read_op(IS_sizes.pages_addr(), global_ts, FS, &mut RS, &mut WS);
       write_op(vm.pre_sp, vm.Y, global_ts, FS, &mut RS, &mut WS);
     }
     Instr::MemoryGrow => {
       read_op(vm.pre_sp - 1, global_ts, FS, &mut RS, &mut WS);
+      read_op(IS_sizes.pages_addr(), global_ts, FS, &mut RS, &mut WS);
       write_op(vm.pre_sp - 1, vm.P, global_ts, FS, &mut RS, &mut WS);
+
+      // A failed grow leaves the page count as is
+      let pages = if vm.P == u32::MAX as u64 {
+        FS[IS_sizes.pages_addr()].1
+      } else {
+        vm.P + vm.Y
+      };
+      write_op(
+        IS_sizes.pages_addr(),
+        pages,
+        global_ts,
+        FS,
+        &mut RS,
+        &mut WS,
+      );
     }
     Instr::MemoryFill => {}
     Instr::MemoryFillStep => {
+      // offset, value & size of the fill
+      read_op(vm.pre_sp - 3, global_ts, FS, &mut RS, &mut WS);
+      read_op(vm.pre_sp - 2, global_ts, FS, &mut RS, &mut WS);
+      read_op(vm.pre_sp - 1, global_ts, FS, &mut RS, &mut WS);
       let write_addr = vm.X as usize + IS_sizes.stack_len();
       write_op(write_addr, vm.P, global_ts, FS, &mut RS, &mut WS);
     }
     Instr::MemoryCopy => {}
     Instr::MemoryCopyStep => {
+      // The bytes copied to the word X start at byte rot of the word q - 1, with
+      // 8 · (X + 1) + src - dst = 8 · q + rot. The source words are clamped to linear memory.
+      let q = (8 * (vm.X + 1) + vm.Y - vm.Q) / 8;
+      let lo = q.saturating_sub(1) as usize;
+      let hi = q.min(IS_sizes.mem_len() as u64 - 1) as usize;
+      read_op(lo + IS_sizes.stack_len(), global_ts, FS, &mut RS, &mut WS);
+      read_op(hi + IS_sizes.stack_len(), global_ts, FS, &mut RS, &mut WS);
+      let write_addr = vm.X as usize + IS_sizes.stack_len();
+      write_op(write_addr, vm.P, global_ts, FS, &mut RS, &mut WS);
+    }
+    Instr::MemoryInit(..) => {}
+    Instr::MemoryInitStep => {
+      // Like a memory.copy step from the words of the data segment laid out from Z, after its
+      // length. The words are padded with a zero word, so the source words need no clamping.
+      let segment = IS_sizes.data_addr() + vm.Z as usize;
+      let q = ((8 * (vm.X + 1) + vm.Y - vm.Q) / 8) as usize;
+      read_op(segment, global_ts, FS, &mut RS, &mut WS); // length
+      read_op(
+        segment + 1 + q.saturating_sub(1),
+        global_ts,
+        FS,
+        &mut RS,
+        &mut WS,
+      );
+      read_op(segment + 1 + q, global_ts, FS, &mut RS, &mut WS);
       let write_addr = vm.X as usize + IS_sizes.stack_len();
       write_op(write_addr, vm.P, global_ts, FS, &mut RS, &mut WS);
     }
+    Instr::DataDrop(..) => {
+      let len_addr = IS_sizes.data_addr() + vm.I as usize;
+      write_op(len_addr, 0, global_ts, FS, &mut RS, &mut WS);
+    }
 
     // const opcodes
-    Instr::I64Const32(_) | Instr::Const32(..) | Instr::ConstRef(..) | Instr::F64Const32(..) => {
+    Instr::I64Const32(_) | Instr::Const32(..) | Instr::F64Const32(..) => {
       write_op(vm.pre_sp, vm.I, global_ts, FS, &mut RS, &mut WS);
     }
+    Instr::ConstRef(..) => {
+      let read_addr = IS_sizes.const_pool_addr() + vm.I as usize;
+      read_op(read_addr, global_ts, FS, &mut RS, &mut WS); // Y
+      write_op(vm.pre_sp, vm.Y, global_ts, FS, &mut RS, &mut WS);
+    }
 
     Instr::I64Add
     | Instr::I64Mul
@@ -344,17 +456,23 @@ pub fn step_RS_WS(
     _ => unimplemented!("{:?}", instr),
   }
 
-  // If the number of memory operations is not equal to MEMORY_OPS_PER_STEP, then we need to pad
+  // If the number of memory operations is not equal to `memory_ops_per_step`, then we need to pad
   // the RS & WS with dummy values
-  for _ in RS.len()..MEMORY_OPS_PER_STEP / 2 {
+  for _ in RS.len()..memory_ops_per_step / 2 {
     read_op(0, global_ts, FS, &mut RS, &mut WS);
   }
 
   (RS, WS)
 }
 
+/// Get the values kept by the drop keep step `vm`, from the top of the stack down (see
+/// [`wasmi::MAX_KEEP_PER_STEP`])
+pub(crate) fn kept_values(vm: &WitnessVM) -> impl Iterator<Item = u64> {
+  [vm.X, vm.Y, vm.Z].into_iter().take(vm.Q as usize)
+}
+
 /// Read operation between an untrusted memory and a checker
-fn read_op(
+pub(crate) fn read_op(
   addr: usize,
   global_ts: &mut u64,
   FS: &mut [(usize, u64, u64)],
@@ -381,7 +499,7 @@ fn read_op(
 }
 
 /// Write operation between an untrusted memory and a checker
-fn write_op(
+pub(crate) fn write_op(
   addr: usize,
   val: u64,
   global_ts: &mut u64,
@@ -408,6 +526,32 @@ fn write_op(
   WS.push((addr, val, *global_ts));
 }
 
+/// Check the multisets of the memory-consistency check in the clear, i.e. that IS ∪ WS = RS ∪ FS,
+/// the identity the grand products of Πops & Πscan check under random challenges.
+///
+/// Returns [`ZKWASMError::MultisetMismatch`] with the (address, value, timestamp) tuple of the
+/// lowest timestamp found in only one side of the identity, e.g. the first op a wrong write
+/// timestamp breaks.
+pub fn validate_multiset(
+  RS: &[(usize, u64, u64)],
+  WS: &[(usize, u64, u64)],
+  IS: &[(usize, u64, u64)],
+  FS: &[(usize, u64, u64)],
+) -> Result<(), ZKWASMError> {
+  // Count each tuple up in IS ∪ WS & down in RS ∪ FS, ordered by timestamp first
+  let mut counts = BTreeMap::<(u64, usize, u64), i64>::new();
+  for &(addr, val, ts) in IS.iter().chain(WS) {
+    *counts.entry((ts, addr, val)).or_default() += 1;
+  }
+  for &(addr, val, ts) in RS.iter().chain(FS) {
+    *counts.entry((ts, addr, val)).or_default() -= 1;
+  }
+  match counts.into_iter().find(|(_, count)| *count != 0) {
+    Some(((ts, addr, val), _)) => Err(ZKWASMError::MultisetMismatch { addr, val, ts }),
+    None => Ok(()),
+  }
+}
+
 /// Converts an addr, val, ts tuple `(usize, u64, u64)` to a `Vec<Scalar>`
 pub fn avt_tuple_to_scalar_vec<F>((addr, val, ts): (usize, u64, u64)) -> Vec<F>
 where
@@ -9,7 +9,7 @@ use crate::{
   error::ZKWASMError,
   utils::logging::init_logger,
   wasm_ctx::{WASMArgsBuilder, WASMCtx, WasiWASMCtx, ZKWASMCtx},
-  wasm_snark::mcc::multiset_ops::step_RS_WS,
+  wasm_snark::{mcc::multiset_ops::step_RS_WS, MEMORY_OPS_PER_STEP},
 };
 
 /// Curve Cycle to prove/verify on
@@ -49,7 +49,8 @@ where
 
   // Build the WASMTransitionCircuit from each traced execution frame.
   execution_trace.into_iter().for_each(|vm| {
-    let (step_rs, step_ws) = step_RS_WS(&vm, &mut FS, &mut global_ts, &IS_sizes);
+    let (step_rs, step_ws) =
+      step_RS_WS(&vm, &mut FS, &mut global_ts, &IS_sizes, MEMORY_OPS_PER_STEP);
 
     RS.extend(step_rs);
     WS.extend(step_ws);
@@ -0,0 +1,292 @@
+//! Proves the memory consistency check (MCC) of a memory trace on its own, without proving the
+//! execution that produced it.
+//!
+//! This is useful when the memory trace comes from a trusted executor and only its consistency,
+//! i.e. that every read returns the last value written, has to be proven.
+use ff::Field;
+use itertools::Itertools;
+use nova::{
+  nebula::{
+    audit_rs::{AuditPublicParams, AuditRecursiveSNARK},
+    ic::IC,
+    rs::{PublicParams, RecursiveSNARK, StepCircuit},
+  },
+  traits::{snark::default_ck_hint, CurveCycleEquipped},
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+  build_scan_circuits,
+  mcc::{
+    multiset_ops::{read_op, write_op},
+    BatchedOpsCircuit, OpsCircuit, ScanCircuit,
+  },
+  mcc_challenges, prove_ops, prove_scan, verify_mcc, StepSize,
+};
+use crate::error::ZKWASMError;
+
+#[cfg(test)]
+mod tests;
+
+/// A memory trace: the initial memory and the reads & writes performed on it, in order.
+///
+/// Each read or write is recorded the same way the zkVM does, i.e. as a read of the current
+/// `(addr, val, ts)` tuple into RS and a write of the new tuple into WS.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryTable {
+  IS: Vec<(usize, u64, u64)>,
+  FS: Vec<(usize, u64, u64)>,
+  RS: Vec<(usize, u64, u64)>,
+  WS: Vec<(usize, u64, u64)>,
+  global_ts: u64,
+}
+
+impl MemoryTable {
+  /// Create a new [`MemoryTable`] over a memory of `memory.len()` words, initialized to `memory`
+  pub fn new(memory: Vec<u64>) -> Self {
+    let IS = memory
+      .into_iter()
+      .enumerate()
+      .map(|(addr, val)| (addr, val, 0))
+      .collect::<Vec<_>>();
+    Self {
+      FS: IS.clone(),
+      IS,
+      ..Default::default()
+    }
+  }
+
+  /// Read the word at `addr`
+  pub fn read(&mut self, addr: usize) -> Result<u64, ZKWASMError> {
+    self.check_bounds(addr)?;
+    read_op(
+      addr,
+      &mut self.global_ts,
+      &mut self.FS,
+      &mut self.RS,
+      &mut self.WS,
+    );
+    Ok(self.FS[addr].1)
+  }
+
+  /// Write `val` to the word at `addr`
+  pub fn write(&mut self, addr: usize, val: u64) -> Result<(), ZKWASMError> {
+    self.check_bounds(addr)?;
+    write_op(
+      addr,
+      val,
+      &mut self.global_ts,
+      &mut self.FS,
+      &mut self.RS,
+      &mut self.WS,
+    );
+    Ok(())
+  }
+
+  /// Get the initial memory, as `(addr, val, ts)` tuples
+  pub fn IS(&self) -> &[(usize, u64, u64)] {
+    &self.IS
+  }
+
+  /// Get the final memory, as `(addr, val, ts)` tuples
+  pub fn FS(&self) -> &[(usize, u64, u64)] {
+    &self.FS
+  }
+
+  fn check_bounds(&self, addr: usize) -> Result<(), ZKWASMError> {
+    if addr >= self.IS.len() {
+      return Err(ZKWASMError::MemoryOutOfBounds(addr));
+    }
+    Ok(())
+  }
+}
+
+/// [`MCCProof`] public parameters
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MCCPublicParams<E>
+where
+  E: CurveCycleEquipped,
+{
+  ops_pp: PublicParams<E>,
+  scan_pp: AuditPublicParams<E>,
+  /// Number of memory ops per step the ops circuit was set up with
+  memory_ops_per_step: usize,
+}
+
+/// A proof of the memory consistency check of a [`MemoryTable`], made of the ops proof (Πops) and
+/// the scan proof (Πscan) of a [`WasmSNARK`](super::WasmSNARK).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MCCProof<E>
+where
+  E: CurveCycleEquipped,
+{
+  ops_rs: RecursiveSNARK<E>,
+  scan_rs: AuditRecursiveSNARK<E>,
+}
+
+impl<E> MCCProof<E>
+where
+  E: CurveCycleEquipped,
+{
+  /// Fn used to obtain setup material for proving the MCC of memory traces. Only the
+  /// `memory_ops_per_step` and the execution & memory step sizes of `step_size` are used, the
+  /// execution step size being the number of ops circuits folded per step.
+  pub fn setup(step_size: StepSize) -> MCCPublicParams<E> {
+    let ops_pp = PublicParams::<E>::setup(
+      &BatchedOpsCircuit::empty(step_size.execution, step_size.memory_ops),
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    );
+    let scan_pp = AuditPublicParams::<E>::setup(
+      &ScanCircuit::empty(step_size.memory),
+      &*default_ck_hint(),
+      &*default_ck_hint(),
+    );
+    MCCPublicParams {
+      ops_pp,
+      scan_pp,
+      memory_ops_per_step: step_size.memory_ops,
+    }
+  }
+
+  #[tracing::instrument(skip_all, name = "MCCProof::prove_mcc_only")]
+  /// Prove that the reads & writes of `memory_table` are consistent with its initial and final
+  /// memory
+  ///
+  /// # Note
+  ///
+  /// The memory must not be empty, as the ops are padded with reads of address 0.
+  pub fn prove_mcc_only(
+    pp: &MCCPublicParams<E>,
+    mut memory_table: MemoryTable,
+    step_size: StepSize,
+  ) -> Result<(Self, MCCInstance<E>), ZKWASMError> {
+    step_size.validate()?;
+    if step_size.memory_ops != pp.memory_ops_per_step {
+      return Err(ZKWASMError::InvalidStepSize(format!(
+        "{} memory ops per step, but the public parameters were set up with {}",
+        step_size.memory_ops, pp.memory_ops_per_step
+      )));
+    }
+
+    // Pad the ops with dummy reads, so they fill at least one and a whole number of batched ops
+    // circuits
+    let ops_per_circuit = step_size.memory_ops / 2;
+    let ops_per_step = ops_per_circuit * step_size.execution;
+    let pad_len = if memory_table.RS.is_empty() {
+      ops_per_step
+    } else {
+      (ops_per_step - (memory_table.RS.len() % ops_per_step)) % ops_per_step
+    };
+    for _ in 0..pad_len {
+      memory_table.read(0)?;
+    }
+    let MemoryTable { IS, FS, RS, WS, .. } = memory_table;
+
+    // Build ops circuits
+    let ops_circuits = RS
+      .chunks(ops_per_circuit)
+      .zip_eq(WS.chunks(ops_per_circuit))
+      .map(|(rs, ws)| OpsCircuit::new(rs.to_vec(), ws.to_vec()))
+      .collect::<Vec<_>>();
+    let ops_circuits = ops_circuits
+      .chunks(step_size.execution)
+      .map(|chunk| BatchedOpsCircuit::new(chunk.to_vec()))
+      .collect::<Vec<_>>();
+
+    // There is no execution proof, so the challenges are derived from the commitment to RS & WS
+    // carried by Πops instead of the one carried by ΠF.
+    let C_n = ops_circuits.iter().fold(E::Scalar::ZERO, |IC_i, circuit| {
+      IC::<E>::commit(
+        &pp.ops_pp.ck_primary,
+        &pp.ops_pp.ro_consts,
+        IC_i,
+        <BatchedOpsCircuit as StepCircuit<E::Scalar>>::non_deterministic_advice(circuit),
+      )
+    });
+    let (scan_circuits, IC_IS, IC_FS) =
+      build_scan_circuits::<E>(&pp.scan_pp, IS, FS, step_size, None);
+    let (gamma, alpha) = mcc_challenges::<E>(
+      C_n,
+      IC_IS,
+      IC_FS,
+      E::Scalar::ZERO,
+      E::Scalar::ZERO,
+      E::Scalar::ZERO,
+      E::Scalar::ZERO,
+    )?;
+
+    // z0 <- [gamma, alpha, ts=gts, h_RS=1, h_WS=1], the initial memory has timestamp 0
+    let ops_z0 = vec![
+      gamma,
+      alpha,
+      E::Scalar::ZERO,
+      E::Scalar::ONE,
+      E::Scalar::ONE,
+    ];
+    let (ops_rs, ops_IC_i) = prove_ops(&pp.ops_pp, &ops_circuits, &ops_z0, None)?;
+    debug_assert_eq!(ops_IC_i, C_n);
+
+    // z0 <- [gamma, alpha, h_IS=1, h_FS=1]
+    let scan_z0 = vec![gamma, alpha, E::Scalar::ONE, E::Scalar::ONE];
+    let (scan_rs, scan_IC_i) = prove_scan(&pp.scan_pp, &scan_circuits, &scan_z0, None)?;
+    debug_assert_eq!(scan_IC_i, (IC_IS, IC_FS));
+
+    Ok((
+      Self { ops_rs, scan_rs },
+      MCCInstance {
+        ops_z0,
+        ops_IC_i,
+        scan_z0,
+        scan_IC_i,
+      },
+    ))
+  }
+
+  /// Verify the [`MCCProof`]
+  pub fn verify(&self, pp: &MCCPublicParams<E>, U: &MCCInstance<E>) -> Result<(), ZKWASMError> {
+    verify_mcc(
+      &pp.ops_pp,
+      &pp.scan_pp,
+      &self.ops_rs,
+      &self.scan_rs,
+      U,
+      E::Scalar::ZERO,
+      E::Scalar::ZERO,
+      E::Scalar::ZERO,
+    )
+  }
+}
+
+/// Public i/o for MCC proving
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct MCCInstance<E>
+where
+  E: CurveCycleEquipped,
+{
+  // ops instance
+  pub(super) ops_z0: Vec<E::Scalar>,
+  pub(super) ops_IC_i: E::Scalar,
+
+  // scan instance
+  pub(super) scan_z0: Vec<E::Scalar>,
+  pub(super) scan_IC_i: (E::Scalar, E::Scalar),
+}
+
+impl<E> MCCInstance<E>
+where
+  E: CurveCycleEquipped,
+{
+  /// Commitment to the initial memory
+  pub fn C_IS(&self) -> E::Scalar {
+    self.scan_IC_i.0
+  }
+
+  /// Commitment to the final memory
+  pub fn C_FS(&self) -> E::Scalar {
+    self.scan_IC_i.1
+  }
+}
@@ -0,0 +1,45 @@
+use nova::provider::Bn256EngineIPA;
+
+use super::{MCCProof, MemoryTable};
+use crate::{error::ZKWASMError, utils::logging::init_logger, wasm_snark::StepSize};
+
+/// Curve Cycle to prove/verify on
+type E = Bn256EngineIPA;
+
+#[test]
+fn test_mcc_only() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(4)?;
+  let pp = MCCProof::<E>::setup(step_size);
+
+  let mut memory_table = MemoryTable::new(vec![1, 2, 3, 4, 5]);
+  assert_eq!(memory_table.read(1)?, 2);
+  memory_table.write(1, 42)?;
+  memory_table.write(4, 7)?;
+  assert_eq!(memory_table.read(1)?, 42);
+  assert_eq!(memory_table.read(4)?, 7);
+  assert!(matches!(
+    memory_table.write(5, 0),
+    Err(ZKWASMError::MemoryOutOfBounds(5))
+  ));
+
+  let (proof, U) = MCCProof::prove_mcc_only(&pp, memory_table, step_size)?;
+  proof.verify(&pp, &U)?;
+  Ok(())
+}
+
+#[test]
+fn test_mcc_only_tampered_instance() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(4)?;
+  let pp = MCCProof::<E>::setup(step_size);
+
+  let mut memory_table = MemoryTable::new(vec![0; 4]);
+  memory_table.write(2, 9)?;
+  assert_eq!(memory_table.read(2)?, 9);
+
+  let (proof, mut U) = MCCProof::prove_mcc_only(&pp, memory_table, step_size)?;
+  U.scan_IC_i.1 = U.scan_IC_i.0;
+  assert!(proof.verify(&pp, &U).is_err());
+  Ok(())
+}
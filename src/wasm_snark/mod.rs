@@ -1,23 +1,27 @@
 //! Implements SNARK proving the WASM module computation
-use std::cell::OnceCell;
+use std::{num::NonZeroUsize, sync::OnceLock};
 
 use super::{
   error::ZKWASMError,
-  wasm_ctx::{ISMemSizes, ZKWASMCtx},
+  wasm_ctx::{
+    CommitmentHash, ExecutionTrace, HostRegistry, ISMemSizes, TraceSliceValues, WASMArgs, ZKWASMCtx,
+  },
 };
 use crate::utils::tracing::split_vector;
-use ff::Field;
+use ff::{Field, PrimeFieldBits};
+use gadgets::hash::hash_native;
 use itertools::Itertools;
 use mcc::{
-  multiset_ops::{avt_tuple_to_scalar_vec, step_RS_WS},
+  multiset_ops::{avt_tuple_to_scalar_vec, kept_values, step_RS_WS},
   BatchedOpsCircuit, OpsCircuit, ScanCircuit,
 };
+use mcc_snark::MCCInstance;
 use nova::{
   nebula::{
     audit_rs::{AuditPublicParams, AuditRecursiveSNARK},
     compression::{CompressedSNARK, NebulaInstance, ProverKey, VerifierKey},
     ic::IC,
-    rs::{PublicParams, RecursiveSNARK},
+    rs::{PublicParams, RecursiveSNARK, StepCircuit},
     traits::{Layer1PPTrait, Layer1RSTrait, MemoryCommitmentsTraits},
   },
   traits::{
@@ -26,15 +30,47 @@ use nova::{
   },
 };
 use serde::{Deserialize, Serialize};
-use wasmi::WitnessVM;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
+use wasmi::{Instruction, StackLimits, TraceHook, WitnessVM, HOST_CALL_ARG, HOST_CALL_RESULT};
 mod gadgets;
-mod mcc;
+pub(crate) mod mcc;
+pub mod mcc_snark;
+mod opening;
+mod streaming;
 mod switchboard;
+mod verifier;
+
+#[cfg(test)]
+mod tests;
+pub use mcc::multiset_ops::validate_multiset;
+pub use opening::StackOpening;
+pub use streaming::StreamingVerifier;
+pub use switchboard::{test_vectors, OpTestVector};
 use switchboard::{BatchedWasmTransitionCircuit, WASMTransitionCircuit};
+pub use verifier::VerifierContext;
 
-/// Maximum number of memory ops allowed per step of the zkVM
+/// Default number of memory ops per step of the zkVM. This is also the minimum, as it is the most
+/// memory ops a single opcode performs, but for the drop keeps of more than two values (see
+/// [`StepSize::set_memory_ops_per_step`]).
 pub const MEMORY_OPS_PER_STEP: usize = 8;
 
+/// Version of the binary format of [`WasmSNARK::to_bytes`], bumped whenever the circuits or the
+/// encoding of a proof change, so older proofs fail to load rather than to verify
+pub const PROOF_FORMAT_VERSION: u32 = 6;
+
+/// Magic bytes a proof serialized by [`WasmSNARK::to_bytes`] starts with
+const PROOF_MAGIC: [u8; 4] = *b"zkWS";
+
+/// Length of the header of [`WasmSNARK::to_bytes`]: the magic bytes, the format version & the
+/// digest of the public parameters
+const PROOF_HEADER_LEN: usize = PROOF_MAGIC.len() + 4 + 32;
+
+/// Callback invoked with each folding step of an execution proof, see
+/// [`WasmSNARK::prove_streaming`]
+pub type StepHook<'a, E> = &'a mut (dyn Send
+           + FnMut(usize, &RecursiveSNARK<E>, <E as Engine>::Scalar) -> Result<(), ZKWASMError>);
+
 /// [`WasmSNARK`] public parameters
 #[derive(Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -47,9 +83,15 @@ where
   execution_pp: PublicParams<E>,
   ops_pp: PublicParams<E>,
   scan_pp: AuditPublicParams<E>,
-  /// Prover and verifier key for final proof compression
+  /// Number of memory ops per step the execution & ops circuits were set up with
+  memory_ops_per_step: usize,
+  /// Label of the application the proofs are made for, see [`WasmSNARK::setup_with_label`]
+  #[serde(default)]
+  app_label: Vec<u8>,
+  /// Prover and verifier key for final proof compression. A `OnceLock`, so the parameters can be
+  /// shared with the threads proving on them.
   #[serde(skip)]
-  pk_and_vk: OnceCell<(ProverKey<E, S1, S2>, VerifierKey<E, S1, S2>)>,
+  pk_and_vk: OnceLock<(ProverKey<E, S1, S2>, VerifierKey<E, S1, S2>)>,
 }
 
 impl<E, S1, S2> WASMPublicParams<E, S1, S2>
@@ -73,6 +115,66 @@ where
       .get_or_init(|| CompressedSNARK::<E, S1, S2>::setup(self).unwrap());
     vk
   }
+
+  /// Label of the application the proofs are made for, empty if there is none
+  pub fn app_label(&self) -> &[u8] {
+    &self.app_label
+  }
+
+  /// Number of memory ops per step the parameters were set up with, which the [`StepSize`] to
+  /// prove with must have, see [`StepSize::set_memory_ops_per_step`]
+  pub fn memory_ops_per_step(&self) -> usize {
+    self.memory_ops_per_step
+  }
+
+  /// Digest of the layout of the circuits the parameters were set up for: the size of the step
+  /// circuit of each phase, the memory ops per step & the application label. Proofs serialized by
+  /// [`WasmSNARK::to_bytes`] carry it, so they only load with parameters of the same layout.
+  pub fn digest(&self) -> [u8; 32] {
+    let layout = (
+      self.circuit_stats(),
+      self.memory_ops_per_step,
+      &self.app_label,
+    );
+    Sha256::digest(bincode::serialize(&layout).unwrap_or_default()).into()
+  }
+
+  /// Number of constraints & variables of the step circuit of each phase, to judge the cost of
+  /// proving on these parameters before running the prover
+  pub fn circuit_stats(&self) -> CircuitStats {
+    let (primary_constraints, secondary_constraints) = self.execution_pp.num_constraints();
+    let (primary_variables, secondary_variables) = self.execution_pp.num_variables();
+    let execution = PhaseStats {
+      primary_constraints,
+      secondary_constraints,
+      primary_variables,
+      secondary_variables,
+    };
+
+    let (primary_constraints, secondary_constraints) = self.ops_pp.num_constraints();
+    let (primary_variables, secondary_variables) = self.ops_pp.num_variables();
+    let ops = PhaseStats {
+      primary_constraints,
+      secondary_constraints,
+      primary_variables,
+      secondary_variables,
+    };
+
+    let (primary_constraints, secondary_constraints) = self.scan_pp.num_constraints();
+    let (primary_variables, secondary_variables) = self.scan_pp.num_variables();
+    let scan = PhaseStats {
+      primary_constraints,
+      secondary_constraints,
+      primary_variables,
+      secondary_variables,
+    };
+
+    CircuitStats {
+      execution,
+      ops,
+      scan,
+    }
+  }
 }
 
 impl<E, S1, S2> Layer1PPTrait<E> for WASMPublicParams<E, S1, S2>
@@ -134,13 +236,23 @@ where
   /// Fn used to obtain setup material for producing succinct arguments for
   /// WASM program executions
   pub fn setup(step_size: StepSize) -> WASMPublicParams<E, S1, S2> {
+    Self::setup_with_label(step_size, &[])
+  }
+
+  /// Obtain setup material for the application labeled `app_label`
+  ///
+  /// The label is absorbed into the transcript deriving the MCC challenges, which bind the
+  /// execution, ops & scan proofs together through their commitments. Proofs made on these
+  /// parameters thus fail to verify on parameters with another label, so proofs of applications
+  /// sharing a verifier can not be recombined.
+  pub fn setup_with_label(step_size: StepSize, app_label: &[u8]) -> WASMPublicParams<E, S1, S2> {
     let execution_pp = PublicParams::<E>::setup(
-      &BatchedWasmTransitionCircuit::empty(step_size.execution),
+      &BatchedWasmTransitionCircuit::empty(step_size.execution, step_size.memory_ops),
       &*default_ck_hint(),
       &*default_ck_hint(),
     );
     let ops_pp = PublicParams::<E>::setup(
-      &BatchedOpsCircuit::empty(step_size.execution),
+      &BatchedOpsCircuit::empty(step_size.execution, step_size.memory_ops),
       &*default_ck_hint(),
       &*default_ck_hint(),
     );
@@ -153,101 +265,338 @@ where
       execution_pp,
       ops_pp,
       scan_pp,
-      pk_and_vk: OnceCell::new(),
+      memory_ops_per_step: step_size.memory_ops,
+      app_label: app_label.to_vec(),
+      pk_and_vk: OnceLock::new(),
     }
   }
 
-  #[tracing::instrument(skip_all, name = "WasmSNARK::prove")]
+  /// Estimate the cost of proving `program` with `step_size`, e.g. to tune the step sizes before
+  /// setting up public parameters for them. The program is run to get its execution trace, but
+  /// nothing is proven.
+  ///
+  /// Returns the errors proving with `step_size` would return before folding any step.
+  pub fn estimate(
+    program: &impl ZKWASMCtx,
+    step_size: StepSize,
+  ) -> Result<ProofEstimate, ZKWASMError> {
+    step_size.validate()?;
+    let (execution_trace, IS, IS_sizes) = program.execution_trace()?;
+    step_size.check_memory_pages(IS_sizes.mem_pages())?;
+
+    // Only the opcodes of the shard are proven, the ones preceding it build its IS
+    let start = program
+      .args()
+      .trace_slice_vals
+      .map_or(0, |slice| slice.start());
+    let execution_steps = execution_trace.len().saturating_sub(start);
+    let execution_rs_steps = execution_steps.div_ceil(step_size.execution);
+    Ok(ProofEstimate {
+      execution_steps,
+      padded_execution_steps: execution_rs_steps * step_size.execution,
+      IS_len: IS.len(),
+      // FS holds the final value of every address of IS
+      FS_len: IS.len(),
+      execution_rs_steps,
+      // Πops proves the memory ops of each batch of opcodes ΠF folds
+      ops_rs_steps: execution_rs_steps,
+      scan_rs_steps: IS.len().div_ceil(step_size.memory),
+      memory_ops_per_step: step_size.memory_ops,
+    })
+  }
+
+  /// Get the memory multisets proving `program` with `step_size` checks, e.g. to find the memory
+  /// op of a program failing the memory-consistency check with [`Multisets::validate`]. The
+  /// program is run to get its execution trace, but nothing is proven.
+  pub fn multisets(
+    program: &impl ZKWASMCtx,
+    step_size: StepSize,
+  ) -> Result<Multisets, ZKWASMError> {
+    step_size.validate()?;
+    let execution_trace = program.execution_trace()?;
+    step_size.check_memory_pages(execution_trace.2.mem_pages())?;
+    let mut witness =
+      build_witness::<E>(execution_trace, program.args().trace_slice_vals, step_size);
+
+    // Build the step circuits, padding included, to fill in the RS & WS of each step
+    witness
+      .circuits
+      .by_ref()
+      .try_for_each(|circuit| circuit.map(drop))?;
+    let StepCircuits {
+      ops_circuits, FS, ..
+    } = witness.circuits;
+    let (RS, WS) = ops_circuits
+      .into_iter()
+      .map(OpsCircuit::into_multisets)
+      .unzip();
+    Ok(Multisets {
+      IS: witness.IS,
+      RS,
+      WS,
+      FS,
+    })
+  }
+
   /// Produce a SNARK for WASM program input
+  ///
+  /// # Determinism
+  ///
+  /// Proving samples no randomness: the MCC challenges are squeezed from a transcript and the trace
+  /// & memory padding is fixed. Proving the same program on the same inputs, step size & parameters
+  /// thus gives the same proof & instance byte for byte (see [`WasmSNARK::to_bytes`]), whatever the
+  /// number of threads, so proofs can be cached & deduplicated. Only the program can make two runs
+  /// differ, e.g. a WASI program reading the clock.
+  ///
+  /// `config` is a [`ProvingConfig`], or a [`StepSize`] to prove with the default one.
   pub fn prove(
     pp: &WASMPublicParams<E, S1, S2>,
     program: &impl ZKWASMCtx,
+    config: impl Into<ProvingConfig<'_>>,
+  ) -> Result<(Self, ZKWASMInstance<E>), ZKWASMError> {
+    Self::prove_inner(pp, program, &config.into(), None, None)
+  }
+
+  /// Produce a SNARK for WASM program input, streaming the execution proof as it is folded
+  ///
+  /// `on_step` is invoked after each folding step of the execution proof with the index of the
+  /// step, the [`RecursiveSNARK`] folded up to it and the commitment it carries, e.g. to send them
+  /// to a [`StreamingVerifier`]. Proving stops at the first error `on_step` returns, which is
+  /// returned.
+  pub fn prove_streaming(
+    pp: &WASMPublicParams<E, S1, S2>,
+    program: &impl ZKWASMCtx,
+    config: impl Into<ProvingConfig<'_>>,
+    on_step: StepHook<'_, E>,
+  ) -> Result<(Self, ZKWASMInstance<E>), ZKWASMError> {
+    Self::prove_inner(pp, program, &config.into(), Some(on_step), None)
+  }
+
+  /// Precompute the commitment `IC_IS` to the initial memory `IS` of an execution, which
+  /// [`WasmSNARK::prove_with_scan`] then reuses instead of committing to IS again, e.g. to prove
+  /// several runs from the same initial memory.
+  ///
+  /// `IS` is the initial memory [`ZKWASMCtx::execution_trace`] returns, which the proof of the
+  /// whole execution starts from. Πscan itself is proven along with the execution, as its grand
+  /// products are over challenges bound to it & to the final memory.
+  pub fn prove_scan(
+    pp: &WASMPublicParams<E, S1, S2>,
+    IS: Vec<(usize, u64, u64)>,
     step_size: StepSize,
+  ) -> Result<(ScanProof, E::Scalar), ZKWASMError> {
+    step_size.validate()?;
+    let (_, IC_IS, _) =
+      build_scan_circuits::<E>(pp.scan(), IS.clone(), IS.clone(), step_size, None);
+    let scan = ScanProof {
+      IS,
+      memory_step_size: step_size.memory,
+    };
+    Ok((scan, IC_IS))
+  }
+
+  /// Produce a SNARK for WASM program input, reusing the commitment `IC_IS` to its initial memory
+  /// precomputed by [`WasmSNARK::prove_scan`]
+  ///
+  /// The proof is the one of [`WasmSNARK::prove`]. Returns [`ZKWASMError::InputMismatch`] if the
+  /// execution does not start from the initial memory of `scan`, or `config` proves it with
+  /// another memory step size, or `IC_IS` is not the commitment to it.
+  pub fn prove_with_scan(
+    pp: &WASMPublicParams<E, S1, S2>,
+    program: &impl ZKWASMCtx,
+    config: impl Into<ProvingConfig<'_>>,
+    scan: &ScanProof,
+    IC_IS: E::Scalar,
+  ) -> Result<(Self, ZKWASMInstance<E>), ZKWASMError> {
+    Self::prove_inner(pp, program, &config.into(), None, Some((scan, IC_IS)))
+  }
+
+  /// Produce a SNARK for the execution trace of a WASM program, e.g. one returned by
+  /// [`ZKWASMCtx::execution_trace`] on another machine and deserialized, without running the
+  /// program again.
+  ///
+  /// `IS` & `IS_sizes` are the initial memory returned along with the trace and `program` the
+  /// bytecode of the traced program, which the proof commits to (see [`program_commitment`]), or
+  /// empty to commit to none.
+  /// The whole trace is proven, so the proof is the one [`WasmSNARK::prove`] produces for the
+  /// unsharded program without memory images. A `config` sharding the execution or feeding it a
+  /// private input returns [`ZKWASMError::InvalidConfig`], as the trace is already executed.
+  pub fn prove_from_trace(
+    pp: &WASMPublicParams<E, S1, S2>,
+    execution_trace: Vec<WitnessVM>,
+    IS: Vec<(usize, u64, u64)>,
+    IS_sizes: ISMemSizes,
+    program: &[u8],
+    config: impl Into<ProvingConfig<'_>>,
+  ) -> Result<(Self, ZKWASMInstance<E>), ZKWASMError> {
+    let config = config.into();
+    config.check_executed()?;
+    Self::prove_trace(
+      pp,
+      (execution_trace, IS, IS_sizes),
+      None,
+      &config,
+      E::Scalar::ZERO,
+      program_commitment(program),
+      None,
+      None,
+    )
+  }
+
+  /// Produce a SNARK for an execution trace consumed step by step, e.g. read from disk or
+  /// received over the network as the program runs, to bound the memory of the prover on long
+  /// executions.
+  ///
+  /// The witnesses of each folding step are built from the next `step_size.execution` opcodes of
+  /// `execution_trace`, and dropped once the step is proven. Only the memory ops (RS & WS) of each
+  /// opcode are kept, for the MCC proofs. The other arguments are the ones of
+  /// [`WasmSNARK::prove_from_trace`], which produces the same proof for the collected trace.
+  ///
+  /// The total of the [`ProvingPhase::Execution`] progress (see [`ProvingConfig::progress`]) is
+  /// the number of steps proven so far plus the ones the size hint of `execution_trace` is sure
+  /// are left, so it is only the final number of steps from the start for a trace of known length.
+  pub fn prove_trace_iter<T>(
+    pp: &WASMPublicParams<E, S1, S2>,
+    execution_trace: T,
+    IS: Vec<(usize, u64, u64)>,
+    IS_sizes: ISMemSizes,
+    program: &[u8],
+    config: impl Into<ProvingConfig<'_>>,
+  ) -> Result<(Self, ZKWASMInstance<E>), ZKWASMError>
+  where
+    T: IntoIterator<Item = WitnessVM>,
+    T::IntoIter: Send,
+  {
+    let config = config.into();
+    config.check_executed()?;
+    Self::check_step_size(pp, config.bounded_step_size(), &IS_sizes)?;
+    // The whole trace is proven, so it starts from IS as is
+    let witness = build_witness::<E>((Vec::new(), IS, IS_sizes), None, config.step_size)
+      .with_trace(execution_trace.into_iter());
+    let program = program_commitment(program);
+    config
+      .install(|| Self::prove_witness(pp, witness, &config, E::Scalar::ZERO, program, None, None))
+  }
+
+  /// Prove `program` as `config` sets, with the args it overrides, reusing the precomputed
+  /// `(scan, IC_IS)` if any
+  fn prove_inner(
+    pp: &WASMPublicParams<E, S1, S2>,
+    program: &impl ZKWASMCtx,
+    config: &ProvingConfig,
+    on_step: Option<StepHook<'_, E>>,
+    scan: Option<(&ScanProof, E::Scalar)>,
+  ) -> Result<(Self, ZKWASMInstance<E>), ZKWASMError> {
+    match config.override_args(program.args())? {
+      Some(args) => {
+        let program = ConfiguredCtx { program, args };
+        Self::prove_execution(pp, &program, config, on_step, scan)
+      }
+      None => Self::prove_execution(pp, program, config, on_step, scan),
+    }
+  }
+
+  /// Run `program` and prove its execution
+  fn prove_execution(
+    pp: &WASMPublicParams<E, S1, S2>,
+    program: &impl ZKWASMCtx,
+    config: &ProvingConfig,
+    on_step: Option<StepHook<'_, E>>,
+    scan: Option<(&ScanProof, E::Scalar)>,
   ) -> Result<(Self, ZKWASMInstance<E>), ZKWASMError> {
     // Run the vm and get the execution trace of the program.
     //
     // # Note:
     //
-    // `start_execution_trace` is an execution trace starting from opcode 0 to opcode `end` from the
-    // WASM program `TraceSliceValues`
+    // The execution trace starts from opcode 0 to opcode `end` from the WASM program
+    // `TraceSliceValues`
     //
     // We do not slice the execution trace at `TraceSliceValues` `start` value because we need the
     // values of the execution trace from *opcode 0 to opcode `start`* to construct the IS for
     // memory checking in continuations/sharding
-    let (start_execution_trace, mut IS, IS_sizes) = program.execution_trace()?;
-
-    /*
-     * Construct IS multiset
-     */
-
-    // Split the execution trace at `TraceSliceValues` `start` value. Use the first half to
-    // construct IS and use the second half for the actual proving of the shard
-    let start = program.args().start();
-    let (IS_execution_trace, mut execution_trace) = split_vector(start_execution_trace, start);
-
-    // We maintain a timestamp counter `globa_ts` that is initialized to
-    // the highest timestamp value in IS.
-    let mut global_ts = 0;
+    let execution_trace = program.execution_trace()?;
+    Self::prove_trace(
+      pp,
+      execution_trace,
+      program.args().trace_slice_vals,
+      config,
+      memory_image_digest(
+        program.args().commitment_hash(),
+        &program.args().memory_images,
+      ),
+      program_commitment(program.args().bytecode()),
+      on_step,
+      scan,
+    )
+  }
 
-    // If this is a shard of a WASM program: calculate shard size & construct IS
-    let is_sharded = program.args().is_sharded();
-    let shard_size = program.args().shard_size().unwrap_or(execution_trace.len());
-    construct_IS(
-      shard_size,
-      step_size,
-      is_sharded,
-      IS_execution_trace,
-      &mut IS,
-      &mut global_ts,
-      &IS_sizes,
-    );
+  /// Prove the shard of `execution_trace` sliced by `trace_slice_vals`, or all of it if `None`.
+  /// `memory_image` is the digest of the memory images loaded in IS, see [`memory_image_digest`],
+  /// and `program` the commitment to the proven bytecode, see [`program_commitment`].
+  #[allow(clippy::too_many_arguments)]
+  fn prove_trace(
+    pp: &WASMPublicParams<E, S1, S2>,
+    execution_trace: ExecutionTrace,
+    trace_slice_vals: Option<TraceSliceValues>,
+    config: &ProvingConfig,
+    memory_image: E::Scalar,
+    program: E::Scalar,
+    on_step: Option<StepHook<'_, E>>,
+    scan: Option<(&ScanProof, E::Scalar)>,
+  ) -> Result<(Self, ZKWASMInstance<E>), ZKWASMError> {
+    Self::check_step_size(pp, config.bounded_step_size(), &execution_trace.2)?;
+    config.install(|| {
+      let witness = build_witness::<E>(execution_trace, trace_slice_vals, config.step_size);
+      Self::prove_witness(pp, witness, config, memory_image, program, on_step, scan)
+    })
+  }
 
-    // Get the highest timestamp in the IS
-    let IS_gts = global_ts;
+  /// Check `step_size` is valid for `pp`, and the linear memory of the execution, of sizes
+  /// `IS_sizes`, is within its maximum
+  fn check_step_size(
+    pp: &WASMPublicParams<E, S1, S2>,
+    step_size: StepSize,
+    IS_sizes: &ISMemSizes,
+  ) -> Result<(), ZKWASMError> {
+    step_size.validate()?;
+    if step_size.memory_ops != pp.memory_ops_per_step {
+      return Err(ZKWASMError::InvalidStepSize(format!(
+        "{} memory ops per step, but the public parameters were set up with {}",
+        step_size.memory_ops, pp.memory_ops_per_step
+      )));
+    }
+    step_size.check_memory_pages(IS_sizes.mem_pages())
+  }
 
-    // Construct RS, WS, & FS multisets for MCC
-    //
-    // # Note:
-    //
-    // * Initialize the RS, and WS multisets as empty, as these will be filled in when we construct
-    //   the step circuits for execution proving
-    //
-    // * IS is already constructed.
-    //
-    // * Initialize the FS multiset to IS, because that will be the starting state of the zkVM which
-    //   we will then modify when we build the execution proving step circuits to derive the actual
-    //   FS.
-    let mut RS: Vec<Vec<(usize, u64, u64)>> = Vec::new();
-    let mut WS: Vec<Vec<(usize, u64, u64)>> = Vec::new();
-    let mut FS = IS.clone();
-
-    // Pad the execution trace, so its length is a multiple of `step_size`.
-    //
-    // 1. This: `step_size.execution - (execution_trace.len() % step_size.execution))` calculates
-    //    the
-    // number of pads needed for execution trace to be a multiple of `step_size.execution`
-    //
-    // 2. We then mod the above value by `step_size.execution` because if the execution trace is
-    //    already a multiple of `step_size.execution` this additional mod makes the pad_len 0
-    let pad_len =
-      (step_size.execution - (execution_trace.len() % step_size.execution)) % step_size.execution;
-    execution_trace.extend((0..pad_len).map(|_| WitnessVM::default()));
-
-    // Build the WASMTransitionCircuit from each traced execution frame and then batch them into
-    // size `step_size`
-    let circuits: Vec<WASMTransitionCircuit> = execution_trace
-      .into_iter()
-      .map(|vm| {
-        let (step_rs, step_ws) = step_RS_WS(&vm, &mut FS, &mut global_ts, &IS_sizes);
-        RS.push(step_rs.clone());
-        WS.push(step_ws.clone());
-        WASMTransitionCircuit::new(vm, step_rs, step_ws, IS_sizes)
-      })
-      .collect();
-    let circuits = circuits
-      .chunks(step_size.execution)
-      .map(|chunk| BatchedWasmTransitionCircuit::new(chunk.to_vec()))
-      .collect::<Vec<_>>();
+  /// Prove the execution of `witness`, building its step circuits as they are proven, with the
+  /// commitment to IS of `scan` if precomputed
+  #[tracing::instrument(skip_all, name = "WasmSNARK::prove")]
+  fn prove_witness<I>(
+    pp: &WASMPublicParams<E, S1, S2>,
+    witness: ExecutionWitness<E, I>,
+    config: &ProvingConfig,
+    memory_image: E::Scalar,
+    program: E::Scalar,
+    mut on_step: Option<StepHook<'_, E>>,
+    scan: Option<(&ScanProof, E::Scalar)>,
+  ) -> Result<(Self, ZKWASMInstance<E>), ZKWASMError>
+  where
+    I: Iterator<Item = WitnessVM>,
+  {
+    let ExecutionWitness {
+      z0,
+      mut circuits,
+      IS,
+      IS_gts,
+    } = witness;
+    let precomputed_IC_IS = match scan {
+      Some((scan, IC_IS)) => {
+        if scan.IS != IS || scan.memory_step_size != config.step_size.memory {
+          return Err(ZKWASMError::InputMismatch("IS".to_string()));
+        }
+        Some(IC_IS)
+      }
+      None => None,
+    };
 
     /*
      * ************** WASM Transition Circuit Proving **************
@@ -257,25 +606,38 @@ where
     //
     // We use commitment-carrying IVC to prove the repeated execution of F
     let mut rs_option: Option<RecursiveSNARK<E>> = None;
-    let z0 = vec![E::Scalar::ZERO];
     let mut IC_i = E::Scalar::ZERO;
     let execution_pp = pp.F();
-    for (i, circuit) in circuits.iter().enumerate() {
-      tracing::debug!("Proving step {}/{}", i + 1, circuits.len());
+    let mut i = 0;
+    while let Some(circuit) = circuits.next() {
+      let circuit = &circuit?;
+      // The steps proven so far and the ones left, which a trace of unknown length only reports as
+      // it is consumed
+      let num_circuits = i + 1 + circuits.size_hint().0;
+      tracing::debug!("Proving step {}/{}", i + 1, num_circuits);
+      let _step = tracing::info_span!("prove_step", phase = %ProvingPhase::Execution).entered();
       let mut rs = rs_option.unwrap_or_else(|| {
         RecursiveSNARK::new(execution_pp, circuit, &z0)
           .expect("failed to construct initial recursive SNARK")
       });
       rs.prove_step(execution_pp, circuit, IC_i)?;
       IC_i = rs.increment_commitment(execution_pp, circuit);
-      rs_option = Some(rs)
+      if let Some(on_step) = on_step.as_mut() {
+        on_step(i, &rs, IC_i)?;
+      }
+      rs_option = Some(rs);
+      if let Some(progress) = config.progress {
+        progress(ProvingPhase::Execution, i + 1, num_circuits);
+      }
+      i += 1;
     }
+    let (ops_circuits, FS, results) = circuits.finish();
 
     // Do an internal check on the final recursive SNARK
     debug_assert!(rs_option.is_some());
     let rs = rs_option.ok_or(ZKWASMError::MalformedRS)?;
     let num_steps = rs.num_steps();
-    rs.verify(execution_pp, num_steps, &z0, IC_i)?;
+    let zi = rs.verify(execution_pp, num_steps, &z0, IC_i)?;
 
     /*
      * ************** Prove grand products for MCC **************
@@ -285,134 +647,58 @@ where
     let ops_pp = pp.ops();
     let scan_pp = pp.scan();
 
-    // Build ops circuits
-    let ops_circuits = RS
-      .into_iter()
-      .zip_eq(WS.into_iter())
-      .map(|(rs, ws)| OpsCircuit::new(rs, ws))
-      .collect::<Vec<_>>();
-    let ops_circuits = ops_circuits
-      .chunks(step_size.execution)
-      .map(|chunk| BatchedOpsCircuit::new(chunk.to_vec()))
-      .collect::<Vec<_>>();
-
-    // Pad IS and FS , so length is a multiple of step_size
-    {
-      let len = IS.len();
-      let pad_len = (step_size.memory - (len % step_size.memory)) % step_size.memory;
-      IS.extend((len..len + pad_len).map(|i| (i, 0, 0)));
-      FS.extend((len..len + pad_len).map(|i| (i, 0, 0)));
-    }
-
-    // sanity check
-    assert_eq!(IS.len() % step_size.memory, 0);
-
-    // Build the Audit MCC circuits.
-    //
-    // 1. To get the challenges alpha and gamma we first have to compute the incremental
-    //    commitmenents to the multisets IS and FS
-    //
-    // 2. We chunk IS and FS into `step_size.memory` sized chunks and build the [`ScanCircuit`]
-    let mut scan_IC_i = (E::Scalar::ZERO, E::Scalar::ZERO);
-    let mut IC_IS = E::Scalar::ZERO;
-    let mut IC_FS = E::Scalar::ZERO;
-    let mut scan_circuits = Vec::new();
-    for (IS_chunk, FS_chunk) in IS
-      .chunks(step_size.memory)
-      .zip_eq(FS.chunks(step_size.memory))
-    {
-      IC_IS = IC::<E>::commit(
-        &scan_pp.ck_primary,
-        &scan_pp.ro_consts,
-        IC_IS,
-        IS_chunk
-          .iter()
-          .flat_map(|avt| avt_tuple_to_scalar_vec(*avt))
-          .collect(),
-      );
-      IC_FS = IC::<E>::commit(
-        &scan_pp.ck_primary,
-        &scan_pp.ro_consts,
-        IC_FS,
-        FS_chunk
-          .iter()
-          .flat_map(|avt| avt_tuple_to_scalar_vec(*avt))
-          .collect(),
-      );
-      let scan_circuit = ScanCircuit::new(IS_chunk.to_vec(), FS_chunk.to_vec());
-      scan_circuits.push(scan_circuit);
-    }
-
-    // Get gamma and alpha
-    let mut keccak = E::TE::new(b"compute MCC challenges");
-    keccak.absorb(b"C_n", &IC_i);
-    keccak.absorb(b"IC_IS", &IC_IS);
-    keccak.absorb(b"IC_FS", &IC_FS);
-    let gamma = keccak.squeeze(b"gamma")?;
-    let alpha = keccak.squeeze(b"alpha")?;
-
-    /*
-     * Grand product checks for RS & WS
-     */
-
-    // z0 <- [gamma, alpha, ts=gts, h_RS=1, h_WS=1]
-    let ops_z0 = vec![
-      gamma,
-      alpha,
-      E::Scalar::from(IS_gts),
-      E::Scalar::ONE,
-      E::Scalar::ONE,
-    ];
-    let mut ops_IC_i = E::Scalar::ZERO;
-    let mut ops_rs_option: Option<RecursiveSNARK<E>> = None;
-    tracing::debug!("Proving MCC ops circuits");
-    for (i, ops_circuit) in ops_circuits.iter().enumerate() {
-      tracing::debug!("Proving step {}/{}", i + 1, ops_circuits.len());
-      let mut ops_rs = ops_rs_option.unwrap_or_else(|| {
-        RecursiveSNARK::new(ops_pp, ops_circuit, &ops_z0)
-          .expect("failed to construct initial recursive SNARK")
-      });
-      ops_rs.prove_step(ops_pp, ops_circuit, ops_IC_i)?;
-      ops_IC_i = ops_rs.increment_commitment(ops_pp, ops_circuit);
-      ops_rs_option = Some(ops_rs)
-    }
+    // Build the Audit MCC circuits & commit to the multisets IS and FS, which are needed to get
+    // the challenges alpha and gamma
+    let (scan_circuits, IC_IS, IC_FS) =
+      build_scan_circuits::<E>(scan_pp, IS, FS, config.step_size, precomputed_IC_IS);
 
-    // internal check
-    let ops_rs = ops_rs_option.ok_or(ZKWASMError::MalformedRS)?;
-    ops_rs.verify(ops_pp, ops_rs.num_steps(), &ops_z0, ops_IC_i)?;
+    // Get gamma and alpha, bound to the context of the proof
+    let context = context_digest(config.context);
+    let app_label = context_digest(&pp.app_label);
+    let (gamma, alpha) = mcc_challenges::<E>(
+      IC_i,
+      IC_IS,
+      IC_FS,
+      app_label,
+      context,
+      memory_image,
+      program,
+    )?;
 
     /*
-     * Grand product checks for IS & FS
+     * Grand product checks for RS & WS and for IS & FS
+     *
+     * Πops & Πscan only depend on the challenges, not on each other, so they are proven
+     * concurrently unless the config disables it (see [`ProvingConfig::parallel`]).
      */
 
-    // z0 <- [gamma, alpha, h_IS=1, h_FS=1]
-    let scan_z0 = vec![gamma, alpha, E::Scalar::ONE, E::Scalar::ONE];
-    let mut scan_rs_option: Option<AuditRecursiveSNARK<E>> = None;
-    tracing::debug!("Proving MCC audit circuits");
-    for (i, scan_circuit) in scan_circuits.iter().enumerate() {
-      tracing::debug!("Proving step {}/{}", i + 1, scan_circuits.len());
-      let mut scan_rs = scan_rs_option.unwrap_or_else(|| {
-        AuditRecursiveSNARK::new(scan_pp, scan_circuit, &scan_z0)
-          .expect("failed to construct initial recursive SNARK")
-      });
-      scan_rs.prove_step(scan_pp, scan_circuit, scan_IC_i)?;
-      scan_IC_i = scan_rs.increment_commitment(scan_pp, scan_circuit);
-      scan_rs_option = Some(scan_rs)
+    let (ops_z0, scan_z0) = mcc_z0::<E>(gamma, alpha, IS_gts);
+    let ops_proof = || prove_ops(ops_pp, &ops_circuits, &ops_z0, config.progress);
+    let scan_proof = || prove_scan(scan_pp, &scan_circuits, &scan_z0, config.progress);
+    let (ops, scan) = if config.parallel {
+      rayon::join(ops_proof, scan_proof)
+    } else {
+      (ops_proof(), scan_proof())
+    };
+    let ((ops_rs, ops_IC_i), (scan_rs, scan_IC_i)) = (ops?, scan?);
+    // Πscan commits to IS as it scans it, which a precomputed IC_IS must be the commitment of
+    if scan_IC_i != (IC_IS, IC_FS) {
+      return Err(ZKWASMError::InputMismatch("IC_IS".to_string()));
     }
 
-    // internal check
-    let scan_rs = scan_rs_option.ok_or(ZKWASMError::MalformedRS)?;
-    scan_rs.verify(scan_pp, scan_rs.num_steps(), &scan_z0, scan_IC_i)?;
-    debug_assert_eq!(scan_IC_i, (IC_IS, IC_FS));
-
     // Instance for [`WasmSNARK`]
     let U = ZKWASMInstance {
       execution_z0: z0,
+      execution_zi: zi,
+      results,
       IC_i,
       ops_z0,
       ops_IC_i,
       scan_z0,
       scan_IC_i,
+      context,
+      memory_image,
+      program,
     };
 
     Ok((
@@ -426,132 +712,862 @@ where
   }
 
   /// Apply Spartan on top of the Nebula IVC proofs
+  ///
+  /// # Note
+  ///
+  /// The compressed SNARK proves the instance it is compressed with, but its verifier does not
+  /// re-derive the MCC challenges from `U`, so it authenticates neither the application label nor
+  /// the context, memory image & program digests of `U`. Proofs bound to a label, a context or
+  /// memory images thus return [`ZKWASMError::NotRecursive`], and so does
+  /// [`WasmSNARK::verify_program`] on a compressed SNARK.
   pub fn compress(
     &self,
     pp: &WASMPublicParams<E, S1, S2>,
     U: &ZKWASMInstance<E>,
   ) -> Result<Self, ZKWASMError> {
     match self {
-      Self::Recursive(rs) => Ok(Self::Compressed(Box::new(CompressedSNARK::prove(
-        pp,
-        pp.pk(),
-        rs.as_ref(),
-        U.into(),
-      )?))),
+      Self::Recursive(rs) => {
+        Self::check_compressed(context_digest(&pp.app_label), U)?;
+        Ok(Self::Compressed(Box::new(CompressedSNARK::prove(
+          pp,
+          pp.pk(),
+          rs.as_ref(),
+          U.into(),
+        )?)))
+      }
       Self::Compressed(..) => Err(ZKWASMError::NotRecursive),
     }
   }
 
+  /// Check a compressed SNARK can stand for `U`, i.e. `U` is bound to neither an application label
+  /// nor a context nor memory images.
+  ///
+  /// The compressed SNARK proves the [`NebulaInstance`] it was compressed with, whose MCC
+  /// challenges its verifier does not re-derive, so nothing ties these digests of `U` to it. Were
+  /// they accepted, a compressed "session-1" proof would verify under "session-2" once `U.context`
+  /// is edited. Requiring them to be zero keeps the checks on them failing closed. The commitment
+  /// to the program is not authenticated either, which [`WasmSNARK::verify_program`] rejects.
+  fn check_compressed(app_label: E::Scalar, U: &ZKWASMInstance<E>) -> Result<(), ZKWASMError> {
+    let unbound = [app_label, U.context, U.memory_image];
+    if unbound.iter().any(|digest| *digest != E::Scalar::ZERO) {
+      return Err(ZKWASMError::NotRecursive);
+    }
+    Ok(())
+  }
+
+  /// Serialize the [`WasmSNARK`] to bytes, e.g. to transmit it to a verifier. The proof is
+  /// prefixed by a header of magic bytes, the [`PROOF_FORMAT_VERSION`] & the
+  /// [`WASMPublicParams::digest`] of `pp`, the parameters it was proven with.
+  pub fn to_bytes(&self, pp: &WASMPublicParams<E, S1, S2>) -> Result<Vec<u8>, ZKWASMError> {
+    let mut bytes = Vec::with_capacity(PROOF_HEADER_LEN);
+    bytes.extend(PROOF_MAGIC);
+    bytes.extend(PROOF_FORMAT_VERSION.to_le_bytes());
+    bytes.extend(pp.digest());
+    bincode::serialize_into(&mut bytes, self)?;
+    Ok(bytes)
+  }
+
+  /// Deserialize a [`WasmSNARK`] serialized with [`WasmSNARK::to_bytes`]. Returns
+  /// [`ZKWASMError::ProofFormatMismatch`] if the bytes are not a proof of this
+  /// [`PROOF_FORMAT_VERSION`], or were serialized for parameters other than `pp`.
+  pub fn from_bytes(bytes: &[u8], pp: &WASMPublicParams<E, S1, S2>) -> Result<Self, ZKWASMError> {
+    if bytes.len() < PROOF_HEADER_LEN || bytes[..PROOF_MAGIC.len()] != PROOF_MAGIC {
+      return Err(ZKWASMError::ProofFormatMismatch(
+        "not a serialized proof".to_string(),
+      ));
+    }
+    let (version, rest) = bytes[PROOF_MAGIC.len()..].split_at(4);
+    let version = u32::from_le_bytes(version.try_into().unwrap_or_default());
+    if version != PROOF_FORMAT_VERSION {
+      return Err(ZKWASMError::ProofFormatMismatch(format!(
+        "proof format version {version}, expected {PROOF_FORMAT_VERSION}"
+      )));
+    }
+    let (digest, proof) = rest.split_at(32);
+    if digest != pp.digest() {
+      return Err(ZKWASMError::ProofFormatMismatch(
+        "proof made for other public parameters".to_string(),
+      ));
+    }
+    Ok(bincode::deserialize(proof)?)
+  }
+
+  /// Get the serialized size of the [`WasmSNARK`] and of each of its IVC proofs, e.g. to decide
+  /// whether to compress it before transmitting it.
+  ///
+  /// # Note
+  ///
+  /// A compressed SNARK is a single proof, so it returns [`ZKWASMError::NotRecursive`]; its size
+  /// is the length of [`WasmSNARK::to_bytes`].
+  pub fn size_report(&self) -> Result<SizeReport, ZKWASMError> {
+    let Self::Recursive(rs) = self else {
+      return Err(ZKWASMError::NotRecursive);
+    };
+    Ok(SizeReport {
+      execution_rs: bincode::serialized_size(&rs.execution_rs)? as usize,
+      ops_rs: bincode::serialized_size(&rs.ops_rs)? as usize,
+      scan_rs: bincode::serialized_size(&rs.scan_rs)? as usize,
+      total: PROOF_HEADER_LEN + bincode::serialized_size(self)? as usize,
+    })
+  }
+
   /// Verify the [`WasmSNARK`]
   pub fn verify(
     &self,
     pp: &WASMPublicParams<E, S1, S2>,
     U: &ZKWASMInstance<E>,
   ) -> Result<(), ZKWASMError> {
-    match self {
-      Self::Recursive(rs) => {
-        // verify F
-        rs.execution_rs
-          .verify(pp.F(), rs.execution_rs.num_steps(), &U.execution_z0, U.IC_i)?;
-
-        // verify F_ops
-        let ops_zi = rs
-          .ops_rs
-          .verify(pp.ops(), rs.ops_rs.num_steps(), &U.ops_z0, U.ops_IC_i)?;
-
-        // verify F_scan
-        let scan_zi =
-          rs.scan_rs
-            .verify(pp.scan(), rs.scan_rs.num_steps(), &U.scan_z0, U.scan_IC_i)?;
-
-        // 1. check h_IS = h_RS = h_WS = h_FS = 1 // initial values are correct
-        let (init_h_is, init_h_rs, init_h_ws, init_h_fs) =
-          { (U.scan_z0[2], U.ops_z0[3], U.ops_z0[4], U.scan_z0[3]) };
-        if init_h_is != E::Scalar::ONE
-          || init_h_rs != E::Scalar::ONE
-          || init_h_ws != E::Scalar::ONE
-          || init_h_fs != E::Scalar::ONE
-        {
-          return Err(ZKWASMError::MultisetVerificationError);
-        }
-
-        // 2. check Cn′ = Cn // commitments carried in both Πops and ΠF are the same
-        if U.IC_i != U.ops_IC_i {
-          return Err(ZKWASMError::MultisetVerificationError);
-        }
+    self.verify_with_context(pp, U, &[])
+  }
 
-        // 3. check γ and γ are derived by hashing C and C′′.
-        // Get alpha and gamma
-        let mut keccak = E::TE::new(b"compute MCC challenges");
-        keccak.absorb(b"C_n", &U.IC_i);
-        keccak.absorb(b"IC_IS", &U.scan_IC_i.0);
-        keccak.absorb(b"IC_FS", &U.scan_IC_i.1);
-        let gamma = keccak.squeeze(b"gamma")?;
-        let alpha = keccak.squeeze(b"alpha")?;
-
-        if U.ops_z0[0] != gamma || U.ops_z0[1] != alpha {
-          return Err(ZKWASMError::MultisetVerificationError);
-        }
+  /// Verify the [`WasmSNARK`] is a proof of the program committed to by `program_commitment`, see
+  /// [`program_commitment`]. Returns [`ZKWASMError::ProgramMismatch`] if the instance commits to
+  /// another program.
+  ///
+  /// # Note
+  ///
+  /// A compressed SNARK does not authenticate the commitment of `U` (see [`WasmSNARK::compress`]),
+  /// so it returns [`ZKWASMError::NotRecursive`] for any program.
+  pub fn verify_program(
+    &self,
+    pp: &WASMPublicParams<E, S1, S2>,
+    U: &ZKWASMInstance<E>,
+    program_commitment: E::Scalar,
+  ) -> Result<(), ZKWASMError> {
+    if matches!(self, Self::Compressed(..)) && program_commitment != E::Scalar::ZERO {
+      return Err(ZKWASMError::NotRecursive);
+    }
+    if U.program != program_commitment {
+      return Err(ZKWASMError::ProgramMismatch);
+    }
+    self.verify(pp, U)
+  }
 
-        // 4. check h_IS' · h_WS' = h_RS' · h_FS'.
+  /// Verify the [`WasmSNARK`] was produced under `context`, see [`ProvingConfig::context`].
+  /// Returns [`ZKWASMError::ContextMismatch`] if the instance commits to another context.
+  pub fn verify_with_context(
+    &self,
+    pp: &WASMPublicParams<E, S1, S2>,
+    U: &ZKWASMInstance<E>,
+    context: &[u8],
+  ) -> Result<(), ZKWASMError> {
+    let app_label = context_digest(&pp.app_label);
+    self.verify_with_parts(pp, || pp.vk(), app_label, U, context)
+  }
 
-        // Inputs for multiset check
-        let (h_is, h_rs, h_ws, h_fs) = { (scan_zi[2], ops_zi[3], ops_zi[4], scan_zi[3]) };
-        if h_is * h_ws != h_rs * h_fs {
-          return Err(ZKWASMError::MultisetVerificationError);
-        }
+  /// Verify the [`WasmSNARK`] with the verifier data derived from `pp`, i.e. the verifier key of
+  /// compressed proofs and the digest of the application label, see [`VerifierContext`].
+  fn verify_with_parts<'a>(
+    &self,
+    pp: &'a WASMPublicParams<E, S1, S2>,
+    vk: impl FnOnce() -> &'a VerifierKey<E, S1, S2>,
+    app_label: E::Scalar,
+    U: &ZKWASMInstance<E>,
+    context: &[u8],
+  ) -> Result<(), ZKWASMError> {
+    if U.context != context_digest(context) {
+      return Err(ZKWASMError::ContextMismatch);
+    }
+    match self {
+      Self::Recursive(rs) => {
+        Self::verify_recursive(pp, app_label, rs, U)?;
+      }
+      Self::Compressed(snark) => {
+        Self::check_compressed(app_label, U)?;
+        let _verify = tracing::info_span!("verify", phase = "Compressed").entered();
+        snark.verify(pp, vk())?
       }
-      Self::Compressed(snark) => snark.verify(pp, pp.vk())?,
     }
 
     Ok(())
   }
-}
-
-/// Helper function to construct IS when WASM program is being sharded
-pub fn construct_IS(
-  shard_size: usize,
-  step_size: StepSize,
-  is_sharded: bool,
-  IS_execution_trace: Vec<WitnessVM>,
-  IS: &mut [(usize, u64, u64)],
-  global_ts: &mut u64,
-  IS_sizes: &ISMemSizes,
-) {
-  // Calculate shard size
-  let sharding_pad_len = if shard_size % step_size.execution != 0 && is_sharded {
-    step_size.execution - (shard_size % step_size.execution)
-  } else {
-    0
-  };
 
-  IS_execution_trace.iter().enumerate().for_each(|(i, vm)| {
-    if i != 0 && i % shard_size == 0 {
-      IS_padding(sharding_pad_len, IS, global_ts, IS_sizes);
-    }
-    let _ = step_RS_WS(vm, IS, global_ts, IS_sizes);
-  });
-  if !IS_execution_trace.is_empty() && is_sharded {
-    IS_padding(sharding_pad_len, IS, global_ts, IS_sizes);
+  /// Verify the [`WasmSNARK`] and return the public outputs it proves, i.e. the values returned by
+  /// the invoked function and the commitments to the initial and final memory.
+  ///
+  /// # Note
+  ///
+  /// The outputs are read from the final IVC state, so this is only supported for
+  /// [`WasmSNARK::Recursive`]; a compressed SNARK returns [`ZKWASMError::NotRecursive`]. They are
+  /// the outputs of a whole execution, so the proof of a shard returns
+  /// [`ZKWASMError::InputMismatch`].
+  pub fn verify_and_extract(
+    &self,
+    pp: &WASMPublicParams<E, S1, S2>,
+    U: &ZKWASMInstance<E>,
+  ) -> Result<ZKWASMOutput<E>, ZKWASMError> {
+    self.verify_and_extract_with_context(pp, U, &[])
   }
-}
 
-fn IS_padding(
-  sharding_pad_len: usize,
-  IS: &mut [(usize, u64, u64)],
-  global_ts: &mut u64,
-  IS_sizes: &ISMemSizes,
-) {
-  for _ in 0..sharding_pad_len {
-    let _ = step_RS_WS(&WitnessVM::default(), IS, global_ts, IS_sizes);
+  /// Verify the [`WasmSNARK`] was produced under `context`, as
+  /// [`WasmSNARK::verify_with_context`] does, and return the public outputs it proves, as
+  /// [`WasmSNARK::verify_and_extract`] does
+  pub fn verify_and_extract_with_context(
+    &self,
+    pp: &WASMPublicParams<E, S1, S2>,
+    U: &ZKWASMInstance<E>,
+    context: &[u8],
+  ) -> Result<ZKWASMOutput<E>, ZKWASMError> {
+    self.extract_with_parts(pp, context_digest(&pp.app_label), U, context)
   }
-}
 
-impl<E> Layer1RSTrait<E> for RecursiveWasmSNARK<E>
-where
-  E: CurveCycleEquipped,
-{
-  fn F(&self) -> &RecursiveSNARK<E> {
+  /// [`WasmSNARK::verify_and_extract_with_context`] with the digest of the application label of
+  /// `pp`
+  fn extract_with_parts(
+    &self,
+    pp: &WASMPublicParams<E, S1, S2>,
+    app_label: E::Scalar,
+    U: &ZKWASMInstance<E>,
+    context: &[u8],
+  ) -> Result<ZKWASMOutput<E>, ZKWASMError> {
+    if U.context != context_digest(context) {
+      return Err(ZKWASMError::ContextMismatch);
+    }
+    let Self::Recursive(rs) = self else {
+      return Err(ZKWASMError::NotRecursive);
+    };
+    let output = Self::verify_recursive(pp, app_label, rs, U)?;
+
+    // The outputs are the ones of the invoked function only if the proof starts from the initial
+    // state of an unsharded run: no kept value, no host call i/o, no instruction executed, no
+    // return & no branch yet. Otherwise the prover could pick, e.g., the instructions counted
+    // before the proven ones. The initial page count is the one of IS, which the proof commits to,
+    // and the table layout the one of the program, see `ZKWASMInstance::expected_inputs`.
+    let [kept, returned] = Returns::default().hashes();
+    let initial_z0 = [
+      E::Scalar::from(last_kept_value(&[])),
+      host_io_hash(&[]),
+      E::Scalar::ZERO,
+      kept,
+      returned,
+      E::Scalar::from(branch_target(&[], &[])),
+    ];
+    let z0_without_layout = U
+      .execution_z0
+      .iter()
+      .enumerate()
+      .filter(|(i, _)| ![3, 6, 7].contains(i))
+      .map(|(_, scalar)| scalar);
+    if !z0_without_layout.eq(&initial_z0) {
+      return Err(ZKWASMError::InputMismatch("execution_z0".to_string()));
+    }
+    Ok(output)
+  }
+
+  /// Verify the three Nebula IVC proofs and the multiset checks binding them together, with the
+  /// MCC challenges derived under the digest `app_label` of the application label of `pp`.
+  ///
+  /// Each proof is verified in a `verify` span of its phase, so the time spent verifying each of
+  /// them shows up in the traces, e.g. in a Chrome trace with the `chrome-trace` feature.
+  fn verify_recursive(
+    pp: &WASMPublicParams<E, S1, S2>,
+    app_label: E::Scalar,
+    rs: &RecursiveWasmSNARK<E>,
+    U: &ZKWASMInstance<E>,
+  ) -> Result<ZKWASMOutput<E>, ZKWASMError> {
+    // verify F
+    let execution_zi = {
+      let _verify = tracing::info_span!("verify", phase = %ProvingPhase::Execution).entered();
+      rs.execution_rs
+        .verify(pp.F(), rs.execution_rs.num_steps(), &U.execution_z0, U.IC_i)?
+    };
+    if execution_zi != U.execution_zi {
+      return Err(ZKWASMError::OutputMismatch);
+    }
+    // and the results claimed in `U` are the ones the final output commits to
+    let return_values = U.return_values()?;
+
+    // check Cn′ = Cn // commitments carried in both Πops and ΠF are the same
+    if U.IC_i != U.ops_IC_i {
+      return Err(ZKWASMError::MultisetVerificationError);
+    }
+
+    // verify F_ops & F_scan and the multiset checks. As Cn′ = Cn the challenges are derived from
+    // Cn′, which binds the scan proof to the execution.
+    let mcc_U = MCCInstance {
+      ops_z0: U.ops_z0.clone(),
+      ops_IC_i: U.ops_IC_i,
+      scan_z0: U.scan_z0.clone(),
+      scan_IC_i: U.scan_IC_i,
+    };
+    verify_mcc(
+      pp.ops(),
+      pp.scan(),
+      &rs.ops_rs,
+      &rs.scan_rs,
+      &mcc_U,
+      app_label,
+      U.context,
+      U.memory_image,
+      U.program,
+    )?;
+
+    Ok(ZKWASMOutput {
+      stack_top: scalar_to_u64(execution_zi[0]).ok_or(ZKWASMError::MalformedRS)?,
+      return_values,
+      instruction_count: U.instruction_count()?,
+      memory_pages: U.memory_pages()?,
+      C_IS: U.scan_IC_i.0,
+      C_FS: U.scan_IC_i.1,
+    })
+  }
+}
+
+/// Verify the MCC proofs Πops & Πscan of `U`, and that their multisets satisfy
+/// h_IS · h_WS = h_RS · h_FS. The challenges are derived under the digests of the application
+/// label, of the proof's `context`, of its `memory_image` and of its `program`.
+#[allow(clippy::too_many_arguments)]
+fn verify_mcc<E>(
+  ops_pp: &PublicParams<E>,
+  scan_pp: &AuditPublicParams<E>,
+  ops_rs: &RecursiveSNARK<E>,
+  scan_rs: &AuditRecursiveSNARK<E>,
+  U: &MCCInstance<E>,
+  app_label: E::Scalar,
+  context: E::Scalar,
+  memory_image: E::Scalar,
+  program: E::Scalar,
+) -> Result<(), ZKWASMError>
+where
+  E: CurveCycleEquipped,
+{
+  // verify F_ops
+  let ops_zi = {
+    let _verify = tracing::info_span!("verify", phase = %ProvingPhase::Ops).entered();
+    ops_rs.verify(ops_pp, ops_rs.num_steps(), &U.ops_z0, U.ops_IC_i)?
+  };
+
+  // verify F_scan
+  let scan_zi = {
+    let _verify = tracing::info_span!("verify", phase = %ProvingPhase::Scan).entered();
+    scan_rs.verify(scan_pp, scan_rs.num_steps(), &U.scan_z0, U.scan_IC_i)?
+  };
+
+  // 1. check h_IS = h_RS = h_WS = h_FS = 1 // initial values are correct
+  let (init_h_is, init_h_rs, init_h_ws, init_h_fs) =
+    { (U.scan_z0[2], U.ops_z0[3], U.ops_z0[4], U.scan_z0[3]) };
+  if init_h_is != E::Scalar::ONE
+    || init_h_rs != E::Scalar::ONE
+    || init_h_ws != E::Scalar::ONE
+    || init_h_fs != E::Scalar::ONE
+  {
+    return Err(ZKWASMError::MultisetVerificationError);
+  }
+
+  // 2. check γ and α are derived by hashing Cn′ and C′′.
+  let (gamma, alpha) = mcc_challenges::<E>(
+    U.ops_IC_i,
+    U.scan_IC_i.0,
+    U.scan_IC_i.1,
+    app_label,
+    context,
+    memory_image,
+    program,
+  )?;
+
+  // Both Πops and Πscan must fingerprint with these challenges. Otherwise the scan proof is not
+  // bound to the RS & WS, and could come from a different run with its own challenges.
+  if U.ops_z0[0] != gamma || U.ops_z0[1] != alpha {
+    return Err(ZKWASMError::MultisetVerificationError);
+  }
+  if U.scan_z0[0] != gamma || U.scan_z0[1] != alpha {
+    return Err(ZKWASMError::MultisetVerificationError);
+  }
+
+  // 3. check h_IS' · h_WS' = h_RS' · h_FS'.
+
+  // Inputs for multiset check
+  let (h_is, h_rs, h_ws, h_fs) = { (scan_zi[2], ops_zi[3], ops_zi[4], scan_zi[3]) };
+  if h_is * h_ws != h_rs * h_fs {
+    return Err(ZKWASMError::MultisetVerificationError);
+  }
+
+  Ok(())
+}
+
+/// The witness of the execution proof & of the RS & WS grand products
+struct ExecutionWitness<E, I>
+where
+  E: CurveCycleEquipped,
+{
+  /// Initial public input of the execution proof
+  z0: Vec<E::Scalar>,
+  /// Batched step circuits of the execution proof, which also build the RS & WS grand products
+  circuits: StepCircuits<I>,
+  /// Initial memory state of the execution
+  IS: Vec<(usize, u64, u64)>,
+  /// Highest timestamp in IS
+  IS_gts: u64,
+}
+
+impl<E, I> ExecutionWitness<E, I>
+where
+  E: CurveCycleEquipped,
+{
+  /// Build the step circuits from `execution_trace` instead, proving it from the same initial
+  /// state
+  fn with_trace<T>(self, execution_trace: T) -> ExecutionWitness<E, T> {
+    ExecutionWitness {
+      z0: self.z0,
+      circuits: self.circuits.with_trace(execution_trace),
+      IS: self.IS,
+      IS_gts: self.IS_gts,
+    }
+  }
+}
+
+/// The batched step circuits of the execution proof, built from the execution trace as they are
+/// iterated over. Only the RS & WS of each step are kept, to build the step circuits of Πops once
+/// the trace is consumed, so each witness of the trace can be dropped once its step is proven.
+struct StepCircuits<I> {
+  execution_trace: I,
+  step_size: StepSize,
+  IS_sizes: ISMemSizes,
+  /// Memory state after the steps built so far
+  FS: Vec<(usize, u64, u64)>,
+  global_ts: u64,
+  ops_circuits: Vec<OpsCircuit>,
+  /// Returns of the steps built so far
+  returns: Returns,
+}
+
+impl<I> StepCircuits<I> {
+  fn with_trace<T>(self, execution_trace: T) -> StepCircuits<T> {
+    StepCircuits {
+      execution_trace,
+      step_size: self.step_size,
+      IS_sizes: self.IS_sizes,
+      FS: self.FS,
+      global_ts: self.global_ts,
+      ops_circuits: self.ops_circuits,
+      returns: self.returns,
+    }
+  }
+
+  /// Build the circuit of the step `vm`, or of a padding step. Errors if the memory ops of `vm` do
+  /// not fit in a step.
+  fn step(&mut self, vm: WitnessVM, padding: bool) -> Result<WASMTransitionCircuit, ZKWASMError> {
+    let (step_rs, step_ws) = step_RS_WS(
+      &vm,
+      &mut self.FS,
+      &mut self.global_ts,
+      &self.IS_sizes,
+      self.step_size.memory_ops,
+    );
+    if step_rs.len() > self.step_size.memory_ops / 2 {
+      return Err(ZKWASMError::InvalidStepSize(format!(
+        "{:?} performs {} memory ops, but the step size has {} per step",
+        vm.instr,
+        step_rs.len() * 2,
+        self.step_size.memory_ops
+      )));
+    }
+    self
+      .ops_circuits
+      .push(OpsCircuit::new(step_rs.clone(), step_ws.clone()));
+    let circuit = WASMTransitionCircuit::new(vm, step_rs, step_ws, self.IS_sizes);
+    Ok(if padding {
+      circuit.into_padding()
+    } else {
+      circuit
+    })
+  }
+
+  /// Batch the RS & WS of the steps built into the step circuits of Πops, and get FS & the
+  /// results of the last return
+  fn finish(self) -> (Vec<BatchedOpsCircuit>, Vec<(usize, u64, u64)>, Vec<u64>) {
+    let ops_circuits = self
+      .ops_circuits
+      .chunks(self.step_size.execution)
+      .map(|chunk| BatchedOpsCircuit::new(chunk.to_vec()))
+      .collect();
+    (ops_circuits, self.FS, self.returns.results())
+  }
+}
+
+impl<I> Iterator for StepCircuits<I>
+where
+  I: Iterator<Item = WitnessVM>,
+{
+  type Item = Result<BatchedWasmTransitionCircuit, ZKWASMError>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let mut steps = Vec::with_capacity(self.step_size.execution);
+    while steps.len() < self.step_size.execution {
+      let Some(vm) = self.execution_trace.next() else {
+        break;
+      };
+      self.returns.step(&vm);
+      match self.step(vm, false) {
+        Ok(step) => steps.push(step),
+        Err(err) => return Some(Err(err)),
+      }
+    }
+    if steps.is_empty() {
+      return None;
+    }
+
+    // Pad the last batch to `step_size.execution` steps
+    while steps.len() < self.step_size.execution {
+      match self.step(WitnessVM::default(), true) {
+        Ok(step) => steps.push(step),
+        Err(err) => return Some(Err(err)),
+      }
+    }
+    Some(Ok(BatchedWasmTransitionCircuit::new(steps)))
+  }
+
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let batches = |len: usize| len.div_ceil(self.step_size.execution);
+    let (lower, upper) = self.execution_trace.size_hint();
+    (batches(lower), upper.map(batches))
+  }
+}
+
+/// Build the [`ExecutionWitness`] of the shard of `execution_trace` sliced by `trace_slice_vals`,
+/// or of all of it if `None`
+fn build_witness<E>(
+  (start_execution_trace, mut IS, IS_sizes): ExecutionTrace,
+  trace_slice_vals: Option<TraceSliceValues>,
+  step_size: StepSize,
+) -> ExecutionWitness<E, std::vec::IntoIter<WitnessVM>>
+where
+  E: CurveCycleEquipped,
+{
+  /*
+   * Construct IS multiset
+   */
+
+  // Split the execution trace at `TraceSliceValues` `start` value. Use the first half to
+  // construct IS and use the second half for the actual proving of the shard
+  let start = trace_slice_vals.map_or(0, |slice| slice.start());
+  let (IS_execution_trace, execution_trace) = split_vector(start_execution_trace, start);
+
+  // The execution circuit carries the last kept value (the stack top on return), the hash of
+  // the host call i/o, the number of executed instructions, the page count and the hashes of the
+  // kept & returned values in `z`, so a shard starts from the stack top left by the opcodes
+  // preceding it, the hash of the host calls they made, their count and their returns.
+  let mut z0 = vec![
+    E::Scalar::from(last_kept_value(&IS_execution_trace)),
+    host_io_hash(&IS_execution_trace),
+    E::Scalar::from(IS_execution_trace.len() as u64),
+  ];
+  let returns = Returns::from_trace(&IS_execution_trace);
+  let target = branch_target(&IS_execution_trace, &execution_trace);
+
+  // We maintain a timestamp counter `globa_ts` that is initialized to
+  // the highest timestamp value in IS.
+  let mut global_ts = 0;
+
+  // If this is a shard of a WASM program: calculate shard size & construct IS
+  let is_sharded = start != 0;
+  let shard_size = trace_slice_vals
+    .and_then(|slice| slice.shard_size())
+    .unwrap_or(execution_trace.len());
+  construct_IS(
+    shard_size,
+    step_size,
+    is_sharded,
+    IS_execution_trace,
+    &mut IS,
+    &mut global_ts,
+    &IS_sizes,
+  );
+
+  // The execution circuit also carries the page count of linear memory in `z`, which a shard starts
+  // from as the opcodes preceding it leave it in IS, the address & number of entries of the
+  // default function table, which the table opcodes index, and the target of the branch preceding
+  // the first opcode.
+  z0.push(E::Scalar::from(IS[IS_sizes.pages_addr()].1));
+  z0.extend(returns.hashes::<E::Scalar>());
+  z0.extend([IS_sizes.table_addr(), IS_sizes.table_len()].map(|v| E::Scalar::from(v as u64)));
+  z0.push(E::Scalar::from(target));
+
+  // Get the highest timestamp in the IS
+  let IS_gts = global_ts;
+
+  // Build the step circuits of the execution & Πops as the execution is proven
+  //
+  // # Note:
+  //
+  // * The RS & WS multisets are filled in as the step circuits are built, and batched into the
+  //   step circuits of Πops once all are.
+  //
+  // * IS is already constructed.
+  //
+  // * FS starts from IS, because that is the starting state of the zkVM which the steps then
+  //   modify to derive the actual FS.
+  //
+  // * The last batch of steps is padded to `step_size.execution` steps.
+  let circuits = StepCircuits {
+    execution_trace: execution_trace.into_iter(),
+    step_size,
+    IS_sizes,
+    FS: IS.clone(),
+    global_ts,
+    ops_circuits: Vec::new(),
+    returns,
+  };
+
+  ExecutionWitness {
+    z0,
+    circuits,
+    IS,
+    IS_gts,
+  }
+}
+
+/// The initial public inputs of Πops & Πscan, bound to the MCC challenges `gamma` & `alpha`
+fn mcc_z0<E>(gamma: E::Scalar, alpha: E::Scalar, IS_gts: u64) -> (Vec<E::Scalar>, Vec<E::Scalar>)
+where
+  E: CurveCycleEquipped,
+{
+  // ops z0 <- [gamma, alpha, ts=gts, h_RS=1, h_WS=1]
+  let ops_z0 = vec![
+    gamma,
+    alpha,
+    E::Scalar::from(IS_gts),
+    E::Scalar::ONE,
+    E::Scalar::ONE,
+  ];
+  // scan z0 <- [gamma, alpha, h_IS=1, h_FS=1]
+  let scan_z0 = vec![gamma, alpha, E::Scalar::ONE, E::Scalar::ONE];
+  (ops_z0, scan_z0)
+}
+
+/// The initial memory IS of an execution & the memory step size it is committed with, from
+/// [`WasmSNARK::prove_scan`], along with the commitment `IC_IS` to it. Reusable by
+/// [`WasmSNARK::prove_with_scan`] across proofs of executions starting from IS.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanProof {
+  IS: Vec<(usize, u64, u64)>,
+  memory_step_size: usize,
+}
+
+/// Pad IS and FS to a multiple of `step_size.memory` and chunk them into [`ScanCircuit`]s.
+///
+/// Also returns the incremental commitments to IS and FS, which the MCC challenges are derived
+/// from. The commitment to IS is `IC_IS` if precomputed (see [`WasmSNARK::prove_scan`]).
+fn build_scan_circuits<E>(
+  scan_pp: &AuditPublicParams<E>,
+  mut IS: Vec<(usize, u64, u64)>,
+  mut FS: Vec<(usize, u64, u64)>,
+  step_size: StepSize,
+  IC_IS: Option<E::Scalar>,
+) -> (Vec<ScanCircuit>, E::Scalar, E::Scalar)
+where
+  E: CurveCycleEquipped,
+{
+  // Pad IS and FS , so length is a multiple of step_size
+  {
+    let len = IS.len();
+    let pad_len = (step_size.memory - (len % step_size.memory)) % step_size.memory;
+    IS.extend((len..len + pad_len).map(|i| (i, 0, 0)));
+    FS.extend((len..len + pad_len).map(|i| (i, 0, 0)));
+  }
+
+  // sanity check
+  assert_eq!(IS.len() % step_size.memory, 0);
+
+  // We chunk IS and FS into `step_size.memory` sized chunks and build the [`ScanCircuit`]
+  let precomputed_IC_IS = IC_IS;
+  let mut IC_IS = E::Scalar::ZERO;
+  let mut IC_FS = E::Scalar::ZERO;
+  let mut scan_circuits = Vec::new();
+  for (IS_chunk, FS_chunk) in IS
+    .chunks(step_size.memory)
+    .zip_eq(FS.chunks(step_size.memory))
+  {
+    if precomputed_IC_IS.is_none() {
+      IC_IS = IC::<E>::commit(
+        &scan_pp.ck_primary,
+        &scan_pp.ro_consts,
+        IC_IS,
+        IS_chunk
+          .iter()
+          .flat_map(|avt| avt_tuple_to_scalar_vec(*avt))
+          .collect(),
+      );
+    }
+    IC_FS = IC::<E>::commit(
+      &scan_pp.ck_primary,
+      &scan_pp.ro_consts,
+      IC_FS,
+      FS_chunk
+        .iter()
+        .flat_map(|avt| avt_tuple_to_scalar_vec(*avt))
+        .collect(),
+    );
+    let scan_circuit = ScanCircuit::new(IS_chunk.to_vec(), FS_chunk.to_vec());
+    scan_circuits.push(scan_circuit);
+  }
+  (scan_circuits, precomputed_IC_IS.unwrap_or(IC_IS), IC_FS)
+}
+
+/// Prove the grand products of the RS & WS multisets, returns the recursive SNARK and its
+/// incremental commitment
+fn prove_ops<E>(
+  ops_pp: &PublicParams<E>,
+  ops_circuits: &[BatchedOpsCircuit],
+  ops_z0: &[E::Scalar],
+  progress: Option<&(dyn Fn(ProvingPhase, usize, usize) + Sync)>,
+) -> Result<(RecursiveSNARK<E>, E::Scalar), ZKWASMError>
+where
+  E: CurveCycleEquipped,
+{
+  let mut ops_IC_i = E::Scalar::ZERO;
+  let mut ops_rs_option: Option<RecursiveSNARK<E>> = None;
+  tracing::debug!("Proving MCC ops circuits");
+  for (i, ops_circuit) in ops_circuits.iter().enumerate() {
+    tracing::debug!("Proving step {}/{}", i + 1, ops_circuits.len());
+    let _step = tracing::info_span!("prove_step", phase = %ProvingPhase::Ops).entered();
+    let mut ops_rs = ops_rs_option.unwrap_or_else(|| {
+      RecursiveSNARK::new(ops_pp, ops_circuit, ops_z0)
+        .expect("failed to construct initial recursive SNARK")
+    });
+    ops_rs.prove_step(ops_pp, ops_circuit, ops_IC_i)?;
+    ops_IC_i = ops_rs.increment_commitment(ops_pp, ops_circuit);
+    ops_rs_option = Some(ops_rs);
+    if let Some(progress) = progress {
+      progress(ProvingPhase::Ops, i + 1, ops_circuits.len());
+    }
+  }
+
+  // internal check
+  let ops_rs = ops_rs_option.ok_or(ZKWASMError::MalformedRS)?;
+  ops_rs.verify(ops_pp, ops_rs.num_steps(), ops_z0, ops_IC_i)?;
+  Ok((ops_rs, ops_IC_i))
+}
+
+/// Prove the grand products of the IS & FS multisets, returns the recursive SNARK and its
+/// incremental commitments
+///
+/// # Note
+///
+/// The grand products are over the challenges derived from the commitments to the execution, IS &
+/// FS, and each step scans an IS chunk along with the FS chunk the execution leaves, so only the
+/// commitment to IS is precomputed ahead of the execution, see [`WasmSNARK::prove_scan`].
+fn prove_scan<E>(
+  scan_pp: &AuditPublicParams<E>,
+  scan_circuits: &[ScanCircuit],
+  scan_z0: &[E::Scalar],
+  progress: Option<&(dyn Fn(ProvingPhase, usize, usize) + Sync)>,
+) -> Result<(AuditRecursiveSNARK<E>, (E::Scalar, E::Scalar)), ZKWASMError>
+where
+  E: CurveCycleEquipped,
+{
+  let mut scan_IC_i = (E::Scalar::ZERO, E::Scalar::ZERO);
+  let mut scan_rs_option: Option<AuditRecursiveSNARK<E>> = None;
+  tracing::debug!("Proving MCC audit circuits");
+  for (i, scan_circuit) in scan_circuits.iter().enumerate() {
+    tracing::debug!("Proving step {}/{}", i + 1, scan_circuits.len());
+    let _step = tracing::info_span!("prove_step", phase = %ProvingPhase::Scan).entered();
+    let mut scan_rs = scan_rs_option.unwrap_or_else(|| {
+      AuditRecursiveSNARK::new(scan_pp, scan_circuit, scan_z0)
+        .expect("failed to construct initial recursive SNARK")
+    });
+    scan_rs.prove_step(scan_pp, scan_circuit, scan_IC_i)?;
+    scan_IC_i = scan_rs.increment_commitment(scan_pp, scan_circuit);
+    scan_rs_option = Some(scan_rs);
+    if let Some(progress) = progress {
+      progress(ProvingPhase::Scan, i + 1, scan_circuits.len());
+    }
+  }
+
+  // internal check
+  let scan_rs = scan_rs_option.ok_or(ZKWASMError::MalformedRS)?;
+  scan_rs.verify(scan_pp, scan_rs.num_steps(), scan_z0, scan_IC_i)?;
+  Ok((scan_rs, scan_IC_i))
+}
+
+/// Derive the MCC challenges γ and α from the execution commitment C_n and the IS & FS commitments.
+///
+/// Non-zero `app_label`, `context`, `memory_image` & `program` digests are absorbed too, while
+/// proofs without them derive the same challenges as before these were supported.
+fn mcc_challenges<E>(
+  C_n: E::Scalar,
+  IC_IS: E::Scalar,
+  IC_FS: E::Scalar,
+  app_label: E::Scalar,
+  context: E::Scalar,
+  memory_image: E::Scalar,
+  program: E::Scalar,
+) -> Result<(E::Scalar, E::Scalar), ZKWASMError>
+where
+  E: CurveCycleEquipped,
+{
+  let mut keccak = E::TE::new(b"compute MCC challenges");
+  if app_label != E::Scalar::ZERO {
+    keccak.absorb(b"app_label", &app_label);
+  }
+  keccak.absorb(b"C_n", &C_n);
+  keccak.absorb(b"IC_IS", &IC_IS);
+  keccak.absorb(b"IC_FS", &IC_FS);
+  if context != E::Scalar::ZERO {
+    keccak.absorb(b"context", &context);
+  }
+  if memory_image != E::Scalar::ZERO {
+    keccak.absorb(b"memory_image", &memory_image);
+  }
+  if program != E::Scalar::ZERO {
+    keccak.absorb(b"program", &program);
+  }
+  let gamma = keccak.squeeze(b"gamma")?;
+  let alpha = keccak.squeeze(b"alpha")?;
+  Ok((gamma, alpha))
+}
+
+/// Helper function to construct IS when WASM program is being sharded
+pub fn construct_IS(
+  shard_size: usize,
+  step_size: StepSize,
+  is_sharded: bool,
+  IS_execution_trace: Vec<WitnessVM>,
+  IS: &mut [(usize, u64, u64)],
+  global_ts: &mut u64,
+  IS_sizes: &ISMemSizes,
+) {
+  // Calculate shard size
+  let sharding_pad_len = if shard_size % step_size.execution != 0 && is_sharded {
+    step_size.execution - (shard_size % step_size.execution)
+  } else {
+    0
+  };
+
+  IS_execution_trace.iter().enumerate().for_each(|(i, vm)| {
+    if i != 0 && i % shard_size == 0 {
+      IS_padding(sharding_pad_len, step_size, IS, global_ts, IS_sizes);
+    }
+    let _ = step_RS_WS(vm, IS, global_ts, IS_sizes, step_size.memory_ops);
+  });
+  if !IS_execution_trace.is_empty() && is_sharded {
+    IS_padding(sharding_pad_len, step_size, IS, global_ts, IS_sizes);
+  }
+}
+
+fn IS_padding(
+  sharding_pad_len: usize,
+  step_size: StepSize,
+  IS: &mut [(usize, u64, u64)],
+  global_ts: &mut u64,
+  IS_sizes: &ISMemSizes,
+) {
+  for _ in 0..sharding_pad_len {
+    let _ = step_RS_WS(
+      &WitnessVM::default(),
+      IS,
+      global_ts,
+      IS_sizes,
+      step_size.memory_ops,
+    );
+  }
+}
+
+impl<E> Layer1RSTrait<E> for RecursiveWasmSNARK<E>
+where
+  E: CurveCycleEquipped,
+{
+  fn F(&self) -> &RecursiveSNARK<E> {
     &self.execution_rs
   }
 
@@ -564,6 +1580,86 @@ where
   }
 }
 
+/// Serialized size in bytes of a recursive [`WasmSNARK`], see [`WasmSNARK::size_report`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeReport {
+  /// Size of ΠF, the proof of the execution
+  pub execution_rs: usize,
+  /// Size of Πops, the proof of the memory ops of the execution
+  pub ops_rs: usize,
+  /// Size of Πscan, the proof of the scan of the initial & final memory
+  pub scan_rs: usize,
+  /// Size of the whole [`WasmSNARK`], i.e. the length of [`WasmSNARK::to_bytes`]
+  pub total: usize,
+}
+
+/// Cost of proving a WASM program, see [`WasmSNARK::estimate`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofEstimate {
+  /// Number of opcodes proven
+  pub execution_steps: usize,
+  /// Number of opcodes proven once padded to a multiple of the execution step size
+  pub padded_execution_steps: usize,
+  /// Length of IS, the initial memory
+  pub IS_len: usize,
+  /// Length of FS, the final memory
+  pub FS_len: usize,
+  /// Number of folding steps of ΠF, the proof of the execution
+  pub execution_rs_steps: usize,
+  /// Number of folding steps of Πops, the proof of the memory ops of the execution
+  pub ops_rs_steps: usize,
+  /// Number of folding steps of Πscan, the proof of the scan of the initial & final memory
+  pub scan_rs_steps: usize,
+  /// Number of memory ops each opcode is padded to, see [`StepSize::set_memory_ops_per_step`]
+  pub memory_ops_per_step: usize,
+}
+
+/// Memory multisets of the memory-consistency check of an execution, as (address, value,
+/// timestamp) tuples, see [`WasmSNARK::multisets`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Multisets {
+  /// Initial memory state
+  pub IS: Vec<(usize, u64, u64)>,
+  /// Reads of each step, padding steps included
+  pub RS: Vec<Vec<(usize, u64, u64)>>,
+  /// Writes of each step, padding steps included
+  pub WS: Vec<Vec<(usize, u64, u64)>>,
+  /// Final memory state
+  pub FS: Vec<(usize, u64, u64)>,
+}
+
+impl Multisets {
+  /// Check the multisets in the clear, see [`validate_multiset`]
+  pub fn validate(&self) -> Result<(), ZKWASMError> {
+    validate_multiset(&self.RS.concat(), &self.WS.concat(), &self.IS, &self.FS)
+  }
+}
+
+/// Size of the circuits of [`WASMPublicParams`], see [`WASMPublicParams::circuit_stats`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitStats {
+  /// Circuit of ΠF, the proof of the execution
+  pub execution: PhaseStats,
+  /// Circuit of Πops, the proof of the memory ops of the execution
+  pub ops: PhaseStats,
+  /// Circuit of Πscan, the proof of the scan of the initial & final memory
+  pub scan: PhaseStats,
+}
+
+/// Size of the folded circuit of a phase, i.e. its step circuit augmented with the folding
+/// verifier, on the primary & secondary curves
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhaseStats {
+  /// Number of constraints on the primary curve
+  pub primary_constraints: usize,
+  /// Number of constraints on the secondary curve
+  pub secondary_constraints: usize,
+  /// Number of variables on the primary curve
+  pub primary_variables: usize,
+  /// Number of variables on the secondary curve
+  pub secondary_variables: usize,
+}
+
 /// Public i/o for WASM execution proving
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -573,6 +1669,9 @@ where
 {
   // execution instance
   execution_z0: Vec<E::Scalar>,
+  execution_zi: Vec<E::Scalar>,
+  // results of the last return, which `execution_zi` commits to
+  results: Vec<u64>,
   IC_i: E::Scalar,
 
   // ops instance
@@ -582,8 +1681,19 @@ where
   // scan instance
   scan_z0: Vec<E::Scalar>,
   scan_IC_i: (E::Scalar, E::Scalar),
+
+  // digest of the context the proof is bound to, zero if it has none
+  context: E::Scalar,
+
+  // digest of the memory images loaded in IS, zero if there are none
+  memory_image: E::Scalar,
+
+  // commitment to the bytecode of the proven program, zero if it was proven without it
+  program: E::Scalar,
 }
 
+// The context, memory image & program digests are only absorbed into the MCC challenges of
+// `ops_z0` & `scan_z0`, see [`WasmSNARK::check_compressed`].
 impl<E> From<&ZKWASMInstance<E>> for NebulaInstance<E>
 where
   E: CurveCycleEquipped,
@@ -600,6 +1710,376 @@ where
   }
 }
 
+impl<E> ZKWASMInstance<E>
+where
+  E: CurveCycleEquipped,
+{
+  /// Get the values returned by the invoked WASM function, in the order it returns them. Returns
+  /// [`ZKWASMError::OutputMismatch`] if they are not the ones the final public output of the
+  /// execution proof commits to, i.e. the results of the last return of the execution.
+  ///
+  /// # Note
+  ///
+  /// The final output is checked against the proof by [`WasmSNARK::verify`] for a recursive SNARK
+  /// only, so only read it after the recursive SNARK verified. For a shard these are the values of
+  /// the last function returning in it or before it.
+  pub fn return_values(&self) -> Result<Vec<u64>, ZKWASMError> {
+    let returned = self.execution_zi.get(5).ok_or(ZKWASMError::MalformedRS)?;
+    if values_hash::<E::Scalar>(self.results.iter().rev()) != *returned {
+      return Err(ZKWASMError::OutputMismatch);
+    }
+    Ok(self.results.clone())
+  }
+
+  /// Get the number of instructions executed by the proven execution, i.e. the length of its
+  /// execution trace without the padding steps. For a shard, these are the instructions of the
+  /// shard.
+  ///
+  /// # Note
+  ///
+  /// Like [`ZKWASMInstance::return_values`], only rely on this after the SNARK verified.
+  pub fn instruction_count(&self) -> Result<u64, ZKWASMError> {
+    let (Some(count_0), Some(count_i)) = (self.execution_z0.get(2), self.execution_zi.get(2))
+    else {
+      return Err(ZKWASMError::MalformedRS);
+    };
+    scalar_to_u64(*count_i - count_0).ok_or(ZKWASMError::MalformedRS)
+  }
+
+  /// Get the page count of linear memory after the proven execution. The execution circuit
+  /// carries it in its public state, checked against the page count in memory by every
+  /// `memory.size` & updated by every `memory.grow`.
+  ///
+  /// # Note
+  ///
+  /// Like [`ZKWASMInstance::return_values`], only rely on this after the SNARK verified. The
+  /// initial page count is the one in IS only if the instance has the expected inputs, see
+  /// [`ZKWASMInstance::expected_inputs`].
+  pub fn memory_pages(&self) -> Result<u64, ZKWASMError> {
+    let pages = self.execution_zi.get(3).ok_or(ZKWASMError::MalformedRS)?;
+    scalar_to_u64(*pages).ok_or(ZKWASMError::MalformedRS)
+  }
+
+  /// Lay the instance out as the `uint256[]` of public inputs of an EVM verifier, each scalar as a
+  /// big-endian 32-byte word. The words are in order:
+  ///
+  /// 1. `execution_z0`, then `execution_zi`
+  /// 2. the commitment `IC_i` of the execution proof
+  /// 3. `ops_z0`, then the commitment `ops_IC_i` of Πops
+  /// 4. `scan_z0`, then the commitments to IS & FS of Πscan
+  /// 5. the digest of the context, then the digest of the memory images
+  /// 6. the commitment to the program
+  ///
+  /// # Note
+  ///
+  /// Only the public inputs are laid out: the compressed proof is a Spartan proof over an IPA
+  /// commitment on the BN254/Grumpkin cycle, which has no pairing check to hand to a Solidity
+  /// verifier.
+  pub fn to_evm_public_inputs(&self) -> Vec<[u8; 32]> {
+    self
+      .execution_z0
+      .iter()
+      .chain(&self.execution_zi)
+      .chain([&self.IC_i])
+      .chain(&self.ops_z0)
+      .chain([&self.ops_IC_i])
+      .chain(&self.scan_z0)
+      .chain([&self.scan_IC_i.0, &self.scan_IC_i.1])
+      .chain([&self.context, &self.memory_image, &self.program])
+      .map(|scalar| scalar_to_be_bytes(*scalar))
+      .collect()
+  }
+
+  /// Check `next` continues the execution proven by `prev`, i.e. the commitment to the initial
+  /// memory state (IS) of `next` is the commitment to the final memory state (FS) of `prev`. This
+  /// chains the memory of successive runs, like the continuity check between shards.
+  ///
+  /// # Note
+  ///
+  /// The commitments are only checked against the proofs by [`WasmSNARK::verify`], so only rely on
+  /// this after both SNARKs verified.
+  pub fn verify_continuation(prev: &Self, next: &Self) -> Result<(), ZKWASMError> {
+    let (_, prev_IC_FS) = prev.scan_IC_i;
+    let (next_IC_IS, _) = next.scan_IC_i;
+    if prev_IC_FS != next_IC_IS {
+      return Err(ZKWASMError::ContinuationMismatch);
+    }
+    Ok(())
+  }
+
+  /// Check the instance commits to the arguments & results of `host_calls`, the host calls made
+  /// by the proven execution in order. E.g. a verifier can pass the responses it expects from an
+  /// oracle.
+  ///
+  /// # Note
+  ///
+  /// Like [`ZKWASMInstance::return_values`], only rely on this after the SNARK verified. For a
+  /// shard, `host_calls` are the host calls made in the shard.
+  pub fn commits_to_host_calls(&self, host_calls: &[HostCall]) -> bool {
+    let (Some(host_io_0), Some(host_io_i)) = (self.execution_z0.get(1), self.execution_zi.get(1))
+    else {
+      return false;
+    };
+    let host_io = host_calls.iter().fold(*host_io_0, |host_io, host_call| {
+      let args = host_call.args.iter().map(|arg| (HOST_CALL_ARG, *arg));
+      let results = host_call.results.iter().map(|res| (HOST_CALL_RESULT, *res));
+      args.chain(results).fold(host_io, |host_io, (kind, val)| {
+        hash_native(&[host_io, E::Scalar::from(kind), E::Scalar::from(val)])
+      })
+    });
+    host_io == *host_io_i
+  }
+
+  /// Get the hash of the memory images loaded into linear memory before execution, set with
+  /// [`WASMArgsBuilder::with_memory_image`], or zero if there are none. The images are hashed with
+  /// the [`CommitmentHash`] of the program. The hash is absorbed into the MCC challenges, so a
+  /// verified proof commits to it.
+  ///
+  /// # Note
+  ///
+  /// The hash is the one the prover claims for the images in IS. To check IS holds them, recompute
+  /// the inputs with [`ZKWASMInstance::expected_inputs`].
+  ///
+  /// [`WASMArgsBuilder::with_memory_image`]: crate::wasm_ctx::WASMArgsBuilder::with_memory_image
+  pub fn memory_image_hash(&self) -> E::Scalar {
+    self.memory_image
+  }
+
+  /// Check the instance commits to the memory images `memory_images`, as `(offset, bytes)` pairs in
+  /// the order they were set with [`WASMArgsBuilder::with_memory_image`], hashed with
+  /// `commitment_hash`.
+  ///
+  /// [`WASMArgsBuilder::with_memory_image`]: crate::wasm_ctx::WASMArgsBuilder::with_memory_image
+  pub fn commits_to_memory_images(
+    &self,
+    commitment_hash: CommitmentHash,
+    memory_images: &[(usize, Vec<u8>)],
+  ) -> bool {
+    self.memory_image == memory_image_digest(commitment_hash, memory_images)
+  }
+
+  /// Get the commitment to the bytecode of the proven program, see [`program_commitment`], or zero
+  /// for a proof made by [`WasmSNARK::prove_from_trace`] without the bytecode. The commitment is
+  /// absorbed into the MCC challenges, so a verified proof commits to it; check it with
+  /// [`WasmSNARK::verify_program`].
+  ///
+  /// # Note
+  ///
+  /// Like [`ZKWASMInstance::memory_image_hash`], this is the program the prover claims to have
+  /// run. To check the trace is the one of this program, recompute the inputs with
+  /// [`ZKWASMInstance::expected_inputs`].
+  pub fn program_commitment(&self) -> E::Scalar {
+    self.program
+  }
+
+  /// Recompute the public inputs a proof of `program` must have, from `program` & `step_size`
+  /// alone, i.e. without trusting the prover. Compare them to an instance with
+  /// [`ExpectedInputs::check`] to catch a prover lying about the public inputs.
+  ///
+  /// # Note
+  ///
+  /// The inputs are the ones of a proof made by [`WasmSNARK::prove`], i.e. bound to no context.
+  pub fn expected_inputs<S1, S2>(
+    pp: &WASMPublicParams<E, S1, S2>,
+    program: &impl ZKWASMCtx,
+    step_size: StepSize,
+  ) -> Result<ExpectedInputs<E>, ZKWASMError>
+  where
+    S1: BatchedRelaxedR1CSSNARKTrait<E>,
+    S2: RelaxedR1CSSNARKTrait<Dual<E>>,
+  {
+    step_size.validate()?;
+    let ExecutionWitness {
+      z0,
+      mut circuits,
+      IS,
+      IS_gts,
+    } = build_witness::<E>(
+      program.execution_trace()?,
+      program.args().trace_slice_vals,
+      step_size,
+    );
+
+    // Commit to the steps as the prover does, one batch of steps per folding step
+    let execution_pp = pp.F();
+    let IC_i = circuits
+      .by_ref()
+      .try_fold(E::Scalar::ZERO, |IC_i, circuit| {
+        Ok::<_, ZKWASMError>(IC::<E>::commit(
+          &execution_pp.ck_primary,
+          &execution_pp.ro_consts,
+          IC_i,
+          <BatchedWasmTransitionCircuit as StepCircuit<E::Scalar>>::non_deterministic_advice(
+            &circuit?,
+          ),
+        ))
+      })?;
+    let (ops_circuits, FS, _) = circuits.finish();
+    let ops_IC_i = ops_circuits.iter().fold(E::Scalar::ZERO, |IC_i, circuit| {
+      IC::<E>::commit(
+        &pp.ops_pp.ck_primary,
+        &pp.ops_pp.ro_consts,
+        IC_i,
+        <BatchedOpsCircuit as StepCircuit<E::Scalar>>::non_deterministic_advice(circuit),
+      )
+    });
+    let (_, IC_IS, IC_FS) = build_scan_circuits::<E>(pp.scan(), IS, FS, step_size, None);
+
+    let app_label = context_digest(&pp.app_label);
+    let memory_image = memory_image_digest(
+      program.args().commitment_hash(),
+      &program.args().memory_images,
+    );
+    let program = program_commitment(program.args().bytecode());
+    let (gamma, alpha) = mcc_challenges::<E>(
+      IC_i,
+      IC_IS,
+      IC_FS,
+      app_label,
+      context_digest(&[]),
+      memory_image,
+      program,
+    )?;
+    let (ops_z0, scan_z0) = mcc_z0::<E>(gamma, alpha, IS_gts);
+    Ok(ExpectedInputs {
+      execution_z0: z0,
+      IC_i,
+      ops_z0,
+      ops_IC_i,
+      scan_z0,
+      scan_IC_i: (IC_IS, IC_FS),
+      memory_image,
+      program,
+    })
+  }
+
+  /// Check the initial memory state (IS) the proof commits to is the one `program` declares, i.e.
+  /// the stack of the invoked call, the data segments & memory images in linear memory, the
+  /// initial values of the globals and the const pool. IS is otherwise chosen by the prover, so a
+  /// proof of the right execution could start from other "initial" memory the execution never
+  /// reads back.
+  ///
+  /// For a shard, IS is the state the opcodes preceding it leave, as `program` executes them.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`ZKWASMError::InputMismatch`] if the commitment to IS is not the one of `program`.
+  pub fn verify_initial_state<S1, S2>(
+    &self,
+    pp: &WASMPublicParams<E, S1, S2>,
+    program: &impl ZKWASMCtx,
+    step_size: StepSize,
+  ) -> Result<(), ZKWASMError>
+  where
+    S1: BatchedRelaxedR1CSSNARKTrait<E>,
+    S2: RelaxedR1CSSNARKTrait<Dual<E>>,
+  {
+    step_size.validate()?;
+    let (start_execution_trace, mut IS, IS_sizes) = program.execution_trace()?;
+
+    // Build IS as `WasmSNARK::prove` does
+    let (IS_execution_trace, execution_trace) =
+      split_vector(start_execution_trace, program.args().start());
+    let mut global_ts = 0;
+    let shard_size = program.args().shard_size().unwrap_or(execution_trace.len());
+    construct_IS(
+      shard_size,
+      step_size,
+      program.args().is_sharded(),
+      IS_execution_trace,
+      &mut IS,
+      &mut global_ts,
+      &IS_sizes,
+    );
+
+    let (_, IC_IS, _) = build_scan_circuits::<E>(pp.scan(), IS.clone(), IS, step_size, None);
+    if IC_IS != self.scan_IC_i.0 {
+      return Err(ZKWASMError::InputMismatch("IS".to_string()));
+    }
+    Ok(())
+  }
+}
+
+/// The public inputs of a [`ZKWASMInstance`], recomputed from the proven program by
+/// [`ZKWASMInstance::expected_inputs`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedInputs<E>
+where
+  E: CurveCycleEquipped,
+{
+  /// Initial public input of the execution proof
+  pub execution_z0: Vec<E::Scalar>,
+  /// Commitment to the steps of the execution proof
+  pub IC_i: E::Scalar,
+  /// Initial public input of Πops
+  pub ops_z0: Vec<E::Scalar>,
+  /// Commitment to the RS & WS of the steps of Πops
+  pub ops_IC_i: E::Scalar,
+  /// Initial public input of Πscan
+  pub scan_z0: Vec<E::Scalar>,
+  /// Commitments to IS & FS
+  pub scan_IC_i: (E::Scalar, E::Scalar),
+  /// Digest of the memory images loaded in IS, see [`ZKWASMInstance::memory_image_hash`]
+  pub memory_image: E::Scalar,
+  /// Commitment to the bytecode of the program, see [`ZKWASMInstance::program_commitment`]
+  pub program: E::Scalar,
+}
+
+impl<E> ExpectedInputs<E>
+where
+  E: CurveCycleEquipped,
+{
+  /// Check `U` has the expected public inputs, returning [`ZKWASMError::InputMismatch`] naming
+  /// the first one that differs
+  ///
+  /// # Note
+  ///
+  /// Only check `U` once its SNARK verified. A compressed SNARK only verifies instances without
+  /// memory images (see [`WasmSNARK::compress`]), so the memory images of one never check.
+  pub fn check(&self, U: &ZKWASMInstance<E>) -> Result<(), ZKWASMError> {
+    let mismatch = |input: &str| Err(ZKWASMError::InputMismatch(input.to_string()));
+    if U.execution_z0 != self.execution_z0 {
+      return mismatch("execution_z0");
+    }
+    if U.IC_i != self.IC_i {
+      return mismatch("IC_i");
+    }
+    if U.ops_z0 != self.ops_z0 {
+      return mismatch("ops_z0");
+    }
+    if U.ops_IC_i != self.ops_IC_i {
+      return mismatch("ops_IC_i");
+    }
+    if U.scan_z0 != self.scan_z0 {
+      return mismatch("scan_z0");
+    }
+    if U.scan_IC_i != self.scan_IC_i {
+      return mismatch("scan_IC_i");
+    }
+    if U.memory_image != self.memory_image {
+      return mismatch("memory_image");
+    }
+    if U.program != self.program {
+      return mismatch("program");
+    }
+    Ok(())
+  }
+}
+
+/// The arguments & results of a host call, as the raw bits of the WASM values
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostCall {
+  args: Vec<u64>,
+  results: Vec<u64>,
+}
+
+impl HostCall {
+  /// Create a new instance of [`HostCall`]
+  pub fn new(args: Vec<u64>, results: Vec<u64>) -> Self {
+    Self { args, results }
+  }
+}
+
 impl<E> MemoryCommitmentsTraits<E> for ZKWASMInstance<E>
 where
   E: CurveCycleEquipped,
@@ -612,29 +2092,630 @@ where
     self.scan_IC_i.1
   }
 }
+
+/// Public outputs of a verified [`WasmSNARK`], see [`WasmSNARK::verify_and_extract`]
+///
+/// # Note
+///
+/// Only executions returning from the invoked function are proven, as a trap aborts tracing, and
+/// the zkVM meters no fuel. The outputs thus have no trap flag, and the instruction count stands
+/// for the fuel total.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct ZKWASMOutput<E>
+where
+  E: CurveCycleEquipped,
+{
+  stack_top: u64,
+  return_values: Vec<u64>,
+  instruction_count: u64,
+  memory_pages: u64,
+  C_IS: E::Scalar,
+  C_FS: E::Scalar,
+}
+
+impl<E> ZKWASMOutput<E>
+where
+  E: CurveCycleEquipped,
+{
+  /// The stack top left by the last return of the proven execution, i.e. the first value returned
+  /// by the invoked function.
+  ///
+  /// # Note
+  ///
+  /// The value is the raw bits of the WASM value, so an `i32` result has to be read from the lower
+  /// 32 bits.
+  pub fn stack_top(&self) -> u64 {
+    self.stack_top
+  }
+
+  /// The values returned by the invoked function, in the order it returns them, see
+  /// [`ZKWASMInstance::return_values`]. Like the stack top, each is the raw bits of its WASM value.
+  pub fn return_values(&self) -> &[u64] {
+    &self.return_values
+  }
+
+  /// The number of instructions the proven execution executed, see
+  /// [`ZKWASMInstance::instruction_count`]
+  pub fn instruction_count(&self) -> u64 {
+    self.instruction_count
+  }
+
+  /// The page count of linear memory after the proven execution, see
+  /// [`ZKWASMInstance::memory_pages`]
+  pub fn memory_pages(&self) -> u64 {
+    self.memory_pages
+  }
+
+  /// Commitment to the initial memory of the proven execution
+  pub fn C_IS(&self) -> E::Scalar {
+    self.C_IS
+  }
+
+  /// Commitment to the final memory of the proven execution
+  pub fn C_FS(&self) -> E::Scalar {
+    self.C_FS
+  }
+}
+
+/// Get the `target + 1` of the branch the last opcode of `IS_execution_trace` takes to the first
+/// opcode of `execution_trace`, or 0 if it is no branch, which the execution circuit carries in its
+/// public state to bind the pc of the opcode after a branch.
+fn branch_target(IS_execution_trace: &[WitnessVM], execution_trace: &[WitnessVM]) -> u64 {
+  match (IS_execution_trace.last(), execution_trace.first()) {
+    (Some(branch), Some(target))
+      if matches!(
+        branch.instr,
+        Instruction::Br(..)
+          | Instruction::BrIfEqz(..)
+          | Instruction::BrIfNez(..)
+          | Instruction::BrTable(..)
+      ) =>
+    {
+      target.pc as u64 + 1
+    }
+    _ => 0,
+  }
+}
+
+/// Get the value kept by the last drop keep in the trace, which is the stack top the execution
+/// circuit carries in its public state.
+fn last_kept_value(execution_trace: &[WitnessVM]) -> u64 {
+  execution_trace
+    .iter()
+    .rev()
+    .find(|vm| matches!(vm.instr, Instruction::DropKeep))
+    .and_then(|vm| kept_values(vm).last())
+    .unwrap_or(0)
+}
+
+/// The values kept by the drop keeps of a trace, whose hashes the execution circuit carries in its
+/// public state. The drop keeps of a return are traced right before it, so the values they keep
+/// are its results.
+#[derive(Clone, Debug, Default)]
+struct Returns {
+  /// Values kept by the drop keeps since the last other opcode, in trace order
+  kept: Vec<u64>,
+  /// Values kept by the drop keeps of the last return, in trace order
+  returned: Vec<u64>,
+}
+
+impl Returns {
+  /// Get the returns of `execution_trace`
+  fn from_trace(execution_trace: &[WitnessVM]) -> Self {
+    let mut returns = Self::default();
+    for vm in execution_trace {
+      returns.step(vm);
+    }
+    returns
+  }
+
+  /// Update the returns with the step `vm`, the way the execution circuit does
+  fn step(&mut self, vm: &WitnessVM) {
+    match vm.instr {
+      Instruction::DropKeep => self.kept.extend(kept_values(vm)),
+      Instruction::Return(..) | Instruction::ReturnIfNez(..) => {
+        self.returned = std::mem::take(&mut self.kept)
+      }
+      _ => self.kept.clear(),
+    }
+  }
+
+  /// Get the hashes of the kept & returned values, as carried in `z`
+  fn hashes<F>(&self) -> [F; 2]
+  where
+    F: PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+  {
+    [values_hash(&self.kept), values_hash(&self.returned)]
+  }
+
+  /// Get the results of the last return, in the order the function returns them. The drop keeps
+  /// keep the values from the top of the stack down, so the last result comes first.
+  fn results(&self) -> Vec<u64> {
+    self.returned.iter().rev().copied().collect()
+  }
+}
+
+/// Hash `values` in order, the way the execution circuit hashes the values kept by consecutive
+/// drop keeps
+fn values_hash<'a, F>(values: impl IntoIterator<Item = &'a u64>) -> F
+where
+  F: PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+{
+  values
+    .into_iter()
+    .fold(F::ZERO, |hash, value| hash_native(&[hash, F::from(*value)]))
+}
+
+/// Hash the arguments & results of the host calls in the trace, the way the execution circuit
+/// absorbs them into its public state.
+fn host_io_hash<F>(execution_trace: &[WitnessVM]) -> F
+where
+  F: PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+{
+  execution_trace
+    .iter()
+    .filter(|vm| {
+      matches!(vm.instr, Instruction::HostCallStackStep)
+        && (vm.I == HOST_CALL_ARG || vm.I == HOST_CALL_RESULT)
+    })
+    .fold(F::ZERO, |host_io, vm| {
+      hash_native(&[host_io, F::from(vm.I), F::from(vm.P)])
+    })
+}
+
+/// Digest of the context or application label a proof is bound to: zero for the empty context, else
+/// the hash of the context length and its bytes packed into little-endian `u64` words.
+fn context_digest<F>(context: &[u8]) -> F
+where
+  F: PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+{
+  if context.is_empty() {
+    return F::ZERO;
+  }
+  let words = context.chunks(8).map(|chunk| {
+    let mut word = [0u8; 8];
+    word[..chunk.len()].copy_from_slice(chunk);
+    F::from(u64::from_le_bytes(word))
+  });
+  let inputs = std::iter::once(F::from(context.len() as u64))
+    .chain(words)
+    .collect::<Vec<_>>();
+  hash_native(&inputs)
+}
+
+/// Digest of the memory images a proof loads in IS: zero if there are none. With
+/// [`CommitmentHash::Poseidon`] it is the hash chain of the offset and the [`context_digest`] of
+/// the bytes of each image. Otherwise it is the hash of the images encoded as the big-endian
+/// `u64` offset & length followed by the bytes of each, shifted right by a byte to fit in a scalar.
+fn memory_image_digest<F>(commitment_hash: CommitmentHash, memory_images: &[(usize, Vec<u8>)]) -> F
+where
+  F: PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+{
+  if memory_images.is_empty() {
+    return F::ZERO;
+  }
+  let encoded = || {
+    memory_images.iter().flat_map(|(offset, bytes)| {
+      (*offset as u64)
+        .to_be_bytes()
+        .into_iter()
+        .chain((bytes.len() as u64).to_be_bytes())
+        .chain(bytes.iter().copied())
+    })
+  };
+  // Drop the last byte of the hash, so the digest is below the modulus of the scalar field
+  let truncate = |hash: &[u8]| {
+    hash[..31].iter().fold(F::ZERO, |digest, byte| {
+      digest * F::from(256) + F::from(*byte as u64)
+    })
+  };
+  match commitment_hash {
+    CommitmentHash::Poseidon => memory_images
+      .iter()
+      .fold(F::ZERO, |digest, (offset, bytes)| {
+        hash_native(&[digest, F::from(*offset as u64), context_digest(bytes)])
+      }),
+    CommitmentHash::Sha256 => truncate(&Sha256::digest(encoded().collect::<Vec<_>>())),
+    CommitmentHash::Keccak256 => truncate(&Keccak256::digest(encoded().collect::<Vec<_>>())),
+  }
+}
+
+/// Commitment to the bytecode of a WASM program, i.e. the [`context_digest`] of its bytes. A
+/// verifier computes it from the module it expects to be proven, to check a proof against it with
+/// [`WasmSNARK::verify_program`].
+pub fn program_commitment<F>(bytecode: &[u8]) -> F
+where
+  F: PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+{
+  context_digest(bytecode)
+}
+
+/// Decode a scalar into a `u64`, returns `None` if it does not fit in 64 bits.
+fn scalar_to_u64<F: PrimeFieldBits>(scalar: F) -> Option<u64> {
+  let bits = scalar.to_le_bits();
+  if bits.iter().skip(64).any(|bit| *bit) {
+    return None;
+  }
+  Some(
+    bits
+      .iter()
+      .take(64)
+      .enumerate()
+      .fold(0, |acc, (i, bit)| acc | ((*bit as u64) << i)),
+  )
+}
+
+/// Big-endian bytes of `scalar`, i.e. its encoding as an EVM `uint256`
+fn scalar_to_be_bytes<F: PrimeFieldBits>(scalar: F) -> [u8; 32] {
+  let mut bytes = [0; 32];
+  for (i, bit) in scalar.to_le_bits().iter().take(256).enumerate() {
+    bytes[31 - i / 8] |= (*bit as u8) << (i % 8);
+  }
+  bytes
+}
+
+/// The phases of [`WasmSNARK::prove`], reported to the progress callback of
+/// [`ProvingConfig::progress`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvingPhase {
+  /// Proving the WASM transition circuits
+  Execution,
+  /// Proving the grand products of the RS & WS multisets
+  Ops,
+  /// Proving the grand products of the IS & FS multisets
+  Scan,
+}
+
+impl std::fmt::Display for ProvingPhase {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Execution => write!(f, "Execution"),
+      Self::Ops => write!(f, "Ops"),
+      Self::Scan => write!(f, "Scan"),
+    }
+  }
+}
+
 /// Step size of used for zkVM execution
 #[derive(Clone, Debug, Copy)]
 pub struct StepSize {
   execution: usize,
   memory: usize,
+  memory_ops: usize,
+  max_memory_pages: Option<usize>,
 }
 
 impl StepSize {
   /// Create a new instance of [`StepSize`]
   ///
-  /// Sets both execution and memory step size to `step_size`
-  pub fn new(step_size: usize) -> Self {
-    Self {
+  /// Sets both execution and memory step size to `step_size`. Returns an error if `step_size` is
+  /// zero.
+  pub fn new(step_size: usize) -> Result<Self, ZKWASMError> {
+    let step_size = Self {
       execution: step_size,
       memory: step_size,
+      memory_ops: MEMORY_OPS_PER_STEP,
+      max_memory_pages: None,
+    };
+    step_size.validate()?;
+    Ok(step_size)
+  }
+
+  /// Get the largest step size whose witness of a single folding step fits in `available_bytes`,
+  /// given an estimate of the witness bytes of each opcode proven per step, e.g. on a prover with
+  /// little memory to not run out of it in `prove_step`.
+  ///
+  /// Returns an error if not even a single opcode per step fits in the budget.
+  pub fn max_under_memory(
+    available_bytes: usize,
+    per_op_witness_estimate: usize,
+  ) -> Result<Self, ZKWASMError> {
+    if per_op_witness_estimate == 0 {
+      return Err(ZKWASMError::InvalidStepSize(
+        "witness estimate per opcode must be greater than zero".to_string(),
+      ));
     }
+    Self::new(available_bytes / per_op_witness_estimate).map_err(|_| {
+      ZKWASMError::InvalidStepSize(format!(
+        "one opcode per step needs {per_op_witness_estimate} bytes, only {available_bytes} fit"
+      ))
+    })
   }
 
   /// Set the memory step size
   ///
-  /// Returns a modified instance of [`StepSize`]
-  pub fn set_memory_step_size(mut self, memory: usize) -> Self {
+  /// Returns a modified instance of [`StepSize`], or an error if `memory` is zero.
+  pub fn set_memory_step_size(mut self, memory: usize) -> Result<Self, ZKWASMError> {
     self.memory = memory;
+    self.validate()?;
+    Ok(self)
+  }
+
+  /// Set the number of memory ops (reads & writes) each zkVM step is padded to. Defaults to
+  /// [`MEMORY_OPS_PER_STEP`].
+  ///
+  /// A drop keep step keeps up to [`wasmi::MAX_KEEP_PER_STEP`] values, each read and written, so
+  /// programs returning or branching with three values or more need 12. Proving them with fewer
+  /// returns [`ZKWASMError::InvalidStepSize`].
+  ///
+  /// Public parameters are specific to this value, so it must be the same for setup and proving.
+  /// Returns an error if `memory_ops` is odd or less than [`MEMORY_OPS_PER_STEP`].
+  pub fn set_memory_ops_per_step(mut self, memory_ops: usize) -> Result<Self, ZKWASMError> {
+    self.memory_ops = memory_ops;
+    self.validate()?;
+    Ok(self)
+  }
+
+  /// Get the number of memory ops per zkVM step
+  pub fn memory_ops_per_step(&self) -> usize {
+    self.memory_ops
+  }
+
+  /// Set the maximum number of pages of linear memory a proven execution may use, grown ones
+  /// included. Proving an execution using more returns [`ZKWASMError::MemoryLimitExceeded`] before
+  /// its IS & FS are built, e.g. to not run out of memory proving untrusted WASM growing its memory
+  /// unboundedly. Unbounded by default.
+  pub fn set_max_memory_pages(mut self, max_memory_pages: usize) -> Self {
+    self.max_memory_pages = Some(max_memory_pages);
+    self
+  }
+
+  /// Get the maximum number of pages of linear memory a proven execution may use, if bounded
+  pub fn max_memory_pages(&self) -> Option<usize> {
+    self.max_memory_pages
+  }
+
+  /// Check an execution using `pages` pages of linear memory is within the maximum, see
+  /// [`StepSize::set_max_memory_pages`]
+  fn check_memory_pages(&self, pages: usize) -> Result<(), ZKWASMError> {
+    match self.max_memory_pages {
+      Some(allowed) if pages > allowed => Err(ZKWASMError::MemoryLimitExceeded {
+        requested: pages,
+        allowed,
+      }),
+      _ => Ok(()),
+    }
+  }
+
+  /// Check that neither the execution nor the memory step size is zero. A zero step size would
+  /// make the padding of the execution trace and the IS/FS multisets divide by zero.
+  ///
+  /// Also check every opcode fits in the memory ops of a step, which are split evenly between
+  /// reads and writes.
+  fn validate(&self) -> Result<(), ZKWASMError> {
+    if self.execution == 0 {
+      return Err(ZKWASMError::InvalidStepSize(
+        "execution step size must be greater than zero".to_string(),
+      ));
+    }
+    if self.memory == 0 {
+      return Err(ZKWASMError::InvalidStepSize(
+        "memory step size must be greater than zero".to_string(),
+      ));
+    }
+    if self.memory_ops < MEMORY_OPS_PER_STEP || self.memory_ops % 2 != 0 {
+      return Err(ZKWASMError::InvalidStepSize(format!(
+        "memory ops per step must be an even number of at least {MEMORY_OPS_PER_STEP}"
+      )));
+    }
+    Ok(())
+  }
+}
+
+/// Configuration of [`WasmSNARK::prove`]: the step size and the optional knobs of the prover
+///
+/// [`ProvingConfig::default`] proves like before the knobs existed, and a [`StepSize`] converts
+/// into the config proving with it, so `prove(pp, program, step_size)` still compiles.
+#[derive(Clone)]
+pub struct ProvingConfig<'a> {
+  step_size: StepSize,
+  max_memory_pages: Option<usize>,
+  parallel: bool,
+  num_threads: Option<NonZeroUsize>,
+  shard: Option<(usize, NonZeroUsize)>,
+  private_input: Option<Vec<u8>>,
+  context: &'a [u8],
+  progress: Option<&'a (dyn Fn(ProvingPhase, usize, usize) + Sync)>,
+}
+
+impl<'a> ProvingConfig<'a> {
+  /// Create a new instance of [`ProvingConfig`] proving with `step_size`
+  pub fn new(step_size: StepSize) -> Self {
+    Self {
+      step_size,
+      max_memory_pages: None,
+      parallel: false,
+      num_threads: None,
+      shard: None,
+      private_input: None,
+      context: &[],
+      progress: None,
+    }
+  }
+
+  /// Set the step size
+  pub fn step_size(mut self, step_size: StepSize) -> Self {
+    self.step_size = step_size;
+    self
+  }
+
+  /// Cap the number of pages of linear memory the proven execution may use, grown ones included,
+  /// as [`StepSize::set_max_memory_pages`] does. This cap replaces the one of the step size, if
+  /// any, whether it is set before or after it.
+  pub fn max_memory_pages(mut self, max_memory_pages: usize) -> Self {
+    self.max_memory_pages = Some(max_memory_pages);
     self
   }
+
+  /// Set whether the grand products of the RS & WS and of the IS & FS multisets are proven
+  /// concurrently. Defaults to `false`.
+  pub fn parallel(mut self, parallel: bool) -> Self {
+    self.parallel = parallel;
+    self
+  }
+
+  /// Prove on a dedicated pool of `num_threads` threads instead of the global one
+  ///
+  /// The proof is the same, but the degree of parallelism does not depend on the global thread
+  /// pool, so e.g. benchmarks can pin it for reproducible timings. `num_threads = 1` proves
+  /// serially.
+  pub fn num_threads(mut self, num_threads: NonZeroUsize) -> Self {
+    self.num_threads = Some(num_threads);
+    self
+  }
+
+  /// Prove only shard `shard_index` of the execution, the shards being `shard_size` opcodes long,
+  /// instead of the trace slice of the program (see [`ShardingSNARK::prove_shard`])
+  ///
+  /// [`ShardingSNARK::prove_shard`]: crate::sharding::ShardingSNARK::prove_shard
+  pub fn shard(mut self, shard_index: usize, shard_size: NonZeroUsize) -> Self {
+    self.shard = Some((shard_index, shard_size));
+    self
+  }
+
+  /// Feed `private_input` to the program instead of its own, see
+  /// [`WASMArgsBuilder::private_input`](crate::wasm_ctx::WASMArgsBuilder::private_input)
+  pub fn private_input(mut self, private_input: Vec<u8>) -> Self {
+    self.private_input = Some(private_input);
+    self
+  }
+
+  /// Bind the proof to `context`
+  ///
+  /// The context (e.g. a session nonce or a domain separator) is absorbed into the MCC challenges
+  /// and committed in the [`ZKWASMInstance`], so the proof only verifies with
+  /// [`WasmSNARK::verify_with_context`] under the same context and can not be replayed in another
+  /// one.
+  pub fn context(mut self, context: &'a [u8]) -> Self {
+    self.context = context;
+    self
+  }
+
+  /// Report the progress of proving to `progress`
+  ///
+  /// `progress` is invoked after each folding step with the [`ProvingPhase`] and the `(current,
+  /// total)` step counts of that phase, e.g. to render a progress bar. With
+  /// [`ProvingConfig::parallel`] the [`ProvingPhase::Ops`] & [`ProvingPhase::Scan`] steps are
+  /// proven concurrently, so their progress is reported interleaved and from several threads.
+  pub fn progress(mut self, progress: &'a (dyn Fn(ProvingPhase, usize, usize) + Sync)) -> Self {
+    self.progress = Some(progress);
+    self
+  }
+
+  /// Get the step size with the memory limit of the config applied
+  fn bounded_step_size(&self) -> StepSize {
+    match self.max_memory_pages {
+      Some(max_memory_pages) => self.step_size.set_max_memory_pages(max_memory_pages),
+      None => self.step_size,
+    }
+  }
+
+  /// Run `prove` on the thread pool of the config
+  fn install<R: Send>(
+    &self,
+    prove: impl FnOnce() -> Result<R, ZKWASMError> + Send,
+  ) -> Result<R, ZKWASMError> {
+    match self.num_threads {
+      Some(num_threads) => rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads.get())
+        .build()
+        .map_err(|err| ZKWASMError::ThreadPoolError(err.to_string()))?
+        .install(prove),
+      None => prove(),
+    }
+  }
+
+  /// Check the config only sets knobs that apply to an already executed trace
+  fn check_executed(&self) -> Result<(), ZKWASMError> {
+    if self.shard.is_some() || self.private_input.is_some() {
+      return Err(ZKWASMError::InvalidConfig(
+        "a shard or a private input can not apply to an executed trace".to_string(),
+      ));
+    }
+    Ok(())
+  }
+
+  /// Get the args of `program` with the overrides of the config applied, or `None` if there are
+  /// none
+  fn override_args(&self, args: &WASMArgs) -> Result<Option<WASMArgs>, ZKWASMError> {
+    if self.shard.is_none() && self.private_input.is_none() {
+      return Ok(None);
+    }
+    let mut args = args.clone();
+    if let Some((shard_index, shard_size)) = self.shard {
+      let start = shard_index.checked_mul(shard_size.get()).ok_or_else(|| {
+        ZKWASMError::InvalidTraceSliceValues(format!("shard {shard_index} overflows usize"))
+      })?;
+      let end = shard_size.checked_add(start).ok_or_else(|| {
+        ZKWASMError::InvalidTraceSliceValues(format!("shard {shard_index} overflows usize"))
+      })?;
+      args.trace_slice_vals = Some(TraceSliceValues::new(start, end)?);
+    }
+    if let Some(private_input) = &self.private_input {
+      args.private_input = private_input.clone();
+    }
+    Ok(Some(args))
+  }
+}
+
+impl std::fmt::Debug for ProvingConfig<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("ProvingConfig")
+      .field("step_size", &self.step_size)
+      .field("max_memory_pages", &self.max_memory_pages)
+      .field("parallel", &self.parallel)
+      .field("num_threads", &self.num_threads)
+      .field("shard", &self.shard)
+      .field("private_input", &self.private_input)
+      .field("context", &self.context)
+      .field("progress", &self.progress.is_some())
+      .finish()
+  }
+}
+
+impl Default for ProvingConfig<'_> {
+  fn default() -> Self {
+    Self::new(StepSize::new(10).expect("10 is a valid step size"))
+  }
+}
+
+impl From<StepSize> for ProvingConfig<'_> {
+  fn from(step_size: StepSize) -> Self {
+    Self::new(step_size)
+  }
+}
+
+/// `program` run with the args a [`ProvingConfig`] overrides
+struct ConfiguredCtx<'a, C> {
+  program: &'a C,
+  args: WASMArgs,
+}
+
+impl<C: ZKWASMCtx> ZKWASMCtx for ConfiguredCtx<'_, C> {
+  type T = C::T;
+
+  fn create_store(engine: &wasmi::Engine) -> wasmi::Store<Self::T> {
+    C::create_store(engine)
+  }
+
+  fn create_linker(engine: &wasmi::Engine) -> Result<wasmi::Linker<Self::T>, ZKWASMError> {
+    C::create_linker(engine)
+  }
+
+  fn args(&self) -> &WASMArgs {
+    &self.args
+  }
+
+  fn stack_limits(&self) -> StackLimits {
+    self.program.stack_limits()
+  }
+
+  fn trace_hook(&self) -> Option<TraceHook> {
+    self.program.trace_hook()
+  }
+
+  fn host_registry(&self) -> Option<&HostRegistry> {
+    self.program.host_registry()
+  }
 }
@@ -0,0 +1,198 @@
+//! Openings of the zkVM stack at a step of a proven execution.
+//!
+//! Πops carries an incremental commitment to the RS & WS of every step, which the verifier checks
+//! is the commitment carried by the execution proof. A [`StackOpening`] carries the RS & WS the
+//! prover claims, so the verifier commits to them again and compares against the proof. The opened
+//! value is then the last one written to the address up to the opened step.
+use ff::Field;
+use itertools::Itertools;
+use nova::{
+  nebula::{ic::IC, rs::StepCircuit},
+  traits::{
+    snark::{BatchedRelaxedR1CSSNARKTrait, RelaxedR1CSSNARKTrait},
+    CurveCycleEquipped, Dual,
+  },
+};
+use serde::{Deserialize, Serialize};
+use wasmi::WitnessVM;
+
+use super::{
+  construct_IS, context_digest,
+  mcc::{multiset_ops::step_RS_WS, BatchedOpsCircuit, OpsCircuit},
+  StepSize, WASMPublicParams, WasmSNARK, ZKWASMInstance,
+};
+use crate::{error::ZKWASMError, utils::tracing::split_vector, wasm_ctx::ZKWASMCtx};
+
+/// Opening of the value at a stack address after a step of a [`WasmSNARK`]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StackOpening {
+  /// Index of the opened step in the proven execution trace
+  step: usize,
+  /// Opened stack address
+  addr: usize,
+  /// Number of steps proven per folding step
+  execution_step_size: usize,
+  /// The (addr, val, ts) tuples read by each step of the proof
+  RS: Vec<Vec<(usize, u64, u64)>>,
+  /// The (addr, val, ts) tuples written by each step of the proof
+  WS: Vec<Vec<(usize, u64, u64)>>,
+}
+
+impl StackOpening {
+  /// Index of the opened step in the proven execution trace
+  pub fn step(&self) -> usize {
+    self.step
+  }
+
+  /// Opened stack address
+  pub fn addr(&self) -> usize {
+    self.addr
+  }
+
+  /// Get the last value written to the opened address up to the opened step
+  fn opened_value(&self) -> Result<u64, ZKWASMError> {
+    if self.step >= self.WS.len() {
+      return Err(ZKWASMError::InvalidOpening(format!(
+        "step {} is out of the {} proven steps",
+        self.step,
+        self.WS.len()
+      )));
+    }
+    self.WS[..=self.step]
+      .iter()
+      .flatten()
+      .rev()
+      .find(|(addr, _, _)| *addr == self.addr)
+      .map(|(_, val, _)| *val)
+      .ok_or_else(|| {
+        ZKWASMError::InvalidOpening(format!(
+          "address {} is not accessed up to step {}",
+          self.addr, self.step
+        ))
+      })
+  }
+}
+
+impl<E, S1, S2> WasmSNARK<E, S1, S2>
+where
+  E: CurveCycleEquipped,
+  S1: BatchedRelaxedR1CSSNARKTrait<E>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E>>,
+{
+  /// Open the value at stack address `addr` after the opcode at index `step` of the execution
+  /// trace proven for `program`, e.g. the stack top after `step` is at the `pre_sp - 1` of the next
+  /// opcode.
+  ///
+  /// `program` & `step_size` must be the ones the [`WasmSNARK`] was proven with, as the opening is
+  /// built by re-running the program.
+  ///
+  /// # Note
+  ///
+  /// Only values accessed in the proven execution can be opened, so opening an address no opcode up
+  /// to `step` touched returns [`ZKWASMError::InvalidOpening`].
+  pub fn open_stack(
+    &self,
+    program: &impl ZKWASMCtx,
+    step_size: StepSize,
+    step: usize,
+    addr: usize,
+  ) -> Result<(u64, StackOpening), ZKWASMError> {
+    if let Self::Compressed(..) = self {
+      return Err(ZKWASMError::NotRecursive);
+    }
+
+    let (start_execution_trace, mut IS, IS_sizes) = program.execution_trace()?;
+    if addr >= IS_sizes.stack_len() {
+      return Err(ZKWASMError::InvalidOpening(format!(
+        "address {addr} is out of the stack of {} values",
+        IS_sizes.stack_len()
+      )));
+    }
+
+    // Build RS & WS as `WasmSNARK::prove` does
+    let (IS_execution_trace, mut execution_trace) =
+      split_vector(start_execution_trace, program.args().start());
+    let mut global_ts = 0;
+    let shard_size = program.args().shard_size().unwrap_or(execution_trace.len());
+    construct_IS(
+      shard_size,
+      step_size,
+      program.args().is_sharded(),
+      IS_execution_trace,
+      &mut IS,
+      &mut global_ts,
+      &IS_sizes,
+    );
+    let pad_len =
+      (step_size.execution - (execution_trace.len() % step_size.execution)) % step_size.execution;
+    execution_trace.extend((0..pad_len).map(|_| WitnessVM::default()));
+    let (RS, WS) = execution_trace
+      .iter()
+      .map(|vm| step_RS_WS(vm, &mut IS, &mut global_ts, &IS_sizes, step_size.memory_ops))
+      .unzip();
+
+    let opening = StackOpening {
+      step,
+      addr,
+      execution_step_size: step_size.execution,
+      RS,
+      WS,
+    };
+    let val = opening.opened_value()?;
+    Ok((val, opening))
+  }
+
+  /// Verify the [`WasmSNARK`] and that `opening` opens `val`, i.e. that the value at the opened
+  /// stack address after the opened step is `val` in the execution the proof commits to.
+  pub fn verify_stack_opening(
+    &self,
+    pp: &WASMPublicParams<E, S1, S2>,
+    U: &ZKWASMInstance<E>,
+    val: u64,
+    opening: &StackOpening,
+  ) -> Result<(), ZKWASMError> {
+    let Self::Recursive(rs) = self else {
+      return Err(ZKWASMError::NotRecursive);
+    };
+    Self::verify_recursive(pp, context_digest(pp.app_label()), rs, U)?;
+
+    if opening.RS.len() != opening.WS.len() || opening.execution_step_size == 0 {
+      return Err(ZKWASMError::InvalidOpening(
+        "RS & WS are malformed".to_string(),
+      ));
+    }
+
+    // Commit to the claimed RS & WS as Πops does, one batch of steps per folding step. The
+    // verified proof checks Πops carries the commitment of the execution.
+    let ops_circuits = opening
+      .RS
+      .iter()
+      .zip_eq(opening.WS.iter())
+      .map(|(rs, ws)| OpsCircuit::new(rs.clone(), ws.clone()))
+      .collect::<Vec<_>>();
+    let ops_IC_i = ops_circuits
+      .chunks(opening.execution_step_size)
+      .map(|chunk| BatchedOpsCircuit::new(chunk.to_vec()))
+      .fold(E::Scalar::ZERO, |IC_i, circuit| {
+        IC::<E>::commit(
+          &pp.ops_pp.ck_primary,
+          &pp.ops_pp.ro_consts,
+          IC_i,
+          <BatchedOpsCircuit as StepCircuit<E::Scalar>>::non_deterministic_advice(&circuit),
+        )
+      });
+    if ops_IC_i != U.ops_IC_i {
+      return Err(ZKWASMError::InvalidOpening(
+        "RS & WS are not the ones the proof commits to".to_string(),
+      ));
+    }
+
+    if opening.opened_value()? != val {
+      return Err(ZKWASMError::InvalidOpening(format!(
+        "address {} holds another value after step {}",
+        opening.addr, opening.step
+      )));
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,76 @@
+//! Verification of the execution proof ΠF step by step, as the prover folds it.
+//!
+//! Each folding step yields a [`RecursiveSNARK`] that verifies on its own against the initial
+//! input of the execution & the commitment carried up to that step. A verifier receiving the steps
+//! as they are proven, e.g. from [`WasmSNARK::prove_streaming`], can thus cut off a misbehaving
+//! prover at the first bad step instead of waiting for the whole proof.
+//!
+//! [`WasmSNARK::prove_streaming`]: super::WasmSNARK::prove_streaming
+use nova::{
+  nebula::{rs::RecursiveSNARK, traits::Layer1PPTrait},
+  traits::{
+    snark::{BatchedRelaxedR1CSSNARKTrait, RelaxedR1CSSNARKTrait},
+    CurveCycleEquipped, Dual,
+  },
+};
+
+use super::WASMPublicParams;
+use crate::error::ZKWASMError;
+
+/// Verifies the steps of an execution proof as they arrive, see [`WasmSNARK::prove_streaming`]
+///
+/// [`WasmSNARK::prove_streaming`]: super::WasmSNARK::prove_streaming
+pub struct StreamingVerifier<'a, E, S1, S2>
+where
+  E: CurveCycleEquipped,
+  S1: BatchedRelaxedR1CSSNARKTrait<E>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E>>,
+{
+  pp: &'a WASMPublicParams<E, S1, S2>,
+  /// Initial input of the execution proof
+  z0: Vec<E::Scalar>,
+  /// Number of steps verified so far
+  num_steps: usize,
+}
+
+impl<'a, E, S1, S2> StreamingVerifier<'a, E, S1, S2>
+where
+  E: CurveCycleEquipped,
+  S1: BatchedRelaxedR1CSSNARKTrait<E>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E>>,
+{
+  /// Verify the steps of an execution proof starting from `z0`, e.g. the
+  /// [`ExpectedInputs::execution_z0`](super::ExpectedInputs::execution_z0) of the proven program
+  pub fn new(pp: &'a WASMPublicParams<E, S1, S2>, z0: Vec<E::Scalar>) -> Self {
+    Self {
+      pp,
+      z0,
+      num_steps: 0,
+    }
+  }
+
+  /// Number of steps verified so far
+  pub fn num_steps(&self) -> usize {
+    self.num_steps
+  }
+
+  /// Verify the next step of the execution proof: `rs` folded up to it, carrying the commitment
+  /// `IC_i`. Returns the output of the execution up to the step.
+  ///
+  /// # Errors
+  ///
+  /// Returns [`ZKWASMError::MalformedRS`] if `rs` is not folded up to the next step, and the
+  /// verification error if it does not verify. The verifier is left at the last verified step.
+  pub fn verify_step(
+    &mut self,
+    rs: &RecursiveSNARK<E>,
+    IC_i: E::Scalar,
+  ) -> Result<Vec<E::Scalar>, ZKWASMError> {
+    if rs.num_steps() != self.num_steps + 1 {
+      return Err(ZKWASMError::MalformedRS);
+    }
+    let zi = rs.verify(self.pp.F(), rs.num_steps(), &self.z0, IC_i)?;
+    self.num_steps += 1;
+    Ok(zi)
+  }
+}
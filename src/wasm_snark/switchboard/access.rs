@@ -0,0 +1,544 @@
+//! Linear memory accesses of loads & stores, which read or write the `size` bytes at the effective
+//! address `ea`. Those bytes span the words `ea / 8` & `ea / 8 + 1` of linear memory, from the
+//! byte `ea % 8` of the first one.
+use super::{
+  alu::{alloc_le_bits, pack},
+  bulk::{one_hot, range_check, val, window_bytes, ADDR_BITS},
+  WASMTransitionCircuit as SwitchBoardCircuit,
+};
+use bellpepper_core::{
+  boolean::AllocatedBit, num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError,
+};
+use ff::PrimeField;
+use wasmi::Instruction as Instr;
+
+/// Sizes in bytes of a linear memory access
+const SIZES: [u64; 4] = [1, 2, 4, 8];
+
+/// Number of bytes an opcode loads from or stores to linear memory
+pub fn access_size(instr: &Instr) -> u64 {
+  match instr {
+    Instr::I32Load8S(..)
+    | Instr::I32Load8U(..)
+    | Instr::I64Load8S(..)
+    | Instr::I64Load8U(..)
+    | Instr::I32Store8(..)
+    | Instr::I64Store8(..) => 1,
+    Instr::I32Load16S(..)
+    | Instr::I32Load16U(..)
+    | Instr::I64Load16S(..)
+    | Instr::I64Load16U(..)
+    | Instr::I32Store16(..)
+    | Instr::I64Store16(..) => 2,
+    Instr::I32Load(..)
+    | Instr::F32Load(..)
+    | Instr::I64Load32S(..)
+    | Instr::I64Load32U(..)
+    | Instr::I32Store(..)
+    | Instr::F32Store(..)
+    | Instr::I64Store32(..) => 4,
+    _ => 8,
+  }
+}
+
+/// How a load extends the `size` bytes it loads to the value it pushes
+pub struct Extension {
+  /// Whether the loaded bytes are sign-extended, otherwise they are zero-extended
+  pub signed: bool,
+  /// Whether the pushed value is 64-bit, otherwise it is 32-bit & its high bytes are 0
+  pub wide: bool,
+}
+
+/// The [`Extension`] of a load opcode
+pub fn load_extension(instr: &Instr) -> Extension {
+  let signed = matches!(
+    instr,
+    Instr::I32Load8S(..)
+      | Instr::I32Load16S(..)
+      | Instr::I64Load8S(..)
+      | Instr::I64Load16S(..)
+      | Instr::I64Load32S(..)
+  );
+  let wide = !matches!(
+    instr,
+    Instr::I32Load(..)
+      | Instr::I32Load8S(..)
+      | Instr::I32Load8U(..)
+      | Instr::I32Load16S(..)
+      | Instr::I32Load16U(..)
+      | Instr::F32Load(..)
+  );
+  Extension { signed, wide }
+}
+
+/// The word of linear memory an access starts in, and the offset & size in bytes of the access
+pub struct AccessWindow<F: PrimeField> {
+  /// Index of the first word, i.e. `ea / 8`
+  pub word: AllocatedNum<F>,
+  /// One-hot encoding of the offset `ea % 8` into the first word
+  rot: Vec<AllocatedBit>,
+  rot_val: u64,
+  /// One-hot encoding of the size, over [`SIZES`]
+  size: Vec<AllocatedBit>,
+  size_val: u64,
+}
+
+impl<F: PrimeField> AccessWindow<F> {
+  /// Whether the byte `j` of the accessed value is in memory, i.e. `j < size`
+  fn in_size(&self, j: usize) -> LinearCombination<F> {
+    SIZES
+      .iter()
+      .zip(&self.size)
+      .filter(|(size, _)| j < **size as usize)
+      .fold(LinearCombination::zero(), |lc, (_, bit)| {
+        lc + bit.get_variable()
+      })
+  }
+}
+
+/// Decompose the effective address `ea` of an access of `size_val` bytes into its
+/// [`AccessWindow`], i.e. `ea = 8 · word + rot`.
+pub fn access_window<F, CS>(
+  mut cs: CS,
+  ea: &AllocatedNum<F>,
+  size_val: u64,
+  switch: F,
+) -> Result<AccessWindow<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let ea_val = val(ea);
+  let one = SwitchBoardCircuit::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+
+  // ea = raw address + offset < 2^33
+  let (ea_bits, packed) =
+    alloc_le_bits(cs.namespace(|| "ea"), u128::from(ea_val), ADDR_BITS, switch)?;
+  cs.enforce(
+    || "ea = packed",
+    |lc| lc + ea.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc + packed.get_variable(),
+  );
+
+  let word =
+    SwitchBoardCircuit::alloc_num(&mut cs, || "word", || Ok(F::from(ea_val >> 3)), switch)?;
+  cs.enforce(
+    || "word = ea >> 3",
+    |_| pack(&ea_bits[3..]),
+    |lc| lc + CS::one(),
+    |lc| lc + word.get_variable(),
+  );
+  let rot_val = ea_val & 7;
+  let rot = one_hot(&mut cs, &ea_bits[..3], rot_val, &one, switch)?;
+
+  let size = SIZES
+    .iter()
+    .map(|&size| {
+      SwitchBoardCircuit::alloc_bit(
+        &mut cs,
+        || format!("size = {size}"),
+        Some(size_val == size),
+        switch,
+      )
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+  cs.enforce(
+    || "Σ size_s = 1",
+    |lc| size.iter().fold(lc, |lc, bit| lc + bit.get_variable()),
+    |lc| lc + CS::one(),
+    |lc| lc + one.get_variable(),
+  );
+
+  Ok(AccessWindow {
+    word,
+    rot,
+    rot_val,
+    size,
+    size_val,
+  })
+}
+
+/// Constrain `loaded` to be the bytes read from the words `words` of its [`AccessWindow`],
+/// extended with `extension`, i.e. for the byte `j` of `loaded`:
+///
+/// - `j < size`: `loaded_byte_j = window byte (rot + j)`
+/// - `size <= j < width`: `loaded_byte_j = 0xff · ext`, where `ext` is the sign bit of the loaded
+///   bytes for signed loads & 0 otherwise
+/// - `width <= j`: `loaded_byte_j = 0`
+pub fn enforce_loaded<F, CS>(
+  mut cs: CS,
+  loaded: &AllocatedNum<F>,
+  words: [&AllocatedNum<F>; 2],
+  window: &AccessWindow<F>,
+  extension: &Extension,
+  switch: F,
+) -> Result<(), SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let one = SwitchBoardCircuit::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+  let bytes = window_bytes(
+    cs.namespace(|| "words"),
+    words,
+    &window.rot,
+    window.rot_val,
+    switch,
+  )?;
+  let loaded_bits = range_check(cs.namespace(|| "loaded"), loaded, 64, switch)?;
+
+  for (j, (byte, _)) in bytes.iter().enumerate() {
+    cs.enforce(
+      || format!("j < size => loaded byte {j} = window byte (rot + {j})"),
+      |_| window.in_size(j),
+      |lc| lc + &pack(&loaded_bits[8 * j..8 * (j + 1)]) - byte,
+      |lc| lc,
+    );
+  }
+
+  // The sign bit of the loaded bytes is the top bit of the byte size - 1 of `loaded`
+  let size_val = window.size_val as usize;
+  let sign_val = size_val > 0 && loaded_bits[8 * size_val - 1].get_value() == Some(true);
+  let mut sign = LinearCombination::zero();
+  for (&size, size_bit) in SIZES.iter().zip(&window.size) {
+    let msb = &loaded_bits[8 * size as usize - 1];
+    let selected = SwitchBoardCircuit::alloc_bit(
+      &mut cs,
+      || format!("size = {size} & sign"),
+      Some(size_val == size as usize && msb.get_value() == Some(true)),
+      switch,
+    )?;
+    cs.enforce(
+      || format!("size_{size} · msb_{size} = selected"),
+      |lc| lc + size_bit.get_variable(),
+      |lc| lc + msb.get_variable(),
+      |lc| lc + selected.get_variable(),
+    );
+    sign = sign + selected.get_variable();
+  }
+
+  let signed = SwitchBoardCircuit::alloc_bit(&mut cs, || "signed", Some(extension.signed), switch)?;
+  let wide = SwitchBoardCircuit::alloc_bit(&mut cs, || "wide", Some(extension.wide), switch)?;
+  let ext = SwitchBoardCircuit::alloc_bit(
+    &mut cs,
+    || "ext",
+    Some(extension.signed && sign_val),
+    switch,
+  )?;
+  cs.enforce(
+    || "signed · sign = ext",
+    |lc| lc + signed.get_variable(),
+    |_| sign,
+    |lc| lc + ext.get_variable(),
+  );
+  let wide_ext = SwitchBoardCircuit::alloc_bit(
+    &mut cs,
+    || "wide ext",
+    Some(extension.wide && extension.signed && sign_val),
+    switch,
+  )?;
+  cs.enforce(
+    || "wide · ext = wide_ext",
+    |lc| lc + wide.get_variable(),
+    |lc| lc + ext.get_variable(),
+    |lc| lc + wide_ext.get_variable(),
+  );
+
+  for j in 0..8 {
+    let ext = if j < 4 { &ext } else { &wide_ext };
+    cs.enforce(
+      || format!("j >= size => loaded byte {j} = 0xff · ext"),
+      |lc| lc + one.get_variable() - &window.in_size(j),
+      |lc| lc + &pack(&loaded_bits[8 * j..8 * (j + 1)]) - (F::from(0xff), ext.get_variable()),
+      |lc| lc,
+    );
+  }
+  Ok(())
+}
+
+/// Allocate the words `old` with the `size` bytes of their [`AccessWindow`] replaced by the low
+/// `size` bytes of the stored `value`, and the other bytes untouched, i.e. over the 16 bytes of
+/// both words:
+///
+/// `new = old + Σ_{r, j} 2^(8 · (r + j)) · rot_r · (j < size) · (value_byte_j - old_byte_(r + j))`
+pub fn store_words<F, CS>(
+  mut cs: CS,
+  value: &AllocatedNum<F>,
+  old: [&AllocatedNum<F>; 2],
+  window: &AccessWindow<F>,
+  switch: F,
+) -> Result<[AllocatedNum<F>; 2], SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let (rot_val, size_val) = (window.rot_val as usize, window.size_val as usize);
+  let value_val = val(value);
+  let old_val = u128::from(val(old[0])) | (u128::from(val(old[1])) << 64);
+
+  let value_bits = range_check(cs.namespace(|| "value"), value, 64, switch)?;
+  let old_bits = range_check(cs.namespace(|| "old lo"), old[0], 64, switch)?
+    .into_iter()
+    .chain(range_check(cs.namespace(|| "old hi"), old[1], 64, switch)?)
+    .collect::<Vec<_>>();
+
+  let mut new_val = old_val;
+  let mut new = LinearCombination::zero()
+    + old[0].get_variable()
+    + (F::from_u128(1 << 64), old[1].get_variable());
+  for (r, rot) in window.rot.iter().enumerate() {
+    for j in 0..8 {
+      let i = r + j;
+      let stored = rot_val == r && j < size_val;
+      let selected = SwitchBoardCircuit::alloc_bit(
+        &mut cs,
+        || format!("rot = {r} & {j} < size"),
+        Some(stored),
+        switch,
+      )?;
+      cs.enforce(
+        || format!("rot_{r} · in_size_{j} = selected"),
+        |lc| lc + rot.get_variable(),
+        |_| window.in_size(j),
+        |lc| lc + selected.get_variable(),
+      );
+
+      let value_byte = (value_val >> (8 * j)) & 0xff;
+      let old_byte = ((old_val >> (8 * i)) & 0xff) as u64;
+      let delta = SwitchBoardCircuit::alloc_num(
+        &mut cs,
+        || format!("byte {i} delta, rot {r}"),
+        || {
+          Ok(if stored {
+            F::from(value_byte) - F::from(old_byte)
+          } else {
+            F::ZERO
+          })
+        },
+        switch,
+      )?;
+      cs.enforce(
+        || format!("selected · (value byte {j} - old byte {i}) = delta"),
+        |lc| lc + selected.get_variable(),
+        |lc| lc + &pack(&value_bits[8 * j..8 * (j + 1)]) - &pack(&old_bits[8 * i..8 * (i + 1)]),
+        |lc| lc + delta.get_variable(),
+      );
+      new = new + (F::from_u128(1 << (8 * i)), delta.get_variable());
+      if stored {
+        new_val = (new_val & !(0xff << (8 * i))) | (u128::from(value_byte) << (8 * i));
+      }
+    }
+  }
+
+  let new_lo =
+    SwitchBoardCircuit::alloc_num(&mut cs, || "new lo", || Ok(F::from(new_val as u64)), switch)?;
+  let new_hi = SwitchBoardCircuit::alloc_num(
+    &mut cs,
+    || "new hi",
+    || Ok(F::from((new_val >> 64) as u64)),
+    switch,
+  )?;
+  range_check(cs.namespace(|| "new lo bits"), &new_lo, 64, switch)?;
+  range_check(cs.namespace(|| "new hi bits"), &new_hi, 64, switch)?;
+  cs.enforce(
+    || "new lo + 2^64 · new hi = old + Σ 2^(8i) · delta_i",
+    |_| new,
+    |lc| lc + CS::one(),
+    |lc| lc + new_lo.get_variable() + (F::from_u128(1 << 64), new_hi.get_variable()),
+  );
+
+  Ok([new_lo, new_hi])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{access_size, access_window, enforce_loaded, load_extension, store_words};
+  use crate::wasm_snark::switchboard::alu::int64::to_u64;
+  use crate::wasm_snark::switchboard::WASMTransitionCircuit as SwitchBoardCircuit;
+  use bellpepper_core::{test_cs::TestConstraintSystem, ConstraintSystem};
+  use ff::Field;
+  use nova::{provider::Bn256EngineIPA, traits::Engine};
+  use wasmi::{AddressOffset, Instruction as Instr};
+
+  type E = Bn256EngineIPA;
+  type F = <E as Engine>::Scalar;
+
+  /// Whether `instr` loading `loaded` from the bytes at `ea` of the words `ea / 8` & `ea / 8 + 1`
+  /// satisfies the constraints
+  fn is_loaded(instr: Instr, words: [u64; 2], ea: u64, loaded: u64) -> bool {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let mut alloc = |name: &str, val: u64| {
+      SwitchBoardCircuit::alloc_num(&mut cs, || name, || Ok(F::from(val)), F::ONE).unwrap()
+    };
+    let (ea, lo, hi, loaded) = (
+      alloc("ea", ea),
+      alloc("lo", words[0]),
+      alloc("hi", words[1]),
+      alloc("loaded", loaded),
+    );
+    let window =
+      access_window(cs.namespace(|| "access"), &ea, access_size(&instr), F::ONE).unwrap();
+    enforce_loaded(
+      cs.namespace(|| "load"),
+      &loaded,
+      [&lo, &hi],
+      &window,
+      &load_extension(&instr),
+      F::ONE,
+    )
+    .unwrap();
+    cs.is_satisfied()
+  }
+
+  #[test]
+  fn test_unaligned_load() {
+    // The bytes of the words 5 & 6 are 0x10, 0x11, ..., 0x1f
+    let words = [0x1716151413121110, 0x1f1e1d1c1b1a1918];
+
+    let offset = AddressOffset::from(0);
+
+    // An i64 load at 8 · 5 + 3 straddles the words 5 & 6
+    assert!(is_loaded(
+      Instr::I64Load(offset),
+      words,
+      43,
+      0x1a19181716151413
+    ));
+    assert!(is_loaded(Instr::I32Load(offset), words, 47, 0x1a191817));
+    assert!(is_loaded(Instr::I64Load(offset), words, 40, words[0]));
+    assert!(is_loaded(Instr::I32Load8U(offset), words, 47, 0x17));
+
+    // The loaded bytes must be the ones of the window
+    assert!(!is_loaded(
+      Instr::I64Load(offset),
+      words,
+      43,
+      0x1a19181716151412
+    ));
+    assert!(!is_loaded(
+      Instr::I64Load(offset),
+      words,
+      43,
+      0x1b1a191817161514
+    ));
+    assert!(!is_loaded(Instr::I32Load16U(offset), words, 42, 0x1413));
+  }
+
+  #[test]
+  fn test_sub_word_load() {
+    let offset = AddressOffset::from(0);
+    let words = [0x8000_00ff, 0];
+
+    // i32.load8_s of 0xff is -1, pushed as a 32-bit value
+    let load8_s = i32::from(0xffu8 as i8) as u32 as u64;
+    assert_eq!(load8_s, 0xffff_ffff);
+    assert!(is_loaded(Instr::I32Load8S(offset), words, 0, load8_s));
+    assert!(is_loaded(Instr::I64Load8S(offset), words, 0, u64::MAX));
+    assert!(is_loaded(Instr::I32Load8U(offset), words, 0, 0xff));
+    assert!(!is_loaded(Instr::I32Load8S(offset), words, 0, 0xff));
+    assert!(!is_loaded(Instr::I32Load8S(offset), words, 0, u64::MAX));
+    assert!(!is_loaded(Instr::I32Load8U(offset), words, 0, load8_s));
+
+    // i64.load16_u of 0x8000 is 32768
+    let load16_u = u64::from(0x8000u16);
+    assert_eq!(load16_u, 32768);
+    assert!(is_loaded(Instr::I64Load16U(offset), words, 2, load16_u));
+    assert!(is_loaded(
+      Instr::I64Load16S(offset),
+      words,
+      2,
+      0xffff_ffff_ffff_8000
+    ));
+    assert!(!is_loaded(
+      Instr::I64Load16U(offset),
+      words,
+      2,
+      0xffff_ffff_ffff_8000
+    ));
+    assert!(!is_loaded(Instr::I64Load16U(offset), words, 2, 0x1_8000));
+  }
+
+  /// Prove `instr` storing `value` at `ea` into the words `old` of `ea / 8` & `ea / 8 + 1`, and
+  /// return the new words if the constraints are satisfied. `tamper` sets the new low word.
+  fn prove_store(
+    instr: Instr,
+    old: [u64; 2],
+    ea: u64,
+    value: u64,
+    tamper: Option<u64>,
+  ) -> Option<[u64; 2]> {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let mut alloc = |name: &str, val: u64| {
+      SwitchBoardCircuit::alloc_num(&mut cs, || name, || Ok(F::from(val)), F::ONE).unwrap()
+    };
+    let (ea, lo, hi, value) = (
+      alloc("ea", ea),
+      alloc("lo", old[0]),
+      alloc("hi", old[1]),
+      alloc("value", value),
+    );
+    let window =
+      access_window(cs.namespace(|| "access"), &ea, access_size(&instr), F::ONE).unwrap();
+    let [new_lo, new_hi] = store_words(
+      cs.namespace(|| "store"),
+      &value,
+      [&lo, &hi],
+      &window,
+      F::ONE,
+    )
+    .unwrap();
+    if let Some(tampered) = tamper {
+      cs.set("store/new lo/num", F::from(tampered));
+    }
+    if !cs.is_satisfied() {
+      return None;
+    }
+    Some([
+      new_lo.get_value().and_then(to_u64)?,
+      new_hi.get_value().and_then(to_u64)?,
+    ])
+  }
+
+  #[test]
+  fn test_sub_word_store() {
+    let offset = AddressOffset::from(0);
+    let old = [0x0706050403020100, 0x0f0e0d0c0b0a0908];
+
+    // i32.store8 only writes the low byte of the value, the other 7 bytes of the word are kept
+    assert_eq!(
+      prove_store(Instr::I32Store8(offset), old, 3, 0x1234_56ab, None),
+      Some([0x07060504ab020100, old[1]])
+    );
+    assert_eq!(
+      prove_store(Instr::I64Store16(offset), old, 7, 0xcdab, None),
+      Some([0xab06050403020100, 0x0f0e0d0c0b0a09cd])
+    );
+    assert_eq!(
+      prove_store(Instr::I64Store(offset), old, 8, u64::MAX, None),
+      Some([old[0], u64::MAX])
+    );
+
+    // The other bytes of the word can't be changed
+    assert_eq!(
+      prove_store(
+        Instr::I32Store8(offset),
+        old,
+        3,
+        0xab,
+        Some(0x07060504ab020101)
+      ),
+      None
+    );
+    assert_eq!(
+      prove_store(
+        Instr::I32Store8(offset),
+        old,
+        3,
+        0xab,
+        Some(0x070605041234ab00)
+      ),
+      None
+    );
+  }
+}
@@ -0,0 +1,764 @@
+//! Float-to-int conversions, on the IEEE 754 bit layout of the float
+use super::{alloc_le_bits, pack};
+use crate::wasm_snark::switchboard::WASMTransitionCircuit as SwitchBoardCircuit;
+use bellpepper_core::{
+  boolean::AllocatedBit, num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError,
+};
+use ff::{Field, PrimeField};
+
+/// Bit layout of an IEEE 754 binary float: sign, exponent & mantissa, from the top bit down
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FloatFormat {
+  /// Number of exponent bits
+  pub exp_bits: usize,
+  /// Number of mantissa bits, without the implicit leading one
+  pub mant_bits: usize,
+}
+
+/// binary32, i.e. `f32`
+pub const F32: FloatFormat = FloatFormat {
+  exp_bits: 8,
+  mant_bits: 23,
+};
+
+/// binary64, i.e. `f64`
+pub const F64: FloatFormat = FloatFormat {
+  exp_bits: 11,
+  mant_bits: 52,
+};
+
+impl FloatFormat {
+  /// Exponent bias
+  pub(super) fn bias(&self) -> u64 {
+    (1 << (self.exp_bits - 1)) - 1
+  }
+}
+
+/// Number of bits of `exp - bias` for a float whose truncation fits in 64 bits
+const SHIFT_BITS: usize = 6;
+
+/// Saturating truncation of the float with bits `a` to an integer of `int_bits` bits, i.e.
+/// `iNN.trunc_sat_fMM_{s,u}`: NaN maps to 0 and floats out of the range of the integer are clamped
+/// to its min or max. Returns the bits of the integer, zero-extended to 64 bits.
+///
+/// # Note
+///
+/// For 1 ≤ |a| < 2^n, with n the number of magnitude bits of the integer, the truncation of |a| is
+/// the quotient of (2^m + mantissa) · 2^(exp - bias) by 2^m, with m the number of mantissa bits. The
+/// quotient is checked with a range check of it and of the remainder, and 2^(exp - bias) is computed
+/// from the bits of the shift.
+pub fn trunc_sat<F, CS>(
+  cs: CS,
+  a: &AllocatedNum<F>,
+  a_bits: u64,
+  format: FloatFormat,
+  signed: bool,
+  int_bits: usize,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  truncate(cs, a, a_bits, format, signed, int_bits, false, switch)
+}
+
+/// Truncation of the float with bits `a` to an integer of `int_bits` bits, i.e.
+/// `iNN.trunc_fMM_{s,u}`. Returns the bits of the integer, zero-extended to 64 bits.
+///
+/// Wasm traps on NaN & on floats whose truncation is out of the range of the integer, so the
+/// constraints are unsatisfiable for them: a step executing the opcode on such a float can not be
+/// proven.
+pub fn trunc<F, CS>(
+  cs: CS,
+  a: &AllocatedNum<F>,
+  a_bits: u64,
+  format: FloatFormat,
+  signed: bool,
+  int_bits: usize,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  truncate(cs, a, a_bits, format, signed, int_bits, true, switch)
+}
+
+/// [`trunc_sat`], with the constraints of [`trunc`] rejecting the floats it clamps when `trapping`
+#[allow(clippy::too_many_arguments)]
+fn truncate<F, CS>(
+  mut cs: CS,
+  a: &AllocatedNum<F>,
+  a_bits: u64,
+  format: FloatFormat,
+  signed: bool,
+  int_bits: usize,
+  trapping: bool,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let FloatFormat {
+    exp_bits,
+    mant_bits,
+  } = format;
+  let bias = format.bias();
+  // number of bits of the magnitude of the integer
+  let mag_bits = if signed { int_bits - 1 } else { int_bits };
+
+  let one = SwitchBoardCircuit::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+
+  // Decompose the float into sign, exponent & mantissa
+  let (bits, packed) = alloc_le_bits(
+    cs.namespace(|| "a bits"),
+    u128::from(a_bits),
+    1 + exp_bits + mant_bits,
+    switch,
+  )?;
+  cs.enforce(
+    || "a = packed bits",
+    |lc| lc + a.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc + packed.get_variable(),
+  );
+  let mant_val = a_bits & ((1 << mant_bits) - 1);
+  let exp_val = (a_bits >> mant_bits) & ((1 << exp_bits) - 1);
+  let sign_val = (a_bits >> (mant_bits + exp_bits)) & 1 == 1;
+  let mant = pack(&bits[..mant_bits]);
+  let exp = pack(&bits[mant_bits..mant_bits + exp_bits]);
+  let sign = &bits[mant_bits + exp_bits];
+
+  // NaN: all exponent bits set & a non-zero mantissa
+  let exp_max = ge_const(
+    cs.namespace(|| "exp is max"),
+    &exp,
+    exp_val,
+    (1 << exp_bits) - 1,
+    exp_bits,
+    &one,
+    switch,
+  )?;
+  let mant_nz = nonzero(
+    cs.namespace(|| "mant != 0"),
+    &mant,
+    F::from(mant_val),
+    switch,
+  )?;
+  let is_nan = AllocatedBit::and(cs.namespace(|| "is nan"), &exp_max, &mant_nz)?;
+
+  // |a| ≥ 1 iff exp ≥ bias, and |a| ≥ 2^n (incl. infinities & NaN) iff exp ≥ bias + n
+  let ge_one = ge_const(
+    cs.namespace(|| "|a| >= 1"),
+    &exp,
+    exp_val,
+    bias,
+    exp_bits,
+    &one,
+    switch,
+  )?;
+  let big_val = exp_val >= bias + mag_bits as u64;
+  let big = ge_const(
+    cs.namespace(|| "|a| >= 2^n"),
+    &exp,
+    exp_val,
+    bias + mag_bits as u64,
+    exp_bits,
+    &one,
+    switch,
+  )?;
+  if trapping {
+    trap(
+      cs.namespace(|| "trap"),
+      &bits,
+      a_bits,
+      format,
+      signed,
+      mag_bits,
+      (&ge_one, &big),
+      &one,
+      switch,
+    )?;
+  }
+
+  // big implies ge_one, so their difference is a bit
+  let in_range_val = exp_val >= bias && !big_val;
+  let in_range = LinearCombination::zero() + ge_one.get_variable() - big.get_variable();
+
+  // shift = in_range · (exp - bias), in [0, n)
+  let shift_val = if in_range_val { exp_val - bias } else { 0 };
+  let (shift_bits, shift) = alloc_le_bits(
+    cs.namespace(|| "shift"),
+    u128::from(shift_val),
+    SHIFT_BITS,
+    switch,
+  )?;
+  cs.enforce(
+    || "in_range * (exp - bias) = shift",
+    |_| in_range.clone(),
+    |lc| lc + &exp - (F::from(bias), one.get_variable()),
+    |lc| lc + shift.get_variable(),
+  );
+
+  // pow = 2^shift = Π (1 + (2^(2^i) - 1) · shift_i)
+  let mut pow = one.clone();
+  for (i, bit) in shift_bits.iter().enumerate() {
+    let pow_val = 1u128 << (shift_val & ((1 << (i + 1)) - 1));
+    let next = SwitchBoardCircuit::alloc_num(
+      &mut cs,
+      || format!("2^shift_{i}"),
+      || Ok(F::from_u128(pow_val)),
+      switch,
+    )?;
+    cs.enforce(
+      || format!("pow_{i} * (1 + (2^2^{i} - 1) * shift_{i}) = pow_{}", i + 1),
+      |lc| lc + pow.get_variable(),
+      |lc| lc + CS::one() + (F::from_u128((1 << (1 << i)) - 1), bit.get_variable()),
+      |lc| lc + next.get_variable(),
+    );
+    pow = next;
+  }
+
+  // m = in_range · (2^m + mant), i.e. the significand of an in-range float & 0 otherwise
+  let m_val = if in_range_val {
+    (1u128 << mant_bits) + u128::from(mant_val)
+  } else {
+    0
+  };
+  let m = SwitchBoardCircuit::alloc_num(&mut cs, || "m", || Ok(F::from_u128(m_val)), switch)?;
+  cs.enforce(
+    || "in_range * (2^m + mant) = m",
+    |_| in_range,
+    |lc| lc + (F::from_u128(1 << mant_bits), one.get_variable()) + &mant,
+    |lc| lc + m.get_variable(),
+  );
+  let prod_val = m_val << shift_val;
+  let prod = SwitchBoardCircuit::alloc_num(
+    &mut cs,
+    || "m * 2^shift",
+    || Ok(F::from_u128(prod_val)),
+    switch,
+  )?;
+  cs.enforce(
+    || "m * pow = prod",
+    |lc| lc + m.get_variable(),
+    |lc| lc + pow.get_variable(),
+    |lc| lc + prod.get_variable(),
+  );
+
+  // t = prod / 2^m, the truncation of |a| when in range & 0 otherwise
+  let t_val = prod_val >> mant_bits;
+  let (_, t) = alloc_le_bits(cs.namespace(|| "t"), t_val, mag_bits, switch)?;
+  let (_, r) = alloc_le_bits(
+    cs.namespace(|| "r"),
+    prod_val & ((1 << mant_bits) - 1),
+    mant_bits,
+    switch,
+  )?;
+  cs.enforce(
+    || "t * 2^m + r = prod",
+    |lc| lc + (F::from_u128(1 << mant_bits), t.get_variable()) + r.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc + prod.get_variable(),
+  );
+
+  // sign · t, and whether it is non-zero to negate t in two's complement
+  let sign_t = SwitchBoardCircuit::alloc_num(
+    &mut cs,
+    || "sign * t",
+    || Ok(F::from_u128(if sign_val { t_val } else { 0 })),
+    switch,
+  )?;
+  cs.enforce(
+    || "sign * t = sign_t",
+    |lc| lc + sign.get_variable(),
+    |lc| lc + t.get_variable(),
+    |lc| lc + sign_t.get_variable(),
+  );
+  let t_nz = nonzero(
+    cs.namespace(|| "t != 0"),
+    &(LinearCombination::zero() + t.get_variable()),
+    F::from_u128(t_val),
+    switch,
+  )?;
+  let sign_t_nz = AllocatedBit::and(cs.namespace(|| "sign & t != 0"), sign, &t_nz)?;
+
+  // The integer of an in-range float:
+  //   signed: (1 - sign) · t + sign · (2^N · (t != 0) - t)
+  //   unsigned: (1 - sign) · t
+  // and of an out-of-range one:
+  //   signed: sign ? MIN : MAX, i.e. MAX + sign
+  //   unsigned: sign ? 0 : MAX
+  let range = 1u128 << int_bits;
+  let (mag_val, sat_val) = match (signed, sign_val) {
+    (true, false) => (t_val, (range >> 1) - 1),
+    (true, true) => ((range - t_val) % range, range >> 1),
+    (false, false) => (t_val, range - 1),
+    (false, true) => (0, 0),
+  };
+  let (mag, sat) = if signed {
+    (
+      LinearCombination::zero() + t.get_variable() - (F::from(2), sign_t.get_variable())
+        + (F::from_u128(range), sign_t_nz.get_variable()),
+      LinearCombination::zero()
+        + (F::from_u128((range >> 1) - 1), one.get_variable())
+        + sign.get_variable(),
+    )
+  } else {
+    (
+      LinearCombination::zero() + t.get_variable() - sign_t.get_variable(),
+      LinearCombination::zero() + (F::from_u128(range - 1), one.get_variable())
+        - (F::from_u128(range - 1), sign.get_variable()),
+    )
+  };
+
+  // res = big ? sat : mag
+  let res_val = if big_val { sat_val } else { mag_val };
+  let res = SwitchBoardCircuit::alloc_num(&mut cs, || "res", || Ok(F::from_u128(res_val)), switch)?;
+  cs.enforce(
+    || "big * (sat - mag) = res - mag",
+    |lc| lc + big.get_variable(),
+    |lc| lc + &sat - &mag,
+    |lc| lc + res.get_variable() - &mag,
+  );
+
+  // NaN maps to 0
+  let is_nan_val = exp_val == (1 << exp_bits) - 1 && mant_val != 0;
+  let Z = SwitchBoardCircuit::alloc_num(
+    &mut cs,
+    || "Z",
+    || Ok(F::from_u128(if is_nan_val { 0 } else { res_val })),
+    switch,
+  )?;
+  cs.enforce(
+    || "(1 - is_nan) * res = Z",
+    |lc| lc + CS::one() - is_nan.get_variable(),
+    |lc| lc + res.get_variable(),
+    |lc| lc + Z.get_variable(),
+  );
+
+  Ok(Z)
+}
+
+/// Enforce the truncation to `mag_bits` magnitude bits of the float with little-endian `bits` is
+/// in the range of the integer, given the bits `|a| >= 1` & `|a| >= 2^mag_bits`
+///
+/// # Note
+///
+/// NaN & the infinities have the max exponent, so they are `|a| >= 2^mag_bits`. Of those floats,
+/// the only ones in range are the signed ones in (MIN - 1, MIN], i.e. with the exponent of MIN & no
+/// mantissa bit above the fraction.
+#[allow(clippy::too_many_arguments)]
+fn trap<F, CS>(
+  mut cs: CS,
+  bits: &[AllocatedBit],
+  a_bits: u64,
+  format: FloatFormat,
+  signed: bool,
+  mag_bits: usize,
+  (ge_one, big): (&AllocatedBit, &AllocatedBit),
+  one: &AllocatedNum<F>,
+  switch: F,
+) -> Result<(), SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let FloatFormat {
+    exp_bits,
+    mant_bits,
+  } = format;
+  let sign = &bits[mant_bits + exp_bits];
+
+  if !signed {
+    // Any float ≥ 2^n, and any float ≤ -1, i.e. truncating to a negative integer
+    cs.enforce(
+      || "big = 0",
+      |lc| lc + big.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc,
+    );
+    cs.enforce(
+      || "sign * ge_one = 0",
+      |lc| lc + sign.get_variable(),
+      |lc| lc + ge_one.get_variable(),
+      |lc| lc,
+    );
+    return Ok(());
+  }
+
+  let exp = pack(&bits[mant_bits..mant_bits + exp_bits]);
+  let exp_val = (a_bits >> mant_bits) & ((1 << exp_bits) - 1);
+  let above = ge_const(
+    cs.namespace(|| "|a| >= 2^(n + 1)"),
+    &exp,
+    exp_val,
+    format.bias() + mag_bits as u64 + 1,
+    exp_bits,
+    one,
+    switch,
+  )?;
+  // The mantissa bits weighing at least 1 once shifted by the exponent of MIN
+  let frac_bits = mant_bits.saturating_sub(mag_bits);
+  let int_mant = &bits[frac_bits..mant_bits];
+  let int_mant_val = (a_bits & ((1 << mant_bits) - 1)) >> frac_bits;
+  let int_mant_nz = nonzero(
+    cs.namespace(|| "integer mantissa != 0"),
+    &pack(int_mant),
+    F::from(int_mant_val),
+    switch,
+  )?;
+
+  // Of the floats ≥ 2^n, only the negative ones below 2^(n + 1) truncating to -2^n are in range
+  cs.enforce(
+    || "above = 0",
+    |lc| lc + above.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc,
+  );
+  cs.enforce(
+    || "big * (1 - sign) = 0",
+    |lc| lc + big.get_variable(),
+    |lc| lc + CS::one() - sign.get_variable(),
+    |lc| lc,
+  );
+  cs.enforce(
+    || "big * int_mant_nz = 0",
+    |lc| lc + big.get_variable(),
+    |lc| lc + int_mant_nz.get_variable(),
+    |lc| lc,
+  );
+  Ok(())
+}
+
+/// Allocate the bit `x >= c`, for `x` and `c` less than `2^n`
+pub(super) fn ge_const<F, CS>(
+  mut cs: CS,
+  x: &LinearCombination<F>,
+  x_val: u64,
+  c: u64,
+  n: usize,
+  one: &AllocatedNum<F>,
+  switch: F,
+) -> Result<AllocatedBit, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  // x - c + 2^n is in [0, 2^(n + 1)), and its bit n is set iff x >= c
+  let (mut bits, packed) = alloc_le_bits(
+    cs.namespace(|| "x - c + 2^n"),
+    u128::from(x_val) + (1 << n) - u128::from(c),
+    n + 1,
+    switch,
+  )?;
+  cs.enforce(
+    || "x - c + 2^n = packed",
+    |lc| lc + x - (F::from(c), one.get_variable()) + (F::from_u128(1 << n), one.get_variable()),
+    |lc| lc + CS::one(),
+    |lc| lc + packed.get_variable(),
+  );
+  Ok(bits.swap_remove(n))
+}
+
+/// Allocate the bit `x != 0`
+pub(super) fn nonzero<F, CS>(
+  mut cs: CS,
+  x: &LinearCombination<F>,
+  x_val: F,
+  switch: F,
+) -> Result<AllocatedBit, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let nz = SwitchBoardCircuit::alloc_bit(
+    &mut cs,
+    || "x != 0",
+    Some(!bool::from(x_val.is_zero())),
+    switch,
+  )?;
+  let inv = SwitchBoardCircuit::alloc_num(
+    &mut cs,
+    || "x^-1",
+    || Ok(Option::from(x_val.invert()).unwrap_or(F::ZERO)),
+    switch,
+  )?;
+  cs.enforce(
+    || "x * x^-1 = nz",
+    |lc| lc + x,
+    |lc| lc + inv.get_variable(),
+    |lc| lc + nz.get_variable(),
+  );
+  cs.enforce(
+    || "x * (1 - nz) = 0",
+    |lc| lc + x,
+    |lc| lc + CS::one() - nz.get_variable(),
+    |lc| lc,
+  );
+  Ok(nz)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{trunc, trunc_sat, FloatFormat, F32, F64};
+  use crate::wasm_snark::switchboard::WASMTransitionCircuit as SwitchBoardCircuit;
+  use bellpepper_core::{test_cs::TestConstraintSystem, ConstraintSystem};
+  use ff::Field;
+  use nova::{provider::Bn256EngineIPA, traits::Engine};
+  use wasmi::core::{TrapCode, UntypedValue};
+
+  type E = Bn256EngineIPA;
+  type F = <E as Engine>::Scalar;
+
+  const F32_INPUTS: [f32; 14] = [
+    f32::NAN,
+    1e30,
+    -1e30,
+    f32::INFINITY,
+    f32::NEG_INFINITY,
+    2_147_483_648.0,
+    -2_147_483_648.0,
+    4_294_967_296.0,
+    -3.7,
+    2.5,
+    0.5,
+    -0.0,
+    0.0,
+    16_777_215.0,
+  ];
+
+  const F64_INPUTS: [f64; 16] = [
+    f64::NAN,
+    1e30,
+    -1e30,
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+    2_147_483_647.9,
+    2_147_483_648.0,
+    -2_147_483_648.9,
+    -2_147_483_649.0,
+    4_294_967_295.5,
+    9_223_372_036_854_775_807.0,
+    -9_223_372_036_854_775_808.0,
+    -3.7,
+    2.5,
+    0.5,
+    -0.0,
+  ];
+
+  /// Prove the truncation of `a` is `expected`, trapping on the floats out of range if `trapping`
+  fn prove(
+    a: UntypedValue,
+    format: FloatFormat,
+    signed: bool,
+    int_bits: usize,
+    trapping: bool,
+    expected: u64,
+  ) -> bool {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let one_var = <TestConstraintSystem<F> as ConstraintSystem<F>>::one();
+    let switch = F::ONE;
+
+    let a_bits = a.to_bits();
+    let a_num =
+      SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a_bits)), switch).unwrap();
+    let cs_trunc = cs.namespace(|| "trunc");
+    let res = if trapping {
+      trunc(cs_trunc, &a_num, a_bits, format, signed, int_bits, switch)
+    } else {
+      trunc_sat(cs_trunc, &a_num, a_bits, format, signed, int_bits, switch)
+    }
+    .unwrap();
+
+    let expected_num =
+      SwitchBoardCircuit::alloc_num(&mut cs, || "expected", || Ok(F::from(expected)), switch)
+        .unwrap();
+    cs.enforce(
+      || "expected == res",
+      |lc| lc + expected_num.get_variable(),
+      |lc| lc + one_var,
+      |lc| lc + res.get_variable(),
+    );
+    cs.is_satisfied()
+  }
+
+  /// Prove the saturating truncation of `a` & check it against wasmi
+  fn check(a: UntypedValue, format: FloatFormat, signed: bool, int_bits: usize, expected: u64) {
+    assert!(
+      prove(a, format, signed, int_bits, false, expected),
+      "{:#x} {format:?} {signed} {int_bits}",
+      a.to_bits()
+    );
+  }
+
+  /// Prove the trapping truncation of `a` against wasmi: a trap must be unsatisfiable, even for
+  /// the result the saturating truncation gives
+  fn check_trunc(
+    a: UntypedValue,
+    format: FloatFormat,
+    signed: bool,
+    int_bits: usize,
+    expected: Result<UntypedValue, TrapCode>,
+    saturated: UntypedValue,
+  ) {
+    let a_bits = a.to_bits();
+    match expected {
+      Ok(z) => assert!(
+        prove(a, format, signed, int_bits, true, z.to_bits()),
+        "{a_bits:#x} {format:?} {signed} {int_bits}"
+      ),
+      Err(_) => assert!(
+        !prove(a, format, signed, int_bits, true, saturated.to_bits()),
+        "{a_bits:#x} {format:?} {signed} {int_bits} must trap"
+      ),
+    }
+  }
+
+  #[test]
+  fn test_trunc_sat() {
+    for a in F32_INPUTS.map(UntypedValue::from) {
+      check(a, F32, true, 32, a.i32_trunc_sat_f32_s().to_bits());
+      check(a, F32, false, 32, a.i32_trunc_sat_f32_u().to_bits());
+      check(a, F32, true, 64, a.i64_trunc_sat_f32_s().to_bits());
+      check(a, F32, false, 64, a.i64_trunc_sat_f32_u().to_bits());
+    }
+    for a in F64_INPUTS.map(UntypedValue::from) {
+      check(a, F64, true, 32, a.i32_trunc_sat_f64_s().to_bits());
+      check(a, F64, false, 32, a.i32_trunc_sat_f64_u().to_bits());
+      check(a, F64, true, 64, a.i64_trunc_sat_f64_s().to_bits());
+      check(a, F64, false, 64, a.i64_trunc_sat_f64_u().to_bits());
+    }
+  }
+
+  #[test]
+  fn test_trunc() {
+    for a in F32_INPUTS.map(UntypedValue::from) {
+      check_trunc(
+        a,
+        F32,
+        true,
+        32,
+        a.i32_trunc_f32_s(),
+        a.i32_trunc_sat_f32_s(),
+      );
+      check_trunc(
+        a,
+        F32,
+        false,
+        32,
+        a.i32_trunc_f32_u(),
+        a.i32_trunc_sat_f32_u(),
+      );
+      check_trunc(
+        a,
+        F32,
+        true,
+        64,
+        a.i64_trunc_f32_s(),
+        a.i64_trunc_sat_f32_s(),
+      );
+      check_trunc(
+        a,
+        F32,
+        false,
+        64,
+        a.i64_trunc_f32_u(),
+        a.i64_trunc_sat_f32_u(),
+      );
+    }
+    for a in F64_INPUTS.map(UntypedValue::from) {
+      check_trunc(
+        a,
+        F64,
+        true,
+        32,
+        a.i32_trunc_f64_s(),
+        a.i32_trunc_sat_f64_s(),
+      );
+      check_trunc(
+        a,
+        F64,
+        false,
+        32,
+        a.i32_trunc_f64_u(),
+        a.i32_trunc_sat_f64_u(),
+      );
+      check_trunc(
+        a,
+        F64,
+        true,
+        64,
+        a.i64_trunc_f64_s(),
+        a.i64_trunc_sat_f64_s(),
+      );
+      check_trunc(
+        a,
+        F64,
+        false,
+        64,
+        a.i64_trunc_f64_u(),
+        a.i64_trunc_sat_f64_u(),
+      );
+    }
+  }
+
+  #[test]
+  fn test_trunc_traps() {
+    // i32::MAX as f32 rounds up to 2^31, & the next f32 is 2^31 + 256: both are out of range
+    let max = UntypedValue::from(i32::MAX as f32);
+    let above_max = UntypedValue::from(f32::from_bits((i32::MAX as f32).to_bits() + 1));
+    let nan = UntypedValue::from(f32::NAN);
+    for a in [max, above_max, nan] {
+      assert!(a.i32_trunc_f32_s().is_err());
+      assert!(!prove(
+        a,
+        F32,
+        true,
+        32,
+        true,
+        a.i32_trunc_sat_f32_s().to_bits()
+      ));
+      assert!(!prove(a, F32, true, 32, true, 0));
+    }
+    // but the f32 below 2^31 is not
+    let below_max = UntypedValue::from(f32::from_bits((i32::MAX as f32).to_bits() - 1));
+    assert!(prove(below_max, F32, true, 32, true, 2_147_483_520));
+
+    // Unsigned truncation accepts (-1, 0) but traps from -1
+    assert!(prove(UntypedValue::from(-0.9f32), F32, false, 32, true, 0));
+    assert!(!prove(UntypedValue::from(-1.0f32), F32, false, 32, true, 0));
+  }
+
+  #[test]
+  fn test_trunc_sat_saturates() {
+    let i32_min = i32::MIN as u32 as u64;
+    for (format, nan, big, small) in [
+      (
+        F32,
+        UntypedValue::from(f32::NAN),
+        UntypedValue::from(1e30f32),
+        UntypedValue::from(-1e30f32),
+      ),
+      (
+        F64,
+        UntypedValue::from(f64::NAN),
+        UntypedValue::from(1e30f64),
+        UntypedValue::from(-1e30f64),
+      ),
+    ] {
+      for (signed, int_bits, max, min) in [
+        (true, 32, i32::MAX as u64, i32_min),
+        (false, 32, u32::MAX as u64, 0),
+        (true, 64, i64::MAX as u64, i64::MIN as u64),
+        (false, 64, u64::MAX, 0),
+      ] {
+        check(nan, format, signed, int_bits, 0);
+        check(big, format, signed, int_bits, max);
+        check(small, format, signed, int_bits, min);
+      }
+    }
+  }
+}
@@ -0,0 +1,258 @@
+//! `f32.add`, `f32.sub`, `f32.mul` & `f32.div`, on the IEEE 754 bit layout of the floats
+//!
+//! Unlike the f64 ops, each op has its own switch, so each function synthesizes its op alone.
+use super::{
+  float::F32,
+  ieee754::{add, div, mul, unpack, Ctx},
+};
+use crate::wasm_snark::switchboard::WASMTransitionCircuit as SwitchBoardCircuit;
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::PrimeField;
+
+#[derive(Clone, Copy)]
+enum F32Op {
+  Add,
+  Sub,
+  Mul,
+  Div,
+}
+
+impl F32Op {
+  /// The result of the op in wasmi
+  fn native(self, a: u32, b: u32) -> u32 {
+    let (a, b) = (f32::from_bits(a), f32::from_bits(b));
+    match self {
+      Self::Add => a + b,
+      Self::Sub => a - b,
+      Self::Mul => a * b,
+      Self::Div => a / b,
+    }
+    .to_bits()
+  }
+}
+
+/// `a + b`
+pub fn f32_add<F, CS>(
+  cs: CS,
+  a: &AllocatedNum<F>,
+  b: &AllocatedNum<F>,
+  a_bits: u32,
+  b_bits: u32,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  f32_op(cs, a, b, a_bits, b_bits, F32Op::Add, switch)
+}
+
+/// `a - b`
+pub fn f32_sub<F, CS>(
+  cs: CS,
+  a: &AllocatedNum<F>,
+  b: &AllocatedNum<F>,
+  a_bits: u32,
+  b_bits: u32,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  f32_op(cs, a, b, a_bits, b_bits, F32Op::Sub, switch)
+}
+
+/// `a · b`
+pub fn f32_mul<F, CS>(
+  cs: CS,
+  a: &AllocatedNum<F>,
+  b: &AllocatedNum<F>,
+  a_bits: u32,
+  b_bits: u32,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  f32_op(cs, a, b, a_bits, b_bits, F32Op::Mul, switch)
+}
+
+/// `a / b`
+pub fn f32_div<F, CS>(
+  cs: CS,
+  a: &AllocatedNum<F>,
+  b: &AllocatedNum<F>,
+  a_bits: u32,
+  b_bits: u32,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  f32_op(cs, a, b, a_bits, b_bits, F32Op::Div, switch)
+}
+
+/// `op` on the f32s with bits `a_bits` & `b_bits`. A NaN result is the quiet NaN wasmi returns,
+/// though any other quiet NaN satisfies the constraints too.
+fn f32_op<F, CS>(
+  mut cs: CS,
+  a: &AllocatedNum<F>,
+  b: &AllocatedNum<F>,
+  a_bits: u32,
+  b_bits: u32,
+  op: F32Op,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let one = SwitchBoardCircuit::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+  let ctx = Ctx::new(&one, switch, F32);
+
+  let x = unpack(cs.namespace(|| "unpack a"), &ctx, a, u64::from(a_bits))?;
+  let y = unpack(cs.namespace(|| "unpack b"), &ctx, b, u64::from(b_bits))?;
+  let (nan, res) = match op {
+    F32Op::Add | F32Op::Sub => {
+      let negate = ctx.constant(i128::from(matches!(op, F32Op::Sub)));
+      add(cs.namespace(|| "a + b"), &ctx, &x, &y, &negate)?
+    }
+    F32Op::Mul => mul(cs.namespace(|| "a * b"), &ctx, &x, &y)?,
+    F32Op::Div => div(cs.namespace(|| "a / b"), &ctx, &x, &y)?,
+  };
+  let z_bits = u64::from(op.native(a_bits, b_bits));
+  let res = ctx.nan_or(cs.namespace(|| "nan or res"), &nan, &res, z_bits)?;
+
+  let Z = SwitchBoardCircuit::alloc_num(
+    &mut cs,
+    || "Z",
+    || Ok(F::from(u64::try_from(res.val).unwrap_or_default())),
+    switch,
+  )?;
+  cs.enforce(
+    || "Z = res",
+    |_| res.lc,
+    |lc| lc + CS::one(),
+    |lc| lc + Z.get_variable(),
+  );
+  Ok(Z)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{f32_add, f32_div, f32_mul, f32_sub};
+  use crate::wasm_snark::switchboard::WASMTransitionCircuit as SwitchBoardCircuit;
+  use bellpepper_core::{test_cs::TestConstraintSystem, ConstraintSystem};
+  use ff::Field;
+  use nova::{provider::Bn256EngineIPA, traits::Engine};
+  use wasmi::core::UntypedValue;
+
+  type E = Bn256EngineIPA;
+  type F = <E as Engine>::Scalar;
+
+  const OPS: [&str; 4] = ["add", "sub", "mul", "div"];
+
+  const F32_INPUTS: [f32; 14] = [
+    0.0,
+    -0.0,
+    1.0,
+    -1.5,
+    0.1,
+    3.0,
+    f32::MAX,
+    -f32::MAX,
+    f32::MIN_POSITIVE,
+    1e-45,
+    f32::EPSILON,
+    f32::INFINITY,
+    f32::NEG_INFINITY,
+    f32::NAN,
+  ];
+
+  /// Prove `op` on `a` & `b`, and check the result against wasmi
+  fn check(op: &str, a: f32, b: f32) {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let switch = F::ONE;
+    let (a_bits, b_bits) = (a.to_bits(), b.to_bits());
+    let a_num =
+      SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(u64::from(a_bits))), switch)
+        .unwrap();
+    let b_num =
+      SwitchBoardCircuit::alloc_num(&mut cs, || "b", || Ok(F::from(u64::from(b_bits))), switch)
+        .unwrap();
+
+    let (x, y) = (UntypedValue::from(a), UntypedValue::from(b));
+    let ns = cs.namespace(|| op);
+    let (Z, expected) = match op {
+      "add" => (
+        f32_add(ns, &a_num, &b_num, a_bits, b_bits, switch),
+        x.f32_add(y),
+      ),
+      "sub" => (
+        f32_sub(ns, &a_num, &b_num, a_bits, b_bits, switch),
+        x.f32_sub(y),
+      ),
+      "mul" => (
+        f32_mul(ns, &a_num, &b_num, a_bits, b_bits, switch),
+        x.f32_mul(y),
+      ),
+      _ => (
+        f32_div(ns, &a_num, &b_num, a_bits, b_bits, switch),
+        x.f32_div(y),
+      ),
+    };
+
+    assert!(cs.is_satisfied(), "{a:?} {op} {b:?}");
+    assert_eq!(
+      Z.unwrap().get_value(),
+      Some(F::from(expected.to_bits())),
+      "{a:?} {op} {b:?}"
+    );
+  }
+
+  fn check_all(a: f32, b: f32) {
+    for op in OPS {
+      check(op, a, b);
+    }
+  }
+
+  #[test]
+  fn test_f32_special_values() {
+    for a in F32_INPUTS {
+      for b in F32_INPUTS {
+        check_all(a, b);
+      }
+    }
+  }
+
+  #[test]
+  fn test_f32_round_to_nearest_even() {
+    let ulp = f32::EPSILON;
+    // Exact ties round to the even significand, down from 1 & up from 1 + ulp
+    check_all(1.0, ulp / 2.0);
+    check_all(1.0 + ulp, ulp / 2.0);
+    // just past a tie rounds up, below it down
+    check_all(1.0, ulp / 2.0 + ulp / 4.0);
+    check_all(1.0, ulp / 4.0);
+    // cancellation, & products or quotients that need rounding
+    check_all(1.0 + ulp, -1.0);
+    check_all(1.0 / 3.0, 3.0);
+    check_all(16_777_215.0, 3.0);
+    // subnormal results
+    check_all(f32::MIN_POSITIVE, 0.75);
+    check_all(1e-45, 0.5);
+  }
+
+  #[test]
+  fn test_f32_overflow() {
+    // Overflows round to ∞, incl. the tie half an ulp past the largest float
+    check_all(f32::MAX, f32::MAX);
+    check_all(f32::MAX, 2.0);
+    check_all(-f32::MAX, 2.0);
+    check_all(f32::MAX, f32::from_bits(0x7300_0000));
+    check_all(f32::MAX, 1e-30);
+  }
+}
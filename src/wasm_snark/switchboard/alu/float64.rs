@@ -0,0 +1,225 @@
+//! `f64.add`, `f64.sub`, `f64.mul` & `f64.div`, on the IEEE 754 bit layout of the floats
+//!
+//! The arithmetic is shared with f32, see `ieee754`; this module selects the op of the step.
+use super::{
+  float::F64,
+  ieee754::{add, div, mul, unpack, Ctx, Num},
+};
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError};
+use ff::PrimeField;
+
+/// The f64 ops of [`arith`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum F64Op {
+  Add,
+  Sub,
+  Mul,
+  Div,
+}
+
+impl F64Op {
+  /// Every op, in the order [`arith`] takes their switches in
+  pub const ALL: [F64Op; 4] = [F64Op::Add, F64Op::Sub, F64Op::Mul, F64Op::Div];
+}
+
+/// `op` on the f64s with bits `x_bits` & `y_bits`, for each op in [`F64Op`] at once. Returns the
+/// bits of the result if `op` is one of them & 0 otherwise.
+///
+/// The result of each op is computed, then selected by `is_op`, the switches of the ops of
+/// [`F64Op::ALL`], which the caller constrains to be bits of which at most one is set. A NaN
+/// result is the quiet NaN with the sign & payload of `z_bits`, the result the prover claims.
+#[allow(clippy::too_many_arguments)]
+pub fn arith<F, CS>(
+  mut cs: CS,
+  X: &AllocatedNum<F>,
+  Y: &AllocatedNum<F>,
+  x_bits: u64,
+  y_bits: u64,
+  z_bits: u64,
+  op: Option<F64Op>,
+  is_op: &[AllocatedNum<F>],
+  one: &AllocatedNum<F>,
+  switch: F,
+) -> Result<LinearCombination<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let ctx = Ctx::new(one, switch, F64);
+
+  let [is_add, is_sub, is_mul, is_div] =
+    F64Op::ALL.map(|o| Num::var(&is_op[o as usize], i128::from(op == Some(o))));
+  let is_sum = &is_add + &is_sub;
+
+  let x = unpack(cs.namespace(|| "unpack x"), &ctx, X, x_bits)?;
+  let y = unpack(cs.namespace(|| "unpack y"), &ctx, Y, y_bits)?;
+  let (sum_nan, sum) = add(cs.namespace(|| "x + y"), &ctx, &x, &y, &is_sub)?;
+  let (prod_nan, prod) = mul(cs.namespace(|| "x * y"), &ctx, &x, &y)?;
+  let (quot_nan, quot) = div(cs.namespace(|| "x / y"), &ctx, &x, &y)?;
+
+  let mut res = ctx.constant(0);
+  let mut nan = ctx.constant(0);
+  for (name, is, (op_nan, op_res)) in [
+    ("sum", &is_sum, (&sum_nan, &sum)),
+    ("prod", &is_mul, (&prod_nan, &prod)),
+    ("quot", &is_div, (&quot_nan, &quot)),
+  ] {
+    res = &res + &ctx.product(cs.namespace(|| format!("is {name} * {name}")), is, op_res)?;
+    nan = &nan + &ctx.product(cs.namespace(|| format!("is {name} * nan")), is, op_nan)?;
+  }
+
+  let res = ctx.nan_or(cs.namespace(|| "nan or res"), &nan, &res, z_bits)?;
+
+  Ok(res.lc)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{arith, F64Op};
+  use crate::wasm_snark::switchboard::WASMTransitionCircuit as SwitchBoardCircuit;
+  use bellpepper_core::{test_cs::TestConstraintSystem, ConstraintSystem};
+  use ff::Field;
+  use nova::{provider::Bn256EngineIPA, traits::Engine};
+  use rand::{rngs::StdRng, Rng, SeedableRng};
+  use wasmi::core::UntypedValue;
+
+  type E = Bn256EngineIPA;
+  type F = <E as Engine>::Scalar;
+
+  /// Bits of the signaling NaN with payload 1
+  const SNAN: u64 = 0x7ff0_0000_0000_0001;
+
+  const F64_INPUTS: [f64; 18] = [
+    0.0,
+    -0.0,
+    1.0,
+    -1.5,
+    0.1,
+    3.0,
+    f64::MAX,
+    -f64::MAX,
+    f64::MIN_POSITIVE,
+    -f64::MIN_POSITIVE,
+    5e-324,
+    -2.5e-320,
+    2.225_073_858_507_201e-308,
+    f64::EPSILON,
+    1e300,
+    f64::INFINITY,
+    f64::NEG_INFINITY,
+    f64::NAN,
+  ];
+
+  fn expected(op: F64Op, x: u64, y: u64) -> u64 {
+    let (x, y) = (UntypedValue::from(x), UntypedValue::from(y));
+    match op {
+      F64Op::Add => x.f64_add(y),
+      F64Op::Sub => x.f64_sub(y),
+      F64Op::Mul => x.f64_mul(y),
+      F64Op::Div => x.f64_div(y),
+    }
+    .to_bits()
+  }
+
+  /// Prove `op` on `x` & `y` claiming the result `z`, returning whether the circuit is satisfied
+  fn prove(op: Option<F64Op>, x: u64, y: u64, z: u64, switch: F) -> bool {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let one_var = <TestConstraintSystem<F> as ConstraintSystem<F>>::one();
+
+    let one = SwitchBoardCircuit::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch).unwrap();
+    let X = SwitchBoardCircuit::alloc_num(&mut cs, || "x", || Ok(F::from(x)), switch).unwrap();
+    let Y = SwitchBoardCircuit::alloc_num(&mut cs, || "y", || Ok(F::from(y)), switch).unwrap();
+    let is_op = F64Op::ALL.map(|o| {
+      let is = F::from((op == Some(o)) as u64);
+      SwitchBoardCircuit::alloc_num(&mut cs, || format!("is {o:?}"), || Ok(is), switch).unwrap()
+    });
+    let res = arith(
+      cs.namespace(|| "arith"),
+      &X,
+      &Y,
+      x,
+      y,
+      z,
+      op,
+      &is_op,
+      &one,
+      switch,
+    )
+    .unwrap();
+
+    let Z = SwitchBoardCircuit::alloc_num(&mut cs, || "z", || Ok(F::from(z)), switch).unwrap();
+    cs.enforce(
+      || "z == res",
+      |lc| lc + Z.get_variable(),
+      |lc| lc + one_var,
+      |_| res,
+    );
+    cs.is_satisfied()
+  }
+
+  /// Check `op` on `x` & `y` against wasmi, along with a result one ulp off
+  fn check(op: F64Op, x: u64, y: u64) {
+    let z = expected(op, x, y);
+    assert!(
+      prove(Some(op), x, y, z, F::ONE),
+      "{op:?} {x:#x} {y:#x} = {z:#x}"
+    );
+    if !f64::from_bits(z).is_nan() {
+      assert!(
+        !prove(Some(op), x, y, z ^ 1, F::ONE),
+        "{op:?} {x:#x} {y:#x} != {:#x}",
+        z ^ 1
+      );
+    }
+  }
+
+  #[test]
+  fn test_f64_arith_special_values() {
+    let inputs = F64_INPUTS.map(f64::to_bits).into_iter().chain([SNAN]);
+    for x in inputs.clone() {
+      for y in inputs.clone() {
+        for op in F64Op::ALL {
+          check(op, x, y);
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_f64_arith_random() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..64 {
+      // Random bits cover the whole range of exponents, near exponents exercise cancellation
+      let x = rng.gen::<u64>();
+      let y = match rng.gen_range(0..3) {
+        0 => rng.gen::<u64>(),
+        1 => (x & !((1 << 52) - 1)) | (rng.gen::<u64>() & ((1 << 52) - 1)),
+        _ => (x ^ (1 << 63)).wrapping_add(rng.gen_range(0..4)),
+      };
+      for op in F64Op::ALL {
+        check(op, x, y);
+      }
+    }
+  }
+
+  #[test]
+  fn test_f64_arith_nan() {
+    let (x, y) = (f64::INFINITY.to_bits(), f64::NEG_INFINITY.to_bits());
+    // ∞ - ∞ may be any quiet NaN, but not a signaling one nor a number
+    assert!(prove(Some(F64Op::Add), x, y, 0xfff8_0000_0000_0000, F::ONE));
+    assert!(prove(Some(F64Op::Add), x, y, 0x7ff8_0000_dead_beef, F::ONE));
+    assert!(!prove(Some(F64Op::Add), x, y, SNAN, F::ONE));
+    assert!(!prove(Some(F64Op::Add), x, y, x, F::ONE));
+    // and a number is not NaN
+    assert!(!prove(Some(F64Op::Add), x, x, f64::NAN.to_bits(), F::ONE));
+  }
+
+  #[test]
+  fn test_f64_arith_other_ops() {
+    // With no op selected the result is zero
+    assert!(prove(None, 1.0f64.to_bits(), 2.0f64.to_bits(), 0, F::ONE));
+    assert!(!prove(None, 1.0f64.to_bits(), 2.0f64.to_bits(), 1, F::ONE));
+    // and with the switch off, every value is zero
+    assert!(prove(Some(F64Op::Div), 1.0f64.to_bits(), 0, 0, F::ZERO));
+  }
+}
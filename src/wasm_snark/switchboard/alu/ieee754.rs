@@ -0,0 +1,789 @@
+//! IEEE 754 arithmetic, on the bit layout of the floats of a [`FloatFormat`]
+//!
+//! Each op reduces its operands to the exact result `M · 2^E`, or to a significand `M` whose lowest
+//! bit is sticky, i.e. set iff any bit past it is, which `round` rounds to the nearest float, ties
+//! to even. Subnormal results & overflows to infinity are rounded as IEEE 754 does, so the result
+//! matches wasmi bit for bit. The one exception is NaN: WASM lets an op return any quiet NaN, so
+//! only its exponent & quiet bit are constrained, see [`Ctx::nan_or`].
+use super::{
+  alloc_le_bits,
+  float::{ge_const, nonzero, FloatFormat},
+  pack,
+};
+use crate::wasm_snark::switchboard::WASMTransitionCircuit as SwitchBoardCircuit;
+use bellpepper_core::{
+  boolean::AllocatedBit, num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError,
+};
+use ff::PrimeField;
+use std::ops::{Add, Mul, Sub};
+
+/// Number of bits of the range checks on exponents & shifts, which are less than 2^13 in magnitude
+const EXP_RANGE_BITS: usize = 14;
+
+/// Number of bits of a shift of a significand, i.e. of a value up to 63
+const SHIFT_BITS: usize = 6;
+
+/// Bits the significand of the larger addend is extended with, two guard bits & a sticky one
+const GUARD_BITS: usize = 3;
+
+/// Encode the integer `v` in the field
+fn field<F: PrimeField>(v: i128) -> F {
+  if v < 0 {
+    -F::from_u128(v.unsigned_abs())
+  } else {
+    F::from_u128(v as u128)
+  }
+}
+
+/// A linear combination & its value
+#[derive(Clone)]
+pub(super) struct Num<F: PrimeField> {
+  pub(super) lc: LinearCombination<F>,
+  pub(super) val: i128,
+}
+
+impl<F: PrimeField> Num<F> {
+  pub(super) fn var(num: &AllocatedNum<F>, val: i128) -> Self {
+    Self {
+      lc: LinearCombination::zero() + num.get_variable(),
+      val,
+    }
+  }
+
+  fn bit(bit: &AllocatedBit, val: bool) -> Self {
+    Self {
+      lc: LinearCombination::zero() + bit.get_variable(),
+      val: i128::from(val),
+    }
+  }
+}
+
+impl<F: PrimeField> Add for &Num<F> {
+  type Output = Num<F>;
+
+  fn add(self, rhs: Self) -> Num<F> {
+    Num {
+      lc: self.lc.clone() + &rhs.lc,
+      val: self.val + rhs.val,
+    }
+  }
+}
+
+impl<F: PrimeField> Sub for &Num<F> {
+  type Output = Num<F>;
+
+  fn sub(self, rhs: Self) -> Num<F> {
+    Num {
+      lc: self.lc.clone() - &rhs.lc,
+      val: self.val - rhs.val,
+    }
+  }
+}
+
+impl<F: PrimeField> Mul<i128> for &Num<F> {
+  type Output = Num<F>;
+
+  fn mul(self, rhs: i128) -> Num<F> {
+    Num {
+      lc: LinearCombination::zero() + (field(rhs), &self.lc),
+      val: self.val * rhs,
+    }
+  }
+}
+
+/// The constant one & the switch the gadgets allocate with, & the format of the floats
+pub(super) struct Ctx<'a, F: PrimeField> {
+  one: &'a AllocatedNum<F>,
+  switch: F,
+  /// Number of mantissa bits, without the implicit leading one
+  mant_bits: usize,
+  /// Number of exponent bits
+  exp_bits: usize,
+  bias: i128,
+}
+
+impl<'a, F: PrimeField> Ctx<'a, F> {
+  pub(super) fn new(one: &'a AllocatedNum<F>, switch: F, format: FloatFormat) -> Self {
+    Self {
+      one,
+      switch,
+      mant_bits: format.mant_bits,
+      exp_bits: format.exp_bits,
+      bias: format.bias() as i128,
+    }
+  }
+
+  /// The float with significand `m` & biased exponent `e` is `m · 2^(e - scale)`
+  fn scale(&self) -> i128 {
+    self.bias + self.mant_bits as i128
+  }
+
+  /// Bits of +∞
+  fn inf(&self) -> i128 {
+    ((1 << self.exp_bits) - 1) << self.mant_bits
+  }
+
+  /// Bit of the sign
+  fn sign(&self) -> i128 {
+    1 << (self.exp_bits + self.mant_bits)
+  }
+
+  /// Largest shift aligning the smaller addend: shifting further only leaves its sticky bit
+  fn max_align(&self) -> i128 {
+    (self.mant_bits + GUARD_BITS + 1) as i128
+  }
+
+  /// `nan ? NaN : res`, for `res` zero when `nan`. The NaN is a quiet NaN, with the sign & payload
+  /// of `z_bits`, the result the prover claims.
+  pub(super) fn nan_or<CS>(
+    &self,
+    mut cs: CS,
+    nan: &Num<F>,
+    res: &Num<F>,
+    z_bits: u64,
+  ) -> Result<Num<F>, SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    let is_nan = nan.val == 1;
+    let nan_sign = self.bit(
+      cs.namespace(|| "nan sign"),
+      is_nan && (z_bits >> (self.exp_bits + self.mant_bits)) & 1 == 1,
+    )?;
+    let payload_bits = self.mant_bits - 1;
+    let payload_val = if is_nan {
+      i128::from(z_bits & ((1 << payload_bits) - 1))
+    } else {
+      0
+    };
+    let (_, payload) =
+      self.alloc_bits(cs.namespace(|| "nan payload"), payload_val, payload_bits)?;
+    let not_nan = self.not(nan);
+    cs.enforce(
+      || "(1 - nan) * nan_sign = 0",
+      |_| not_nan.lc.clone(),
+      |_| nan_sign.lc.clone(),
+      |lc| lc,
+    );
+    cs.enforce(
+      || "(1 - nan) * payload = 0",
+      |_| not_nan.lc.clone(),
+      |_| payload.lc.clone(),
+      |lc| lc,
+    );
+
+    // the quiet NaN has all exponent bits set & the top mantissa bit
+    let quiet_nan = self.inf() | (1 << payload_bits);
+    Ok(&(&(res + &(nan * quiet_nan)) + &(&nan_sign * self.sign())) + &payload)
+  }
+
+  /// The constant `c`, as a multiple of `one` so that it is zero when the switch is off
+  pub(super) fn constant(&self, c: i128) -> Num<F> {
+    Num {
+      lc: LinearCombination::zero() + (field(c), self.one.get_variable()),
+      val: c,
+    }
+  }
+
+  pub(super) fn not(&self, a: &Num<F>) -> Num<F> {
+    &self.constant(1) - a
+  }
+
+  pub(super) fn bit<CS>(&self, mut cs: CS, val: bool) -> Result<Num<F>, SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    let bit = SwitchBoardCircuit::alloc_bit(&mut cs, || "bit", Some(val), self.switch)?;
+    Ok(Num::bit(&bit, val))
+  }
+
+  /// Allocate the `n` little-endian bits of `val` & the num they pack into
+  fn alloc_bits<CS>(
+    &self,
+    cs: CS,
+    val: i128,
+    n: usize,
+  ) -> Result<(Vec<AllocatedBit>, Num<F>), SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    let (bits, packed) =
+      alloc_le_bits(cs, u128::try_from(val).unwrap_or_default(), n, self.switch)?;
+    Ok((bits, Num::var(&packed, val)))
+  }
+
+  /// Range check `a` to `[0, 2^n)`, returning its bits
+  fn bits<CS>(&self, mut cs: CS, a: &Num<F>, n: usize) -> Result<Vec<AllocatedBit>, SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    let (bits, packed) = self.alloc_bits(cs.namespace(|| "bits"), a.val, n)?;
+    cs.enforce(
+      || "a = packed bits",
+      |_| a.lc.clone(),
+      |lc| lc + CS::one(),
+      |_| packed.lc,
+    );
+    Ok(bits)
+  }
+
+  pub(super) fn product<CS>(
+    &self,
+    mut cs: CS,
+    a: &Num<F>,
+    b: &Num<F>,
+  ) -> Result<Num<F>, SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    let val = a.val * b.val;
+    let c = SwitchBoardCircuit::alloc_num(&mut cs, || "a * b", || Ok(field(val)), self.switch)?;
+    cs.enforce(
+      || "a * b = c",
+      |_| a.lc.clone(),
+      |_| b.lc.clone(),
+      |lc| lc + c.get_variable(),
+    );
+    Ok(Num::var(&c, val))
+  }
+
+  fn or<CS>(&self, cs: CS, a: &Num<F>, b: &Num<F>) -> Result<Num<F>, SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    Ok(&(a + b) - &self.product(cs, a, b)?)
+  }
+
+  fn xor<CS>(&self, cs: CS, a: &Num<F>, b: &Num<F>) -> Result<Num<F>, SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    Ok(&(a + b) - &(&self.product(cs, a, b)? * 2))
+  }
+
+  /// `flag ? a : b`
+  fn select<CS>(
+    &self,
+    cs: CS,
+    flag: &Num<F>,
+    a: &Num<F>,
+    b: &Num<F>,
+  ) -> Result<Num<F>, SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    Ok(b + &self.product(cs, flag, &(a - b))?)
+  }
+
+  /// The bit `a != 0`
+  fn nonzero<CS>(&self, cs: CS, a: &Num<F>) -> Result<Num<F>, SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    let nz = nonzero(cs, &a.lc, field(a.val), self.switch)?;
+    Ok(Num::bit(&nz, a.val != 0))
+  }
+
+  /// The bit `a > 0`, for `|a| < 2^n`
+  fn positive<CS>(&self, mut cs: CS, a: &Num<F>, n: usize) -> Result<Num<F>, SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    let pos = self.bit(cs.namespace(|| "a > 0"), a.val > 0)?;
+    // pos ? a - 1 : -a, i.e. -a + pos · (2a - 1), is in range
+    let pos_a = self.product(cs.namespace(|| "pos * a"), &pos, a)?;
+    self.bits(cs.namespace(|| "range"), &(&(&(&pos_a * 2) - a) - &pos), n)?;
+    Ok(pos)
+  }
+
+  /// `2^e`, for `e` with little-endian bits `e_bits`
+  fn pow2<CS>(&self, mut cs: CS, e_bits: &[AllocatedBit], e: i128) -> Result<Num<F>, SynthesisError>
+  where
+    CS: ConstraintSystem<F>,
+  {
+    // 2^e = Π (1 + (2^(2^i) - 1) · e_i)
+    let mut pow = self.constant(1);
+    for (i, bit) in e_bits.iter().enumerate() {
+      let val = 1 << (e & ((1 << (i + 1)) - 1));
+      let next = SwitchBoardCircuit::alloc_num(
+        &mut cs,
+        || format!("2^e_{i}"),
+        || Ok(field(val)),
+        self.switch,
+      )?;
+      cs.enforce(
+        || format!("pow_{i} * (1 + (2^2^{i} - 1) * e_{i}) = pow_{}", i + 1),
+        |_| pow.lc.clone(),
+        |lc| lc + CS::one() + (F::from_u128((1 << (1 << i)) - 1), bit.get_variable()),
+        |lc| lc + next.get_variable(),
+      );
+      pow = Num::var(&next, val);
+    }
+    Ok(pow)
+  }
+}
+
+/// A float unpacked into its sign, significand & exponent
+pub(super) struct Unpacked<F: PrimeField> {
+  sign: Num<F>,
+  /// Bits of the float without the sign, which order floats by magnitude
+  mag: Num<F>,
+  /// Significand, with the implicit leading one of a normal float
+  m: Num<F>,
+  /// Exponent, with subnormals at the exponent of the smallest normal float: the float is
+  /// `m · 2^(e - bias - mant_bits)`
+  e: Num<F>,
+  /// `m` & `e` normalized so that `m_n` has its leading bit at `2^mant_bits`, or zero for ±0
+  m_n: Num<F>,
+  e_n: Num<F>,
+  is_nan: Num<F>,
+  is_inf: Num<F>,
+  is_zero: Num<F>,
+}
+
+pub(super) fn unpack<F, CS>(
+  mut cs: CS,
+  ctx: &Ctx<'_, F>,
+  a: &AllocatedNum<F>,
+  a_bits: u64,
+) -> Result<Unpacked<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let (bits, packed) = alloc_le_bits(
+    cs.namespace(|| "a bits"),
+    u128::from(a_bits),
+    1 + ctx.exp_bits + ctx.mant_bits,
+    ctx.switch,
+  )?;
+  cs.enforce(
+    || "a = packed bits",
+    |lc| lc + a.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc + packed.get_variable(),
+  );
+  let mant_val = a_bits & ((1 << ctx.mant_bits) - 1);
+  let exp_val = (a_bits >> ctx.mant_bits) & ((1 << ctx.exp_bits) - 1);
+  let mant = Num {
+    lc: pack(&bits[..ctx.mant_bits]),
+    val: i128::from(mant_val),
+  };
+  let exp = Num {
+    lc: pack(&bits[ctx.mant_bits..ctx.mant_bits + ctx.exp_bits]),
+    val: i128::from(exp_val),
+  };
+  let sign = Num::bit(
+    &bits[ctx.mant_bits + ctx.exp_bits],
+    (a_bits >> (ctx.mant_bits + ctx.exp_bits)) & 1 == 1,
+  );
+  let mag = Num {
+    lc: pack(&bits[..ctx.mant_bits + ctx.exp_bits]),
+    val: i128::from(a_bits & ((1 << (ctx.mant_bits + ctx.exp_bits)) - 1)),
+  };
+
+  // NaN & ±∞ have all exponent bits set, ±0 & subnormals none
+  let exp_max = ge_const(
+    cs.namespace(|| "exp is max"),
+    &exp.lc,
+    exp_val,
+    (1 << ctx.exp_bits) - 1,
+    ctx.exp_bits,
+    ctx.one,
+    ctx.switch,
+  )?;
+  let exp_max = Num::bit(&exp_max, exp_val == (1 << ctx.exp_bits) - 1);
+  let exp_nz = ctx.nonzero(cs.namespace(|| "exp != 0"), &exp)?;
+  let mant_nz = ctx.nonzero(cs.namespace(|| "mant != 0"), &mant)?;
+  let is_nan = ctx.product(cs.namespace(|| "is nan"), &exp_max, &mant_nz)?;
+  let is_inf = &exp_max - &is_nan;
+  let is_zero = ctx.product(
+    cs.namespace(|| "is zero"),
+    &ctx.not(&exp_nz),
+    &ctx.not(&mant_nz),
+  )?;
+
+  // m = mant + 2^mant_bits · (exp != 0), e = exp + (exp == 0)
+  let m = &mant + &(&exp_nz * (1 << ctx.mant_bits));
+  let e = &(&exp + &ctx.constant(1)) - &exp_nz;
+
+  // m_n = m · 2^lz, with lz the number of leading zeros of the mant_bits + 1 bits of m
+  let lz_val = if m.val == 0 {
+    0
+  } else {
+    i128::from((m.val as u128).leading_zeros()) - (128 - ctx.mant_bits as i128 - 1)
+  };
+  let (lz_bits, lz) = ctx.alloc_bits(cs.namespace(|| "lz"), lz_val, SHIFT_BITS)?;
+  let pow = ctx.pow2(cs.namespace(|| "2^lz"), &lz_bits, lz_val)?;
+  let m_n = ctx.product(cs.namespace(|| "m * 2^lz"), &m, &pow)?;
+  ctx.bits(
+    cs.namespace(|| "m_n in [2^m, 2^(m + 1))"),
+    &(&m_n - &(&ctx.not(&is_zero) * (1 << ctx.mant_bits))),
+    ctx.mant_bits,
+  )?;
+  let e_n = &e - &lz;
+
+  Ok(Unpacked {
+    sign,
+    mag,
+    m,
+    e,
+    m_n,
+    e_n,
+    is_nan,
+    is_inf,
+    is_zero,
+  })
+}
+
+/// Round `M · 2^E`, for `M < 2^w`, to the nearest float of sign `sign`, ties to even. Returns the
+/// bits of the float & whether `M` is non-zero, the bits being those of `2^E` for `M = 0`.
+///
+/// # Note
+///
+/// With `L` the bit length of `M`, the leading bit of `M` is at the biased exponent
+/// `be = E + L - 1 + bias`. `M` is shifted right by `s = L - mant_bits - 1`, or left by `-s`, into
+/// the `mant_bits + 1` bits of a normal significand `q`; a subnormal result (`be < 1`) is shifted
+/// `1 - be` further, to the exponent of the smallest normal float. Rounding up may carry into the
+/// exponent bits, which is the next exponent, or +∞ past the largest float.
+fn round<F, CS>(
+  mut cs: CS,
+  ctx: &Ctx<'_, F>,
+  sign: &Num<F>,
+  M: &Num<F>,
+  E: &Num<F>,
+  w: usize,
+) -> Result<(Num<F>, Num<F>), SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  // shifting M < 2^w right by more than w + 1 bits rounds it to 0 all the same
+  let max_shift = w as i128 + 1;
+
+  // M = 0 is rounded as 1, for the caller to replace
+  let m_nz = ctx.nonzero(cs.namespace(|| "M != 0"), M)?;
+  let M = &(M + &ctx.constant(1)) - &m_nz;
+
+  // 2^(L - 1) ≤ M < 2^L
+  let len_val = 128 - i128::from((M.val as u128).leading_zeros());
+  let (len_bits, len_m1) = ctx.alloc_bits(cs.namespace(|| "L - 1"), len_val - 1, SHIFT_BITS + 1)?;
+  let pow_len = ctx.pow2(cs.namespace(|| "2^(L - 1)"), &len_bits, len_val - 1)?;
+  ctx.bits(cs.namespace(|| "M >= 2^(L - 1)"), &(&M - &pow_len), w)?;
+  ctx.bits(
+    cs.namespace(|| "M < 2^L"),
+    &(&(&pow_len * 2) - &(&M + &ctx.constant(1))),
+    w,
+  )?;
+
+  // The biased exponent, raised to 1 for a subnormal result by a further shift of `sub`
+  let be = &(E + &len_m1) + &ctx.constant(ctx.bias);
+  let is_sub = ctx.bit(cs.namespace(|| "be < 1"), be.val < 1)?;
+  let sub_be = ctx.product(cs.namespace(|| "is_sub * be"), &is_sub, &be)?;
+  // is_sub ? -be : be - 1, i.e. be - 1 + is_sub · (1 - 2be), is in range
+  ctx.bits(
+    cs.namespace(|| "be range"),
+    &(&(&(&be - &ctx.constant(1)) + &is_sub) - &(&sub_be * 2)),
+    EXP_RANGE_BITS,
+  )?;
+  let sub = &is_sub - &sub_be;
+  let be = &be + &sub;
+
+  // s = L - mant_bits - 1 + sub, split into a right shift of max(s, 0), capped at max_shift, & a
+  // left shift of max(-s, 0)
+  let s = &(&len_m1 - &ctx.constant(ctx.mant_bits as i128)) + &sub;
+  let neg = ctx.bit(cs.namespace(|| "s < 0"), s.val < 0)?;
+  let neg_s = ctx.product(cs.namespace(|| "neg * s"), &neg, &s)?;
+  // neg ? -s - 1 : s, i.e. s - neg · (2s + 1), is in range
+  ctx.bits(
+    cs.namespace(|| "s range"),
+    &(&(&s - &(&neg_s * 2)) - &neg),
+    EXP_RANGE_BITS,
+  )?;
+  let s = &s - &neg_s;
+  let left = Num {
+    lc: LinearCombination::zero() - &neg_s.lc,
+    val: -neg_s.val,
+  };
+  let cap = ctx.bit(cs.namespace(|| "s > max_shift"), s.val > max_shift)?;
+  let cap_s = ctx.product(cs.namespace(|| "cap * s"), &cap, &s)?;
+  // cap ? s - max_shift - 1 : max_shift - s, i.e. max_shift - s + cap · (2s - 2max_shift - 1), is
+  // in range
+  ctx.bits(
+    cs.namespace(|| "cap range"),
+    &(&(&(&ctx.constant(max_shift) - &s) + &(&cap_s * 2)) - &(&cap * (2 * max_shift + 1))),
+    EXP_RANGE_BITS,
+  )?;
+  let right = &(&s - &cap_s) + &(&cap * max_shift);
+  let right_bits = ctx.bits(cs.namespace(|| "right bits"), &right, SHIFT_BITS + 1)?;
+  let left_bits = ctx.bits(cs.namespace(|| "left bits"), &left, SHIFT_BITS)?;
+  let pow_right = ctx.pow2(cs.namespace(|| "2^right"), &right_bits, right.val)?;
+  let pow_left = ctx.pow2(cs.namespace(|| "2^left"), &left_bits, left.val)?;
+
+  // M · 2^left = q · 2^right + rem, with rem < 2^right
+  let shifted = ctx.product(cs.namespace(|| "M * 2^left"), &M, &pow_left)?;
+  let q_val = shifted.val >> right.val;
+  let (q_bits, q) = ctx.alloc_bits(cs.namespace(|| "q"), q_val, ctx.mant_bits + 1)?;
+  let (_, rem) = ctx.alloc_bits(
+    cs.namespace(|| "rem"),
+    shifted.val - (q_val << right.val),
+    w + 1,
+  )?;
+  cs.enforce(
+    || "q * 2^right = M * 2^left - rem",
+    |_| q.lc.clone(),
+    |_| pow_right.lc.clone(),
+    |_| (&shifted - &rem).lc,
+  );
+  ctx.bits(
+    cs.namespace(|| "rem < 2^right"),
+    &(&(&pow_right - &ctx.constant(1)) - &rem),
+    w + 1,
+  )?;
+
+  // Round up past the halfway point 2^right / 2, and at it iff q is odd
+  let c = &(&rem * 2) - &pow_right;
+  let gt = ctx.positive(cs.namespace(|| "c > 0"), &c, w + 2)?;
+  let eq = ctx.not(&ctx.nonzero(cs.namespace(|| "c != 0"), &c)?);
+  let q0 = Num::bit(&q_bits[0], q_val & 1 == 1);
+  let up = &gt + &ctx.product(cs.namespace(|| "eq & q odd"), &eq, &q0)?;
+
+  // q has its leading bit at 2^mant_bits, which adds the exponent bits be - 1 to be
+  let mag = &(&(&(&be - &ctx.constant(1)) * (1 << ctx.mant_bits)) + &q) + &up;
+  let ovf = ctx.positive(
+    cs.namespace(|| "mag >= inf"),
+    &(&mag - &ctx.constant(ctx.inf() - 1)),
+    64,
+  )?;
+  let ovf_mag = ctx.product(cs.namespace(|| "ovf * mag"), &ovf, &mag)?;
+  let mag = &(&mag + &(&ovf * ctx.inf())) - &ovf_mag;
+
+  Ok((&mag + &(sign * ctx.sign()), m_nz))
+}
+
+/// `x + y`, or `x - y` if `negate`. Returns whether the result is NaN, and its bits otherwise
+/// (0 for NaN).
+pub(super) fn add<F, CS>(
+  mut cs: CS,
+  ctx: &Ctx<'_, F>,
+  x: &Unpacked<F>,
+  y: &Unpacked<F>,
+  negate: &Num<F>,
+) -> Result<(Num<F>, Num<F>), SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let sy = ctx.xor(cs.namespace(|| "sign y"), &y.sign, negate)?;
+  let differ = ctx.xor(cs.namespace(|| "signs differ"), &x.sign, &sy)?;
+
+  // NaN for a NaN operand or ∞ - ∞, ∞ for any other ∞ operand
+  let nan_in = ctx.or(cs.namespace(|| "nan operand"), &x.is_nan, &y.is_nan)?;
+  let inf_inf = ctx.product(cs.namespace(|| "inf & inf"), &x.is_inf, &y.is_inf)?;
+  let inf_minus_inf = ctx.product(cs.namespace(|| "inf - inf"), &inf_inf, &differ)?;
+  let nan = ctx.or(cs.namespace(|| "nan"), &nan_in, &inf_minus_inf)?;
+  let inf_in = ctx.or(cs.namespace(|| "inf operand"), &x.is_inf, &y.is_inf)?;
+  let inf = ctx.product(cs.namespace(|| "inf"), &inf_in, &ctx.not(&nan))?;
+  let inf_sign = ctx.select(cs.namespace(|| "inf sign"), &x.is_inf, &x.sign, &sy)?;
+
+  // Order the addends by magnitude: |A| ≥ |B|
+  let swap = ctx.positive(
+    cs.namespace(|| "|y| > |x|"),
+    &(&y.mag - &x.mag),
+    ctx.mant_bits + ctx.exp_bits,
+  )?;
+  let mA = ctx.select(cs.namespace(|| "mA"), &swap, &y.m, &x.m)?;
+  let eA = ctx.select(cs.namespace(|| "eA"), &swap, &y.e, &x.e)?;
+  let sA = ctx.select(cs.namespace(|| "sA"), &swap, &sy, &x.sign)?;
+  let mB = &(&x.m + &y.m) - &mA;
+  let eB = &(&x.e + &y.e) - &eA;
+  let sB = &(&x.sign + &sy) - &sA;
+
+  // Align B to the 3 guard bits of A: B · 2^2 = Bq · 2^d + Br, with d = eA - eB capped at
+  // max_align
+  let d = &eA - &eB;
+  let far = ctx.positive(
+    cs.namespace(|| "d > max_align"),
+    &(&d - &ctx.constant(ctx.max_align())),
+    EXP_RANGE_BITS,
+  )?;
+  let far_d = ctx.product(cs.namespace(|| "far * d"), &far, &d)?;
+  let d = &(&d - &far_d) + &(&far * ctx.max_align());
+  let d_bits = ctx.bits(cs.namespace(|| "d bits"), &d, SHIFT_BITS)?;
+  let pow_d = ctx.pow2(cs.namespace(|| "2^d"), &d_bits, d.val)?;
+  let mB = &mB * (1 << (GUARD_BITS - 1));
+  let bq_val = mB.val >> d.val;
+  let (_, Bq) = ctx.alloc_bits(cs.namespace(|| "Bq"), bq_val, ctx.mant_bits + GUARD_BITS)?;
+  let (_, Br) = ctx.alloc_bits(
+    cs.namespace(|| "Br"),
+    mB.val - (bq_val << d.val),
+    ctx.max_align() as usize,
+  )?;
+  cs.enforce(
+    || "Bq * 2^d = B * 2^2 - Br",
+    |_| Bq.lc.clone(),
+    |_| pow_d.lc.clone(),
+    |_| (&mB - &Br).lc,
+  );
+  ctx.bits(
+    cs.namespace(|| "Br < 2^d"),
+    &(&(&pow_d - &ctx.constant(1)) - &Br),
+    ctx.max_align() as usize,
+  )?;
+  let sticky = ctx.nonzero(cs.namespace(|| "Br != 0"), &Br)?;
+  let B = &(&Bq * 2) + &sticky;
+
+  // M = A ± B, which is non-negative as |A| ≥ |B|
+  let differ_B = ctx.product(cs.namespace(|| "differ * B"), &differ, &B)?;
+  let M = &(&(&mA * (1 << GUARD_BITS)) + &B) - &(&differ_B * 2);
+  let E = &eA - &ctx.constant(ctx.scale() + GUARD_BITS as i128);
+  let (rounded, m_nz) = round(
+    cs.namespace(|| "round"),
+    ctx,
+    &sA,
+    &M,
+    &E,
+    ctx.mant_bits + GUARD_BITS + 2,
+  )?;
+
+  // An exact zero is +0, but for -0 + -0
+  let zero_sign = ctx.product(cs.namespace(|| "sA & sB"), &sA, &sB)?;
+  let finite = ctx.select(
+    cs.namespace(|| "finite"),
+    &m_nz,
+    &rounded,
+    &(&zero_sign * ctx.sign()),
+  )?;
+
+  let res = special(cs.namespace(|| "res"), ctx, &nan, &inf, &inf_sign, &finite)?;
+  Ok((nan, res))
+}
+
+/// `x · y`. Returns whether the result is NaN, and its bits otherwise (0 for NaN).
+pub(super) fn mul<F, CS>(
+  mut cs: CS,
+  ctx: &Ctx<'_, F>,
+  x: &Unpacked<F>,
+  y: &Unpacked<F>,
+) -> Result<(Num<F>, Num<F>), SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let sign = ctx.xor(cs.namespace(|| "sign"), &x.sign, &y.sign)?;
+
+  // NaN for a NaN operand or ∞ · 0, else ∞ for an ∞ operand & 0 for a zero one
+  let nan_in = ctx.or(cs.namespace(|| "nan operand"), &x.is_nan, &y.is_nan)?;
+  let inf_zero = &ctx.product(cs.namespace(|| "inf * 0"), &x.is_inf, &y.is_zero)?
+    + &ctx.product(cs.namespace(|| "0 * inf"), &x.is_zero, &y.is_inf)?;
+  let nan = ctx.or(cs.namespace(|| "nan"), &nan_in, &inf_zero)?;
+  let inf_in = ctx.or(cs.namespace(|| "inf operand"), &x.is_inf, &y.is_inf)?;
+  let zero_in = ctx.or(cs.namespace(|| "zero operand"), &x.is_zero, &y.is_zero)?;
+  let not_nan = ctx.not(&nan);
+  let inf = ctx.product(cs.namespace(|| "inf"), &inf_in, &not_nan)?;
+  let zero = ctx.product(cs.namespace(|| "zero"), &zero_in, &not_nan)?;
+
+  let M = ctx.product(cs.namespace(|| "M"), &x.m_n, &y.m_n)?;
+  let E = &(&x.e_n + &y.e_n) - &ctx.constant(2 * ctx.scale());
+  let (rounded, _) = round(
+    cs.namespace(|| "round"),
+    ctx,
+    &sign,
+    &M,
+    &E,
+    2 * (ctx.mant_bits + 1),
+  )?;
+  let finite = ctx.select(
+    cs.namespace(|| "finite"),
+    &zero,
+    &(&sign * ctx.sign()),
+    &rounded,
+  )?;
+
+  let res = special(cs.namespace(|| "res"), ctx, &nan, &inf, &sign, &finite)?;
+  Ok((nan, res))
+}
+
+/// `x / y`. Returns whether the result is NaN, and its bits otherwise (0 for NaN).
+pub(super) fn div<F, CS>(
+  mut cs: CS,
+  ctx: &Ctx<'_, F>,
+  x: &Unpacked<F>,
+  y: &Unpacked<F>,
+) -> Result<(Num<F>, Num<F>), SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let sign = ctx.xor(cs.namespace(|| "sign"), &x.sign, &y.sign)?;
+
+  // NaN for a NaN operand, ∞ / ∞ or 0 / 0, else ∞ for ∞ / y or x / 0 & 0 for 0 / y or x / ∞
+  let nan_in = ctx.or(cs.namespace(|| "nan operand"), &x.is_nan, &y.is_nan)?;
+  let indeterminate = &ctx.product(cs.namespace(|| "inf / inf"), &x.is_inf, &y.is_inf)?
+    + &ctx.product(cs.namespace(|| "0 / 0"), &x.is_zero, &y.is_zero)?;
+  let nan = ctx.or(cs.namespace(|| "nan"), &nan_in, &indeterminate)?;
+  let inf_in = ctx.or(cs.namespace(|| "inf operand"), &x.is_inf, &y.is_zero)?;
+  let zero_in = ctx.or(cs.namespace(|| "zero operand"), &x.is_zero, &y.is_inf)?;
+  let not_nan = ctx.not(&nan);
+  let inf = ctx.product(cs.namespace(|| "inf"), &inf_in, &not_nan)?;
+  let zero = ctx.product(cs.namespace(|| "zero"), &zero_in, &not_nan)?;
+
+  // x_n · 2^(mant_bits + 3) = q · y_n + r, with r < y_n & a zero divisor replaced by
+  // 2^mant_bits
+  let num = &x.m_n * (1 << (ctx.mant_bits + 3));
+  let den = &y.m_n + &(&y.is_zero * (1 << ctx.mant_bits));
+  let q_val = num.val / den.val;
+  let (_, q) = ctx.alloc_bits(cs.namespace(|| "q"), q_val, ctx.mant_bits + 4)?;
+  let (_, r) = ctx.alloc_bits(cs.namespace(|| "r"), num.val % den.val, ctx.mant_bits + 1)?;
+  cs.enforce(
+    || "q * den = num - r",
+    |_| q.lc.clone(),
+    |_| den.lc.clone(),
+    |_| (&num - &r).lc,
+  );
+  ctx.bits(
+    cs.namespace(|| "r < den"),
+    &(&(&den - &ctx.constant(1)) - &r),
+    ctx.mant_bits + 1,
+  )?;
+  let sticky = ctx.nonzero(cs.namespace(|| "r != 0"), &r)?;
+
+  let M = &(&q * 2) + &sticky;
+  let E = &(&x.e_n - &y.e_n) - &ctx.constant(ctx.mant_bits as i128 + 4);
+  let (rounded, _) = round(
+    cs.namespace(|| "round"),
+    ctx,
+    &sign,
+    &M,
+    &E,
+    ctx.mant_bits + 5,
+  )?;
+  let finite = ctx.select(
+    cs.namespace(|| "finite"),
+    &zero,
+    &(&sign * ctx.sign()),
+    &rounded,
+  )?;
+
+  let res = special(cs.namespace(|| "res"), ctx, &nan, &inf, &sign, &finite)?;
+  Ok((nan, res))
+}
+
+/// `nan ? 0 : inf ? ±∞ : finite`, for `nan` & `inf` exclusive
+fn special<F, CS>(
+  mut cs: CS,
+  ctx: &Ctx<'_, F>,
+  nan: &Num<F>,
+  inf: &Num<F>,
+  inf_sign: &Num<F>,
+  finite: &Num<F>,
+) -> Result<Num<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let is_finite = &ctx.not(nan) - inf;
+  let finite = ctx.product(cs.namespace(|| "is_finite * finite"), &is_finite, finite)?;
+  let inf_sign = ctx.product(cs.namespace(|| "inf * sign"), inf, inf_sign)?;
+  Ok(&(&finite + &(inf * ctx.inf())) + &(&inf_sign * ctx.sign()))
+}
@@ -1,5 +1,5 @@
+use super::{enforce_boolean, shr_bits};
 use crate::wasm_snark::switchboard::WASMTransitionCircuit as SwitchBoardCircuit;
-use bellpepper::gadgets::Assignment;
 use bellpepper_core::{
   boolean::{AllocatedBit, Boolean},
   num::AllocatedNum,
@@ -156,6 +156,7 @@ where
   let c = SwitchBoardCircuit::alloc_num(&mut cs, || "c", || Ok(F::from(c as u64)), switch)?;
   let lt = of;
   let lt_flag = SwitchBoardCircuit::alloc_num(&mut cs, || "lt", || Ok(F::from(lt as u64)), switch)?;
+  enforce_boolean(cs.namespace(|| "lt is boolean"), &lt_flag);
 
   cs.enforce(
     || "a - b + range*lt = c",
@@ -300,6 +301,7 @@ where
   let c = SwitchBoardCircuit::alloc_num(&mut cs, || "c", || Ok(F::from(c as u64)), switch)?;
   let gt = of;
   let gt_flag = SwitchBoardCircuit::alloc_num(&mut cs, || "gt", || Ok(F::from(gt as u64)), switch)?;
+  enforce_boolean(cs.namespace(|| "gt is boolean"), &gt_flag);
 
   cs.enforce(
     || "b - a + range*lt = c",
@@ -674,31 +676,53 @@ pub(crate) fn add_to_lc<F: PrimeField, CS: ConstraintSystem<F>>(
 }
 
 #[tracing::instrument(skip_all, name = "to_u32_le_bits")]
+/// Decompose `a` into its lower 32 bits.
+///
+/// # Note
+///
+/// All 64 bits wasmi holds a value in are decomposed and constrained to pack back into `a`, only
+/// the lower 32 bits are returned. This matches wasmi which truncates an untyped value to an i32.
 fn to_u32_le_bits<F, CS>(mut cs: CS, a: &AllocatedNum<F>) -> Result<Vec<Boolean>, SynthesisError>
 where
   F: PrimeField,
   CS: ConstraintSystem<F>,
 {
-  let mut a_u32 = a.get_value().and_then(|a| to_u32(a)).unwrap_or(0);
+  let mut a_u64 = a.get_value().and_then(|a| to_u64(a)).unwrap_or(0);
 
-  let mut bits: Vec<Boolean> = Vec::with_capacity(32);
-  for i in 0..32 {
-    let b = a_u32 & 1;
+  let mut bits: Vec<Boolean> = Vec::with_capacity(64);
+  for i in 0..64 {
+    let b = a_u64 & 1;
     let b_bool = Boolean::Is(AllocatedBit::alloc(
       cs.namespace(|| format!("b.{i}")),
       Some(b == 1),
     )?);
     bits.push(b_bool);
 
-    a_u32 /= 2;
+    a_u64 /= 2;
   }
+
+  // a == sum(b_i * 2^i)
+  let packed = bits
+    .iter()
+    .enumerate()
+    .fold(LinearCombination::<F>::zero(), |lc, (i, bit)| {
+      add_to_lc::<F, CS>(bit, lc, F::from(1u64 << i))
+    });
+  cs.enforce(
+    || "a == packed bits",
+    |_| packed,
+    |lc| lc + CS::one(),
+    |lc| lc + a.get_variable(),
+  );
+
+  bits.truncate(32);
   Ok(bits)
 }
 
-/// Attempts to convert the field element to a u32
+/// Attempts to convert the field element to a u64
 ///
 /// Becuase of how wasmi hold's values we can't have a value that is larger than 64 bits
-fn to_u32<F>(a: F) -> Option<u32>
+fn to_u64<F>(a: F) -> Option<u64>
 where
   F: PrimeField,
 {
@@ -709,7 +733,7 @@ where
   }
   let mut byte_array = [0u8; 8];
   byte_array.copy_from_slice(&a.to_repr().as_ref()[0..8]);
-  Some(u64::from_le_bytes(byte_array) as u32)
+  Some(u64::from_le_bytes(byte_array))
 }
 
 #[tracing::instrument(skip_all, name = "u64_le_bits_to_num")]
@@ -762,6 +786,20 @@ where
     Ok(F::from(value.unwrap_or(0)))
   })?;
 
+  // num == sum(b_i * 2^i)
+  let packed = bits
+    .iter()
+    .enumerate()
+    .fold(LinearCombination::<F>::zero(), |lc, (i, bit)| {
+      add_to_lc::<F, CS>(bit, lc, F::from(1u64 << i))
+    });
+  cs.enforce(
+    || "num == packed bits",
+    |_| packed,
+    |lc| lc + CS::one(),
+    |lc| lc + num.get_variable(),
+  );
+
   Ok(num)
 }
 
@@ -807,33 +845,9 @@ where
   Ok((and, xor, or))
 }
 
-/// Gadget to perform bitwise shl operation.
-#[tracing::instrument(skip_all, name = "shl_bits_32")]
-pub fn shl_bits_32<F: PrimeField, CS: ConstraintSystem<F>>(
-  mut cs: CS,
-  bits: &[Boolean],
-  shift: usize,
-) -> Result<Vec<Boolean>, SynthesisError> {
-  let fill = Boolean::Is(AllocatedBit::alloc(
-    cs.namespace(|| "fill bit"),
-    Some(false),
-  )?);
-
-  let res_bits: Vec<Boolean> = Some(&fill)
-    .into_iter()
-    .cycle()
-    .take(shift & 0x1F)
-    .chain(bits.iter())
-    .take(32)
-    .cloned()
-    .collect();
-
-  Ok(res_bits)
-}
-
-/// Perform a shifts and rotates on 64 bit integers
+/// Perform a shifts and rotates on 32 bit integers
 ///
-/// i64.shl, i64.shr_u, i64.shr_s, i64.rotr, i64.rotl
+/// i32.shl, i32.shr_u, i32.shr_s, i32.rotr, i32.rotl
 ///
 /// # Returns
 ///
@@ -841,7 +855,7 @@ pub fn shl_bits_32<F: PrimeField, CS: ConstraintSystem<F>>(
 pub fn shift_rotate_32<F, CS>(
   mut cs: CS,
   a: &AllocatedNum<F>,
-  by: usize,
+  by: &AllocatedNum<F>,
 ) -> Result<
   (
     AllocatedNum<F>,
@@ -857,59 +871,51 @@ where
   CS: ConstraintSystem<F>,
 {
   let a_bits = to_u32_le_bits(cs.namespace(|| "a_bits"), a)?;
-
-  // shl
-  let shl_bits = shl_bits_32(cs.namespace(|| "shl bits"), &a_bits, by)?;
+  let rev_a_bits: Vec<Boolean> = a_bits.iter().rev().cloned().collect();
+
+  // WASM takes the shift amount mod 32. `by` is decomposed into bits in-circuit and only its lower
+  // 5 bits are used, so the reduction is enforced rather than trusted from the trace.
+  let by_bits = to_u32_le_bits(cs.namespace(|| "by_bits"), by)?;
+  let by_bits = &by_bits[..5];
+
+  let fill_bit = Boolean::Constant(false);
+  let sign_bit = &a_bits[31];
+
+  // shl: shl(a, by) = rev(shr_u(rev(a), by))
+  let shl_bits = shr_bits(
+    cs.namespace(|| "shl bits"),
+    &rev_a_bits,
+    by_bits,
+    Some(&fill_bit),
+  )?;
+  let shl_bits: Vec<Boolean> = shl_bits.into_iter().rev().collect();
   let shl = u32_le_bits_to_num(cs.namespace(|| "pack shl bits"), &shl_bits)?;
 
   // shr_u
-  let fill_bit = &Boolean::Is(AllocatedBit::alloc(
-    cs.namespace(|| "fill bit"),
-    Some(false),
-  )?);
-
-  let shr_u_bits: Vec<Boolean> = a_bits
-    .iter()
-    .skip(by & 0x1F)
-    .chain(Some(fill_bit).into_iter().cycle())
-    .take(32)
-    .cloned()
-    .collect();
-
+  let shr_u_bits = shr_bits(
+    cs.namespace(|| "shr_u bits"),
+    &a_bits,
+    by_bits,
+    Some(&fill_bit),
+  )?;
   let shr_u = u32_le_bits_to_num(cs.namespace(|| "pack shr_u bits"), &shr_u_bits)?;
 
   // shr_s
-  let sign_bit = *a_bits.last().get()?;
-  let shr_s_bits: Vec<Boolean> = a_bits
-    .iter()
-    .skip(by & 0x1F)
-    .chain(Some(sign_bit).into_iter().cycle())
-    .take(32)
-    .cloned()
-    .collect();
-
+  let shr_s_bits = shr_bits(
+    cs.namespace(|| "shr_s bits"),
+    &a_bits,
+    by_bits,
+    Some(sign_bit),
+  )?;
   let shr_s = u32_le_bits_to_num(cs.namespace(|| "pack shr_s bits"), &shr_s_bits)?;
 
   // rotr
-  let rotr_bits: Vec<Boolean> = a_bits
-    .iter()
-    .skip(by & 0x1F)
-    .chain(a_bits.iter())
-    .take(32)
-    .cloned()
-    .collect();
-
+  let rotr_bits = shr_bits(cs.namespace(|| "rotr bits"), &a_bits, by_bits, None)?;
   let rotr = u32_le_bits_to_num(cs.namespace(|| "pack rotr bits"), &rotr_bits)?;
 
-  // rotl
-  let rotl_bits: Vec<Boolean> = a_bits
-    .iter()
-    .skip(32 - (by & 0x1F))
-    .chain(a_bits.iter())
-    .take(32)
-    .cloned()
-    .collect();
-
+  // rotl: rotl(a, by) = rev(rotr(rev(a), by))
+  let rotl_bits = shr_bits(cs.namespace(|| "rotl bits"), &rev_a_bits, by_bits, None)?;
+  let rotl_bits: Vec<Boolean> = rotl_bits.into_iter().rev().collect();
   let rotl = u32_le_bits_to_num(cs.namespace(|| "pack rotl bits"), &rotl_bits)?;
 
   Ok((shl, shr_u, shr_s, rotr, rotl))
@@ -920,12 +926,15 @@ mod tests {
 
   use crate::{
     utils::logging::init_logger,
-    wasm_snark::switchboard::WASMTransitionCircuit as SwitchBoardCircuit,
+    wasm_snark::switchboard::{
+      alu::{test_vectors, OpTestVector},
+      WASMTransitionCircuit as SwitchBoardCircuit,
+    },
   };
   use bellpepper_core::{test_cs::TestConstraintSystem, ConstraintSystem};
   use nova::{provider::Bn256EngineIPA, traits::Engine};
   use rand::{rngs::StdRng, Rng, SeedableRng};
-  use wasmi::core::UntypedValue;
+  use wasmi::{core::UntypedValue, Instruction as Instr};
 
   type E = Bn256EngineIPA;
   type F = <E as Engine>::Scalar;
@@ -1682,14 +1691,13 @@ mod tests {
         SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a.to_bits())), switch)
           .unwrap();
 
+      let alloc_by =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "by", || Ok(F::from(by.to_bits())), switch)
+          .unwrap();
+
       let (shl, shr_u, shr_s, rotr, rotl) =
         tracing_texray::examine(tracing::info_span!("shift_rotate")).in_scope(|| {
-          super::shift_rotate_32(
-            cs.namespace(|| "shift_rotate"),
-            &alloc_a,
-            by.to_bits() as usize,
-          )
-          .unwrap()
+          super::shift_rotate_32(cs.namespace(|| "shift_rotate"), &alloc_a, &alloc_by).unwrap()
         });
 
       cs.enforce(
@@ -1730,4 +1738,227 @@ mod tests {
       assert!(cs.is_satisfied());
     }
   }
+
+  #[test]
+  fn test_shift_rotate_by_width() {
+    let mut rng = StdRng::from_seed([114u8; 32]);
+    let switch = F::one();
+
+    // WASM reduces the shift amount mod 32
+    for by in [0u64, 31, 32, 33] {
+      for _ in 0..10 {
+        let a = UntypedValue::from(rng.gen::<i32>());
+        let by = UntypedValue::from(by);
+
+        let mut cs = TestConstraintSystem::<F>::new();
+        let alloc_a =
+          SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a.to_bits())), switch)
+            .unwrap();
+        let alloc_by =
+          SwitchBoardCircuit::alloc_num(&mut cs, || "by", || Ok(F::from(by.to_bits())), switch)
+            .unwrap();
+
+        let (shl, shr_u, shr_s, rotr, rotl) =
+          super::shift_rotate_32(cs.namespace(|| "shift_rotate"), &alloc_a, &alloc_by).unwrap();
+
+        assert_eq!(shl.get_value(), Some(F::from(a.i32_shl(by).to_bits())));
+        assert_eq!(shr_u.get_value(), Some(F::from(a.i32_shr_u(by).to_bits())));
+        assert_eq!(shr_s.get_value(), Some(F::from(a.i32_shr_s(by).to_bits())));
+        assert_eq!(rotr.get_value(), Some(F::from(a.i32_rotr(by).to_bits())));
+        assert_eq!(rotl.get_value(), Some(F::from(a.i32_rotl(by).to_bits())));
+        assert!(cs.is_satisfied());
+      }
+    }
+  }
+
+  #[test]
+  fn test_shift_rotate_unconstrained_by() {
+    let switch = F::one();
+    let a = UntypedValue::from(0x0123_4567_u32);
+
+    // A prover claiming `rotl` by 1 when the shift operand on the stack is 0 must fail
+    let mut cs = TestConstraintSystem::<F>::new();
+    let one_var = <TestConstraintSystem<F> as ConstraintSystem<F>>::one();
+    let alloc_a =
+      SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a.to_bits())), switch).unwrap();
+    let alloc_by = SwitchBoardCircuit::alloc_num(&mut cs, || "by", || Ok(F::ZERO), switch).unwrap();
+    let alloc_claimed = SwitchBoardCircuit::alloc_num(
+      &mut cs,
+      || "claimed rotl",
+      || Ok(F::from(a.i32_rotl(UntypedValue::from(1u64)).to_bits())),
+      switch,
+    )
+    .unwrap();
+
+    let (_, _, _, _, rotl) =
+      super::shift_rotate_32(cs.namespace(|| "shift_rotate"), &alloc_a, &alloc_by).unwrap();
+
+    cs.enforce(
+      || "claimed == rotl",
+      |lc| lc + alloc_claimed.get_variable(),
+      |lc| lc + one_var,
+      |lc| lc + rotl.get_variable(),
+    );
+
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_alu_vectors() {
+    let switch = F::one();
+
+    for vector in test_vectors() {
+      let OpTestVector {
+        instr,
+        a,
+        b,
+        expected,
+      } = vector;
+
+      let mut cs = TestConstraintSystem::<F>::new();
+      let one_var = <TestConstraintSystem<F> as ConstraintSystem<F>>::one();
+      let alloc_a =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a)), switch).unwrap();
+      let alloc_b =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "b", || Ok(F::from(b)), switch).unwrap();
+
+      let res = match instr {
+        Instr::I32Add => super::add32(
+          cs.namespace(|| "add32"),
+          &alloc_a,
+          &alloc_b,
+          a as u32,
+          b as u32,
+          switch,
+        )
+        .unwrap(),
+        Instr::I32Sub => super::sub32(
+          cs.namespace(|| "sub32"),
+          &alloc_a,
+          &alloc_b,
+          a as u32,
+          b as u32,
+          switch,
+        )
+        .unwrap(),
+        Instr::I32Mul => super::mul32(
+          cs.namespace(|| "mul32"),
+          &alloc_a,
+          &alloc_b,
+          a as u32,
+          b as u32,
+          switch,
+        )
+        .unwrap(),
+        Instr::I32DivS | Instr::I32RemS => {
+          let (quotient, rem) = super::div_rem_s_32(
+            cs.namespace(|| "div_rem_s"),
+            &alloc_a,
+            &alloc_b,
+            a as u32,
+            b as u32,
+            switch,
+          )
+          .unwrap();
+          if matches!(instr, Instr::I32DivS) {
+            quotient
+          } else {
+            rem
+          }
+        }
+        Instr::I32DivU | Instr::I32RemU => {
+          let (quotient, rem) = super::div_rem_u_32(
+            cs.namespace(|| "div_rem_u"),
+            &alloc_a,
+            &alloc_b,
+            a as u32,
+            b as u32,
+            switch,
+          )
+          .unwrap();
+          if matches!(instr, Instr::I32DivU) {
+            quotient
+          } else {
+            rem
+          }
+        }
+        Instr::I32And | Instr::I32Xor | Instr::I32Or => {
+          let (and, xor, or) =
+            super::bitops_32(cs.namespace(|| "bitops"), &alloc_a, &alloc_b).unwrap();
+          match instr {
+            Instr::I32And => and,
+            Instr::I32Xor => xor,
+            _ => or,
+          }
+        }
+        Instr::I32Shl | Instr::I32ShrU | Instr::I32ShrS | Instr::I32Rotl | Instr::I32Rotr => {
+          let (shl, shr_u, shr_s, rotr, rotl) =
+            super::shift_rotate_32(cs.namespace(|| "shift_rotate"), &alloc_a, &alloc_b).unwrap();
+          match instr {
+            Instr::I32Shl => shl,
+            Instr::I32ShrU => shr_u,
+            Instr::I32ShrS => shr_s,
+            Instr::I32Rotr => rotr,
+            _ => rotl,
+          }
+        }
+        Instr::I32LtU | Instr::I32GeU | Instr::I32LtS | Instr::I32GeS => {
+          let (lt, ge, slt, sge) = super::lt_ge_s_32(
+            cs.namespace(|| "lt_ge"),
+            &alloc_a,
+            &alloc_b,
+            a as u32,
+            b as u32,
+            switch,
+          )
+          .unwrap();
+          match instr {
+            Instr::I32LtU => lt,
+            Instr::I32GeU => ge,
+            Instr::I32LtS => slt,
+            _ => sge,
+          }
+        }
+        Instr::I32LeU | Instr::I32GtU | Instr::I32LeS | Instr::I32GtS => {
+          let (le, gt, sle, sgt) = super::le_gt_s_32(
+            cs.namespace(|| "le_gt"),
+            &alloc_a,
+            &alloc_b,
+            a as u32,
+            b as u32,
+            switch,
+          )
+          .unwrap();
+          match instr {
+            Instr::I32LeU => le,
+            Instr::I32GtU => gt,
+            Instr::I32LeS => sle,
+            _ => sgt,
+          }
+        }
+        Instr::I32Popcnt | Instr::I32Clz | Instr::I32Ctz => {
+          let (popcnt, clz, ctz) =
+            super::unary_ops_32(cs.namespace(|| "unary_ops"), &alloc_a, a as u32, switch).unwrap();
+          match instr {
+            Instr::I32Popcnt => popcnt,
+            Instr::I32Clz => clz,
+            _ => ctz,
+          }
+        }
+        // i64 ops, and eq, ne & eqz, are proven by other gadgets
+        _ => continue,
+      };
+
+      let alloc_expected =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "expected", || Ok(F::from(expected)), switch)
+          .unwrap();
+      cs.enforce(
+        || "expected ==  res",
+        |lc| lc + alloc_expected.get_variable(),
+        |lc| lc + one_var,
+        |lc| lc + res.get_variable(),
+      );
+      assert!(cs.is_satisfied(), "{vector:?}");
+    }
+  }
 }
@@ -1,5 +1,5 @@
+use super::{alloc_le_bits, enforce_boolean, pack, shr_bits};
 use crate::wasm_snark::switchboard::WASMTransitionCircuit as SwitchBoardCircuit;
-use bellpepper::gadgets::Assignment;
 use bellpepper_core::{
   boolean::{AllocatedBit, Boolean},
   num::AllocatedNum,
@@ -158,6 +158,7 @@ where
   let c = SwitchBoardCircuit::alloc_num(&mut cs, || "c", || Ok(F::from(c)), switch)?;
   let lt = of;
   let lt_flag = SwitchBoardCircuit::alloc_num(&mut cs, || "lt", || Ok(F::from(lt as u64)), switch)?;
+  enforce_boolean(cs.namespace(|| "lt is boolean"), &lt_flag);
 
   cs.enforce(
     || "a - b + range*lt = c",
@@ -302,6 +303,7 @@ where
   let c = SwitchBoardCircuit::alloc_num(&mut cs, || "c", || Ok(F::from(c)), switch)?;
   let gt = of;
   let gt_flag = SwitchBoardCircuit::alloc_num(&mut cs, || "gt", || Ok(F::from(gt as u64)), switch)?;
+  enforce_boolean(cs.namespace(|| "gt is boolean"), &gt_flag);
 
   cs.enforce(
     || "b - a + range*lt = c",
@@ -696,11 +698,26 @@ where
 
     a_u64 /= 2;
   }
+
+  // a == sum(b_i * 2^i)
+  let packed = bits
+    .iter()
+    .enumerate()
+    .fold(LinearCombination::<F>::zero(), |lc, (i, bit)| {
+      add_to_lc::<F, CS>(bit, lc, F::from(1u64 << i))
+    });
+  cs.enforce(
+    || "a == packed bits",
+    |_| packed,
+    |lc| lc + CS::one(),
+    |lc| lc + a.get_variable(),
+  );
+
   Ok(bits)
 }
 
 /// Attempts to convert the field element to a u64
-fn to_u64<F>(a: F) -> Option<u64>
+pub(crate) fn to_u64<F>(a: F) -> Option<u64>
 where
   F: PrimeField,
 {
@@ -764,6 +781,20 @@ where
     Ok(F::from(value.unwrap_or(0)))
   })?;
 
+  // num == sum(b_i * 2^i)
+  let packed = bits
+    .iter()
+    .enumerate()
+    .fold(LinearCombination::<F>::zero(), |lc, (i, bit)| {
+      add_to_lc::<F, CS>(bit, lc, F::from(1u64 << i))
+    });
+  cs.enforce(
+    || "num == packed bits",
+    |_| packed,
+    |lc| lc + CS::one(),
+    |lc| lc + num.get_variable(),
+  );
+
   Ok(num)
 }
 
@@ -809,30 +840,6 @@ where
   Ok((and, xor, or))
 }
 
-/// Gadget to perform bitwise shl operation.
-#[tracing::instrument(skip_all, name = "shl_bits_64")]
-pub fn shl_bits_64<F: PrimeField, CS: ConstraintSystem<F>>(
-  mut cs: CS,
-  bits: &[Boolean],
-  shift: usize,
-) -> Result<Vec<Boolean>, SynthesisError> {
-  let fill = Boolean::Is(AllocatedBit::alloc(
-    cs.namespace(|| "fill bit"),
-    Some(false),
-  )?);
-
-  let res_bits: Vec<Boolean> = Some(&fill)
-    .into_iter()
-    .cycle()
-    .take(shift & 0x3F)
-    .chain(bits.iter())
-    .take(64)
-    .cloned()
-    .collect();
-
-  Ok(res_bits)
-}
-
 /// Perform a shifts and rotates on 64 bit integers
 ///
 /// i64.shl, i64.shr_u, i64.shr_s, i64.rotr, i64.rotl
@@ -843,7 +850,7 @@ pub fn shl_bits_64<F: PrimeField, CS: ConstraintSystem<F>>(
 pub fn shift_rotate_64<F, CS>(
   mut cs: CS,
   a: &AllocatedNum<F>,
-  by: usize,
+  by: &AllocatedNum<F>,
 ) -> Result<
   (
     AllocatedNum<F>,
@@ -859,76 +866,223 @@ where
   CS: ConstraintSystem<F>,
 {
   let a_bits = to_u64_le_bits(cs.namespace(|| "a_bits"), a)?;
-
-  // shl
-  let shl_bits = shl_bits_64(cs.namespace(|| "shl bits"), &a_bits, by)?;
+  let rev_a_bits: Vec<Boolean> = a_bits.iter().rev().cloned().collect();
+
+  // WASM takes the shift amount mod 64. `by` is decomposed into bits in-circuit and only its lower
+  // 6 bits are used, so the reduction is enforced rather than trusted from the trace.
+  let by_bits = to_u64_le_bits(cs.namespace(|| "by_bits"), by)?;
+  let by_bits = &by_bits[..6];
+
+  let fill_bit = Boolean::Constant(false);
+  let sign_bit = &a_bits[63];
+
+  // shl: shl(a, by) = rev(shr_u(rev(a), by))
+  let shl_bits = shr_bits(
+    cs.namespace(|| "shl bits"),
+    &rev_a_bits,
+    by_bits,
+    Some(&fill_bit),
+  )?;
+  let shl_bits: Vec<Boolean> = shl_bits.into_iter().rev().collect();
   let shl = u64_le_bits_to_num(cs.namespace(|| "pack shl bits"), &shl_bits)?;
 
   // shr_u
-  let fill_bit = &Boolean::Is(AllocatedBit::alloc(
-    cs.namespace(|| "fill bit"),
-    Some(false),
-  )?);
-
-  let shr_u_bits: Vec<Boolean> = a_bits
-    .iter()
-    .skip(by & 0x3F)
-    .chain(Some(fill_bit).into_iter().cycle())
-    .take(64)
-    .cloned()
-    .collect();
-
+  let shr_u_bits = shr_bits(
+    cs.namespace(|| "shr_u bits"),
+    &a_bits,
+    by_bits,
+    Some(&fill_bit),
+  )?;
   let shr_u = u64_le_bits_to_num(cs.namespace(|| "pack shr_u bits"), &shr_u_bits)?;
 
   // shr_s
-  let sign_bit = *a_bits.last().get()?;
-  let shr_s_bits: Vec<Boolean> = a_bits
-    .iter()
-    .skip(by & 0x3F)
-    .chain(Some(sign_bit).into_iter().cycle())
-    .take(64)
-    .cloned()
-    .collect();
-
+  let shr_s_bits = shr_bits(
+    cs.namespace(|| "shr_s bits"),
+    &a_bits,
+    by_bits,
+    Some(sign_bit),
+  )?;
   let shr_s = u64_le_bits_to_num(cs.namespace(|| "pack shr_s bits"), &shr_s_bits)?;
 
   // rotr
-  let rotr_bits: Vec<Boolean> = a_bits
-    .iter()
-    .skip(by & 0x3F)
-    .chain(a_bits.iter())
-    .take(64)
-    .cloned()
-    .collect();
-
+  let rotr_bits = shr_bits(cs.namespace(|| "rotr bits"), &a_bits, by_bits, None)?;
   let rotr = u64_le_bits_to_num(cs.namespace(|| "pack rotr bits"), &rotr_bits)?;
 
-  // rotl
-  let rotl_bits: Vec<Boolean> = a_bits
-    .iter()
-    .skip(64 - (by & 0x3F))
-    .chain(a_bits.iter())
-    .take(64)
-    .cloned()
-    .collect();
-
+  // rotl: rotl(a, by) = rev(rotr(rev(a), by))
+  let rotl_bits = shr_bits(cs.namespace(|| "rotl bits"), &rev_a_bits, by_bits, None)?;
+  let rotl_bits: Vec<Boolean> = rotl_bits.into_iter().rev().collect();
   let rotl = u64_le_bits_to_num(cs.namespace(|| "pack rotl bits"), &rotl_bits)?;
 
   Ok((shl, shr_u, shr_s, rotr, rotl))
 }
 
+/// Extend the i32 in the low 32 bits of `a` to an i64: sign-extend it when `signed` is 1
+/// (i64.extend_i32_s), zero-extend it when it is 0 (i64.extend_i32_u).
+///
+/// # Note
+///
+/// `a` is decomposed into its low & high 32-bit halves, so any 64-bit value is accepted and its
+/// high half ignored. Both extensions share the same constraints, `signed` selects the extension
+/// in the circuit, so it must be a boolean variable, e.g. the switch of i64.extend_i32_s.
+pub fn extend_i32<F, CS>(
+  mut cs: CS,
+  a: &AllocatedNum<F>,
+  a_bits: u64,
+  signed: &AllocatedNum<F>,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let (low_bits, _) = alloc_le_bits(
+    cs.namespace(|| "low"),
+    (a_bits & 0xFFFF_FFFF).into(),
+    32,
+    switch,
+  )?;
+  let (_, high) = alloc_le_bits(cs.namespace(|| "high"), (a_bits >> 32).into(), 32, switch)?;
+
+  // a = low + 2^32 · high
+  let shift = F::from(1 << 32);
+  cs.enforce(
+    || "a = low + 2^32 · high",
+    |_| pack(&low_bits) + (shift, high.get_variable()),
+    |lc| lc + CS::one(),
+    |lc| lc + a.get_variable(),
+  );
+
+  let low = a_bits as u32;
+  let res = SwitchBoardCircuit::alloc_num(
+    &mut cs,
+    || "res",
+    || {
+      let signed = signed
+        .get_value()
+        .ok_or(SynthesisError::AssignmentMissing)?;
+      Ok(F::from(if signed == F::ONE {
+        low as i32 as i64 as u64
+      } else {
+        low as u64
+      }))
+    },
+    switch,
+  )?;
+
+  // The high half of the result is all ones when sign-extending a negative i32, i.e. when both
+  // `signed` and the sign bit are set
+  let sign_bit = &low_bits[31];
+  let fill = AllocatedNum::alloc(cs.namespace(|| "signed & sign bit"), || {
+    let signed = signed
+      .get_value()
+      .ok_or(SynthesisError::AssignmentMissing)?;
+    let sign_bit = sign_bit
+      .get_value()
+      .ok_or(SynthesisError::AssignmentMissing)?;
+    Ok(if sign_bit { signed } else { F::ZERO })
+  })?;
+  cs.enforce(
+    || "signed · sign bit = fill",
+    |lc| lc + signed.get_variable(),
+    |lc| lc + sign_bit.get_variable(),
+    |lc| lc + fill.get_variable(),
+  );
+
+  // res = low + (2^64 - 2^32) · fill
+  let high_ones = F::from(0xFFFF_FFFF_0000_0000);
+  cs.enforce(
+    || "res = low + (2^64 - 2^32) · fill",
+    |_| pack(&low_bits) + (high_ones, fill.get_variable()),
+    |lc| lc + CS::one(),
+    |lc| lc + res.get_variable(),
+  );
+
+  Ok(res)
+}
+
+/// Truncate `a` to its low `from_bits` bits, then sign-extend them to `to_bits` when `signed` or
+/// leave them zero-extended otherwise. This covers i32.wrap_i64 (32 to 32, unsigned) & the in-place
+/// sign extensions i32.extend8_s, i32.extend16_s, i64.extend8_s, i64.extend16_s & i64.extend32_s.
+///
+/// # Note
+///
+/// Unlike [`extend_i32`], `signed` selects the constraints, so it must not depend on the witness.
+pub fn extend<F, CS>(
+  mut cs: CS,
+  a: &AllocatedNum<F>,
+  a_bits: u64,
+  from_bits: usize,
+  to_bits: usize,
+  signed: bool,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let low_mask = u64::MAX >> (64 - from_bits);
+  let (low_bits, _) = alloc_le_bits(
+    cs.namespace(|| "low"),
+    (a_bits & low_mask).into(),
+    from_bits,
+    switch,
+  )?;
+  let (_, high) = alloc_le_bits(
+    cs.namespace(|| "high"),
+    (a_bits >> from_bits).into(),
+    64 - from_bits,
+    switch,
+  )?;
+
+  // a = low + 2^from · high
+  let shift = F::from_u128(1 << from_bits);
+  cs.enforce(
+    || "a = low + 2^from · high",
+    |_| pack(&low_bits) + (shift, high.get_variable()),
+    |lc| lc + CS::one(),
+    |lc| lc + a.get_variable(),
+  );
+
+  let low = a_bits & low_mask;
+  let sign = signed && low >> (from_bits - 1) == 1;
+  let fill = if sign {
+    (u64::MAX >> (64 - to_bits)) & !low_mask
+  } else {
+    0
+  };
+  let res = SwitchBoardCircuit::alloc_num(&mut cs, || "res", || Ok(F::from(low | fill)), switch)?;
+
+  // res = low + (2^to - 2^from) · sign bit, the fill term only exists when sign-extending
+  let mut lc = pack(&low_bits);
+  if signed {
+    let fill = F::from_u128((1 << to_bits) - (1 << from_bits));
+    lc = lc + (fill, low_bits[from_bits - 1].get_variable());
+  }
+  cs.enforce(
+    || "res = low + fill",
+    |_| lc,
+    |lc| lc + CS::one(),
+    |lc| lc + res.get_variable(),
+  );
+
+  Ok(res)
+}
+
 #[cfg(test)]
 mod tests {
 
   use super::{add64, mul64};
   use crate::{
     utils::logging::init_logger,
-    wasm_snark::switchboard::{alu::int64::sub64, WASMTransitionCircuit as SwitchBoardCircuit},
+    wasm_snark::switchboard::{
+      alu::{int64::sub64, test_vectors, OpTestVector},
+      WASMTransitionCircuit as SwitchBoardCircuit,
+    },
   };
   use bellpepper_core::{test_cs::TestConstraintSystem, ConstraintSystem};
   use nova::{provider::Bn256EngineIPA, traits::Engine};
   use rand::{rngs::StdRng, Rng, SeedableRng};
-  use wasmi::core::UntypedValue;
+  use wasmi::{core::UntypedValue, Instruction as Instr};
 
   type E = Bn256EngineIPA;
   type F = <E as Engine>::Scalar;
@@ -1680,14 +1834,13 @@ mod tests {
         SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a.to_bits())), switch)
           .unwrap();
 
+      let alloc_by =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "by", || Ok(F::from(by.to_bits())), switch)
+          .unwrap();
+
       let (shl, shr_u, shr_s, rotr, rotl) =
         tracing_texray::examine(tracing::info_span!("shift_rotate")).in_scope(|| {
-          super::shift_rotate_64(
-            cs.namespace(|| "shift_rotate"),
-            &alloc_a,
-            by.to_bits() as usize,
-          )
-          .unwrap()
+          super::shift_rotate_64(cs.namespace(|| "shift_rotate"), &alloc_a, &alloc_by).unwrap()
         });
 
       cs.enforce(
@@ -1728,4 +1881,357 @@ mod tests {
       assert!(cs.is_satisfied());
     }
   }
+
+  #[test]
+  fn test_shift_rotate_by_width() {
+    let mut rng = StdRng::from_seed([114u8; 32]);
+    let switch = F::one();
+
+    // WASM reduces the shift amount mod 64
+    for by in [0u64, 63, 64, 65] {
+      for _ in 0..10 {
+        let a = UntypedValue::from(rng.gen::<i64>());
+        let by = UntypedValue::from(by);
+
+        let mut cs = TestConstraintSystem::<F>::new();
+        let alloc_a =
+          SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a.to_bits())), switch)
+            .unwrap();
+        let alloc_by =
+          SwitchBoardCircuit::alloc_num(&mut cs, || "by", || Ok(F::from(by.to_bits())), switch)
+            .unwrap();
+
+        let (shl, shr_u, shr_s, rotr, rotl) =
+          super::shift_rotate_64(cs.namespace(|| "shift_rotate"), &alloc_a, &alloc_by).unwrap();
+
+        assert_eq!(shl.get_value(), Some(F::from(a.i64_shl(by).to_bits())));
+        assert_eq!(shr_u.get_value(), Some(F::from(a.i64_shr_u(by).to_bits())));
+        assert_eq!(shr_s.get_value(), Some(F::from(a.i64_shr_s(by).to_bits())));
+        assert_eq!(rotr.get_value(), Some(F::from(a.i64_rotr(by).to_bits())));
+        assert_eq!(rotl.get_value(), Some(F::from(a.i64_rotl(by).to_bits())));
+        assert!(cs.is_satisfied());
+      }
+    }
+  }
+
+  #[test]
+  fn test_shift_rotate_unconstrained_by() {
+    let switch = F::one();
+    let a = UntypedValue::from(0x0123_4567_89ab_cdefu64);
+
+    // A prover claiming `rotl` by 1 when the shift operand on the stack is 0 must fail
+    let mut cs = TestConstraintSystem::<F>::new();
+    let one_var = <TestConstraintSystem<F> as ConstraintSystem<F>>::one();
+    let alloc_a =
+      SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a.to_bits())), switch).unwrap();
+    let alloc_by = SwitchBoardCircuit::alloc_num(&mut cs, || "by", || Ok(F::ZERO), switch).unwrap();
+    let alloc_claimed = SwitchBoardCircuit::alloc_num(
+      &mut cs,
+      || "claimed rotl",
+      || Ok(F::from(a.i64_rotl(UntypedValue::from(1u64)).to_bits())),
+      switch,
+    )
+    .unwrap();
+
+    let (_, _, _, _, rotl) =
+      super::shift_rotate_64(cs.namespace(|| "shift_rotate"), &alloc_a, &alloc_by).unwrap();
+
+    cs.enforce(
+      || "claimed == rotl",
+      |lc| lc + alloc_claimed.get_variable(),
+      |lc| lc + one_var,
+      |lc| lc + rotl.get_variable(),
+    );
+
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_extend_i32() {
+    let switch = F::one();
+    // i32 values with the high bit set are negative under S & large positive under U. The high half
+    // of the operand is ignored.
+    let values = [
+      0,
+      1,
+      0x7FFF_FFFF,
+      0x8000_0000,
+      0x8000_0001,
+      0xFFFF_FFFF,
+      0xDEAD_BEEF_8000_0001,
+    ];
+    for a in values {
+      for signed in [true, false] {
+        let value = UntypedValue::from(a);
+        let expected = if signed {
+          value.i64_extend_i32_s()
+        } else {
+          value.i64_extend_i32_u()
+        };
+
+        let mut cs = TestConstraintSystem::<F>::new();
+        let alloc_a =
+          SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a)), switch).unwrap();
+        let alloc_signed = SwitchBoardCircuit::alloc_num(
+          &mut cs,
+          || "signed",
+          || Ok(F::from(signed as u64)),
+          switch,
+        )
+        .unwrap();
+        let res = super::extend_i32(
+          cs.namespace(|| "extend_i32"),
+          &alloc_a,
+          a,
+          &alloc_signed,
+          switch,
+        )
+        .unwrap();
+        assert_eq!(res.get_value(), Some(F::from(expected.to_bits())));
+        assert!(cs.is_satisfied(), "a: {a:#x}, signed: {signed}");
+      }
+    }
+  }
+
+  #[test]
+  fn test_extend_i32_wrong_result() {
+    let switch = F::one();
+    let a = 0x8000_0001_u64;
+
+    // Zero-extending a negative i32 does not give its sign extension
+    let mut cs = TestConstraintSystem::<F>::new();
+    let one_var = <TestConstraintSystem<F> as ConstraintSystem<F>>::one();
+    let alloc_a =
+      SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a)), switch).unwrap();
+    let unsigned =
+      SwitchBoardCircuit::alloc_num(&mut cs, || "signed", || Ok(F::ZERO), switch).unwrap();
+    let res = super::extend_i32(
+      cs.namespace(|| "extend_i32"),
+      &alloc_a,
+      a,
+      &unsigned,
+      switch,
+    )
+    .unwrap();
+    let sign_extended = SwitchBoardCircuit::alloc_num(
+      &mut cs,
+      || "sign extended",
+      || Ok(F::from(0xFFFF_FFFF_8000_0001)),
+      switch,
+    )
+    .unwrap();
+    cs.enforce(
+      || "sign extended == res",
+      |lc| lc + sign_extended.get_variable(),
+      |lc| lc + one_var,
+      |lc| lc + res.get_variable(),
+    );
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_extend() {
+    let switch = F::one();
+    type Conversion = (fn(UntypedValue) -> UntypedValue, usize, usize, bool);
+    let conversions: [Conversion; 6] = [
+      (UntypedValue::i32_wrap_i64, 32, 32, false),
+      (UntypedValue::i32_extend8_s, 8, 32, true),
+      (UntypedValue::i32_extend16_s, 16, 32, true),
+      (UntypedValue::i64_extend8_s, 8, 64, true),
+      (UntypedValue::i64_extend16_s, 16, 64, true),
+      (UntypedValue::i64_extend32_s, 32, 64, true),
+    ];
+    // The sign bit of each width set & clear, with garbage in the bits above it
+    let values = [
+      0,
+      0x7F,
+      0x80,
+      0xFF,
+      0x7FFF,
+      0x8000,
+      0x1234_8081,
+      0x7FFF_FFFF,
+      0x8000_0000,
+      0xFFFF_FFFF,
+      0xDEAD_BEEF_8000_7F80,
+      u64::MAX,
+    ];
+    for (op, from_bits, to_bits, signed) in conversions {
+      for a in values {
+        let expected = op(UntypedValue::from(a)).to_bits();
+
+        let mut cs = TestConstraintSystem::<F>::new();
+        let alloc_a =
+          SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a)), switch).unwrap();
+        let res = super::extend(
+          cs.namespace(|| "extend"),
+          &alloc_a,
+          a,
+          from_bits,
+          to_bits,
+          signed,
+          switch,
+        )
+        .unwrap();
+        assert_eq!(
+          res.get_value(),
+          Some(F::from(expected)),
+          "a: {a:#x}, {from_bits} to {to_bits}"
+        );
+        assert!(cs.is_satisfied(), "a: {a:#x}, {from_bits} to {to_bits}");
+      }
+    }
+  }
+
+  #[test]
+  fn test_extend_wrong_result() {
+    let switch = F::one();
+    let a = 0xFFFF_FFFF_0000_0080_u64;
+
+    // Neither the untruncated operand nor a zero-extension is the sign extension of 0x80
+    for claimed in [a, 0x80] {
+      let mut cs = TestConstraintSystem::<F>::new();
+      let one_var = <TestConstraintSystem<F> as ConstraintSystem<F>>::one();
+      let alloc_a =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a)), switch).unwrap();
+      let res = super::extend(cs.namespace(|| "extend"), &alloc_a, a, 8, 64, true, switch).unwrap();
+      let claimed =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "claimed", || Ok(F::from(claimed)), switch)
+          .unwrap();
+      cs.enforce(
+        || "claimed == res",
+        |lc| lc + claimed.get_variable(),
+        |lc| lc + one_var,
+        |lc| lc + res.get_variable(),
+      );
+      assert!(!cs.is_satisfied());
+    }
+  }
+
+  #[test]
+  fn test_alu_vectors() {
+    let switch = F::one();
+
+    for vector in test_vectors() {
+      let OpTestVector {
+        instr,
+        a,
+        b,
+        expected,
+      } = vector;
+
+      let mut cs = TestConstraintSystem::<F>::new();
+      let one_var = <TestConstraintSystem<F> as ConstraintSystem<F>>::one();
+      let alloc_a =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a)), switch).unwrap();
+      let alloc_b =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "b", || Ok(F::from(b)), switch).unwrap();
+
+      let res = match instr {
+        Instr::I64Add => {
+          super::add64(cs.namespace(|| "add64"), &alloc_a, &alloc_b, a, b, switch).unwrap()
+        }
+        Instr::I64Sub => {
+          super::sub64(cs.namespace(|| "sub64"), &alloc_a, &alloc_b, a, b, switch).unwrap()
+        }
+        Instr::I64Mul => {
+          super::mul64(cs.namespace(|| "mul64"), &alloc_a, &alloc_b, a, b, switch).unwrap()
+        }
+        Instr::I64DivS | Instr::I64RemS => {
+          let (quotient, rem) = super::div_rem_s_64(
+            cs.namespace(|| "div_rem_s"),
+            &alloc_a,
+            &alloc_b,
+            a,
+            b,
+            switch,
+          )
+          .unwrap();
+          if matches!(instr, Instr::I64DivS) {
+            quotient
+          } else {
+            rem
+          }
+        }
+        Instr::I64DivU | Instr::I64RemU => {
+          let (quotient, rem) = super::div_rem_u_64(
+            cs.namespace(|| "div_rem_u"),
+            &alloc_a,
+            &alloc_b,
+            a,
+            b,
+            switch,
+          )
+          .unwrap();
+          if matches!(instr, Instr::I64DivU) {
+            quotient
+          } else {
+            rem
+          }
+        }
+        Instr::I64And | Instr::I64Xor | Instr::I64Or => {
+          let (and, xor, or) =
+            super::bitops_64(cs.namespace(|| "bitops"), &alloc_a, &alloc_b).unwrap();
+          match instr {
+            Instr::I64And => and,
+            Instr::I64Xor => xor,
+            _ => or,
+          }
+        }
+        Instr::I64Shl | Instr::I64ShrU | Instr::I64ShrS | Instr::I64Rotl | Instr::I64Rotr => {
+          let (shl, shr_u, shr_s, rotr, rotl) =
+            super::shift_rotate_64(cs.namespace(|| "shift_rotate"), &alloc_a, &alloc_b).unwrap();
+          match instr {
+            Instr::I64Shl => shl,
+            Instr::I64ShrU => shr_u,
+            Instr::I64ShrS => shr_s,
+            Instr::I64Rotr => rotr,
+            _ => rotl,
+          }
+        }
+        Instr::I64LtU | Instr::I64GeU | Instr::I64LtS | Instr::I64GeS => {
+          let (lt, ge, slt, sge) =
+            super::lt_ge_s(cs.namespace(|| "lt_ge"), &alloc_a, &alloc_b, a, b, switch).unwrap();
+          match instr {
+            Instr::I64LtU => lt,
+            Instr::I64GeU => ge,
+            Instr::I64LtS => slt,
+            _ => sge,
+          }
+        }
+        Instr::I64LeU | Instr::I64GtU | Instr::I64LeS | Instr::I64GtS => {
+          let (le, gt, sle, sgt) =
+            super::le_gt_s(cs.namespace(|| "le_gt"), &alloc_a, &alloc_b, a, b, switch).unwrap();
+          match instr {
+            Instr::I64LeU => le,
+            Instr::I64GtU => gt,
+            Instr::I64LeS => sle,
+            _ => sgt,
+          }
+        }
+        Instr::I64Popcnt | Instr::I64Clz | Instr::I64Ctz => {
+          let (popcnt, clz, ctz) =
+            super::unary_ops_64(cs.namespace(|| "unary_ops"), &alloc_a, a, switch).unwrap();
+          match instr {
+            Instr::I64Popcnt => popcnt,
+            Instr::I64Clz => clz,
+            _ => ctz,
+          }
+        }
+        // i32 ops, and eq, ne & eqz, are proven by other gadgets
+        _ => continue,
+      };
+
+      let alloc_expected =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "expected", || Ok(F::from(expected)), switch)
+          .unwrap();
+      cs.enforce(
+        || "expected ==  res",
+        |lc| lc + alloc_expected.get_variable(),
+        |lc| lc + one_var,
+        |lc| lc + res.get_variable(),
+      );
+      assert!(cs.is_satisfied(), "{vector:?}");
+    }
+  }
 }
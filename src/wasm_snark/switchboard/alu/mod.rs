@@ -1,9 +1,20 @@
 use super::WASMTransitionCircuit as SwitchBoardCircuit;
-use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use bellpepper_core::{
+  boolean::{AllocatedBit, Boolean},
+  num::AllocatedNum,
+  ConstraintSystem, LinearCombination, SynthesisError,
+};
 use ff::PrimeField;
 
+pub mod float;
+pub mod float32;
+pub mod float64;
+mod ieee754;
 pub mod int32;
 pub mod int64;
+mod vectors;
+
+pub use vectors::{test_vectors, OpTestVector};
 
 pub fn eq<F, CS>(
   mut cs: CS,
@@ -31,6 +42,7 @@ where
     },
     switch,
   )?;
+  enforce_boolean(cs.namespace(|| "result is boolean"), &res);
 
   // result * (a - b) = 0
   // This means that at least one of result or a - b is zero.
@@ -98,6 +110,16 @@ where
     },
     switch,
   )?;
+  enforce_boolean(cs.namespace(|| "result is boolean"), &res);
+
+  // (1 - result) * (a - b) = 0
+  // This enforces that result is 1 when diff is not 0.
+  cs.enforce(
+    || "(1 - result) * (a - b) = 0",
+    |lc| lc + CS::one() - res.get_variable(),
+    |lc| lc + a.get_variable() - b.get_variable(),
+    |lc| lc,
+  );
 
   // Inverse of `a - b`, if it exists, otherwise one.
   let q = cs.alloc(
@@ -155,6 +177,7 @@ where
     },
     switch,
   )?;
+  enforce_boolean(cs.namespace(|| "res is boolean"), &res);
 
   // result * a = 0
   // This means that at least one of result or a is zero.
@@ -193,15 +216,114 @@ where
   Ok(res)
 }
 
+/// Enforce that `a` is either `0` or `1`. Comparison results are written to the stack as numbers,
+/// and branches treat any nonzero value as true, so they must be constrained to a bit.
+pub fn enforce_boolean<F, CS>(mut cs: CS, a: &AllocatedNum<F>)
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  cs.enforce(
+    || "a * (1 - a) = 0",
+    |lc| lc + a.get_variable(),
+    |lc| lc + CS::one() - a.get_variable(),
+    |lc| lc,
+  );
+}
+
+/// Shift the little-endian `bits` right by the amount encoded in the little-endian `shift` bits.
+///
+/// Vacated bits are set to `fill`, when `fill` is `None` the shifted out bits wrap around (i.e. a
+/// rotate right). This is a barrel shifter: each bit of `shift` selects between the current bits
+/// and the bits shifted by its power of two, so the circuit does not depend on the shift amount.
+pub fn shr_bits<F, CS>(
+  mut cs: CS,
+  bits: &[Boolean],
+  shift: &[Boolean],
+  fill: Option<&Boolean>,
+) -> Result<Vec<Boolean>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let width = bits.len();
+  let mut res = bits.to_vec();
+  for (k, shift_bit) in shift.iter().enumerate() {
+    let by = 1 << k;
+    res = (0..width)
+      .map(|i| {
+        let shifted = match fill {
+          Some(fill) if i + by >= width => fill,
+          _ => &res[(i + by) % width],
+        };
+
+        // shift_bit ? shifted : res[i]
+        Boolean::sha256_ch(
+          cs.namespace(|| format!("layer {k}, bit {i}")),
+          shift_bit,
+          shifted,
+          &res[i],
+        )
+      })
+      .collect::<Result<_, _>>()?;
+  }
+
+  Ok(res)
+}
+
+/// Pack little-endian bits into a linear combination
+pub(crate) fn pack<F: PrimeField>(bits: &[AllocatedBit]) -> LinearCombination<F> {
+  let mut coeff = F::ONE;
+  let mut lc = LinearCombination::zero();
+  for bit in bits {
+    lc = lc + (coeff, bit.get_variable());
+    coeff = coeff.double();
+  }
+  lc
+}
+
+/// Allocate the `n` little-endian bits of `value`, and the num they pack into
+pub(crate) fn alloc_le_bits<F, CS>(
+  mut cs: CS,
+  value: u128,
+  n: usize,
+  switch: F,
+) -> Result<(Vec<AllocatedBit>, AllocatedNum<F>), SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let bits = (0..n)
+    .map(|i| {
+      SwitchBoardCircuit::alloc_bit(
+        &mut cs,
+        || format!("bit {i}"),
+        Some((value >> i) & 1 == 1),
+        switch,
+      )
+    })
+    .collect::<Result<Vec<_>, _>>()?;
+  let packed =
+    SwitchBoardCircuit::alloc_num(&mut cs, || "packed", || Ok(F::from_u128(value)), switch)?;
+  cs.enforce(
+    || "packed = Σ 2^i · bit_i",
+    |_| pack(&bits),
+    |lc| lc + CS::one(),
+    |lc| lc + packed.get_variable(),
+  );
+  Ok((bits, packed))
+}
+
 #[cfg(test)]
 mod tests {
   use bellpepper_core::{test_cs::TestConstraintSystem, ConstraintSystem};
+  use ff::Field;
   use nova::{provider::Bn256EngineIPA, traits::Engine};
   use rand::{rngs::StdRng, Rng, SeedableRng};
-  use wasmi::core::UntypedValue;
+  use wasmi::{core::UntypedValue, Instruction as Instr};
 
   use crate::wasm_snark::switchboard::{
-    alu::{self, eq, eqz},
+    alu::{self, eq, eqz, ne, test_vectors, OpTestVector},
     WASMTransitionCircuit as SwitchBoardCircuit,
   };
 
@@ -340,4 +462,102 @@ mod tests {
       assert!(cs.is_satisfied());
     }
   }
+
+  #[test]
+  fn test_eq_result_not_boolean() {
+    let switch = F::ONE;
+    let mut cs = TestConstraintSystem::<F>::new();
+    let a = SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(7)), switch).unwrap();
+    let b = SwitchBoardCircuit::alloc_num(&mut cs, || "b", || Ok(F::from(7)), switch).unwrap();
+    let res = eq(cs.namespace(|| "eq"), &a, &b, switch).unwrap();
+    assert_eq!(res.get_value(), Some(F::ONE));
+    assert!(cs.is_satisfied());
+
+    // A result of 2 with a matching inverse satisfies `(a - b + res) * q = 1`, but is not a bit
+    cs.set("eq/a == b/num", F::from(2));
+    cs.set("eq/q", F::from(2).invert().unwrap());
+    assert_eq!(
+      cs.which_is_unsatisfied(),
+      Some("eq/result is boolean/a * (1 - a) = 0")
+    );
+  }
+
+  #[test]
+  fn test_ne_result_not_boolean() {
+    let switch = F::ONE;
+    let mut cs = TestConstraintSystem::<F>::new();
+    let a = SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(7)), switch).unwrap();
+    let b = SwitchBoardCircuit::alloc_num(&mut cs, || "b", || Ok(F::from(5)), switch).unwrap();
+    let res = ne(cs.namespace(|| "ne"), &a, &b, switch).unwrap();
+    assert_eq!(res.get_value(), Some(F::ONE));
+    assert!(cs.is_satisfied());
+
+    // (a - b) * q = 2 with q = 1
+    cs.set("ne/a != b/num", F::from(2));
+    cs.set("ne/q", F::ONE);
+    assert_eq!(
+      cs.which_is_unsatisfied(),
+      Some("ne/result is boolean/a * (1 - a) = 0")
+    );
+
+    // (a - b) * q = 0 would claim a == b
+    cs.set("ne/a != b/num", F::ZERO);
+    cs.set("ne/q", F::ZERO);
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_eqz_result_not_boolean() {
+    let switch = F::ONE;
+    let mut cs = TestConstraintSystem::<F>::new();
+    let a = SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::ZERO), switch).unwrap();
+    let res = eqz(cs.namespace(|| "eqz"), &a, switch).unwrap();
+    assert_eq!(res.get_value(), Some(F::ONE));
+    assert!(cs.is_satisfied());
+
+    cs.set("eqz/a == 0/num", F::from(2));
+    cs.set("eqz/q", F::from(2).invert().unwrap());
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_alu_vectors() {
+    let switch = F::one();
+
+    for vector in test_vectors() {
+      let OpTestVector {
+        instr,
+        a,
+        b,
+        expected,
+      } = vector;
+
+      let mut cs = TestConstraintSystem::<F>::new();
+      let one_var = <TestConstraintSystem<F> as ConstraintSystem<F>>::one();
+      let alloc_a =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "a", || Ok(F::from(a)), switch).unwrap();
+      let alloc_b =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "b", || Ok(F::from(b)), switch).unwrap();
+
+      let res = match instr {
+        Instr::I64Eq | Instr::I32Eq => eq(cs.namespace(|| "eq"), &alloc_a, &alloc_b, switch),
+        Instr::I64Ne | Instr::I32Ne => ne(cs.namespace(|| "ne"), &alloc_a, &alloc_b, switch),
+        Instr::I64Eqz | Instr::I32Eqz => eqz(cs.namespace(|| "eqz"), &alloc_a, switch),
+        // the other ops are proven by the int32 & int64 gadgets
+        _ => continue,
+      }
+      .unwrap();
+
+      let alloc_expected =
+        SwitchBoardCircuit::alloc_num(&mut cs, || "expected", || Ok(F::from(expected)), switch)
+          .unwrap();
+      cs.enforce(
+        || "expected ==  res",
+        |lc| lc + alloc_expected.get_variable(),
+        |lc| lc + one_var,
+        |lc| lc + res.get_variable(),
+      );
+      assert!(cs.is_satisfied(), "{vector:?}");
+    }
+  }
 }
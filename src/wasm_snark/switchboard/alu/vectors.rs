@@ -0,0 +1,237 @@
+//! Canonical test vectors for the ALU gadgets.
+//!
+//! Each vector is an integer op of the WASM ISA applied to boundary values, along with the result
+//! wasmi computes for it, so the gadget tests and any other implementation of the ALU can share
+//! them.
+use wasmi::{core::UntypedValue, Instruction as Instr};
+
+/// Operands & expected result of an ALU op. Values are the bits of the [`UntypedValue`]s the zkVM
+/// stack holds, i.e. i32 values are zero-extended to 64 bits.
+#[derive(Clone, Copy, Debug)]
+pub struct OpTestVector {
+  /// The op
+  pub instr: Instr,
+  /// First operand, the only one of unary ops
+  pub a: u64,
+  /// Second operand, 0 for unary ops
+  pub b: u64,
+  /// Result of the op
+  pub expected: u64,
+}
+
+const I64_BOUNDARIES: [i64; 10] = [
+  0,
+  1,
+  2,
+  63,
+  64,
+  -1,
+  i64::MIN,
+  i64::MAX,
+  u32::MAX as i64,
+  1 << 32,
+];
+
+const I32_BOUNDARIES: [i32; 9] = [0, 1, 2, 31, 32, -1, i32::MIN, i32::MAX, u16::MAX as i32];
+
+const I64_UNARY: [Instr; 4] = [
+  Instr::I64Clz,
+  Instr::I64Ctz,
+  Instr::I64Popcnt,
+  Instr::I64Eqz,
+];
+
+const I64_BINARY: [Instr; 25] = [
+  Instr::I64Add,
+  Instr::I64Sub,
+  Instr::I64Mul,
+  Instr::I64DivS,
+  Instr::I64DivU,
+  Instr::I64RemS,
+  Instr::I64RemU,
+  Instr::I64And,
+  Instr::I64Or,
+  Instr::I64Xor,
+  Instr::I64Shl,
+  Instr::I64ShrS,
+  Instr::I64ShrU,
+  Instr::I64Rotl,
+  Instr::I64Rotr,
+  Instr::I64Eq,
+  Instr::I64Ne,
+  Instr::I64LtS,
+  Instr::I64LtU,
+  Instr::I64GtS,
+  Instr::I64GtU,
+  Instr::I64LeS,
+  Instr::I64LeU,
+  Instr::I64GeS,
+  Instr::I64GeU,
+];
+
+const I32_UNARY: [Instr; 4] = [
+  Instr::I32Clz,
+  Instr::I32Ctz,
+  Instr::I32Popcnt,
+  Instr::I32Eqz,
+];
+
+const I32_BINARY: [Instr; 25] = [
+  Instr::I32Add,
+  Instr::I32Sub,
+  Instr::I32Mul,
+  Instr::I32DivS,
+  Instr::I32DivU,
+  Instr::I32RemS,
+  Instr::I32RemU,
+  Instr::I32And,
+  Instr::I32Or,
+  Instr::I32Xor,
+  Instr::I32Shl,
+  Instr::I32ShrS,
+  Instr::I32ShrU,
+  Instr::I32Rotl,
+  Instr::I32Rotr,
+  Instr::I32Eq,
+  Instr::I32Ne,
+  Instr::I32LtS,
+  Instr::I32LtU,
+  Instr::I32GtS,
+  Instr::I32GtU,
+  Instr::I32LeS,
+  Instr::I32LeU,
+  Instr::I32GeS,
+  Instr::I32GeU,
+];
+
+/// Get the ALU test vectors: each i32 & i64 unary op applied to every boundary value of its type,
+/// and each binary op applied to every pair of them.
+///
+/// # Note
+///
+/// Division & remainder are proven by a single gadget, so they only get vectors for operands both
+/// are defined on. This leaves out division by zero and the signed `MIN / -1` overflow.
+pub fn test_vectors() -> Vec<OpTestVector> {
+  let i64_values = I64_BOUNDARIES.map(UntypedValue::from);
+  let i32_values = I32_BOUNDARIES.map(UntypedValue::from);
+
+  let mut vectors = Vec::new();
+  for (values, unary, binary) in [
+    (&i64_values[..], &I64_UNARY[..], &I64_BINARY[..]),
+    (&i32_values[..], &I32_UNARY[..], &I32_BINARY[..]),
+  ] {
+    for &instr in unary {
+      for &a in values {
+        vectors.extend(vector(instr, a, UntypedValue::from(0u64)));
+      }
+    }
+    for &instr in binary {
+      for &a in values {
+        for &b in values {
+          vectors.extend(vector(instr, a, b));
+        }
+      }
+    }
+  }
+  vectors
+}
+
+/// Compute the vector of `instr` on `a` & `b`, if the op is defined on them
+fn vector(instr: Instr, a: UntypedValue, b: UntypedValue) -> Option<OpTestVector> {
+  let expected = match instr {
+    Instr::I64Clz => a.i64_clz(),
+    Instr::I64Ctz => a.i64_ctz(),
+    Instr::I64Popcnt => a.i64_popcnt(),
+    Instr::I64Eqz => a.i64_eqz(),
+    Instr::I64Add => a.i64_add(b),
+    Instr::I64Sub => a.i64_sub(b),
+    Instr::I64Mul => a.i64_mul(b),
+    Instr::I64DivS | Instr::I64RemS => {
+      let quotient = a.i64_div_s(b).ok()?;
+      let rem = a.i64_rem_s(b).ok()?;
+      if matches!(instr, Instr::I64DivS) {
+        quotient
+      } else {
+        rem
+      }
+    }
+    Instr::I64DivU | Instr::I64RemU => {
+      let quotient = a.i64_div_u(b).ok()?;
+      let rem = a.i64_rem_u(b).ok()?;
+      if matches!(instr, Instr::I64DivU) {
+        quotient
+      } else {
+        rem
+      }
+    }
+    Instr::I64And => a.i64_and(b),
+    Instr::I64Or => a.i64_or(b),
+    Instr::I64Xor => a.i64_xor(b),
+    Instr::I64Shl => a.i64_shl(b),
+    Instr::I64ShrS => a.i64_shr_s(b),
+    Instr::I64ShrU => a.i64_shr_u(b),
+    Instr::I64Rotl => a.i64_rotl(b),
+    Instr::I64Rotr => a.i64_rotr(b),
+    Instr::I64Eq => a.i64_eq(b),
+    Instr::I64Ne => a.i64_ne(b),
+    Instr::I64LtS => a.i64_lt_s(b),
+    Instr::I64LtU => a.i64_lt_u(b),
+    Instr::I64GtS => a.i64_gt_s(b),
+    Instr::I64GtU => a.i64_gt_u(b),
+    Instr::I64LeS => a.i64_le_s(b),
+    Instr::I64LeU => a.i64_le_u(b),
+    Instr::I64GeS => a.i64_ge_s(b),
+    Instr::I64GeU => a.i64_ge_u(b),
+
+    Instr::I32Clz => a.i32_clz(),
+    Instr::I32Ctz => a.i32_ctz(),
+    Instr::I32Popcnt => a.i32_popcnt(),
+    Instr::I32Eqz => a.i32_eqz(),
+    Instr::I32Add => a.i32_add(b),
+    Instr::I32Sub => a.i32_sub(b),
+    Instr::I32Mul => a.i32_mul(b),
+    Instr::I32DivS | Instr::I32RemS => {
+      let quotient = a.i32_div_s(b).ok()?;
+      let rem = a.i32_rem_s(b).ok()?;
+      if matches!(instr, Instr::I32DivS) {
+        quotient
+      } else {
+        rem
+      }
+    }
+    Instr::I32DivU | Instr::I32RemU => {
+      let quotient = a.i32_div_u(b).ok()?;
+      let rem = a.i32_rem_u(b).ok()?;
+      if matches!(instr, Instr::I32DivU) {
+        quotient
+      } else {
+        rem
+      }
+    }
+    Instr::I32And => a.i32_and(b),
+    Instr::I32Or => a.i32_or(b),
+    Instr::I32Xor => a.i32_xor(b),
+    Instr::I32Shl => a.i32_shl(b),
+    Instr::I32ShrS => a.i32_shr_s(b),
+    Instr::I32ShrU => a.i32_shr_u(b),
+    Instr::I32Rotl => a.i32_rotl(b),
+    Instr::I32Rotr => a.i32_rotr(b),
+    Instr::I32Eq => a.i32_eq(b),
+    Instr::I32Ne => a.i32_ne(b),
+    Instr::I32LtS => a.i32_lt_s(b),
+    Instr::I32LtU => a.i32_lt_u(b),
+    Instr::I32GtS => a.i32_gt_s(b),
+    Instr::I32GtU => a.i32_gt_u(b),
+    Instr::I32LeS => a.i32_le_s(b),
+    Instr::I32LeU => a.i32_le_u(b),
+    Instr::I32GeS => a.i32_ge_s(b),
+    Instr::I32GeU => a.i32_ge_u(b),
+    _ => unimplemented!("no ALU test vectors for {instr:?}"),
+  };
+  Some(OpTestVector {
+    instr,
+    a: a.to_bits(),
+    b: b.to_bits(),
+    expected: expected.to_bits(),
+  })
+}
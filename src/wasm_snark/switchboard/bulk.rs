@@ -0,0 +1,747 @@
+//! Bulk memory ops, which the zkVM executes as one step per word of linear memory they write
+use super::{
+  alu::{alloc_le_bits, int64::to_u64, pack},
+  WASMTransitionCircuit as SwitchBoardCircuit,
+};
+use bellpepper_core::{
+  boolean::AllocatedBit, num::AllocatedNum, ConstraintSystem, LinearCombination, SynthesisError,
+};
+use ff::{Field, PrimeField};
+
+/// Number of bits of the addresses compared by the bulk memory ops. Offsets & sizes are `i32`
+/// operands, so every byte address (and the end of a range of them) is less than 2^35.
+pub(super) const ADDR_BITS: usize = 35;
+
+/// Operands of `memory.copy`: copy `size` bytes from `src` to `dst`
+pub struct CopyOperands<'a, F: PrimeField> {
+  pub dst: &'a AllocatedNum<F>,
+  pub src: &'a AllocatedNum<F>,
+  pub size: &'a AllocatedNum<F>,
+}
+
+/// The two consecutive words of linear memory the bytes copied to a word are read from, and the
+/// offset in bytes of the copied bytes into them
+pub struct SourceWindow<F: PrimeField> {
+  /// Index of the low word
+  pub lo: AllocatedNum<F>,
+  /// Index of the high word
+  pub hi: AllocatedNum<F>,
+  /// One-hot encoding of the offset
+  rot: Vec<AllocatedBit>,
+  rot_val: u64,
+}
+
+/// Value of the word `word` of linear memory after `memory.fill` with `offset`, `value` & `size`,
+/// given its value `old` before it: the bytes of the word in `[offset, offset + size)` are set to
+/// the low byte of `value`, and the other bytes keep their value.
+pub fn fill_word<F, CS>(
+  mut cs: CS,
+  word: &AllocatedNum<F>,
+  old: &AllocatedNum<F>,
+  offset: &AllocatedNum<F>,
+  value: &AllocatedNum<F>,
+  size: &AllocatedNum<F>,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let (word_val, offset_val, value_val, size_val) = (val(word), val(offset), val(value), val(size));
+
+  let one = SwitchBoardCircuit::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+
+  // Range check the operands, and get the fill byte
+  for (name, num) in [("word", word), ("offset", offset), ("size", size)] {
+    range_check(cs.namespace(|| name), num, 32, switch)?;
+  }
+  let value_bits = range_check(cs.namespace(|| "value"), value, 64, switch)?;
+  let fill_byte = (pack(&value_bits[..8]), value_val & 0xff);
+
+  let range = ByteRange {
+    start: (
+      LinearCombination::zero() + offset.get_variable(),
+      offset_val,
+    ),
+    end: (
+      LinearCombination::zero() + offset.get_variable() + size.get_variable(),
+      offset_val + size_val,
+    ),
+  };
+  let in_range = bytes_in_range(
+    cs.namespace(|| "in range"),
+    word,
+    word_val,
+    &range,
+    &one,
+    switch,
+  )?;
+  let bytes = (0..8).map(|_| fill_byte.clone()).collect::<Vec<_>>();
+  merge_bytes(cs.namespace(|| "merge"), old, &bytes, &in_range, switch)
+}
+
+/// Allocate the [`SourceWindow`] of the word `word` for `memory.copy`, in a linear memory of
+/// `mem_words` words.
+///
+/// # Note
+///
+/// The byte `8 · word + k` is copied from `8 · word + k + src - dst`, i.e. from the byte `rot + k`
+/// of the 16 bytes of the words `q - 1` & `q`, with `t = 8 · (word + 1) + src - dst = 8 · q + rot`.
+/// `t` is positive for every word overlapping `[dst, dst + size)`. The low word is clamped to 0
+/// and the high word to the last word of memory; the bytes of a clamped word are never copied.
+pub fn source_window<F, CS>(
+  mut cs: CS,
+  word: &AllocatedNum<F>,
+  operands: &CopyOperands<'_, F>,
+  mem_words: u64,
+  switch: F,
+) -> Result<SourceWindow<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let (word_val, dst_val, src_val) = (val(word), val(operands.dst), val(operands.src));
+
+  let one = SwitchBoardCircuit::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+
+  for (name, num) in [
+    ("word", word),
+    ("dst", operands.dst),
+    ("src", operands.src),
+    ("size", operands.size),
+  ] {
+    range_check(cs.namespace(|| name), num, 32, switch)?;
+  }
+
+  // t = 8 · (word + 1) + src - dst = 8 · q + rot
+  let t_val = (8 * (word_val + 1) + src_val).saturating_sub(dst_val);
+  let (t_bits, t) = alloc_le_bits(cs.namespace(|| "t"), u128::from(t_val), ADDR_BITS, switch)?;
+  cs.enforce(
+    || "t = 8 · (word + 1) + src - dst",
+    |lc| {
+      lc + (F::from(8), word.get_variable())
+        + (F::from(8), one.get_variable())
+        + operands.src.get_variable()
+        - operands.dst.get_variable()
+    },
+    |lc| lc + CS::one(),
+    |lc| lc + t.get_variable(),
+  );
+  let (rot_val, q_val) = (t_val & 7, t_val >> 3);
+  let q = pack(&t_bits[3..]);
+
+  let rot = one_hot(&mut cs, &t_bits[..3], rot_val, &one, switch)?;
+
+  // lo = q == 0 ? 0 : q - 1, hi = q == mem_words ? q - 1 : q
+  let q_zero = is_zero(cs.namespace(|| "q == 0"), &q, F::from(q_val), &one, switch)?;
+  let q_end = is_zero(
+    cs.namespace(|| "q == mem_words"),
+    &(q.clone() - (F::from(mem_words), one.get_variable())),
+    F::from(q_val) - F::from(mem_words),
+    &one,
+    switch,
+  )?;
+  let lo = SwitchBoardCircuit::alloc_num(
+    &mut cs,
+    || "lo",
+    || Ok(F::from(q_val.saturating_sub(1))),
+    switch,
+  )?;
+  cs.enforce(
+    || "lo = q - 1 + (q == 0)",
+    |lc| lc + &q - one.get_variable() + q_zero.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc + lo.get_variable(),
+  );
+  let hi = SwitchBoardCircuit::alloc_num(
+    &mut cs,
+    || "hi",
+    || Ok(F::from(q_val.min(mem_words.saturating_sub(1)))),
+    switch,
+  )?;
+  cs.enforce(
+    || "hi = q - (q == mem_words)",
+    |lc| lc + &q - q_end.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc + hi.get_variable(),
+  );
+
+  Ok(SourceWindow {
+    lo,
+    hi,
+    rot,
+    rot_val,
+  })
+}
+
+/// Value of the word `word` of linear memory after `memory.copy` with `operands`, given its value
+/// `old` before it and the values `source` of the words of its [`SourceWindow`]: the bytes of the
+/// word in `[dst, dst + size)` are set to the bytes they are copied from, and the other bytes keep
+/// their value.
+pub fn copy_word<F, CS>(
+  mut cs: CS,
+  word: &AllocatedNum<F>,
+  old: &AllocatedNum<F>,
+  source: [&AllocatedNum<F>; 2],
+  window: &SourceWindow<F>,
+  operands: &CopyOperands<'_, F>,
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let (word_val, dst_val, size_val) = (val(word), val(operands.dst), val(operands.size));
+
+  let one = SwitchBoardCircuit::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+
+  let bytes = window_bytes(
+    cs.namespace(|| "source"),
+    source,
+    &window.rot,
+    window.rot_val,
+    switch,
+  )?;
+
+  let range = ByteRange {
+    start: (
+      LinearCombination::zero() + operands.dst.get_variable(),
+      dst_val,
+    ),
+    end: (
+      LinearCombination::zero() + operands.dst.get_variable() + operands.size.get_variable(),
+      dst_val + size_val,
+    ),
+  };
+  let in_range = bytes_in_range(
+    cs.namespace(|| "in range"),
+    word,
+    word_val,
+    &range,
+    &one,
+    switch,
+  )?;
+  merge_bytes(cs.namespace(|| "merge"), old, &bytes, &in_range, switch)
+}
+
+/// Allocate the one-hot encoding of the 3-bit `bits` of value `rot_val`. `one` is the switched
+/// one, so every bit is unset when the switch is off.
+pub(super) fn one_hot<F, CS>(
+  cs: &mut CS,
+  bits: &[AllocatedBit],
+  rot_val: u64,
+  one: &AllocatedNum<F>,
+  switch: F,
+) -> Result<Vec<AllocatedBit>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let rot = (0..8)
+    .map(|r| SwitchBoardCircuit::alloc_bit(cs, || format!("rot = {r}"), Some(rot_val == r), switch))
+    .collect::<Result<Vec<_>, _>>()?;
+  cs.enforce(
+    || "Σ rot_r = 1",
+    |lc| rot.iter().fold(lc, |lc, bit| lc + bit.get_variable()),
+    |lc| lc + CS::one(),
+    |lc| lc + one.get_variable(),
+  );
+  cs.enforce(
+    || "Σ r · rot_r = rot",
+    |lc| {
+      rot.iter().enumerate().fold(lc, |lc, (r, bit)| {
+        lc + (F::from(r as u64), bit.get_variable())
+      })
+    },
+    |lc| lc + CS::one(),
+    |_| pack(bits),
+  );
+  Ok(rot)
+}
+
+/// The 8 bytes at offset `rot` into the 16 bytes of the consecutive words `words`, where `rot` is
+/// one-hot encoded, i.e. `byte k = Σ_r rot_r · window byte (r + k)`
+pub(super) fn window_bytes<F, CS>(
+  mut cs: CS,
+  words: [&AllocatedNum<F>; 2],
+  rot: &[AllocatedBit],
+  rot_val: u64,
+  switch: F,
+) -> Result<Vec<(LinearCombination<F>, u64)>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let lo_bits = range_check(cs.namespace(|| "lo"), words[0], 64, switch)?;
+  let hi_bits = range_check(cs.namespace(|| "hi"), words[1], 64, switch)?;
+  let window_val = u128::from(val(words[0])) | (u128::from(val(words[1])) << 64);
+  let window_bits = lo_bits.into_iter().chain(hi_bits).collect::<Vec<_>>();
+
+  let mut bytes = Vec::with_capacity(8);
+  for k in 0..8 {
+    let mut byte = LinearCombination::zero();
+    for (r, rot) in rot.iter().enumerate() {
+      let i = r + k;
+      let selected = SwitchBoardCircuit::alloc_num(
+        &mut cs,
+        || format!("byte {k}, rot {r}"),
+        || {
+          Ok(if rot_val == r as u64 {
+            F::from(((window_val >> (8 * i)) & 0xff) as u64)
+          } else {
+            F::ZERO
+          })
+        },
+        switch,
+      )?;
+      cs.enforce(
+        || format!("rot_{r} · window byte {i} = selected"),
+        |lc| lc + rot.get_variable(),
+        |_| pack(&window_bits[8 * i..8 * (i + 1)]),
+        |lc| lc + selected.get_variable(),
+      );
+      byte = byte + selected.get_variable();
+    }
+    let i = rot_val as usize + k;
+    bytes.push((byte, ((window_val >> (8 * i)) & 0xff) as u64));
+  }
+  Ok(bytes)
+}
+
+/// A range `[start, end)` of byte addresses
+struct ByteRange<F: PrimeField> {
+  start: (LinearCombination<F>, u64),
+  end: (LinearCombination<F>, u64),
+}
+
+/// Allocate a bit for each byte of the word `word`, set iff the byte is in `range`
+fn bytes_in_range<F, CS>(
+  mut cs: CS,
+  word: &AllocatedNum<F>,
+  word_val: u64,
+  range: &ByteRange<F>,
+  one: &AllocatedNum<F>,
+  switch: F,
+) -> Result<Vec<AllocatedBit>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  (0..8)
+    .map(|k| {
+      let addr = LinearCombination::zero()
+        + (F::from(8), word.get_variable())
+        + (F::from(k), one.get_variable());
+      let addr_val = 8 * word_val + k;
+      let after_start = ge(
+        cs.namespace(|| format!("byte {k} >= start")),
+        (&addr, addr_val),
+        (&range.start.0, range.start.1),
+        one,
+        switch,
+      )?;
+      let after_end = ge(
+        cs.namespace(|| format!("byte {k} >= end")),
+        (&addr, addr_val),
+        (&range.end.0, range.end.1),
+        one,
+        switch,
+      )?;
+      AllocatedBit::and_not(
+        cs.namespace(|| format!("byte {k} in range")),
+        &after_start,
+        &after_end,
+      )
+    })
+    .collect()
+}
+
+/// Allocate the word `old` with its byte k set to `bytes[k]` if `in_range[k]`, i.e.
+/// `old + Σ 2^(8k) · in_range_k · (byte_k - old_byte_k)`
+fn merge_bytes<F, CS>(
+  mut cs: CS,
+  old: &AllocatedNum<F>,
+  bytes: &[(LinearCombination<F>, u64)],
+  in_range: &[AllocatedBit],
+  switch: F,
+) -> Result<AllocatedNum<F>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let old_val = val(old);
+  let old_bits = range_check(cs.namespace(|| "old"), old, 64, switch)?;
+
+  let mut new_val = old_val;
+  let mut new = LinearCombination::zero() + old.get_variable();
+  for (k, ((byte, byte_val), in_range)) in bytes.iter().zip(in_range).enumerate() {
+    let old_byte = pack(&old_bits[8 * k..8 * (k + 1)]);
+    let old_byte_val = (old_val >> (8 * k)) & 0xff;
+    let in_range_val = in_range.get_value() == Some(true);
+    let delta = SwitchBoardCircuit::alloc_num(
+      &mut cs,
+      || format!("byte {k} delta"),
+      || {
+        Ok(if in_range_val {
+          F::from(*byte_val) - F::from(old_byte_val)
+        } else {
+          F::ZERO
+        })
+      },
+      switch,
+    )?;
+    cs.enforce(
+      || format!("in_range_{k} * (byte_{k} - old_byte_{k}) = delta_{k}"),
+      |lc| lc + in_range.get_variable(),
+      |lc| lc + byte - &old_byte,
+      |lc| lc + delta.get_variable(),
+    );
+    new = new + (F::from(1 << (8 * k)), delta.get_variable());
+    if in_range_val {
+      new_val = (new_val & !(0xff << (8 * k))) | (byte_val << (8 * k));
+    }
+  }
+
+  let new_word = SwitchBoardCircuit::alloc_num(&mut cs, || "new", || Ok(F::from(new_val)), switch)?;
+  cs.enforce(
+    || "new = old + Σ 2^(8k) · delta_k",
+    |_| new,
+    |lc| lc + CS::one(),
+    |lc| lc + new_word.get_variable(),
+  );
+
+  Ok(new_word)
+}
+
+/// Value of an allocated num, 0 if it is not a u64
+pub(super) fn val<F: PrimeField>(num: &AllocatedNum<F>) -> u64 {
+  num.get_value().and_then(to_u64).unwrap_or(0)
+}
+
+/// Range check `num` in `n` bits, and return its little-endian bits
+pub(super) fn range_check<F, CS>(
+  mut cs: CS,
+  num: &AllocatedNum<F>,
+  n: usize,
+  switch: F,
+) -> Result<Vec<AllocatedBit>, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let (bits, packed) = alloc_le_bits(cs.namespace(|| "bits"), u128::from(val(num)), n, switch)?;
+  cs.enforce(
+    || "num = packed",
+    |lc| lc + num.get_variable(),
+    |lc| lc + CS::one(),
+    |lc| lc + packed.get_variable(),
+  );
+  Ok(bits)
+}
+
+/// Allocate the bit `x >= y`, for `x` and `y` less than 2^[`ADDR_BITS`]
+fn ge<F, CS>(
+  mut cs: CS,
+  (x, x_val): (&LinearCombination<F>, u64),
+  (y, y_val): (&LinearCombination<F>, u64),
+  one: &AllocatedNum<F>,
+  switch: F,
+) -> Result<AllocatedBit, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  // x - y + 2^n is in [0, 2^(n + 1)), and its bit n is set iff x >= y
+  let (mut bits, packed) = alloc_le_bits(
+    cs.namespace(|| "x - y + 2^n"),
+    u128::from(x_val) + (1 << ADDR_BITS) - u128::from(y_val),
+    ADDR_BITS + 1,
+    switch,
+  )?;
+  cs.enforce(
+    || "x - y + 2^n = packed",
+    |lc| lc + x - y + (F::from(1 << ADDR_BITS), one.get_variable()),
+    |lc| lc + CS::one(),
+    |lc| lc + packed.get_variable(),
+  );
+  Ok(bits.swap_remove(ADDR_BITS))
+}
+
+/// Allocate the bit `x == 0`. `one` is the switched one, so the bit is unset when the switch is
+/// off.
+fn is_zero<F, CS>(
+  mut cs: CS,
+  x: &LinearCombination<F>,
+  x_val: F,
+  one: &AllocatedNum<F>,
+  switch: F,
+) -> Result<AllocatedBit, SynthesisError>
+where
+  F: PrimeField,
+  CS: ConstraintSystem<F>,
+{
+  let zero = SwitchBoardCircuit::alloc_bit(
+    &mut cs,
+    || "x == 0",
+    Some(bool::from(x_val.is_zero())),
+    switch,
+  )?;
+  let inv = SwitchBoardCircuit::alloc_num(
+    &mut cs,
+    || "x^-1",
+    || Ok(Option::from(x_val.invert()).unwrap_or(F::ZERO)),
+    switch,
+  )?;
+  cs.enforce(
+    || "x * (x == 0) = 0",
+    |lc| lc + x,
+    |lc| lc + zero.get_variable(),
+    |lc| lc,
+  );
+  cs.enforce(
+    || "x * x^-1 = one - (x == 0)",
+    |lc| lc + x,
+    |lc| lc + inv.get_variable(),
+    |lc| lc + one.get_variable() - zero.get_variable(),
+  );
+  Ok(zero)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{copy_word, fill_word, source_window, CopyOperands};
+  use crate::wasm_snark::switchboard::{
+    alu::int64::to_u64, WASMTransitionCircuit as SwitchBoardCircuit,
+  };
+  use bellpepper_core::{test_cs::TestConstraintSystem, ConstraintSystem};
+  use ff::Field;
+  use nova::{provider::Bn256EngineIPA, traits::Engine};
+
+  type E = Bn256EngineIPA;
+  type F = <E as Engine>::Scalar;
+
+  /// Prove the fill of `word` & return the new word, if the constraints are satisfied
+  fn prove_fill_word(word: u64, old: u64, offset: u64, value: u64, size: u64) -> Option<u64> {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let mut alloc = |name: &str, val: u64| {
+      SwitchBoardCircuit::alloc_num(&mut cs, || name, || Ok(F::from(val)), F::ONE).unwrap()
+    };
+    let (word, old, offset, value, size) = (
+      alloc("word", word),
+      alloc("old", old),
+      alloc("offset", offset),
+      alloc("value", value),
+      alloc("size", size),
+    );
+    let new = fill_word(
+      cs.namespace(|| "fill word"),
+      &word,
+      &old,
+      &offset,
+      &value,
+      &size,
+      F::ONE,
+    )
+    .unwrap();
+    if !cs.is_satisfied() {
+      return None;
+    }
+    new.get_value().and_then(to_u64)
+  }
+
+  #[test]
+  fn test_fill_word() {
+    // Fill 17 bytes with 0xAB from byte 3, over memory whose byte i is i
+    let (offset, size, value) = (3, 17, 0xAB);
+    let memory = (0..32u8).collect::<Vec<_>>();
+    let mut filled = memory.clone();
+    filled[offset as usize..(offset + size) as usize].fill(value as u8);
+
+    for word in 0..4 {
+      let bytes =
+        |mem: &[u8]| u64::from_le_bytes(mem[8 * word..8 * (word + 1)].try_into().unwrap());
+      let new = prove_fill_word(word as u64, bytes(&memory), offset, value, size);
+      assert_eq!(new, Some(bytes(&filled)), "word {word}");
+    }
+
+    // Only the low byte of the value fills memory
+    assert_eq!(
+      prove_fill_word(0, 0, 0, 0x1234, 8),
+      Some(0x3434_3434_3434_3434)
+    );
+
+    // Nothing is filled by a fill of size 0
+    assert_eq!(prove_fill_word(0, 42, 0, 0xAB, 0), Some(42));
+  }
+
+  #[test]
+  fn test_fill_word_wrong_value() {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let mut alloc = |name: &str, val: u64| {
+      SwitchBoardCircuit::alloc_num(&mut cs, || name, || Ok(F::from(val)), F::ONE).unwrap()
+    };
+    let (word, old, offset, value, size) = (
+      alloc("word", 0),
+      alloc("old", 0),
+      alloc("offset", 0),
+      alloc("value", 0xAB),
+      alloc("size", 4),
+    );
+    let _ = fill_word(
+      cs.namespace(|| "fill word"),
+      &word,
+      &old,
+      &offset,
+      &value,
+      &size,
+      F::ONE,
+    )
+    .unwrap();
+    assert!(cs.is_satisfied());
+
+    // A trace filling the whole word instead of its first 4 bytes
+    cs.set("fill word/merge/new/num", F::from(0xABAB_ABAB_ABAB_ABAB));
+    assert!(!cs.is_satisfied());
+  }
+
+  /// Copy `size` bytes from `src` to `dst` in `memory` a word at a time, like the zkVM does:
+  /// proving each word read & written, in descending order when `dst > src` so overlapping
+  /// source bytes are read before they are overwritten.
+  fn prove_copy(memory: &mut [u8], dst: u64, src: u64, size: u64) {
+    fn read(memory: &[u8], word: u64) -> u64 {
+      let word = word as usize;
+      u64::from_le_bytes(memory[8 * word..8 * (word + 1)].try_into().unwrap())
+    }
+
+    let mem_words = (memory.len() / 8) as u64;
+    let mut words = (dst / 8..=(dst + size - 1) / 8).collect::<Vec<_>>();
+    if dst > src {
+      words.reverse();
+    }
+    for word in words {
+      let mut cs = TestConstraintSystem::<F>::new();
+      let mut alloc = |name: &str, val: u64| {
+        SwitchBoardCircuit::alloc_num(&mut cs, || name, || Ok(F::from(val)), F::ONE).unwrap()
+      };
+      let (word_num, dst_num, src_num, size_num) = (
+        alloc("word", word),
+        alloc("dst", dst),
+        alloc("src", src),
+        alloc("size", size),
+      );
+      let operands = CopyOperands {
+        dst: &dst_num,
+        src: &src_num,
+        size: &size_num,
+      };
+      let window = source_window(
+        cs.namespace(|| "source window"),
+        &word_num,
+        &operands,
+        mem_words,
+        F::ONE,
+      )
+      .unwrap();
+      let (lo, hi) = (
+        to_u64(window.lo.get_value().unwrap()).unwrap(),
+        to_u64(window.hi.get_value().unwrap()).unwrap(),
+      );
+      let mut alloc = |name: &str, val: u64| {
+        SwitchBoardCircuit::alloc_num(&mut cs, || name, || Ok(F::from(val)), F::ONE).unwrap()
+      };
+      let (lo, hi, old) = (
+        alloc("lo", read(memory, lo)),
+        alloc("hi", read(memory, hi)),
+        alloc("old", read(memory, word)),
+      );
+      let new = copy_word(
+        cs.namespace(|| "copy word"),
+        &word_num,
+        &old,
+        [&lo, &hi],
+        &window,
+        &operands,
+        F::ONE,
+      )
+      .unwrap();
+      assert!(cs.is_satisfied(), "word {word}");
+
+      let new = to_u64(new.get_value().unwrap()).unwrap();
+      let word = word as usize;
+      memory[8 * word..8 * (word + 1)].copy_from_slice(&new.to_le_bytes());
+    }
+  }
+
+  #[test]
+  fn test_copy_word() {
+    // (dst, src, size): overlapping forward & backward, unaligned, aligned, up to the end of
+    // memory & from its start
+    let copies = [
+      (7, 2, 13),
+      (3, 9, 13),
+      (8, 0, 16),
+      (0, 8, 16),
+      (5, 21, 11),
+      (21, 5, 11),
+      (1, 0, 31),
+      (0, 1, 31),
+      (24, 0, 8),
+      (12, 12, 4),
+      (6, 6, 1),
+    ];
+    for (dst, src, size) in copies {
+      let mut memory = (1..=32u8).collect::<Vec<_>>();
+      let mut expected = memory.clone();
+      expected.copy_within(src as usize..(src + size) as usize, dst as usize);
+
+      prove_copy(&mut memory, dst, src, size);
+      assert_eq!(memory, expected, "copy {size} bytes from {src} to {dst}");
+    }
+  }
+
+  #[test]
+  fn test_copy_word_wrong_value() {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let mut alloc = |name: &str, val: u64| {
+      SwitchBoardCircuit::alloc_num(&mut cs, || name, || Ok(F::from(val)), F::ONE).unwrap()
+    };
+    let (word, dst, src, size, lo, hi, old) = (
+      alloc("word", 1),
+      alloc("dst", 8),
+      alloc("src", 0),
+      alloc("size", 8),
+      alloc("lo", 0x0807_0605_0403_0201),
+      alloc("hi", 0x100F_0E0D_0C0B_0A09),
+      alloc("old", 0x100F_0E0D_0C0B_0A09),
+    );
+    let operands = CopyOperands {
+      dst: &dst,
+      src: &src,
+      size: &size,
+    };
+    let window = source_window(
+      cs.namespace(|| "source window"),
+      &word,
+      &operands,
+      4,
+      F::ONE,
+    )
+    .unwrap();
+    let new = copy_word(
+      cs.namespace(|| "copy word"),
+      &word,
+      &old,
+      [&lo, &hi],
+      &window,
+      &operands,
+      F::ONE,
+    )
+    .unwrap();
+    assert!(cs.is_satisfied());
+    assert_eq!(new.get_value(), Some(F::from(0x0807_0605_0403_0201)));
+
+    // A trace copying the source word as is, i.e. the old value of the destination
+    cs.set("copy word/merge/new/num", F::from(0x100F_0E0D_0C0B_0A09));
+    assert!(!cs.is_satisfied());
+  }
+}
@@ -2,22 +2,27 @@ use crate::wasm_ctx::ISMemSizes;
 
 use super::{
   gadgets::{
+    hash::hash,
     int::{add, eqz_bit},
     utils::{alloc_one, conditionally_select},
   },
   mcc::multiset_ops::avt_tuple_to_scalar_vec,
-  MEMORY_OPS_PER_STEP,
 };
+use access::{access_size, access_window, enforce_loaded, load_extension, store_words};
 use alu::{
-  eq, eqz,
+  alloc_le_bits, enforce_boolean, eq, eqz,
+  float::{trunc, trunc_sat, FloatFormat, F32, F64},
+  float32::{f32_add, f32_div, f32_mul, f32_sub},
+  float64::{arith, F64Op},
   int32::{
     add32, bitops_32, div_rem_s_32, div_rem_u_32, le_gt_s_32, lt_ge_s_32, mul32, shift_rotate_32,
     sub32, unary_ops_32,
   },
   int64::{
-    add64, bitops_64, div_rem_s_64, div_rem_u_64, le_gt_s, lt_ge_s, mul64, shift_rotate_64, sub64,
-    unary_ops_64,
+    add64, bitops_64, div_rem_s_64, div_rem_u_64, extend, extend_i32, le_gt_s, lt_ge_s, mul64,
+    shift_rotate_64, sub64, unary_ops_64,
   },
+  pack,
 };
 use bellpepper_core::{
   self,
@@ -25,15 +30,22 @@ use bellpepper_core::{
   num::AllocatedNum,
   ConstraintSystem, SynthesisError,
 };
+use bulk::{copy_word, fill_word, range_check, source_window, CopyOperands, ADDR_BITS};
 use ff::{PrimeField, PrimeFieldBits};
 use itertools::Itertools;
 use nova::nebula::rs::StepCircuit;
+use serde::{Deserialize, Serialize};
 use wasmi::{
-  AddressOffset, BCGlobalIdx, BranchOffset, BranchTableTargets, DropKeep, Instruction as Instr,
-  WitnessVM,
+  AddressOffset, BCDataSegmentIdx, BCFuncIdx, BCGlobalIdx, BCTableIdx, BranchOffset,
+  BranchTableTargets, ConstRef, DropKeep, Instruction as Instr, SignatureIdx, WitnessVM,
+  HOST_CALL_ARG, HOST_CALL_GLOBAL, HOST_CALL_READ, HOST_CALL_RESULT, MAX_KEEP_PER_STEP,
 };
 
+mod access;
 mod alu;
+mod bulk;
+
+pub use alu::{test_vectors, OpTestVector};
 
 /// The circuit representing a step in the execution of a WASM program. Each step in WASM execution
 /// corresponds to an opcode (from the WASM ISA) that gets executed.
@@ -46,14 +58,24 @@ pub struct WASMTransitionCircuit {
   RS: Vec<(usize, u64, u64)>,
   WS: Vec<(usize, u64, u64)>,
   IS_sizes: ISMemSizes,
+  is_padding: bool,
+}
+
+/// Name of an opcode without its operands, e.g. `Br` for `Br(BranchOffset(3))`
+fn opcode_name(instr: &Instr) -> String {
+  let name = format!("{instr:?}");
+  match name.split_once('(') {
+    Some((opcode, _)) => opcode.to_string(),
+    None => name,
+  }
 }
 
 impl<F> StepCircuit<F> for WASMTransitionCircuit
 where
-  F: PrimeField + PrimeFieldBits,
+  F: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
 {
   fn arity(&self) -> usize {
-    1
+    9
   }
 
   fn synthesize<CS: ConstraintSystem<F>>(
@@ -61,6 +83,8 @@ where
     cs: &mut CS,
     z: &[AllocatedNum<F>],
   ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+    let _span = tracing::trace_span!("synthesize", opcode = opcode_name(&self.vm.instr)).entered();
+
     /*
      * ***************** Switchboard Circuit *****************
      */
@@ -71,27 +95,51 @@ where
 
     // unreachable, i.e. nop
     self.visit_unreachable(cs.namespace(|| "unreachable"), &mut switches)?;
+    let nop_switch = switches[0].clone();
 
     // local.get, local.set, local.tee
     self.visit_local_get(cs.namespace(|| "local.get"), &mut switches)?;
     self.visit_local_set(cs.namespace(|| "local.set"), &mut switches)?;
     self.visit_local_tee(cs.namespace(|| "local.tee"), &mut switches)?;
 
+    // The pc of the opcode, the target of the branch preceding it if any
+    let pc = self.pc(cs.namespace(|| "pc"), &z[8])?;
+
     // branch opcodes
-    self.visit_br(cs.namespace(|| "br"), &mut switches)?;
-    self.visit_br_if_eqz(cs.namespace(|| "Instr::BrIfEqz"), &mut switches)?;
-    self.visit_br_if_nez(cs.namespace(|| "Instr::BrIfNez"), &mut switches)?;
+    let br = self.visit_br(cs.namespace(|| "br"), &mut switches, &pc)?;
+    let br_if_eqz = self.visit_br_if_eqz(cs.namespace(|| "Instr::BrIfEqz"), &mut switches, &pc)?;
+    let br_if_nez = self.visit_br_if_nez(cs.namespace(|| "Instr::BrIfNez"), &mut switches, &pc)?;
     self.visit_br_adjust(cs.namespace(|| "visit_br_adjust"), &mut switches)?;
-    self.visit_br_table(cs.namespace(|| "Instr::BrTable"), &mut switches)?;
+    let br_table = self.visit_br_table(cs.namespace(|| "Instr::BrTable"), &mut switches, &pc)?;
 
     // return opcodes
-    self.drop_keep(cs.namespace(|| "drop keep"), &mut switches)?;
+    let (stack_top, kept) =
+      self.drop_keep(cs.namespace(|| "drop keep"), &mut switches, &z[0], &z[4])?;
     self.visit_ret(cs.namespace(|| "return"), &mut switches)?;
+    let ret_switch = switches[switches.len() - 1].clone();
+    self.visit_return_if_nez(cs.namespace(|| "return_if_nez"), &mut switches)?;
+    let return_if_nez_switch = switches[switches.len() - 1].clone();
+    let returned = Self::latch_returned(
+      cs.namespace(|| "latch returned"),
+      &[ret_switch, return_if_nez_switch],
+      &z[4],
+      &z[5],
+    )?;
 
     // call related opcodes
+    self.visit_call(cs.namespace(|| "visit_call"), &mut switches)?;
+    self.visit_call_indirect(
+      cs.namespace(|| "visit_call_indirect"),
+      &mut switches,
+      &z[6],
+      &z[7],
+    )?;
     self.visit_call_internal_step(cs.namespace(|| "visit_call_internal_step"), &mut switches)?;
-    self
-      .visit_host_call_stack_step(cs.namespace(|| "visit_host_call_stack_step"), &mut switches)?;
+    let host_io = self.visit_host_call_stack_step(
+      cs.namespace(|| "visit_host_call_stack_step"),
+      &mut switches,
+      &z[1],
+    )?;
     self.visit_host_call_step(cs.namespace(|| "visit_host_call_step"), &mut switches)?;
 
     // select opcode
@@ -106,15 +154,37 @@ where
     self.visit_load(cs.namespace(|| "load"), &mut switches)?;
 
     // specific linear memory opcodes
-    self.visit_memory_size(cs.namespace(|| "visit_memory_size"), &mut switches)?;
-    self.visit_memory_grow(cs.namespace(|| "visit_memory_grow"), &mut switches)?;
+    self.visit_memory_size(cs.namespace(|| "visit_memory_size"), &mut switches, &z[3])?;
+    let pages =
+      self.visit_memory_grow(cs.namespace(|| "visit_memory_grow"), &mut switches, &z[3])?;
     self.visit_memory_fill(cs.namespace(|| "visit_memory_fill"), &mut switches)?;
     self.visit_memory_fill_step(cs.namespace(|| "visit_memory_fill_step"), &mut switches)?;
     self.visit_memory_copy(cs.namespace(|| "visit_memory_copy"), &mut switches)?;
     self.visit_memory_copy_step(cs.namespace(|| "visit_memory_copy_step"), &mut switches)?;
+    self.visit_memory_init(cs.namespace(|| "visit_memory_init"), &mut switches)?;
+    self.visit_memory_init_step(cs.namespace(|| "visit_memory_init_step"), &mut switches)?;
+    self.visit_data_drop(cs.namespace(|| "visit_data_drop"), &mut switches)?;
+
+    // table opcodes
+    self.visit_table_get(
+      cs.namespace(|| "visit_table_get"),
+      &mut switches,
+      &z[6],
+      &z[7],
+    )?;
+    self.visit_table_set(
+      cs.namespace(|| "visit_table_set"),
+      &mut switches,
+      &z[6],
+      &z[7],
+    )?;
+
+    // reference opcodes, `ref.null` & `ref.is_null` are a const & an `i64.eqz` in the bytecode
+    self.visit_ref_func(cs.namespace(|| "visit_ref_func"), &mut switches)?;
 
     // const opcodes
     self.visit_const(cs.namespace(|| "const"), &mut switches)?;
+    self.visit_const_ref(cs.namespace(|| "const_ref"), &mut switches)?;
 
     // i32 opcodes
     self.visit_i32_add(cs.namespace(|| "i32.add"), &mut switches)?;
@@ -145,10 +215,29 @@ where
     self.visit_eq(cs.namespace(|| "visit_eq"), &mut switches)?;
     self.visit_ne(cs.namespace(|| "visit_ne"), &mut switches)?;
 
+    // float-to-int conversions
+    self.visit_trunc_sat(cs.namespace(|| "visit_trunc_sat"), &mut switches)?;
+    self.visit_trunc_float_to_int(cs.namespace(|| "visit_trunc_float_to_int"), &mut switches)?;
+
+    // i32-to-i64 conversions
+    self.visit_i64_extend_i32(cs.namespace(|| "visit_i64_extend_i32"), &mut switches)?;
+
+    // wrap & in-place sign extensions
+    self.visit_conversions(cs.namespace(|| "visit_conversions"), &mut switches)?;
+
     // unary and binary ops
     self.visit_unary(cs.namespace(|| "visit_unary"), &mut switches)?;
     self.visit_binary(cs.namespace(|| "visit_binary"), &mut switches)?;
 
+    // f64 arithmetic
+    self.visit_f64_arith(cs.namespace(|| "f64 arith"), &mut switches)?;
+
+    // f32 arithmetic
+    self.visit_f32_add(cs.namespace(|| "f32.add"), &mut switches)?;
+    self.visit_f32_sub(cs.namespace(|| "f32.sub"), &mut switches)?;
+    self.visit_f32_mul(cs.namespace(|| "f32.mul"), &mut switches)?;
+    self.visit_f32_div(cs.namespace(|| "f32.div"), &mut switches)?;
+
     /*
      *  ***************** Switch constraints *****************
      */
@@ -175,7 +264,55 @@ where
       );
     }
 
-    Ok(z.to_vec())
+    /*
+     *  ***************** Instruction count *****************
+     */
+
+    // Count the executed instructions in `z`, i.e. every step but the padding ones. Only a nop can
+    // be padding.
+    let is_padding = AllocatedBit::alloc(cs.namespace(|| "is padding"), Some(self.is_padding))?;
+    cs.enforce(
+      || "padding is a nop",
+      |lc| lc + is_padding.get_variable(),
+      |lc| lc + CS::one() - nop_switch.get_variable(),
+      |lc| lc,
+    );
+    let instr_count = AllocatedNum::alloc(cs.namespace(|| "instruction count"), || {
+      let count = z[2].get_value().ok_or(SynthesisError::AssignmentMissing)?;
+      Ok(if self.is_padding {
+        count
+      } else {
+        count + F::ONE
+      })
+    })?;
+    cs.enforce(
+      || "instruction count = count + 1 - is_padding",
+      |lc| lc + z[2].get_variable() + CS::one() - is_padding.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + instr_count.get_variable(),
+    );
+
+    /*
+     *  ***************** Branch target *****************
+     */
+
+    let branch_target = Self::branch_target(
+      cs.namespace(|| "branch target"),
+      &[br, br_if_eqz, br_if_nez, br_table],
+    )?;
+
+    // The table layout is the same in every step
+    Ok(vec![
+      stack_top,
+      host_io,
+      instr_count,
+      pages,
+      kept,
+      returned,
+      z[6].clone(),
+      z[7].clone(),
+      branch_target,
+    ])
   }
 
   fn non_deterministic_advice(&self) -> Vec<F> {
@@ -193,6 +330,101 @@ where
 }
 
 impl WASMTransitionCircuit {
+  /// Allocate the pc of the opcode of the step, which the branch preceding it selects: the public
+  /// `branch_target` is `target + 1` out of a branch step & 0 out of any other, so
+  /// `branch_target * (pc + 1 - branch_target) == 0`. A padding step right after a branch is at its
+  /// target.
+  fn pc<CS, F>(
+    &self,
+    mut cs: CS,
+    branch_target: &AllocatedNum<F>,
+  ) -> Result<AllocatedNum<F>, SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let pc = AllocatedNum::alloc(cs.namespace(|| "pc"), || {
+      let branch_target = branch_target
+        .get_value()
+        .ok_or(SynthesisError::AssignmentMissing)?;
+      Ok(if self.is_padding && branch_target != F::ZERO {
+        branch_target - F::ONE
+      } else {
+        F::from(self.vm.pc as u64)
+      })
+    })?;
+    cs.enforce(
+      || "branch_target * (pc + 1 - branch_target) == 0",
+      |lc| lc + branch_target.get_variable(),
+      |lc| lc + pc.get_variable() + CS::one() - branch_target.get_variable(),
+      |lc| lc,
+    );
+    Ok(pc)
+  }
+
+  /// Compute the public `branch_target` of the step, see [`Self::pc`], from the `(target, switch)`
+  /// of each branch opcode, its switched target & its switch: `branch_target = sum(target +
+  /// switch)`, as at most one switch is on.
+  fn branch_target<CS, F>(
+    mut cs: CS,
+    branches: &[(AllocatedNum<F>, AllocatedNum<F>)],
+  ) -> Result<AllocatedNum<F>, SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let branch_target = AllocatedNum::alloc(cs.namespace(|| "branch_target"), || {
+      branches
+        .iter()
+        .try_fold(F::ZERO, |sum, (target, switch)| {
+          let target = target.get_value()?;
+          let switch = switch.get_value()?;
+          Some(sum + target + switch)
+        })
+        .ok_or(SynthesisError::AssignmentMissing)
+    })?;
+    cs.enforce(
+      || "branch_target == sum(target + switch)",
+      |lc| {
+        branches.iter().fold(lc, |lc, (target, switch)| {
+          lc + target.get_variable() + switch.get_variable()
+        })
+      },
+      |lc| lc + CS::one(),
+      |lc| lc + branch_target.get_variable(),
+    );
+    Ok(branch_target)
+  }
+
+  /// The branch offset of a branch opcode in the field: the tracer sign-extends it into `vm.I`, as
+  /// a backward branch has a negative offset.
+  fn branch_offset<F: PrimeField>(&self) -> F {
+    let offset = self.vm.I as i64;
+    if offset < 0 {
+      -F::from(offset.unsigned_abs())
+    } else {
+      F::from(offset as u64)
+    }
+  }
+
+  /// Bind the switched `pc` a branch opcode witnesses to the `step_pc` of the step
+  fn bind_pc<CS, F>(
+    mut cs: CS,
+    pc: &AllocatedNum<F>,
+    step_pc: &AllocatedNum<F>,
+    switch_var: &AllocatedNum<F>,
+  ) where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    cs.enforce(
+      || "switch * step_pc == pc",
+      |lc| lc + switch_var.get_variable(),
+      |lc| lc + step_pc.get_variable(),
+      |lc| lc + pc.get_variable(),
+    );
+  }
+
   /// Allocate a switch. Depending on the instruction it could be on or off.
   fn switch<CS, F>(
     &self,
@@ -207,10 +439,12 @@ impl WASMTransitionCircuit {
     // Check if instruction is on or off
     let switch = if J == self.vm.J { F::ONE } else { F::ZERO };
 
-    // Push the allocated switch to the switches vector to be used in the switch constraints
-    switches.push(AllocatedNum::alloc(cs.namespace(|| "switch"), || {
-      Ok(switch)
-    })?);
+    // Push the allocated switch to the switches vector to be used in the switch constraints. The
+    // namespace names the J index the switch is for.
+    switches.push(AllocatedNum::alloc(
+      cs.namespace(|| format!("switch {J}")),
+      || Ok(switch),
+    )?);
 
     // return the switch as a constant
     Ok(switch)
@@ -337,9 +571,85 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
+  /// Get the address of the entry of the default function table at index `Y`, i.e.
+  /// `table_addr + 2 * Y`, checking the index is within the table.
+  ///
+  /// The address & the number of entries of the table are the ones carried in `z`, `(z_table_addr,
+  /// z_table_len)`, so they are those of the proven program rather than the prover's choice.
+  fn table_entry_addr<CS, F>(
+    &self,
+    mut cs: CS,
+    Y: &AllocatedNum<F>,
+    one: &AllocatedNum<F>,
+    (z_table_addr, z_table_len): (&AllocatedNum<F>, &AllocatedNum<F>),
+    switches: &[AllocatedNum<F>],
+    switch: F,
+  ) -> Result<AllocatedNum<F>, SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let switch_var = switches.last().ok_or(SynthesisError::AssignmentMissing)?;
+
+    // index + slack + 1 = table_len, with both terms in 32 bits so the sum can not wrap
+    let table_len = self.IS_sizes.table_len() as u64;
+    let (_, index) = alloc_le_bits(cs.namespace(|| "index"), self.vm.Y as u128, 32, switch)?;
+    cs.enforce(
+      || "index == Y",
+      |lc| lc + index.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + Y.get_variable(),
+    );
+    let slack = table_len.saturating_sub(self.vm.Y.saturating_add(1));
+    let (_, slack) = alloc_le_bits(cs.namespace(|| "slack"), slack as u128, 32, switch)?;
+    let table_len = Self::alloc_num(&mut cs, || "table_len", || Ok(F::from(table_len)), switch)?;
+    cs.enforce(
+      || "switch * z_table_len == table_len",
+      |lc| lc + switch_var.get_variable(),
+      |lc| lc + z_table_len.get_variable(),
+      |lc| lc + table_len.get_variable(),
+    );
+    cs.enforce(
+      || "index + slack + 1 == table_len",
+      |lc| lc + index.get_variable() + slack.get_variable() + one.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + table_len.get_variable(),
+    );
+
+    let table_addr = Self::alloc_num(
+      &mut cs,
+      || "table_addr",
+      || Ok(F::from(self.IS_sizes.table_addr() as u64)),
+      switch,
+    )?;
+    cs.enforce(
+      || "switch * z_table_addr == table_addr",
+      |lc| lc + switch_var.get_variable(),
+      |lc| lc + z_table_addr.get_variable(),
+      |lc| lc + table_addr.get_variable(),
+    );
+    let entry_addr = Self::alloc_num(
+      &mut cs,
+      || "entry_addr",
+      || Ok(F::from(self.IS_sizes.table_addr() as u64) + F::from(self.vm.Y).double()),
+      switch,
+    )?;
+    cs.enforce(
+      || "entry_addr == table_addr + 2 * index",
+      |lc| lc + table_addr.get_variable() + (F::from(2), index.get_variable()),
+      |lc| lc + CS::one(),
+      |lc| lc + entry_addr.get_variable(),
+    );
+    Ok(entry_addr)
+  }
+
   /// # Unreacable instruction
   ///
-  /// Basically a no-op instruction.
+  /// Basically a no-op instruction, which also pads the execution trace to a multiple of the step
+  /// size. A nop must leave the zkVM untouched, so every memory op of the step has to write back
+  /// the (addr, val) it read; otherwise a padding step could write arbitrary values to memory.
+  ///
+  /// The pc & sp are not part of the step state, so the memory ops are the only state to check.
   fn visit_unreachable<CS, F>(
     &self,
     mut cs: CS,
@@ -350,7 +660,12 @@ impl WASMTransitionCircuit {
     CS: ConstraintSystem<F>,
   {
     let J: u64 = { Instr::Unreachable }.index_j();
-    let _ = self.switch(&mut cs, J, switches)?;
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    for (i, (rs, ws)) in self.RS.iter().zip_eq(self.WS.iter()).enumerate() {
+      let (addr, val, _) = Self::alloc_avt(cs.namespace(|| format!("RS[{i}]")), rs, switch)?;
+      Self::write(cs.namespace(|| format!("WS[{i}]")), &addr, &val, ws, switch)?;
+    }
     Ok(())
   }
 
@@ -480,30 +795,39 @@ impl WASMTransitionCircuit {
   }
 
   /// # Instr::Br
+  ///
+  /// Branch to `pc + branch_offset`. Returns the switched target pc & the switch, see
+  /// [`Self::visit_br_table`].
   fn visit_br<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
-  ) -> Result<(), SynthesisError>
+    step_pc: &AllocatedNum<F>,
+  ) -> Result<(AllocatedNum<F>, AllocatedNum<F>), SynthesisError>
   where
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
     let J: u64 = { Instr::Br(BranchOffset::uninit()) }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
+    let switch_var = switches
+      .last()
+      .cloned()
+      .ok_or(SynthesisError::AssignmentMissing)?;
 
     let pc = Self::alloc_num(&mut cs, || "pc", || Ok(F::from(self.vm.pc as u64)), switch)?;
+    Self::bind_pc(cs.namespace(|| "bind pc"), &pc, step_pc, &switch_var);
 
     let branch_offset = Self::alloc_num(
       &mut cs,
       || "branch_offset",
-      || Ok(F::from(self.vm.I)),
+      || Ok(self.branch_offset()),
       switch,
     )?;
 
-    let _branch_pc = add(cs.namespace(|| "pc + branch_offset"), &pc, &branch_offset)?;
+    let branch_pc = add(cs.namespace(|| "pc + branch_offset"), &pc, &branch_offset)?;
 
-    Ok(())
+    Ok((branch_pc, switch_var))
   }
 
   /// # Instr::BrIfEqz
@@ -511,23 +835,30 @@ impl WASMTransitionCircuit {
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
-  ) -> Result<(), SynthesisError>
+    step_pc: &AllocatedNum<F>,
+  ) -> Result<(AllocatedNum<F>, AllocatedNum<F>), SynthesisError>
   where
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
     let J: u64 = { Instr::BrIfEqz(BranchOffset::uninit()) }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
+    let switch_var = switches
+      .last()
+      .cloned()
+      .ok_or(SynthesisError::AssignmentMissing)?;
 
-    let one = alloc_one(cs.namespace(|| "one"));
+    // switched, so that the selected pc is 0 when the switch is off
+    let one = Self::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
 
     let pc = Self::alloc_num(&mut cs, || "pc", || Ok(F::from(self.vm.pc as u64)), switch)?;
+    Self::bind_pc(cs.namespace(|| "bind pc"), &pc, step_pc, &switch_var);
     let next_pc = add(cs.namespace(|| "pc + 1"), &pc, &one)?;
 
     let branch_offset = Self::alloc_num(
       &mut cs,
       || "branch_offset",
-      || Ok(F::from(self.vm.I)),
+      || Ok(self.branch_offset()),
       switch,
     )?;
 
@@ -547,14 +878,14 @@ impl WASMTransitionCircuit {
     // if condtion == 0 then new_pc = branch_pc else new_pc = next_pc
     //
     // In other words if condition_eqz is true then new_pc = branch_pc else new_pc = next_pc
-    let _new_pc = conditionally_select(
+    let new_pc = conditionally_select(
       cs.namespace(|| "new_pc"),
       &branch_pc,
       &next_pc,
       &condition_eqz,
-    )?; // TODO: constrain pc
+    )?;
 
-    Ok(())
+    Ok((new_pc, switch_var))
   }
 
   /// # Instr::BrIfNez
@@ -562,23 +893,30 @@ impl WASMTransitionCircuit {
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
-  ) -> Result<(), SynthesisError>
+    step_pc: &AllocatedNum<F>,
+  ) -> Result<(AllocatedNum<F>, AllocatedNum<F>), SynthesisError>
   where
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
     let J: u64 = { Instr::BrIfNez(BranchOffset::uninit()) }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
+    let switch_var = switches
+      .last()
+      .cloned()
+      .ok_or(SynthesisError::AssignmentMissing)?;
 
-    let one = alloc_one(cs.namespace(|| "one"));
+    // switched, so that the selected pc is 0 when the switch is off
+    let one = Self::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
 
     let pc = Self::alloc_num(&mut cs, || "pc", || Ok(F::from(self.vm.pc as u64)), switch)?;
+    Self::bind_pc(cs.namespace(|| "bind pc"), &pc, step_pc, &switch_var);
     let next_pc = add(cs.namespace(|| "pc + 1"), &pc, &one)?;
 
     let branch_offset = Self::alloc_num(
       &mut cs,
       || "branch_offset",
-      || Ok(F::from(self.vm.I)),
+      || Ok(self.branch_offset()),
       switch,
     )?;
 
@@ -598,14 +936,14 @@ impl WASMTransitionCircuit {
     // if condtion == 0 then new_pc = next_pc  else  new_pc = branch_pc
     //
     // In other words if condition_eqz is true then new_pc = next_pc else new_pc = branch_pc
-    let _new_pc = conditionally_select(
+    let new_pc = conditionally_select(
       cs.namespace(|| "new_pc"),
       &next_pc,
       &branch_pc,
       &condition_eqz,
-    )?; // TODO: constrain pc
+    )?;
 
-    Ok(())
+    Ok((new_pc, switch_var))
   }
 
   /// # BrTable
@@ -624,72 +962,312 @@ impl WASMTransitionCircuit {
   }
 
   /// # BrTable
+  ///
+  /// Pop the index and branch to the target it selects, or to the default target (the last one)
+  /// if the index is out of range: `target_pc = pc + 2 * min(index, len - 1) + 1`, as each target
+  /// takes two instruction words after the `br_table` itself. Returns the switched `target_pc` &
+  /// the switch, which bind the pc of the next step, see [`Self::branch_target`].
+  ///
+  /// # Note
+  ///
+  /// The number of targets `len` is an immediate of the opcode, which the step takes from the
+  /// trace like the branch offsets of the other branch opcodes.
   fn visit_br_table<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
-  ) -> Result<(), SynthesisError>
+    step_pc: &AllocatedNum<F>,
+  ) -> Result<(AllocatedNum<F>, AllocatedNum<F>), SynthesisError>
   where
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
     let J: u64 = { Instr::BrTable(BranchTableTargets::try_from(0).unwrap()) }.index_j();
-    let _ = self.switch(&mut cs, J, switches)?;
-    Ok(())
+    let switch = self.switch(&mut cs, J, switches)?;
+    let switch_var = switches
+      .last()
+      .cloned()
+      .ok_or(SynthesisError::AssignmentMissing)?;
+
+    let pc = Self::alloc_num(&mut cs, || "pc", || Ok(F::from(self.vm.pc as u64)), switch)?;
+    Self::bind_pc(cs.namespace(|| "bind pc"), &pc, step_pc, &switch_var);
+
+    // pop the index off the stack
+    let last = Self::alloc_num(
+      &mut cs,
+      || "last",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+    let value = Self::read(cs.namespace(|| "index"), &last, &self.RS[0], switch)?;
+
+    // The index is the i32 in the low 32 bits of the popped value
+    let (bits, packed) = alloc_le_bits(
+      cs.namespace(|| "value bits"),
+      self.RS[0].1 as u128,
+      64,
+      switch,
+    )?;
+    cs.enforce(
+      || "packed == value",
+      |lc| lc + packed.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + value.get_variable(),
+    );
+    let index = pack::<F>(&bits[..32]);
+
+    // The last target is the default one
+    let max_index = self.vm.I.saturating_sub(1);
+    let index_u64 = self.RS[0].1 as u32 as u64;
+    let max_index_num =
+      Self::alloc_num(&mut cs, || "max_index", || Ok(F::from(max_index)), switch)?;
+
+    // out_of_range is true iff index > max_index: the 32-bit d is index - max_index - 1 if it
+    // is, and max_index - index otherwise, i.e.
+    //
+    // out_of_range * (2 * (index - max_index) - 1) == d - max_index + index
+    let out_of_range = Self::alloc_bit(
+      &mut cs,
+      || "out_of_range",
+      Some(index_u64 > max_index),
+      switch,
+    )?;
+    let d = if index_u64 > max_index {
+      index_u64 - max_index - 1
+    } else {
+      max_index - index_u64
+    };
+    let (d_bits, _) = alloc_le_bits(cs.namespace(|| "d bits"), d as u128, 32, switch)?;
+    cs.enforce(
+      || "out_of_range * (2 * (index - max_index) - 1) == d - max_index + index",
+      |lc| lc + out_of_range.get_variable(),
+      |lc| lc + &index + &index + (-F::from(2), max_index_num.get_variable()) - CS::one(),
+      |lc| lc + &pack(&d_bits) - max_index_num.get_variable() + &index,
+    );
+
+    // selected = out_of_range ? max_index : index
+    let selected = Self::alloc_num(
+      &mut cs,
+      || "selected",
+      || Ok(F::from(index_u64.min(max_index))),
+      switch,
+    )?;
+    cs.enforce(
+      || "out_of_range * (max_index - index) == selected - index",
+      |lc| lc + out_of_range.get_variable(),
+      |lc| lc + max_index_num.get_variable() - &index,
+      |lc| lc + selected.get_variable() - &index,
+    );
+
+    // Every target takes two instruction words: target_pc = pc + 2 * selected + 1
+    let target_pc = Self::alloc_num(&mut cs, || "target_pc", || Ok(F::from(self.vm.Z)), switch)?;
+    cs.enforce(
+      || "target_pc == pc + 2 * selected + switch",
+      |lc| {
+        lc + pc.get_variable() + (F::from(2), selected.get_variable()) + switch_var.get_variable()
+      },
+      |lc| lc + CS::one(),
+      |lc| lc + target_pc.get_variable(),
+    );
+
+    Ok((target_pc, switch_var))
   }
 
   /// # drop_keep
   ///
-  /// Read the keep value at `pre_sp - keep` and write it to `pre_sp - drop - keep`
+  /// Read the keep values at `pre_sp - keep` and write them to `pre_sp - drop - keep`, for the
+  /// `vm.Q` positions `keep` from `vm.P` on
+  ///
+  /// # Note
+  ///
+  /// A drop keep step keeps up to [`MAX_KEEP_PER_STEP`] values, as many as its memory ops allow:
+  /// each keep value takes a read (`RS[2 * slot]`) and a write (`WS[2 * slot + 1]`). The active
+  /// slots are the first `vm.Q` ones, so a drop keep can not skip a keep value in the middle of
+  /// its chunk. Like `drop` & the first keep position `vm.P`, `vm.Q` is an immediate the step takes
+  /// from the trace: the number of values returned is the one the results are checked against, see
+  /// [`crate::wasm_snark::ZKWASMInstance::return_values`].
+  ///
+  /// The last keep value is also carried forward as the public stack top: `z_out = z_in + active
+  /// * (keep_val - z_in)` for each slot. Since every function return is traced as drop keeps, the
+  /// last kept value of the trace is the first value returned by the invoked function.
+  ///
+  /// The values kept by consecutive drop keeps are hashed into `kept`, which any other opcode
+  /// resets: `kept' = switch * Hash(...Hash(kept, keep_val_0)..., keep_val_n)` over the active
+  /// slots. The drop keeps of a return are traced right before it, from the top of the stack
+  /// down, so at the return `kept` commits to its results.
   fn drop_keep<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
-  ) -> Result<(), SynthesisError>
+    stack_top: &AllocatedNum<F>,
+    kept: &AllocatedNum<F>,
+  ) -> Result<(AllocatedNum<F>, AllocatedNum<F>), SynthesisError>
   where
-    F: PrimeField,
+    F: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
     CS: ConstraintSystem<F>,
   {
     let J: u64 = { Instr::DropKeep }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
+    let switch_var = switches
+      .last()
+      .cloned()
+      .ok_or(SynthesisError::AssignmentMissing)?;
 
     let drop = self.vm.I;
-    let keep = self.vm.P;
     let pre_sp_u64 = self.vm.pre_sp as u64;
+    let slots = MAX_KEEP_PER_STEP.min(self.RS.len() / 2);
+
+    // The number of active slots, 0 but for a drop keep
+    let count = Self::alloc_num(&mut cs, || "count", || Ok(F::from(self.vm.Q)), switch)?;
+    let mut actives: Vec<AllocatedBit> = Vec::with_capacity(slots);
+
+    let mut new_stack_top = stack_top.clone();
+    let mut chain = kept.clone();
+    for slot in 0..slots {
+      let mut cs = cs.namespace(|| format!("slot {slot}"));
+      let keep = self.vm.P.wrapping_add(slot as u64);
+      let slot_switch = if slot < self.vm.Q as usize {
+        switch
+      } else {
+        F::ZERO
+      };
+
+      // Only a drop keep activates a slot
+      let active = Self::alloc_bit(&mut cs, || "active", Some(true), slot_switch)?;
+      cs.enforce(
+        || "active * (1 - switch) == 0",
+        |lc| lc + active.get_variable(),
+        |lc| lc + CS::one() - switch_var.get_variable(),
+        |lc| lc,
+      );
+      if let Some(prev) = actives.last() {
+        cs.enforce(
+          || "active * (1 - prev_active) == 0",
+          |lc| lc + active.get_variable(),
+          |lc| lc + CS::one() - prev.get_variable(),
+          |lc| lc,
+        );
+      }
+      actives.push(active.clone());
+      let active = Boolean::from(active);
+
+      // keep value address
+      let read_addr = Self::alloc_num(
+        &mut cs,
+        || "read_addr",
+        || {
+          let read_addr_u64 = pre_sp_u64.wrapping_sub(keep);
+          Ok(F::from(read_addr_u64))
+        },
+        slot_switch,
+      )?;
+      let read_val = Self::read(
+        cs.namespace(|| "read val"),
+        &read_addr,
+        &self.RS[2 * slot],
+        slot_switch,
+      )?;
+
+      // write value address for keep value
+      let write_addr = Self::alloc_num(
+        &mut cs,
+        || "write addr",
+        || {
+          let write_addr_u64 = pre_sp_u64.wrapping_sub(drop.wrapping_add(keep));
+          Ok(F::from(write_addr_u64))
+        },
+        slot_switch,
+      )?;
+
+      // write keep value to new write address
+      Self::write(
+        cs.namespace(|| "drop keep write"),
+        &write_addr,
+        &read_val,
+        &self.WS[2 * slot + 1],
+        slot_switch,
+      )?;
+
+      // update the public stack top with the keep value
+      new_stack_top = conditionally_select(
+        cs.namespace(|| "new stack top"),
+        &read_val,
+        &new_stack_top,
+        &active,
+      )?;
+
+      // chain' = active ? Hash(chain, keep_val) : chain
+      let hash = hash(
+        cs.namespace(|| "Hash(chain, read_val)"),
+        &[chain.clone(), read_val],
+      )?;
+      chain = conditionally_select(cs.namespace(|| "chain"), &hash, &chain, &active)?;
+    }
 
-    // keep value address
-    let read_addr = Self::alloc_num(
-      &mut cs,
-      || "read_addr",
-      || {
-        let read_addr_u64 = pre_sp_u64 - keep;
-        Ok(F::from(read_addr_u64))
+    cs.enforce(
+      || "sum(active) == count",
+      |lc| {
+        actives
+          .iter()
+          .fold(lc, |lc, active| lc + active.get_variable())
       },
-      switch,
-    )?;
-    let read_val = Self::read(cs.namespace(|| "read val"), &read_addr, &self.RS[0], switch)?;
+      |lc| lc + CS::one(),
+      |lc| lc + count.get_variable(),
+    );
 
-    // write value address for keep value
-    let write_addr = Self::alloc_num(
-      &mut cs,
-      || "write addr",
-      || {
-        let write_addr_u64 = pre_sp_u64 - drop - keep;
-        Ok(F::from(write_addr_u64))
-      },
-      switch,
-    )?;
+    // kept' = switch * chain
+    let new_kept = AllocatedNum::alloc(cs.namespace(|| "new kept"), || {
+      Ok(switch * chain.get_value().ok_or(SynthesisError::AssignmentMissing)?)
+    })?;
+    cs.enforce(
+      || "new_kept == switch * chain",
+      |lc| lc + switch_var.get_variable(),
+      |lc| lc + chain.get_variable(),
+      |lc| lc + new_kept.get_variable(),
+    );
 
-    // write keep value to new write address
-    Self::write(
-      cs.namespace(|| "drop keep write"),
-      &write_addr,
-      &read_val,
-      &self.WS[1],
-      switch,
-    )?;
+    Ok((new_stack_top, new_kept))
+  }
 
-    Ok(())
+  /// Latch the values kept by the drop keeps preceding a return (`Instr::Return` or
+  /// `Instr::ReturnIfNez`) as the public `returned` hash: `returned' = returned + switch * (kept -
+  /// returned)`. The last return of the trace is the one of the invoked function, so `returned`
+  /// ends up committing to its results.
+  ///
+  /// A `return_if_nez` not taken has no drop keep before it, so it latches the hash of no values.
+  fn latch_returned<CS, F>(
+    mut cs: CS,
+    return_switches: &[AllocatedNum<F>],
+    kept: &AllocatedNum<F>,
+    returned: &AllocatedNum<F>,
+  ) -> Result<AllocatedNum<F>, SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let is_return = return_switches
+      .iter()
+      .map(|switch| switch.get_value())
+      .try_fold(F::ZERO, |sum, switch| switch.map(|switch| sum + switch));
+    let new_returned = AllocatedNum::alloc(cs.namespace(|| "new returned"), || {
+      let is_return = is_return.ok_or(SynthesisError::AssignmentMissing)?;
+      let kept = kept.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+      let returned = returned
+        .get_value()
+        .ok_or(SynthesisError::AssignmentMissing)?;
+      Ok(returned + is_return * (kept - returned))
+    })?;
+    cs.enforce(
+      || "new_returned - returned == (return switches) * (kept - returned)",
+      |lc| {
+        return_switches
+          .iter()
+          .fold(lc, |lc, switch| lc + switch.get_variable())
+      },
+      |lc| lc + kept.get_variable() - returned.get_variable(),
+      |lc| lc + new_returned.get_variable() - returned.get_variable(),
+    );
+    Ok(new_returned)
   }
 
   /// # Return instruction
@@ -714,10 +1292,15 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # visit_call_internal_step
+  /// # Instr::ReturnIfNez
   ///
-  /// Performs the necessary zero-writes to stack when preparing for a call instruction.
-  fn visit_call_internal_step<CS, F>(
+  /// Pop the condition and return to the caller if it is non-zero.
+  ///
+  /// # Note
+  ///
+  /// As for the return instruction, the drop keep of a taken return is proven by the drop keep
+  /// instructions traced before it, so this circuit reads the condition and selects the next pc.
+  fn visit_return_if_nez<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -726,46 +1309,185 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::CallZeroWrite }.index_j();
+    let J: u64 = { Instr::ReturnIfNez(DropKeep::new(0, 0).unwrap()) }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
-    let write_addr = Self::alloc_num(
+
+    let one = alloc_one(cs.namespace(|| "one"));
+
+    let pc = Self::alloc_num(&mut cs, || "pc", || Ok(F::from(self.vm.pc as u64)), switch)?;
+    let next_pc = add(cs.namespace(|| "pc + 1"), &pc, &one)?;
+
+    // pc of the caller to return to
+    let return_pc = Self::alloc_num(&mut cs, || "return_pc", || Ok(F::from(self.vm.Z)), switch)?;
+
+    // addr of last value on stack
+    let last = Self::alloc_num(
       &mut cs,
-      || "write addr",
-      || Ok(F::from(self.vm.pre_sp as u64)),
-      switch,
-    )?;
-    let write_val = Self::alloc_num(&mut cs, || "write val", || Ok(F::from(self.vm.P)), switch)?;
-    Self::write(
-      cs.namespace(|| "perform write"),
-      &write_addr,
-      &write_val,
-      &self.WS[0],
+      || "last",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
       switch,
     )?;
-    Ok(())
-  }
 
-  /// # host call step
-  ///
-  /// Host calls can make changes to WASM linear memory, thus we have to account for this
-  fn visit_host_call_step<CS, F>(
-    &self,
-    mut cs: CS,
+    let condition = Self::read(cs.namespace(|| "condition"), &last, &self.RS[0], switch)?;
+    let condition_eqz = eqz_bit(cs.namespace(|| "condition == 0"), &condition)?;
+
+    // if condition == 0 then new_pc = next_pc  else  new_pc = return_pc
+    let _new_pc = conditionally_select(
+      cs.namespace(|| "new_pc"),
+      &next_pc,
+      &return_pc,
+      &condition_eqz,
+    )?; // TODO: constrain pc
+
+    Ok(())
+  }
+
+  /// # call
+  ///
+  /// Call to an imported (host) function. The host function is not executed by the zkVM, instead
+  /// the call is followed by host call stack steps which read its arguments and write its results,
+  /// and by host call steps which write the linear memory it may have changed. This circuit is
+  /// thus a no-op, but having its own J index keeps imported calls distinct from `unreachable`.
+  fn visit_call<CS, F>(
+    &self,
+    mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
   where
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::HostCallStep }.index_j();
+    let J: u64 = { Instr::Call(BCFuncIdx::from(0)) }.index_j();
+    let _ = self.switch(&mut cs, J, switches)?;
+    Ok(())
+  }
+
+  /// # call_indirect
+  ///
+  /// Pop the table index and read the signature word of the entry of the default function table
+  /// at it, see [`ISMemSizes::table_addr`]. The index must be within the table, and the signature
+  /// word must be `signature + 1` for the expected signature, so a `null` entry, whose signature
+  /// word is 0, matches none. The reference of the entry must be the one of the function called,
+  /// `vm.X`.
+  ///
+  /// As for `call`, the callee's locals are then zero-written by the call internal steps.
+  fn visit_call_indirect<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+    z_table_addr: &AllocatedNum<F>,
+    z_table_len: &AllocatedNum<F>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::CallIndirect(SignatureIdx::from(0)) }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    // pop the table index
+    let last_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+    let Y = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
+
+    // read the signature word of the entry
+    let one = Self::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+    let entry_addr = self.table_entry_addr(
+      cs.namespace(|| "entry_addr"),
+      &Y,
+      &one,
+      (z_table_addr, z_table_len),
+      switches,
+      switch,
+    )?;
+    let signature_addr = Self::alloc_num(
+      &mut cs,
+      || "signature_addr",
+      || {
+        Ok(
+          entry_addr
+            .get_value()
+            .ok_or(SynthesisError::AssignmentMissing)?
+            + F::ONE,
+        )
+      },
+      switch,
+    )?;
+    cs.enforce(
+      || "signature_addr == entry_addr + 1",
+      |lc| lc + entry_addr.get_variable() + one.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + signature_addr.get_variable(),
+    );
+    let signature_word = Self::read(
+      cs.namespace(|| "signature_word"),
+      &signature_addr,
+      &self.RS[1],
+      switch,
+    )?;
+
+    let signature = Self::alloc_num(&mut cs, || "signature", || Ok(F::from(self.vm.I)), switch)?;
+    cs.enforce(
+      || "signature_word == signature + 1",
+      |lc| lc + signature.get_variable() + one.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + signature_word.get_variable(),
+    );
+
+    // read the reference of the entry, which is the function called
+    let reference = Self::read(
+      cs.namespace(|| "reference"),
+      &entry_addr,
+      &self.RS[2],
+      switch,
+    )?;
+    let callee = Self::alloc_num(&mut cs, || "callee", || Ok(F::from(self.vm.X)), switch)?;
+    cs.enforce(
+      || "reference == callee",
+      |lc| lc + reference.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + callee.get_variable(),
+    );
+
+    Ok(())
+  }
+
+  /// # visit_call_internal_step
+  ///
+  /// Performs the necessary zero-writes to stack when preparing for a call instruction.
+  ///
+  /// The arguments of the callee are not copied: they stay at the top of the caller's stack, where
+  /// the callee's `local.get` reads them, so they are bound to the values the caller pushed by the
+  /// MCC. The locals following them must start at zero though, otherwise a prover could pass the
+  /// callee arbitrary values in its locals.
+  fn visit_call_internal_step<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::CallZeroWrite }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
     let write_addr = Self::alloc_num(
       &mut cs,
       || "write addr",
-      || Ok(F::from(self.vm.Y + self.IS_sizes.stack_len() as u64)),
+      || Ok(F::from(self.vm.pre_sp as u64)),
       switch,
     )?;
     let write_val = Self::alloc_num(&mut cs, || "write val", || Ok(F::from(self.vm.P)), switch)?;
+    cs.enforce(
+      || "write_val == 0",
+      |lc| lc + write_val.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc,
+    );
     Self::write(
       cs.namespace(|| "perform write"),
       &write_addr,
@@ -776,10 +1498,15 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # host call stack step
+  /// # host call step
   ///
-  /// Performs the necessary zero-writes to stack when preparing for a call instruction.
-  fn visit_host_call_stack_step<CS, F>(
+  /// Host calls can make changes to WASM linear memory, thus we have to account for this: each word
+  /// a host call modified is written with its new value. A host call re-entering the guest can also
+  /// set globals, which are written after the linear memory.
+  ///
+  /// A word of linear memory a host function read, e.g. the input of a precompile, is read &
+  /// written back unchanged before the call returns.
+  fn visit_host_call_step<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -788,15 +1515,42 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::HostCallStackStep }.index_j();
+    let J: u64 = { Instr::HostCallStep }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
     let write_addr = Self::alloc_num(
       &mut cs,
       || "write addr",
-      || Ok(F::from(self.vm.pre_sp as u64)),
+      || {
+        let offset = if self.vm.I == HOST_CALL_GLOBAL {
+          self.IS_sizes.stack_len() + self.IS_sizes.mem_len()
+        } else {
+          self.IS_sizes.stack_len()
+        };
+        Ok(F::from(self.vm.Y + offset as u64))
+      },
       switch,
     )?;
     let write_val = Self::alloc_num(&mut cs, || "write val", || Ok(F::from(self.vm.P)), switch)?;
+    let read_val = Self::read(
+      cs.namespace(|| "read val"),
+      &write_addr,
+      &self.RS[0],
+      switch,
+    )?;
+    let is_read = Self::alloc_bit(
+      &mut cs,
+      || "is read",
+      Some(self.vm.I == HOST_CALL_READ),
+      switch,
+    )?;
+
+    // a word the host function read is written back unchanged
+    cs.enforce(
+      || "is_read * (write_val - read_val) == 0",
+      |lc| lc + is_read.get_variable(),
+      |lc| lc + write_val.get_variable() - read_val.get_variable(),
+      |lc| lc,
+    );
     Self::write(
       cs.namespace(|| "perform write"),
       &write_addr,
@@ -807,6 +1561,117 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
+  /// # host call stack step
+  ///
+  /// Performs the necessary zero-writes to stack when preparing for a call instruction, and the
+  /// writes of the host call results to the stack.
+  ///
+  /// The arguments and results of host calls are absorbed into the public `host_io` hash, so the
+  /// verifier can check the values a host (e.g. an oracle) was called with and responded with. An
+  /// argument is read from the stack and written back unchanged.
+  fn visit_host_call_stack_step<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+    host_io: &AllocatedNum<F>,
+  ) -> Result<AllocatedNum<F>, SynthesisError>
+  where
+    F: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::HostCallStackStep }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+    let switch_var = switches
+      .last()
+      .cloned()
+      .ok_or(SynthesisError::AssignmentMissing)?;
+
+    let is_arg = Self::alloc_bit(
+      &mut cs,
+      || "is arg",
+      Some(self.vm.I == HOST_CALL_ARG),
+      switch,
+    )?;
+    let is_result = Self::alloc_bit(
+      &mut cs,
+      || "is result",
+      Some(self.vm.I == HOST_CALL_RESULT),
+      switch,
+    )?;
+    cs.enforce(
+      || "is_arg * is_result == 0",
+      |lc| lc + is_arg.get_variable(),
+      |lc| lc + is_result.get_variable(),
+      |lc| lc,
+    );
+
+    let addr = Self::alloc_num(
+      &mut cs,
+      || "addr",
+      || Ok(F::from(self.vm.pre_sp as u64)),
+      switch,
+    )?;
+    let read_val = Self::read(cs.namespace(|| "read val"), &addr, &self.RS[0], switch)?;
+    let write_val = Self::alloc_num(&mut cs, || "write val", || Ok(F::from(self.vm.P)), switch)?;
+
+    // an argument is written back unchanged
+    cs.enforce(
+      || "is_arg * (write_val - read_val) == 0",
+      |lc| lc + is_arg.get_variable(),
+      |lc| lc + write_val.get_variable() - read_val.get_variable(),
+      |lc| lc,
+    );
+    Self::write(
+      cs.namespace(|| "perform write"),
+      &addr,
+      &write_val,
+      &self.WS[0],
+      switch,
+    )?;
+
+    // host_io' = Hash(host_io, kind, val) for an argument or result, otherwise host_io' = host_io
+    let kind = AllocatedNum::alloc(cs.namespace(|| "kind"), || Ok(F::from(self.vm.I) * switch))?;
+    cs.enforce(
+      || "kind == is_arg * HOST_CALL_ARG + is_result * HOST_CALL_RESULT",
+      |lc| {
+        lc + (F::from(HOST_CALL_ARG), is_arg.get_variable())
+          + (F::from(HOST_CALL_RESULT), is_result.get_variable())
+      },
+      |lc| lc + CS::one(),
+      |lc| lc + kind.get_variable(),
+    );
+    let hash = hash(
+      cs.namespace(|| "Hash(host_io, kind, val)"),
+      &[host_io.clone(), kind, write_val],
+    )?;
+
+    let absorb_val =
+      switch * F::from((self.vm.I == HOST_CALL_ARG || self.vm.I == HOST_CALL_RESULT) as u64);
+    let absorb = AllocatedNum::alloc(cs.namespace(|| "absorb"), || Ok(absorb_val))?;
+    cs.enforce(
+      || "absorb == switch * (is_arg + is_result)",
+      |lc| lc + switch_var.get_variable(),
+      |lc| lc + is_arg.get_variable() + is_result.get_variable(),
+      |lc| lc + absorb.get_variable(),
+    );
+
+    let new_host_io = AllocatedNum::alloc(cs.namespace(|| "new host io"), || {
+      if absorb_val == F::ONE {
+        hash.get_value().ok_or(SynthesisError::AssignmentMissing)
+      } else {
+        host_io.get_value().ok_or(SynthesisError::AssignmentMissing)
+      }
+    })?;
+    cs.enforce(
+      || "new_host_io - host_io == absorb * (hash - host_io)",
+      |lc| lc + absorb.get_variable(),
+      |lc| lc + hash.get_variable() - host_io.get_variable(),
+      |lc| lc + new_host_io.get_variable() - host_io.get_variable(),
+    );
+
+    Ok(new_host_io)
+  }
+
   /// # Select
   ///
   /// if condition return X else return Y
@@ -957,6 +1822,10 @@ impl WASMTransitionCircuit {
   }
 
   /// # Store instruction
+  ///
+  /// Stores the low `size` bytes of the value at the effective address `ea`, into the words
+  /// `ea / 8` & `ea / 8 + 1` of linear memory. Only the stored bytes of the words change, so a
+  /// sub-word store keeps the surrounding bytes.
   fn visit_store<CS, F>(
     &self,
     mut cs: CS,
@@ -983,11 +1852,25 @@ impl WASMTransitionCircuit {
       || Ok(F::from((self.vm.pre_sp - 1) as u64)),
       switch,
     )?;
-    let _ = Self::read(cs.namespace(|| "val"), &val_addr, &self.RS[1], switch)?;
+    let value = Self::read(cs.namespace(|| "val"), &val_addr, &self.RS[1], switch)?;
 
     // linear mem ops
     let effective_addr = self.vm.I;
+    let ea = Self::alloc_num(&mut cs, || "ea", || Ok(F::from(effective_addr)), switch)?;
+    let window = access_window(
+      cs.namespace(|| "access window"),
+      &ea,
+      access_size(&self.vm.instr),
+      switch,
+    )?;
 
+    let stack_len = Self::alloc_num(
+      &mut cs,
+      || "stack_len",
+      || Ok(F::from(self.IS_sizes.stack_len() as u64)),
+      switch,
+    )?;
+    let one = Self::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
     let write_addr_1 = Self::alloc_num(
       &mut cs,
       || "write_addr_1",
@@ -997,6 +1880,12 @@ impl WASMTransitionCircuit {
       },
       switch,
     )?;
+    cs.enforce(
+      || "write_addr_1 = ea / 8 + stack_len",
+      |lc| lc + window.word.get_variable() + stack_len.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + write_addr_1.get_variable(),
+    );
     let write_addr_2 = Self::alloc_num(
       &mut cs,
       || "write_addr_2",
@@ -1006,10 +1895,33 @@ impl WASMTransitionCircuit {
       },
       switch,
     )?;
-    let write_val_1 =
-      Self::alloc_num(&mut cs, || "write_val_1", || Ok(F::from(self.vm.P)), switch)?;
-    let write_val_2 =
-      Self::alloc_num(&mut cs, || "write_val_2", || Ok(F::from(self.vm.Q)), switch)?;
+    cs.enforce(
+      || "write_addr_2 = write_addr_1 + 1",
+      |lc| lc + write_addr_1.get_variable() + one.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + write_addr_2.get_variable(),
+    );
+
+    // The words before the store, read by the writes
+    let block_val_1 = Self::read(
+      cs.namespace(|| "block_val_1"),
+      &write_addr_1,
+      &self.RS[2],
+      switch,
+    )?;
+    let block_val_2 = Self::read(
+      cs.namespace(|| "block_val_2"),
+      &write_addr_2,
+      &self.RS[3],
+      switch,
+    )?;
+    let [write_val_1, write_val_2] = store_words(
+      cs.namespace(|| "stored"),
+      &value,
+      [&block_val_1, &block_val_2],
+      &window,
+      switch,
+    )?;
     Self::write(
       cs.namespace(|| "store 1"),
       &write_addr_1,
@@ -1028,6 +1940,11 @@ impl WASMTransitionCircuit {
   }
 
   /// # Load instruction
+  ///
+  /// Loads the `size` bytes at the effective address `ea`, from the words `ea / 8` & `ea / 8 + 1`
+  /// of linear memory, so the loaded value is constrained from the bytes of both words even when
+  /// the load is unaligned and straddles them. Sub-word loads then zero- or sign-extend the loaded
+  /// bytes, to 32 or 64 bits depending on the opcode.
   fn visit_load<CS, F>(
     &self,
     mut cs: CS,
@@ -1051,6 +1968,21 @@ impl WASMTransitionCircuit {
 
     // linear mem ops
     let effective_addr = self.vm.I;
+    let ea = Self::alloc_num(&mut cs, || "ea", || Ok(F::from(effective_addr)), switch)?;
+    let window = access_window(
+      cs.namespace(|| "access window"),
+      &ea,
+      access_size(&self.vm.instr),
+      switch,
+    )?;
+
+    let stack_len = Self::alloc_num(
+      &mut cs,
+      || "stack_len",
+      || Ok(F::from(self.IS_sizes.stack_len() as u64)),
+      switch,
+    )?;
+    let one = Self::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
     let read_addr_1 = Self::alloc_num(
       &mut cs,
       || "read_addr_1",
@@ -1060,6 +1992,12 @@ impl WASMTransitionCircuit {
       },
       switch,
     )?;
+    cs.enforce(
+      || "read_addr_1 = ea / 8 + stack_len",
+      |lc| lc + window.word.get_variable() + stack_len.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + read_addr_1.get_variable(),
+    );
     let read_addr_2 = Self::alloc_num(
       &mut cs,
       || "read_addr_2",
@@ -1069,20 +2007,34 @@ impl WASMTransitionCircuit {
       },
       switch,
     )?;
-    let _ = Self::read(
+    cs.enforce(
+      || "read_addr_2 = read_addr_1 + 1",
+      |lc| lc + read_addr_1.get_variable() + one.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + read_addr_2.get_variable(),
+    );
+    let block_val_1 = Self::read(
       cs.namespace(|| "block_val_1"),
       &read_addr_1,
       &self.RS[1],
       switch,
     )?;
-    let _ = Self::read(
-      cs.namespace(|| "block_val_1"),
+    let block_val_2 = Self::read(
+      cs.namespace(|| "block_val_2"),
       &read_addr_2,
       &self.RS[2],
       switch,
     )?;
     let stack_write_val =
       Self::alloc_num(&mut cs, || "stack write", || Ok(F::from(self.vm.Z)), switch)?;
+    enforce_loaded(
+      cs.namespace(|| "loaded"),
+      &stack_write_val,
+      [&block_val_1, &block_val_2],
+      &window,
+      &load_extension(&self.vm.instr),
+      switch,
+    )?;
     Self::write(
       cs.namespace(|| "store 1"),
       &last,
@@ -1094,10 +2046,19 @@ impl WASMTransitionCircuit {
   }
 
   /// # memory.size
+  ///
+  /// Push the page count of linear memory, which IS tracks at [`ISMemSizes::pages_addr`] and
+  /// `memory.grow` updates. The page count read is the one carried in `z`.
+  ///
+  /// # Note
+  ///
+  /// Memory grown by a host call re-entering the guest is not tracked, so a `memory.size` after it
+  /// can not be proven.
   fn visit_memory_size<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
+    z_pages: &AllocatedNum<F>,
   ) -> Result<(), SynthesisError>
   where
     F: PrimeField,
@@ -1105,18 +2066,38 @@ impl WASMTransitionCircuit {
   {
     let J: u64 = { Instr::MemorySize }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
+    let switch_var = switches
+      .last()
+      .cloned()
+      .ok_or(SynthesisError::AssignmentMissing)?;
+
+    // Read the page count
+    let pages_addr = Self::alloc_num(
+      &mut cs,
+      || "pages addr",
+      || Ok(F::from(self.IS_sizes.pages_addr() as u64)),
+      switch,
+    )?;
+    let pages = Self::read(cs.namespace(|| "pages"), &pages_addr, &self.RS[0], switch)?;
+    cs.enforce(
+      || "switch * z_pages == pages",
+      |lc| lc + switch_var.get_variable(),
+      |lc| lc + z_pages.get_variable(),
+      |lc| lc + pages.get_variable(),
+    );
+
+    // push it on the stack
     let write_addr = Self::alloc_num(
       &mut cs,
       || "write addr",
       || Ok(F::from(self.vm.pre_sp as u64)),
       switch,
     )?;
-    let write_val = Self::alloc_num(&mut cs, || "write val", || Ok(F::from(self.vm.Y)), switch)?;
     Self::write(
       cs.namespace(|| "perform write"),
       &write_addr,
-      &write_val,
-      &self.WS[0],
+      &pages,
+      &self.WS[1],
       switch,
     )?;
     Ok(())
@@ -1124,18 +2105,28 @@ impl WASMTransitionCircuit {
 
   /// # memory.grow
   ///
-  /// This is handle via our MCC
+  /// Pop the number of pages to grow by and push the previous page count, or -1 if the grow
+  /// fails. The page count at [`ISMemSizes::pages_addr`] grows by the popped pages on success, and
+  /// is left as is on failure.
+  ///
+  /// The page count is also carried in `z`: the one read must be `z_pages`, and the grown one is
+  /// returned as the next `z_pages`, i.e. `z_out = z_in + new_pages - pages`.
   fn visit_memory_grow<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
-  ) -> Result<(), SynthesisError>
+    z_pages: &AllocatedNum<F>,
+  ) -> Result<AllocatedNum<F>, SynthesisError>
   where
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
     let J: u64 = { Instr::MemoryGrow }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
+    let switch_var = switches
+      .last()
+      .cloned()
+      .ok_or(SynthesisError::AssignmentMissing)?;
 
     // pop value from stack
     let last_addr = Self::alloc_num(
@@ -1144,18 +2135,93 @@ impl WASMTransitionCircuit {
       || Ok(F::from((self.vm.pre_sp - 1) as u64)),
       switch,
     )?;
-    let _ = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
+    let delta = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
 
-    // write result
+    // read the page count
+    let pages_addr = Self::alloc_num(
+      &mut cs,
+      || "pages addr",
+      || Ok(F::from(self.IS_sizes.pages_addr() as u64)),
+      switch,
+    )?;
+    let pages = Self::read(cs.namespace(|| "pages"), &pages_addr, &self.RS[1], switch)?;
+    cs.enforce(
+      || "switch * z_pages == pages",
+      |lc| lc + switch_var.get_variable(),
+      |lc| lc + z_pages.get_variable(),
+      |lc| lc + pages.get_variable(),
+    );
+
+    // res = failed ? -1 : pages
+    let failed = Self::alloc_bit(
+      &mut cs,
+      || "failed",
+      Some(self.vm.P == u32::MAX as u64),
+      switch,
+    )?;
     let res = Self::alloc_num(&mut cs, || "write val", || Ok(F::from(self.vm.P)), switch)?;
+    cs.enforce(
+      || "failed * (-1 - pages) == res - pages",
+      |lc| lc + failed.get_variable(),
+      |lc| lc + (F::from(u32::MAX as u64), CS::one()) - pages.get_variable(),
+      |lc| lc + res.get_variable() - pages.get_variable(),
+    );
+
+    // new_pages = failed ? pages : pages + delta
+    let new_pages = Self::alloc_num(
+      &mut cs,
+      || "new pages",
+      || {
+        let pages = self.RS[1].1;
+        Ok(F::from(if self.vm.P == u32::MAX as u64 {
+          pages
+        } else {
+          pages + self.vm.Y
+        }))
+      },
+      switch,
+    )?;
+    cs.enforce(
+      || "(1 - failed) * delta == new_pages - pages",
+      |lc| lc + CS::one() - failed.get_variable(),
+      |lc| lc + delta.get_variable(),
+      |lc| lc + new_pages.get_variable() - pages.get_variable(),
+    );
+
+    // write result
     Self::write(
       cs.namespace(|| "set memory.grow write"),
       &last_addr,
       &res,
-      &self.WS[1],
+      &self.WS[2],
       switch,
     )?;
-    Ok(())
+    Self::write(
+      cs.namespace(|| "set pages"),
+      &pages_addr,
+      &new_pages,
+      &self.WS[3],
+      switch,
+    )?;
+
+    // carry the new page count in `z`
+    let new_z_pages = AllocatedNum::alloc(cs.namespace(|| "new z pages"), || {
+      let z_pages = z_pages
+        .get_value()
+        .ok_or(SynthesisError::AssignmentMissing)?;
+      let pages = pages.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+      let new_pages = new_pages
+        .get_value()
+        .ok_or(SynthesisError::AssignmentMissing)?;
+      Ok(z_pages + new_pages - pages)
+    })?;
+    cs.enforce(
+      || "new_z_pages == z_pages + new_pages - pages",
+      |lc| lc + z_pages.get_variable() + new_pages.get_variable() - pages.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + new_z_pages.get_variable(),
+    );
+    Ok(new_z_pages)
   }
 
   /// # memory.fill
@@ -1174,8 +2240,891 @@ impl WASMTransitionCircuit {
   }
 
   /// # memory.fill step
-  fn visit_memory_fill_step<CS, F>(
-    &self,
+  ///
+  /// Write the word `X` of the range filled by `memory.fill`. The offset, value & size operands
+  /// are read from the stack, and the word written is the word read with its bytes in
+  /// `[offset, offset + size)` set to the fill byte.
+  ///
+  /// # Note
+  ///
+  /// Each step only constrains the word it writes; the steps of a fill are not linked to each
+  /// other, like the rest of the control flow between steps.
+  fn visit_memory_fill_step<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::MemoryFillStep }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    // Stack ops
+    let mut operands = Vec::with_capacity(3);
+    for (i, name) in ["offset", "value", "size"].into_iter().enumerate() {
+      let addr = Self::alloc_num(
+        &mut cs,
+        || format!("{name} addr"),
+        || Ok(F::from((self.vm.pre_sp - 3 + i) as u64)),
+        switch,
+      )?;
+      operands.push(Self::read(
+        cs.namespace(|| name),
+        &addr,
+        &self.RS[i],
+        switch,
+      )?);
+    }
+
+    // linear mem ops
+    let word = Self::alloc_num(&mut cs, || "word", || Ok(F::from(self.vm.X)), switch)?;
+    let write_addr = self.mem_addr(cs.namespace(|| "write addr"), &word, switch)?;
+    let old = Self::read(
+      cs.namespace(|| "old word"),
+      &write_addr,
+      &self.RS[3],
+      switch,
+    )?;
+    let write_val = fill_word(
+      cs.namespace(|| "fill word"),
+      &word,
+      &old,
+      &operands[0],
+      &operands[1],
+      &operands[2],
+      switch,
+    )?;
+    Self::write(
+      cs.namespace(|| "perform write"),
+      &write_addr,
+      &write_val,
+      &self.WS[3],
+      switch,
+    )?;
+    Ok(())
+  }
+
+  /// # memory.copy
+  fn visit_memory_copy<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::MemoryCopy }.index_j();
+    let _ = self.switch(&mut cs, J, switches)?;
+    Ok(())
+  }
+
+  /// # memory.copy step
+  ///
+  /// Write the word `X` of the range `memory.copy` copies to. The word is read along with the two
+  /// source words its bytes are copied from, and the word written is the word read with its bytes
+  /// in `[dst, dst + size)` set to the bytes they are copied from.
+  ///
+  /// # Note
+  ///
+  /// Overlapping copies are correct as long as the steps write the words in the order the tracer
+  /// emits them (from the last word when `dst > src`), so no source byte is overwritten before it
+  /// is copied. Like for `memory.fill`, the steps are not linked to each other.
+  fn visit_memory_copy_step<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::MemoryCopyStep }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let word = Self::alloc_num(&mut cs, || "word", || Ok(F::from(self.vm.X)), switch)?;
+    let dst = Self::alloc_num(&mut cs, || "dst", || Ok(F::from(self.vm.Q)), switch)?;
+    let src = Self::alloc_num(&mut cs, || "src", || Ok(F::from(self.vm.Y)), switch)?;
+    let size = Self::alloc_num(&mut cs, || "size", || Ok(F::from(self.vm.I)), switch)?;
+    let operands = CopyOperands {
+      dst: &dst,
+      src: &src,
+      size: &size,
+    };
+
+    // Read the source words, then read & write the destination word
+    let window = source_window(
+      cs.namespace(|| "source window"),
+      &word,
+      &operands,
+      self.IS_sizes.mem_len() as u64,
+      switch,
+    )?;
+    let lo_addr = self.mem_addr(cs.namespace(|| "lo addr"), &window.lo, switch)?;
+    let lo = Self::read(cs.namespace(|| "lo"), &lo_addr, &self.RS[0], switch)?;
+    let hi_addr = self.mem_addr(cs.namespace(|| "hi addr"), &window.hi, switch)?;
+    let hi = Self::read(cs.namespace(|| "hi"), &hi_addr, &self.RS[1], switch)?;
+    let write_addr = self.mem_addr(cs.namespace(|| "write addr"), &word, switch)?;
+    let old = Self::read(
+      cs.namespace(|| "old word"),
+      &write_addr,
+      &self.RS[2],
+      switch,
+    )?;
+    let write_val = copy_word(
+      cs.namespace(|| "copy word"),
+      &word,
+      &old,
+      [&lo, &hi],
+      &window,
+      &operands,
+      switch,
+    )?;
+    Self::write(
+      cs.namespace(|| "perform write"),
+      &write_addr,
+      &write_val,
+      &self.WS[2],
+      switch,
+    )?;
+    Ok(())
+  }
+
+  /// # memory.init
+  fn visit_memory_init<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::MemoryInit(BCDataSegmentIdx::from(0)) }.index_j();
+    let _ = self.switch(&mut cs, J, switches)?;
+    Ok(())
+  }
+
+  /// # memory.init step
+  ///
+  /// Write the word `X` of the range `memory.init` copies to, like a `memory.copy` step whose
+  /// source words are the ones of the data segment laid out from `Z` in the data segments, see
+  /// [`ISMemSizes::data_addr`]. The copied bytes must be within the length of the segment, which
+  /// is zero once it is dropped.
+  ///
+  /// # Note
+  ///
+  /// The words of a segment are followed by a zero word, so unlike for `memory.copy` the high
+  /// source word is never clamped.
+  fn visit_memory_init_step<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::MemoryInitStep }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let word = Self::alloc_num(&mut cs, || "word", || Ok(F::from(self.vm.X)), switch)?;
+    let dst = Self::alloc_num(&mut cs, || "dst", || Ok(F::from(self.vm.Q)), switch)?;
+    let src = Self::alloc_num(&mut cs, || "src", || Ok(F::from(self.vm.Y)), switch)?;
+    let size = Self::alloc_num(&mut cs, || "size", || Ok(F::from(self.vm.I)), switch)?;
+    let segment = Self::alloc_num(&mut cs, || "segment", || Ok(F::from(self.vm.Z)), switch)?;
+    let operands = CopyOperands {
+      dst: &dst,
+      src: &src,
+      size: &size,
+    };
+
+    // src + size <= len
+    let len_addr = self.segment_addr(cs.namespace(|| "len addr"), &segment, None, switch)?;
+    let len = Self::read(cs.namespace(|| "len"), &len_addr, &self.RS[0], switch)?;
+    let slack = Self::alloc_num(
+      &mut cs,
+      || "len - src - size",
+      || {
+        let len = len.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+        Ok(len - F::from(self.vm.Y) - F::from(self.vm.I))
+      },
+      switch,
+    )?;
+    cs.enforce(
+      || "slack = len - src - size",
+      |lc| lc + len.get_variable() - src.get_variable() - size.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + slack.get_variable(),
+    );
+    range_check(cs.namespace(|| "slack"), &slack, 32, switch)?;
+
+    // Read the source words, then read & write the destination word. Every word index is less
+    // than 2^(ADDR_BITS - 3), so the high word is not clamped.
+    let window = source_window(
+      cs.namespace(|| "source window"),
+      &word,
+      &operands,
+      1 << (ADDR_BITS - 3),
+      switch,
+    )?;
+    let lo_addr = self.segment_addr(
+      cs.namespace(|| "lo addr"),
+      &segment,
+      Some(&window.lo),
+      switch,
+    )?;
+    let lo = Self::read(cs.namespace(|| "lo"), &lo_addr, &self.RS[1], switch)?;
+    let hi_addr = self.segment_addr(
+      cs.namespace(|| "hi addr"),
+      &segment,
+      Some(&window.hi),
+      switch,
+    )?;
+    let hi = Self::read(cs.namespace(|| "hi"), &hi_addr, &self.RS[2], switch)?;
+    let write_addr = self.mem_addr(cs.namespace(|| "write addr"), &word, switch)?;
+    let old = Self::read(
+      cs.namespace(|| "old word"),
+      &write_addr,
+      &self.RS[3],
+      switch,
+    )?;
+    let write_val = copy_word(
+      cs.namespace(|| "copy word"),
+      &word,
+      &old,
+      [&lo, &hi],
+      &window,
+      &operands,
+      switch,
+    )?;
+    Self::write(
+      cs.namespace(|| "perform write"),
+      &write_addr,
+      &write_val,
+      &self.WS[3],
+      switch,
+    )?;
+    Ok(())
+  }
+
+  /// # data.drop
+  ///
+  /// Zero the length of the data segment laid out from `I` in the data segments, so no later
+  /// `memory.init` step copies from it.
+  fn visit_data_drop<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::DataDrop(BCDataSegmentIdx::from(0)) }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let segment = Self::alloc_num(&mut cs, || "segment", || Ok(F::from(self.vm.I)), switch)?;
+    let len_addr = self.segment_addr(cs.namespace(|| "len addr"), &segment, None, switch)?;
+    let zero = Self::alloc_num(&mut cs, || "zero", || Ok(F::ZERO), switch)?;
+    Self::write(
+      cs.namespace(|| "perform write"),
+      &len_addr,
+      &zero,
+      &self.WS[0],
+      switch,
+    )?;
+    Ok(())
+  }
+
+  /// Allocate the address in zkVM memory of the word `word` of the data segment laid out from
+  /// `segment` in the data segments, or of its length if `None`. Its words follow its length.
+  fn segment_addr<CS, F>(
+    &self,
+    mut cs: CS,
+    segment: &AllocatedNum<F>,
+    word: Option<&AllocatedNum<F>>,
+    switch: F,
+  ) -> Result<AllocatedNum<F>, SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let data_addr = Self::alloc_num(
+      &mut cs,
+      || "data_addr",
+      || Ok(F::from(self.IS_sizes.data_addr() as u64)),
+      switch,
+    )?;
+    // the words of a segment follow its length
+    let offset = match word {
+      Some(word) => Some((
+        word,
+        Self::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?,
+      )),
+      None => None,
+    };
+    let addr = Self::alloc_num(
+      &mut cs,
+      || "addr",
+      || {
+        let segment = segment
+          .get_value()
+          .ok_or(SynthesisError::AssignmentMissing)?;
+        let offset = match word {
+          Some(word) => word.get_value().ok_or(SynthesisError::AssignmentMissing)? + F::ONE,
+          None => F::ZERO,
+        };
+        Ok(F::from(self.IS_sizes.data_addr() as u64) + segment + offset)
+      },
+      switch,
+    )?;
+    cs.enforce(
+      || "addr = data addr + segment + (word + 1)",
+      |lc| {
+        let lc = lc + segment.get_variable() + data_addr.get_variable();
+        match &offset {
+          Some((word, one)) => lc + word.get_variable() + one.get_variable(),
+          None => lc,
+        }
+      },
+      |lc| lc + CS::one(),
+      |lc| lc + addr.get_variable(),
+    );
+    Ok(addr)
+  }
+
+  /// Allocate the address in zkVM memory of the word `word` of linear memory, which is laid out
+  /// after the stack
+  fn mem_addr<CS, F>(
+    &self,
+    mut cs: CS,
+    word: &AllocatedNum<F>,
+    switch: F,
+  ) -> Result<AllocatedNum<F>, SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let stack_len = Self::alloc_num(
+      &mut cs,
+      || "stack_len",
+      || Ok(F::from(self.IS_sizes.stack_len() as u64)),
+      switch,
+    )?;
+    let addr = Self::alloc_num(
+      &mut cs,
+      || "addr",
+      || {
+        let word = word.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+        Ok(word + F::from(self.IS_sizes.stack_len() as u64))
+      },
+      switch,
+    )?;
+    cs.enforce(
+      || "addr = word + stack len",
+      |lc| lc + word.get_variable() + stack_len.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + addr.get_variable(),
+    );
+    Ok(addr)
+  }
+
+  /// # table.get
+  ///
+  /// Pop the table index and push the reference of the entry of the default function table at it,
+  /// see [`ISMemSizes::table_addr`]. The index must be within the table.
+  fn visit_table_get<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+    z_table_addr: &AllocatedNum<F>,
+    z_table_len: &AllocatedNum<F>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::TableGet(BCTableIdx::from(0)) }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    // pop the table index
+    let last_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+    let Y = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
+
+    // read the reference of the entry
+    let one = Self::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+    let entry_addr = self.table_entry_addr(
+      cs.namespace(|| "entry_addr"),
+      &Y,
+      &one,
+      (z_table_addr, z_table_len),
+      switches,
+      switch,
+    )?;
+    let reference = Self::read(
+      cs.namespace(|| "reference"),
+      &entry_addr,
+      &self.RS[1],
+      switch,
+    )?;
+
+    // push it on the stack
+    Self::write(
+      cs.namespace(|| "push reference on stack"),
+      &last_addr, // pre_sp - 1
+      &reference,
+      &self.WS[2],
+      switch,
+    )?;
+
+    Ok(())
+  }
+
+  /// # table.set
+  ///
+  /// Pop a reference & the table index below it, and write the reference to the entry of the
+  /// default function table at the index, see [`ISMemSizes::table_addr`]. The index must be within
+  /// the table.
+  ///
+  /// # Note
+  ///
+  /// The signature word of the entry, which `call_indirect` checks, is set from the trace, without
+  /// a constraint binding it to the reference.
+  fn visit_table_set<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+    z_table_addr: &AllocatedNum<F>,
+    z_table_len: &AllocatedNum<F>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::TableSet(BCTableIdx::from(0)) }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    // pop the table index & the reference
+    let index_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
+      switch,
+    )?;
+    let Y = Self::read(cs.namespace(|| "Y"), &index_addr, &self.RS[0], switch)?;
+    let last_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+    let reference = Self::read(cs.namespace(|| "X"), &last_addr, &self.RS[1], switch)?;
+
+    // write the entry
+    let one = Self::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+    let entry_addr = self.table_entry_addr(
+      cs.namespace(|| "entry_addr"),
+      &Y,
+      &one,
+      (z_table_addr, z_table_len),
+      switches,
+      switch,
+    )?;
+    Self::write(
+      cs.namespace(|| "set reference"),
+      &entry_addr,
+      &reference,
+      &self.WS[2],
+      switch,
+    )?;
+    let signature_addr = Self::alloc_num(
+      &mut cs,
+      || "signature_addr",
+      || {
+        Ok(
+          entry_addr
+            .get_value()
+            .ok_or(SynthesisError::AssignmentMissing)?
+            + F::ONE,
+        )
+      },
+      switch,
+    )?;
+    cs.enforce(
+      || "signature_addr == entry_addr + 1",
+      |lc| lc + entry_addr.get_variable() + one.get_variable(),
+      |lc| lc + CS::one(),
+      |lc| lc + signature_addr.get_variable(),
+    );
+    let signature_word = Self::alloc_num(
+      &mut cs,
+      || "signature_word",
+      || Ok(F::from(self.vm.P)),
+      switch,
+    )?;
+    Self::write(
+      cs.namespace(|| "set signature word"),
+      &signature_addr,
+      &signature_word,
+      &self.WS[3],
+      switch,
+    )?;
+
+    Ok(())
+  }
+
+  /// # ref.func
+  ///
+  /// Push a reference to a function on the stack. References are opaque handles in the zkWASM
+  /// memory, the same ones stored in the function table & in reference typed locals & globals, and
+  /// a null reference is 0.
+  ///
+  /// # Note
+  ///
+  /// Like the signature word of `table.set`, the handle is set from the trace, without a
+  /// constraint binding it to the function index.
+  fn visit_ref_func<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::RefFunc(BCFuncIdx::from(0)) }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let pre_sp = Self::alloc_num(
+      &mut cs,
+      || "pre_sp",
+      || Ok(F::from(self.vm.pre_sp as u64)),
+      switch,
+    )?;
+    let reference = Self::alloc_num(&mut cs, || "reference", || Ok(F::from(self.vm.P)), switch)?;
+    Self::write(
+      cs.namespace(|| "push reference on stack"),
+      &pre_sp,
+      &reference,
+      &self.WS[0],
+      switch,
+    )?;
+
+    Ok(())
+  }
+
+  /// # Const instruction
+  ///
+  /// Push a const onto the stack
+  fn visit_const<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::I64Const32(0) }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let pre_sp = Self::alloc_num(
+      &mut cs,
+      || "pre_sp",
+      || Ok(F::from(self.vm.pre_sp as u64)),
+      switch,
+    )?;
+
+    let I = Self::alloc_num(&mut cs, || "I", || Ok(F::from(self.vm.I)), switch)?;
+
+    Self::write(
+      cs.namespace(|| "push I on stack"),
+      &pre_sp,
+      &I,
+      &self.WS[0],
+      switch,
+    )?;
+
+    Ok(())
+  }
+
+  /// # ConstRef instruction
+  ///
+  /// Push a value from the const pool onto the stack. The const pool is part of IS and is laid out
+  /// right after the page count of linear memory.
+  fn visit_const_ref<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::ConstRef(ConstRef::from(0)) }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    // Read the constant at its const pool address
+    let read_addr = Self::alloc_num(
+      &mut cs,
+      || "read_addr",
+      || Ok(F::from(self.IS_sizes.const_pool_addr() as u64 + self.vm.I)),
+      switch,
+    )?;
+    let read_val = Self::read(
+      cs.namespace(|| "read at const pool"),
+      &read_addr,
+      &self.RS[0],
+      switch,
+    )?;
+
+    // write that value to the top of the stack
+    let pre_sp = Self::alloc_num(
+      &mut cs,
+      || "pre_sp",
+      || Ok(F::from(self.vm.pre_sp as u64)),
+      switch,
+    )?;
+    Self::write(
+      cs.namespace(|| "push const on stack"),
+      &pre_sp,
+      &read_val,
+      &self.WS[1],
+      switch,
+    )?;
+
+    Ok(())
+  }
+  /// # i32.sub
+  fn visit_i32_sub<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::I32Sub }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let X_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
+      switch,
+    )?;
+
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+
+    let Z = sub32(
+      cs.namespace(|| "X - Y"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
+
+    Self::write(
+      cs.namespace(|| "push Z on stack"),
+      &X_addr, // pre_sp - 2
+      &Z,
+      &self.WS[2],
+      switch,
+    )?;
+
+    Ok(())
+  }
+
+  /// # i32.add
+  fn visit_i32_add<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::I32Add }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let X_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
+      switch,
+    )?;
+
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+
+    let Z = add32(
+      cs.namespace(|| "X + Y"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
+
+    Self::write(
+      cs.namespace(|| "push Z on stack"),
+      &X_addr, // pre_sp - 2
+      &Z,
+      &self.WS[2],
+      switch,
+    )?;
+
+    Ok(())
+  }
+
+  /// # i32.mul
+  fn visit_i32_mul<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::I32Mul }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let X_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
+      switch,
+    )?;
+
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+
+    let Z = mul32(
+      cs.namespace(|| "X * Y"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
+
+    Self::write(
+      cs.namespace(|| "push Z on stack"),
+      &X_addr, // pre_sp - 2
+      &Z,
+      &self.WS[2],
+      switch,
+    )?;
+
+    Ok(())
+  }
+
+  /// # i32.div_u, i32.rem_u
+  fn visit_i32_div_rem_u<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::I32DivU }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let X_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
+      switch,
+    )?;
+
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+
+    let (quotient, rem) = div_rem_u_32(
+      cs.namespace(|| "div_rem_u_32"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
+
+    let Z = Self::alloc_num(
+      &mut cs,
+      || "Z",
+      || match self.vm.instr {
+        Instr::I32DivU => Ok(
+          quotient
+            .get_value()
+            .ok_or(SynthesisError::AssignmentMissing)?,
+        ),
+        Instr::I32RemU => Ok(rem.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        _ => Ok(F::ZERO),
+      },
+      switch,
+    )?;
+
+    Self::write(
+      cs.namespace(|| "push Z on stack"),
+      &X_addr, // pre_sp - 2
+      &Z,
+      &self.WS[2],
+      switch,
+    )?;
+
+    Ok(())
+  }
+
+  /// # i32.div_s, i32.rem_s
+  fn visit_i32_div_rem_s<CS, F>(
+    &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
@@ -1183,105 +3132,368 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::MemoryFillStep }.index_j();
+    let J: u64 = { Instr::I32DivS }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let X_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
+      switch,
+    )?;
+
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+
+    let (quotient, rem) = div_rem_s_32(
+      cs.namespace(|| "div_rem_s_32"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
+
+    let Z = Self::alloc_num(
+      &mut cs,
+      || "Z",
+      || match self.vm.instr {
+        Instr::I32DivS => Ok(
+          quotient
+            .get_value()
+            .ok_or(SynthesisError::AssignmentMissing)?,
+        ),
+        Instr::I32RemS => Ok(rem.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        _ => Ok(F::ZERO),
+      },
+      switch,
+    )?;
+
+    Self::write(
+      cs.namespace(|| "push Z on stack"),
+      &X_addr, // pre_sp - 2
+      &Z,
+      &self.WS[2],
+      switch,
+    )?;
+
+    Ok(())
+  }
+
+  /// # i32.and, i32.xor, i32.or
+  fn visit_i32_bitops<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField + PrimeFieldBits,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::I32And }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let X_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
+      switch,
+    )?;
+
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+
+    let (and, xor, or) = bitops_32(cs.namespace(|| "bitops_32"), &X, &Y)?;
+    let Z = Self::alloc_num(
+      &mut cs,
+      || "Z",
+      || match self.vm.instr {
+        Instr::I32And => Ok(and.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32Xor => Ok(xor.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32Or => Ok(or.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        _ => Ok(F::ZERO),
+      },
+      switch,
+    )?;
+
+    Self::write(
+      cs.namespace(|| "push Z on stack"),
+      &X_addr, // pre_sp - 2
+      &Z,
+      &self.WS[2],
+      switch,
+    )?;
+
+    Ok(())
+  }
+
+  /// # i32.popcnt, i32.clz, i32.ctz
+  fn visit_i32_unary_ops<CS, F>(
+    &self,
+    mut cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField + PrimeFieldBits,
+    CS: ConstraintSystem<F>,
+  {
+    let J: u64 = { Instr::I32Popcnt }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
-    let write_addr = Self::alloc_num(
+
+    let last_addr = Self::alloc_num(
       &mut cs,
-      || "write addr",
-      || Ok(F::from(self.vm.X + self.IS_sizes.stack_len() as u64)),
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
       switch,
     )?;
-    let write_val = Self::alloc_num(&mut cs, || "write val", || Ok(F::from(self.vm.P)), switch)?;
+
+    let Y = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
+
+    let (popcnt, clz, ctz) = unary_ops_32(
+      cs.namespace(|| "unary_ops_32"),
+      &Y,
+      self.vm.Y as u32,
+      switch,
+    )?;
+
+    let Z = Self::alloc_num(
+      &mut cs,
+      || "Z",
+      || match self.vm.instr {
+        Instr::I32Popcnt => Ok(
+          popcnt
+            .get_value()
+            .ok_or(SynthesisError::AssignmentMissing)?,
+        ),
+        Instr::I32Clz => Ok(clz.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32Ctz => Ok(ctz.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        _ => Ok(F::ZERO),
+      },
+      switch,
+    )?;
+
     Self::write(
-      cs.namespace(|| "perform write"),
-      &write_addr,
-      &write_val,
-      &self.WS[0],
+      cs.namespace(|| "push Z on stack"),
+      &last_addr, // pre_sp - 1
+      &Z,
+      &self.WS[1],
       switch,
     )?;
+
     Ok(())
   }
 
-  /// # memory.copy
-  fn visit_memory_copy<CS, F>(
+  /// # i32.lt_u, i32.lt_s, i32.ge_u, i32.ge_s
+  fn visit_i32_lt_ge_s<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
   where
-    F: PrimeField,
+    F: PrimeField + PrimeFieldBits,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::MemoryCopy }.index_j();
-    let _ = self.switch(&mut cs, J, switches)?;
+    let J: u64 = { Instr::I32LtS }.index_j();
+    let switch = self.switch(&mut cs, J, switches)?;
+
+    let X_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
+      switch,
+    )?;
+
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+
+    let (lt, ge, lt_s, ge_s) = lt_ge_s_32(
+      cs.namespace(|| "lt_ge_s"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
+
+    let Z = Self::alloc_num(
+      &mut cs,
+      || "Z",
+      || match self.vm.instr {
+        Instr::I32LtU => Ok(lt.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32GeU => Ok(ge.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32LtS => Ok(lt_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32GeS => Ok(ge_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        _ => Ok(F::ZERO),
+      },
+      switch,
+    )?;
+
+    // Z is one of the comparison flags above and must be 0 or 1
+    enforce_boolean(cs.namespace(|| "Z is boolean"), &Z);
+
+    Self::write(
+      cs.namespace(|| "push Z on stack"),
+      &X_addr, // pre_sp - 2
+      &Z,
+      &self.WS[2],
+      switch,
+    )?;
+
     Ok(())
   }
 
-  /// # memory.copy step
-  fn visit_memory_copy_step<CS, F>(
+  /// # i32.le_u, i32.gt_s, i32.le_u, i32.gt_s
+  fn visit_i32_le_gt_s<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
   where
-    F: PrimeField,
+    F: PrimeField + PrimeFieldBits,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::MemoryCopyStep }.index_j();
+    let J: u64 = { Instr::I32LeS }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
-    let write_addr = Self::alloc_num(
+
+    let X_addr = Self::alloc_num(
       &mut cs,
-      || "write addr",
-      || Ok(F::from(self.vm.X + self.IS_sizes.stack_len() as u64)),
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
       switch,
     )?;
-    let write_val = Self::alloc_num(&mut cs, || "write val", || Ok(F::from(self.vm.P)), switch)?;
+
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+
+    let (le, gt, le_s, gt_s) = le_gt_s_32(
+      cs.namespace(|| "le_gt_s"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
+
+    let Z = Self::alloc_num(
+      &mut cs,
+      || "Z",
+      || match self.vm.instr {
+        Instr::I32LeU => Ok(le.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32GtU => Ok(gt.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32LeS => Ok(le_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32GtS => Ok(gt_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        _ => Ok(F::ZERO),
+      },
+      switch,
+    )?;
+
+    // Z is one of the comparison flags above and must be 0 or 1
+    enforce_boolean(cs.namespace(|| "Z is boolean"), &Z);
+
     Self::write(
-      cs.namespace(|| "perform write"),
-      &write_addr,
-      &write_val,
-      &self.WS[0],
+      cs.namespace(|| "push Z on stack"),
+      &X_addr, // pre_sp - 2
+      &Z,
+      &self.WS[2],
       switch,
     )?;
+
     Ok(())
   }
 
-  /// # Const instruction
-  ///
-  /// Push a const onto the stack
-  fn visit_const<CS, F>(
+  /// # i32.shl, i32.shr_u, i32.shr_s, i32.rotr, i32.rotl
+  fn visit_i32_shift_rotate<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
   where
-    F: PrimeField,
+    F: PrimeField + PrimeFieldBits,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64Const32(0) }.index_j();
+    let J: u64 = { Instr::I32Shl }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
-    let pre_sp = Self::alloc_num(
+    let X_addr = Self::alloc_num(
       &mut cs,
-      || "pre_sp",
-      || Ok(F::from(self.vm.pre_sp as u64)),
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
       switch,
     )?;
 
-    let I = Self::alloc_num(&mut cs, || "I", || Ok(F::from(self.vm.I)), switch)?;
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 1",
+      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
+      switch,
+    )?;
+
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+
+    let (shl, shr_u, shr_s, rotr, rotl) =
+      shift_rotate_32(cs.namespace(|| "shift_rotate_32"), &X, &Y)?;
+
+    let Z = Self::alloc_num(
+      &mut cs,
+      || "Z",
+      || match self.vm.instr {
+        Instr::I32Shl => Ok(shl.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32ShrU => Ok(shr_u.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32ShrS => Ok(shr_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32Rotr => Ok(rotr.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I32Rotl => Ok(rotl.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        _ => Ok(F::ZERO),
+      },
+      switch,
+    )?;
 
     Self::write(
-      cs.namespace(|| "push I on stack"),
-      &pre_sp,
-      &I,
-      &self.WS[0],
+      cs.namespace(|| "push Z on stack"),
+      &X_addr, // pre_sp - 2
+      &Z,
+      &self.WS[2],
       switch,
     )?;
 
     Ok(())
   }
-  /// # i32.sub
-  fn visit_i32_sub<CS, F>(
+
+  /// # i64.sub
+  fn visit_i64_sub<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -1290,7 +3502,7 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I32Sub }.index_j();
+    let J: u64 = { Instr::I64Sub }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -1311,12 +3523,12 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let Z = sub32(
+    let Z = sub64(
       cs.namespace(|| "X - Y"),
       &X,
       &Y,
-      self.vm.X as u32,
-      self.vm.Y as u32,
+      self.vm.X,
+      self.vm.Y,
       switch,
     )?;
 
@@ -1331,8 +3543,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i32.add
-  fn visit_i32_add<CS, F>(
+  /// # i64.add
+  fn visit_i64_add<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -1341,7 +3553,7 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I32Add }.index_j();
+    let J: u64 = { Instr::I64Add }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -1362,12 +3574,12 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let Z = add32(
+    let Z = add64(
       cs.namespace(|| "X + Y"),
       &X,
       &Y,
-      self.vm.X as u32,
-      self.vm.Y as u32,
+      self.vm.X,
+      self.vm.Y,
       switch,
     )?;
 
@@ -1382,8 +3594,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i32.mul
-  fn visit_i32_mul<CS, F>(
+  /// # i64.mul
+  fn visit_i64_mul<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -1392,7 +3604,7 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I32Mul }.index_j();
+    let J: u64 = { Instr::I64Mul }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -1413,12 +3625,12 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let Z = mul32(
+    let Z = mul64(
       cs.namespace(|| "X * Y"),
       &X,
       &Y,
-      self.vm.X as u32,
-      self.vm.Y as u32,
+      self.vm.X,
+      self.vm.Y,
       switch,
     )?;
 
@@ -1433,8 +3645,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i32.div_u, i32.rem_u
-  fn visit_i32_div_rem_u<CS, F>(
+  /// # i64.div_u, i64.rem_u
+  fn visit_i64_div_rem_u<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -1443,7 +3655,7 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I32DivU }.index_j();
+    let J: u64 = { Instr::I64DivU }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -1464,12 +3676,12 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let (quotient, rem) = div_rem_u_32(
-      cs.namespace(|| "div_rem_u_32"),
+    let (quotient, rem) = div_rem_u_64(
+      cs.namespace(|| "div_rem_u_64"),
       &X,
       &Y,
-      self.vm.X as u32,
-      self.vm.Y as u32,
+      self.vm.X,
+      self.vm.Y,
       switch,
     )?;
 
@@ -1477,12 +3689,12 @@ impl WASMTransitionCircuit {
       &mut cs,
       || "Z",
       || match self.vm.instr {
-        Instr::I32DivU => Ok(
+        Instr::I64DivU => Ok(
           quotient
             .get_value()
             .ok_or(SynthesisError::AssignmentMissing)?,
         ),
-        Instr::I32RemU => Ok(rem.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64RemU => Ok(rem.get_value().ok_or(SynthesisError::AssignmentMissing)?),
         _ => Ok(F::ZERO),
       },
       switch,
@@ -1499,8 +3711,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i32.div_s, i32.rem_s
-  fn visit_i32_div_rem_s<CS, F>(
+  /// # i64.div_s, i64.rem_s
+  fn visit_i64_div_rem_s<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -1509,7 +3721,7 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I32DivS }.index_j();
+    let J: u64 = { Instr::I64DivS }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -1530,12 +3742,12 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let (quotient, rem) = div_rem_s_32(
-      cs.namespace(|| "div_rem_s_32"),
+    let (quotient, rem) = div_rem_s_64(
+      cs.namespace(|| "div_rem_s_64"),
       &X,
       &Y,
-      self.vm.X as u32,
-      self.vm.Y as u32,
+      self.vm.X,
+      self.vm.Y,
       switch,
     )?;
 
@@ -1543,12 +3755,12 @@ impl WASMTransitionCircuit {
       &mut cs,
       || "Z",
       || match self.vm.instr {
-        Instr::I32DivS => Ok(
+        Instr::I64DivS => Ok(
           quotient
             .get_value()
             .ok_or(SynthesisError::AssignmentMissing)?,
         ),
-        Instr::I32RemS => Ok(rem.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64RemS => Ok(rem.get_value().ok_or(SynthesisError::AssignmentMissing)?),
         _ => Ok(F::ZERO),
       },
       switch,
@@ -1565,8 +3777,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i32.and, i32.xor, i32.or
-  fn visit_i32_bitops<CS, F>(
+  /// # i64.and, i64.xor, i64.or
+  fn visit_i64_bitops<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -1575,7 +3787,7 @@ impl WASMTransitionCircuit {
     F: PrimeField + PrimeFieldBits,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I32And }.index_j();
+    let J: u64 = { Instr::I64And }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -1596,14 +3808,14 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let (and, xor, or) = bitops_32(cs.namespace(|| "bitops_32"), &X, &Y)?;
+    let (and, xor, or) = bitops_64(cs.namespace(|| "bitops_64"), &X, &Y)?;
     let Z = Self::alloc_num(
       &mut cs,
       || "Z",
       || match self.vm.instr {
-        Instr::I32And => Ok(and.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32Xor => Ok(xor.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32Or => Ok(or.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64And => Ok(and.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64Xor => Ok(xor.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64Or => Ok(or.get_value().ok_or(SynthesisError::AssignmentMissing)?),
         _ => Ok(F::ZERO),
       },
       switch,
@@ -1620,8 +3832,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i32.popcnt, i32.clz, i32.ctz
-  fn visit_i32_unary_ops<CS, F>(
+  /// # i64.popcnt, i64.clz, i64.ctz
+  fn visit_i64_unary_ops<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -1630,7 +3842,7 @@ impl WASMTransitionCircuit {
     F: PrimeField + PrimeFieldBits,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I32Popcnt }.index_j();
+    let J: u64 = { Instr::I64Popcnt }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let last_addr = Self::alloc_num(
@@ -1642,24 +3854,19 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
 
-    let (popcnt, clz, ctz) = unary_ops_32(
-      cs.namespace(|| "unary_ops_32"),
-      &Y,
-      self.vm.Y as u32,
-      switch,
-    )?;
+    let (popcnt, clz, ctz) = unary_ops_64(cs.namespace(|| "unary_ops_64"), &Y, self.vm.Y, switch)?;
 
     let Z = Self::alloc_num(
       &mut cs,
       || "Z",
       || match self.vm.instr {
-        Instr::I32Popcnt => Ok(
+        Instr::I64Popcnt => Ok(
           popcnt
             .get_value()
             .ok_or(SynthesisError::AssignmentMissing)?,
         ),
-        Instr::I32Clz => Ok(clz.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32Ctz => Ok(ctz.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64Clz => Ok(clz.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64Ctz => Ok(ctz.get_value().ok_or(SynthesisError::AssignmentMissing)?),
         _ => Ok(F::ZERO),
       },
       switch,
@@ -1676,8 +3883,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i32.lt_u, i32.lt_s, i32.ge_u, i32.ge_s
-  fn visit_i32_lt_ge_s<CS, F>(
+  /// # i64.lt_u, i64.lt_s, i64.ge_u, i64.ge_s
+  fn visit_i64_lt_ge_s<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -1686,7 +3893,7 @@ impl WASMTransitionCircuit {
     F: PrimeField + PrimeFieldBits,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I32LtS }.index_j();
+    let J: u64 = { Instr::I64LtS }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -1707,12 +3914,12 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let (lt, ge, lt_s, ge_s) = lt_ge_s_32(
+    let (lt, ge, lt_s, ge_s) = lt_ge_s(
       cs.namespace(|| "lt_ge_s"),
       &X,
       &Y,
-      self.vm.X as u32,
-      self.vm.Y as u32,
+      self.vm.X,
+      self.vm.Y,
       switch,
     )?;
 
@@ -1720,15 +3927,18 @@ impl WASMTransitionCircuit {
       &mut cs,
       || "Z",
       || match self.vm.instr {
-        Instr::I32LtU => Ok(lt.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32GeU => Ok(ge.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32LtS => Ok(lt_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32GeS => Ok(ge_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64LtU => Ok(lt.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64GeU => Ok(ge.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64LtS => Ok(lt_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64GeS => Ok(ge_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
         _ => Ok(F::ZERO),
       },
       switch,
     )?;
 
+    // Z is one of the comparison flags above and must be 0 or 1
+    enforce_boolean(cs.namespace(|| "Z is boolean"), &Z);
+
     Self::write(
       cs.namespace(|| "push Z on stack"),
       &X_addr, // pre_sp - 2
@@ -1740,8 +3950,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i32.le_u, i32.gt_s, i32.le_u, i32.gt_s
-  fn visit_i32_le_gt_s<CS, F>(
+  /// # i64.le_u, i64.gt_s, i64.le_u, i64.gt_s
+  fn visit_i64_le_gt_s<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -1750,7 +3960,7 @@ impl WASMTransitionCircuit {
     F: PrimeField + PrimeFieldBits,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I32LeS }.index_j();
+    let J: u64 = { Instr::I64LeS }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -1771,12 +3981,12 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let (le, gt, le_s, gt_s) = le_gt_s_32(
+    let (le, gt, le_s, gt_s) = le_gt_s(
       cs.namespace(|| "le_gt_s"),
       &X,
       &Y,
-      self.vm.X as u32,
-      self.vm.Y as u32,
+      self.vm.X,
+      self.vm.Y,
       switch,
     )?;
 
@@ -1784,15 +3994,18 @@ impl WASMTransitionCircuit {
       &mut cs,
       || "Z",
       || match self.vm.instr {
-        Instr::I32LeU => Ok(le.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32GtU => Ok(gt.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32LeS => Ok(le_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32GtS => Ok(gt_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64LeU => Ok(le.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64GtU => Ok(gt.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64LeS => Ok(le_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64GtS => Ok(gt_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
         _ => Ok(F::ZERO),
       },
       switch,
     )?;
 
+    // Z is one of the comparison flags above and must be 0 or 1
+    enforce_boolean(cs.namespace(|| "Z is boolean"), &Z);
+
     Self::write(
       cs.namespace(|| "push Z on stack"),
       &X_addr, // pre_sp - 2
@@ -1804,8 +4017,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i32.shl, i32.shr_u, i32.shr_s, i32.rotr, i32.rotl
-  fn visit_i32_shift_rotate<CS, F>(
+  /// # i64.shl, i64.shr_u, i64.shr_s, i64.rotr, i64.rotl
+  fn visit_i64_shift_rotate<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -1814,7 +4027,7 @@ impl WASMTransitionCircuit {
     F: PrimeField + PrimeFieldBits,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I32Shl }.index_j();
+    let J: u64 = { Instr::I64Shl }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -1833,20 +4046,20 @@ impl WASMTransitionCircuit {
       switch,
     )?;
 
-    Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
     let (shl, shr_u, shr_s, rotr, rotl) =
-      shift_rotate_32(cs.namespace(|| "shift_rotate_32"), &X, self.vm.Y as usize)?;
+      shift_rotate_64(cs.namespace(|| "shift_rotate_64"), &X, &Y)?;
 
     let Z = Self::alloc_num(
       &mut cs,
       || "Z",
       || match self.vm.instr {
-        Instr::I32Shl => Ok(shl.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32ShrU => Ok(shr_u.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32ShrS => Ok(shr_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32Rotr => Ok(rotr.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I32Rotl => Ok(rotl.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64Shl => Ok(shl.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64ShrU => Ok(shr_u.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64ShrS => Ok(shr_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64Rotr => Ok(rotr.get_value().ok_or(SynthesisError::AssignmentMissing)?),
+        Instr::I64Rotl => Ok(rotl.get_value().ok_or(SynthesisError::AssignmentMissing)?),
         _ => Ok(F::ZERO),
       },
       switch,
@@ -1863,161 +4076,43 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i64.sub
-  fn visit_i64_sub<CS, F>(
-    &self,
-    mut cs: CS,
-    switches: &mut Vec<AllocatedNum<F>>,
-  ) -> Result<(), SynthesisError>
-  where
-    F: PrimeField,
-    CS: ConstraintSystem<F>,
-  {
-    let J: u64 = { Instr::I64Sub }.index_j();
-    let switch = self.switch(&mut cs, J, switches)?;
-
-    let X_addr = Self::alloc_num(
-      &mut cs,
-      || "pre_sp - 2",
-      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
-      switch,
-    )?;
-
-    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
-
-    let Y_addr = Self::alloc_num(
-      &mut cs,
-      || "pre_sp - 1",
-      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
-      switch,
-    )?;
-
-    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
-
-    let Z = sub64(
-      cs.namespace(|| "X - Y"),
-      &X,
-      &Y,
-      self.vm.X,
-      self.vm.Y,
-      switch,
-    )?;
-
-    Self::write(
-      cs.namespace(|| "push Z on stack"),
-      &X_addr, // pre_sp - 2
-      &Z,
-      &self.WS[2],
-      switch,
-    )?;
-
-    Ok(())
-  }
-
-  /// # i64.add
-  fn visit_i64_add<CS, F>(
-    &self,
-    mut cs: CS,
-    switches: &mut Vec<AllocatedNum<F>>,
-  ) -> Result<(), SynthesisError>
-  where
-    F: PrimeField,
-    CS: ConstraintSystem<F>,
-  {
-    let J: u64 = { Instr::I64Add }.index_j();
-    let switch = self.switch(&mut cs, J, switches)?;
-
-    let X_addr = Self::alloc_num(
-      &mut cs,
-      || "pre_sp - 2",
-      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
-      switch,
-    )?;
-
-    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
-
-    let Y_addr = Self::alloc_num(
-      &mut cs,
-      || "pre_sp - 1",
-      || Ok(F::from((self.vm.pre_sp - 1) as u64)),
-      switch,
-    )?;
-
-    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
-
-    let Z = add64(
-      cs.namespace(|| "X + Y"),
-      &X,
-      &Y,
-      self.vm.X,
-      self.vm.Y,
-      switch,
-    )?;
-
-    Self::write(
-      cs.namespace(|| "push Z on stack"),
-      &X_addr, // pre_sp - 2
-      &Z,
-      &self.WS[2],
-      switch,
-    )?;
-
-    Ok(())
-  }
-
-  /// # i64.mul
-  fn visit_i64_mul<CS, F>(
+  /// # i64.eqz, i32.eqz
+  fn visit_eqz<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
   where
-    F: PrimeField,
+    F: PrimeField + PrimeFieldBits,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64Mul }.index_j();
+    let J: u64 = { Instr::I64Eqz }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
-    let X_addr = Self::alloc_num(
-      &mut cs,
-      || "pre_sp - 2",
-      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
-      switch,
-    )?;
-
-    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
-
-    let Y_addr = Self::alloc_num(
+    let last_addr = Self::alloc_num(
       &mut cs,
       || "pre_sp - 1",
       || Ok(F::from((self.vm.pre_sp - 1) as u64)),
       switch,
     )?;
 
-    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
-
-    let Z = mul64(
-      cs.namespace(|| "X * Y"),
-      &X,
-      &Y,
-      self.vm.X,
-      self.vm.Y,
-      switch,
-    )?;
-
+    let Y = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
+
+    let Z = eqz(cs.namespace(|| "eqz"), &Y, switch)?;
+
     Self::write(
       cs.namespace(|| "push Z on stack"),
-      &X_addr, // pre_sp - 2
+      &last_addr, // pre_sp - 1
       &Z,
-      &self.WS[2],
+      &self.WS[1],
       switch,
     )?;
 
     Ok(())
   }
 
-  /// # i64.div_u, i64.rem_u
-  fn visit_i64_div_rem_u<CS, F>(
+  /// # i64.eq, i32.eq
+  fn visit_eq<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -2026,7 +4121,7 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64DivU }.index_j();
+    let J: u64 = { Instr::I64Eq }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -2047,29 +4142,7 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let (quotient, rem) = div_rem_u_64(
-      cs.namespace(|| "div_rem_u_64"),
-      &X,
-      &Y,
-      self.vm.X,
-      self.vm.Y,
-      switch,
-    )?;
-
-    let Z = Self::alloc_num(
-      &mut cs,
-      || "Z",
-      || match self.vm.instr {
-        Instr::I64DivU => Ok(
-          quotient
-            .get_value()
-            .ok_or(SynthesisError::AssignmentMissing)?,
-        ),
-        Instr::I64RemU => Ok(rem.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        _ => Ok(F::ZERO),
-      },
-      switch,
-    )?;
+    let Z = eq(cs.namespace(|| "X == Y"), &X, &Y, switch)?;
 
     Self::write(
       cs.namespace(|| "push Z on stack"),
@@ -2082,8 +4155,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i64.div_s, i64.rem_s
-  fn visit_i64_div_rem_s<CS, F>(
+  /// # i64.ne, i32.ne
+  fn visit_ne<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -2092,7 +4165,7 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64DivS }.index_j();
+    let J: u64 = { Instr::I64Ne }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -2113,29 +4186,7 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let (quotient, rem) = div_rem_s_64(
-      cs.namespace(|| "div_rem_s_64"),
-      &X,
-      &Y,
-      self.vm.X,
-      self.vm.Y,
-      switch,
-    )?;
-
-    let Z = Self::alloc_num(
-      &mut cs,
-      || "Z",
-      || match self.vm.instr {
-        Instr::I64DivS => Ok(
-          quotient
-            .get_value()
-            .ok_or(SynthesisError::AssignmentMissing)?,
-        ),
-        Instr::I64RemS => Ok(rem.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        _ => Ok(F::ZERO),
-      },
-      switch,
-    )?;
+    let Z = alu::ne(cs.namespace(|| "X != Y"), &X, &Y, switch)?;
 
     Self::write(
       cs.namespace(|| "push Z on stack"),
@@ -2148,8 +4199,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i64.and, i64.xor, i64.or
-  fn visit_i64_bitops<CS, F>(
+  /// # Unary op
+  fn visit_unary<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -2158,63 +4209,108 @@ impl WASMTransitionCircuit {
     F: PrimeField + PrimeFieldBits,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64And }.index_j();
+    let J: u64 = { Instr::F32Abs }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
-    let X_addr = Self::alloc_num(
-      &mut cs,
-      || "pre_sp - 2",
-      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
-      switch,
-    )?;
-
-    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
-
-    let Y_addr = Self::alloc_num(
+    let last_addr = Self::alloc_num(
       &mut cs,
       || "pre_sp - 1",
       || Ok(F::from((self.vm.pre_sp - 1) as u64)),
       switch,
     )?;
 
-    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+    let _ = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
 
-    let (and, xor, or) = bitops_64(cs.namespace(|| "bitops_64"), &X, &Y)?;
-    let Z = Self::alloc_num(
-      &mut cs,
-      || "Z",
-      || match self.vm.instr {
-        Instr::I64And => Ok(and.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64Xor => Ok(xor.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64Or => Ok(or.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        _ => Ok(F::ZERO),
-      },
-      switch,
-    )?;
+    let Z = Self::alloc_num(&mut cs, || "unary_op(Y)", || Ok(F::from(self.vm.Z)), switch)?;
 
     Self::write(
       cs.namespace(|| "push Z on stack"),
-      &X_addr, // pre_sp - 2
+      &last_addr, // pre_sp - 1
       &Z,
-      &self.WS[2],
+      &self.WS[1],
       switch,
     )?;
 
     Ok(())
   }
 
-  /// # i64.popcnt, i64.clz, i64.ctz
-  fn visit_i64_unary_ops<CS, F>(
+  /// # i32.trunc_sat_f32_s, i32.trunc_sat_f32_u, i32.trunc_sat_f64_s, i32.trunc_sat_f64_u,
+  /// i64.trunc_sat_f32_s, i64.trunc_sat_f32_u, i64.trunc_sat_f64_s, i64.trunc_sat_f64_u
+  ///
+  /// See [`Self::float_to_int`]
+  fn visit_trunc_sat<CS, F>(
+    &self,
+    cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let conversions = [
+      (Instr::I32TruncSatF32S, F32, true, 32),
+      (Instr::I32TruncSatF32U, F32, false, 32),
+      (Instr::I32TruncSatF64S, F64, true, 32),
+      (Instr::I32TruncSatF64U, F64, false, 32),
+      (Instr::I64TruncSatF32S, F32, true, 64),
+      (Instr::I64TruncSatF32U, F32, false, 64),
+      (Instr::I64TruncSatF64S, F64, true, 64),
+      (Instr::I64TruncSatF64U, F64, false, 64),
+    ];
+    self.float_to_int(cs, switches, conversions, false)
+  }
+
+  /// # i32.trunc_f32_s, i32.trunc_f32_u, i32.trunc_f64_s, i32.trunc_f64_u, i64.trunc_f32_s,
+  /// i64.trunc_f32_u, i64.trunc_f64_s, i64.trunc_f64_u
+  ///
+  /// Like [`Self::visit_trunc_sat`], but these trap instead of clamping: NaN & the floats out of
+  /// the range of the integer make the step unsatisfiable.
+  fn visit_trunc_float_to_int<CS, F>(
+    &self,
+    cs: CS,
+    switches: &mut Vec<AllocatedNum<F>>,
+  ) -> Result<(), SynthesisError>
+  where
+    F: PrimeField,
+    CS: ConstraintSystem<F>,
+  {
+    let conversions = [
+      (Instr::I32TruncF32S, F32, true, 32),
+      (Instr::I32TruncF32U, F32, false, 32),
+      (Instr::I32TruncF64S, F64, true, 32),
+      (Instr::I32TruncF64U, F64, false, 32),
+      (Instr::I64TruncF32S, F32, true, 64),
+      (Instr::I64TruncF32U, F32, false, 64),
+      (Instr::I64TruncF64S, F64, true, 64),
+      (Instr::I64TruncF64U, F64, false, 64),
+    ];
+    self.float_to_int(cs, switches, conversions, true)
+  }
+
+  /// Pop a float & push its conversion to an integer, for the `conversions` `(instr, format,
+  /// signed, int_bits)`, with [`trunc`] if `trapping` & [`trunc_sat`] otherwise.
+  ///
+  /// Each conversion has its own J index & switch, and is synthesized on its own copy of the
+  /// operand, as the float decomposition of one format does not hold for the other. The copy of
+  /// every conversion switched off is zero, so the prover can not convert the operand as another
+  /// one.
+  fn float_to_int<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
+    conversions: [(Instr, FloatFormat, bool, usize); 8],
+    trapping: bool,
   ) -> Result<(), SynthesisError>
   where
-    F: PrimeField + PrimeFieldBits,
+    F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64Popcnt }.index_j();
-    let switch = self.switch(&mut cs, J, switches)?;
+    let mut switch = F::ZERO;
+    let mut op_switches = Vec::with_capacity(conversions.len());
+    for (instr, ..) in conversions {
+      switch += self.switch(&mut cs, instr.index_j(), switches)?;
+      op_switches.push(switches[switches.len() - 1].clone());
+    }
 
     let last_addr = Self::alloc_num(
       &mut cs,
@@ -2225,24 +4321,75 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
 
-    let (popcnt, clz, ctz) = unary_ops_64(cs.namespace(|| "unary_ops_64"), &Y, self.vm.Y, switch)?;
+    let mut operands = Vec::with_capacity(conversions.len());
+    let mut results = Vec::with_capacity(conversions.len());
+    for ((instr, format, signed, int_bits), op_switch) in conversions.into_iter().zip(&op_switches)
+    {
+      let switch = if instr.index_j() == self.vm.J {
+        switch
+      } else {
+        F::ZERO
+      };
+      let a = Self::alloc_num(
+        &mut cs,
+        || format!("{instr:?} operand"),
+        || Ok(F::from(self.vm.Y)),
+        switch,
+      )?;
+      cs.enforce(
+        || format!("{instr:?} operand * (1 - switch) == 0"),
+        |lc| lc + a.get_variable(),
+        |lc| lc + CS::one() - op_switch.get_variable(),
+        |lc| lc,
+      );
+      let cs = cs.namespace(|| format!("{instr:?}"));
+      let res = if trapping {
+        trunc(cs, &a, self.vm.Y, format, signed, int_bits, switch)
+      } else {
+        trunc_sat(cs, &a, self.vm.Y, format, signed, int_bits, switch)
+      }?;
+      operands.push(a);
+      results.push((instr, res));
+    }
+
+    // At most one copy of the operand is non-zero, so their sum is the operand
+    cs.enforce(
+      || "Σ operands = Y",
+      |lc| {
+        operands
+          .iter()
+          .fold(lc, |lc, operand| lc + operand.get_variable())
+      },
+      |lc| lc + CS::one(),
+      |lc| lc + Y.get_variable(),
+    );
 
     let Z = Self::alloc_num(
       &mut cs,
       || "Z",
-      || match self.vm.instr {
-        Instr::I64Popcnt => Ok(
-          popcnt
-            .get_value()
-            .ok_or(SynthesisError::AssignmentMissing)?,
-        ),
-        Instr::I64Clz => Ok(clz.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64Ctz => Ok(ctz.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        _ => Ok(F::ZERO),
+      || {
+        results
+          .iter()
+          .find(|(instr, _)| *instr == self.vm.instr)
+          .map_or(Ok(F::ZERO), |(_, res)| {
+            res.get_value().ok_or(SynthesisError::AssignmentMissing)
+          })
       },
       switch,
     )?;
 
+    // The conversions of the zero operand copies are zero, so their sum is the result
+    cs.enforce(
+      || "Σ results = Z",
+      |lc| {
+        results
+          .iter()
+          .fold(lc, |lc, (_, res)| lc + res.get_variable())
+      },
+      |lc| lc + CS::one(),
+      |lc| lc + Z.get_variable(),
+    );
+
     Self::write(
       cs.namespace(|| "push Z on stack"),
       &last_addr, // pre_sp - 1
@@ -2254,145 +4401,190 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i64.lt_u, i64.lt_s, i64.ge_u, i64.ge_s
-  fn visit_i64_lt_ge_s<CS, F>(
+  /// # i64.extend_i32_s, i64.extend_i32_u
+  ///
+  /// Each extension has its own J index & switch, and the switch of i64.extend_i32_s selects the
+  /// sign extension, see [`extend_i32`].
+  fn visit_i64_extend_i32<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
   where
-    F: PrimeField + PrimeFieldBits,
+    F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64LtS }.index_j();
-    let switch = self.switch(&mut cs, J, switches)?;
-
-    let X_addr = Self::alloc_num(
-      &mut cs,
-      || "pre_sp - 2",
-      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
-      switch,
-    )?;
+    let mut switch = self.switch(&mut cs, Instr::I64ExtendI32S.index_j(), switches)?;
+    let signed = switches[switches.len() - 1].clone();
+    switch += self.switch(&mut cs, Instr::I64ExtendI32U.index_j(), switches)?;
 
-    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
-
-    let Y_addr = Self::alloc_num(
+    let last_addr = Self::alloc_num(
       &mut cs,
       || "pre_sp - 1",
       || Ok(F::from((self.vm.pre_sp - 1) as u64)),
       switch,
     )?;
 
-    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+    let Y = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
 
-    let (lt, ge, lt_s, ge_s) = lt_ge_s(
-      cs.namespace(|| "lt_ge_s"),
-      &X,
+    let Z = extend_i32(
+      cs.namespace(|| "extend_i32(Y)"),
       &Y,
-      self.vm.X,
       self.vm.Y,
-      switch,
-    )?;
-
-    let Z = Self::alloc_num(
-      &mut cs,
-      || "Z",
-      || match self.vm.instr {
-        Instr::I64LtU => Ok(lt.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64GeU => Ok(ge.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64LtS => Ok(lt_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64GeS => Ok(ge_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        _ => Ok(F::ZERO),
-      },
+      &signed,
       switch,
     )?;
 
     Self::write(
       cs.namespace(|| "push Z on stack"),
-      &X_addr, // pre_sp - 2
+      &last_addr, // pre_sp - 1
       &Z,
-      &self.WS[2],
+      &self.WS[1],
       switch,
     )?;
 
     Ok(())
   }
 
-  /// # i64.le_u, i64.gt_s, i64.le_u, i64.gt_s
-  fn visit_i64_le_gt_s<CS, F>(
+  /// # i32.wrap_i64, i32.extend8_s, i32.extend16_s, i64.extend8_s, i64.extend16_s,
+  /// i64.extend32_s
+  ///
+  /// As in [`Self::float_to_int`], each conversion has its own J index & switch, and is
+  /// synthesized on its own copy of the operand, zero unless it is switched on, since the widths it
+  /// truncates to & extends from set its constraints.
+  fn visit_conversions<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
   where
-    F: PrimeField + PrimeFieldBits,
+    F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64LeS }.index_j();
-    let switch = self.switch(&mut cs, J, switches)?;
-
-    let X_addr = Self::alloc_num(
-      &mut cs,
-      || "pre_sp - 2",
-      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
-      switch,
-    )?;
-
-    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+    // (instr, bits kept from the operand, bits of the result, sign-extend)
+    let conversions = [
+      (Instr::I32WrapI64, 32, 32, false),
+      (Instr::I32Extend8S, 8, 32, true),
+      (Instr::I32Extend16S, 16, 32, true),
+      (Instr::I64Extend8S, 8, 64, true),
+      (Instr::I64Extend16S, 16, 64, true),
+      (Instr::I64Extend32S, 32, 64, true),
+    ];
+    let mut switch = F::ZERO;
+    let mut op_switches = Vec::with_capacity(conversions.len());
+    for (instr, ..) in conversions {
+      switch += self.switch(&mut cs, instr.index_j(), switches)?;
+      op_switches.push(switches[switches.len() - 1].clone());
+    }
 
-    let Y_addr = Self::alloc_num(
+    let last_addr = Self::alloc_num(
       &mut cs,
       || "pre_sp - 1",
       || Ok(F::from((self.vm.pre_sp - 1) as u64)),
       switch,
     )?;
 
-    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+    let Y = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
 
-    let (le, gt, le_s, gt_s) = le_gt_s(
-      cs.namespace(|| "le_gt_s"),
-      &X,
-      &Y,
-      self.vm.X,
-      self.vm.Y,
-      switch,
-    )?;
+    let mut operands = Vec::with_capacity(conversions.len());
+    let mut results = Vec::with_capacity(conversions.len());
+    for ((instr, from_bits, to_bits, signed), op_switch) in
+      conversions.into_iter().zip(&op_switches)
+    {
+      let switch = if instr.index_j() == self.vm.J {
+        switch
+      } else {
+        F::ZERO
+      };
+      let a = Self::alloc_num(
+        &mut cs,
+        || format!("{instr:?} operand"),
+        || Ok(F::from(self.vm.Y)),
+        switch,
+      )?;
+      cs.enforce(
+        || format!("{instr:?} operand * (1 - switch) == 0"),
+        |lc| lc + a.get_variable(),
+        |lc| lc + CS::one() - op_switch.get_variable(),
+        |lc| lc,
+      );
+      let res = extend(
+        cs.namespace(|| format!("{instr:?}")),
+        &a,
+        self.vm.Y,
+        from_bits,
+        to_bits,
+        signed,
+        switch,
+      )?;
+      operands.push(a);
+      results.push((instr, res));
+    }
+
+    // At most one copy of the operand is non-zero, so their sum is the operand
+    cs.enforce(
+      || "Σ operands = Y",
+      |lc| {
+        operands
+          .iter()
+          .fold(lc, |lc, operand| lc + operand.get_variable())
+      },
+      |lc| lc + CS::one(),
+      |lc| lc + Y.get_variable(),
+    );
 
     let Z = Self::alloc_num(
       &mut cs,
       || "Z",
-      || match self.vm.instr {
-        Instr::I64LeU => Ok(le.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64GtU => Ok(gt.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64LeS => Ok(le_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64GtS => Ok(gt_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        _ => Ok(F::ZERO),
+      || {
+        results
+          .iter()
+          .find(|(instr, _)| *instr == self.vm.instr)
+          .map_or(Ok(F::ZERO), |(_, res)| {
+            res.get_value().ok_or(SynthesisError::AssignmentMissing)
+          })
       },
       switch,
     )?;
 
+    // The conversions of the zero operand copies are zero, so their sum is the result
+    cs.enforce(
+      || "Σ results = Z",
+      |lc| {
+        results
+          .iter()
+          .fold(lc, |lc, (_, res)| lc + res.get_variable())
+      },
+      |lc| lc + CS::one(),
+      |lc| lc + Z.get_variable(),
+    );
+
     Self::write(
       cs.namespace(|| "push Z on stack"),
-      &X_addr, // pre_sp - 2
+      &last_addr, // pre_sp - 1
       &Z,
-      &self.WS[2],
+      &self.WS[1],
       switch,
     )?;
 
     Ok(())
   }
 
-  /// # i64.shl, i64.shr_u, i64.shr_s, i64.rotr, i64.rotl
-  fn visit_i64_shift_rotate<CS, F>(
+  /// # visit_binary
+  ///
+  /// The result of the float binaries without a circuit of their own, i.e. min, max, copysign & the
+  /// comparisons, is still taken from the trace. The f32 & f64 arithmetic have their own
+  /// `visit_f32_*` & [`Self::visit_f64_arith`].
+  fn visit_binary<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
   where
-    F: PrimeField + PrimeFieldBits,
+    F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64Shl }.index_j();
+    let J: u64 = { Instr::F32Eq }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -2402,7 +4594,7 @@ impl WASMTransitionCircuit {
       switch,
     )?;
 
-    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+    let _X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
 
     let Y_addr = Self::alloc_num(
       &mut cs,
@@ -2411,24 +4603,9 @@ impl WASMTransitionCircuit {
       switch,
     )?;
 
-    Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
-
-    let (shl, shr_u, shr_s, rotr, rotl) =
-      shift_rotate_64(cs.namespace(|| "shift_rotate_64"), &X, self.vm.Y as usize)?;
+    let _Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let Z = Self::alloc_num(
-      &mut cs,
-      || "Z",
-      || match self.vm.instr {
-        Instr::I64Shl => Ok(shl.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64ShrU => Ok(shr_u.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64ShrS => Ok(shr_s.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64Rotr => Ok(rotr.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        Instr::I64Rotl => Ok(rotl.get_value().ok_or(SynthesisError::AssignmentMissing)?),
-        _ => Ok(F::ZERO),
-      },
-      switch,
-    )?;
+    let Z = Self::alloc_num(&mut cs, || "Z", || Ok(F::from(self.vm.Z)), switch)?;
 
     Self::write(
       cs.namespace(|| "push Z on stack"),
@@ -2441,43 +4618,84 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i64.eqz, i32.eqz
-  fn visit_eqz<CS, F>(
+  /// # f64.add, f64.sub, f64.mul & f64.div
+  ///
+  /// The ops share the sub-circuit of [`arith`], but each has its own J index & switch, which
+  /// selects its result: the prover can neither pick the result of another op nor of none.
+  fn visit_f64_arith<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
   where
-    F: PrimeField + PrimeFieldBits,
+    F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64Eqz }.index_j();
-    let switch = self.switch(&mut cs, J, switches)?;
+    let instrs = [Instr::F64Add, Instr::F64Sub, Instr::F64Mul, Instr::F64Div];
+    let mut switch = F::ZERO;
+    let mut is_op = Vec::with_capacity(instrs.len());
+    for instr in instrs {
+      switch += self.switch(&mut cs, instr.index_j(), switches)?;
+      is_op.push(switches[switches.len() - 1].clone());
+    }
+    let op = F64Op::ALL
+      .into_iter()
+      .zip(instrs)
+      .find_map(|(op, instr)| (instr.index_j() == self.vm.J).then_some(op));
 
-    let last_addr = Self::alloc_num(
+    let X_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
+      switch,
+    )?;
+
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
       &mut cs,
       || "pre_sp - 1",
       || Ok(F::from((self.vm.pre_sp - 1) as u64)),
       switch,
     )?;
 
-    let Y = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let Z = eqz(cs.namespace(|| "eqz"), &Y, switch)?;
+    let one = Self::alloc_num(&mut cs, || "one", || Ok(F::ONE), switch)?;
+    let res = arith(
+      cs.namespace(|| "f64 arith"),
+      &X,
+      &Y,
+      self.vm.X,
+      self.vm.Y,
+      self.vm.Z,
+      op,
+      &is_op,
+      &one,
+      switch,
+    )?;
+
+    let Z = Self::alloc_num(&mut cs, || "Z", || Ok(F::from(self.vm.Z)), switch)?;
+    cs.enforce(
+      || "Z = res",
+      |_| res,
+      |lc| lc + CS::one(),
+      |lc| lc + Z.get_variable(),
+    );
 
     Self::write(
       cs.namespace(|| "push Z on stack"),
-      &last_addr, // pre_sp - 1
+      &X_addr, // pre_sp - 2
       &Z,
-      &self.WS[1],
+      &self.WS[2],
       switch,
     )?;
 
     Ok(())
   }
 
-  /// # i64.eq, i32.eq
-  fn visit_eq<CS, F>(
+  /// # f32.add
+  fn visit_f32_add<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -2486,7 +4704,7 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64Eq }.index_j();
+    let J: u64 = { Instr::F32Add }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -2507,7 +4725,14 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let Z = eq(cs.namespace(|| "X == Y"), &X, &Y, switch)?;
+    let Z = f32_add(
+      cs.namespace(|| "X + Y"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
 
     Self::write(
       cs.namespace(|| "push Z on stack"),
@@ -2520,8 +4745,8 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # i64.ne, i32.ne
-  fn visit_ne<CS, F>(
+  /// # f32.sub
+  fn visit_f32_sub<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -2530,7 +4755,7 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::I64Ne }.index_j();
+    let J: u64 = { Instr::F32Sub }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -2551,7 +4776,14 @@ impl WASMTransitionCircuit {
 
     let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let Z = alu::ne(cs.namespace(|| "X != Y"), &X, &Y, switch)?;
+    let Z = f32_sub(
+      cs.namespace(|| "X - Y"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
 
     Self::write(
       cs.namespace(|| "push Z on stack"),
@@ -2564,43 +4796,59 @@ impl WASMTransitionCircuit {
     Ok(())
   }
 
-  /// # Unary op
-  fn visit_unary<CS, F>(
+  /// # f32.mul
+  fn visit_f32_mul<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
   ) -> Result<(), SynthesisError>
   where
-    F: PrimeField + PrimeFieldBits,
+    F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::F32Abs }.index_j();
+    let J: u64 = { Instr::F32Mul }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
-    let last_addr = Self::alloc_num(
+    let X_addr = Self::alloc_num(
+      &mut cs,
+      || "pre_sp - 2",
+      || Ok(F::from((self.vm.pre_sp - 2) as u64)),
+      switch,
+    )?;
+
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+
+    let Y_addr = Self::alloc_num(
       &mut cs,
       || "pre_sp - 1",
       || Ok(F::from((self.vm.pre_sp - 1) as u64)),
       switch,
     )?;
 
-    let _ = Self::read(cs.namespace(|| "Y"), &last_addr, &self.RS[0], switch)?;
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let Z = Self::alloc_num(&mut cs, || "unary_op(Y)", || Ok(F::from(self.vm.Z)), switch)?;
+    let Z = f32_mul(
+      cs.namespace(|| "X * Y"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
 
     Self::write(
       cs.namespace(|| "push Z on stack"),
-      &last_addr, // pre_sp - 1
+      &X_addr, // pre_sp - 2
       &Z,
-      &self.WS[1],
+      &self.WS[2],
       switch,
     )?;
 
     Ok(())
   }
 
-  /// # visit_binary
-  fn visit_binary<CS, F>(
+  /// # f32.div
+  fn visit_f32_div<CS, F>(
     &self,
     mut cs: CS,
     switches: &mut Vec<AllocatedNum<F>>,
@@ -2609,7 +4857,7 @@ impl WASMTransitionCircuit {
     F: PrimeField,
     CS: ConstraintSystem<F>,
   {
-    let J: u64 = { Instr::F32Eq }.index_j();
+    let J: u64 = { Instr::F32Div }.index_j();
     let switch = self.switch(&mut cs, J, switches)?;
 
     let X_addr = Self::alloc_num(
@@ -2619,7 +4867,7 @@ impl WASMTransitionCircuit {
       switch,
     )?;
 
-    let _X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
+    let X = Self::read(cs.namespace(|| "X"), &X_addr, &self.RS[0], switch)?;
 
     let Y_addr = Self::alloc_num(
       &mut cs,
@@ -2628,9 +4876,16 @@ impl WASMTransitionCircuit {
       switch,
     )?;
 
-    let _Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
+    let Y = Self::read(cs.namespace(|| "Y"), &Y_addr, &self.RS[1], switch)?;
 
-    let Z = Self::alloc_num(&mut cs, || "Z", || Ok(F::from(self.vm.Z)), switch)?;
+    let Z = f32_div(
+      cs.namespace(|| "X / Y"),
+      &X,
+      &Y,
+      self.vm.X as u32,
+      self.vm.Y as u32,
+      switch,
+    )?;
 
     Self::write(
       cs.namespace(|| "push Z on stack"),
@@ -2657,18 +4912,27 @@ impl WASMTransitionCircuit {
       RS,
       WS,
       IS_sizes,
+      is_padding: false,
     }
   }
+
+  /// Mark the step as padding, which the instruction count of the execution skips
+  pub fn into_padding(mut self) -> Self {
+    self.is_padding = true;
+    self
+  }
 }
 
-impl Default for WASMTransitionCircuit {
-  fn default() -> Self {
+impl WASMTransitionCircuit {
+  /// Create an empty instance of the [`WASMTransitionCircuit`] with `memory_ops_per_step` memory
+  /// ops
+  pub fn empty(memory_ops_per_step: usize) -> Self {
     Self {
       vm: WitnessVM::default(),
-      // max memory ops per recursive step is 8
-      RS: vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2],
-      WS: vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2],
+      RS: vec![(0, 0, 0); memory_ops_per_step / 2],
+      WS: vec![(0, 0, 0); memory_ops_per_step / 2],
       IS_sizes: ISMemSizes::default(),
+      is_padding: false,
     }
   }
 }
@@ -2681,10 +4945,10 @@ pub struct BatchedWasmTransitionCircuit {
 
 impl<F> StepCircuit<F> for BatchedWasmTransitionCircuit
 where
-  F: PrimeField + PrimeFieldBits,
+  F: PrimeField + PrimeFieldBits + Serialize + for<'de> Deserialize<'de>,
 {
   fn arity(&self) -> usize {
-    1
+    9
   }
 
   fn synthesize<CS: ConstraintSystem<F>>(
@@ -2712,9 +4976,9 @@ where
 
 impl BatchedWasmTransitionCircuit {
   /// Create an empty instance of [`BatchedWasmTransitionCircuit`]
-  pub fn empty(step_size: usize) -> Self {
+  pub fn empty(step_size: usize, memory_ops_per_step: usize) -> Self {
     Self {
-      circuits: vec![WASMTransitionCircuit::default(); step_size],
+      circuits: vec![WASMTransitionCircuit::empty(memory_ops_per_step); step_size],
     }
   }
 
@@ -2723,3 +4987,649 @@ impl BatchedWasmTransitionCircuit {
     Self { circuits }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::WASMTransitionCircuit;
+  use crate::{wasm_ctx::ISMemSizes, wasm_snark::MEMORY_OPS_PER_STEP};
+  use bellpepper_core::{
+    num::AllocatedNum, test_cs::TestConstraintSystem, ConstraintSystem, Index, LinearCombination,
+    SynthesisError, Variable,
+  };
+  use ff::{Field, PrimeField};
+  use nova::{nebula::rs::StepCircuit, provider::Bn256EngineIPA, traits::Engine};
+  use wasmi::{
+    BCDataSegmentIdx, BCFuncIdx, BCTableIdx, BranchTableTargets, Instruction as Instr,
+    SignatureIdx, WitnessVM,
+  };
+
+  type E = Bn256EngineIPA;
+  type F = <E as Engine>::Scalar;
+
+  /// Constraint system recording the J index of each switch the switchboard allocates, from the
+  /// namespaces of the switches
+  #[derive(Default)]
+  struct SwitchRecorder {
+    num_vars: usize,
+    switch_Js: Vec<u64>,
+  }
+
+  impl<Scalar: PrimeField> ConstraintSystem<Scalar> for SwitchRecorder {
+    type Root = Self;
+
+    fn alloc<Func, A, AR>(&mut self, _annotation: A, _f: Func) -> Result<Variable, SynthesisError>
+    where
+      Func: FnOnce() -> Result<Scalar, SynthesisError>,
+      A: FnOnce() -> AR,
+      AR: Into<String>,
+    {
+      self.num_vars += 1;
+      Ok(Variable::new_unchecked(Index::Aux(self.num_vars - 1)))
+    }
+
+    fn alloc_input<Func, A, AR>(
+      &mut self,
+      annotation: A,
+      f: Func,
+    ) -> Result<Variable, SynthesisError>
+    where
+      Func: FnOnce() -> Result<Scalar, SynthesisError>,
+      A: FnOnce() -> AR,
+      AR: Into<String>,
+    {
+      self.alloc(annotation, f)
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _annotation: A, _a: LA, _b: LB, _c: LC)
+    where
+      A: FnOnce() -> AR,
+      AR: Into<String>,
+      LA: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+      LB: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+      LC: FnOnce(LinearCombination<Scalar>) -> LinearCombination<Scalar>,
+    {
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+      NR: Into<String>,
+      N: FnOnce() -> NR,
+    {
+      if let Some(J) = name_fn().into().strip_prefix("switch ") {
+        self.switch_Js.push(J.parse().unwrap());
+      }
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+      self
+    }
+  }
+
+  /// Constraint system recording the constraints of a circuit without computing any value, as the
+  /// public parameters are set up
+  #[derive(Default)]
+  struct ShapeRecorder {
+    num_vars: usize,
+    constraints: Vec<[Vec<(Index, F)>; 3]>,
+  }
+
+  impl ConstraintSystem<F> for ShapeRecorder {
+    type Root = Self;
+
+    fn alloc<Func, A, AR>(&mut self, _annotation: A, _f: Func) -> Result<Variable, SynthesisError>
+    where
+      Func: FnOnce() -> Result<F, SynthesisError>,
+      A: FnOnce() -> AR,
+      AR: Into<String>,
+    {
+      self.num_vars += 1;
+      Ok(Variable::new_unchecked(Index::Aux(self.num_vars - 1)))
+    }
+
+    fn alloc_input<Func, A, AR>(
+      &mut self,
+      annotation: A,
+      f: Func,
+    ) -> Result<Variable, SynthesisError>
+    where
+      Func: FnOnce() -> Result<F, SynthesisError>,
+      A: FnOnce() -> AR,
+      AR: Into<String>,
+    {
+      self.alloc(annotation, f)
+    }
+
+    fn enforce<A, AR, LA, LB, LC>(&mut self, _annotation: A, a: LA, b: LB, c: LC)
+    where
+      A: FnOnce() -> AR,
+      AR: Into<String>,
+      LA: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+      LB: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+      LC: FnOnce(LinearCombination<F>) -> LinearCombination<F>,
+    {
+      let terms = |lc: LinearCombination<F>| {
+        lc.iter()
+          .map(|(var, coeff)| (var.get_unchecked(), *coeff))
+          .collect()
+      };
+      self.constraints.push([
+        terms(a(LinearCombination::zero())),
+        terms(b(LinearCombination::zero())),
+        terms(c(LinearCombination::zero())),
+      ]);
+    }
+
+    fn push_namespace<NR, N>(&mut self, _name_fn: N)
+    where
+      NR: Into<String>,
+      N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self) {}
+
+    fn get_root(&mut self) -> &mut Self::Root {
+      self
+    }
+  }
+
+  /// Allocate the public state `z` of a step, zero but for the given `(index, value)` entries
+  fn alloc_z<CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    entries: &[(usize, u64)],
+  ) -> Vec<AllocatedNum<F>> {
+    let arity = StepCircuit::<F>::arity(&WASMTransitionCircuit::empty(MEMORY_OPS_PER_STEP));
+    (0..arity)
+      .map(|i| {
+        let val = entries
+          .iter()
+          .find(|(j, _)| *j == i)
+          .map_or(0, |(_, val)| *val);
+        AllocatedNum::alloc(cs.namespace(|| format!("z{i}")), || Ok(F::from(val))).unwrap()
+      })
+      .collect()
+  }
+
+  /// Record the shape of a step
+  fn shape(circuit: WASMTransitionCircuit) -> ShapeRecorder {
+    let mut cs = ShapeRecorder::default();
+    let z = alloc_z(&mut cs, &[]);
+    circuit.synthesize(&mut cs, &z).unwrap();
+    cs
+  }
+
+  /// The public parameters are set up from [`WASMTransitionCircuit::empty`], so the constraints of
+  /// a step must not depend on its opcode nor on the memory sizes of the program: a coefficient
+  /// scaled by a switch or by a memory size would make the steps of that opcode unprovable.
+  fn assert_same_shape(instr: Instr) {
+    let empty = shape(WASMTransitionCircuit::empty(MEMORY_OPS_PER_STEP));
+    let vm = WitnessVM {
+      pre_sp: 16,
+      instr,
+      J: instr.index_j(),
+      ..Default::default()
+    };
+    let ops = vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2];
+    let IS_sizes = ISMemSizes::new(32, 64, 4, 2, 8);
+    let step = shape(WASMTransitionCircuit::new(vm, ops.clone(), ops, IS_sizes));
+    assert_eq!(step.num_vars, empty.num_vars);
+    assert!(
+      step.constraints == empty.constraints,
+      "{instr:?} changes the shape of a step"
+    );
+  }
+
+  #[test]
+  fn test_memory_copy_step_shape() {
+    assert_same_shape(Instr::MemoryCopyStep);
+  }
+
+  #[test]
+  fn test_memory_fill_step_shape() {
+    assert_same_shape(Instr::MemoryFillStep);
+  }
+
+  #[test]
+  fn test_data_segment_shapes() {
+    assert_same_shape(Instr::MemoryInitStep);
+    assert_same_shape(Instr::DataDrop(BCDataSegmentIdx::from(0)));
+  }
+
+  /// Synthesize a step from `z`, zero but for the given `(index, value)` entries
+  fn synthesize_step(
+    circuit: WASMTransitionCircuit,
+    z: &[(usize, u64)],
+  ) -> TestConstraintSystem<F> {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let z = alloc_z(&mut cs, z);
+    circuit.synthesize(&mut cs, &z).unwrap();
+    cs
+  }
+
+  /// Synthesize consecutive steps, the first from `z`, zero but for the given `(index, value)`
+  /// entries, & each next one from the `z` the previous one outputs
+  fn synthesize_steps(
+    circuits: Vec<WASMTransitionCircuit>,
+    z: &[(usize, u64)],
+  ) -> TestConstraintSystem<F> {
+    let mut cs = TestConstraintSystem::<F>::new();
+    let mut z = alloc_z(&mut cs, z);
+    for (i, circuit) in circuits.iter().enumerate() {
+      z = circuit
+        .synthesize(&mut cs.namespace(|| format!("step {i}")), &z)
+        .unwrap();
+    }
+    cs
+  }
+
+  /// A nop at `pc`
+  fn nop_step(pc: usize) -> WASMTransitionCircuit {
+    let ops = vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2];
+    let vm = WitnessVM {
+      pc,
+      ..Default::default()
+    };
+    WASMTransitionCircuit::new(vm, ops.clone(), ops, ISMemSizes::default())
+  }
+
+  /// A `br_table` at `pc` of `len` targets popping `index` & branching to `target`
+  fn br_table_step(pc: usize, len: usize, index: u64, target: usize) -> WASMTransitionCircuit {
+    let instr = Instr::BrTable(BranchTableTargets::try_from(len).unwrap());
+    let vm = WitnessVM {
+      pre_sp: 16,
+      pc,
+      instr,
+      J: instr.index_j(),
+      I: len as u64,
+      Z: target as u64,
+      ..Default::default()
+    };
+    let mut RS = vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2];
+    let WS = RS.clone();
+    RS[0] = (15, index, 0);
+    WASMTransitionCircuit::new(vm, RS, WS, ISMemSizes::default())
+  }
+
+  /// The opcode after a `br_table` is at the target it selects
+  #[test]
+  fn test_br_table_binds_next_pc() {
+    // index 1 of 3 targets at pc 10 selects the target at 10 + 2 * 1 + 1
+    let steps = |target, next| vec![br_table_step(10, 3, 1, target), nop_step(next)];
+    assert!(synthesize_steps(steps(13, 13), &[]).is_satisfied());
+    assert!(!synthesize_steps(steps(13, 15), &[]).is_satisfied());
+    assert!(!synthesize_steps(steps(15, 15), &[]).is_satisfied());
+
+    // an index out of range selects the default target, the last one
+    let steps = vec![br_table_step(10, 3, 7, 15), nop_step(15)];
+    assert!(synthesize_steps(steps, &[]).is_satisfied());
+
+    // a padding step after a branch is at its target, & the pc of the step after it is free
+    let steps = vec![
+      br_table_step(10, 3, 1, 13),
+      nop_step(0).into_padding(),
+      nop_step(0).into_padding(),
+    ];
+    assert!(synthesize_steps(steps, &[]).is_satisfied());
+
+    // the first step is at the target of the branch preceding it, e.g. in a shard
+    assert!(synthesize_steps(vec![nop_step(13)], &[(8, 14)]).is_satisfied());
+    assert!(!synthesize_steps(vec![nop_step(12)], &[(8, 14)]).is_satisfied());
+  }
+
+  /// A `br`, `br_if_eqz` or `br_if_nez` at `pc` of `offset`, popping `condition`
+  fn br_step(
+    instr: fn(BranchOffset) -> Instr,
+    pc: usize,
+    offset: i32,
+    condition: u64,
+  ) -> WASMTransitionCircuit {
+    let instr = instr(BranchOffset::from(offset));
+    let vm = WitnessVM {
+      pre_sp: 16,
+      pc,
+      instr,
+      J: instr.index_j(),
+      I: offset as u64,
+      Y: condition,
+      ..Default::default()
+    };
+    let mut RS = vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2];
+    let WS = RS.clone();
+    if !matches!(instr, Instr::Br(..)) {
+      RS[0] = (15, condition, 0);
+    }
+    WASMTransitionCircuit::new(vm, RS, WS, ISMemSizes::default())
+  }
+
+  /// The opcode after a `br` or `br_if` is at the target it selects
+  #[test]
+  fn test_br_if_binds_next_pc() {
+    let steps =
+      |instr, offset, condition, next| vec![br_step(instr, 20, offset, condition), nop_step(next)];
+
+    // a taken branch, backward as in a loop, & a branch that is not taken, each with the wrong
+    // target of the other case
+    for (instr, offset, condition, target, wrong) in [
+      (Instr::Br as fn(BranchOffset) -> Instr, -5, 0, 15, 21),
+      (Instr::Br, 3, 0, 23, 21),
+      (Instr::BrIfNez, -5, 1, 15, 21),
+      (Instr::BrIfNez, -5, 0, 21, 15),
+      (Instr::BrIfEqz, 4, 0, 24, 21),
+      (Instr::BrIfEqz, 4, 7, 21, 24),
+    ] {
+      assert!(synthesize_steps(steps(instr, offset, condition, target), &[]).is_satisfied());
+      assert!(!synthesize_steps(steps(instr, offset, condition, wrong), &[]).is_satisfied());
+      assert!(!synthesize_steps(steps(instr, offset, condition, target + 1), &[]).is_satisfied());
+    }
+  }
+
+  /// A drop keep of `drop` values keeping the top `values` of the stack
+  fn drop_keep_step(drop: u64, values: &[u64]) -> TestConstraintSystem<F> {
+    let (pre_sp, keep) = (16, values.len());
+    let vm = WitnessVM {
+      pre_sp,
+      instr: Instr::DropKeep,
+      J: Instr::DropKeep.index_j(),
+      I: drop,
+      P: 1,
+      Q: keep as u64,
+      ..Default::default()
+    };
+    let mut RS = vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2];
+    let mut WS = RS.clone();
+    for (slot, val) in values.iter().enumerate() {
+      RS[2 * slot] = (pre_sp - 1 - slot, *val, 0);
+      WS[2 * slot + 1] = (pre_sp - 1 - slot - drop as usize, *val, 1);
+    }
+    synthesize_step(
+      WASMTransitionCircuit::new(vm, RS, WS, ISMemSizes::default()),
+      &[],
+    )
+  }
+
+  /// A drop keep keeps its first `vm.Q` values, the prover can not skip one
+  #[test]
+  fn test_drop_keep_active_slots() {
+    let active = |slot: usize| format!("drop keep/slot {slot}/active/boolean");
+    assert!(drop_keep_step(2, &[7, 9]).is_satisfied());
+
+    // Both keep values are kept
+    let mut cs = drop_keep_step(2, &[7, 9]);
+    cs.set(&active(1), F::ZERO);
+    assert!(!cs.is_satisfied());
+
+    // The kept value is the first one
+    let mut cs = drop_keep_step(2, &[7]);
+    assert!(cs.is_satisfied());
+    cs.set(&active(0), F::ZERO);
+    cs.set(&active(1), F::ONE);
+    assert!(!cs.is_satisfied());
+  }
+
+  /// Synthesize a padding step with the given RS & WS, & check the constraints are satisfied
+  fn padding_step_is_satisfied(RS: Vec<(usize, u64, u64)>, WS: Vec<(usize, u64, u64)>) -> bool {
+    let circuit = WASMTransitionCircuit::new(WitnessVM::default(), RS, WS, ISMemSizes::default())
+      .into_padding();
+    synthesize_step(circuit, &[]).is_satisfied()
+  }
+
+  /// Synthesize an `f64.add` step of `x` & `y` pushing `z`
+  fn f64_add_step(x: f64, y: f64, z: f64) -> TestConstraintSystem<F> {
+    let (x, y, z) = (x.to_bits(), y.to_bits(), z.to_bits());
+    let vm = WitnessVM {
+      pre_sp: 16,
+      instr: Instr::F64Add,
+      J: Instr::F64Add.index_j(),
+      X: x,
+      Y: y,
+      Z: z,
+      ..Default::default()
+    };
+    let mut RS = vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2];
+    let mut WS = RS.clone();
+    RS[0] = (14, x, 0);
+    RS[1] = (15, y, 0);
+    WS[2] = (14, z, 1);
+    synthesize_step(
+      WASMTransitionCircuit::new(vm, RS, WS, ISMemSizes::default()),
+      &[],
+    )
+  }
+
+  /// The op of an f64 arithmetic step is selected by the switch of its J index, which the prover
+  /// can not zero to push a result of its choice
+  #[test]
+  fn test_f64_arith_op_is_switched() {
+    assert!(f64_add_step(1.5, 2.0, 3.5).is_satisfied());
+
+    let mut cs = f64_add_step(1.5, 2.0, 7.0);
+    assert!(!cs.is_satisfied());
+    let switch = format!("f64 arith/switch {}/num", Instr::F64Add.index_j());
+    cs.set(&switch, F::ZERO);
+    assert!(!cs.is_satisfied());
+  }
+
+  /// Synthesize a step of the unary `instr` popping `y` & pushing `z`
+  fn unary_step(instr: Instr, y: u64, z: u64) -> TestConstraintSystem<F> {
+    let vm = WitnessVM {
+      pre_sp: 16,
+      instr,
+      J: instr.index_j(),
+      Y: y,
+      Z: z,
+      ..Default::default()
+    };
+    let mut RS = vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2];
+    let mut WS = RS.clone();
+    RS[0] = (15, y, 0);
+    WS[1] = (15, z, 1);
+    synthesize_step(
+      WASMTransitionCircuit::new(vm, RS, WS, ISMemSizes::default()),
+      &[],
+    )
+  }
+
+  /// Switch the step from the opcode `from` to `to` of the handler in `namespace`
+  fn set_switch(cs: &mut TestConstraintSystem<F>, namespace: &str, from: Instr, to: Instr) {
+    cs.set(
+      &format!("{namespace}/switch {}/num", from.index_j()),
+      F::ZERO,
+    );
+    cs.set(&format!("{namespace}/switch {}/num", to.index_j()), F::ONE);
+  }
+
+  #[test]
+  fn test_trunc_sat_op_is_switched() {
+    let (y, z) = ((-2.5f64).to_bits(), -2i64 as u64);
+    assert!(unary_step(Instr::I64TruncSatF64S, y, z).is_satisfied());
+
+    // The unsigned conversion saturates to 0, which the signed one can not push
+    let mut cs = unary_step(Instr::I64TruncSatF64S, y, 0);
+    assert!(!cs.is_satisfied());
+    set_switch(
+      &mut cs,
+      "visit_trunc_sat",
+      Instr::I64TruncSatF64S,
+      Instr::I64TruncSatF64U,
+    );
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_trunc_op_is_switched() {
+    let (y, z) = (3.5f32.to_bits() as u64, 3);
+    assert!(unary_step(Instr::I32TruncF32U, y, z).is_satisfied());
+
+    // The f64 conversion of the bits of an f32 is 0
+    let mut cs = unary_step(Instr::I32TruncF32U, y, 0);
+    assert!(!cs.is_satisfied());
+    set_switch(
+      &mut cs,
+      "visit_trunc_float_to_int",
+      Instr::I32TruncF32U,
+      Instr::I32TruncF64U,
+    );
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_conversion_op_is_switched() {
+    let y = 0x80;
+    assert!(unary_step(Instr::I32Extend8S, y, -128i32 as u32 as u64).is_satisfied());
+
+    // The i64 extension of the byte can not be pushed as the i32 one
+    let mut cs = unary_step(Instr::I32Extend8S, y, -128i64 as u64);
+    assert!(!cs.is_satisfied());
+    set_switch(
+      &mut cs,
+      "visit_conversions",
+      Instr::I32Extend8S,
+      Instr::I64Extend8S,
+    );
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_extend_i32_op_is_switched() {
+    let y = 0x8000_0001;
+    let sign_extended = 0xFFFF_FFFF_8000_0001;
+    assert!(unary_step(Instr::I64ExtendI32S, y, sign_extended).is_satisfied());
+    assert!(unary_step(Instr::I64ExtendI32U, y, y).is_satisfied());
+
+    // The sign extension is the one of i64.extend_i32_s only
+    assert!(!unary_step(Instr::I64ExtendI32U, y, sign_extended).is_satisfied());
+    let mut cs = unary_step(Instr::I64ExtendI32S, y, y);
+    assert!(!cs.is_satisfied());
+    set_switch(
+      &mut cs,
+      "visit_i64_extend_i32",
+      Instr::I64ExtendI32S,
+      Instr::I64ExtendI32U,
+    );
+    assert!(!cs.is_satisfied());
+  }
+
+  #[test]
+  fn test_padding_step_is_nop() {
+    let RS = vec![(0, 5, 0); 4];
+    let WS = vec![(0, 5, 1); 4];
+    assert!(padding_step_is_satisfied(RS.clone(), WS.clone()));
+
+    // A padding step can not write another value
+    let mut bad_val = WS.clone();
+    bad_val[1].1 = 6;
+    assert!(!padding_step_is_satisfied(RS.clone(), bad_val));
+
+    // nor write to another address, e.g. a moved stack pointer
+    let mut bad_addr = WS;
+    bad_addr[0].0 = 1;
+    assert!(!padding_step_is_satisfied(RS, bad_addr));
+  }
+
+  /// Synthesize a `table.get` pushing the entry `index` of the table laid out in `IS_sizes`, from
+  /// `z` carrying the table layout `(table_addr, table_len)`
+  fn table_get_step(
+    index: u64,
+    IS_sizes: ISMemSizes,
+    (table_addr, table_len): (usize, usize),
+  ) -> TestConstraintSystem<F> {
+    let instr = Instr::TableGet(BCTableIdx::from(0));
+    let vm = WitnessVM {
+      pre_sp: 16,
+      instr,
+      J: instr.index_j(),
+      Y: index,
+      ..Default::default()
+    };
+    let mut RS = vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2];
+    let mut WS = RS.clone();
+    RS[0] = (15, index, 0);
+    RS[1] = (IS_sizes.table_addr() + 2 * index as usize, 7, 0);
+    WS[2] = (15, 7, 1);
+    synthesize_step(
+      WASMTransitionCircuit::new(vm, RS, WS, IS_sizes),
+      &[(6, table_addr as u64), (7, table_len as u64)],
+    )
+  }
+
+  /// The table opcodes index the table laid out in `z`, so a prover can neither grow the table to
+  /// read past its end nor move it
+  #[test]
+  fn test_table_layout_is_public() {
+    let sizes = ISMemSizes::new(32, 0, 0, 2, 0);
+    let layout = |sizes: ISMemSizes| (sizes.table_addr(), sizes.table_len());
+    assert!(table_get_step(1, sizes, layout(sizes)).is_satisfied());
+
+    let larger = ISMemSizes::new(32, 0, 0, 4, 0);
+    assert!(table_get_step(3, larger, layout(larger)).is_satisfied());
+    assert!(!table_get_step(3, larger, layout(sizes)).is_satisfied());
+
+    let moved = ISMemSizes::new(40, 0, 0, 2, 0);
+    assert!(!table_get_step(1, moved, layout(sizes)).is_satisfied());
+  }
+
+  /// Every J index an opcode can have must have exactly one switch, else the single switch
+  /// constraint can not hold for the opcodes of that index, or holds with two sub-circuits on.
+  /// Opcodes get J indices up to [`Instr::MAX_J`], so adding one without its `visit_*` fails here.
+  #[test]
+  fn test_every_J_has_one_switch() {
+    let mut cs = SwitchRecorder::default();
+    let z = alloc_z(&mut cs, &[]);
+    WASMTransitionCircuit::empty(MEMORY_OPS_PER_STEP)
+      .synthesize(&mut cs, &z)
+      .unwrap();
+
+    let mut switch_Js = cs.switch_Js;
+    switch_Js.sort_unstable();
+    assert_eq!(switch_Js, (0..=Instr::MAX_J).collect::<Vec<_>>());
+  }
+
+  /// Synthesize a `call_indirect` of the signature 3 through the entry 1 of a table of 2 entries,
+  /// holding the reference 42, to `callee`
+  fn call_indirect_step(callee: u64) -> TestConstraintSystem<F> {
+    let IS_sizes = ISMemSizes::new(32, 0, 0, 2, 0);
+    let instr = Instr::CallIndirect(SignatureIdx::from(3));
+    let vm = WitnessVM {
+      pre_sp: 16,
+      instr,
+      J: instr.index_j(),
+      I: 3,
+      X: callee,
+      Y: 1,
+      P: 4,
+      ..Default::default()
+    };
+    let entry_addr = IS_sizes.table_addr() + 2;
+    let mut RS = vec![(0, 0, 0); MEMORY_OPS_PER_STEP / 2];
+    let WS = RS.clone();
+    RS[0] = (15, 1, 0);
+    RS[1] = (entry_addr + 1, 4, 0);
+    RS[2] = (entry_addr, 42, 0);
+    let (table_addr, table_len) = (IS_sizes.table_addr(), IS_sizes.table_len());
+    synthesize_step(
+      WASMTransitionCircuit::new(vm, RS, WS, IS_sizes),
+      &[(6, table_addr as u64), (7, table_len as u64)],
+    )
+  }
+
+  /// `call_indirect` calls the function of the table entry
+  #[test]
+  fn test_call_indirect_callee() {
+    assert!(call_indirect_step(42).is_satisfied());
+    assert!(!call_indirect_step(41).is_satisfied());
+  }
+
+  /// Calls must not share the J index of `unreachable`, whose sub-circuit is a no-op: a trace
+  /// calling a function would otherwise be proven as if the call never happened.
+  #[test]
+  fn test_calls_are_not_nops() {
+    let unreachable = Instr::Unreachable.index_j();
+    let call = Instr::Call(BCFuncIdx::from(0)).index_j();
+    let call_indirect = Instr::CallIndirect(SignatureIdx::from(0)).index_j();
+    assert_ne!(call, unreachable);
+    assert_ne!(call_indirect, unreachable);
+    assert_ne!(call, call_indirect);
+  }
+}
@@ -0,0 +1,886 @@
+use std::{num::NonZeroUsize, path::PathBuf, sync::Mutex};
+
+use bellpepper_core::{num::AllocatedNum, ConstraintSystem, SynthesisError};
+use ff::{Field, PrimeField};
+use nova::{
+  nebula::rs::{PublicParams, StepCircuit},
+  provider::{ipa_pc, Bn256EngineIPA},
+  spartan,
+  traits::{snark::default_ck_hint, Dual, Engine},
+};
+
+use super::{
+  memory_image_digest, program_commitment, CircuitStats, ProofEstimate, ProvingConfig,
+  ProvingPhase, RecursiveWasmSNARK, SizeReport, StepSize, StreamingVerifier, VerifierContext,
+  WasmSNARK, ZKWASMInstance,
+};
+use crate::{
+  error::ZKWASMError,
+  utils::logging::init_logger,
+  wasm_ctx::{CommitmentHash, WASMArgsBuilder, WASMCtx, ZKWASMCtx},
+};
+
+/// Curve Cycle to prove/verify on
+type E = Bn256EngineIPA;
+type EE1 = ipa_pc::EvaluationEngine<E>;
+type EE2 = ipa_pc::EvaluationEngine<Dual<E>>;
+type S1 = spartan::batched::BatchedRelaxedR1CSSNARK<E, EE1>;
+type S2 = spartan::snark::RelaxedR1CSSNARK<Dual<E>, EE2>;
+
+fn fib_ctx(n: &str) -> Result<WASMCtx, ZKWASMError> {
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/misc/fib.wat"))?
+    .invoke("fib")
+    .func_args(vec![n.to_string()])
+    .build();
+  Ok(WASMCtx::new(wasm_args))
+}
+
+fn into_recursive(snark: WasmSNARK<E, S1, S2>) -> RecursiveWasmSNARK<E> {
+  match snark {
+    WasmSNARK::Recursive(rs) => *rs,
+    WasmSNARK::Compressed(..) => unreachable!("prove returns a recursive SNARK"),
+  }
+}
+
+#[test]
+fn test_mcc_from_other_execution() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  let (snark_a, U_a) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("16")?, step_size)?;
+  let (snark_b, U_b) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("10")?, step_size)?;
+  snark_a.verify(&pp, &U_a)?;
+  snark_b.verify(&pp, &U_b)?;
+  let (rs_a, rs_b) = (into_recursive(snark_a), into_recursive(snark_b));
+
+  // Execution of run A with both MCC proofs (ops & scan) of run B
+  let snark = WasmSNARK::<E, S1, S2>::Recursive(Box::new(RecursiveWasmSNARK {
+    execution_rs: rs_a.execution_rs.clone(),
+    ops_rs: rs_b.ops_rs.clone(),
+    scan_rs: rs_b.scan_rs.clone(),
+  }));
+  let U = ZKWASMInstance {
+    ops_z0: U_b.ops_z0.clone(),
+    ops_IC_i: U_b.ops_IC_i,
+    scan_z0: U_b.scan_z0.clone(),
+    scan_IC_i: U_b.scan_IC_i,
+    ..U_a.clone()
+  };
+  assert!(snark.verify(&pp, &U).is_err());
+
+  // Execution & ops of run A with the scan proof (IS & FS) of run B
+  let snark = WasmSNARK::<E, S1, S2>::Recursive(Box::new(RecursiveWasmSNARK {
+    execution_rs: rs_a.execution_rs.clone(),
+    ops_rs: rs_a.ops_rs.clone(),
+    scan_rs: rs_b.scan_rs.clone(),
+  }));
+  let U = ZKWASMInstance {
+    scan_z0: U_b.scan_z0.clone(),
+    scan_IC_i: U_b.scan_IC_i,
+    ..U_a.clone()
+  };
+  assert!(snark.verify(&pp, &U).is_err());
+
+  // Run A with a scan proof of the same IS & FS, so of the same IC_i, under other challenges: it
+  // verifies on its own, but does not fingerprint with the challenges of run A
+  let config = ProvingConfig::new(step_size).context(b"other");
+  let (snark_c, U_c) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("16")?, config)?;
+  assert_eq!((U_c.IC_i, U_c.scan_IC_i), (U_a.IC_i, U_a.scan_IC_i));
+  assert_ne!(U_c.scan_z0[0..2], U_a.scan_z0[0..2]);
+  let snark = WasmSNARK::<E, S1, S2>::Recursive(Box::new(RecursiveWasmSNARK {
+    execution_rs: rs_a.execution_rs.clone(),
+    ops_rs: rs_a.ops_rs.clone(),
+    scan_rs: into_recursive(snark_c).scan_rs,
+  }));
+  let U = ZKWASMInstance {
+    scan_z0: U_c.scan_z0.clone(),
+    ..U_a.clone()
+  };
+  assert!(matches!(
+    snark.verify(&pp, &U),
+    Err(ZKWASMError::MultisetVerificationError)
+  ));
+
+  Ok(())
+}
+
+#[test]
+fn test_stack_opening() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+
+  let program = fib_ctx("16")?;
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &program, step_size)?;
+
+  // The stack top after step 5 is right below the stack pointer step 6 starts with
+  let (execution_trace, _, _) = program.execution_trace()?;
+  let stack_top = execution_trace[6].pre_sp - 1;
+  let (val, opening) = snark.open_stack(&program, step_size, 5, stack_top)?;
+  snark.verify_stack_opening(&pp, &U, val, &opening)?;
+
+  // The opening does not open any other value
+  assert!(snark
+    .verify_stack_opening(&pp, &U, val + 1, &opening)
+    .is_err());
+
+  // An opening of another execution is not bound to the proof
+  let (other_val, other_opening) = snark.open_stack(&fib_ctx("10")?, step_size, 5, stack_top)?;
+  assert!(snark
+    .verify_stack_opening(&pp, &U, other_val, &other_opening)
+    .is_err());
+
+  Ok(())
+}
+
+#[test]
+fn test_app_label() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp_a = WasmSNARK::<E, S1, S2>::setup_with_label(step_size, b"A");
+  let pp_b = WasmSNARK::<E, S1, S2>::setup_with_label(step_size, b"B");
+
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp_a, &fib_ctx("16")?, step_size)?;
+  snark.verify(&pp_a, &U)?;
+
+  // The circuits are the same, but the MCC challenges are derived under another label
+  assert!(snark.verify(&pp_b, &U).is_err());
+
+  // A compressed SNARK does not bind the label, so labelled proofs are not compressed
+  assert!(matches!(
+    snark.compress(&pp_a, &U),
+    Err(ZKWASMError::NotRecursive)
+  ));
+  // nor does a compressed unlabelled proof verify under a label
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("16")?, step_size)?;
+  let compressed = snark.compress(&pp, &U)?;
+  compressed.verify(&pp, &U)?;
+  assert!(matches!(
+    compressed.verify(&pp_a, &U),
+    Err(ZKWASMError::NotRecursive)
+  ));
+
+  Ok(())
+}
+
+#[test]
+fn test_size_report() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("16")?, step_size)?;
+
+  let bytes = snark.to_bytes(&pp)?;
+  let SizeReport {
+    execution_rs,
+    ops_rs,
+    scan_rs,
+    total,
+  } = snark.size_report()?;
+  assert_eq!(total, bytes.len());
+  assert!(execution_rs + ops_rs + scan_rs <= total);
+
+  // The proof verifies once transmitted
+  WasmSNARK::<E, S1, S2>::from_bytes(&bytes, &pp)?.verify(&pp, &U)?;
+
+  // A compressed SNARK is a single proof
+  let compressed = snark.compress(&pp, &U)?;
+  assert!(compressed.size_report().is_err());
+  assert!(compressed.to_bytes(&pp)?.len() < total);
+  Ok(())
+}
+
+#[test]
+fn test_proof_bytes_header() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("16")?, step_size)?;
+
+  let bytes = snark.to_bytes(&pp)?;
+  WasmSNARK::<E, S1, S2>::from_bytes(&bytes, &pp)?.verify(&pp, &U)?;
+
+  // A proof of another format version fails to load
+  let mut tampered = bytes.clone();
+  tampered[4] ^= 1;
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::from_bytes(&tampered, &pp),
+    Err(ZKWASMError::ProofFormatMismatch(_))
+  ));
+
+  // and so does a proof loaded with parameters of another layout
+  let other_pp = WasmSNARK::<E, S1, S2>::setup_with_label(step_size, b"other app");
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::from_bytes(&bytes, &other_pp),
+    Err(ZKWASMError::ProofFormatMismatch(_))
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_deterministic_proofs() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("16")?, step_size)?;
+  let config = ProvingConfig::new(step_size).num_threads(NonZeroUsize::MIN);
+  let (other_snark, other_U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("16")?, config)?;
+  assert_eq!(snark.to_bytes(&pp)?, other_snark.to_bytes(&pp)?);
+  assert_eq!(bincode::serialize(&U)?, bincode::serialize(&other_U)?);
+
+  let compressed = snark.compress(&pp, &U)?;
+  let other_compressed = other_snark.compress(&pp, &other_U)?;
+  assert_eq!(compressed.to_bytes(&pp)?, other_compressed.to_bytes(&pp)?);
+  Ok(())
+}
+
+/// A step circuit without any constraint of its own
+#[derive(Clone, Debug)]
+struct TrivialCircuit;
+
+impl<F: PrimeField> StepCircuit<F> for TrivialCircuit {
+  fn arity(&self) -> usize {
+    1
+  }
+
+  fn synthesize<CS: ConstraintSystem<F>>(
+    &self,
+    _cs: &mut CS,
+    z: &[AllocatedNum<F>],
+  ) -> Result<Vec<AllocatedNum<F>>, SynthesisError> {
+    Ok(z.to_vec())
+  }
+
+  fn non_deterministic_advice(&self) -> Vec<F> {
+    vec![]
+  }
+}
+
+#[test]
+fn test_circuit_stats() -> Result<(), ZKWASMError> {
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(StepSize::new(10)?);
+  let CircuitStats {
+    execution,
+    ops,
+    scan,
+  } = pp.circuit_stats();
+  for stats in [execution, ops, scan] {
+    assert!(stats.primary_constraints > 0 && stats.secondary_constraints > 0);
+    assert!(stats.primary_variables > 0 && stats.secondary_variables > 0);
+  }
+
+  // The folding verifier alone is smaller than the execution circuit it is augmented with
+  let trivial_pp =
+    PublicParams::<E>::setup(&TrivialCircuit, &*default_ck_hint(), &*default_ck_hint());
+  let (trivial_constraints, _) = trivial_pp.num_constraints();
+  assert!(execution.primary_constraints > trivial_constraints);
+
+  // Batching more steps per folding step grows the execution circuit
+  let larger = WasmSNARK::<E, S1, S2>::setup(StepSize::new(20)?).circuit_stats();
+  assert!(larger.execution.primary_constraints > execution.primary_constraints);
+  Ok(())
+}
+
+#[test]
+fn test_verifier_context() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark_a, U_a) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("16")?, step_size)?;
+  let (snark_b, U_b) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("10")?, step_size)?;
+  let compressed = snark_a.compress(&pp, &U_a)?;
+
+  // One context verifies every proof made with its parameters
+  let ctx = VerifierContext::new(&pp);
+  ctx.verify(&snark_a, &U_a)?;
+  ctx.verify(&snark_b, &U_b)?;
+  ctx.verify(&compressed, &U_a)?;
+  assert_eq!(
+    ctx.verify_and_extract(&snark_b, &U_b)?.stack_top(),
+    snark_b.verify_and_extract(&pp, &U_b)?.stack_top()
+  );
+  assert!(ctx.verify(&snark_a, &U_b).is_err());
+
+  // The context keeps the application label of its parameters
+  let pp_label = WasmSNARK::<E, S1, S2>::setup_with_label(step_size, b"A");
+  assert!(VerifierContext::new(&pp_label)
+    .verify(&snark_a, &U_a)
+    .is_err());
+  Ok(())
+}
+
+#[test]
+fn test_streaming_verifier() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let program = fib_ctx("16")?;
+  let z0 = ZKWASMInstance::expected_inputs(&pp, &program, step_size)?.execution_z0;
+
+  // Verify each step as it is proven
+  let mut verifier = StreamingVerifier::new(&pp, z0.clone());
+  let mut steps = Vec::new();
+  let (snark, U) =
+    WasmSNARK::<E, S1, S2>::prove_streaming(&pp, &program, step_size, &mut |_, rs, IC_i| {
+      verifier.verify_step(rs, IC_i)?;
+      steps.push((rs.clone(), IC_i));
+      Ok(())
+    })?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(verifier.num_steps(), steps.len());
+  assert!(steps.len() > 2);
+
+  // A corrupted intermediate step is caught as it arrives, as is a step out of order
+  let mut verifier = StreamingVerifier::new(&pp, z0);
+  verifier.verify_step(&steps[0].0, steps[0].1)?;
+  let corrupted = steps[1].1 + <E as Engine>::Scalar::ONE;
+  assert!(verifier.verify_step(&steps[1].0, corrupted).is_err());
+  assert!(verifier.verify_step(&steps[2].0, steps[2].1).is_err());
+  assert_eq!(verifier.num_steps(), 1);
+
+  // and the prover is cut off there
+  let mut proven = 0;
+  let cut_off =
+    WasmSNARK::<E, S1, S2>::prove_streaming(&pp, &program, step_size, &mut |i, _, _| {
+      proven = i + 1;
+      match i {
+        1 => Err(ZKWASMError::MalformedRS),
+        _ => Ok(()),
+      }
+    });
+  assert!(matches!(cut_off, Err(ZKWASMError::MalformedRS)));
+  assert_eq!(proven, 2);
+  Ok(())
+}
+
+#[test]
+fn test_prove_with_scan() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let program = fib_ctx("16")?;
+
+  // Commit to IS once, and reuse it across two proofs of executions starting from it
+  let (_, IS, _) = program.execution_trace()?;
+  let (scan, IC_IS) = WasmSNARK::<E, S1, S2>::prove_scan(&pp, IS, step_size)?;
+  for context in [&b"A"[..], b"B"] {
+    let config = ProvingConfig::new(step_size).context(context);
+    let (snark, U) =
+      WasmSNARK::<E, S1, S2>::prove_with_scan(&pp, &program, config.clone(), &scan, IC_IS)?;
+    snark.verify_with_context(&pp, &U, context)?;
+    assert_eq!(U.scan_IC_i.0, IC_IS);
+
+    // the proof is the one committing to IS again
+    let (expected, expected_U) = WasmSNARK::<E, S1, S2>::prove(&pp, &program, config)?;
+    assert_eq!(snark.to_bytes(&pp)?, expected.to_bytes(&pp)?);
+    assert_eq!(
+      (U.IC_i, U.scan_IC_i, U.scan_z0),
+      (expected_U.IC_i, expected_U.scan_IC_i, expected_U.scan_z0)
+    );
+  }
+
+  // An execution starting from other memory, or another commitment to IS, is rejected
+  let other = fib_ctx("10")?;
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::prove_with_scan(&pp, &other, step_size, &scan, IC_IS),
+    Err(ZKWASMError::InputMismatch(input)) if input == "IS"
+  ));
+  let IC_IS = IC_IS + <E as Engine>::Scalar::ONE;
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::prove_with_scan(&pp, &program, step_size, &scan, IC_IS),
+    Err(ZKWASMError::InputMismatch(input)) if input == "IC_IS"
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_prove_from_trace() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let program = fib_ctx("16")?;
+  let (_, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &program, step_size)?;
+
+  // Execute once, then prove from the trace as transmitted to the prover
+  let bytes = bincode::serialize(&program.execution_trace()?)?;
+  let (execution_trace, IS, IS_sizes) = bincode::deserialize(&bytes)?;
+  let (snark, U_trace) = WasmSNARK::<E, S1, S2>::prove_from_trace(
+    &pp,
+    execution_trace.clone(),
+    IS.clone(),
+    IS_sizes,
+    program.args().bytecode(),
+    step_size,
+  )?;
+  snark.verify(&pp, &U_trace)?;
+  assert_eq!(bincode::serialize(&U_trace)?, bincode::serialize(&U)?);
+
+  // The trace is already executed, so it can not be sharded
+  let sharded = ProvingConfig::new(step_size).shard(1, NonZeroUsize::MIN);
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::prove_from_trace(&pp, execution_trace, IS, IS_sizes, &[], sharded),
+    Err(ZKWASMError::InvalidConfig(_))
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_prove_trace_iter() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let program = fib_ctx("16")?;
+  let (_, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &program, step_size)?;
+
+  // Prove the trace as it is read, one witness at a time
+  let (execution_trace, IS, IS_sizes) = program.execution_trace()?;
+  let num_steps = execution_trace.len().div_ceil(10);
+  let mut execution_trace = execution_trace.into_iter();
+  let reports = Mutex::new(Vec::new());
+  let progress = |phase: ProvingPhase, current: usize, total: usize| {
+    if phase == ProvingPhase::Execution {
+      reports.lock().unwrap().push((current, total));
+    }
+  };
+  let (snark, U_iter) = WasmSNARK::<E, S1, S2>::prove_trace_iter(
+    &pp,
+    std::iter::from_fn(|| execution_trace.next()),
+    IS,
+    IS_sizes,
+    program.args().bytecode(),
+    ProvingConfig::new(step_size).progress(&progress),
+  )?;
+  snark.verify(&pp, &U_iter)?;
+  assert_eq!(U_iter.IC_i, U.IC_i);
+  assert_eq!(bincode::serialize(&U_iter)?, bincode::serialize(&U)?);
+
+  // The length of the trace is unknown, so the total of the progress is the steps proven so far
+  let reports = reports.into_inner().unwrap();
+  assert_eq!(reports.len(), num_steps);
+  assert!(reports.iter().all(|&(current, total)| current == total));
+  Ok(())
+}
+
+#[test]
+fn test_expected_inputs() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let program = fib_ctx("16")?;
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &program, step_size)?;
+  snark.verify(&pp, &U)?;
+
+  let expected = ZKWASMInstance::expected_inputs(&pp, &program, step_size)?;
+  expected.check(&U)?;
+
+  // A prover claiming another initial global timestamp for Πops is caught
+  let mut ops_z0 = U.ops_z0.clone();
+  ops_z0[2] += <E as Engine>::Scalar::ONE;
+  let tampered = ZKWASMInstance { ops_z0, ..U };
+  assert!(matches!(
+    expected.check(&tampered),
+    Err(ZKWASMError::InputMismatch(input)) if input == "ops_z0"
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_memory_image() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let blob = (0..32).map(|i| i * 7 + 1).collect::<Vec<u8>>();
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/memory/blob_sum.wat"))?
+    .invoke("main")
+    .func_args(vec![String::from("1024")])
+    .with_memory_image(1024, blob.clone())
+    .build();
+
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &WASMCtx::new(wasm_args), step_size)?;
+  snark.verify(&pp, &U)?;
+  let sum = blob
+    .chunks(8)
+    .map(|word| u64::from_le_bytes(word.try_into().unwrap()))
+    .fold(0, u64::wrapping_add);
+  assert_eq!(U.return_values()?, vec![sum]);
+
+  // The instance commits to the blob & its offset
+  let poseidon = CommitmentHash::Poseidon;
+  assert!(U.commits_to_memory_images(poseidon, &[(1024, blob.clone())]));
+  assert!(!U.commits_to_memory_images(poseidon, &[(1024, vec![0; 32])]));
+  assert!(!U.commits_to_memory_images(poseidon, &[(2048, blob)]));
+
+  // and the proof to the hash in the instance
+  let forged = ZKWASMInstance {
+    memory_image: U.memory_image + <E as Engine>::Scalar::ONE,
+    ..U.clone()
+  };
+  assert!(snark.verify(&pp, &forged).is_err());
+
+  // A compressed SNARK does not bind the hash, so the proof is not compressed
+  assert!(matches!(
+    snark.compress(&pp, &U),
+    Err(ZKWASMError::NotRecursive)
+  ));
+  // nor does a compressed proof without images verify once the hash is edited in
+  let (plain_snark, plain_U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("10")?, step_size)?;
+  let compressed = plain_snark.compress(&pp, &plain_U)?;
+  let forged = ZKWASMInstance {
+    memory_image: U.memory_image,
+    ..plain_U
+  };
+  assert!(matches!(
+    compressed.verify(&pp, &forged),
+    Err(ZKWASMError::NotRecursive)
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_commitment_hash() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  init_logger();
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let blob = (0..32).map(|i| i * 7 + 1).collect::<Vec<u8>>();
+  let images = [(1024, blob.clone())];
+  let hashes = [
+    CommitmentHash::Poseidon,
+    CommitmentHash::Sha256,
+    CommitmentHash::Keccak256,
+  ];
+
+  // Each hash gives its own digest of the same images, & the same digest of them every time
+  for (i, a) in hashes.iter().enumerate() {
+    let digest = memory_image_digest::<<E as Engine>::Scalar>(*a, &images);
+    assert_eq!(digest, memory_image_digest(*a, &images));
+    for b in &hashes[i + 1..] {
+      assert_ne!(digest, memory_image_digest(*b, &images));
+    }
+  }
+
+  // A proof commits to the images with the hash of its program
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/memory/blob_sum.wat"))?
+    .invoke("main")
+    .func_args(vec![String::from("1024")])
+    .with_memory_image(1024, blob)
+    .commitment_hash(CommitmentHash::Keccak256)
+    .build();
+  let program = WASMCtx::new(wasm_args);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &program, step_size)?;
+  snark.verify(&pp, &U)?;
+  ZKWASMInstance::expected_inputs(&pp, &program, step_size)?.check(&U)?;
+  assert!(U.commits_to_memory_images(CommitmentHash::Keccak256, &images));
+  assert!(!U.commits_to_memory_images(CommitmentHash::Sha256, &images));
+  assert!(!U.commits_to_memory_images(CommitmentHash::Poseidon, &images));
+  Ok(())
+}
+
+/// A module loading the first word of its data segment, which also declares a second word it never
+/// reads
+fn data_segment_ctx(data: &str) -> Result<WASMCtx, ZKWASMError> {
+  let wat = format!(
+    r#"(module
+  (memory 1)
+  (data (i32.const 0) "{data}")
+  (func (export "main") (result i64)
+    (i64.load (i32.const 0))))"#
+  );
+  let program = wat::parse_str(wat).map_err(|err| ZKWASMError::WASMError(err.to_string()))?;
+  let wasm_args = WASMArgsBuilder::default()
+    .bytecode(program)
+    .invoke("main")
+    .build();
+  Ok(WASMCtx::new(wasm_args))
+}
+
+#[test]
+fn test_verify_initial_state() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let program = data_segment_ctx("\\2a\\00\\00\\00\\00\\00\\00\\00\\07")?;
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &program, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![42]);
+  U.verify_initial_state(&pp, &program, step_size)?;
+
+  // The proof is not one of a module declaring other data
+  let other = data_segment_ctx("\\2a\\00\\00\\00\\00\\00\\00\\00\\08")?;
+  assert!(matches!(
+    U.verify_initial_state(&pp, &other, step_size),
+    Err(ZKWASMError::InputMismatch(input)) if input == "IS"
+  ));
+
+  // A prover starting from another value for the word the execution never reads still proves
+  // the execution, but not from the initial state of the module
+  let (execution_trace, mut IS, IS_sizes) = program.execution_trace()?;
+  let unread = IS
+    .iter_mut()
+    .find(|(addr, _, _)| *addr == IS_sizes.stack_len() + 1)
+    .unwrap();
+  unread.1 = 0;
+  let (forged_snark, forged) = WasmSNARK::<E, S1, S2>::prove_from_trace(
+    &pp,
+    execution_trace,
+    IS,
+    IS_sizes,
+    program.args().bytecode(),
+    step_size,
+  )?;
+  forged_snark.verify(&pp, &forged)?;
+  assert_eq!(forged.return_values()?, vec![42]);
+  assert!(matches!(
+    forged.verify_initial_state(&pp, &program, step_size),
+    Err(ZKWASMError::InputMismatch(input)) if input == "IS"
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_program_commitment() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let fib = fib_ctx("16")?;
+  let data = data_segment_ctx("\\2a\\00\\00\\00\\00\\00\\00\\00")?;
+  let (fib_snark, fib_U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib, step_size)?;
+  let (data_snark, data_U) = WasmSNARK::<E, S1, S2>::prove(&pp, &data, step_size)?;
+
+  // Each instance commits to the bytecode of its own module
+  let fib_commitment = program_commitment(fib.args().bytecode());
+  let data_commitment = program_commitment(data.args().bytecode());
+  assert_eq!(fib_U.program_commitment(), fib_commitment);
+  assert_eq!(data_U.program_commitment(), data_commitment);
+  assert_ne!(fib_commitment, data_commitment);
+  fib_snark.verify_program(&pp, &fib_U, fib_commitment)?;
+  data_snark.verify_program(&pp, &data_U, data_commitment)?;
+  assert!(matches!(
+    fib_snark.verify_program(&pp, &fib_U, data_commitment),
+    Err(ZKWASMError::ProgramMismatch)
+  ));
+
+  // and the proof to the commitment in the instance
+  let forged = ZKWASMInstance {
+    program: data_commitment,
+    ..fib_U
+  };
+  assert!(fib_snark
+    .verify_program(&pp, &forged, data_commitment)
+    .is_err());
+
+  // A compressed SNARK does not authenticate the commitment, so a program can't be checked on it
+  let compressed = fib_snark.compress(&pp, &fib_U)?;
+  compressed.verify(&pp, &fib_U)?;
+  assert!(matches!(
+    compressed.verify_program(&pp, &fib_U, fib_commitment),
+    Err(ZKWASMError::NotRecursive)
+  ));
+  assert!(matches!(
+    compressed.verify_program(&pp, &forged, data_commitment),
+    Err(ZKWASMError::NotRecursive)
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_compressed_context() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("10")?, step_size)?;
+  let config = ProvingConfig::new(step_size).context(b"session-2");
+  let (_, session_U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("10")?, config)?;
+  let compressed = snark.compress(&pp, &U)?;
+  compressed.verify(&pp, &U)?;
+
+  // Editing the context of the instance does not bind the compressed SNARK to it
+  let forged = ZKWASMInstance {
+    context: session_U.context,
+    ..U
+  };
+  assert!(matches!(
+    compressed.verify_with_context(&pp, &forged, b"session-2"),
+    Err(ZKWASMError::NotRecursive)
+  ));
+  assert!(compressed.verify(&pp, &forged).is_err());
+  Ok(())
+}
+
+#[test]
+fn test_evm_public_inputs() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("16")?, step_size)?;
+  snark.verify(&pp, &U)?;
+
+  let words = U.to_evm_public_inputs();
+  let scalars = U
+    .execution_z0
+    .iter()
+    .chain(&U.execution_zi)
+    .chain([&U.IC_i])
+    .chain(&U.ops_z0)
+    .chain([&U.ops_IC_i])
+    .chain(&U.scan_z0)
+    .chain([
+      &U.scan_IC_i.0,
+      &U.scan_IC_i.1,
+      &U.context,
+      &U.memory_image,
+      &U.program,
+    ])
+    .collect::<Vec<_>>();
+  assert_eq!(words.len(), scalars.len());
+
+  // Each word is the big-endian encoding of its scalar
+  for (word, scalar) in words.iter().zip(scalars) {
+    let mut repr = <E as Engine>::Scalar::ZERO.to_repr();
+    repr.as_mut().copy_from_slice(word);
+    repr.as_mut().reverse();
+    assert_eq!(<E as Engine>::Scalar::from_repr(repr).unwrap(), *scalar);
+  }
+
+  // The stack top of the final output is the returned value
+  let stack_top = &words[U.execution_z0.len()];
+  assert!(stack_top[..24].iter().all(|byte| *byte == 0));
+  assert_eq!(u64::from_be_bytes(stack_top[24..].try_into().unwrap()), 987);
+  Ok(())
+}
+
+#[test]
+fn test_step_size_under_memory() -> Result<(), ZKWASMError> {
+  // A smaller budget never gets a larger step size
+  let per_op = 1 << 20;
+  let step_sizes = [1, 2, 10, 64, 1000]
+    .map(|ops| StepSize::max_under_memory(ops * per_op + per_op / 2, per_op).unwrap());
+  assert!(step_sizes
+    .windows(2)
+    .all(|pair| pair[0].execution < pair[1].execution));
+  assert_eq!(step_sizes[2].execution, 10);
+  assert_eq!(step_sizes[2].memory, 10);
+
+  // Not even one opcode fits
+  assert!(matches!(
+    StepSize::max_under_memory(per_op - 1, per_op),
+    Err(ZKWASMError::InvalidStepSize(_))
+  ));
+  assert!(matches!(
+    StepSize::max_under_memory(per_op, 0),
+    Err(ZKWASMError::InvalidStepSize(_))
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_estimate() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?.set_memory_step_size(7)?;
+  let program = fib_ctx("16")?;
+  let ProofEstimate {
+    execution_steps,
+    padded_execution_steps,
+    IS_len,
+    FS_len,
+    execution_rs_steps,
+    ops_rs_steps,
+    scan_rs_steps,
+    memory_ops_per_step,
+  } = WasmSNARK::<E, S1, S2>::estimate(&program, step_size)?;
+  let (execution_trace, IS, _) = program.execution_trace()?;
+  assert_eq!(execution_steps, execution_trace.len());
+  assert_eq!(padded_execution_steps % 10, 0);
+  assert!(padded_execution_steps - execution_steps < 10);
+  assert_eq!((IS_len, FS_len), (IS.len(), IS.len()));
+  assert_eq!(memory_ops_per_step, step_size.memory_ops_per_step());
+
+  // The estimate matches the steps actually folded
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &program, step_size)?;
+  snark.verify(&pp, &U)?;
+  let rs = into_recursive(snark);
+  assert_eq!(execution_rs_steps, rs.execution_rs.num_steps());
+  assert_eq!(ops_rs_steps, rs.ops_rs.num_steps());
+  assert_eq!(scan_rs_steps, rs.scan_rs.num_steps());
+  Ok(())
+}
+
+#[test]
+fn test_multisets() -> Result<(), ZKWASMError> {
+  let step_size = StepSize::new(10)?;
+  let mut multisets = WasmSNARK::<E, S1, S2>::multisets(&fib_ctx("16")?, step_size)?;
+  multisets.validate()?;
+  assert_eq!(multisets.RS.len(), multisets.WS.len());
+  assert_eq!(multisets.RS.len() % 10, 0);
+
+  // A wrong write timestamp is pinpointed by the op it was written at
+  let (addr, val, ts) = multisets.WS[3][0];
+  multisets.WS[3][0].2 = u64::MAX;
+  assert!(matches!(
+    multisets.validate(),
+    Err(ZKWASMError::MultisetMismatch { addr: a, val: v, ts: t }) if (a, v, t) == (addr, val, ts)
+  ));
+  Ok(())
+}
+
+#[test]
+fn test_max_memory_pages() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let wasm_args = WASMArgsBuilder::default()
+    .file_path(PathBuf::from("wasm/memory/grow_size.wat"))?
+    .invoke("main")
+    .build();
+  let program = WASMCtx::new(wasm_args);
+
+  // The module grows its one page to two, over a limit of one page
+  let bounded = step_size.set_max_memory_pages(1);
+  assert!(matches!(
+    WasmSNARK::<E, S1, S2>::prove(&pp, &program, bounded),
+    Err(ZKWASMError::MemoryLimitExceeded {
+      requested: 2,
+      allowed: 1
+    })
+  ));
+
+  // but within a limit of two
+  let bounded = step_size.set_max_memory_pages(2);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &program, bounded)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![2]);
+  Ok(())
+}
+
+#[test]
+fn test_forged_return_values() -> Result<(), ZKWASMError> {
+  init_logger();
+  let step_size = StepSize::new(10)?;
+  let pp = WasmSNARK::<E, S1, S2>::setup(step_size);
+  let (snark, U) = WasmSNARK::<E, S1, S2>::prove(&pp, &fib_ctx("10")?, step_size)?;
+  snark.verify(&pp, &U)?;
+  assert_eq!(U.return_values()?, vec![55]);
+
+  // The proof commits to the results, so a prover can neither hide, edit nor add any
+  for results in [vec![], vec![54], vec![55, 0], vec![0, 55]] {
+    let forged = ZKWASMInstance {
+      results,
+      ..U.clone()
+    };
+    assert!(matches!(
+      snark.verify(&pp, &forged),
+      Err(ZKWASMError::OutputMismatch)
+    ));
+    assert!(matches!(
+      forged.return_values(),
+      Err(ZKWASMError::OutputMismatch)
+    ));
+  }
+  Ok(())
+}
@@ -0,0 +1,89 @@
+//! Verifier-side data derived from [`WASMPublicParams`], to verify many proofs against the same
+//! parameters without deriving it again for each of them.
+use nova::{
+  nebula::compression::VerifierKey,
+  traits::{
+    snark::{BatchedRelaxedR1CSSNARKTrait, RelaxedR1CSSNARKTrait},
+    CurveCycleEquipped, Dual,
+  },
+};
+
+use super::{context_digest, WASMPublicParams, WasmSNARK, ZKWASMInstance, ZKWASMOutput};
+use crate::error::ZKWASMError;
+
+/// The data a verifier derives from [`WASMPublicParams`]: the verifier key of compressed proofs
+/// and the digest of the application label the MCC challenges are derived under.
+///
+/// Building it derives everything up front, so the first verification is as fast as the next
+/// ones, e.g. when benchmarking verification.
+pub struct VerifierContext<'a, E, S1, S2>
+where
+  E: CurveCycleEquipped,
+  S1: BatchedRelaxedR1CSSNARKTrait<E>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E>>,
+{
+  pp: &'a WASMPublicParams<E, S1, S2>,
+  vk: &'a VerifierKey<E, S1, S2>,
+  app_label: E::Scalar,
+}
+
+impl<'a, E, S1, S2> VerifierContext<'a, E, S1, S2>
+where
+  E: CurveCycleEquipped,
+  S1: BatchedRelaxedR1CSSNARKTrait<E>,
+  S2: RelaxedR1CSSNARKTrait<Dual<E>>,
+{
+  /// Derive the verifier data of `pp`
+  pub fn new(pp: &'a WASMPublicParams<E, S1, S2>) -> Self {
+    Self {
+      pp,
+      vk: pp.vk(),
+      app_label: context_digest(pp.app_label()),
+    }
+  }
+
+  /// The public parameters the context is derived from
+  pub fn pp(&self) -> &WASMPublicParams<E, S1, S2> {
+    self.pp
+  }
+
+  /// Verify `snark`, as [`WasmSNARK::verify`] does
+  pub fn verify(
+    &self,
+    snark: &WasmSNARK<E, S1, S2>,
+    U: &ZKWASMInstance<E>,
+  ) -> Result<(), ZKWASMError> {
+    self.verify_with_context(snark, U, &[])
+  }
+
+  /// Verify `snark` was produced under `context`, as [`WasmSNARK::verify_with_context`] does
+  pub fn verify_with_context(
+    &self,
+    snark: &WasmSNARK<E, S1, S2>,
+    U: &ZKWASMInstance<E>,
+    context: &[u8],
+  ) -> Result<(), ZKWASMError> {
+    snark.verify_with_parts(self.pp, || self.vk, self.app_label, U, context)
+  }
+
+  /// Verify `snark` and return the public outputs it proves, as [`WasmSNARK::verify_and_extract`]
+  /// does
+  pub fn verify_and_extract(
+    &self,
+    snark: &WasmSNARK<E, S1, S2>,
+    U: &ZKWASMInstance<E>,
+  ) -> Result<ZKWASMOutput<E>, ZKWASMError> {
+    self.verify_and_extract_with_context(snark, U, &[])
+  }
+
+  /// Verify `snark` was produced under `context` and return the public outputs it proves, as
+  /// [`WasmSNARK::verify_and_extract_with_context`] does
+  pub fn verify_and_extract_with_context(
+    &self,
+    snark: &WasmSNARK<E, S1, S2>,
+    U: &ZKWASMInstance<E>,
+    context: &[u8],
+  ) -> Result<ZKWASMOutput<E>, ZKWASMError> {
+    snark.extract_with_parts(self.pp, self.app_label, U, context)
+  }
+}
@@ -22,8 +22,9 @@ pub use self::utils::{
     TableIdx,
 };
 use super::{const_pool::ConstRef, CompiledFunc, TranslationError};
-use core::fmt::Debug;
-use wasmi_core::F32;
+use core::fmt::{self, Debug, Display};
+use serde::{Deserialize, Serialize};
+use wasmi_core::{HostError, F32};
 
 /// The internal `wasmi` bytecode that is stored for Wasm functions.
 ///
@@ -33,7 +34,7 @@ use wasmi_core::F32;
 ///
 /// For example the `BrTable` instruction is unrolled into separate instructions
 /// each representing either the `BrTable` head or one of its branching targets.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum Instruction {
     LocalGet(LocalDepth),
     LocalSet(LocalDepth),
@@ -391,6 +392,7 @@ pub enum Instruction {
     DropKeep,
     MemoryCopyStep,
     MemoryFillStep,
+    MemoryInitStep,
     HostCallStep,
     HostCallStackStep,
     // Special instruction to trace the zero writes to the stack when vm is preparing for a function call
@@ -464,18 +466,28 @@ impl Instruction {
 }
 
 impl Instruction {
-    pub const MAX_J: u64 = 50;
+    pub const MAX_J: u64 = 92;
 
     /// Get an index for each instruction to constrain the zkVM's computation result at the end of each zkVM cycle.
     /// To elaborate the zkVM multiplexer circuit has to perform all computation instructions and at then end of the circuit
     /// we use this index to constraint the right computation result for the corresponding instruction getting executed.
+    ///
+    /// # Panics
+    ///
+    /// If the zkVM does not support the instruction, see [`Instruction::try_index_j`].
     pub fn index_j(&self) -> u64 {
-        match self {
+        self.try_index_j().unwrap_or_else(|error| panic!("{error}"))
+    }
+
+    /// Get the index of the instruction, see [`Instruction::index_j`].
+    ///
+    /// # Errors
+    ///
+    /// If the zkVM does not support the instruction, i.e. has no circuit for it.
+    pub fn try_index_j(&self) -> Result<u64, UnsupportedInstruction> {
+        let j = match self {
             Self::Unreachable => 0,
-            Self::I64Const32(..)
-            | Self::Const32(..)
-            | Self::ConstRef(..)
-            | Self::F64Const32(..) => 1,
+            Self::I64Const32(..) | Self::Const32(..) | Self::F64Const32(..) => 1,
             Self::LocalGet(..) => 2,
             Self::I64Add => 3,
             Self::I64Mul => 4,
@@ -532,17 +544,6 @@ impl Instruction {
             | Self::F64Trunc
             | Self::F64Nearest
             | Self::F64Sqrt
-            | Self::I32WrapI64
-            | Self::I32TruncF32S
-            | Self::I32TruncF32U
-            | Self::I32TruncF64S
-            | Self::I32TruncF64U
-            | Self::I64ExtendI32S
-            | Self::I64ExtendI32U
-            | Self::I64TruncF32S
-            | Self::I64TruncF32U
-            | Self::I64TruncF64S
-            | Self::I64TruncF64U
             | Self::F32ConvertI32S
             | Self::F32ConvertI32U
             | Self::F32ConvertI64S
@@ -552,20 +553,7 @@ impl Instruction {
             | Self::F64ConvertI32U
             | Self::F64ConvertI64S
             | Self::F64ConvertI64U
-            | Self::F64PromoteF32
-            | Self::I32Extend8S
-            | Self::I32Extend16S
-            | Self::I64Extend8S
-            | Self::I64Extend16S
-            | Self::I64Extend32S
-            | Self::I32TruncSatF32S
-            | Self::I32TruncSatF32U
-            | Self::I32TruncSatF64S
-            | Self::I32TruncSatF64U
-            | Self::I64TruncSatF32S
-            | Self::I64TruncSatF32U
-            | Self::I64TruncSatF64S
-            | Self::I64TruncSatF64U => 19,
+            | Self::F64PromoteF32 => 19,
 
             // visit_binary
             Self::F32Eq
@@ -580,17 +568,9 @@ impl Instruction {
             | Self::F64Gt
             | Self::F64Le
             | Self::F64Ge
-            | Self::F32Add
-            | Self::F32Sub
-            | Self::F32Mul
-            | Self::F32Div
             | Self::F32Min
             | Self::F32Max
             | Self::F32Copysign
-            | Self::F64Add
-            | Self::F64Sub
-            | Self::F64Mul
-            | Self::F64Div
             | Self::F64Min
             | Self::F64Max
             | Self::F64Copysign => 20,
@@ -642,13 +622,89 @@ impl Instruction {
             Self::I64LtS | Self::I64LtU | Self::I64GeS | Self::I64GeU => 48,
             Self::I64GtS | Self::I64GtU | Self::I64LeS | Self::I64LeU => 49,
 
-            Self::CallInternal(..) | Self::CallIndirect(..) | Self::Call(..) => 0, // TODO: all 0 J_indexes
-            Self::Drop => 0,
-            Self::Return(..) => Self::MAX_J, // TODO
-            _ => {
-                println!("{:?}", self);
-                unimplemented!()
+            Self::ConstRef(..) => 50,
+
+            Self::Call(..) => 51,
+
+            Self::I32TruncSatF32S => 52,
+
+            Self::I64ExtendI32S => 53,
+
+            Self::ReturnIfNez(..) => 55,
+
+            Self::F32Add => 56,
+            Self::F32Sub => 57,
+            Self::F32Mul => 58,
+            Self::F32Div => 59,
+
+            Self::I32WrapI64 => 60,
+
+            Self::I32TruncF32S => 61,
+
+            Self::CallIndirect(..) => 62,
+            // Only the default table is laid out in the zkWASM memory
+            Self::TableGet(table) | Self::TableSet(table) if table.to_u32() != 0 => {
+                return Err(UnsupportedInstruction(*self))
             }
-        }
+            Self::TableGet(..) => 63,
+            Self::TableSet(..) => 64,
+
+            Self::MemoryInit(..) => 65,
+            Self::MemoryInitStep => 66,
+            Self::DataDrop(..) => 67,
+
+            Self::RefFunc(..) => 68,
+
+            Self::F64Add => 69,
+            Self::F64Sub => 70,
+            Self::F64Mul => 71,
+            Self::F64Div => 72,
+
+            Self::I32TruncSatF32U => 73,
+            Self::I32TruncSatF64S => 74,
+            Self::I32TruncSatF64U => 75,
+            Self::I64TruncSatF32S => 76,
+            Self::I64TruncSatF32U => 77,
+            Self::I64TruncSatF64S => 78,
+            Self::I64TruncSatF64U => 79,
+
+            Self::I32TruncF32U => 80,
+            Self::I32TruncF64S => 81,
+            Self::I32TruncF64U => 82,
+            Self::I64TruncF32S => 83,
+            Self::I64TruncF32U => 84,
+            Self::I64TruncF64S => 85,
+            Self::I64TruncF64U => 86,
+
+            Self::I32Extend8S => 87,
+            Self::I32Extend16S => 88,
+            Self::I64Extend8S => 89,
+            Self::I64Extend16S => 90,
+            Self::I64Extend32S => 91,
+
+            Self::I64ExtendI32U => 92,
+
+            Self::CallInternal(..) => 0, // TODO: all 0 J_indexes
+            Self::Drop => 0,
+            Self::Return(..) => 54, // TODO
+            _ => return Err(UnsupportedInstruction(*self)),
+        };
+        Ok(j)
     }
 }
+
+/// Error returned when the zkVM has no circuit for an [`Instruction`], see
+/// [`Instruction::try_index_j`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UnsupportedInstruction(pub Instruction);
+
+impl Display for UnsupportedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unsupported instruction: {:?}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnsupportedInstruction {}
+
+impl HostError for UnsupportedInstruction {}
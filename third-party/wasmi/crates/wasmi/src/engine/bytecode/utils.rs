@@ -1,8 +1,9 @@
 use crate::engine::{func_builder::TranslationErrorInner, Instr, TranslationError};
 use core::fmt::{self, Display};
+use serde::{Deserialize, Serialize};
 
 /// A 32-bit encoded `f64` value.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct F64Const32(u32);
 
 impl F64Const32 {
@@ -24,7 +25,7 @@ impl F64Const32 {
 }
 
 /// A function index.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct FuncIdx(u32);
 
@@ -42,7 +43,7 @@ impl FuncIdx {
 }
 
 /// A table index.
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct TableIdx([u8; 4]);
 
@@ -60,7 +61,7 @@ impl TableIdx {
 }
 
 /// An index of a unique function signature.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct SignatureIdx(u32);
 
@@ -84,7 +85,7 @@ impl SignatureIdx {
 /// The depth refers to the relative position of a local
 /// variable on the value stack with respect to the height
 /// of the value stack at the time of access.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct LocalDepth(u32);
 
@@ -108,7 +109,7 @@ impl LocalDepth {
 /// Refers to a global variable of a [`Store`].
 ///
 /// [`Store`]: [`crate::Store`]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct GlobalIdx(u32);
 
@@ -132,7 +133,7 @@ impl GlobalIdx {
 /// Refers to a data segment of a [`Store`].
 ///
 /// [`Store`]: [`crate::Store`]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct DataSegmentIdx(u32);
 
@@ -156,7 +157,7 @@ impl DataSegmentIdx {
 /// Refers to a data segment of a [`Store`].
 ///
 /// [`Store`]: [`crate::Store`]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct ElementSegmentIdx(u32);
 
@@ -176,7 +177,7 @@ impl ElementSegmentIdx {
 /// The number of branches of an [`Instruction::BrTable`].
 ///
 /// [`Instruction::BrTable`]: [`super::Instruction::BrTable`]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct BranchTableTargets(u32);
 
@@ -203,7 +204,7 @@ impl BranchTableTargets {
 /// The accumulated fuel to execute a block via [`Instruction::ConsumeFuel`].
 ///
 /// [`Instruction::ConsumeFuel`]: [`super::Instruction::ConsumeFuel`]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct BlockFuel(u32);
 
@@ -249,7 +250,7 @@ impl BlockFuel {
 /// # Note
 ///
 /// Used to calculate the effective address of a linear memory access.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(transparent)]
 pub struct AddressOffset(u32);
 
@@ -270,7 +271,7 @@ impl AddressOffset {
 ///
 /// This defines how much the instruction pointer is offset
 /// upon taking the respective branch.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BranchOffset(i32);
 
 impl From<i32> for BranchOffset {
@@ -329,7 +330,7 @@ impl BranchOffset {
 }
 
 /// Defines how many stack values are going to be dropped and kept after branching.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct DropKeep {
     drop: u16,
     keep: u16,
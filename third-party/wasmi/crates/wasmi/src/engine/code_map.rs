@@ -1,10 +1,11 @@
 //! Datastructure to efficiently store function bodies and their instructions.
 
 use super::Instruction;
+use serde::{Deserialize, Serialize};
 use wasmi_arena::ArenaIndex;
 
 /// A reference to a compiled function stored in the [`CodeMap`] of an [`Engine`](crate::Engine).
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CompiledFunc(u32);
 
 impl CompiledFunc {
@@ -203,6 +204,15 @@ impl CodeMap {
         InstructionPtr::new(self.instrs[iref.to_usize()..].as_ptr())
     }
 
+    /// Returns an [`InstructionPtr`] to the instruction at index `pc` of the [`CodeMap`], if any.
+    #[inline]
+    pub fn instr_ptr_at(&self, pc: usize) -> Option<InstructionPtr> {
+        self.instrs
+            .get(pc..)
+            .filter(|instrs| !instrs.is_empty())
+            .map(|instrs| InstructionPtr::new(instrs.as_ptr()))
+    }
+
     /// Returns the [`FuncHeader`] of the [`CompiledFunc`].
     pub fn header(&self, func_body: CompiledFunc) -> &FuncHeader {
         &self.headers[func_body.into_usize()]
@@ -218,11 +228,17 @@ impl CodeMap {
         instrs.get(index)
     }
 
+    /// Returns the instructions of the [`CompiledFunc`].
+    pub fn instrs(&self, func_body: CompiledFunc) -> &[Instruction] {
+        let start = self.header(func_body).iref.to_usize();
+        let end = self.instr_end(func_body);
+        &self.instrs[start..end]
+    }
+
     /// Returns the `end` index of the instructions of [`CompiledFunc`].
     ///
     /// This is important to synthesize how many instructions there are in
     /// the function referred to by [`CompiledFunc`].
-    #[cfg(test)]
     fn instr_end(&self, func_body: CompiledFunc) -> usize {
         self.headers
             .get(func_body.into_usize() + 1)
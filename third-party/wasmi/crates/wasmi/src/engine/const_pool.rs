@@ -1,8 +1,9 @@
 use super::{func_builder::TranslationErrorInner, TranslationError};
 use alloc::collections::{btree_map, BTreeMap};
+use serde::{Deserialize, Serialize};
 use wasmi_core::UntypedValue;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct ConstRef(u32);
 
 impl TryFrom<usize> for ConstRef {
@@ -18,6 +19,12 @@ impl TryFrom<usize> for ConstRef {
     }
 }
 
+impl From<u32> for ConstRef {
+    fn from(index: u32) -> Self {
+        Self(index)
+    }
+}
+
 impl ConstRef {
     /// Returns the index of the [`ConstRef`] as `usize` value.
     pub fn to_usize(self) -> usize {
@@ -79,6 +86,11 @@ impl ConstPool {
         self.idx2const.get(cref.to_usize()).copied()
     }
 
+    /// Returns all constant values of the [`ConstPool`] ordered by their [`ConstRef`] index.
+    pub fn values(&self) -> &[UntypedValue] {
+        &self.idx2const
+    }
+
     /// Returns the read-only [`ConstPoolView`] of this [`ConstPool`].
     pub fn view(&self) -> ConstPoolView {
         ConstPoolView {
@@ -344,9 +344,10 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
 
                 Some(RunInstructionTracePre::Call { args })
             }
-            Instruction::CallIndirect(idx) => {
-                Some(RunInstructionTracePre::CallIndirect { idx: idx.to_u32() })
-            }
+            Instruction::CallIndirect(idx) => Some(RunInstructionTracePre::CallIndirect {
+                idx: idx.to_u32(),
+                index: self.sp.last().into(),
+            }),
 
             Instruction::Drop => None,
             Instruction::Select => Some(RunInstructionTracePre::Select {
@@ -639,6 +640,13 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                     pages: size,
                 })
             }
+            Instruction::TableGet(_) => Some(RunInstructionTracePre::TableGet {
+                index: self.sp.last().into(),
+            }),
+            Instruction::TableSet(_) => Some(RunInstructionTracePre::TableSet {
+                index: self.sp.nth_back(2).into(),
+                value: self.sp.nth_back(1).to_bits(),
+            }),
             Instruction::ConstRef(_) => None,
             Instruction::Const32(_) => None,
             Instruction::I64Const32(_) => None,
@@ -1067,8 +1075,11 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                 StepInfo::CallInternal { args }
             }
             Instruction::CallIndirect(..) => {
-                if let RunInstructionTracePre::CallIndirect { idx } = pre_status.unwrap() {
-                    StepInfo::CallIndirect { func_index: idx }
+                if let RunInstructionTracePre::CallIndirect { idx, index } = pre_status.unwrap() {
+                    StepInfo::CallIndirect {
+                        type_index: idx,
+                        index,
+                    }
                 } else {
                     unreachable!()
                 }
@@ -1322,6 +1333,28 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                     unreachable!()
                 }
             }
+            Instruction::TableGet(idx) => {
+                if let RunInstructionTracePre::TableGet { index } = pre_status.unwrap() {
+                    StepInfo::TableGet {
+                        idx: idx.to_u32(),
+                        index,
+                        value: self.sp.last().to_bits(),
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
+            Instruction::TableSet(idx) => {
+                if let RunInstructionTracePre::TableSet { index, value } = pre_status.unwrap() {
+                    StepInfo::TableSet {
+                        idx: idx.to_u32(),
+                        index,
+                        value,
+                    }
+                } else {
+                    unreachable!()
+                }
+            }
             Instruction::ConstRef(const_ref) => StepInfo::ConstRef {
                 value: self.const_pool.get(const_ref).unwrap_or_default().into(),
             },
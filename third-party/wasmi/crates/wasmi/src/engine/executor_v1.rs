@@ -6,7 +6,7 @@ use super::{
     ConstPoolView,
 };
 use crate::{
-    core::TrapCode,
+    core::{Trap, TrapCode},
     engine::{
         bytecode::{
             AddressOffset,
@@ -20,6 +20,7 @@ use crate::{
             LocalDepth,
             SignatureIdx,
             TableIdx,
+            UnsupportedInstruction,
         },
         cache::InstanceCache,
         code_map::{CodeMap, InstructionPtr},
@@ -31,9 +32,10 @@ use crate::{
     },
     error::EntityGrowError,
     func::FuncEntity,
+    module::DEFAULT_MEMORY_INDEX,
     store::ResourceLimiterRef,
     table::TableEntity,
-    tracer::WitnessVM,
+    tracer::{table_signature, WitnessVM, HOST_CALL_GLOBAL, MAX_KEEP_PER_STEP},
     FuelConsumptionMode,
     Func,
     FuncRef,
@@ -68,7 +70,7 @@ pub fn execute_wasm<'ctx, 'engine>(
     code_map: &'engine CodeMap,
     const_pool: ConstPoolView<'engine>,
     resource_limiter: &'ctx mut ResourceLimiterRef<'ctx>,
-) -> Result<WasmOutcome, TrapCode> {
+) -> Result<WasmOutcome, Trap> {
     Executor::new(
         ctx,
         cache,
@@ -101,7 +103,7 @@ pub fn execute_wasm_with_trace<'ctx, 'engine>(
     const_pool: ConstPoolView<'engine>,
     resource_limiter: &'ctx mut ResourceLimiterRef<'ctx>,
     tracer: Rc<RefCell<Tracer>>,
-) -> Result<WasmOutcome, TrapCode> {
+) -> Result<WasmOutcome, Trap> {
     Executor::new(
         ctx,
         cache,
@@ -216,7 +218,7 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
     fn execute(
         mut self,
         resource_limiter: &'ctx mut ResourceLimiterRef<'ctx>,
-    ) -> Result<WasmOutcome, TrapCode> {
+    ) -> Result<WasmOutcome, Trap> {
         use Instruction as Instr;
         // "Fetch, Decode, Execute" loop
         loop {
@@ -230,25 +232,29 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                     self.sync_stack_ptr();
                     let mut tracer = tracer.borrow_mut();
 
-                    if matches!(tracer.last(), Some(Instr::HostCallStackStep)) {
-                        tracer.execution_trace.extend(self.trace_host_call());
+                    if let Some(pre_memory) = tracer.take_host_call() {
+                        self.trace_host_call(&mut tracer, &pre_memory);
                     }
 
-                    // Capture/Trace the necessary pre-execution values
-                    vm = self.execute_instr_pre(self.value_stack.stack_ptr, self.pc());
+                    // Capture/Trace the necessary pre-execution values, the zkVM can not prove an
+                    // instruction it has no circuit for
+                    vm = self.execute_instr_pre(self.value_stack.stack_ptr, self.pc())?;
 
                     // handle tracing edge cases
                     match *instr {
                         Instr::MemoryCopy => {
-                            tracer
-                                .execution_trace
-                                .extend(self.trace_memory_copy(vm.clone()));
+                            tracer.extend(self.trace_memory_copy(vm.clone()));
+                        }
+                        Instr::MemoryInit(segment) => {
+                            let addr = tracer.data_segment_addr(segment);
+                            tracer.extend(self.trace_memory_init(vm.clone(), segment, addr));
+                        }
+                        Instr::DataDrop(segment) => {
+                            vm.I = tracer.data_segment_addr(segment);
                         }
                         Instr::BrAdjust(..) => {
                             let drop_keep = self.fetch_drop_keep(1);
-                            tracer
-                                .execution_trace
-                                .extend(self.trace_drop_keep(vm.clone(), drop_keep));
+                            tracer.extend(self.trace_drop_keep(vm.clone(), drop_keep));
                         }
                         _ => {}
                     }
@@ -267,19 +273,19 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                         tracer.set_max_sp(vm.pre_sp);
                         match *instr {
                             Instr::CallInternal(compiled_func) => {
-                                tracer
-                                    .execution_trace
-                                    .extend(self.trace_call_internal(vm.clone(), compiled_func));
+                                tracer.extend(self.trace_call_internal(vm.clone(), compiled_func));
                             }
                             Instr::Return(drop_keep) => {
-                                tracer
-                                    .execution_trace
-                                    .extend(self.trace_drop_keep(vm.clone(), drop_keep));
+                                tracer.extend(self.trace_drop_keep(vm.clone(), drop_keep));
+                            }
+                            Instr::ReturnIfNez(drop_keep) if vm.Y != 0 => {
+                                // A taken return drops & keeps once the condition is popped
+                                let mut init_vm = vm.clone();
+                                init_vm.pre_sp -= 1;
+                                tracer.extend(self.trace_drop_keep(init_vm, drop_keep));
                             }
                             Instr::MemoryFill => {
-                                tracer
-                                    .execution_trace
-                                    .extend(self.trace_memory_fill(vm.clone()));
+                                tracer.extend(self.trace_memory_fill(vm.clone()));
                             }
                             Instr::MemoryGrow => {
                                 let last = self.sp.last().to_bits() as i32;
@@ -291,7 +297,7 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                         }
                         // Get post instruction VM state changes
                         self.execute_instr_post(&mut vm, instr);
-                        tracer.execution_trace.push(vm);
+                        tracer.push(vm);
                     }
                 };
             }
@@ -308,13 +314,17 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                 Instr::BrTable(targets) => self.visit_br_table(targets),
                 Instr::Unreachable => self.visit_unreachable()?,
                 Instr::ConsumeFuel(block_fuel) => self.visit_consume_fuel(block_fuel)?,
+                // The return to the host is traced too, so the drop keeps of the results of the
+                // invoked function are proven
                 Instr::Return(drop_keep) => {
                     if let ReturnOutcome::Host = self.visit_ret(drop_keep) {
+                        trace_post_state_change!();
                         return Ok(WasmOutcome::Return);
                     }
                 }
                 Instr::ReturnIfNez(drop_keep) => {
                     if let ReturnOutcome::Host = self.visit_return_if_nez(drop_keep) {
+                        trace_post_state_change!();
                         return Ok(WasmOutcome::Return);
                     }
                 }
@@ -328,9 +338,30 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                     forward_call!(self.visit_return_call_indirect(func_type))
                 }
                 Instr::CallInternal(compiled_func) => self.visit_call_internal(compiled_func)?,
-                Instr::Call(func) => forward_call!(self.visit_call(func)),
+                Instr::Call(func) => {
+                    let outcome = self.visit_call(func);
+                    // Calls to host functions leave the executor here, so trace the call before
+                    // the host call steps emitted once the host function returns.
+                    if let (Some(tracer), Ok(CallOutcome::Call { .. })) =
+                        (self.tracer.clone(), &outcome)
+                    {
+                        let mut tracer = tracer.borrow_mut();
+                        tracer.set_max_sp(vm.pre_sp);
+                        tracer.push(vm.clone());
+                    }
+                    forward_call!(outcome)
+                }
                 Instr::CallIndirect(func_type) => {
-                    forward_call!(self.visit_call_indirect(func_type))
+                    // The callee's locals are zero-written over the popped table index, so the
+                    // call is traced ahead of them rather than post execution.
+                    if let Some(tracer) = self.tracer.clone() {
+                        self.trace_call_indirect(&mut vm, func_type);
+                        let mut tracer = tracer.borrow_mut();
+                        tracer.set_max_sp(vm.pre_sp);
+                        tracer.push(vm.clone());
+                    }
+                    forward_call!(self.visit_call_indirect(func_type));
+                    continue;
                 }
                 Instr::Drop => self.visit_drop(),
                 Instr::Select => self.visit_select(),
@@ -705,9 +736,7 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                 // trace the zero-writes produced from `prepare_wasm_call(..)`
                 if let Some(tracer) = self.tracer.clone() {
                     let mut tracer = tracer.borrow_mut();
-                    tracer
-                        .execution_trace
-                        .extend(self.trace_call(header.len_locals(), self.value_stack.stack_ptr));
+                    tracer.extend(self.trace_call(header.len_locals(), self.value_stack.stack_ptr));
                 };
                 self.value_stack.prepare_wasm_call(header)?;
                 self.sp = self.value_stack.stack_ptr();
@@ -1721,24 +1750,32 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
 
 impl<'ctx, 'engine> Executor<'ctx, 'engine> {
     /// Used to capture necessary values before state change
-    fn execute_instr_pre(&self, pre_sp: usize, pc: usize) -> WitnessVM {
+    ///
+    /// # Errors
+    ///
+    /// If the zkVM does not support the instruction.
+    fn execute_instr_pre(
+        &self,
+        pre_sp: usize,
+        pc: usize,
+    ) -> Result<WitnessVM, UnsupportedInstruction> {
         use Instruction as Instr;
         let mut vm = WitnessVM::default();
         let instruction = unsafe { &*self.ip.ptr };
         vm.pre_sp = pre_sp;
         vm.pc = pc;
         vm.instr = *instruction;
-        vm.J = instruction.index_j();
+        vm.J = instruction.try_index_j()?;
         match *instruction {
             Instr::LocalGet(..) => {}
             Instr::LocalSet(depth) | Instr::LocalTee(depth) => {
                 vm.I = depth.to_usize() as u64;
                 vm.Y = self.sp.last().to_bits();
             }
-            Instr::Const32(..)
-            | Instr::ConstRef(..)
-            | Instr::I64Const32(..)
-            | Instr::F64Const32(..) => {}
+            Instr::Const32(..) | Instr::I64Const32(..) | Instr::F64Const32(..) => {}
+            Instr::ConstRef(cref) => {
+                vm.I = cref.to_usize() as u64;
+            }
             Instr::BrIfEqz(branch_offset) | Instr::BrIfNez(branch_offset) => {
                 vm.Y = self.sp.nth_back(1).to_bits(); // condition value
                 vm.I = branch_offset.to_i32() as u64;
@@ -1765,6 +1802,9 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                 vm.Y = self.sp.nth_back(1).to_bits();
             }
             Instr::Return(..) => {}
+            Instr::ReturnIfNez(..) => {
+                vm.Y = self.sp.nth_back(1).to_bits(); // condition value
+            }
             Instr::CallInternal(..) => {}
             Instr::Drop => {}
             Instr::I32Store(offset)
@@ -1946,9 +1986,12 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                 vm.I = idx.to_u32() as u64;
                 vm.Y = value;
             }
-            Instr::BrTable(..) => {}
+            Instr::BrTable(targets) => {
+                vm.I = targets.to_usize() as u64; // number of targets, incl. the default
+                vm.Y = self.sp.last().to_bits(); // index
+            }
             Instr::BrAdjust(..) => {}
-            Instr::MemoryCopy => {
+            Instr::MemoryCopy | Instr::MemoryInit(..) => {
                 let num_bytes_to_copy = self.sp.nth_back(1).to_bits();
                 let src = self.sp.nth_back(2).to_bits();
                 let destination = self.sp.nth_back(3).to_bits();
@@ -1956,6 +1999,7 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                 vm.Y = src;
                 vm.X = destination;
             }
+            Instr::DataDrop(..) => {}
             Instr::MemoryFill => {
                 let size = self.sp.nth_back(1).to_bits();
                 let value = self.sp.nth_back(2).to_bits();
@@ -1964,18 +2008,29 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
                 vm.Y = value;
                 vm.X = offset;
             }
+            // Only the default table is laid out in the zkWASM memory
+            Instr::TableGet(table) | Instr::TableSet(table) if table.to_u32() != 0 => {
+                return Err(UnsupportedInstruction(*instruction))
+            }
+            Instr::TableGet(..) => {
+                vm.Y = self.sp.last().to_bits(); // index
+            }
+            Instr::TableSet(..) => {
+                let value = self.sp.nth_back(1);
+                vm.Y = self.sp.nth_back(2).to_bits(); // index
+                vm.X = value.to_bits();
+                vm.P = table_signature(self.ctx, value);
+            }
+            Instr::RefFunc(..) => {}
             Instr::Call(..) => {}
             Instr::CallIndirect(..) => {}
             Instr::MemorySize => {}
             Instr::MemoryGrow => {
                 vm.Y = self.sp.last().to_bits();
             }
-            _ => {
-                println!("Instruction not supported: {:?}", instruction);
-                unimplemented!();
-            }
+            _ => return Err(UnsupportedInstruction(*instruction)),
         }
-        vm
+        Ok(vm)
     }
 
     /// Trace the affected values in the VM state change post instruction
@@ -1983,13 +2038,13 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
     fn execute_instr_post(&mut self, vm: &mut WitnessVM, instr: &Instruction) {
         use Instruction as Instr;
         match *instr {
-            Instr::Const32(..)
-            | Instr::ConstRef(..)
-            | Instr::I64Const32(..)
-            | Instr::F64Const32(..) => {
+            Instr::Const32(..) | Instr::I64Const32(..) | Instr::F64Const32(..) => {
                 vm.I = self.sp.last().to_bits();
                 vm.P = vm.I;
             }
+            Instr::ConstRef(..) => {
+                vm.Y = self.sp.last().to_bits();
+            }
             Instr::LocalGet(local_depth) => {
                 vm.I = local_depth.to_usize() as u64;
                 vm.P = self.sp.last().to_bits();
@@ -2011,6 +2066,9 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
             | Instr::I64ShrS => {
                 vm.Z = self.sp.last().to_bits();
             }
+            Instr::ReturnIfNez(..) => {
+                vm.Z = self.pc() as u64; // pc to continue at, the caller's if the return is taken
+            }
             Instr::I32Store(..)
             | Instr::I32Store8(..)
             | Instr::I32Store16(..)
@@ -2215,6 +2273,15 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
             Instr::MemoryGrow => {
                 vm.P = self.sp.last().to_bits();
             }
+            Instr::TableGet(..) => {
+                vm.Z = self.sp.last().to_bits();
+            }
+            Instr::RefFunc(..) => {
+                vm.P = self.sp.last().to_bits(); // reference
+            }
+            Instr::BrTable(..) => {
+                vm.Z = self.pc() as u64; // pc of the selected target
+            }
             _ => {}
         }
     }
@@ -2223,7 +2290,7 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
     ///
     /// # Note
     ///
-    /// - Each VM state holds one read of the keep value and one write to the new address for the keep value.
+    /// - Each VM state holds up to [`MAX_KEEP_PER_STEP`] keep values, each read and written to its new address.
     ///
     /// - We don't need to trace the dropped values because they will be overwritten or shadowed due to how the stack pointer works.
     /// The stack doesn't actually pop values but instead shadows them by moving the stack pointer to precede those values.
@@ -2239,18 +2306,25 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
         // # How the drop keep is traced
         //
         // 1. Determine how many values need to be "kept".
-        // 2. Iterate over the keep values, tracing their value and their position in the stack.
-        //    The position is stored in `vm.P`, and the value is accessed from the stack via `self.sp.nth_back(keep as usize)`.
-        let keep = drop_keep.keep();
-        for keep in 1..=keep {
-            // Create a new drop_keep VM state, as we trace one keep value per step.
+        // 2. Iterate over the keep values in chunks of up to `MAX_KEEP_PER_STEP`, from the top of the stack down.
+        //    The position of the first keep value of the chunk is stored in `vm.P` and the number of keep values in `vm.Q`.
+        //    The values are accessed from the stack via `self.sp.nth_back(keep)` and stored in `vm.X`, `vm.Y` & `vm.Z`.
+        let keep = usize::from(drop_keep.keep());
+        for first in (1..=keep).step_by(MAX_KEEP_PER_STEP) {
+            // Create a new drop_keep VM state, as we trace one chunk of keep values per step.
             let mut vm = init_vm.clone();
 
-            // Get the position of the keep value in the stack before it is written to the new address.
-            vm.P = keep as u64;
+            // Get the position of the first keep value in the stack before it is written to the new address.
+            vm.P = first as u64;
 
-            // Get the value of the keep value.
-            vm.Y = self.sp.nth_back(keep as usize).to_bits();
+            // Get the keep values of the chunk, unused slots being 0.
+            let mut values = [0; MAX_KEEP_PER_STEP];
+            let count = MAX_KEEP_PER_STEP.min(keep - first + 1);
+            for (offset, value) in values[..count].iter_mut().enumerate() {
+                *value = self.sp.nth_back(first + offset).to_bits();
+            }
+            [vm.X, vm.Y, vm.Z] = values;
+            vm.Q = count as u64;
 
             // This is all we need to trace, so we add the VM state to the list of traced states.
             vms.push(vm);
@@ -2295,8 +2369,13 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
         init_vm.instr = Instr::MemoryFillStep;
         init_vm.J = init_vm.instr.index_j();
         let mut vms = Vec::new();
+        // One step per word overlapping [offset, offset + size)
+        let num_words = match size {
+            0 => 0,
+            _ => ((offset + size - 1) / 8 - offset / 8 + 1) as usize,
+        };
         let new_val_vec = self.read_memory(offset, size);
-        for (i, new_val) in new_val_vec.into_iter().enumerate() {
+        for (i, new_val) in new_val_vec.into_iter().take(num_words).enumerate() {
             let mut vm = init_vm.clone();
             vm.P = new_val;
             vm.X = offset / 8 + i as u64;
@@ -2313,18 +2392,94 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
         let destination = init_vm.X;
         init_vm.instr = Instr::MemoryCopyStep;
         init_vm.J = init_vm.instr.index_j();
-        let val_vec = self.read_memory(src, num_bytes_to_copy);
+        init_vm.Q = destination;
+        if num_bytes_to_copy == 0 {
+            return Vec::new();
+        }
+
+        // One step per word overlapping [destination, destination + num_bytes_to_copy). The copy
+        // is traced before it executes, so the written words are computed from the memory before
+        // the copy, as if through a temporary buffer. When the ranges overlap with
+        // `destination > src` the words are written from the last one, so that each step reads
+        // source words no earlier step overwrote.
+        let first_word = destination / 8;
+        let last_word = (destination + num_bytes_to_copy - 1) / 8;
+        let mut words = (first_word..=last_word).collect::<Vec<_>>();
+        if destination > src {
+            words.reverse();
+        }
+        let memory = self.cache.default_memory(self.ctx);
+        let memref = self.ctx.resolve_memory(&memory);
         let mut vms = Vec::new();
-        for (i, val) in val_vec.into_iter().enumerate() {
+        for word in words {
+            let mut buf = [0u8; 8];
+            memref.read(word as usize * 8, &mut buf).unwrap();
+            for (k, byte) in buf.iter_mut().enumerate() {
+                let addr = word * 8 + k as u64;
+                if (destination..destination + num_bytes_to_copy).contains(&addr) {
+                    let mut src_byte = [0u8; 1];
+                    memref
+                        .read((addr - destination + src) as usize, &mut src_byte)
+                        .unwrap();
+                    *byte = src_byte[0];
+                }
+            }
             let mut vm = init_vm.clone();
-            vm.P = val;
-            vm.Y = src / 8 + i as u64;
-            vm.X = destination / 8 + i as u64;
+            vm.P = u64::from_le_bytes(buf);
+            vm.X = word;
             vms.push(vm);
         }
         vms
     }
 
+    /// Special method to handle memory init: one step per word overlapping the range written, like
+    /// for memory copy but with the bytes copied from the data segment `segment`, laid out from
+    /// `addr` in the data segments of IS.
+    fn trace_memory_init(
+        &mut self,
+        mut init_vm: WitnessVM,
+        segment: DataSegmentIdx,
+        addr: u64,
+    ) -> Vec<WitnessVM> {
+        use Instruction as Instr;
+        let num_bytes = init_vm.I as usize;
+        let src = init_vm.Y as usize;
+        let destination = init_vm.X as usize;
+        init_vm.instr = Instr::MemoryInitStep;
+        init_vm.J = init_vm.instr.index_j();
+        init_vm.Q = destination as u64;
+        init_vm.Z = addr;
+        let (memory, data) = self
+            .cache
+            .get_default_memory_and_data_segment(self.ctx, segment);
+        // An out of bounds init traps, so there is nothing to trace
+        if num_bytes == 0
+            || src + num_bytes > data.len()
+            || destination + num_bytes > memory.len()
+        {
+            return Vec::new();
+        }
+
+        let first_word = destination / 8;
+        let last_word = (destination + num_bytes - 1) / 8;
+        (first_word..=last_word)
+            .map(|word| {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(&memory[word * 8..word * 8 + 8]);
+                for (k, byte) in buf.iter_mut().enumerate() {
+                    let addr = word * 8 + k;
+                    if (destination..destination + num_bytes).contains(&addr) {
+                        *byte = data[addr - destination + src];
+                    }
+                }
+                let mut vm = init_vm.clone();
+                vm.P = u64::from_le_bytes(buf);
+                vm.X = word as u64;
+                vm
+            })
+            .collect()
+    }
+
     /// Special method to handle CallInternal
     fn trace_call_internal(
         &self,
@@ -2345,6 +2500,28 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
         vms
     }
 
+    /// Special method to handle CallIndirect: the popped table index, the expected signature, the
+    /// signature word of the table entry the call goes through, see [`table_signature`], & the
+    /// reference of the function it calls
+    fn trace_call_indirect(&mut self, vm: &mut WitnessVM, func_type: SignatureIdx) {
+        let table = self.fetch_table_idx(1);
+        let table = self.cache.get_table(self.ctx, table);
+        let index: u32 = self.sp.last().into();
+        vm.Y = index.into();
+        let expected_signature = self
+            .ctx
+            .resolve_instance(self.cache.instance())
+            .get_signature(func_type.to_u32())
+            .unwrap_or_else(|| {
+                panic!("missing signature for call_indirect at index: {func_type:?}")
+            });
+        vm.I = self.ctx.engine().func_type_index(expected_signature).into();
+        // An out-of-bounds or `null` entry traps, which the zkWASM can not prove anyway
+        let entry = self.ctx.resolve_table(&table).get_untyped(index);
+        vm.P = entry.map_or(0, |entry| table_signature(self.ctx, entry));
+        vm.X = entry.map_or(0, |entry| entry.to_bits());
+    }
+
     /// Special method to handle Call instruction
     fn trace_call(&self, len: usize, pre_sp: usize) -> Vec<WitnessVM> {
         use Instruction as Instr;
@@ -2361,30 +2538,55 @@ impl<'ctx, 'engine> Executor<'ctx, 'engine> {
     }
 
     /// Special tracing method to handle host calls
-    fn trace_host_call(&mut self) -> Vec<WitnessVM> {
+    ///
+    /// Writes the words of linear memory the host call modified, given the linear memory before the
+    /// call `pre_memory`, & the globals after the host call, as a host call can modify them either
+    /// directly or by re-entering the guest through one of its exports. Words the host call leaves
+    /// unchanged already hold their value in the memory trace, so they need no write.
+    fn trace_host_call(&mut self, tracer: &mut Tracer, pre_memory: &[u8]) {
         use Instruction as Instr;
         let mut init_vm = WitnessVM::default();
         init_vm.instr = Instr::HostCallStep;
         init_vm.J = init_vm.instr.index_j();
-        let memory = self.cache.default_memory(self.ctx);
-        let memref = self.ctx.resolve_memory(&memory);
-        let pages: u32 = self
-            .ctx
-            .resolve_memory(self.cache.default_memory(self.ctx))
-            .current_pages()
-            .into();
         let mut vms = Vec::new();
-        for i in 0..(pages * 8192) {
+        // A module without linear memory only has its globals to write
+        let memory = self
+            .ctx
+            .resolve_instance(self.cache.instance())
+            .get_memory(DEFAULT_MEMORY_INDEX);
+        if let Some(memory) = memory {
+            let bytes = self.ctx.resolve_memory(&memory).data();
+            // A host call re-entering the guest can grow the linear memory
+            let mem_len = bytes.len() / 8;
+            if mem_len > tracer.IS_mem_len() {
+                tracer.memory_grow(((mem_len - tracer.IS_mem_len()) / 8192) as u64);
+            }
+            for (i, word) in bytes.chunks_exact(8).enumerate() {
+                // grown memory is zero before the call
+                let pre_word = pre_memory.get(i * 8..(i + 1) * 8).unwrap_or(&[0; 8]);
+                if word == pre_word {
+                    continue;
+                }
+                let mut vm = init_vm.clone();
+                // address
+                vm.Y = i as u64;
+                // value
+                vm.P = u64::from_le_bytes(word.try_into().unwrap());
+                vms.push(vm);
+            }
+        }
+        for i in 0..tracer.IS_globals_len() {
             let mut vm = init_vm.clone();
-            let mut buf = [0u8; 8];
-            memref.read(i as usize * 8, &mut buf).unwrap();
-            // address
+            vm.I = HOST_CALL_GLOBAL;
+            // global index
             vm.Y = i as u64;
-            // value
-            vm.P = u64::from_le_bytes(buf);
+            vm.P = self
+                .cache
+                .get_global(self.ctx, GlobalIdx::from(i as u32))
+                .to_bits();
             vms.push(vm);
         }
-        vms
+        tracer.extend(vms);
     }
 
     /// Get `usize` value for the pc
@@ -123,4 +123,18 @@ impl FuncTypeRegistry {
             .get(entity_index)
             .unwrap_or_else(|| panic!("failed to resolve stored function type: {entity_index:?}"))
     }
+
+    /// Returns the index of a deduplicated function type in the engine.
+    ///
+    /// # Note
+    ///
+    /// Equal function types share the same index, which is used by the zkWASM to check the
+    /// signature of an indirect call.
+    ///
+    /// # Panics
+    ///
+    /// If the deduplicated function type is not owned by the engine.
+    pub(crate) fn dedup_index(&self, func_type: &DedupFuncType) -> u32 {
+        self.unwrap_index(func_type.into_inner()).into_usize() as u32
+    }
 }
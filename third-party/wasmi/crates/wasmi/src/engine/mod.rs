@@ -27,6 +27,7 @@ pub use self::{
     bytecode::DropKeep,
     code_map::CompiledFunc,
     config::{Config, EngineBackend, FuelConsumptionMode},
+    const_pool::ConstRef,
     func_builder::{Instr, RelativeDepth, TranslationError},
     resumable::{ResumableCall, ResumableInvocation, TypedResumableCall, TypedResumableInvocation},
     stack::StackLimits,
@@ -37,7 +38,7 @@ use self::{
     bytecode::Instruction,
     cache::InstanceCache,
     code_map::CodeMap,
-    const_pool::{ConstPool, ConstPoolView, ConstRef},
+    const_pool::{ConstPool, ConstPoolView},
     executor::{execute_wasm, execute_wasm_with_trace_v0, WasmOutcome},
     func_types::FuncTypeRegistry,
     regmach::{
@@ -162,6 +163,15 @@ impl Engine {
         self.inner.resolve_func_type(func_type, f)
     }
 
+    /// Returns the index of a deduplicated function type in the [`Engine`].
+    ///
+    /// # Panics
+    ///
+    /// If the deduplicated function type is not owned by the engine.
+    pub(crate) fn func_type_index(&self, func_type: &DedupFuncType) -> u32 {
+        self.inner.res.read().func_types.dedup_index(func_type)
+    }
+
     /// Allocates a new constant value to the [`Engine`].
     ///
     /// # Errors
@@ -239,6 +249,24 @@ impl Engine {
         self.inner.resolve_func_2(func, f)
     }
 
+    /// Returns the maximum value stack height of the [`CompiledFunc`], its locals included.
+    ///
+    /// # Panics
+    ///
+    /// If [`CompiledFunc`] is invalid for [`Engine`].
+    pub(super) fn max_stack_height(&self, func: CompiledFunc) -> usize {
+        self.inner.max_stack_height(func)
+    }
+
+    /// Returns the instructions of the [`CompiledFunc`] which fail [`Instruction::try_index_j`].
+    ///
+    /// # Panics
+    ///
+    /// If [`CompiledFunc`] is invalid for [`Engine`].
+    pub(super) fn unsupported_instructions(&self, func: CompiledFunc) -> Vec<Instruction> {
+        self.inner.unsupported_instructions(func)
+    }
+
     /// Resolves the [`CompiledFunc`] to the underlying `wasmi` bytecode instructions.
     ///
     /// # Note
@@ -669,6 +697,31 @@ impl EngineInner {
         f(self.res.read().code_map_2.get(func))
     }
 
+    /// Returns the maximum value stack height of the [`CompiledFunc`], its locals included.
+    ///
+    /// # Panics
+    ///
+    /// If [`CompiledFunc`] is invalid for [`Engine`].
+    fn max_stack_height(&self, func: CompiledFunc) -> usize {
+        self.res.read().code_map.header(func).max_stack_height()
+    }
+
+    /// Returns the instructions of the [`CompiledFunc`] which fail [`Instruction::try_index_j`].
+    ///
+    /// # Panics
+    ///
+    /// If [`CompiledFunc`] is invalid for [`Engine`].
+    fn unsupported_instructions(&self, func: CompiledFunc) -> Vec<Instruction> {
+        self.res
+            .read()
+            .code_map
+            .instrs(func)
+            .iter()
+            .filter(|instr| instr.try_index_j().is_err())
+            .copied()
+            .collect()
+    }
+
     #[cfg(test)]
     fn resolve_instr(&self, func_body: CompiledFunc, index: usize) -> Option<Instruction> {
         self.res
@@ -1132,8 +1185,16 @@ impl<'engine> EngineExecutor<'engine> {
         self.stack.values.extend(params.clone().call_params());
         match ctx.as_context().store.inner.resolve_func(func) {
             FuncEntity::Wasm(wasm_func) => {
-                self.stack
-                    .prepare_wasm_call(wasm_func, &self.res.code_map)?;
+                let resume = tracer.borrow().resume_point().cloned();
+                match resume {
+                    Some(resume) => {
+                        self.stack
+                            .prepare_wasm_resume(wasm_func, &self.res.code_map, &resume)?
+                    }
+                    None => self
+                        .stack
+                        .prepare_wasm_call(wasm_func, &self.res.code_map)?,
+                }
                 // Get initial values for MCC
                 self.tracer_prepare_wasm_call(tracer.clone(), &self.stack.values.entries.to_vec());
                 self.execute_wasm_func_with_trace(ctx.as_context_mut(), tracer)?;
@@ -1150,7 +1211,17 @@ impl<'engine> EngineExecutor<'engine> {
         init_stack: &[UntypedValue],
     ) {
         let mut tracer = tracer.borrow_mut();
-        tracer.set_IS_stack(init_stack);
+        if tracer.len() == 0 {
+            tracer.set_IS_stack(init_stack);
+        } else {
+            // The execution traced before, i.e. the `start` function of the module, ran on the
+            // stack the call puts its params & locals on. Move it above them, so it does not
+            // overwrite the initial stack of the call in the memory trace.
+            let init_stack = &init_stack[..self.stack.values.stack_ptr];
+            tracer.shift_stack(init_stack.len());
+            tracer.set_IS_stack(init_stack);
+        }
+        tracer.set_IS_const_pool(self.res.const_pool.values());
     }
 
     /// Executes the given [`Func`] using the given `params`.
@@ -1343,6 +1414,9 @@ impl<'engine> EngineExecutor<'engine> {
                         &self.res.func_types,
                         tracer.clone(),
                     );
+                    // The host function may have re-entered the guest, which can grow the
+                    // memory & thus invalidate the cached memory bytes
+                    cache.reset();
                     if self.stack.frames.peek().is_some() {
                         // Case: There is a frame on the call stack.
                         //
@@ -1484,16 +1558,6 @@ impl<'engine> EngineExecutor<'engine> {
         cache: &mut InstanceCache,
         tracer: Rc<RefCell<Tracer>>,
     ) -> Result<WasmOutcome, Trap> {
-        /// Converts a [`TrapCode`] into a [`Trap`].
-        ///
-        /// This function exists for performance reasons since its `#[cold]`
-        /// annotation has severe effects on performance.
-        #[inline]
-        #[cold]
-        fn make_trap(code: TrapCode) -> Trap {
-            code.into()
-        }
-
         let (store_inner, mut resource_limiter) = ctx.store.store_inner_and_resource_limiter_ref();
         let value_stack = &mut self.stack.values;
         let call_stack = &mut self.stack.frames;
@@ -1510,7 +1574,6 @@ impl<'engine> EngineExecutor<'engine> {
             &mut resource_limiter,
             tracer,
         )
-        .map_err(make_trap)
     }
 
     /// Executes the given function `frame`.
@@ -10,15 +10,20 @@ use crate::{
     engine::{code_map::CodeMap, func_types::FuncTypeRegistry, FuncParams},
     etable::step_info::StepInfo,
     func::{HostFuncEntity, WasmFuncEntity},
+    module::DEFAULT_MEMORY_INDEX,
     AsContext,
     Instance,
     Instruction,
+    ResumePoint,
     StoreContextMut,
     Tracer,
     TracerV0,
     WitnessVM,
+    HOST_CALL_ARG,
+    HOST_CALL_READ,
+    HOST_CALL_RESULT,
 };
-use alloc::rc::Rc;
+use alloc::{collections::BTreeSet, rc::Rc};
 use core::{
     cell::RefCell,
     fmt::{self, Display},
@@ -161,6 +166,35 @@ impl Stack {
         Ok(())
     }
 
+    /// Prepares the [`Stack`] to resume a call to `wasm_func` in the middle of its body, at the
+    /// instruction and on the value stack of `resume`, below the reconstructed caller frames of
+    /// `resume`.
+    ///
+    /// # Errors
+    ///
+    /// - If the value stack or the call stack overflow.
+    /// - If a resumed instruction is out of the [`CodeMap`], which traps as unreachable code.
+    pub fn prepare_wasm_resume(
+        &mut self,
+        wasm_func: &WasmFuncEntity,
+        code_map: &CodeMap,
+        resume: &ResumePoint,
+    ) -> Result<(), TrapCode> {
+        let header = code_map.header(wasm_func.func_body());
+        self.reset();
+        self.values
+            .reserve(resume.stack.len() + header.max_stack_height())?;
+        self.values.extend(resume.stack.iter().copied());
+        let instance = wasm_func.instance();
+        for &pc in resume.frames.iter().chain(core::iter::once(&resume.pc)) {
+            let ip = code_map
+                .instr_ptr_at(pc)
+                .ok_or(TrapCode::UnreachableCodeReached)?;
+            self.frames.push(FuncFrame::new(ip, instance))?;
+        }
+        Ok(())
+    }
+
     /// Executes the given host function.
     ///
     /// # Errors
@@ -241,7 +275,7 @@ impl Stack {
     #[inline(always)]
     pub fn call_host_with_trace<T>(
         &mut self,
-        ctx: StoreContextMut<T>,
+        mut ctx: StoreContextMut<T>,
         host_func: HostFuncEntity,
         instance: Option<&Instance>,
         func_types: &FuncTypeRegistry,
@@ -262,6 +296,22 @@ impl Stack {
         let len_inputs = input_types.len();
         let len_outputs = output_types.len();
         let max_inout = len_inputs.max(len_outputs);
+
+        let mut stack_vms = Vec::new();
+
+        let mut init_vm = WitnessVM::default();
+        init_vm.instr = Instruction::HostCallStackStep;
+        init_vm.J = init_vm.instr.index_j();
+
+        // Read the arguments of the host call, so the zkVM can commit to them
+        for i in 0..len_inputs {
+            let mut vm = init_vm.clone();
+            vm.I = HOST_CALL_ARG;
+            vm.pre_sp = pre_sp - len_inputs + i;
+            vm.P = self.values.stack_ptr().nth_back(len_inputs - i).to_bits();
+            stack_vms.push(vm);
+        }
+
         self.values.reserve(max_inout)?;
         let delta = if len_outputs > len_inputs {
             // Note: We have to save the delta of values pushed
@@ -273,11 +323,6 @@ impl Stack {
         } else {
             0
         };
-        let mut stack_vms = Vec::new();
-
-        let mut init_vm = WitnessVM::default();
-        init_vm.instr = Instruction::HostCallStackStep;
-        init_vm.J = init_vm.instr.index_j();
 
         for i in 0..delta {
             let mut vm = init_vm.clone();
@@ -290,6 +335,19 @@ impl Stack {
             len_inputs,
             len_outputs,
         );
+        // Keep the linear memory before the call, so only the words the host function writes are
+        // traced after it
+        let store = &ctx.as_context().store.inner;
+        let pre_memory = instance
+            .and_then(|instance| {
+                store
+                    .resolve_instance(instance)
+                    .get_memory(DEFAULT_MEMORY_INDEX)
+            })
+            .map(|memory| store.resolve_memory(&memory).data().to_vec())
+            .unwrap_or_default();
+        // Drop the reads of host functions called without tracing
+        ctx.store.inner.take_host_memory_reads();
         // Now we are ready to perform the host function call.
         // Note: We need to clone the host function due to some borrowing issues.
         //       This should not be a big deal since host functions usually are cheap to clone.
@@ -298,17 +356,17 @@ impl Stack {
             .store
             .resolve_trampoline(host_func.trampoline())
             .clone();
-        trampoline
-            .call(ctx, instance, params_results)
-            .map_err(|error| {
-                // Note: We drop the values that have been temporarily added to
-                //       the stack to act as parameter and result buffer for the
-                //       called host function. Since the host function failed we
-                //       need to clean up the temporary buffer values here.
-                //       This is required for resumable calls to work properly.
-                self.values.drop(delta);
-                error
-            })?;
+        let result = trampoline.call(ctx.as_context_mut(), instance, params_results);
+        let reads = ctx.store.inner.take_host_memory_reads();
+        result.map_err(|error| {
+            // Note: We drop the values that have been temporarily added to
+            //       the stack to act as parameter and result buffer for the
+            //       called host function. Since the host function failed we
+            //       need to clean up the temporary buffer values here.
+            //       This is required for resumable calls to work properly.
+            self.values.drop(delta);
+            error
+        })?;
         // If the host functions returns fewer results than it receives parameters
         // the value stack needs to be shrinked for the delta.
         if len_outputs < len_inputs {
@@ -316,6 +374,25 @@ impl Stack {
             self.values.drop(delta);
         }
 
+        // Read the words of linear memory the host function read, with their values before the
+        // call, so its input is bound to the memory the guest wrote
+        let words = reads
+            .into_iter()
+            .flat_map(|(offset, len)| offset / 8..(offset + len).div_ceil(8))
+            .collect::<BTreeSet<_>>();
+        for word in words {
+            let Some(bytes) = pre_memory.get(word * 8..(word + 1) * 8) else {
+                break;
+            };
+            let mut vm = WitnessVM::default();
+            vm.instr = Instruction::HostCallStep;
+            vm.J = vm.instr.index_j();
+            vm.I = HOST_CALL_READ;
+            vm.Y = word as u64;
+            vm.P = u64::from_le_bytes(bytes.try_into().unwrap());
+            stack_vms.push(vm);
+        }
+
         let mut stack_witness_vm = WitnessVM::default();
         stack_witness_vm.instr = Instruction::HostCallStackStep;
         stack_witness_vm.J = stack_witness_vm.instr.index_j();
@@ -323,23 +400,21 @@ impl Stack {
         let post_sp = self.values.stack_ptr();
         let post_sp = post_sp.offset_from(self.values.base_ptr()) as usize;
 
-        for i in 1..=max_inout {
+        // Write the top `max_inout` stack values from the bottom up, so the results of the host call
+        // are written in order
+        for i in (1..=max_inout).rev() {
             let mut vm = stack_witness_vm.clone();
+            if i <= len_outputs {
+                vm.I = HOST_CALL_RESULT;
+            }
             vm.pre_sp = post_sp - i;
             vm.P = self.values.stack_ptr().nth_back(i).to_bits();
             stack_vms.push(vm);
         }
 
-        // We add a dummy write to indicate to tracer that a host function was called
-        if stack_vms.is_empty() {
-            let mut vm = stack_witness_vm.clone();
-            vm.pre_sp = post_sp - 1;
-            vm.P = self.values.stack_ptr().nth_back(1).to_bits();
-            stack_vms.push(vm);
-        }
-
         let mut tracer = tracer.borrow_mut();
-        tracer.execution_trace.extend(stack_vms);
+        tracer.extend(stack_vms);
+        tracer.set_host_call(pre_memory);
         // At this point the host function has been called and has directly
         // written its results into the value stack so that the last entries
         // in the value stack are the result values of the host function call.
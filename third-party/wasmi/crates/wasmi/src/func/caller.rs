@@ -84,6 +84,15 @@ impl<'a, T> Caller<'a, T> {
     pub fn consume_fuel(&mut self, delta: u64) -> Result<u64, FuelError> {
         self.ctx.store.consume_fuel(delta)
     }
+
+    /// Records that the host function read `len` bytes of the default linear memory at `offset`,
+    /// e.g. the input of a precompile.
+    ///
+    /// When executing with a [`Tracer`](crate::Tracer) the words of linear memory the bytes are in
+    /// are read in the execution trace, with their values before the host call.
+    pub fn trace_memory_read(&mut self, offset: usize, len: usize) {
+        self.ctx.store.inner.push_host_memory_read(offset, len);
+    }
 }
 
 impl<T> AsContext for Caller<'_, T> {
@@ -119,11 +119,17 @@ pub use self::{
             AddressOffset,
             BranchOffset,
             BranchTableTargets,
+            DataSegmentIdx as BCDataSegmentIdx,
             DropKeep,
+            FuncIdx as BCFuncIdx,
             GlobalIdx as BCGlobalIdx,
             Instruction,
+            SignatureIdx,
+            TableIdx as BCTableIdx,
+            UnsupportedInstruction,
         },
         Config,
+        ConstRef,
         Engine,
         EngineBackend,
         FuelConsumptionMode,
@@ -164,8 +170,18 @@ pub use self::{
     },
     store::{AsContext, AsContextMut, Store, StoreContext, StoreContextMut},
     table::{Table, TableType},
-    tracer::{Tracer, WitnessVM},
-    tracer_v0::{continuations, etable, mtable, TraceSliceValues, TracerV0},
+    tracer::{
+        ResumePoint,
+        TraceHook,
+        Tracer,
+        WitnessVM,
+        HOST_CALL_ARG,
+        HOST_CALL_GLOBAL,
+        HOST_CALL_READ,
+        HOST_CALL_RESULT,
+        MAX_KEEP_PER_STEP,
+    },
+    tracer_v0::{continuations, etable, mtable, TraceSliceValues, TracerError, TracerV0},
     value::Value,
 };
 use self::{
@@ -129,11 +129,23 @@ impl Module {
             tracer.push_global(i, &globalref, &context);
         }
 
+        if self.len_tables() > 0 {
+            tracer.push_init_table(builder.get_table(0), &context);
+        }
+
         if self.memories.len() > 0 {
             let memref = builder.get_memory(0);
+            tracer.write_memory_images(memref, &mut context)?;
             tracer.push_init_memory(memref, &context);
         }
 
+        for segment in &self.data_segments[..] {
+            match segment.kind() {
+                DataSegmentKind::Passive => tracer.push_data_segment(segment.bytes()),
+                DataSegmentKind::Active(_) => tracer.push_data_segment(&[]),
+            }
+        }
+
         // At this point the module instantiation is nearly done.
         // The only thing that is missing is to run the `start` function.
         Ok(InstancePre::new(handle, builder))
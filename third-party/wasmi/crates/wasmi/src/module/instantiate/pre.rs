@@ -1,5 +1,7 @@
 use super::InstantiationError;
-use crate::{module::FuncIdx, AsContextMut, Error, Instance, InstanceEntityBuilder};
+use crate::{module::FuncIdx, AsContextMut, Error, Instance, InstanceEntityBuilder, Tracer};
+use alloc::rc::Rc;
+use core::cell::RefCell;
 
 /// A partially instantiated [`Instance`] where the `start` function has not yet been executed.
 ///
@@ -59,6 +61,43 @@ impl InstancePre {
         Ok(self.handle)
     }
 
+    /// Runs the `start` function of the [`Instance`], tracing its execution, and returns its
+    /// handle.
+    ///
+    /// # Note
+    ///
+    /// This finishes the instantiation procedure.
+    ///
+    /// # Errors
+    ///
+    /// If executing the `start` function traps.
+    ///
+    /// # Panics
+    ///
+    /// If the `start` function is invalid albeit successful validation.
+    pub fn start_with_trace(
+        self,
+        mut context: impl AsContextMut,
+        tracer: Rc<RefCell<Tracer>>,
+    ) -> Result<Instance, Error> {
+        let opt_start_index = self.start_fn();
+        context
+            .as_context_mut()
+            .store
+            .inner
+            .initialize_instance(self.handle, self.builder.finish());
+        if let Some(start_index) = opt_start_index {
+            let start_func = self
+                .handle
+                .get_func_by_index(&mut context, start_index)
+                .unwrap_or_else(|| {
+                    panic!("encountered invalid start function after validation: {start_index}")
+                });
+            start_func.call_with_trace(context.as_context_mut(), &[], &mut [], tracer)?
+        }
+        Ok(self.handle)
+    }
+
     /// Finishes instantiation ensuring that no `start` function exists.
     ///
     /// # Errors
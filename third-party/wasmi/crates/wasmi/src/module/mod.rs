@@ -44,11 +44,12 @@ use crate::{
     ExternType,
     FuncType,
     GlobalType,
+    Instruction,
     MemoryType,
     TableType,
 };
 use alloc::{collections::BTreeMap, sync::Arc};
-use core::{iter, slice::Iter as SliceIter};
+use core::{iter, mem, slice::Iter as SliceIter};
 
 /// A parsed and validated WebAssembly module.
 #[derive(Debug)]
@@ -189,6 +190,79 @@ impl Module {
         self.globals.len()
     }
 
+    /// Returns the number of global variables of the [`Module`], imported ones included.
+    pub fn len_all_globals(&self) -> usize {
+        self.globals.len()
+    }
+
+    /// Returns the [`MemoryType`] of the default linear memory of the [`Module`], if any.
+    pub fn default_memory(&self) -> Option<MemoryType> {
+        self.memories.get(DEFAULT_MEMORY_INDEX as usize).copied()
+    }
+
+    /// Returns the [`TableType`] of the default table of the [`Module`], if any.
+    pub fn default_table(&self) -> Option<TableType> {
+        self.tables.first().copied()
+    }
+
+    /// Returns the number of words the tracer lays the data segments of the [`Module`] out in,
+    /// see [`Tracer::push_data_segment`].
+    ///
+    /// [`Tracer::push_data_segment`]: crate::Tracer::push_data_segment
+    pub fn len_data_words(&self) -> usize {
+        self.data_segments
+            .iter()
+            .map(|segment| match segment.kind() {
+                DataSegmentKind::Passive => 2 + (segment.bytes().len() + 7) / 8,
+                DataSegmentKind::Active(_) => 2,
+            })
+            .sum()
+    }
+
+    /// Returns an upper bound on the value stack height of an execution of the [`Module`].
+    ///
+    /// This is the sum of the maximum stack heights of its internal functions, plus the largest
+    /// amount of parameters a function takes, i.e. the arguments a host pushes to call it.
+    ///
+    /// # Note
+    ///
+    /// The bound only holds for executions in which no function is on the call stack twice, i.e.
+    /// without recursion.
+    pub fn max_stack_height(&self) -> usize {
+        let max_params = self
+            .funcs
+            .iter()
+            .map(|func_type| {
+                self.engine
+                    .resolve_func_type(func_type, |func_type| func_type.params().len())
+            })
+            .max()
+            .unwrap_or(0);
+        self.internal_funcs()
+            .map(|(_, func_body)| self.engine.max_stack_height(func_body))
+            .sum::<usize>()
+            + max_params
+    }
+
+    /// Returns the instructions of the [`Module`] the zkVM has no circuit for, i.e. which fail
+    /// [`Instruction::try_index_j`], one per opcode in the order they are compiled in.
+    ///
+    /// [`Instruction::try_index_j`]: crate::Instruction::try_index_j
+    pub fn unsupported_instructions(&self) -> Vec<Instruction> {
+        let mut unsupported = Vec::<Instruction>::new();
+        for (_, func_body) in self.internal_funcs() {
+            for instr in self.engine.unsupported_instructions(func_body) {
+                if !unsupported
+                    .iter()
+                    .any(|kind| mem::discriminant(kind) == mem::discriminant(&instr))
+                {
+                    unsupported.push(instr);
+                }
+            }
+        }
+        unsupported
+    }
+
     /// Returns a slice to the function types of the [`Module`].
     ///
     /// # Note
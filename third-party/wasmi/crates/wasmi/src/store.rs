@@ -29,8 +29,10 @@ use crate::{
     TableEntity,
     TableIdx,
 };
+use alloc::vec::Vec;
 use core::{
     fmt::{self, Debug},
+    mem,
     sync::atomic::{AtomicU32, Ordering},
 };
 use wasmi_arena::{Arena, ArenaIndex, GuardedEntity};
@@ -149,6 +151,11 @@ pub struct StoreInner {
     engine: Engine,
     /// The fuel of the [`Store`].
     fuel: Fuel,
+    /// Byte ranges of the default linear memory the running host function read, as
+    /// `(offset, len)`, see [`Caller::trace_memory_read`].
+    ///
+    /// [`Caller::trace_memory_read`]: crate::Caller::trace_memory_read
+    host_memory_reads: Vec<(usize, usize)>,
 }
 
 #[test]
@@ -271,9 +278,21 @@ impl StoreInner {
             elems: Arena::new(),
             extern_objects: Arena::new(),
             fuel: Fuel::default(),
+            host_memory_reads: Vec::new(),
         }
     }
 
+    /// Records that the running host function read `len` bytes of the default linear memory at
+    /// `offset`.
+    pub(crate) fn push_host_memory_read(&mut self, offset: usize, len: usize) {
+        self.host_memory_reads.push((offset, len));
+    }
+
+    /// Takes the byte ranges of the default linear memory host functions read since the last call.
+    pub(crate) fn take_host_memory_reads(&mut self) -> Vec<(usize, usize)> {
+        mem::take(&mut self.host_memory_reads)
+    }
+
     /// Returns the [`Engine`] that this store is associated with.
     pub fn engine(&self) -> &Engine {
         &self.engine
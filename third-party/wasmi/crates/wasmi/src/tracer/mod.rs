@@ -1,10 +1,26 @@
 #![allow(non_snake_case)]
 
-use core::cmp;
+use alloc::sync::Arc;
+use core::{
+    cmp,
+    fmt::{self, Debug},
+};
 
-use wasmi_core::UntypedValue;
+use serde::{Deserialize, Serialize};
+use spin::Mutex;
+use wasmi_core::{UntypedValue, ValueType};
 
-use crate::{engine::bytecode::Instruction, AsContext, Global, Memory};
+use crate::{
+    engine::bytecode::{DataSegmentIdx, Instruction},
+    memory::MemoryError,
+    AsContext,
+    AsContextMut,
+    FuncRef,
+    Global,
+    Memory,
+    StoreInner,
+    Table,
+};
 
 #[derive(Debug, Clone, Default)]
 /// Hold the execution trace from VM execution and manages other miscellaneous
@@ -21,6 +37,56 @@ pub struct Tracer {
     IS_mem: Vec<(usize, u64, u64)>,
     /// Initial set of globals
     IS_globals: Vec<(usize, u64, u64)>,
+    /// Initial page count of linear memory, tracked in IS for `memory.size` & `memory.grow`
+    IS_pages: u64,
+    /// Initial set of the default function table, two words per entry: the reference as pushed on
+    /// the stack, then its signature word, see [`table_signature`]
+    IS_table: Vec<(usize, u64, u64)>,
+    /// Initial set of the data segments, see [`Tracer::push_data_segment`]
+    IS_data: Vec<(usize, u64, u64)>,
+    /// Offset in the data segments IS of each data segment, by index
+    data_segments: Vec<u64>,
+    /// Initial set of the const pool, i.e. constants too large to be inlined in the bytecode
+    IS_const_pool: Vec<(usize, u64, u64)>,
+    /// Callback invoked for each instruction as it is traced
+    hook: Option<TraceHook>,
+    /// Byte offsets & contents written to linear memory before its initial contents are traced
+    memory_images: Vec<(usize, Vec<u8>)>,
+    /// State to resume the invoked function from, instead of its entry
+    resume: Option<ResumePoint>,
+    /// Set when a host function returns to the linear memory before the call, until the words of
+    /// it the host function wrote & the globals are traced before the next instruction
+    host_call: Option<Vec<u8>>,
+}
+
+/// The state to resume a traced function from in the middle of its body, e.g. where a prior
+/// shard of its execution halted.
+#[derive(Debug, Clone)]
+pub struct ResumePoint {
+    /// Index in the code map of the instruction to resume at
+    pub(crate) pc: usize,
+    /// The value stack to resume on, incl. the locals of every frame
+    pub(crate) stack: Vec<UntypedValue>,
+    /// Indices of the instructions the callers of the resumed frame return to, outermost first
+    pub(crate) frames: Vec<usize>,
+}
+
+/// A callback invoked with the index in the execution trace & the [`WitnessVM`] of each traced
+/// instruction, e.g. to gather statistics over an execution as it runs.
+#[derive(Clone)]
+pub struct TraceHook(Arc<Mutex<dyn FnMut(usize, &WitnessVM) + Send>>);
+
+impl TraceHook {
+    /// Creates a new [`TraceHook`] invoking `hook`
+    pub fn new(hook: impl FnMut(usize, &WitnessVM) + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(hook)))
+    }
+}
+
+impl Debug for TraceHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TraceHook")
+    }
 }
 
 impl Tracer {
@@ -35,6 +101,22 @@ impl Tracer {
         self.execution_trace.len()
     }
 
+    /// Get the number of steps traced, i.e. the length of the execution trace
+    pub fn step_count(&self) -> usize {
+        self.execution_trace.len()
+    }
+
+    /// Count how many times each opcode was traced, keyed by its [`Instruction::index_j`]. Opcodes
+    /// sharing a J index, e.g. `i64.and` & `i64.or`, share a bucket, and untraced ones are absent.
+    #[cfg(feature = "std")]
+    pub fn opcode_histogram(&self) -> std::collections::HashMap<u64, usize> {
+        let mut histogram = std::collections::HashMap::new();
+        for vm in &self.execution_trace {
+            *histogram.entry(vm.instr.index_j()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
     /// Get last instruction traced
     pub fn last(&self) -> Option<Instruction> {
         self.execution_trace.last().map(|witness| witness.instr)
@@ -45,6 +127,83 @@ impl Tracer {
         self.execution_trace
     }
 
+    /// Set the callback invoked for each instruction as it is traced
+    pub fn set_hook(&mut self, hook: TraceHook) {
+        self.hook = Some(hook);
+    }
+
+    /// Set the bytes written to linear memory at the given byte offsets on instantiation, after
+    /// the data segments and before the initial contents of linear memory are traced
+    pub fn set_memory_images(&mut self, memory_images: Vec<(usize, Vec<u8>)>) {
+        self.memory_images = memory_images;
+    }
+
+    /// Resume the invoked function at the instruction `pc`, on the value stack `stack` and below
+    /// the callers returning to the instructions `frames`, outermost first. All frames are in the
+    /// instance of the invoked function, and `stack` replaces the call's params & locals.
+    pub fn set_resume(&mut self, pc: usize, stack: Vec<u64>, frames: Vec<usize>) {
+        self.resume = Some(ResumePoint {
+            pc,
+            stack: stack.into_iter().map(UntypedValue::from).collect(),
+            frames,
+        });
+    }
+
+    /// The state to resume the invoked function from, if it is not run from its entry
+    pub(crate) fn resume_point(&self) -> Option<&ResumePoint> {
+        self.resume.as_ref()
+    }
+
+    /// Write the memory images to the linear memory `memref`
+    ///
+    /// # Errors
+    ///
+    /// If an image does not fit in linear memory.
+    pub(crate) fn write_memory_images(
+        &self,
+        memref: Memory,
+        mut context: impl AsContextMut,
+    ) -> Result<(), MemoryError> {
+        for (offset, bytes) in &self.memory_images {
+            memref.write(&mut context, *offset, bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Push a traced instruction to the execution trace
+    pub(crate) fn push(&mut self, vm: WitnessVM) {
+        if let Some(TraceHook(hook)) = &self.hook {
+            (hook.lock())(self.execution_trace.len(), &vm);
+        }
+        self.execution_trace.push(vm);
+    }
+
+    /// Push traced instructions to the execution trace
+    pub(crate) fn extend(&mut self, vms: impl IntoIterator<Item = WitnessVM>) {
+        if self.hook.is_none() {
+            self.execution_trace.extend(vms);
+            return;
+        }
+        for vm in vms {
+            self.push(vm);
+        }
+    }
+
+    /// Record that a host function returned, given the linear `memory` before the call, so the
+    /// words of it & the globals the call may have written are traced before the next instruction.
+    ///
+    /// A host call taking & returning no values pushes no [`Instruction::HostCallStackStep`], e.g.
+    /// on an empty value stack, so it can not be told apart from the trace alone.
+    pub(crate) fn set_host_call(&mut self, memory: Vec<u8>) {
+        self.host_call = Some(memory);
+    }
+
+    /// Take the linear memory before the host function call which returned since the last check,
+    /// if any
+    pub(crate) fn take_host_call(&mut self) -> Option<Vec<u8>> {
+        self.host_call.take()
+    }
+
     /// Getter for max_sp
     pub fn max_sp(&self) -> usize {
         self.max_sp
@@ -64,6 +223,17 @@ impl Tracer {
         IS_stack
     }
 
+    /// Move the traced execution up the stack by `offset` values.
+    ///
+    /// Stack addresses of the memory trace are all relative to [`WitnessVM::pre_sp`], so this moves
+    /// every stack access of the traced execution.
+    pub(crate) fn shift_stack(&mut self, offset: usize) {
+        for vm in &mut self.execution_trace {
+            vm.pre_sp += offset;
+        }
+        self.max_sp += offset;
+    }
+
     /// Setter for IS
     pub(crate) fn set_IS_stack(&mut self, stack: &[UntypedValue]) {
         self.IS_stack = stack
@@ -83,17 +253,66 @@ impl Tracer {
         self.IS_mem.len()
     }
 
+    /// Get IS_globals len
+    pub fn IS_globals_len(&self) -> usize {
+        self.IS_globals.len()
+    }
+
+    /// Get IS_table len, i.e. the number of entries of the default function table
+    pub fn IS_table_len(&self) -> usize {
+        self.IS_table.len() / 2
+    }
+
+    /// Get IS_data len, i.e. the number of words the data segments are laid out in
+    pub fn IS_data_len(&self) -> usize {
+        self.IS_data.len()
+    }
+
+    /// Get the offset in the data segments IS of the data segment `segment`
+    pub(crate) fn data_segment_addr(&self, segment: DataSegmentIdx) -> u64 {
+        self.data_segments[segment.to_u32() as usize]
+    }
+
+    /// Setter for the const pool IS
+    pub(crate) fn set_IS_const_pool(&mut self, const_pool: &[UntypedValue]) {
+        self.IS_const_pool = const_pool
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i, v.to_bits(), 0))
+            .collect();
+    }
+
     /// Getter for IS
     pub fn IS(&self) -> Vec<(usize, u64, u64)> {
         let mut IS = self.IS_stack();
         let stack_len = IS.len();
         let linear_mem_len = self.IS_mem.len();
+        let globals_len = self.IS_globals.len();
         IS.extend(self.IS_mem.iter().map(|(i, v, _)| (*i + stack_len, *v, 0)));
         IS.extend(
             self.IS_globals
                 .iter()
                 .map(|(i, v, _)| (*i + stack_len + linear_mem_len, *v, 0)),
         );
+        let pages_addr = stack_len + linear_mem_len + globals_len;
+        IS.push((pages_addr, self.IS_pages, 0));
+        IS.extend(
+            self.IS_table
+                .iter()
+                .map(|(i, v, _)| (*i + pages_addr + 1, *v, 0)),
+        );
+        let data_addr = pages_addr + 1 + self.IS_table.len();
+        IS.extend(
+            self.IS_data
+                .iter()
+                .map(|(i, v, _)| (*i + data_addr, *v, 0)),
+        );
+        let const_pool_addr = data_addr + self.IS_data.len();
+        IS.extend(
+            self.IS_const_pool
+                .iter()
+                .map(|(i, v, _)| (*i + const_pool_addr, *v, 0)),
+        );
 
         IS
     }
@@ -101,6 +320,7 @@ impl Tracer {
     /// Push initial heap/linear WASM memory to tracer for MCC
     pub fn push_init_memory(&mut self, memref: Memory, context: impl AsContext) {
         let pages: u32 = memref.ty(&context).initial_pages().into();
+        self.IS_pages = pages.into();
         for i in 0..(pages * 8192) {
             let mut buf = [0u8; 8];
             memref
@@ -118,15 +338,83 @@ impl Tracer {
         }
     }
 
+    /// Push the next data segment of the module, of contents `bytes`, to tracer for MCC. Active
+    /// segments are dropped on instantiation, so they are pushed empty.
+    ///
+    /// A segment is laid out as its length in bytes, which `data.drop` zeroes, then its bytes in
+    /// 8-byte words and a zero word, so the words `memory.init` reads its bytes from are all in
+    /// the segment.
+    pub fn push_data_segment(&mut self, bytes: &[u8]) {
+        let addr = self.IS_data.len();
+        self.data_segments.push(addr as u64);
+        self.IS_data.push((addr, bytes.len() as u64, 0));
+        let words = bytes.chunks(8).chain([&[][..]]).map(|chunk| {
+            let mut buf = [0u8; 8];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            u64::from_le_bytes(buf)
+        });
+        for (i, word) in words.enumerate() {
+            self.IS_data.push((addr + 1 + i, word, 0));
+        }
+    }
+
     /// Push globals
     pub fn push_global(&mut self, globalidx: usize, globalref: &Global, context: impl AsContext) {
         let value = UntypedValue::from(globalref.get(&context));
         self.IS_globals.push((globalidx, value.to_bits(), 0));
     }
+
+    /// Push the initial entries of the default function table to tracer for MCC. A table of
+    /// `externref`s is not traced, as `call_indirect` can not go through it.
+    pub fn push_init_table(&mut self, table: Table, context: impl AsContext) {
+        let store = &context.as_context().store.inner;
+        let table = store.resolve_table(&table);
+        if table.ty().element() != ValueType::FuncRef {
+            return;
+        }
+        self.IS_table = (0..table.size())
+            .flat_map(|i| {
+                let entry = table.get_untyped(i).unwrap_or_default();
+                let i = 2 * i as usize;
+                [
+                    (i, entry.to_bits(), 0),
+                    (i + 1, table_signature(store, entry), 0),
+                ]
+            })
+            .collect();
+    }
 }
 
+/// The signature word of the function table entry `value`, which the zkWASM checks an indirect
+/// call against: `signature + 1`, with `signature` the index of the deduplicated type of the
+/// function in the engine. A `null` entry is 0, which no indirect call matches.
+pub(crate) fn table_signature(store: &StoreInner, value: UntypedValue) -> u64 {
+    FuncRef::from(value).func().map_or(0, |func| {
+        let ty = store.resolve_func(func).ty_dedup();
+        u64::from(store.engine().func_type_index(ty)) + 1
+    })
+}
+
+/// [`WitnessVM::I`] of an [`Instruction::HostCallStackStep`] reading an argument of the host call
+pub const HOST_CALL_ARG: u64 = 1;
+
+/// [`WitnessVM::I`] of an [`Instruction::HostCallStackStep`] writing a result of the host call
+pub const HOST_CALL_RESULT: u64 = 2;
+
+/// [`WitnessVM::I`] of an [`Instruction::HostCallStep`] writing a global, rather than a word of
+/// linear memory, after a host call which may have re-entered the guest
+pub const HOST_CALL_GLOBAL: u64 = 3;
+
+/// [`WitnessVM::I`] of an [`Instruction::HostCallStep`] reading a word of linear memory the host
+/// call read, see [`Caller::trace_memory_read`](crate::Caller::trace_memory_read)
+pub const HOST_CALL_READ: u64 = 4;
+
+/// Most values an [`Instruction::DropKeep`] step keeps. A drop keep is traced in steps of up to
+/// this many keep values, held in [`WitnessVM::X`], [`WitnessVM::Y`] & [`WitnessVM::Z`].
+pub const MAX_KEEP_PER_STEP: usize = 3;
+
 /// The VM state at each step of execution
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct WitnessVM {
     /// Stack pointer before execution
     pub pre_sp: usize,
@@ -1,7 +1,10 @@
 pub(crate) mod pre;
 pub mod step_info;
 
-use crate::mtable::{imtable::IMTable, memory_event_of_step, MTable, VarType};
+use crate::{
+    mtable::{imtable::IMTable, memory_event_of_step, MTable, VarType},
+    TracerError,
+};
 use serde::{Deserialize, Serialize};
 use step_info::StepInfo;
 use wasmi_core::UntypedValue;
@@ -50,15 +53,15 @@ impl ETable {
         self.entries_mut().push(etable_entry);
     }
 
-    pub fn mtable(&self, imtable: &IMTable) -> MTable {
+    pub fn mtable(&self, imtable: &IMTable) -> Result<MTable, TracerError> {
         let mentries = self
             .entries()
             .iter()
             .map(|eentry| memory_event_of_step(eentry, &mut 1))
-            .collect::<Vec<Vec<_>>>()
+            .collect::<Result<Vec<Vec<_>>, _>>()?
             .concat();
 
-        MTable::new_with_imtable(mentries, imtable)
+        Ok(MTable::new_with_imtable(mentries, imtable))
     }
     /// Builds execution trace from the entries in the table
     pub fn plain_execution_trace(&self) -> Vec<StepInfo> {
@@ -23,6 +23,7 @@ pub enum RunInstructionTracePre {
     },
     CallIndirect {
         idx: u32,
+        index: u32,
     },
     SetLocal {
         depth: usize,
@@ -53,6 +54,13 @@ pub enum RunInstructionTracePre {
         grow_size: u32,
         pages: u32,
     },
+    TableGet {
+        index: u32,
+    },
+    TableSet {
+        index: u32,
+        value: u64,
+    },
 
     I32BinOp {
         left: i32,
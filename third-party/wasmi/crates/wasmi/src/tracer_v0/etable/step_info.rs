@@ -44,7 +44,8 @@ pub enum StepInfo {
         post_heap_values: Vec<(u64, usize)>,
     },
     CallIndirect {
-        func_index: u32,
+        type_index: u32,
+        index: u32,
     },
     LocalGet {
         depth: usize,
@@ -94,6 +95,16 @@ pub enum StepInfo {
         result: i32,
         current_pages: u32,
     },
+    TableGet {
+        idx: u32,
+        index: u32,
+        value: u64,
+    },
+    TableSet {
+        idx: u32,
+        index: u32,
+        value: u64,
+    },
 
     I32Const {
         value: i32,
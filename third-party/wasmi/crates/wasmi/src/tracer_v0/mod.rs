@@ -3,6 +3,7 @@ pub mod etable;
 pub mod mtable;
 
 use crate::{AsContext, Global, Memory};
+use core::fmt;
 use mtable::imtable::IMTable;
 use wasmi_core::UntypedValue;
 
@@ -12,6 +13,24 @@ use self::{
     mtable::{memory_event_of_step, LocationType, MTable},
 };
 
+/// An error that may occur upon building the tables of a trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TracerError {
+    /// The trace has more memory ops than a `u32` event id can count.
+    EventIdOverflow,
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TracerError {}
+
+impl fmt::Display for TracerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::EventIdOverflow => write!(f, "event id overflows u32"),
+        }
+    }
+}
+
 /// Represents a slice range of the execution trace
 ///
 /// Also used to store the start and end indexes of the memory snapshot
@@ -120,16 +139,16 @@ impl TracerV0 {
     }
 
     /// Get memory trace from execution trace
-    pub fn mtable(&self) -> MTable {
+    pub fn mtable(&self) -> Result<MTable, TracerError> {
         let mentries = self
             .etable
             .entries()
             .iter()
             .map(|eentry| memory_event_of_step(eentry, &mut 1))
-            .collect::<Vec<Vec<_>>>()
+            .collect::<Result<Vec<Vec<_>>, _>>()?
             .concat();
 
-        MTable::new_with_imtable(mentries, &self.imtable)
+        Ok(MTable::new_with_imtable(mentries, &self.imtable))
     }
 
     /// Getter for shard start value
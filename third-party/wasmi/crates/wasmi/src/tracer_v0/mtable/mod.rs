@@ -6,7 +6,10 @@ use imtable::IMTable;
 use serde::{Deserialize, Serialize};
 use wasmi_core::ValueType;
 
-use crate::etable::{step_info::StepInfo, ETEntry};
+use crate::{
+    etable::{step_info::StepInfo, ETEntry},
+    TracerError,
+};
 
 #[derive(Default, Debug, Clone)]
 pub struct MTable(Vec<MemoryTableEntry>);
@@ -88,7 +91,7 @@ fn mem_op_from_stack_only_step(
     emid: &mut u32,
     pop_value: &[u64],
     push_value: &[u64],
-) -> Vec<MemoryTableEntry> {
+) -> Result<Vec<MemoryTableEntry>, TracerError> {
     let mut mem_op = vec![];
     let mut depth = 0;
 
@@ -102,7 +105,7 @@ fn mem_op_from_stack_only_step(
             is_mutable: true,
             value: pop_value[i],
         });
-        *emid = (*emid).checked_add(1).unwrap();
+        next_emid(emid)?;
         depth += 1;
     }
 
@@ -120,17 +123,27 @@ fn mem_op_from_stack_only_step(
             break;
         }
         depth -= 1;
-        *emid = (*emid).checked_add(1).unwrap();
+        next_emid(emid)?;
     }
 
-    mem_op
+    Ok(mem_op)
 }
 
-pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableEntry> {
+/// Move `emid` to the id of the next memory op of a step
+fn next_emid(emid: &mut u32) -> Result<(), TracerError> {
+    *emid = (*emid).checked_add(1).ok_or(TracerError::EventIdOverflow)?;
+    Ok(())
+}
+
+/// Get the memory ops of an execution step, with sub memory op ids counted from `emid`
+pub fn memory_event_of_step(
+    event: &ETEntry,
+    emid: &mut u32,
+) -> Result<Vec<MemoryTableEntry>, TracerError> {
     let eid = event.eid;
     let sp_before_execution = event.pre_sp;
 
-    match &event.step_info {
+    let ops = match &event.step_info {
         StepInfo::Br { .. } => vec![],
         StepInfo::BrIfEqz { .. } => vec![],
         StepInfo::BrIfNez { .. } => vec![],
@@ -147,7 +160,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     is_mutable: true,
                     value: keep_values[i],
                 });
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
             }
 
             for i in 0..keep_values.len() {
@@ -161,7 +174,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     is_mutable: true,
                     value: keep_values[i],
                 });
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
             }
 
             ops
@@ -186,7 +199,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 value: *cond,
             });
             depth += 1;
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             ops.push(MemoryTableEntry {
                 eid,
@@ -198,7 +211,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 value: *val2,
             });
             depth += 1;
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             ops.push(MemoryTableEntry {
                 eid,
@@ -210,7 +223,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 value: *val1,
             });
 
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             ops.push(MemoryTableEntry {
                 eid,
@@ -221,7 +234,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *result,
             });
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             ops
         }
@@ -237,7 +250,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     is_mutable: true,
                     value: args[i],
                 });
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
             }
             ops
         }
@@ -253,7 +266,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     is_mutable: true,
                     value: args[i],
                 });
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
             }
             ops
         }
@@ -267,7 +280,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *value,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let write = MemoryTableEntry {
                 eid,
@@ -278,7 +291,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *value,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
             vec![read, write]
         }
         StepInfo::SetLocal { depth, value } => {
@@ -291,7 +304,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *value,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let write = MemoryTableEntry {
                 eid,
@@ -302,7 +315,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *value,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             vec![read, write]
         }
@@ -317,7 +330,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 value: *value,
             };
 
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let write = MemoryTableEntry {
                 eid,
@@ -328,7 +341,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *value,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
             vec![read, write]
         }
 
@@ -342,7 +355,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *value,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let stack_write = MemoryTableEntry {
                 eid,
@@ -353,7 +366,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *value,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             vec![global_get, stack_write]
         }
@@ -367,7 +380,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *value,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let global_set = MemoryTableEntry {
                 eid,
@@ -378,7 +391,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *value,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             vec![stack_read, global_set]
         }
@@ -400,7 +413,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *raw_address as u64,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let load_value1 = MemoryTableEntry {
                 eid,
@@ -415,7 +428,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             };
 
             let load_value2 = if *effective_address % 8 + load_size.byte_size() > 8 {
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
                 Some(MemoryTableEntry {
                     eid,
                     emid: *emid,
@@ -430,7 +443,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 None
             };
 
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
             let push_value = MemoryTableEntry {
                 eid,
                 emid: *emid,
@@ -468,7 +481,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *value,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let load_address_from_stack = MemoryTableEntry {
                 eid,
@@ -479,7 +492,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 is_mutable: true,
                 value: *raw_address as u64,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let load_value1 = MemoryTableEntry {
                 eid,
@@ -491,7 +504,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 // The value will be used to lookup within imtable, hence block_value is given here
                 value: *pre_block_value1,
             };
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let write_value1 = MemoryTableEntry {
                 eid,
@@ -505,7 +518,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             };
 
             if *effective_address % 8 + store_size.byte_size() > 8 {
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
                 let load_value2 = MemoryTableEntry {
                     eid,
                     emid: *emid,
@@ -517,7 +530,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     value: *pre_block_value2,
                 };
 
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
                 let write_value2 = MemoryTableEntry {
                     eid,
                     emid: *emid,
@@ -551,7 +564,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[],
             &[event.allocated_memory_pages as u32 as u64],
-        ),
+        )?,
         StepInfo::MemoryGrow {
             grow_size,
             result,
@@ -563,7 +576,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 emid,
                 &[*grow_size as u32 as u64],
                 &[*result as u32 as u64],
-            );
+            )?;
 
             let start = *current_pages as usize * 8192;
 
@@ -578,22 +591,22 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     value: 0,
                 };
                 mem_vec.push(grow_mem);
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
             }
 
             mem_vec
         }
         StepInfo::Const32 { value } => {
-            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[], &[*value as u64])
+            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[], &[*value as u64])?
         }
         StepInfo::ConstRef { value } => {
-            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[], &[*value])
+            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[], &[*value])?
         }
         StepInfo::F32Const { value } => {
-            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[], &[*value as u64])
+            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[], &[*value as u64])?
         }
         StepInfo::F64Const { value } => {
-            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[], &[*value])
+            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[], &[*value])?
         }
         StepInfo::I32BinShiftOp {
             left, right, value, ..
@@ -606,7 +619,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*right as u32 as u64, *left as u32 as u64],
             &[*value as u32 as u64],
-        ),
+        )?,
         StepInfo::I32BinOp {
             left, right, value, ..
         } => mem_op_from_stack_only_step(
@@ -615,7 +628,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*right as u32 as u64, *left as u32 as u64],
             &[*value as u32 as u64],
-        ),
+        )?,
         StepInfo::I32Comp {
             left, right, value, ..
         } => mem_op_from_stack_only_step(
@@ -624,7 +637,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*right as u32 as u64, *left as u32 as u64],
             &[*value as u32 as u64],
-        ),
+        )?,
 
         StepInfo::I64BinOp {
             left, right, value, ..
@@ -640,10 +653,10 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*right as u64, *left as u64],
             &[*value as u64],
-        ),
+        )?,
 
         StepInfo::I64Const { value } => {
-            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[], &[*value as u64])
+            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[], &[*value as u64])?
         }
         StepInfo::I64Comp {
             left, right, value, ..
@@ -653,34 +666,34 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*right as u64, *left as u64],
             &[*value as u32 as u64],
-        ),
+        )?,
         StepInfo::UnaryOp {
             operand, result, ..
-        } => mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[*operand], &[*result]),
+        } => mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[*operand], &[*result])?,
         StepInfo::CompZ { value, result, .. } => mem_op_from_stack_only_step(
             sp_before_execution,
             eid,
             emid,
             &[*value],
             &[*result as u32 as u64],
-        ),
+        )?,
         StepInfo::I32WrapI64 { value, result } => mem_op_from_stack_only_step(
             sp_before_execution,
             eid,
             emid,
             &[*value as u64],
             &[*result as u32 as u64],
-        ),
+        )?,
         StepInfo::I64ExtendI32 { value, result, .. } => mem_op_from_stack_only_step(
             sp_before_execution,
             eid,
             emid,
             &[*value as u32 as u64],
             &[*result as u64],
-        ),
+        )?,
         StepInfo::I32SignExtendI8 { value, result }
         | StepInfo::I32SignExtendI16 { value, result } => {
-            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[*value], &[*result])
+            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[*value], &[*result])?
         }
         StepInfo::I64SignExtendI8 { value, result }
         | StepInfo::I64SignExtendI16 { value, result }
@@ -690,7 +703,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*value as u64],
             &[*result as u64],
-        ),
+        )?,
         StepInfo::MemoryFill {
             offset,
             value,
@@ -709,7 +722,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 value: *size,
             };
             mem_vec.push(fill_size_from_stack);
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let fill_value_from_stack = MemoryTableEntry {
                 eid,
@@ -721,7 +734,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 value: *value,
             };
             mem_vec.push(fill_value_from_stack);
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let fill_offset_from_stack = MemoryTableEntry {
                 eid,
@@ -734,7 +747,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             };
 
             mem_vec.push(fill_offset_from_stack);
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             for (i, prev_val) in prev_val_vec.iter().enumerate() {
                 let fill_read = MemoryTableEntry {
@@ -747,7 +760,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     value: *prev_val,
                 };
                 mem_vec.push(fill_read);
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
             }
 
             for (i, new_val) in new_val_vec.iter().enumerate() {
@@ -761,7 +774,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     value: *new_val,
                 };
                 mem_vec.push(fill_write);
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
             }
             mem_vec
         }
@@ -784,7 +797,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 value: *num_bytes_to_copy,
             };
             mem_vec.push(num_bytes_to_copy_from_stack);
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let copy_src_from_stack = MemoryTableEntry {
                 eid,
@@ -796,7 +809,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                 value: *src,
             };
             mem_vec.push(copy_src_from_stack);
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             let copy_dst_from_stack = MemoryTableEntry {
                 eid,
@@ -809,7 +822,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             };
 
             mem_vec.push(copy_dst_from_stack);
-            *emid = (*emid).checked_add(1).unwrap();
+            next_emid(emid)?;
 
             for (i, prev_val) in prev_val_vec.iter().enumerate() {
                 let copy_read = MemoryTableEntry {
@@ -821,7 +834,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     is_mutable: true,
                     value: *prev_val,
                 };
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
                 mem_vec.push(copy_read);
             }
 
@@ -835,12 +848,76 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     is_mutable: true,
                     value: *new_val,
                 };
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
                 mem_vec.push(copy_write);
             }
             mem_vec
         }
-        StepInfo::CallIndirect { .. } => vec![],
+        StepInfo::TableGet { index, value, .. } => {
+            let index_read = MemoryTableEntry {
+                eid,
+                emid: *emid,
+                addr: sp_before_execution - 1,
+                ltype: LocationType::Stack,
+                atype: AccessType::Read,
+                is_mutable: true,
+                value: *index as u64,
+            };
+            next_emid(emid)?;
+
+            let table_get = MemoryTableEntry {
+                eid,
+                emid: *emid,
+                addr: *index as usize,
+                ltype: LocationType::Table,
+                atype: AccessType::Read,
+                is_mutable: true,
+                value: *value,
+            };
+            next_emid(emid)?;
+
+            let stack_write = MemoryTableEntry {
+                eid,
+                emid: *emid,
+                addr: sp_before_execution - 1,
+                ltype: LocationType::Stack,
+                atype: AccessType::Write,
+                is_mutable: true,
+                value: *value,
+            };
+            next_emid(emid)?;
+
+            vec![index_read, table_get, stack_write]
+        }
+        StepInfo::TableSet { index, value, .. } => {
+            let mut ops = mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                &[*value, *index as u64],
+                &[],
+            )?;
+
+            ops.push(MemoryTableEntry {
+                eid,
+                emid: *emid,
+                addr: *index as usize,
+                ltype: LocationType::Table,
+                atype: AccessType::Write,
+                is_mutable: true,
+                value: *value,
+            });
+            next_emid(emid)?;
+
+            ops
+        }
+        StepInfo::CallIndirect { index, .. } => mem_op_from_stack_only_step(
+            sp_before_execution,
+            eid,
+            emid,
+            &[*index as u64],
+            &[],
+        )?,
         StepInfo::CallHost {
             zero_writes,
             post_values,
@@ -856,7 +933,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     is_mutable: true,
                     value: 0,
                 });
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
             }
 
             for (post_value, addr) in post_values.iter() {
@@ -870,7 +947,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     value: *post_value,
                 });
 
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
             }
             ops
         }
@@ -887,12 +964,12 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
                     value: *post_value,
                 });
 
-                *emid = (*emid).checked_add(1).unwrap();
+                next_emid(emid)?;
             }
             ops
         }
         StepInfo::F64ConvertI64 { value, result, .. } => {
-            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[*value], &[*result])
+            mem_op_from_stack_only_step(sp_before_execution, eid, emid, &[*value], &[*result])?
         }
         StepInfo::F64ConvertI32 { value, result, .. } => mem_op_from_stack_only_step(
             sp_before_execution,
@@ -900,7 +977,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*value as u64],
             &[*result as u64],
-        ),
+        )?,
         StepInfo::F64BinOp {
             left, right, value, ..
         } => mem_op_from_stack_only_step(
@@ -909,7 +986,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*right as u64, *left as u64],
             &[*value as u64],
-        ),
+        )?,
         StepInfo::F32BinOp {
             left, right, value, ..
         } => mem_op_from_stack_only_step(
@@ -918,7 +995,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*right as u64, *left as u64],
             &[*value as u64],
-        ),
+        )?,
         StepInfo::F32Comp {
             left, right, value, ..
         } => mem_op_from_stack_only_step(
@@ -927,7 +1004,7 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*right as u32 as u64, *left as u32 as u64],
             &[*value as u32 as u64],
-        ),
+        )?,
         StepInfo::F64Comp {
             left, right, value, ..
         } => mem_op_from_stack_only_step(
@@ -936,23 +1013,24 @@ pub fn memory_event_of_step(event: &ETEntry, emid: &mut u32) -> Vec<MemoryTableE
             emid,
             &[*right as u64, *left as u64],
             &[*value as u32 as u64],
-        ),
+        )?,
         StepInfo::F32DemoteF64 { value, result } => mem_op_from_stack_only_step(
             sp_before_execution,
             eid,
             emid,
             &[*value as u64],
             &[*result as u64],
-        ),
+        )?,
         StepInfo::F64PromoteF32 { value, result } => mem_op_from_stack_only_step(
             sp_before_execution,
             eid,
             emid,
             &[*value as u64],
             &[*result as u64],
-        ),
+        )?,
         _ => unimplemented!("eid: {}, {:?}", event.eid, event.step_info),
-    }
+    };
+    Ok(ops)
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -960,6 +1038,7 @@ pub enum LocationType {
     Stack = 1,
     Heap = 2,
     Global = 3,
+    Table = 4,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Hash, Eq)]
@@ -0,0 +1,13 @@
+(module
+  (memory 1)
+  ;; sum the four little-endian i64 words of the 32-byte blob at $ptr
+  (func (export "main") (param $ptr i32) (result i64)
+    (i64.add
+      (i64.add
+        (i64.load (local.get $ptr))
+        (i64.load offset=8 (local.get $ptr)))
+      (i64.add
+        (i64.load offset=16 (local.get $ptr))
+        (i64.load offset=24 (local.get $ptr))))
+  )
+)
@@ -0,0 +1,12 @@
+(module
+  (memory 1 3) ;; start with one memory page, and max of 3 pages
+  (func (export "main") (result i32)
+    ;; grow by 1 page, returning the previous page count 1
+    (drop (memory.grow (i32.const 1)))
+
+    ;; growing past the max fails, returning -1 and leaving the page count as is
+    (drop (memory.grow (i32.const 5)))
+
+    memory.size
+  )
+)
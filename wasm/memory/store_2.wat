@@ -0,0 +1,7 @@
+(module
+    (memory $0 1)
+    (func (export "call")
+        (i64.store (i32.const 0) (i64.const 1))
+        (i64.store (i32.const 64) (i64.const 2))
+    )
+)
@@ -0,0 +1,10 @@
+(module
+    (memory $0 1)
+    (data (i32.const 0) "\ff\00\00\80")
+    (func (export "load8_s") (result i32)
+        (i32.load8_s (i32.const 0))
+    )
+    (func (export "load16_u") (result i64)
+        (i64.load16_u (i32.const 2))
+    )
+)
@@ -0,0 +1,15 @@
+(module
+    (memory $0 1)
+    (global $g (mut i64) (i64.const 42))
+    (data (i32.const 0) "\00\01\02\03\04\05\06\07")
+    ;; stores a byte into a word with existing data
+    (func (export "store8") (result i64)
+        (i32.store8 (i32.const 3) (i32.const 0x123456ab))
+        (i64.load (i32.const 0))
+    )
+    ;; stores a byte into the last word of memory, which must leave the global after it untouched
+    (func (export "store_last") (result i64)
+        (i32.store8 (i32.const 65535) (i32.const 1))
+        (global.get $g)
+    )
+)
@@ -0,0 +1,11 @@
+(module
+    (memory $0 1)
+    ;; byte i is i + 1
+    (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10")
+    ;; an i64 load at 5 straddles the words 0 & 1, and an i32 load at 6 too
+    (func (export "main") (result i64)
+        (i64.add
+            (i64.load offset=2 (i32.const 3))
+            (i64.extend_i32_u (i32.load (i32.const 6))))
+    )
+)
@@ -0,0 +1,6 @@
+;; Exports a function `main` returning 42
+(module
+    (func (export "main") (result i64)
+        (i64.mul (i64.const 6) (i64.const 7))
+    )
+)
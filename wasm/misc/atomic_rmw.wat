@@ -0,0 +1,7 @@
+(module
+  (memory 1)
+  ;; read-modify-write atomics have no single non-atomic equivalent
+  (func (export "main") (param i32) (result i32)
+    (i32.atomic.rmw.add (i32.const 0) (local.get 0))
+  )
+)
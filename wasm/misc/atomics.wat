@@ -0,0 +1,13 @@
+(module
+  (memory 1)
+  ;; atomic accesses as emitted for single threaded code, which are lowered to plain ones
+  (func (export "main") (param i32 i64) (result i64)
+    (i32.atomic.store (i32.const 0) (local.get 0))
+    (i64.atomic.store32 (i32.const 8) (local.get 1))
+    atomic.fence
+    (i64.atomic.store8 (i32.const 16) (i64.atomic.load (i32.const 8)))
+    (i64.add
+      (i64.extend_i32_u (i32.atomic.load (i32.const 0)))
+      (i64.atomic.load8_u (i32.const 16)))
+  )
+)
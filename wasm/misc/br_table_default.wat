@@ -0,0 +1,19 @@
+;; Exports a function `main` that branches on `$i` with a br_table of three targets and a default.
+;; It returns 10, 20 & 30 for indices 0, 1 & 2 and 40 for any other index.
+(module
+    (func (export "main") (param $i i32) (result i32)
+        (block $default
+            (block $case2
+                (block $case1
+                    (block $case0
+                        (br_table $case0 $case1 $case2 $default (local.get $i))
+                    )
+                    (return (i32.const 10))
+                )
+                (return (i32.const 20))
+            )
+            (return (i32.const 30))
+        )
+        (i32.const 40)
+    )
+)
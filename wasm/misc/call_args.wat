@@ -0,0 +1,16 @@
+(module
+  ;; $f gets its arguments computed by the caller & a zero-initialized local
+  (func $f (param i64 i64) (result i64) (local i64)
+    local.get 0
+    local.get 1
+    i64.mul
+    local.get 2
+    i64.add)
+  (func (export "main") (param i64) (result i64)
+    local.get 0
+    i64.const 3
+    i64.add
+    local.get 0
+    i64.const 2
+    i64.mul
+    call $f))
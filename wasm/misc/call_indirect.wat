@@ -0,0 +1,12 @@
+(module
+    (type $unop (func (param i32) (result i32)))
+    (table 3 funcref)
+    (elem (i32.const 0) $double $square $negate)
+    (func $double (type $unop) (i32.add (local.get 0) (local.get 0)))
+    (func $square (type $unop) (i32.mul (local.get 0) (local.get 0)))
+    (func $negate (type $unop) (i32.sub (i32.const 0) (local.get 0)))
+    ;; The callee is selected through the table by $idx
+    (func (export "main") (param $idx i32) (result i32)
+        (call_indirect (type $unop) (i32.const 7) (local.get $idx))
+    )
+)
@@ -0,0 +1,16 @@
+(module
+    (global $g0 (mut i64) (i64.const 1))
+    ;; constants that do not fit in 32 bits are stored in the const pool
+    (func (export "main") (result i64)
+        (global.set $g0
+            (i64.add
+                (global.get $g0)
+                (i64.const 0x0123456789abcdef)
+            )
+        )
+        (i64.add
+            (global.get $g0)
+            (i64.const 0x1000000000000000)
+        )
+    )
+)
@@ -0,0 +1,13 @@
+(module
+    (func $depth (export "depth") (param $n i64) (result i64)
+        (if (result i64) (i64.eqz (local.get $n))
+            (then (i64.const 0))
+            (else
+                (i64.add
+                    (call $depth (i64.sub (local.get $n) (i64.const 1)))
+                    (i64.const 1)
+                )
+            )
+        )
+    )
+)
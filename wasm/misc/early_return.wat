@@ -0,0 +1,10 @@
+(module
+    ;; returns early, with a `return_if_nez`, when $x is above 100
+    (func $clamp (param $x i64) (result i64)
+        (drop (br_if 0 (i64.const 100) (i64.gt_u (local.get $x) (i64.const 100))))
+        (local.get $x)
+    )
+    (func (export "main") (param $x i64) (result i64)
+        (call $clamp (local.get $x))
+    )
+)
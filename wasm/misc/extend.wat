@@ -0,0 +1,13 @@
+(module
+    ;; Sign extensions must fill the high bits with the sign bit of the operand
+    (func (export "extend_i32_s") (param $x i32) (result i64)
+        (i64.extend_i32_s (local.get $x))
+    )
+    (func (export "extend8_s") (param $x i64) (result i64)
+        (i64.extend8_s (local.get $x))
+    )
+    ;; wrap must drop the high half before the sign extension of the low 16 bits
+    (func (export "wrap_extend16_s") (param $x i64) (result i32)
+        (i32.extend16_s (i32.wrap_i64 (local.get $x)))
+    )
+)
@@ -0,0 +1,19 @@
+;; Stores a funcref to `$double` in a global, reads it back through a funcref local & calls it
+;; through the function table. `main` traps if the global is not null before it is set, or is after.
+(module
+    (type $unop (func (param i32) (result i32)))
+    (table 1 funcref)
+    (global $f (mut funcref) (ref.null func))
+    (elem declare func $double)
+    (func $double (type $unop) (i32.mul (local.get 0) (i32.const 2)))
+
+    (func (export "main") (param $x i32) (result i32)
+        (local $r funcref)
+        (if (i32.eqz (ref.is_null (global.get $f))) (then (unreachable)))
+        (global.set $f (ref.func $double))
+        (local.set $r (global.get $f))
+        (if (ref.is_null (local.get $r)) (then (unreachable)))
+        (table.set (i32.const 0) (local.get $r))
+        (call_indirect (type $unop) (local.get $x) (i32.const 0))
+    )
+)
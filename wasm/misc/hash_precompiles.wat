@@ -0,0 +1,14 @@
+;; Hashes "abc" with the `zk.keccak256` & `zk.sha256` precompiles, writing the digests at 64 & 96.
+;; `main` returns the xor of the first words of the digests.
+(module
+    (import "zk" "keccak256" (func $keccak256 (param i32 i32 i32)))
+    (import "zk" "sha256" (func $sha256 (param i32 i32 i32)))
+    (memory (export "memory") 1)
+    (data (i32.const 0) "abc")
+
+    (func (export "main") (result i64)
+        (call $keccak256 (i32.const 0) (i32.const 3) (i32.const 64))
+        (call $sha256 (i32.const 0) (i32.const 3) (i32.const 96))
+        (i64.xor (i64.load (i32.const 64)) (i64.load (i32.const 96)))
+    )
+)
@@ -0,0 +1,8 @@
+;; Exports a function `main` that adds its arguments through the `host.add` host import, then
+;; doubles the sum.
+(module
+    (import "host" "add" (func $add (param i64 i64) (result i64)))
+    (func (export "main") (param $x i64) (param $y i64) (result i64)
+        (i64.mul (call $add (local.get $x) (local.get $y)) (i64.const 2))
+    )
+)
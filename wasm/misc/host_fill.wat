@@ -0,0 +1,14 @@
+;; Calls the imported `env/fill` host function, which writes a 32-byte buffer at the given pointer.
+;; `main` returns the sum of the 4 i64 words of the buffer.
+(module
+    (import "env" "fill" (func $fill (param i32)))
+    (memory (export "memory") 1)
+
+    (func (export "main") (result i64)
+        (call $fill (i32.const 16))
+        (i64.add
+            (i64.add (i64.load (i32.const 16)) (i64.load (i32.const 24)))
+            (i64.add (i64.load (i32.const 32)) (i64.load (i32.const 40)))
+        )
+    )
+)
@@ -0,0 +1,12 @@
+;; Grows the linear memory, then calls the imported `env/nop` host function, which takes & returns
+;; nothing, on an empty value stack. `main` returns the page count after the host call.
+(module
+    (import "env" "nop" (func $nop))
+    (memory 1)
+
+    (func (export "main") (result i32)
+        (drop (memory.grow (i32.const 1)))
+        (call $nop)
+        (memory.size)
+    )
+)
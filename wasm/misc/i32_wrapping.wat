@@ -0,0 +1,28 @@
+(module
+    ;; i32 arithmetic that overflows 32 bits must wrap
+    (func (export "main") (result i32)
+        (i32.sub
+            (i32.add
+                (i32.mul (i32.const 65536) (i32.const 65537))
+                (i32.const 0x7fffffff)
+            )
+            (i32.const -2)
+        )
+    )
+    ;; i32 division & remainder read their operands as 32 bits, signed or not
+    (func (export "div_rem") (result i32)
+        (i32.add
+            (i32.add
+                (i32.rem_s (i32.const 0x80000000) (i32.const -1))
+                (i32.div_u (i32.const -1) (i32.const 3))
+            )
+            (i32.add
+                (i32.mul
+                    (i32.div_s (i32.const -7) (i32.const 2))
+                    (i32.rem_s (i32.const -7) (i32.const 2))
+                )
+                (i32.rem_u (i32.const -1) (i32.const 10))
+            )
+        )
+    )
+)
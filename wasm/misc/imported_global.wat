@@ -0,0 +1,8 @@
+(module
+    (global $counter (import "env" "counter") (mut i64))
+    (global $step i64 (i64.const 1))
+    (func (export "main") (result i64)
+        (global.set $counter (i64.add (global.get $counter) (global.get $step)))
+        (global.get $counter)
+    )
+)
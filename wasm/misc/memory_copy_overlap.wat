@@ -0,0 +1,13 @@
+;; Exports a function `main` that copies overlapping ranges of linear memory
+;; forward then backward, and returns the word at byte 8.
+(module
+    (memory 1)
+    (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10\11\12\13\14\15\16\17\18\19\1a\1b\1c\1d\1e\1f\20")
+    (func (export "main") (result i64)
+        ;; copy 13 bytes from 2 to 7
+        (memory.copy (i32.const 7) (i32.const 2) (i32.const 13))
+        ;; copy 13 bytes from 9 to 3
+        (memory.copy (i32.const 3) (i32.const 9) (i32.const 13))
+        (i64.load (i32.const 8))
+    )
+)
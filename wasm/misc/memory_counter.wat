@@ -0,0 +1,21 @@
+;; Exports a function `count` that increments a counter kept in linear memory
+;; `n` times and returns it, so each iteration reads the state the previous
+;; one wrote.
+(module
+    (memory (export "mem") 1)
+    (func (export "count") (param $n i32) (result i32)
+        (local $i i32)
+        (block $exit
+            (loop $loop
+                (br_if $exit (i32.eq (local.get $i) (local.get $n)))
+                (i32.store
+                    (i32.const 0)
+                    (i32.add (i32.load (i32.const 0)) (i32.const 1))
+                )
+                (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                (br $loop)
+            )
+        )
+        (i32.load (i32.const 0))
+    )
+)
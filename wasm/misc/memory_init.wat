@@ -0,0 +1,20 @@
+;; Exports a function `main` that initializes linear memory from a passive data
+;; segment at unaligned offsets, drops the segment and returns the word at byte 8,
+;; and a function `init_dropped` that initializes memory from the dropped segment.
+(module
+    (memory 1)
+    (data (i32.const 0) "\01\02\03\04\05\06\07\08\09\0a\0b\0c\0d\0e\0f\10")
+    (data $segment "\a1\a2\a3\a4\a5\a6\a7\a8\a9\aa\ab\ac\ad\ae\af\b0\b1\b2\b3")
+    (func (export "main") (result i64)
+        ;; copy 11 bytes from 3 to 5
+        (memory.init $segment (i32.const 5) (i32.const 3) (i32.const 11))
+        ;; copy the last 2 bytes to 14
+        (memory.init $segment (i32.const 14) (i32.const 17) (i32.const 2))
+        (data.drop $segment)
+        (i64.load (i32.const 8))
+    )
+    (func (export "init_dropped")
+        (data.drop $segment)
+        (memory.init $segment (i32.const 0) (i32.const 0) (i32.const 1))
+    )
+)
@@ -0,0 +1,13 @@
+(module
+    ;; Divide $a by $b, returning the quotient & the remainder
+    (func $divmod (param $a i64) (param $b i64) (result i64 i64)
+        (i64.div_u (local.get $a) (local.get $b))
+        (i64.rem_u (local.get $a) (local.get $b))
+    )
+
+    ;; Return the quotient & the remainder of $n by 7, then $n
+    (func (export "split") (param $n i64) (result i64 i64 i64)
+        (call $divmod (local.get $n) (i64.const 7))
+        (local.get $n)
+    )
+)
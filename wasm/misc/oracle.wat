@@ -0,0 +1,12 @@
+;; Calls the imported `env/oracle` host function with `x` and returns its response plus one.
+(module
+    (import "env" "oracle" (func $oracle (param i64) (result i64)))
+    (memory 1)
+
+    (func (export "main") (param $x i64) (result i64)
+        (i64.add
+            (call $oracle (local.get $x))
+            (i64.const 1)
+        )
+    )
+)
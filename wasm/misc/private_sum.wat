@@ -0,0 +1,22 @@
+;; Exports a function `main` that reads its private input, an array of i64s, through the
+;; `zk.read_private` host import and returns their sum.
+(module
+    (import "zk" "read_private" (func $read_private (param i32 i32) (result i32)))
+    (memory (export "memory") 1)
+    (func (export "main") (result i64)
+        (local $end i32)
+        (local $ptr i32)
+        (local $sum i64)
+        ;; read the whole input, at most a page
+        (local.set $end (call $read_private (i32.const 0) (i32.const 65536)))
+        (block $done
+            (loop $sum_words
+                (br_if $done (i32.ge_u (local.get $ptr) (local.get $end)))
+                (local.set $sum (i64.add (local.get $sum) (i64.load (local.get $ptr))))
+                (local.set $ptr (i32.add (local.get $ptr) (i32.const 8)))
+                (br $sum_words)
+            )
+        )
+        (local.get $sum)
+    )
+)
@@ -0,0 +1,20 @@
+;; Calls the imported `env/reenter` host function, which calls back the exported `add`. The
+;; re-entrant `add` writes the linear memory & a global, which `main` reads after the host call.
+(module
+    (import "env" "reenter" (func $reenter (param i64) (result i64)))
+    (memory (export "memory") 1)
+    (global $calls (mut i64) (i64.const 0))
+
+    (func $add (export "add") (param $a i64) (param $b i64) (result i64)
+        (global.set $calls (i64.add (global.get $calls) (i64.const 1)))
+        (i64.store (i32.const 0) (i64.add (local.get $a) (local.get $b)))
+        (i64.add (local.get $a) (local.get $b))
+    )
+
+    (func (export "main") (param $x i64) (result i64)
+        (i64.add
+            (call $reenter (local.get $x))
+            (i64.add (i64.load (i32.const 0)) (global.get $calls))
+        )
+    )
+)
@@ -0,0 +1,8 @@
+(module
+  (func (export "main") (param i64 i64) (result i64)
+    local.get 0
+    i64x2.splat
+    local.get 1
+    i64x2.splat
+    i64x2.add
+    i64x2.extract_lane 0))
@@ -0,0 +1,6 @@
+(module
+  ;; v128 in a signature only, without any SIMD opcode
+  (func $id (param v128) (result v128)
+    local.get 0)
+  (func (export "main") (result i64)
+    i64.const 1))
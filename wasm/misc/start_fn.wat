@@ -0,0 +1,11 @@
+(module
+    (memory 1)
+    ;; initializes the memory the exported function reads
+    (func $init
+        (i64.store (i32.const 8) (i64.const 42))
+    )
+    (start $init)
+    (func (export "main") (param $x i64) (result i64)
+        (i64.add (i64.load (i32.const 8)) (local.get $x))
+    )
+)
@@ -0,0 +1,13 @@
+;; Exports a function `main` that copies entries of a table with `table.copy` & drops an element
+;; segment with `elem.drop`, which the zkVM has no circuit for.
+(module
+    (table 2 funcref)
+    (elem (i32.const 0) $f)
+    (elem $passive func $f)
+    (func $f)
+    (func (export "main")
+        (table.copy (i32.const 1) (i32.const 0) (i32.const 1))
+        (table.copy (i32.const 0) (i32.const 1) (i32.const 1))
+        (elem.drop $passive)
+    )
+)
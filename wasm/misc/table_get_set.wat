@@ -0,0 +1,13 @@
+;; Exports a function `main` that copies an entry of the default table into a second table with
+;; `table.set` & reads it back with `table.get`. Only the default table is laid out in the zkVM
+;; memory, so the accesses to the second table are unsupported.
+(module
+    (table $default 1 funcref)
+    (table $other 1 funcref)
+    (elem (table $default) (i32.const 0) func $f)
+    (func $f)
+    (func (export "main")
+        (table.set $other (i32.const 0) (table.get $default (i32.const 0)))
+        (drop (table.get $other (i32.const 0)))
+    )
+)
@@ -0,0 +1,12 @@
+(module
+    (type $unop (func (param i32) (result i32)))
+    (table 2 funcref)
+    (elem (i32.const 0) $inc $dec)
+    (func $inc (type $unop) (i32.add (local.get 0) (i32.const 1)))
+    (func $dec (type $unop) (i32.sub (local.get 0) (i32.const 1)))
+    ;; Copy the entry at $src over the entry at 0, then call through the entry at 0
+    (func (export "main") (param $src i32) (result i32)
+        (table.set (i32.const 0) (table.get (local.get $src)))
+        (call_indirect (type $unop) (i32.const 10) (i32.const 0))
+    )
+)
@@ -0,0 +1,29 @@
+(module
+    ;; Shift the window ($a, $b, $c) of the tribonacci sequence by one
+    (func $step (param $a i64) (param $b i64) (param $c i64) (result i64 i64 i64)
+        (local.get $b)
+        (local.get $c)
+        (i64.add (i64.add (local.get $a) (local.get $b)) (local.get $c))
+    )
+
+    ;; Return the terms $n, $n + 1 & $n + 2 of the tribonacci sequence, shifting the window of the
+    ;; first three terms $n times
+    (func (export "tribonacci") (param $n i64) (result i64 i64 i64)
+        (local $a i64) (local $b i64) (local $c i64)
+        (local.set $c (i64.const 1))
+        (block $done
+            (loop $continue
+                (br_if $done (i64.eqz (local.get $n)))
+                (call $step (local.get $a) (local.get $b) (local.get $c))
+                (local.set $c)
+                (local.set $b)
+                (local.set $a)
+                (local.set $n (i64.sub (local.get $n) (i64.const 1)))
+                (br $continue)
+            )
+        )
+        (local.get $a)
+        (local.get $b)
+        (local.get $c)
+    )
+)